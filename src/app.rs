@@ -1,4 +1,6 @@
-use crate::ui::{FileList, MainArea, TopPanel};
+use crate::ui::main_area::ShortcutAction;
+use crate::ui::{main_area, FileList, MainArea, TopPanel};
+use crate::session::{SavedState, SessionGuard};
 use crate::version_check;
 
 /// We derive Deserialize/Serialize so we can persist app state on shutdown.
@@ -9,6 +11,19 @@ pub struct TemplateApp {
     file_list: FileList,
     // Remove skip attribute to persist main area settings (like output path) between sessions
     main_area: MainArea,
+    // Removes the crash marker when the app closes normally
+    #[serde(skip)]
+    session: Option<SessionGuard>,
+    // Shown while running on defaults after `--safe-mode` or a crash
+    #[serde(skip)]
+    safe_mode: Option<SafeModeNotice>,
+}
+
+/// Why the editor started on defaults, and the state it set aside
+struct SafeModeNotice {
+    after_crash: bool,
+    saved_state: Option<String>,
+    message: Option<String>,
 }
 
 impl Default for TemplateApp {
@@ -16,6 +31,8 @@ impl Default for TemplateApp {
         Self {
             file_list: FileList::new(),
             main_area: MainArea::new(),
+            session: None,
+            safe_mode: None,
         }
     }
 }
@@ -31,8 +48,54 @@ impl TemplateApp {
         &mut self.main_area
     }
     
+    /// Get a mutable reference to the file list
+    pub fn file_list_mut(&mut self) -> &mut FileList {
+        &mut self.file_list
+    }
+
+    /// Run the actions of the shortcuts pressed this frame. Called after the panels are
+    /// drawn so focused text fields and open dialogs have had their say.
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if self.main_area.shortcuts_modal.open {
+            return;
+        }
+        let plain_keys = self.main_area.plain_shortcuts_allowed(ctx);
+        for action in self.main_area.shortcuts.pressed(ctx, plain_keys) {
+            match action {
+                ShortcutAction::Open => {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .set_title("Open Audio File")
+                        .add_filter("Audio Files", &["nus3audio", "nus3bank"])
+                        .pick_file()
+                    {
+                        let path = path.to_string_lossy().to_string();
+                        self.file_list.add_file(path.clone());
+                        self.file_list.select_file(path.clone());
+                        self.main_area.open_file(path);
+                    }
+                }
+                ShortcutAction::Save => TopPanel::save_changes(self),
+                ShortcutAction::PlayPause => self.main_area.toggle_playback(),
+                ShortcutAction::RemoveSelected => {
+                    if self.main_area.selected_file.is_some() {
+                        self.main_area.request_remove_selected();
+                    }
+                }
+                ShortcutAction::FocusSearch => self.main_area.focus_search(ctx),
+                ShortcutAction::SelectPrevious => self.main_area.pending_row_step = Some(-1),
+                ShortcutAction::SelectNext => self.main_area.pending_row_step = Some(1),
+            }
+        }
+    }
+
     /// Called once before the first frame.
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::new_with_safe_mode(cc, false)
+    }
+
+    /// Like `new`, but with `safe_mode` (or after a crash) the saved state is set aside and
+    /// the editor starts on defaults, so corrupted settings cannot keep it from starting
+    pub fn new_with_safe_mode(cc: &eframe::CreationContext<'_>, safe_mode: bool) -> Self {
         let mut fonts = egui::FontDefinitions::default();
         egui_phosphor::add_to_fonts(&mut fonts, egui_phosphor::Variant::Regular);
         cc.egui_ctx.set_fonts(fonts);
@@ -43,23 +106,107 @@ impl TemplateApp {
 
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        if let Some(storage) = cc.storage {
-            let mut app: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-            // Make sure audio player is initialized after deserialization
-            app.main_area.ensure_audio_player_initialized();
-            return app;
-        }
+        let (session, after_crash) = SessionGuard::start();
+        let mut app: Self = match cc.storage {
+            Some(storage) if safe_mode || after_crash => {
+                let saved_state = storage.get_string(eframe::APP_KEY);
+                println!("Starting in safe mode (after crash: {})", after_crash);
+                Self {
+                    safe_mode: Some(SafeModeNotice { after_crash, saved_state, message: None }),
+                    ..Default::default()
+                }
+            }
+            Some(storage) => eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default(),
+            None => Default::default(),
+        };
+        app.session = session;
+        app.apply_loaded_settings();
+        app
+    }
+
+    /// Push the deserialized settings into the parts that keep their own copy
+    fn apply_loaded_settings(&mut self) {
+        // Make sure audio player is initialized after deserialization
+        self.main_area.ensure_audio_player_initialized();
+        let vgmstream_path = self.main_area.vgmstream_path.clone();
+        self.main_area.set_vgmstream_path(vgmstream_path);
+        self.main_area.sync_bank_arg_overrides();
+        let decode_threads = self.main_area.decode_threads;
+        self.main_area.set_decode_threads(decode_threads);
+    }
 
-        Default::default()
+    /// Explain safe mode and offer the saved settings back
+    fn show_safe_mode_notice(&mut self, ctx: &egui::Context) {
+        let Some(notice) = &mut self.safe_mode else {
+            return;
+        };
+        let mut restore = false;
+        let mut dismiss = false;
+        egui::Window::new("Safe Mode")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                if notice.after_crash {
+                    ui.label("The editor did not close normally last time, so it started with default settings.");
+                } else {
+                    ui.label("Started with --safe-mode: default settings, saved settings not loaded.");
+                }
+                ui.label("If the saved settings caused the problem, keep the defaults; they replace the saved settings when the editor closes.");
+                if let Some(message) = &notice.message {
+                    ui.colored_label(egui::Color32::from_rgb(255, 180, 60), message);
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Keep Defaults").clicked() {
+                        dismiss = true;
+                    }
+                    if ui
+                        .add_enabled(notice.saved_state.is_some(), egui::Button::new("Restore Saved Settings"))
+                        .on_disabled_hover_text("No saved settings were found")
+                        .clicked()
+                    {
+                        restore = true;
+                    }
+                });
+            });
+
+        if restore {
+            let saved_state = notice.saved_state.take().unwrap_or_default();
+            match eframe::get_value::<Self>(&SavedState(saved_state), eframe::APP_KEY) {
+                Some(restored) => {
+                    self.file_list = restored.file_list;
+                    self.main_area = restored.main_area;
+                    self.apply_loaded_settings();
+                    dismiss = true;
+                }
+                None => {
+                    notice.message = Some("The saved settings could not be read; continuing with defaults.".to_string());
+                }
+            }
+        }
+        if dismiss {
+            self.safe_mode = None;
+        }
     }
 }
 
 impl eframe::App for TemplateApp {
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        // Until the user decides, leave the settings safe mode set aside untouched
+        if self.safe_mode.is_some() {
+            return;
+        }
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 
+    /// Save (including the window geometry) more often than the 30 s default, so the
+    /// window still reopens where it was if the editor is killed instead of closed
+    fn auto_save_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(10)
+    }
+
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Custom dark theme with consistent black background
@@ -93,13 +240,35 @@ impl eframe::App for TemplateApp {
                     // If a file is selected, handle it here
                     if let Some(selected) = &self.file_list.selected_file {
                         println!("Processing file: {}", selected);
-                        // Update main area with selected file
-                        self.main_area.update_selected_file(Some(selected.clone()));
+                        // Open the selected file in a tab, keeping the edits of the others
+                        self.main_area.open_file(selected.clone());
                     }
                 }
             });
 
         // Display the main editing area
         self.main_area.show(ctx);
+
+        // Bank files dropped anywhere on the window are listed and the last one is opened
+        main_area::paint_bank_drop_hint(ctx);
+        let dropped_banks = main_area::dropped_bank_files(ctx);
+        if let Some(last) = dropped_banks.last().cloned() {
+            for path in dropped_banks {
+                self.file_list.add_file(path);
+            }
+            self.file_list.select_file(last.clone());
+            println!("Opening dropped file: {}", last);
+            self.main_area.open_file(last);
+        }
+
+        self.handle_shortcuts(ctx);
+        self.show_safe_mode_notice(ctx);
+
+        // Keep the file list in step with the tab bar
+        if let Some(active) = &self.main_area.selected_file {
+            if self.file_list.selected_file.as_ref() != Some(active) {
+                self.file_list.select_file(active.clone());
+            }
+        }
     }
 }
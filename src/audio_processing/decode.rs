@@ -0,0 +1,140 @@
+//! Decoding of common audio formats to PCM16 WAV in memory.
+//!
+//! Covers what symphonia reads here (MP3, FLAC, Ogg Vorbis and plain WAV), so replacements
+//! in those formats need neither temp files nor vgmstream-cli. Game formats still go
+//! through vgmstream.
+
+use std::io::Cursor;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+
+use super::splice;
+
+/// Extensions worth trying in memory before falling back to vgmstream-cli
+pub const EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav"];
+
+/// Whether a file name has an extension decoded in memory
+pub fn handles_extension(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| EXTENSIONS.iter().any(|known| known.eq_ignore_ascii_case(e)))
+}
+
+/// Title tag of an audio file: ID3 `TIT2` in MP3, `TITLE` in Ogg and FLAC
+pub fn read_title(path: &std::path::Path) -> Option<String> {
+    let file = std::fs::File::open(path).ok()?;
+    let source = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+        .ok()?;
+    let title = |revision: &MetadataRevision| {
+        revision
+            .tags()
+            .iter()
+            .find(|tag| tag.std_key == Some(StandardTagKey::TrackTitle))
+            .map(|tag| tag.value.to_string().trim().to_string())
+            .filter(|title| !title.is_empty())
+    };
+    // ID3 tags are read by the probe, stream comments by the format reader
+    if let Some(found) = probed.metadata.get().as_ref().and_then(|m| m.current().and_then(title)) {
+        return Some(found);
+    }
+    probed.format.metadata().current().and_then(title)
+}
+
+/// Decode `data` to a PCM16 WAV. `extension` helps the format probe but is not required.
+pub fn decode_to_pcm16_wav(data: &[u8], extension: Option<&str>) -> Result<Vec<u8>, String> {
+    let source = MediaSourceStream::new(Box::new(Cursor::new(data.to_vec())), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = extension {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(&hint, source, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| format!("Unsupported audio format: {}", e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or("No audio track found")?;
+    let track_id = track.id;
+    let mut sample_rate = track.codec_params.sample_rate;
+    let mut channels = track.codec_params.channels.map(|c| c.count());
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported codec: {}", e))?;
+
+    let mut samples: Vec<u8> = Vec::new();
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(Error::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(format!("Failed to read audio: {}", e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        match decoder.decode(&packet) {
+            Ok(decoded) => {
+                let spec = *decoded.spec();
+                sample_rate = Some(spec.rate);
+                channels = Some(spec.channels.count());
+                let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, spec);
+                buffer.copy_interleaved_ref(decoded);
+                samples.extend(buffer.samples().iter().flat_map(|s| s.to_le_bytes()));
+            }
+            // A corrupt frame costs a few milliseconds, not the whole file
+            Err(Error::DecodeError(e)) => println!("Skipping undecodable packet: {}", e),
+            Err(e) => return Err(format!("Failed to decode audio: {}", e)),
+        }
+    }
+
+    let sample_rate = sample_rate.ok_or("Unknown sample rate")?;
+    let channels = channels.filter(|c| *c > 0).ok_or("Unknown channel count")?;
+    if samples.is_empty() {
+        return Err("No audio decoded".to_string());
+    }
+    Ok(splice::write_pcm16(channels as u16, sample_rate, &samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_processing::smpl;
+
+    #[test]
+    fn decodes_float_wav_to_pcm16() {
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 32000,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut data = Vec::new();
+        let mut writer = hound::WavWriter::new(Cursor::new(&mut data), spec).unwrap();
+        for i in 0..1000 {
+            writer.write_sample((i as f32 / 1000.0) - 0.5).unwrap();
+            writer.write_sample(0.25f32).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let wav = decode_to_pcm16_wav(&data, Some("wav")).unwrap();
+        let info = smpl::pcm16_info(&wav).unwrap();
+        assert_eq!((info.channels, info.sample_rate, info.frames), (2, 32000, 1000));
+        assert!(handles_extension("Voice.FLAC"));
+        assert!(!handles_extension("voice.lopus"));
+    }
+}
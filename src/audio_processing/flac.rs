@@ -0,0 +1,422 @@
+//! FLAC encoder for PCM16 WAV data.
+//!
+//! Frames use the fixed predictors (orders 0 to 4) with partitioned Rice residuals and,
+//! for stereo, the best of the four channel decorrelation modes. There is no LPC search,
+//! so files come out a little larger than the reference encoder's, but any FLAC decoder
+//! reads them. The STREAMINFO MD5 is left zero, which the format defines as "not computed".
+
+use super::smpl;
+
+/// Samples per channel in every frame but the last
+pub const BLOCK_SIZE: usize = 4096;
+
+/// Highest supported compression level
+pub const MAX_LEVEL: u8 = 8;
+
+const BITS_PER_SAMPLE: u32 = 16;
+/// Largest parameter the 4-bit Rice coding method can store without escaping
+const MAX_RICE_PARAM: u32 = 14;
+
+/// MSB-first bit writer
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u64,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self { bytes: Vec::new(), acc: 0, bits: 0 }
+    }
+
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits == 0 {
+            return;
+        }
+        self.acc = (self.acc << bits) | (value & (u64::MAX >> (64 - bits)));
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bits -= 8;
+            self.bytes.push((self.acc >> self.bits) as u8);
+        }
+        self.acc &= (1u64 << self.bits) - 1;
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64, bits);
+    }
+
+    /// `count` zero bits followed by a one
+    fn write_unary(&mut self, mut count: u32) {
+        while count >= 32 {
+            self.write(0, 32);
+            count -= 32;
+        }
+        self.write(1, count + 1);
+    }
+
+    /// Pad with zero bits to the next byte boundary
+    fn align(&mut self) {
+        if self.bits > 0 {
+            self.write(0, 8 - self.bits);
+        }
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |mut crc, byte| {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x8005 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// Frame number in FLAC's extended UTF-8 style coding
+fn write_utf8_number(out: &mut BitWriter, value: u64) {
+    if value < 0x80 {
+        out.write(value, 8);
+        return;
+    }
+    let bytes: u64 = match value {
+        0..0x800 => 2,
+        0x800..0x1_0000 => 3,
+        0x1_0000..0x20_0000 => 4,
+        0x20_0000..0x400_0000 => 5,
+        0x400_0000..0x8000_0000 => 6,
+        _ => 7,
+    };
+    let lead_mark = (0xFF00u64 >> bytes) & 0xFF;
+    out.write(lead_mark | (value >> (6 * (bytes - 1))), 8);
+    for i in (0..bytes - 1).rev() {
+        out.write(0x80 | ((value >> (6 * i)) & 0x3F), 8);
+    }
+}
+
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i32> {
+    samples
+        .windows(order + 1)
+        .map(|w| {
+            let x = |back: usize| w[order - back] as i64;
+            let r = match order {
+                0 => x(0),
+                1 => x(0) - x(1),
+                2 => x(0) - 2 * x(1) + x(2),
+                3 => x(0) - 3 * x(1) + 3 * x(2) - x(3),
+                _ => x(0) - 4 * x(1) + 6 * x(2) - 4 * x(3) + x(4),
+            };
+            r as i32
+        })
+        .collect()
+}
+
+fn zigzag(r: i32) -> u64 {
+    ((r << 1) ^ (r >> 31)) as u32 as u64
+}
+
+/// Rice parameter with the smallest estimated size for a partition, and that size
+fn best_rice_param(count: usize, sum: u64) -> (u32, u64) {
+    (0..=MAX_RICE_PARAM)
+        .map(|k| (k, count as u64 * (k as u64 + 1) + (sum >> k)))
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap_or((0, 0))
+}
+
+/// Partition order and Rice parameters for a residual, with its estimated size in bits
+fn plan_residual(residuals: &[i32], block_size: usize, order: usize, max_partition_order: u32) -> (u32, Vec<u32>, u64) {
+    let mut best: Option<(u32, Vec<u32>, u64)> = None;
+    for partition_order in 0..=max_partition_order {
+        let partitions = 1usize << partition_order;
+        if block_size % partitions != 0 || block_size / partitions <= order {
+            break;
+        }
+        let per_partition = block_size / partitions;
+        let mut start = 0;
+        let mut params = Vec::with_capacity(partitions);
+        let mut bits = 2 + 4;
+        for p in 0..partitions {
+            let len = if p == 0 { per_partition - order } else { per_partition };
+            let sum: u64 = residuals[start..start + len].iter().map(|r| zigzag(*r)).sum();
+            let (k, partition_bits) = best_rice_param(len, sum);
+            params.push(k);
+            bits += 4 + partition_bits;
+            start += len;
+        }
+        if best.as_ref().is_none_or(|b| bits < b.2) {
+            best = Some((partition_order, params, bits));
+        }
+    }
+    best.unwrap_or((0, vec![0], u64::MAX))
+}
+
+/// How one channel of a frame is stored
+enum Subframe {
+    Constant(i32),
+    Verbatim,
+    Fixed { order: usize, residuals: Vec<i32>, partition_order: u32, params: Vec<u32> },
+}
+
+/// Cheapest subframe for `samples` and its size in bits
+fn plan_subframe(samples: &[i32], bps: u32, max_order: usize, max_partition_order: u32) -> (Subframe, u64) {
+    let header_bits = 8;
+    if samples.iter().all(|s| *s == samples[0]) {
+        return (Subframe::Constant(samples[0]), header_bits + bps as u64);
+    }
+    let mut best = (Subframe::Verbatim, header_bits + bps as u64 * samples.len() as u64);
+    for order in 0..=max_order.min(samples.len().saturating_sub(1)) {
+        let residuals = fixed_residuals(samples, order);
+        let (partition_order, params, residual_bits) =
+            plan_residual(&residuals, samples.len(), order, max_partition_order);
+        let bits = (header_bits + bps as u64 * order as u64).saturating_add(residual_bits);
+        if bits < best.1 {
+            best = (Subframe::Fixed { order, residuals, partition_order, params }, bits);
+        }
+    }
+    best
+}
+
+fn write_subframe(out: &mut BitWriter, samples: &[i32], bps: u32, subframe: &Subframe) {
+    match subframe {
+        Subframe::Constant(value) => {
+            out.write(0, 8);
+            out.write_signed(*value as i64, bps);
+        }
+        Subframe::Verbatim => {
+            out.write(0b0000_0010, 8);
+            for s in samples {
+                out.write_signed(*s as i64, bps);
+            }
+        }
+        Subframe::Fixed { order, residuals, partition_order, params } => {
+            out.write((0b00_1000 | *order as u64) << 1, 8);
+            for s in &samples[..*order] {
+                out.write_signed(*s as i64, bps);
+            }
+            out.write(0, 2);
+            out.write(*partition_order as u64, 4);
+            let per_partition = samples.len() >> partition_order;
+            let mut start = 0;
+            for (p, k) in params.iter().enumerate() {
+                let len = if p == 0 { per_partition - order } else { per_partition };
+                out.write(*k as u64, 4);
+                for r in &residuals[start..start + len] {
+                    let u = zigzag(*r);
+                    out.write_unary((u >> k) as u32);
+                    out.write(u, *k);
+                }
+                start += len;
+            }
+        }
+    }
+}
+
+/// Channels of one candidate layout as (samples, bits per sample), keyed by its assignment code
+type ChannelLayout = Vec<(Vec<i32>, u32)>;
+
+/// Encode one frame of deinterleaved channels
+fn encode_frame(
+    frame_number: u64,
+    channels: &[Vec<i32>],
+    max_order: usize,
+    max_partition_order: u32,
+    decorrelate: bool,
+) -> Vec<u8> {
+    let block_size = channels[0].len();
+
+    let mut layouts: Vec<(u64, ChannelLayout)> = vec![(
+        channels.len() as u64 - 1,
+        channels.iter().map(|c| (c.clone(), BITS_PER_SAMPLE)).collect(),
+    )];
+    if decorrelate && channels.len() == 2 {
+        let (left, right) = (&channels[0], &channels[1]);
+        let side: Vec<i32> = left.iter().zip(right).map(|(l, r)| l - r).collect();
+        let mid: Vec<i32> = left.iter().zip(right).map(|(l, r)| (l + r) >> 1).collect();
+        let side_bps = BITS_PER_SAMPLE + 1;
+        layouts.push((8, vec![(left.clone(), BITS_PER_SAMPLE), (side.clone(), side_bps)]));
+        layouts.push((9, vec![(side.clone(), side_bps), (right.clone(), BITS_PER_SAMPLE)]));
+        layouts.push((10, vec![(mid, BITS_PER_SAMPLE), (side, side_bps)]));
+    }
+
+    let (assignment, subframes) = layouts
+        .into_iter()
+        .map(|(assignment, layout)| {
+            let planned: Vec<(Vec<i32>, u32, Subframe, u64)> = layout
+                .into_iter()
+                .map(|(samples, bps)| {
+                    let (subframe, bits) = plan_subframe(&samples, bps, max_order, max_partition_order);
+                    (samples, bps, subframe, bits)
+                })
+                .collect();
+            (assignment, planned)
+        })
+        .min_by_key(|(_, planned)| planned.iter().map(|p| p.3).sum::<u64>())
+        .expect("at least the independent layout");
+
+    let mut out = BitWriter::new();
+    out.write(0b1111_1111_1111_1000, 16); // sync code, fixed block size
+    out.write(0b0111, 4); // block size stored as 16 bits after the frame number
+    out.write(0, 4); // sample rate from STREAMINFO
+    out.write(assignment, 4);
+    out.write(0b100, 3); // 16 bits per sample
+    out.write(0, 1);
+    write_utf8_number(&mut out, frame_number);
+    out.write(block_size as u64 - 1, 16);
+    let header = out.bytes.clone();
+    out.write(crc8(&header) as u64, 8);
+
+    for (samples, bps, subframe, _) in &subframes {
+        write_subframe(&mut out, samples, *bps, subframe);
+    }
+    let mut frame = out.into_bytes();
+    let crc = crc16(&frame);
+    frame.extend_from_slice(&crc.to_be_bytes());
+    frame
+}
+
+/// Encode a PCM16 WAV as FLAC. `level` runs from 0 (fastest) to `MAX_LEVEL` (smallest);
+/// it bounds the predictor order and the Rice partition search.
+pub fn encode_wav(wav: &[u8], level: u8) -> Result<Vec<u8>, String> {
+    let (info, data) = smpl::pcm16_samples(wav).ok_or("FLAC export needs PCM16 WAV input")?;
+    let channel_count = info.channels as usize;
+    if channel_count == 0 || channel_count > 8 {
+        return Err(format!("FLAC supports 1 to 8 channels, not {}", channel_count));
+    }
+    if info.sample_rate == 0 || info.sample_rate >= 1 << 20 {
+        return Err(format!("Unsupported sample rate for FLAC: {}", info.sample_rate));
+    }
+
+    let level = level.min(MAX_LEVEL);
+    let max_order = if level == 0 { 2 } else { 4 };
+    let max_partition_order = level as u32;
+    let decorrelate = level > 0;
+
+    let samples: Vec<i32> = data
+        .chunks_exact(2)
+        .map(|s| i16::from_le_bytes([s[0], s[1]]) as i32)
+        .collect();
+    let total_frames = samples.len() / channel_count;
+
+    let mut frames = Vec::new();
+    let (mut min_frame, mut max_frame) = (u32::MAX, 0u32);
+    for (number, block) in samples[..total_frames * channel_count]
+        .chunks(BLOCK_SIZE * channel_count)
+        .enumerate()
+    {
+        let channels: Vec<Vec<i32>> = (0..channel_count)
+            .map(|c| block.iter().skip(c).step_by(channel_count).copied().collect())
+            .collect();
+        let frame = encode_frame(number as u64, &channels, max_order, max_partition_order, decorrelate);
+        min_frame = min_frame.min(frame.len() as u32);
+        max_frame = max_frame.max(frame.len() as u32);
+        frames.extend_from_slice(&frame);
+    }
+    if min_frame == u32::MAX {
+        min_frame = 0;
+    }
+
+    let mut out = BitWriter::new();
+    out.write(u32::from_be_bytes(*b"fLaC") as u64, 32);
+    out.write(1, 1); // last metadata block
+    out.write(0, 7); // STREAMINFO
+    out.write(34, 24);
+    out.write(BLOCK_SIZE as u64, 16);
+    out.write(BLOCK_SIZE as u64, 16);
+    out.write(min_frame as u64, 24);
+    out.write(max_frame as u64, 24);
+    out.write(info.sample_rate as u64, 20);
+    out.write(channel_count as u64 - 1, 3);
+    out.write(BITS_PER_SAMPLE as u64 - 1, 5);
+    out.write(total_frames as u64, 36);
+    for _ in 0..4 {
+        out.write(0, 32); // MD5 not computed
+    }
+    let mut flac = out.into_bytes();
+    flac.extend_from_slice(&frames);
+    Ok(flac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_processing::splice;
+
+    #[test]
+    fn encodes_streaminfo_and_checksummed_frames() {
+        // 1.5 blocks of a stereo ramp, so the last frame is short
+        let frames = BLOCK_SIZE + BLOCK_SIZE / 2;
+        let mut samples = Vec::new();
+        for i in 0..frames {
+            let left = ((i % 200) as i16 - 100) * 50;
+            samples.extend_from_slice(&left.to_le_bytes());
+            samples.extend_from_slice(&(left / 2).to_le_bytes());
+        }
+        let wav = splice::write_pcm16(2, 44100, &samples);
+        let flac = encode_wav(&wav, 5).unwrap();
+
+        assert_eq!(&flac[0..4], b"fLaC");
+        assert_eq!(flac[4], 0x80);
+        let info = &flac[8..42];
+        let rate = ((info[10] as u32) << 12) | ((info[11] as u32) << 4) | ((info[12] as u32) >> 4);
+        assert_eq!(rate, 44100);
+        assert_eq!((info[12] >> 1) & 0x07, 1); // two channels
+        assert_eq!(u32::from_be_bytes([info[14], info[15], info[16], info[17]]) as usize, frames);
+        assert!(flac.len() < samples.len() / 2);
+
+        // Every frame starts with a sync code and ends with a valid CRC-16
+        let mut offsets: Vec<usize> = (42..flac.len() - 1)
+            .filter(|&i| flac[i] == 0xFF && flac[i + 1] == 0xF8 && crc8(&flac[i..i + 7]) == flac[i + 7])
+            .collect();
+        assert_eq!(offsets.len(), 2);
+        offsets.push(flac.len());
+        for pair in offsets.windows(2) {
+            let frame = &flac[pair[0]..pair[1]];
+            let (body, crc) = frame.split_at(frame.len() - 2);
+            assert_eq!(crc16(body).to_be_bytes(), [crc[0], crc[1]]);
+        }
+
+        assert!(encode_wav(b"RIFF", 5).is_err());
+    }
+
+    #[test]
+    fn decodes_back_to_the_same_samples() {
+        // Noise over a tone, with full-scale peaks, so every predictor order and both
+        // residual coding parameters get used
+        let mut seed = 0x1234_5678u32;
+        let mut sample = |i: usize| {
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let tone = (i as f32 * 0.05).sin() * 12000.0;
+            match i % 997 {
+                0 => i16::MAX,
+                1 => i16::MIN,
+                _ => (tone as i32 + (seed >> 22) as i32 - 512) as i16,
+            }
+        };
+        for channels in [1u16, 2] {
+            for frames in [1, 37, BLOCK_SIZE * 2 + 333] {
+                let samples: Vec<u8> = (0..frames * channels as usize).flat_map(|i| sample(i).to_le_bytes()).collect();
+                let flac = encode_wav(&splice::write_pcm16(channels, 32000, &samples), 5).unwrap();
+
+                let decoded = crate::audio_processing::decode::decode_to_pcm16_wav(&flac, Some("flac")).unwrap();
+                let (info, decoded_samples) = crate::audio_processing::smpl::pcm16_samples(&decoded).unwrap();
+                assert_eq!((info.channels, info.sample_rate), (channels, 32000));
+                assert!(decoded_samples == samples, "{} channel(s), {} frames", channels, frames);
+            }
+        }
+    }
+}
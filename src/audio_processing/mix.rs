@@ -0,0 +1,295 @@
+//! Gain, normalization, fades and downmix of PCM16 WAV files.
+//!
+//! Loudness follows ITU-R BS.1770: K-weighted, 400 ms blocks with 75% overlap, gated at
+//! -70 LUFS and then 10 LU under the ungated level. Channels are weighted equally.
+
+use super::smpl;
+use super::splice;
+
+/// Level a track is brought to before the gain is applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Normalize {
+    Off,
+    /// Sample peak in dBFS
+    Peak(f32),
+    /// Integrated loudness in LUFS
+    Lufs(f32),
+}
+
+/// Channel layout of the result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Downmix {
+    Keep,
+    /// More than two channels folded to two, even channels left and odd channels right
+    Stereo,
+    Mono,
+}
+
+impl Downmix {
+    pub const ALL: [Downmix; 3] = [Downmix::Keep, Downmix::Stereo, Downmix::Mono];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Downmix::Keep => "Keep channels",
+            Downmix::Stereo => "Stereo",
+            Downmix::Mono => "Mono",
+        }
+    }
+
+    fn channels(&self, channels: usize) -> usize {
+        match self {
+            Downmix::Keep => channels,
+            Downmix::Stereo => channels.min(2),
+            Downmix::Mono => 1,
+        }
+    }
+}
+
+/// Everything the mixing panel applies to a track, in this order: downmix, normalize,
+/// gain, fades
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixSettings {
+    /// Applied after normalization, so it trims the normalized level
+    pub gain_db: f32,
+    pub normalize: Normalize,
+    pub fade_in_ms: u32,
+    pub fade_out_ms: u32,
+    pub downmix: Downmix,
+}
+
+impl Default for MixSettings {
+    fn default() -> Self {
+        Self {
+            gain_db: 0.0,
+            normalize: Normalize::Off,
+            fade_in_ms: 0,
+            fade_out_ms: 0,
+            downmix: Downmix::Keep,
+        }
+    }
+}
+
+impl MixSettings {
+    /// Whether applying these settings would leave the audio as it is
+    pub fn is_neutral(&self) -> bool {
+        self.gain_db.abs() <= f32::EPSILON && MixSettings { gain_db: 0.0, ..*self } == Self::default()
+    }
+
+    /// Short summary for the edit log, e.g. "normalize -16.0 LUFS, fade in 20 ms, mono"
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        match self.normalize {
+            Normalize::Off => {}
+            Normalize::Peak(db) => parts.push(format!("normalize peak {:.1} dBFS", db)),
+            Normalize::Lufs(lufs) => parts.push(format!("normalize {:.1} LUFS", lufs)),
+        }
+        if self.gain_db.abs() > f32::EPSILON {
+            parts.push(format!("gain {:+.1} dB", self.gain_db));
+        }
+        if self.fade_in_ms > 0 {
+            parts.push(format!("fade in {} ms", self.fade_in_ms));
+        }
+        if self.fade_out_ms > 0 {
+            parts.push(format!("fade out {} ms", self.fade_out_ms));
+        }
+        if self.downmix != Downmix::Keep {
+            parts.push(self.downmix.name().to_lowercase());
+        }
+        parts.join(", ")
+    }
+}
+
+/// Interleaved samples of a PCM16 WAV as floats in -1.0..1.0
+fn read_frames(samples: &[u8]) -> Vec<f32> {
+    samples
+        .chunks_exact(2)
+        .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)
+        .collect()
+}
+
+fn downmix(samples: &[f32], channels: usize, out_channels: usize) -> Vec<f32> {
+    if out_channels == channels {
+        return samples.to_vec();
+    }
+    let mut out = Vec::with_capacity(samples.len() / channels * out_channels);
+    for frame in samples.chunks_exact(channels) {
+        for target in 0..out_channels {
+            let sources: Vec<f32> = frame.iter().skip(target).step_by(out_channels).copied().collect();
+            out.push(sources.iter().sum::<f32>() / sources.len() as f32);
+        }
+    }
+    out
+}
+
+/// Highest absolute sample in dBFS; `None` for silence
+pub fn peak_dbfs(samples: &[f32]) -> Option<f32> {
+    let peak = samples.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+    (peak > 0.0).then(|| 20.0 * peak.log10())
+}
+
+/// One biquad section, direct form I
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn run(&mut self, input: f64) -> f64 {
+        let out = self.b[0] * input + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [input, self.x[0]];
+        self.y = [out, self.y[0]];
+        out
+    }
+}
+
+/// The two K-weighting stages (high shelf, then high pass) for `sample_rate`
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let rate = sample_rate as f64;
+
+    let k = (std::f64::consts::PI * 1681.974450955533 / rate).tan();
+    let q = 0.7071752369554196;
+    let vh = 10f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        x: [0.0; 2],
+        y: [0.0; 2],
+    };
+
+    let k = (std::f64::consts::PI * 38.13547087602444 / rate).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        x: [0.0; 2],
+        y: [0.0; 2],
+    };
+    [shelf, high_pass]
+}
+
+/// Integrated loudness of interleaved samples in LUFS; `None` when every block is gated out
+pub fn integrated_lufs(samples: &[f32], channels: usize, sample_rate: u32) -> Option<f32> {
+    let frames = samples.len() / channels;
+    // Squared K-weighted signal, summed over channels per frame
+    let mut energy = vec![0.0f64; frames];
+    for channel in 0..channels {
+        let [mut shelf, mut high_pass] = k_weighting(sample_rate);
+        for (frame, e) in energy.iter_mut().enumerate() {
+            let weighted = high_pass.run(shelf.run(samples[frame * channels + channel] as f64));
+            *e += weighted * weighted;
+        }
+    }
+
+    let block = (sample_rate as usize * 4 / 10).max(1);
+    let step = (block / 4).max(1);
+    let blocks: Vec<f64> = if frames < block {
+        vec![energy.iter().sum::<f64>() / block as f64]
+    } else {
+        (0..=(frames - block) / step)
+            .map(|i| energy[i * step..i * step + block].iter().sum::<f64>() / block as f64)
+            .collect()
+    };
+
+    let loudness = |mean: f64| -0.691 + 10.0 * mean.log10();
+    let gated_mean = |threshold: f64| {
+        let kept: Vec<f64> = blocks.iter().copied().filter(|m| *m > 0.0 && loudness(*m) > threshold).collect();
+        (!kept.is_empty()).then(|| kept.iter().sum::<f64>() / kept.len() as f64)
+    };
+    let ungated = gated_mean(-70.0)?;
+    let integrated = gated_mean(loudness(ungated) - 10.0)?;
+    Some(loudness(integrated) as f32)
+}
+
+/// Apply `settings` to a PCM16 WAV. A `smpl` loop is kept, since the length does not change.
+pub fn apply_pcm16(data: &[u8], settings: &MixSettings) -> Result<Vec<u8>, String> {
+    let (info, samples) = smpl::pcm16_samples(data).ok_or("Not a PCM16 WAV file")?;
+    let channels = info.channels as usize;
+    let out_channels = settings.downmix.channels(channels);
+    let mut samples = downmix(&read_frames(samples), channels, out_channels);
+
+    let normalize_db = match settings.normalize {
+        Normalize::Off => 0.0,
+        Normalize::Peak(target) => {
+            target - peak_dbfs(&samples).ok_or("The track is silent and cannot be normalized")?
+        }
+        Normalize::Lufs(target) => {
+            target
+                - integrated_lufs(&samples, out_channels, info.sample_rate)
+                    .ok_or("The track is too quiet to measure its loudness")?
+        }
+    };
+    let gain = 10f32.powf((normalize_db + settings.gain_db) / 20.0);
+
+    let frames = samples.len() / out_channels;
+    let fade_len = |ms: u32| ((ms as u64 * info.sample_rate as u64 / 1000) as usize).min(frames);
+    let fade_in = fade_len(settings.fade_in_ms);
+    let fade_out = fade_len(settings.fade_out_ms);
+    for (frame, values) in samples.chunks_exact_mut(out_channels).enumerate() {
+        let mut factor = gain;
+        if frame < fade_in {
+            factor *= frame as f32 / fade_in as f32;
+        }
+        let from_end = frames - 1 - frame;
+        if from_end < fade_out {
+            factor *= from_end as f32 / fade_out as f32;
+        }
+        for value in values {
+            *value *= factor;
+        }
+    }
+
+    let out: Vec<u8> = samples
+        .iter()
+        .flat_map(|v| ((v.clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes())
+        .collect();
+    let wav = splice::write_pcm16(out_channels as u16, info.sample_rate, &out);
+    match smpl::read_loop(data) {
+        Some(lp) => smpl::rewrite_pcm16(&wav, Some(lp)),
+        None => Ok(wav),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_processing::smpl::SmplLoop;
+
+    #[test]
+    fn normalizes_downmixes_fades_and_keeps_the_loop() {
+        // Stereo tone where the right channel is twice as loud as the left
+        let samples: Vec<u8> = (0..4800)
+            .flat_map(|i| {
+                let v = ((i as f32 * 0.05).sin() * 8000.0) as i16;
+                [v, v * 2]
+            })
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        let wav = smpl::rewrite_pcm16(&splice::write_pcm16(2, 48000, &samples), Some(SmplLoop { start: 10, end: 4000 })).unwrap();
+
+        let settings = MixSettings {
+            normalize: Normalize::Peak(-6.0),
+            fade_in_ms: 10,
+            downmix: Downmix::Mono,
+            ..Default::default()
+        };
+        let out = apply_pcm16(&wav, &settings).unwrap();
+        let (info, data) = smpl::pcm16_samples(&out).unwrap();
+        assert_eq!((info.channels, info.frames), (1, 4800));
+        let floats = read_frames(data);
+        assert!((peak_dbfs(&floats).unwrap() + 6.0).abs() < 0.1);
+        assert_eq!(floats[0], 0.0);
+        assert_eq!(smpl::read_loop(&out), Some(SmplLoop { start: 10, end: 4000 }));
+
+        // A full-scale 1 kHz sine measures close to -3 LUFS
+        let sine: Vec<f32> = (0..48000).map(|i| (i as f32 * 2.0 * std::f32::consts::PI * 1000.0 / 48000.0).sin()).collect();
+        assert!((integrated_lufs(&sine, 1, 48000).unwrap() + 3.0).abs() < 0.2);
+        assert!(apply_pcm16(&splice::write_pcm16(1, 48000, &[0; 200]), &settings).is_err());
+    }
+}
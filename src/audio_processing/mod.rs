@@ -0,0 +1,8 @@
+//! Audio processing done in-process, without external tools
+#[cfg(not(target_arch = "wasm32"))]
+pub mod decode;
+pub mod flac;
+pub mod mix;
+pub mod resample;
+pub mod smpl;
+pub mod splice;
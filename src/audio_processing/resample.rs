@@ -0,0 +1,70 @@
+//! Sample rate and channel reduction of PCM16 WAV files.
+
+use super::smpl::{self, SmplLoop};
+use super::splice;
+
+/// Resample a PCM16 WAV to `sample_rate` with linear interpolation, optionally mixing it
+/// down to mono. A `smpl` loop is moved to the new rate.
+pub fn downsample_pcm16(data: &[u8], sample_rate: u32, mono: bool) -> Result<Vec<u8>, String> {
+    let (info, samples) = smpl::pcm16_samples(data).ok_or("Not a PCM16 WAV file")?;
+    if sample_rate == 0 || sample_rate > info.sample_rate {
+        return Err(format!("Cannot resample {} Hz audio to {} Hz", info.sample_rate, sample_rate));
+    }
+
+    let channels = info.channels as usize;
+    let frames = samples.len() / (channels * 2);
+    let frame = |index: usize| -> Vec<f32> {
+        (0..channels)
+            .map(|c| {
+                let at = (index * channels + c) * 2;
+                i16::from_le_bytes([samples[at], samples[at + 1]]) as f32
+            })
+            .collect()
+    };
+
+    let ratio = info.sample_rate as f64 / sample_rate as f64;
+    let out_frames = ((frames as f64) / ratio).floor() as usize;
+    let out_channels = if mono { 1 } else { channels };
+    let mut out = Vec::with_capacity(out_frames * out_channels * 2);
+    for i in 0..out_frames {
+        let position = i as f64 * ratio;
+        let index = position as usize;
+        let fraction = (position - index as f64) as f32;
+        let a = frame(index);
+        let b = frame((index + 1).min(frames - 1));
+        let mixed: Vec<f32> = a.iter().zip(&b).map(|(a, b)| a + (b - a) * fraction).collect();
+        let values = if mono { vec![mixed.iter().sum::<f32>() / channels as f32] } else { mixed };
+        for value in values {
+            out.extend_from_slice(&(value.round().clamp(-32768.0, 32767.0) as i16).to_le_bytes());
+        }
+    }
+
+    let wav = splice::write_pcm16(out_channels as u16, sample_rate, &out);
+    let scale = |sample: u32| (sample as f64 / ratio).round() as u32;
+    match smpl::read_loop(data) {
+        Some(lp) if out_frames > 0 => {
+            let end = scale(lp.end).min(out_frames as u32 - 1);
+            smpl::rewrite_pcm16(&wav, Some(SmplLoop { start: scale(lp.start).min(end), end }))
+        }
+        _ => Ok(wav),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn halves_rate_mixes_to_mono_and_moves_loop() {
+        let samples: Vec<u8> = (0..400i16).flat_map(|i| [i * 2, i * 4]).flat_map(|s| s.to_le_bytes()).collect();
+        let wav = smpl::rewrite_pcm16(&splice::write_pcm16(2, 48000, &samples), Some(SmplLoop { start: 100, end: 399 })).unwrap();
+
+        let out = downsample_pcm16(&wav, 24000, true).unwrap();
+        let (info, data) = smpl::pcm16_samples(&out).unwrap();
+        assert_eq!((info.channels, info.sample_rate, info.frames), (1, 24000, 200));
+        // Frame 10 of the output is frame 20 of the input: (40 + 80) / 2
+        assert_eq!(i16::from_le_bytes([data[20], data[21]]), 60);
+        assert_eq!(smpl::read_loop(&out), Some(SmplLoop { start: 50, end: 199 }));
+        assert!(downsample_pcm16(&wav, 96000, false).is_err());
+    }
+}
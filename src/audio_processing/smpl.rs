@@ -0,0 +1,152 @@
+//! Native `smpl` chunk writing for PCM16 WAV files.
+//!
+//! Produces the same layout as `vgmstream-cli -L`: `fmt `, then `smpl` at offset 0x24 with a
+//! single forward loop, then `data`. The loop start and end sample are at 0x58 and 0x5C.
+//! Chunks are found with the shared [`Riff`] walker.
+
+use std::borrow::Cow;
+
+use crate::wav::riff::{Chunk, Riff};
+pub use crate::wav::riff::{build_smpl_chunk, SmplLoop};
+
+/// Format of a PCM16 WAV as read from its `fmt ` chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pcm16Info {
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// Number of sample frames in the `data` chunk
+    pub frames: u32,
+}
+
+/// Format of a parsed plain PCM16 WAV (format tag 1, 16-bit)
+fn riff_pcm16_info(riff: &Riff<'_>) -> Option<Pcm16Info> {
+    let fmt = riff.fmt()?;
+    let data_len = riff.get(b"data")?.data.len();
+    if fmt.tag != 1 || fmt.bits_per_sample != 16 || fmt.channels == 0 || fmt.sample_rate == 0 {
+        return None;
+    }
+    Some(Pcm16Info {
+        channels: fmt.channels,
+        sample_rate: fmt.sample_rate,
+        frames: (data_len / (fmt.channels as usize * 2)) as u32,
+    })
+}
+
+/// Read the format of a plain PCM16 WAV (format tag 1, 16-bit). Extensible or
+/// compressed WAVs return `None` and still need vgmstream-cli.
+pub fn pcm16_info(data: &[u8]) -> Option<Pcm16Info> {
+    riff_pcm16_info(&Riff::parse_wav(data).ok()?)
+}
+
+/// Whether a WAV has the plain 16-byte PCM16 `fmt ` chunk the game decodes. Extensible
+/// headers, even ones wrapping PCM16, need converting.
+pub fn is_standard_pcm16_wav(data: &[u8]) -> bool {
+    let Ok(riff) = Riff::parse_wav_exact(data) else {
+        return false;
+    };
+    let plain = riff.get(b"fmt ").is_some_and(|fmt| fmt.data.len() == 16);
+    plain && riff.fmt().is_some_and(|fmt| fmt.tag == 1 && fmt.bits_per_sample == 16)
+}
+
+/// Format and raw interleaved sample bytes of a plain PCM16 WAV
+pub fn pcm16_samples(data: &[u8]) -> Option<(Pcm16Info, &[u8])> {
+    let riff = Riff::parse_wav(data).ok()?;
+    let info = riff_pcm16_info(&riff)?;
+    Some((info, riff.get(b"data")?.borrowed()?))
+}
+
+/// Rewrite a PCM16 WAV as `fmt ` + optional `smpl` + `data`. Any existing `smpl` and other
+/// chunks are dropped, matching the output of `vgmstream-cli -i [-L]`.
+pub fn rewrite_pcm16(data: &[u8], lp: Option<SmplLoop>) -> Result<Vec<u8>, String> {
+    let riff = Riff::parse_wav(data)?;
+    let info = riff_pcm16_info(&riff).ok_or("Not a PCM16 WAV file")?;
+    let fmt = riff.get(b"fmt ").ok_or("Missing fmt chunk")?;
+    let samples = riff.get(b"data").ok_or("Missing data chunk")?;
+
+    if let Some(lp) = lp {
+        if lp.start > lp.end || lp.end >= info.frames.max(1) {
+            return Err(format!(
+                "Loop {}..{} is outside the audio ({} samples)",
+                lp.start, lp.end, info.frames
+            ));
+        }
+    }
+
+    let mut chunks = vec![Chunk { id: *b"fmt ", data: Cow::Borrowed(&fmt.data[..16]) }];
+    if let Some(lp) = lp {
+        chunks.push(Chunk { id: *b"smpl", data: Cow::Owned(build_smpl_chunk(info.sample_rate, lp)) });
+    }
+    chunks.push(samples.clone());
+    Ok(Riff { form: *b"WAVE", chunks }.to_bytes())
+}
+
+/// Loop points of the `smpl` chunk, if the WAV has one
+pub fn read_loop(data: &[u8]) -> Option<SmplLoop> {
+    Riff::parse_wav(data).ok()?.smpl_loop()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pcm16_wav(frames: i16, with_list: bool) -> Vec<u8> {
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 32000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for i in 0..frames {
+            writer.write_sample(i).unwrap();
+            writer.write_sample(-i).unwrap();
+        }
+        writer.finalize().unwrap();
+        let mut wav = cursor.into_inner();
+        if with_list {
+            wav.extend_from_slice(b"LIST\x04\x00\x00\x00INFO");
+            let riff_size = (wav.len() - 8) as u32;
+            wav[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        }
+        wav
+    }
+
+    #[test]
+    fn smpl_is_written_where_vgmstream_puts_it() {
+        let wav = pcm16_wav(100, true);
+        let looped = rewrite_pcm16(&wav, Some(SmplLoop { start: 10, end: 99 })).unwrap();
+
+        assert_eq!(&looped[0x24..0x28], b"smpl");
+        assert_eq!(u32::from_le_bytes(looped[0x58..0x5C].try_into().unwrap()), 10);
+        assert_eq!(u32::from_le_bytes(looped[0x5C..0x60].try_into().unwrap()), 99);
+        assert_eq!(read_loop(&looped), Some(SmplLoop { start: 10, end: 99 }));
+
+        let mut reader = hound::WavReader::new(std::io::Cursor::new(looped.clone())).unwrap();
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples.len(), 200);
+        assert_eq!(samples[198], 99);
+        assert_eq!(samples[199], -99);
+
+        // Rewriting replaces the loop instead of adding a second smpl chunk
+        let relooped = rewrite_pcm16(&looped, Some(SmplLoop { start: 0, end: 50 })).unwrap();
+        assert_eq!(relooped.len(), looped.len());
+        assert_eq!(read_loop(&relooped), Some(SmplLoop { start: 0, end: 50 }));
+        assert_eq!(read_loop(&rewrite_pcm16(&relooped, None).unwrap()), None);
+    }
+
+    #[test]
+    fn rejects_loops_past_the_end_and_non_pcm16() {
+        let wav = pcm16_wav(100, false);
+        assert!(rewrite_pcm16(&wav, Some(SmplLoop { start: 0, end: 100 })).is_err());
+        assert!(rewrite_pcm16(&wav, Some(SmplLoop { start: 60, end: 50 })).is_err());
+
+        let mut float = wav.clone();
+        float[20] = 3; // format tag: IEEE float
+        assert_eq!(pcm16_info(&float), None);
+        assert_eq!(
+            pcm16_info(&wav),
+            Some(Pcm16Info { channels: 2, sample_rate: 32000, frames: 100 })
+        );
+    }
+}
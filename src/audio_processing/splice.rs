@@ -0,0 +1,100 @@
+//! Joining and splitting PCM16 WAV files at frame boundaries.
+
+use super::smpl::{self, Pcm16Info};
+
+/// Build a PCM16 WAV from interleaved sample bytes
+pub fn write_pcm16(channels: u16, sample_rate: u32, samples: &[u8]) -> Vec<u8> {
+    let block_align = channels as u32 * 2;
+    let mut out = Vec::with_capacity(44 + samples.len() + 1);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&((36 + samples.len() + (samples.len() & 1)) as u32).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * block_align).to_le_bytes());
+    out.extend_from_slice(&(block_align as u16).to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    out.extend_from_slice(samples);
+    if samples.len() % 2 == 1 {
+        out.push(0);
+    }
+    out
+}
+
+fn read(data: &[u8], what: &str) -> Result<(Pcm16Info, Vec<u8>), String> {
+    let (info, samples) = smpl::pcm16_samples(data).ok_or_else(|| format!("{} is not a PCM16 WAV", what))?;
+    // Drop a trailing partial frame so frame math stays exact
+    let whole = samples.len() - samples.len() % (info.channels as usize * 2);
+    Ok((info, samples[..whole].to_vec()))
+}
+
+/// Append `second` to `first`. Both must share channels and sample rate. Returns the joined
+/// WAV and the frame where `second` starts.
+pub fn join_pcm16(first: &[u8], second: &[u8]) -> Result<(Vec<u8>, u32), String> {
+    let (a, mut samples) = read(first, "First part")?;
+    let (b, rest) = read(second, "Second part")?;
+    if (a.channels, a.sample_rate) != (b.channels, b.sample_rate) {
+        return Err(format!(
+            "Parts differ in format: {} ch at {} Hz vs {} ch at {} Hz",
+            a.channels, a.sample_rate, b.channels, b.sample_rate
+        ));
+    }
+    let join_frame = (samples.len() / (a.channels as usize * 2)) as u32;
+    samples.extend_from_slice(&rest);
+    Ok((write_pcm16(a.channels, a.sample_rate, &samples), join_frame))
+}
+
+/// Split a PCM16 WAV into `[0, frame)` and `[frame, end)`. Both parts must be non-empty.
+pub fn split_pcm16(data: &[u8], frame: u32) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let (info, samples) = read(data, "Source")?;
+    let frames = (samples.len() / (info.channels as usize * 2)) as u32;
+    if frame == 0 || frame >= frames {
+        return Err(format!("Split point {} is outside the audio (1..{} samples)", frame, frames));
+    }
+    let at = frame as usize * info.channels as usize * 2;
+    Ok((
+        write_pcm16(info.channels, info.sample_rate, &samples[..at]),
+        write_pcm16(info.channels, info.sample_rate, &samples[at..]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp(frames: i16, channels: u16, rate: u32) -> Vec<u8> {
+        let samples: Vec<u8> = (0..frames)
+            .flat_map(|i| (0..channels).flat_map(move |_| i.to_le_bytes()))
+            .collect();
+        write_pcm16(channels, rate, &samples)
+    }
+
+    #[test]
+    fn split_then_join_round_trips() {
+        let wav = ramp(300, 2, 32000);
+        let (intro, body) = split_pcm16(&wav, 120).unwrap();
+        assert_eq!(smpl::pcm16_info(&intro).unwrap().frames, 120);
+        assert_eq!(smpl::pcm16_info(&body).unwrap().frames, 180);
+
+        let (joined, join_frame) = join_pcm16(&intro, &body).unwrap();
+        assert_eq!(join_frame, 120);
+        assert_eq!(joined, wav);
+
+        let reader = hound::WavReader::new(std::io::Cursor::new(body)).unwrap();
+        assert_eq!(reader.into_samples::<i16>().next().unwrap().unwrap(), 120);
+    }
+
+    #[test]
+    fn rejects_bad_split_points_and_mismatched_formats() {
+        let wav = ramp(10, 1, 8000);
+        assert!(split_pcm16(&wav, 0).is_err());
+        assert!(split_pcm16(&wav, 10).is_err());
+        assert!(join_pcm16(&wav, &ramp(10, 2, 8000)).unwrap_err().contains("differ"));
+        assert!(join_pcm16(&wav, b"OPUS").is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! Reading the CSV files users hand the editor: replacement manifests and label tables.
+
+/// Fields of one CSV row. Quoted fields may hold commas, and `""` inside them is a quote;
+/// the quotes themselves are dropped and fields are returned untrimmed.
+pub fn split_row(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_fields() {
+        assert_eq!(split_row(r#"a, "b, c" ,"say ""hi""""#), ["a", " b, c ", "say \"hi\""]);
+        assert_eq!(split_row(""), [""]);
+    }
+}
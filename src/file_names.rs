@@ -0,0 +1,57 @@
+/// Characters that cannot appear in a file name on Windows, path separators included
+const RESERVED_CHARS: [char; 9] = ['<', '>', ':', '"', '|', '?', '*', '/', '\\'];
+
+/// Device names Windows treats specially whatever the extension
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1",
+    "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Longest file name written, in bytes, leaving room for the folder on systems with short
+/// path limits
+const MAX_FILE_NAME_BYTES: usize = 200;
+
+/// Turn a name taken from bank data into a single file name that stays inside the folder it
+/// is joined to: separators, reserved and control characters become '_', names made only of
+/// dots or matching a Windows device name get a '_' prefix, and trailing dots and spaces are
+/// dropped. Unicode is kept; use `path_utils::ascii_file_name` for tool arguments.
+pub fn safe_file_name(name: &str) -> String {
+    let mut cleaned: String = name
+        .chars()
+        .map(|c| if c.is_control() || RESERVED_CHARS.contains(&c) { '_' } else { c })
+        .collect();
+    if cleaned.len() > MAX_FILE_NAME_BYTES {
+        let mut end = MAX_FILE_NAME_BYTES;
+        while !cleaned.is_char_boundary(end) {
+            end -= 1;
+        }
+        cleaned.truncate(end);
+    }
+    let mut cleaned = cleaned.trim_end_matches(['.', ' ']).trim_start().to_string();
+    if cleaned.is_empty() {
+        return "audio".to_string();
+    }
+    let stem = cleaned.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        cleaned.insert(0, '_');
+    }
+    cleaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_cannot_leave_the_output_folder() {
+        assert_eq!(safe_file_name("bgm_stage01"), "bgm_stage01");
+        assert_eq!(safe_file_name("../../evil"), ".._.._evil");
+        assert_eq!(safe_file_name("..\\win\\system32"), ".._win_system32");
+        assert_eq!(safe_file_name("/etc/passwd"), "_etc_passwd");
+        assert_eq!(safe_file_name(".."), "audio");
+        assert_eq!(safe_file_name("C:secret"), "C_secret");
+        assert_eq!(safe_file_name("con.wav"), "_con.wav");
+        assert_eq!(safe_file_name("ボイス\u{0}01. "), "ボイス_01");
+        assert!(safe_file_name(&"あ".repeat(100)).len() <= MAX_FILE_NAME_BYTES);
+    }
+}
@@ -0,0 +1,157 @@
+//! Companion label files shipped next to a bank by the community (cue sheets, translation
+//! tables). They are only read, never written.
+//!
+//! Three layouts are understood:
+//! - `.tonelabel` / `.labels.txt`: one `key = label` per line; `=`, a tab or `:` separate the
+//!   key from the label. Lines starting with `#` or `//` are comments.
+//! - `.labels.csv`: `key,label` rows; a header row naming a `label` column is skipped.
+//! - `.labels.json`: an object mapping keys to label strings.
+//!
+//! A key is a track name, its numeric ID or its hex ID (`0x1a`), matched case-insensitively.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name suffixes tried next to `<bank stem>`, in order
+pub const LABEL_SUFFIXES: [&str; 4] = [".tonelabel", ".labels.txt", ".labels.csv", ".labels.json"];
+
+/// Labels read from one companion file
+#[derive(Debug, Clone, Default)]
+pub struct LabelFile {
+    pub path: PathBuf,
+    /// Normalized key -> label
+    entries: HashMap<String, String>,
+}
+
+/// Lowercase, and hex IDs without leading zeros so `0x001A` matches `0x1a`
+fn normalize_key(key: &str) -> String {
+    let key = key.trim().to_lowercase();
+    match key.strip_prefix("0x") {
+        Some(hex) if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            let digits = hex.trim_start_matches('0');
+            format!("0x{}", if digits.is_empty() { "0" } else { digits })
+        }
+        _ => key,
+    }
+}
+
+/// Key and label of a CSV row; commas after the first belong to the label
+fn split_csv_row(line: &str) -> Option<(String, String)> {
+    let fields = crate::csv::split_row(line);
+    let (key, label) = fields.split_first().filter(|(_, label)| !label.is_empty())?;
+    Some((key.trim().to_string(), label.join(",").trim().to_string()))
+}
+
+impl LabelFile {
+    /// Parse the text of a label file; `file_name` selects the layout by its suffix
+    pub fn parse(text: &str, file_name: &str) -> Result<Self, String> {
+        let lower = file_name.to_lowercase();
+        let pairs: Vec<(String, String)> = if lower.ends_with(".json") {
+            let map: HashMap<String, String> =
+                serde_json::from_str(text).map_err(|e| format!("Invalid label JSON: {}", e))?;
+            map.into_iter().collect()
+        } else if lower.ends_with(".csv") {
+            let mut pairs: Vec<(String, String)> = text
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(split_csv_row)
+                .collect();
+            if pairs.first().is_some_and(|(_, label)| label.eq_ignore_ascii_case("label")) {
+                pairs.remove(0);
+            }
+            pairs
+        } else {
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+                .filter_map(|line| {
+                    let split = line.find(['=', '\t', ':'])?;
+                    Some((line[..split].to_string(), line[split + 1..].trim().to_string()))
+                })
+                .collect()
+        };
+
+        let entries: HashMap<String, String> = pairs
+            .into_iter()
+            .filter(|(key, label)| !key.trim().is_empty() && !label.is_empty())
+            .map(|(key, label)| (normalize_key(&key), label))
+            .collect();
+        if entries.is_empty() {
+            return Err("No labels found".to_string());
+        }
+        Ok(Self {
+            path: PathBuf::from(file_name),
+            entries,
+        })
+    }
+
+    /// The first companion file next to `bank_path`, trying `<stem><suffix>` and then
+    /// `<file name><suffix>` for every suffix in `LABEL_SUFFIXES`
+    pub fn find_for(bank_path: &Path) -> Option<PathBuf> {
+        let dir = bank_path.parent()?;
+        let stem = bank_path.file_stem()?.to_string_lossy().to_string();
+        let file_name = bank_path.file_name()?.to_string_lossy().to_string();
+        LABEL_SUFFIXES
+            .iter()
+            .flat_map(|suffix| [format!("{}{}", stem, suffix), format!("{}{}", file_name, suffix)])
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Labels for a bank, `Ok(None)` when it has no companion file
+    pub fn load_for(bank_path: &Path) -> Result<Option<Self>, String> {
+        let Some(path) = Self::find_for(bank_path) else {
+            return Ok(None);
+        };
+        let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        let mut labels = Self::parse(&text, &path.to_string_lossy())
+            .map_err(|e| format!("{:?}: {}", path.file_name().unwrap_or_default(), e))?;
+        labels.path = path;
+        Ok(Some(labels))
+    }
+
+    /// Label of a track, looked up by name, then hex ID, then numeric ID
+    pub fn label_for(&self, name: &str, id: &str, hex_id: Option<&str>) -> Option<&str> {
+        [Some(name), hex_id, Some(id)]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.entries.get(&normalize_key(key)))
+            .map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_layout_and_matches_by_name_or_id() {
+        let text = "# cue sheet\nvc_amuro_01 = Amuro: \"Get out!\"\n0x001A\tBoss intro\n7: Title\n";
+        let labels = LabelFile::parse(text, "bank.tonelabel").unwrap();
+        assert_eq!(labels.len(), 3);
+        assert_eq!(labels.label_for("VC_Amuro_01", "0", Some("0x0")), Some("Amuro: \"Get out!\""));
+        assert_eq!(labels.label_for("bgm", "26", Some("0x1a")), Some("Boss intro"));
+        assert_eq!(labels.label_for("bgm", "7", None), Some("Title"));
+        assert_eq!(labels.label_for("missing", "8", Some("0x8")), None);
+
+        let csv = "id,label\n0x2,\"Line, with comma\"\nse_hit,Hit\n";
+        let labels = LabelFile::parse(csv, "bank.labels.csv").unwrap();
+        assert_eq!(labels.label_for("x", "2", Some("0x2")), Some("Line, with comma"));
+        assert_eq!(labels.len(), 2);
+
+        let labels = LabelFile::parse(r#"{ "se_hit": "Hit" }"#, "bank.labels.json").unwrap();
+        assert_eq!(labels.label_for("SE_HIT", "0", None), Some("Hit"));
+
+        assert!(LabelFile::parse("# only comments\n", "bank.tonelabel").is_err());
+        assert!(LabelFile::parse("[1, 2]", "bank.labels.json").is_err());
+    }
+}
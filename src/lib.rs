@@ -1,9 +1,19 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+pub mod audio_processing;
+pub mod csv;
+pub mod file_names;
+pub mod labels;
 mod ui;
 mod version_check;
 pub mod nus3bank;
+mod session;
+pub mod tools;
+pub mod track_key;
+pub mod wav;
 
 pub use app::TemplateApp;
+pub use ui::main_area::manifest_import::{apply_manifest_file, dry_run_manifest_file};
+pub use session::APP_NAME;
 pub use version_check::{check_for_updates_async, get_version_check_result, VersionCheckResult};
@@ -13,58 +13,41 @@ fn main() -> eframe::Result {
     //   exvs2_audio_editor --debug-convert-all-to-wav <input.nus3bank> [output.nus3bank]
     //   exvs2_audio_editor --debug-convert-all-to-wav <input.nus3bank> --output <output.nus3bank>
     // If output is omitted, the input file is overwritten.
+    // Add `--dry-run` to run the full write pipeline in memory, validate the
+    // result and print a report without touching the output file.
+    //
+    // Round-trip check: save into memory, re-parse and compare every track.
+    // Usage:
+    //   exvs2_audio_editor verify <input.nus3bank>
+    // Exits with 1 when anything differs.
+    //
+    // Regression scripts: run open/replace/add/remove/save/validate/expect steps from a
+    // JSON Lines file headlessly, one `{"op": ...}` object per line.
+    // Usage:
+    //   exvs2_audio_editor --run-script <test.jsonl>
+    // Exits with 1 at the first failing step.
+    //
+    // Batch replacement: replace the tracks listed in a CSV/JSON manifest (track name or
+    // hex ID, replacement WAV, loop start/end in seconds, gain) and save.
+    // Usage:
+    //   exvs2_audio_editor apply-manifest <input.nus3bank> <manifest.json|csv> [--output <output.nus3bank>]
+    // If output is omitted, the input file is overwritten. Nothing is written and the exit
+    // code is 1 when any row fails.
+    //
+    // Recovery: start with default settings, ignoring the saved state (offered automatically
+    // after a crash). The saved state can be restored from the notice shown at startup.
+    // Usage:
+    //   exvs2_audio_editor --safe-mode
+    let safe_mode = std::env::args().any(|a| a == "--safe-mode");
     {
-        #[cfg(windows)]
-        use std::os::windows::process::CommandExt;
-        use std::path::{Path, PathBuf};
-        use std::process::Command;
-
-        fn is_standard_pcm16_wav(data: &[u8]) -> bool {
-            if data.len() < 12 {
-                return false;
-            }
-            if &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
-                return false;
-            }
-
-            let mut p = 12usize;
-            while p + 8 <= data.len() {
-                let cid = &data[p..p + 4];
-                p += 4;
-                let clen =
-                    u32::from_le_bytes([data[p], data[p + 1], data[p + 2], data[p + 3]]) as usize;
-                p += 4;
-
-                if p + clen > data.len() {
-                    return false;
-                }
-
-                if cid == b"fmt " {
-                    if clen != 16 {
-                        return false;
-                    }
-                    let w_format_tag = u16::from_le_bytes([data[p], data[p + 1]]);
-                    let bits_per_sample = u16::from_le_bytes([data[p + 14], data[p + 15]]);
-                    return w_format_tag == 1 && bits_per_sample == 16;
-                }
-
-                p += clen;
-                if clen % 2 != 0 {
-                    p += 1;
-                }
-            }
-
-            false
-        }
+        use exvs2_audio_editor::tools::VgmstreamRunner;
+        use std::path::PathBuf;
 
         fn convert_audio_bytes_to_pcm_wav(data: &[u8]) -> Result<Vec<u8>, String> {
             // Convert an embedded audio payload into a standard PCM WAV using vgmstream-cli.
             // This is used to normalize legacy WAV payloads that the game cannot decode
             // (e.g. WAVEFORMATEXTENSIBLE with a custom SubFormat GUID).
-            let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
-            if !vgmstream_path.exists() {
-                return Err(format!("vgmstream-cli not found at {:?}", vgmstream_path));
-            }
+            let vgmstream = VgmstreamRunner::locate()?;
 
             let temp_dir = std::env::temp_dir();
             let input_path = temp_dir.join("nus3bank_cli_in.wav");
@@ -73,30 +56,19 @@ fn main() -> eframe::Result {
             std::fs::write(&input_path, data)
                 .map_err(|e| format!("Failed to write temp input audio: {}", e))?;
 
-            let mut command = Command::new(&vgmstream_path);
-            #[cfg(windows)]
-            {
-                use winapi::um::winbase::CREATE_NO_WINDOW;
-                command.creation_flags(CREATE_NO_WINDOW);
-            }
-
             // -i: ignore any looping information (decode once)
             // -o: output WAV path
-            let result = command
-                .args([
-                    "-i",
-                    "-o",
-                    &output_path.to_string_lossy(),
-                    &input_path.to_string_lossy(),
-                ])
-                .output()
-                .map_err(|e| format!("Failed to run vgmstream-cli: {}", e))?;
-
-            if !result.status.success() {
-                let stderr = String::from_utf8_lossy(&result.stderr);
+            let result = vgmstream.run(&[
+                "-i",
+                "-o",
+                &output_path.to_string_lossy(),
+                &input_path.to_string_lossy(),
+            ]);
+
+            if let Err(e) = result {
                 let _ = std::fs::remove_file(&input_path);
                 let _ = std::fs::remove_file(&output_path);
-                return Err(format!("vgmstream-cli error: {}", stderr));
+                return Err(e);
             }
 
             let wav_data = std::fs::read(&output_path)
@@ -108,7 +80,9 @@ fn main() -> eframe::Result {
             Ok(wav_data)
         }
 
-        let argv: Vec<String> = std::env::args().skip(1).collect();
+        let mut argv: Vec<String> = std::env::args().skip(1).collect();
+        let dry_run = argv.iter().any(|a| a == "--dry-run");
+        argv.retain(|a| a != "--dry-run");
         let mut i = 0usize;
         while i < argv.len() {
             let a = &argv[i];
@@ -141,6 +115,106 @@ fn main() -> eframe::Result {
                 return Ok(());
             }
 
+            if a == "verify" {
+                let input = argv.get(i + 1).cloned().expect("Missing input path for verify");
+                let file =
+                    match exvs2_audio_editor::nus3bank::structures::Nus3bankFile::open(&input) {
+                        Ok(f) => f,
+                        Err(e) => {
+                            eprintln!("Error loading NUS3BANK file: {e:?}");
+                            std::process::exit(1);
+                        }
+                    };
+                let report = match file.verify() {
+                    Ok(r) => r,
+                    Err(e) => {
+                        eprintln!("Error serializing NUS3BANK file: {e:?}");
+                        std::process::exit(1);
+                    }
+                };
+                println!("{}", report.describe());
+                if !report.is_ok() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            if a == "--run-script" {
+                let script = argv.get(i + 1).cloned().expect("Missing script path for --run-script");
+                let report = match exvs2_audio_editor::nus3bank::script::run_script_file(&script) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        eprintln!("Error loading script: {e}");
+                        std::process::exit(1);
+                    }
+                };
+                println!("{}", report.describe());
+                if !report.is_ok() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            if a == "apply-manifest" {
+                let input = argv.get(i + 1).cloned().expect("Missing input path for apply-manifest");
+                let manifest = argv.get(i + 2).cloned().expect("Missing manifest path for apply-manifest");
+                // Support:
+                // - positional output: apply-manifest in manifest out
+                // - named output:      apply-manifest in manifest --output out
+                // Without either, the input bank is overwritten
+                let output = match argv.get(i + 3) {
+                    Some(flag) if flag == "--output" => {
+                        argv.get(i + 4).cloned().expect("Missing output path for --output")
+                    }
+                    Some(next) if !next.starts_with("--") => next.clone(),
+                    Some(other) => {
+                        eprintln!("Unknown option for apply-manifest: {other}");
+                        eprintln!("Usage: apply-manifest <bank> <manifest> [<output> | --output <output>] [--dry-run]");
+                        std::process::exit(2);
+                    }
+                    None => input.clone(),
+                };
+                if dry_run {
+                    let (report, dry_run) = match exvs2_audio_editor::dry_run_manifest_file(
+                        &PathBuf::from(&input),
+                        &PathBuf::from(&manifest),
+                        &PathBuf::from(&output),
+                    ) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            eprintln!("Error applying manifest: {e}");
+                            std::process::exit(1);
+                        }
+                    };
+                    println!("{}", report.describe());
+                    if let Some(dry_run) = &dry_run {
+                        println!("{}", dry_run.describe());
+                    }
+                    if !dry_run.is_some_and(|r| r.is_valid()) {
+                        std::process::exit(1);
+                    }
+                    return Ok(());
+                }
+                let report = match exvs2_audio_editor::apply_manifest_file(
+                    &PathBuf::from(&input),
+                    &PathBuf::from(&manifest),
+                    &PathBuf::from(&output),
+                ) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        eprintln!("Error applying manifest: {e}");
+                        std::process::exit(1);
+                    }
+                };
+                println!("{}", report.describe());
+                if !report.is_ok() {
+                    eprintln!("Nothing was written.");
+                    std::process::exit(1);
+                }
+                println!("Saved {output}");
+                return Ok(());
+            }
+
             if a == "--debug-convert-all-to-wav" || a == "--debug-convert-all-wav" {
                 let input = argv
                     .get(i + 1)
@@ -168,6 +242,10 @@ fn main() -> eframe::Result {
                             std::process::exit(1);
                         }
                     };
+                if let Err(e) = file.load_payloads() {
+                    eprintln!("Error reading NUS3BANK payloads: {e:?}");
+                    std::process::exit(1);
+                }
 
                 let mut converted = 0usize;
                 let mut skipped = 0usize;
@@ -180,7 +258,7 @@ fn main() -> eframe::Result {
 
                     let hex_id = format!("0x{:x}", idx as u32);
                     let source = tone.payload.clone();
-                    if is_standard_pcm16_wav(&source) {
+                    if exvs2_audio_editor::audio_processing::smpl::is_standard_pcm16_wav(&source) {
                         skipped += 1;
                         continue;
                     }
@@ -197,6 +275,25 @@ fn main() -> eframe::Result {
                     }
                 }
 
+                if dry_run {
+                    let report = match file.dry_run(&output.to_string_lossy()) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            eprintln!("Error serializing NUS3BANK file: {e:?}");
+                            std::process::exit(1);
+                        }
+                    };
+                    println!(
+                        "Debug convert dry run: converted={}, skipped={}, failed={}",
+                        converted, skipped, failed
+                    );
+                    println!("{}", report.describe());
+                    if !report.is_valid() {
+                        std::process::exit(1);
+                    }
+                    return Ok(());
+                }
+
                 if let Err(e) = file.save(&output) {
                     eprintln!("Error saving NUS3BANK file: {e:?}");
                     std::process::exit(1);
@@ -218,18 +315,26 @@ fn main() -> eframe::Result {
 
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
+    // Size and placement on first launch. Afterwards eframe restores the saved window
+    // size, position and maximized state from its storage, clamped to the connected
+    // monitors so a window last shown on a disconnected screen comes back on-screen.
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default().with_icon(
-            // NOTE: Adding an icon is optional
-            eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
-                .expect("Failed to load icon"),
-        ),
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size([1280.0, 800.0])
+            .with_min_inner_size([800.0, 500.0])
+            .with_icon(
+                // NOTE: Adding an icon is optional
+                eframe::icon_data::from_png_bytes(&include_bytes!("../assets/icon-256.png")[..])
+                    .expect("Failed to load icon"),
+            ),
+        // Safe mode also ignores the saved window geometry
+        persist_window: !safe_mode,
         ..Default::default()
     };
     eframe::run_native(
-        "EXVS2 Audio Editor",
+        exvs2_audio_editor::APP_NAME,
         native_options,
-        Box::new(|cc| Ok(Box::new(exvs2_audio_editor::TemplateApp::new(cc)))),
+        Box::new(move |cc| Ok(Box::new(exvs2_audio_editor::TemplateApp::new_with_safe_mode(cc, safe_mode)))),
     )
 }
 
@@ -1,7 +1,7 @@
 use base64::Engine as _;
 use serde_json::{json, Value};
 
-use super::structures::{AudioTrack, Nus3bankFile, RawSection, TocEntry, ToneMeta};
+use super::structures::{AudioTrack, Nus3bankFile, RawSection, TableEntry, TocEntry, ToneMeta};
 
 /// Options to control debug JSON output.
 #[derive(Clone, Debug)]
@@ -65,6 +65,15 @@ fn raw_section_json(s: &RawSection, opt: &DebugJsonOptions) -> Value {
     v
 }
 
+fn table_entry_json(e: &TableEntry, opt: &DebugJsonOptions) -> Value {
+    json!({
+        "offset": e.offset,
+        "size": e.size,
+        "name": e.name,
+        "data": bytes_preview_base64(&e.data, opt.max_preview_bytes),
+    })
+}
+
 fn audio_track_json(t: &AudioTrack) -> Value {
     json!({
         "index": t.index,
@@ -76,7 +85,6 @@ fn audio_track_json(t: &AudioTrack) -> Value {
         "metadata_size": t.metadata_size,
         "tone_index": t.tone_index,
         "audio_format": format!("{:?}", t.audio_format),
-        "audio_data_len": t.audio_data.as_ref().map(|d| d.len()).unwrap_or(0),
     })
 }
 
@@ -138,7 +146,7 @@ impl Nus3bankFile {
             })
         });
 
-        let grp = self.grp.as_ref().map(|g| json!({ "names": g.names }));
+        let grp = self.grp.as_ref().map(|g| json!({ "names": g.names, "raw_len": g.raw.len() }));
 
         let dton = self.dton.as_ref().map(|d| {
             json!({
@@ -148,7 +156,22 @@ impl Nus3bankFile {
                     "name": td.name,
                     "data_len": td.data.len(),
                     "data": td.data,
-                })).collect::<Vec<_>>()
+                })).collect::<Vec<_>>(),
+                "raw_len": d.raw.len(),
+            })
+        });
+
+        let mark = self.mark.as_ref().map(|m| {
+            json!({
+                "entries": m.entries.iter().map(|e| table_entry_json(e, opt)).collect::<Vec<_>>(),
+                "raw_len": m.raw.len(),
+            })
+        });
+
+        let busc = self.busc.as_ref().map(|b| {
+            json!({
+                "entries": b.entries.iter().map(|e| table_entry_json(e, opt)).collect::<Vec<_>>(),
+                "raw_len": b.raw.len(),
             })
         });
 
@@ -159,6 +182,8 @@ impl Nus3bankFile {
 
         let mut pack = json!({
             "data_len": self.pack.data.len(),
+            "size": self.pack.size,
+            "on_disk": self.pack.on_disk,
         });
         if opt.include_pack_preview {
             pack["data_preview"] = bytes_preview_base64(&self.pack.data, opt.max_preview_bytes);
@@ -186,6 +211,8 @@ impl Nus3bankFile {
                 "binf": binf,
                 "grp": grp,
                 "dton": dton,
+                "mark": mark,
+                "busc": busc,
                 "tone": tone,
                 "junk": junk,
                 "pack": pack,
@@ -0,0 +1,120 @@
+//! Dry-run saves of NUS3BANK files
+//!
+//! Runs the save pipeline into memory and reports what would be written, without touching
+//! the destination.
+
+use std::path::Path;
+
+use super::error::Nus3bankError;
+use super::parser::Nus3bankParser;
+use super::structures::Nus3bankFile;
+
+/// Result of running the save pipeline into memory without writing the destination.
+#[derive(Clone, Debug)]
+pub struct DryRunReport {
+    /// Where the real save would write
+    pub destination: String,
+    /// Whether the destination already exists (and would be overwritten)
+    pub destination_exists: bool,
+    /// Size of the destination on disk, if it exists
+    pub destination_size: Option<u64>,
+    /// Size of the serialized output
+    pub output_size: usize,
+    /// Number of tracks found when re-parsing the serialized output
+    pub track_count: usize,
+    /// Validation problems found in the serialized output (empty = valid)
+    pub problems: Vec<String>,
+}
+
+impl DryRunReport {
+    /// Build a report for an already serialized output buffer.
+    pub fn new(destination: &str, output_size: usize, track_count: usize, problems: Vec<String>) -> Self {
+        let metadata = std::fs::metadata(Path::new(destination)).ok();
+        Self {
+            destination: destination.to_string(),
+            destination_exists: metadata.is_some(),
+            destination_size: metadata.map(|m| m.len()),
+            output_size,
+            track_count,
+            problems,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// Human readable multi-line description used by both the GUI and the CLI.
+    pub fn describe(&self) -> String {
+        let mut lines = Vec::new();
+        if self.destination_exists {
+            lines.push(format!(
+                "Would overwrite: {} ({} bytes)",
+                self.destination,
+                self.destination_size.unwrap_or(0)
+            ));
+        } else {
+            lines.push(format!("Would create: {}", self.destination));
+        }
+        lines.push(format!("Output size: {} bytes", self.output_size));
+        lines.push(format!("Tracks after save: {}", self.track_count));
+        if self.problems.is_empty() {
+            lines.push("Validation: OK".to_string());
+        } else {
+            lines.push(format!("Validation: {} problem(s)", self.problems.len()));
+            for problem in &self.problems {
+                lines.push(format!("  - {}", problem));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Nus3bankFile {
+    /// Serialize into memory, re-parse the result and compare it against this file.
+    /// Nothing is written to `destination`; it is only used for the report.
+    pub fn dry_run(&self, destination: &str) -> Result<DryRunReport, Nus3bankError> {
+        let file = self.with_payloads()?;
+        let bytes = file.to_bytes()?;
+        let mut problems = Vec::new();
+
+        let expected_tracks = file.tone.tones.iter().filter(|t| !t.removed).count();
+        let track_count = match Nus3bankParser::parse_bytes(&bytes, destination.to_string()) {
+            Ok(reparsed) => {
+                if reparsed.tracks.len() != expected_tracks {
+                    problems.push(format!(
+                        "Track count mismatch: expected {}, parsed {}",
+                        expected_tracks,
+                        reparsed.tracks.len()
+                    ));
+                }
+
+                let expected = file.tone.tones.iter().filter(|t| !t.removed);
+                for (tone, track) in expected.zip(reparsed.tracks.iter()) {
+                    if tone.name != track.name {
+                        problems.push(format!(
+                            "Track {} name mismatch: expected '{}', parsed '{}'",
+                            track.hex_id, tone.name, track.name
+                        ));
+                    }
+                    if tone.payload.len() != track.size as usize {
+                        problems.push(format!(
+                            "Track {} size mismatch: expected {}, parsed {}",
+                            track.hex_id,
+                            tone.payload.len(),
+                            track.size
+                        ));
+                    }
+                }
+
+                reparsed.tracks.len()
+            }
+            Err(e) => {
+                problems.push(format!("Output does not re-parse: {}", e));
+                0
+            }
+        };
+
+        Ok(DryRunReport::new(destination, bytes.len(), track_count, problems))
+    }
+}
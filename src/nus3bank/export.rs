@@ -1,4 +1,5 @@
 use super::structures::Nus3bankFile;
+use crate::file_names;
 use std::fs;
 
 /// NUS3BANK export utilities
@@ -17,14 +18,13 @@ impl Nus3bankExporter {
         let track = nus3bank_file.get_track_by_hex_id(hex_id)
             .ok_or_else(|| format!("Track with hex ID {} not found", hex_id))?;
         
-        let output_path = format!("{}/{}", output_dir, track.filename());
+        let output_path = format!("{}/{}", output_dir, file_names::safe_file_name(&track.filename()));
         
-        if let Some(audio_data) = &track.audio_data {
-            fs::write(&output_path, audio_data)
-                .map_err(|e| format!("Failed to write audio file: {}", e))?;
-        } else {
-            return Err(format!("Audio data not loaded for track '{}' ({}). The track may be corrupted or the file may not have been parsed correctly.", track.name, track.hex_id));
-        }
+        let audio_data = nus3bank_file
+            .read_track_payload(track.index)
+            .map_err(|e| format!("Failed to read audio data of track '{}' ({}): {}", track.name, track.hex_id, e))?;
+        fs::write(&output_path, audio_data)
+            .map_err(|e| format!("Failed to write audio file: {}", e))?;
         
         Ok(output_path)
     }
@@ -60,7 +60,8 @@ impl Nus3bankExporter {
         let track = nus3bank_file.get_track_by_hex_id(hex_id)
             .ok_or_else(|| format!("Track with hex ID {} not found", hex_id))?;
         
-        track.audio_data.clone()
-            .ok_or_else(|| format!("Audio data not loaded for track '{}' ({}). The track may be corrupted or the file may not have been parsed correctly.", track.name, track.hex_id))
+        nus3bank_file
+            .read_track_payload(track.index)
+            .map_err(|e| format!("Failed to read audio data of track '{}' ({}): {}", track.name, track.hex_id, e))
     }
 }
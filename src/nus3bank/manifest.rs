@@ -0,0 +1,158 @@
+//! Replacement manifests for NUS3BANK and NUS3AUDIO tracks
+//!
+//! Reads the CSV or JSON manifests that batch replacement applies, one track per row.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// One row of a replacement manifest: the track, the file replacing it, and its loop and
+/// gain. The source manifest the editor exports reads as one too; its `add` and `silence`
+/// rows are skipped.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct ManifestRow {
+    /// `replace` when missing
+    #[serde(default)]
+    pub action: Option<String>,
+    /// Track name, or hex ID when it starts with "0x" in either case
+    #[serde(default)]
+    pub track: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub hex_id: Option<String>,
+    #[serde(default, alias = "wav", alias = "path")]
+    pub source: Option<PathBuf>,
+    /// Whether to loop; rows that give a loop start loop when it is left out
+    #[serde(default, alias = "loop")]
+    pub loop_enabled: Option<bool>,
+    /// Loop start and end in seconds; without them the whole file loops
+    #[serde(default)]
+    pub loop_start: Option<f32>,
+    #[serde(default)]
+    pub loop_end: Option<f32>,
+    #[serde(default, alias = "gain")]
+    pub gain_db: f32,
+}
+
+impl ManifestRow {
+    pub fn is_replace(&self) -> bool {
+        self.action.as_deref().is_none_or(|a| a.trim().is_empty() || a.eq_ignore_ascii_case("replace"))
+    }
+
+    pub fn looping(&self) -> bool {
+        self.loop_enabled.unwrap_or(self.loop_start.is_some())
+    }
+
+    /// How the row names its track, for messages
+    pub fn target(&self) -> String {
+        self.hex_id
+            .clone()
+            .or_else(|| self.track.clone())
+            .or_else(|| self.name.clone())
+            .unwrap_or_else(|| "(no track)".to_string())
+    }
+
+    /// Whether the row is about the track `name` with `id` and, in a NUS3BANK, `hex_id`
+    pub fn matches(&self, name: &str, id: &str, hex_id: Option<&str>) -> bool {
+        let by_hex = |wanted: &str| hex_id.is_some_and(|h| h.eq_ignore_ascii_case(wanted));
+        if let Some(wanted) = &self.hex_id {
+            return by_hex(wanted);
+        }
+        if let Some(track) = &self.track {
+            let is_hex = track.get(..2).is_some_and(|prefix| prefix.eq_ignore_ascii_case("0x"));
+            return if is_hex { by_hex(track) } else { track == name };
+        }
+        self.name.as_deref() == Some(name) && self.id.as_deref().is_none_or(|wanted| wanted.is_empty() || wanted == id)
+    }
+}
+
+/// Rows read from a manifest file
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplacementManifest {
+    pub rows: Vec<ManifestRow>,
+}
+
+impl ReplacementManifest {
+    /// Read a CSV manifest when `path` ends in `.csv`, JSON otherwise
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv")) {
+            Self::parse_csv(&text)
+        } else {
+            Self::parse_json(&text)
+        }
+    }
+
+    /// A JSON array of rows, or an object listing them under `entries`
+    pub fn parse_json(text: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(text).map_err(|e| format!("Manifest is not valid JSON: {}", e))?;
+        let rows = match value {
+            serde_json::Value::Object(mut object) => object.remove("entries").ok_or("Manifest has no \"entries\" list")?,
+            rows => rows,
+        };
+        let rows = serde_json::from_value(rows).map_err(|e| format!("Manifest rows: {}", e))?;
+        Ok(Self { rows })
+    }
+
+    /// CSV with a header row naming the columns; unknown columns are ignored
+    pub fn parse_csv(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or("Manifest is empty")?;
+        let header: Vec<String> = crate::csv::split_row(header).iter().map(|h| h.trim().to_ascii_lowercase()).collect();
+
+        let mut rows = Vec::new();
+        for (n, line) in lines {
+            let fields = crate::csv::split_row(line);
+            let mut row = ManifestRow::default();
+            for (column, field) in header.iter().zip(fields) {
+                let field = field.trim();
+                if field.is_empty() {
+                    continue;
+                }
+                let number = |field: &str| {
+                    field.parse::<f32>().map_err(|_| format!("Line {}: {} is not a number: {}", n + 1, column, field))
+                };
+                match column.as_str() {
+                    "action" => row.action = Some(field.to_string()),
+                    "track" => row.track = Some(field.to_string()),
+                    "name" => row.name = Some(field.to_string()),
+                    "id" => row.id = Some(field.to_string()),
+                    "hex_id" => row.hex_id = Some(field.to_string()),
+                    "source" | "wav" | "path" => row.source = Some(PathBuf::from(field)),
+                    "loop" | "loop_enabled" => row.loop_enabled = Some(matches!(field.to_ascii_lowercase().as_str(), "true" | "yes" | "1")),
+                    "loop_start" => row.loop_start = Some(number(field)?),
+                    "loop_end" => row.loop_end = Some(number(field)?),
+                    "gain_db" | "gain" => row.gain_db = number(field)?,
+                    _ => {}
+                }
+            }
+            rows.push(row);
+        }
+        Ok(Self { rows })
+    }
+}
+
+/// Outcome of applying a manifest: rows applied, rows skipped, and one line per failure
+#[derive(Clone, Debug, Default)]
+pub struct ManifestReport {
+    pub applied: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+impl ManifestReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Human readable multi-line description used by both the GUI and the CLI
+    pub fn describe(&self) -> String {
+        let mut lines = vec![format!("Manifest: {} replaced, {} skipped", self.applied, self.skipped)];
+        lines.extend(self.errors.iter().map(|e| format!("  - {}", e)));
+        lines.join("\n")
+    }
+}
@@ -11,11 +11,19 @@ pub mod error;
 pub mod export;
 pub mod replace;
 pub mod debug_json;
+pub mod dry_run;
+pub mod verify;
+pub mod script;
+pub mod manifest;
 
 // Re-export main types
 pub use structures::Nus3bankFile;
 
 pub use export::Nus3bankExporter;
+pub use dry_run::DryRunReport;
+pub use verify::VerifyReport;
+pub use script::ScriptReport;
+pub use manifest::ManifestReport;
 
 
 #[cfg(test)]
@@ -1,23 +1,67 @@
 use std::fs::File;
-use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
 
 use super::{
     binary_utils::BinaryReader,
     error::Nus3bankError,
     structures::{
-        BinfSection, DtonSection, GrpSection, JunkSection, Nus3bankFile, PackSection, PropSection,
-        RawSection, TocEntry, ToneDes, ToneMeta, ToneSection, UnkvaluesPairOrder,
+        BinfSection, BuscSection, DtonSection, GrpSection, JunkSection, MarkSection, Nus3bankFile,
+        PackSection, PropSection, RawSection, TableEntry, TocEntry, ToneDes, ToneMeta, ToneSection,
+        UnkvaluesPairOrder,
     },
 };
 
+/// Largest single read while parsing from a file, so progress keeps moving through a big PACK
+const PROGRESS_CHUNK: usize = 4 * 1024 * 1024;
+
+/// Reader that reports its position and the file size after every read
+struct ProgressReader<'a, R> {
+    inner: R,
+    position: u64,
+    total: u64,
+    progress: &'a mut dyn FnMut(u64, u64),
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(PROGRESS_CHUNK);
+        let n = self.inner.read(&mut buf[..len])?;
+        self.position += n as u64;
+        (self.progress)(self.position, self.total);
+        Ok(n)
+    }
+}
+
+/// Seeks to an absolute position are reported too, so skipped sections count as read. Seeks
+/// from the end only measure the file.
+impl<R: Seek> Seek for ProgressReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        if let SeekFrom::Start(_) = pos {
+            (self.progress)(self.position, self.total);
+        }
+        Ok(self.position)
+    }
+}
+
 /// NUS3BANK parser (BANKTOC-only), ported from `NUS3BANK.cs`.
 pub struct Nus3bankParser;
 
 impl Nus3bankParser {
+    /// Parse a file, leaving the PACK payload on disk; see `Nus3bankFile::read_track_payload`.
     pub fn parse_file<P: AsRef<std::path::Path>>(path: P) -> Result<Nus3bankFile, Nus3bankError> {
+        Self::parse_file_with_progress(path, &mut |_, _| {})
+    }
+
+    /// Parse a file, calling `progress` with the bytes read so far and the file size. The
+    /// PACK payload is skipped rather than read.
+    pub fn parse_file_with_progress<P: AsRef<std::path::Path>>(
+        path: P,
+        progress: &mut dyn FnMut(u64, u64),
+    ) -> Result<Nus3bankFile, Nus3bankError> {
         let file_path = path.as_ref().to_string_lossy().to_string();
         let file = File::open(&path)?;
-        let mut reader = BufReader::new(file);
+        let reader = BufReader::new(file);
 
         let size = reader.get_ref().metadata().map(|m| m.len()).unwrap_or(0);
         if size < 0x20 {
@@ -26,13 +70,35 @@ impl Nus3bankParser {
             });
         }
 
-        Self::parse_banktoc_only(&mut reader, file_path)
+        let mut reader = ProgressReader {
+            inner: reader,
+            position: 0,
+            total: size,
+            progress,
+        };
+        Self::parse_banktoc_only(&mut reader, file_path, true)
+    }
+
+    /// Parse a NUS3BANK image that is already in memory (e.g. a freshly written buffer).
+    pub fn parse_bytes(data: &[u8], file_path: String) -> Result<Nus3bankFile, Nus3bankError> {
+        if data.len() < 0x20 {
+            return Err(Nus3bankError::InvalidFormat {
+                reason: format!("File too small: {} bytes", data.len()),
+            });
+        }
+
+        let mut reader = Cursor::new(data);
+        Self::parse_banktoc_only(&mut reader, file_path, false)
     }
 
+    /// With `pack_on_disk`, only the PACK header is read and tone payloads stay empty.
     fn parse_banktoc_only<R: Read + Seek>(
         reader: &mut R,
         file_path: String,
+        pack_on_disk: bool,
     ) -> Result<Nus3bankFile, Nus3bankError> {
+        let stream_len = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
         BinaryReader::assert_magic(reader, b"NUS3")?;
         let _total_size = BinaryReader::read_u32_le(reader)?;
 
@@ -72,6 +138,8 @@ impl Nus3bankParser {
         let mut binf: Option<BinfSection> = None;
         let mut grp: Option<GrpSection> = None;
         let mut dton: Option<DtonSection> = None;
+        let mut mark: Option<MarkSection> = None;
+        let mut busc: Option<BuscSection> = None;
         let mut tone: Option<ToneSection> = None;
         let mut junk: Option<JunkSection> = None;
         let mut pack: Option<PackSection> = None;
@@ -79,7 +147,27 @@ impl Nus3bankParser {
 
         // Read each section using TOC ordering and sizes, matching `headerSize += size + 8`.
         for entry in &toc {
+            // Checked before the section buffer is allocated, so a corrupt size fails fast
+            if header_pos + 8 + entry.size as u64 > stream_len {
+                return Err(Nus3bankError::InvalidFormat {
+                    reason: format!(
+                        "Section {:?} runs past the end of the file",
+                        String::from_utf8_lossy(&entry.magic)
+                    ),
+                });
+            }
             reader.seek(SeekFrom::Start(header_pos))?;
+            if pack_on_disk && &entry.magic == b"PACK" {
+                Self::read_section_header(reader, entry.magic, entry.size)?;
+                pack = Some(PackSection {
+                    data: Vec::new(),
+                    file_offset: header_pos + 8,
+                    size: entry.size as u64,
+                    on_disk: true,
+                });
+                header_pos += 8u64 + entry.size as u64;
+                continue;
+            }
             let section_bytes = Self::read_section_block(reader, entry.magic, entry.size)?;
 
             match &entry.magic[..] {
@@ -89,22 +177,27 @@ impl Nus3bankParser {
                 b"DTON" => dton = Some(Self::parse_dton(&section_bytes)?),
                 b"TONE" => tone = Some(Self::parse_tone(&section_bytes)?),
                 b"JUNK" => junk = Some(Self::parse_junk(&section_bytes)?),
-                b"PACK" => pack = Some(Self::parse_pack(&section_bytes)?),
-                _ => {
-                    // Preserve unknown section payload bytes.
-                    let mut cur = Cursor::new(section_bytes.as_slice());
-                    cur.seek(SeekFrom::Start(8))?;
-                    let data = BinaryReader::read_bytes(&mut cur, entry.size as usize)?;
-                    unknown_sections.push(RawSection {
-                        magic: entry.magic,
-                        size: entry.size,
-                        data,
-                    });
-                }
+                b"PACK" => pack = Some(Self::parse_pack(section_bytes)?),
+                // MARK/BUSC layouts are only partly known; if the pointer table does not
+                // parse, the section is kept as an unknown blob instead of failing the file.
+                b"MARK" | b"BUSC" => match Self::parse_table_entries(&section_bytes) {
+                    Ok(entries) => {
+                        let raw = section_bytes[8..].to_vec();
+                        if &entry.magic == b"MARK" {
+                            mark = Some(MarkSection { entries, raw });
+                        } else {
+                            busc = Some(BuscSection { entries, raw });
+                        }
+                    }
+                    Err(_) => unknown_sections.push(Self::raw_section(entry, &section_bytes)?),
+                },
+                _ => unknown_sections.push(Self::raw_section(entry, &section_bytes)?),
             }
 
             header_pos += 8u64 + entry.size as u64;
         }
+        // Lets a progress reader count a skipped PACK payload as read
+        reader.seek(SeekFrom::Start(header_pos))?;
 
         let mut tone = tone.ok_or_else(|| Nus3bankError::SectionValidation {
             section: "TONE section not found".to_string(),
@@ -123,6 +216,8 @@ impl Nus3bankParser {
             binf,
             grp,
             dton,
+            mark,
+            busc,
             tone,
             junk,
             pack,
@@ -134,12 +229,37 @@ impl Nus3bankParser {
         Ok(file)
     }
 
+    /// Preserve unknown section payload bytes.
+    fn raw_section(entry: &TocEntry, section_bytes: &[u8]) -> Result<RawSection, Nus3bankError> {
+        let mut cur = Cursor::new(section_bytes);
+        cur.seek(SeekFrom::Start(8))?;
+        let data = BinaryReader::read_bytes(&mut cur, entry.size as usize)?;
+        Ok(RawSection {
+            magic: entry.magic,
+            size: entry.size,
+            data,
+        })
+    }
+
     fn read_section_block<R: Read>(
         reader: &mut R,
         expected_magic: [u8; 4],
         expected_size: u32,
     ) -> Result<Vec<u8>, Nus3bankError> {
+        let header = Self::read_section_header(reader, expected_magic, expected_size)?;
         let mut buf = vec![0u8; 8 + expected_size as usize];
+        buf[..8].copy_from_slice(&header);
+        reader.read_exact(&mut buf[8..])?;
+        Ok(buf)
+    }
+
+    /// Read the 8-byte section header and check it against the TOC entry
+    fn read_section_header<R: Read>(
+        reader: &mut R,
+        expected_magic: [u8; 4],
+        expected_size: u32,
+    ) -> Result<[u8; 8], Nus3bankError> {
+        let mut buf = [0u8; 8];
         reader.read_exact(&mut buf)?;
 
         if buf[0..4] != expected_magic {
@@ -164,7 +284,7 @@ impl Nus3bankParser {
         Ok(buf)
     }
 
-    fn parse_prop(section: &[u8]) -> Result<PropSection, Nus3bankError> {
+    pub(crate) fn parse_prop(section: &[u8]) -> Result<PropSection, Nus3bankError> {
         let mut r = Cursor::new(section);
         BinaryReader::assert_magic(&mut r, b"PROP")?;
         let _section_size = BinaryReader::read_u32_le(&mut r)?;
@@ -218,6 +338,7 @@ impl Nus3bankParser {
             unk2,
             unk3,
             layout,
+            raw: section[8..].to_vec(),
         })
     }
 
@@ -249,7 +370,7 @@ impl Nus3bankParser {
         })
     }
 
-    fn parse_grp(section: &[u8]) -> Result<GrpSection, Nus3bankError> {
+    pub(crate) fn parse_grp(section: &[u8]) -> Result<GrpSection, Nus3bankError> {
         let mut r = Cursor::new(section);
         BinaryReader::assert_magic(&mut r, b"GRP ")?;
         let _section_size = BinaryReader::read_u32_le(&mut r)?;
@@ -320,7 +441,10 @@ impl Nus3bankParser {
             names.push(name);
         }
 
-        Ok(GrpSection { names })
+        Ok(GrpSection {
+            names,
+            raw: section[8..].to_vec(),
+        })
     }
 
     pub(crate) fn parse_dton(section: &[u8]) -> Result<DtonSection, Nus3bankError> {
@@ -407,10 +531,13 @@ impl Nus3bankParser {
             });
         }
 
-        Ok(DtonSection { tones })
+        Ok(DtonSection {
+            tones,
+            raw: section[8..].to_vec(),
+        })
     }
 
-    fn parse_tone(section: &[u8]) -> Result<ToneSection, Nus3bankError> {
+    pub(crate) fn parse_tone(section: &[u8]) -> Result<ToneSection, Nus3bankError> {
         let mut r = Cursor::new(section);
         BinaryReader::assert_magic(&mut r, b"TONE")?;
         let _section_size = BinaryReader::read_u32_le(&mut r)?;
@@ -499,7 +626,10 @@ impl Nus3bankParser {
             tones.push(meta);
         }
 
-        Ok(ToneSection { tones })
+        Ok(ToneSection {
+            tones,
+            raw: section[8..].to_vec(),
+        })
     }
 
     fn parse_tone_meta_block(meta: &[u8], tone_idx: usize) -> Result<ToneMeta, Nus3bankError> {
@@ -736,6 +866,57 @@ impl Nus3bankParser {
         }
     }
 
+    /// Entries of a `count, (offset, size)[count]` pointer-table section such as MARK and BUSC.
+    /// Unlike GRP/DTON, every entry must lie inside the section, since nothing else about
+    /// the layout can be checked.
+    fn parse_table_entries(section: &[u8]) -> Result<Vec<TableEntry>, Nus3bankError> {
+        let mut r = Cursor::new(section);
+        let _magic = BinaryReader::read_bytes(&mut r, 4)?;
+        let _section_size = BinaryReader::read_u32_le(&mut r)?;
+
+        let count = BinaryReader::read_u32_le(&mut r)? as usize;
+        let start = r.position() as usize;
+        if count > (section.len() - start) / 8 {
+            return Err(Nus3bankError::InvalidFormat {
+                reason: format!("Pointer table count {} exceeds section size", count),
+            });
+        }
+
+        let mut entries = Vec::with_capacity(count);
+        for _ in 0..count {
+            let offset = BinaryReader::read_u32_le(&mut r)?;
+            let size = BinaryReader::read_u32_le(&mut r)?;
+            let entry_start = start + offset as usize;
+            let entry_end = entry_start + size as usize;
+            if entry_end > section.len() {
+                return Err(Nus3bankError::InvalidFormat {
+                    reason: "Pointer table entry out of bounds".to_string(),
+                });
+            }
+            let data = section[entry_start..entry_end].to_vec();
+            entries.push(TableEntry {
+                offset,
+                size,
+                name: Self::entry_name(&data),
+                data,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Name of an entry that starts with `hash: i32, unk1: i32, len: u8, name, 0`
+    fn entry_name(data: &[u8]) -> Option<String> {
+        let len_with_null = *data.get(8)? as usize;
+        if len_with_null < 2 {
+            return None;
+        }
+        let name = data.get(9..8 + len_with_null)?;
+        if data.get(8 + len_with_null) != Some(&0) || !name.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+            return None;
+        }
+        Some(String::from_utf8_lossy(name).to_string())
+    }
+
     fn parse_junk(section: &[u8]) -> Result<JunkSection, Nus3bankError> {
         let mut r = Cursor::new(section);
         BinaryReader::assert_magic(&mut r, b"JUNK")?;
@@ -746,17 +927,24 @@ impl Nus3bankParser {
         Ok(JunkSection { data })
     }
 
-    fn parse_pack(section: &[u8]) -> Result<PackSection, Nus3bankError> {
-        let mut r = Cursor::new(section);
+    /// Takes the section buffer so the PACK payload, usually most of the file, is not copied.
+    fn parse_pack(mut section: Vec<u8>) -> Result<PackSection, Nus3bankError> {
+        let mut r = Cursor::new(&section[..]);
         BinaryReader::assert_magic(&mut r, b"PACK")?;
         let size = BinaryReader::read_u32_le(&mut r)? as usize;
-        if size > 200_000_000 {
+        if section.len() < 8 + size {
             return Err(Nus3bankError::InvalidFormat {
-                reason: format!("PACK too large: {} bytes", size),
+                reason: format!("PACK shorter than its declared {} bytes", size),
             });
         }
-        let data = BinaryReader::read_bytes(&mut r, size)?;
-        Ok(PackSection { data })
+        section.truncate(8 + size);
+        section.drain(..8);
+        Ok(PackSection {
+            data: section,
+            file_offset: 0,
+            size: size as u64,
+            on_disk: false,
+        })
     }
 
     fn attach_pack_payloads(
@@ -770,12 +958,14 @@ impl Nus3bankParser {
             }
             let start = t.offset as usize;
             let end = start + t.size as usize;
-            if end > pack.data.len() {
+            if end as u64 > pack.size {
                 return Err(Nus3bankError::InvalidFormat {
                     reason: "TONE pack offset/size out of bounds".to_string(),
                 });
             }
-            t.payload = pack.data[start..end].to_vec();
+            if !pack.on_disk {
+                t.payload = pack.data[start..end].to_vec();
+            }
         }
         Ok(())
     }
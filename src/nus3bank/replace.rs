@@ -1,5 +1,6 @@
 use super::structures::Nus3bankFile;
 use super::error::Nus3bankError;
+use crate::track_key::TrackKey;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, Ordering};
@@ -11,11 +12,15 @@ pub static REPLACEMENT_DATA: Lazy<Mutex<HashMap<String, HashMap<String, ReplaceO
 
 static TEMP_ID_COUNTER: AtomicU32 = AtomicU32::new(0);
 
+// Pending TONE order of each file, by normalized file path; see `register_tone_order`
+static TONE_ORDERS: Lazy<Mutex<HashMap<String, Vec<usize>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
 #[derive(Clone)]
 pub enum ReplaceOperation {
     Remove(String), // hex_id
     Replace(String, Vec<u8>), // hex_id, new_data
     Add(String, String, Vec<u8>), // name, generated_hex_id, data
+    Duplicate(String, String, String), // source hex_id, name, generated_hex_id
 }
 
 /// NUS3BANK replace utilities
@@ -46,6 +51,11 @@ impl Nus3bankReplacer {
         let file_key = Self::normalize_file_key(file_path);
         if let Ok(mut data) = REPLACEMENT_DATA.lock() {
             let per_file = data.entry(file_key).or_insert_with(HashMap::new);
+            // A copy queued by `register_duplicate` is only dropped; there is nothing to remove
+            if let Some(ReplaceOperation::Duplicate(..)) = per_file.get(hex_id) {
+                per_file.remove(hex_id);
+                return Ok(());
+            }
             per_file.insert(hex_id.to_string(), ReplaceOperation::Remove(hex_id.to_string()));
             Ok(())
         } else {
@@ -86,6 +96,52 @@ impl Nus3bankReplacer {
         }
     }
     
+    /// Register a copy of the track `hex_id`, tone metadata included, named `name`. Returns
+    /// the temporary hex ID the copy is tracked by until it is saved.
+    pub fn register_duplicate(file_path: &str, hex_id: &str, name: &str) -> Result<String, String> {
+        if name.is_empty() {
+            return Err("Track name cannot be empty".to_string());
+        }
+
+        let n = TEMP_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let temp_hex_id = format!("0x{:x}", 0x8000_0000u32.wrapping_add(n));
+
+        let file_key = Self::normalize_file_key(file_path);
+        let mut data = REPLACEMENT_DATA.lock().map_err(|_| "Failed to register duplicate operation".to_string())?;
+        let per_file = data.entry(file_key).or_insert_with(HashMap::new);
+        per_file.insert(
+            temp_hex_id.clone(),
+            ReplaceOperation::Duplicate(hex_id.to_string(), name.to_string(), temp_hex_id.clone()),
+        );
+        println!("Registered duplicate of track {} as '{}' with temp_id {}", hex_id, name, temp_hex_id);
+        Ok(temp_hex_id)
+    }
+
+    /// Register a new order of the TONE entries, slot i taking the tone read at slot
+    /// `order[i]`; see `Nus3bankFile::reorder_tones`. An order that leaves every tone in
+    /// place drops the pending one.
+    pub fn register_tone_order(file_path: &str, order: Vec<usize>) -> Result<(), String> {
+        let file_key = Self::normalize_file_key(file_path);
+        let mut orders = TONE_ORDERS.lock().map_err(|_| "Failed to register tone order".to_string())?;
+        if order.iter().enumerate().all(|(slot, &from)| slot == from) {
+            orders.remove(&file_key);
+        } else {
+            orders.insert(file_key, order);
+        }
+        Ok(())
+    }
+
+    /// Pending TONE order of a file, if one was registered
+    pub fn get_tone_order(file_path: &str) -> Option<Vec<usize>> {
+        let file_key = Self::normalize_file_key(file_path);
+        TONE_ORDERS.lock().ok()?.get(&file_key).cloned()
+    }
+
+    /// Number of files with a pending TONE order
+    pub fn tone_order_count() -> usize {
+        TONE_ORDERS.lock().map(|orders| orders.len()).unwrap_or(0)
+    }
+
     /// Replace track in memory only (does not modify the actual file on disk)
     pub fn replace_track_in_memory(
         file_path: &str,
@@ -106,6 +162,19 @@ impl Nus3bankReplacer {
         }
     }
 
+    /// Replace the track identified by `key` in memory. Only keys of existing NUS3BANK
+    /// tracks are accepted; added tracks carry their data in their Add operation.
+    pub fn replace_track_by_key(
+        file_path: &str,
+        key: &TrackKey,
+        new_audio_data: Vec<u8>,
+    ) -> Result<(), String> {
+        match key {
+            TrackKey::Bank { hex_id, .. } => Self::replace_track_in_memory(file_path, hex_id, new_audio_data),
+            _ => Err(format!("{} is not an existing NUS3BANK track", key)),
+        }
+    }
+
     /// Check if there are any replacement data stored
     pub fn has_replacement_data() -> bool {
         if let Ok(data) = REPLACEMENT_DATA.lock() {
@@ -124,27 +193,53 @@ impl Nus3bankReplacer {
         }
     }
     
-    /// Apply all operations to a file
-    pub fn apply_to_file(file_path: &str, file: &mut Nus3bankFile) -> Result<(), Nus3bankError> {
+    /// Get a snapshot of the pending operations registered for a file
+    pub fn get_operations(file_path: &str) -> Vec<ReplaceOperation> {
         let file_key = Self::normalize_file_key(file_path);
-        let mut ops: Vec<ReplaceOperation> = if let Ok(data) = REPLACEMENT_DATA.lock() {
+        if let Ok(data) = REPLACEMENT_DATA.lock() {
             data.get(&file_key)
                 .map(|m| m.values().cloned().collect::<Vec<_>>())
                 .unwrap_or_default()
         } else {
             Vec::new()
-        };
+        }
+    }
+
+    /// Apply all operations to a file
+    pub fn apply_to_file(file_path: &str, file: &mut Nus3bankFile) -> Result<(), Nus3bankError> {
+        Self::apply_to_file_with(file_path, HashMap::new(), file)
+    }
+
+    /// Apply all operations to a file, with `replacements` (new data by hex ID) taking the
+    /// place of the stored ones. The stored operations are left as they are.
+    pub fn apply_to_file_with(
+        file_path: &str,
+        replacements: HashMap<String, Vec<u8>>,
+        file: &mut Nus3bankFile,
+    ) -> Result<(), Nus3bankError> {
+        let file_key = Self::normalize_file_key(file_path);
+        let mut per_file = REPLACEMENT_DATA
+            .lock()
+            .ok()
+            .and_then(|data| data.get(&file_key).cloned())
+            .unwrap_or_default();
+        for (hex_id, new_data) in replacements {
+            per_file.insert(hex_id.clone(), ReplaceOperation::Replace(hex_id, new_data));
+        }
+        let mut ops: Vec<ReplaceOperation> = per_file.into_values().collect();
 
         // Deterministic application order:
         // - Remove first (lowest risk of offset conflicts)
         // - Replace next (does not change entry count)
-        // - Add last (changes entry count and PACK layout)
+        // - Add and Duplicate next (change entry count and PACK layout); a duplicate copies
+        //   the replaced payload of its source
+        // The TONE order goes last, as the operations address tones by the slot they were read at
         ops.sort_by(|a, b| {
             fn prio(op: &ReplaceOperation) -> u8 {
                 match op {
                     ReplaceOperation::Remove(_) => 0,
                     ReplaceOperation::Replace(_, _) => 1,
-                    ReplaceOperation::Add(_, _, _) => 2,
+                    ReplaceOperation::Add(_, _, _) | ReplaceOperation::Duplicate(_, _, _) => 2,
                 }
             }
             let pa = prio(a);
@@ -160,10 +255,12 @@ impl Nus3bankReplacer {
                 (ReplaceOperation::Replace(ha, _), ReplaceOperation::Replace(hb, _)) => {
                     Nus3bankReplacer::hex_id_sort_key(ha).cmp(&Nus3bankReplacer::hex_id_sort_key(hb))
                 }
-                (ReplaceOperation::Add(na, ha, _), ReplaceOperation::Add(nb, hb, _)) => {
-                    na.cmp(nb)
-                        .then_with(|| Nus3bankReplacer::hex_id_sort_key(ha).cmp(&Nus3bankReplacer::hex_id_sort_key(hb)))
-                }
+                (
+                    ReplaceOperation::Add(na, ha, _) | ReplaceOperation::Duplicate(_, na, ha),
+                    ReplaceOperation::Add(nb, hb, _) | ReplaceOperation::Duplicate(_, nb, hb),
+                ) => na
+                    .cmp(nb)
+                    .then_with(|| Nus3bankReplacer::hex_id_sort_key(ha).cmp(&Nus3bankReplacer::hex_id_sort_key(hb))),
                 // Different types but same priority should not happen.
                 _ => std::cmp::Ordering::Equal,
             }
@@ -184,9 +281,19 @@ impl Nus3bankReplacer {
                     let new_hex_id = file.add_track(name.clone(), audio_data)?;
                     println!("Successfully added track '{}' with ID: {}", name, new_hex_id);
                 }
+                ReplaceOperation::Duplicate(hex_id, name, _temp_hex_id) => {
+                    println!("Applying duplicate of track {} as '{}'", hex_id, name);
+                    let new_hex_id = file.duplicate_track(&hex_id, name.clone())?;
+                    println!("Successfully duplicated track {} as '{}' with ID: {}", hex_id, name, new_hex_id);
+                }
             }
         }
 
+        if let Some(order) = Self::get_tone_order(file_path) {
+            println!("Applying new order of {} tones", order.len());
+            file.reorder_tones(&order)?;
+        }
+
         Ok(())
     }
     
@@ -196,6 +303,33 @@ impl Nus3bankReplacer {
         if let Ok(mut data) = REPLACEMENT_DATA.lock() {
             data.remove(&file_key);
         }
+        if let Ok(mut orders) = TONE_ORDERS.lock() {
+            orders.remove(&file_key);
+        }
+    }
+
+    /// Copy the operations of every file
+    pub fn capture_operations() -> HashMap<String, HashMap<String, ReplaceOperation>> {
+        REPLACEMENT_DATA.lock().map(|data| data.clone()).unwrap_or_default()
+    }
+
+    /// Replace the operations of every file with a copy from `capture_operations`
+    pub fn restore_operations(operations: HashMap<String, HashMap<String, ReplaceOperation>>) {
+        if let Ok(mut data) = REPLACEMENT_DATA.lock() {
+            *data = operations;
+        }
+    }
+
+    /// Copy the pending TONE order of every file
+    pub fn capture_tone_orders() -> HashMap<String, Vec<usize>> {
+        TONE_ORDERS.lock().map(|orders| orders.clone()).unwrap_or_default()
+    }
+
+    /// Replace the pending TONE orders with a copy from `capture_tone_orders`
+    pub fn restore_tone_orders(orders: HashMap<String, Vec<usize>>) {
+        if let Ok(mut pending) = TONE_ORDERS.lock() {
+            *pending = orders;
+        }
     }
 
     /// Clear all NUS3BANK replacement data from memory
@@ -203,6 +337,9 @@ impl Nus3bankReplacer {
         if let Ok(mut data) = REPLACEMENT_DATA.lock() {
             data.clear();
         }
+        if let Ok(mut orders) = TONE_ORDERS.lock() {
+            orders.clear();
+        }
         println!("Cleared all NUS3BANK audio replacements from memory");
     }
 
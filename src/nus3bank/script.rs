@@ -0,0 +1,217 @@
+//! Headless NUS3BANK edit scripts
+//!
+//! Reads and runs the JSON Lines scripts passed to `--run-script`, one edit or check per line.
+
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use super::structures::Nus3bankFile;
+use crate::ui::main_area::{LoopSettings, ReplaceUtils};
+
+/// One line of a `--run-script` file. Tracks are named by hex ID ("0x1a") or by name, and
+/// relative paths resolve against the folder holding the script. Audio files are processed
+/// like a replacement picked in the table, so any source the editor imports works.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ScriptStep {
+    Open { path: String },
+    Replace { track: String, wav: String },
+    /// Loop points are sample frames of the source; `loop_end` defaults to the last frame.
+    /// Gain and loop are applied like a replacement's, writing a smpl chunk.
+    Add {
+        name: String,
+        wav: String,
+        loop_start: Option<u32>,
+        loop_end: Option<u32>,
+        gain_db: Option<f32>,
+    },
+    Remove { track: String },
+    /// Write the bank, then reopen it from disk so later steps see what was saved
+    Save { path: Option<String> },
+    /// Save into memory and compare the re-parsed output with the open bank
+    Validate,
+    /// Check the open bank; every field given must hold
+    Expect {
+        tracks: Option<usize>,
+        track: Option<String>,
+        exists: Option<bool>,
+        size: Option<u32>,
+        /// File whose bytes the track's payload must equal
+        payload: Option<String>,
+    },
+}
+
+/// Outcome of a script run: steps that passed, and the failure that stopped it, if any
+#[derive(Clone, Debug, Default)]
+pub struct ScriptReport {
+    pub steps_passed: usize,
+    pub failure: Option<String>,
+}
+
+impl ScriptReport {
+    pub fn is_ok(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Human readable multi-line description for the CLI
+    pub fn describe(&self) -> String {
+        match &self.failure {
+            None => format!("Script: OK, {} step(s) passed", self.steps_passed),
+            Some(failure) => format!("Script: {} step(s) passed, then\n  - {}", self.steps_passed, failure),
+        }
+    }
+}
+
+/// Parse a JSON Lines script. Blank lines and lines starting with `#` or `//` are skipped.
+pub fn parse_script(text: &str) -> Result<Vec<(usize, ScriptStep)>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(n, line)| (n + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#') && !line.starts_with("//"))
+        .map(|(n, line)| {
+            serde_json::from_str(line)
+                .map(|step| (n, step))
+                .map_err(|e| format!("Line {}: {}", n, e))
+        })
+        .collect()
+}
+
+/// Run the script at `path` headlessly, stopping at the first step that fails
+pub fn run_script_file(path: &str) -> Result<ScriptReport, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Failed to read script {}: {}", path, e))?;
+    let steps = parse_script(&text)?;
+    let base = Path::new(path).parent().map(Path::to_path_buf).unwrap_or_default();
+    Ok(run_script(&steps, &base))
+}
+
+/// Run parsed steps with relative paths resolved against `base`
+pub fn run_script(steps: &[(usize, ScriptStep)], base: &Path) -> ScriptReport {
+    let mut report = ScriptReport::default();
+    let mut bank: Option<Nus3bankFile> = None;
+    for (line, step) in steps {
+        if let Err(e) = run_step(step, base, &mut bank) {
+            report.failure = Some(format!("Line {}: {}", line, e));
+            break;
+        }
+        report.steps_passed += 1;
+    }
+    report
+}
+
+fn run_step(step: &ScriptStep, base: &Path, bank: &mut Option<Nus3bankFile>) -> Result<(), String> {
+    let file = match (step, bank.as_mut()) {
+        (ScriptStep::Open { path }, None) => {
+            let path = resolve(base, path);
+            *bank = Some(Nus3bankFile::open(&path).map_err(|e| format!("open {}: {}", path.display(), e))?);
+            return Ok(());
+        }
+        (ScriptStep::Open { .. }, Some(_)) => return Err("open is only allowed as the first step".to_string()),
+        (_, None) => return Err("no bank is open".to_string()),
+        (_, Some(file)) => file,
+    };
+    match step {
+        ScriptStep::Open { .. } => Ok(()),
+        ScriptStep::Replace { track, wav } => {
+            let hex_id = find_track(file, track)?;
+            let data = process(base, wav, None, None, 0.0).map_err(|e| format!("replace {}: {}", track, e))?;
+            file.replace_track_data(&hex_id, data)
+                .map_err(|e| format!("replace {}: {}", track, e))
+        }
+        ScriptStep::Add { name, wav, loop_start, loop_end, gain_db } => {
+            let data = process(base, wav, *loop_start, *loop_end, gain_db.unwrap_or_default())
+                .map_err(|e| format!("add {}: {}", name, e))?;
+            file.add_track(name.clone(), data)
+                .map(|_| ())
+                .map_err(|e| format!("add {}: {}", name, e))
+        }
+        ScriptStep::Remove { track } => {
+            let hex_id = find_track(file, track)?;
+            file.remove_track(&hex_id).map_err(|e| format!("remove {}: {}", track, e))
+        }
+        ScriptStep::Save { path } => {
+            let path = path.as_ref().map_or_else(|| PathBuf::from(&file.file_path), |p| resolve(base, p));
+            file.save(&path).map_err(|e| format!("save {}: {}", path.display(), e))?;
+            let saved = Nus3bankFile::open(&path).map_err(|e| format!("reopen {}: {}", path.display(), e))?;
+            let problems = file.check_saved_order(&saved);
+            if !problems.is_empty() {
+                return Err(format!("save {}: track order changed: {}", path.display(), problems.join("; ")));
+            }
+            *file = saved;
+            Ok(())
+        }
+        ScriptStep::Validate => {
+            let report = file.verify().map_err(|e| format!("validate: {}", e))?;
+            if report.is_ok() {
+                Ok(())
+            } else {
+                Err(format!("validate: {}", report.mismatches.join("; ")))
+            }
+        }
+        ScriptStep::Expect { tracks, track, exists, size, payload } => {
+            if let Some(expected) = tracks {
+                if file.tracks.len() != *expected {
+                    return Err(format!("expected {} track(s), found {}", expected, file.tracks.len()));
+                }
+            }
+            let Some(track) = track else {
+                return Ok(());
+            };
+            let index = file.tracks.iter().position(|t| t.hex_id == *track || t.name == *track);
+            if let Some(exists) = exists {
+                if index.is_some() != *exists {
+                    let state = if *exists { "missing" } else { "still present" };
+                    return Err(format!("expected {} to exist: {}, but it is {}", track, exists, state));
+                }
+            }
+            if size.is_none() && payload.is_none() {
+                return Ok(());
+            }
+            let index = index.ok_or_else(|| format!("track {} not found", track))?;
+            if let Some(size) = size {
+                if file.tracks[index].size != *size {
+                    return Err(format!("expected {} to be {} bytes, found {}", track, size, file.tracks[index].size));
+                }
+            }
+            if let Some(payload) = payload {
+                let actual = file.read_track_payload(index).map_err(|e| format!("read {}: {}", track, e))?;
+                if actual != read(base, payload)? {
+                    return Err(format!("payload of {} differs from {}", track, payload));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Run the audio file at `path` through the replacement pipeline with this loop and gain.
+/// Without loop points the result has no loop, as in a replace dialog with looping off.
+fn process(base: &Path, path: &str, loop_start: Option<u32>, loop_end: Option<u32>, gain_db: f32) -> Result<Vec<u8>, String> {
+    let looping = loop_start.is_some() || loop_end.is_some();
+    let settings = LoopSettings {
+        enable_loop: looping,
+        use_custom_loop: looping,
+        gain_db,
+        loop_start_sample: looping.then_some(loop_start.unwrap_or_default()),
+        loop_end_sample: loop_end,
+        ..LoopSettings::default()
+    };
+    ReplaceUtils::process_source(&resolve(base, path), &settings)
+}
+
+fn find_track(file: &Nus3bankFile, track: &str) -> Result<String, String> {
+    file.tracks
+        .iter()
+        .find(|t| t.hex_id == track || t.name == track)
+        .map(|t| t.hex_id.clone())
+        .ok_or_else(|| format!("track {} not found", track))
+}
+
+fn resolve(base: &Path, path: &str) -> PathBuf {
+    base.join(path)
+}
+
+fn read(base: &Path, path: &str) -> Result<Vec<u8>, String> {
+    let path = resolve(base, path);
+    std::fs::read(&path).map_err(|e| format!("read {}: {}", path.display(), e))
+}
@@ -1,3 +1,7 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
 use super::error::Nus3bankError;
 
 /// A single BANKTOC entry: section magic and section data size (excluding the 8-byte section header).
@@ -26,6 +30,8 @@ pub struct PropSection {
     pub unk3: u16,
     /// Controls how PROP is rebuilt to preserve original layout.
     pub layout: PropLayout,
+    /// Payload bytes as read; see `GrpSection::raw`.
+    pub raw: Vec<u8>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -49,12 +55,17 @@ pub struct BinfSection {
 #[derive(Clone, Debug, Default)]
 pub struct GrpSection {
     pub names: Vec<String>,
+    /// Payload bytes as read. Written back unchanged while `names` still matches them, so
+    /// quirks the rebuild would normalize survive a save. Empty for sections built in memory.
+    pub raw: Vec<u8>,
 }
 
 /// DTON section (C# `NusDton`)
 #[derive(Clone, Debug, Default)]
 pub struct DtonSection {
     pub tones: Vec<ToneDes>,
+    /// Payload bytes as read; see `GrpSection::raw`.
+    pub raw: Vec<u8>,
 }
 
 #[derive(Clone, Debug)]
@@ -69,6 +80,9 @@ pub struct ToneDes {
 #[derive(Clone, Debug, Default)]
 pub struct ToneSection {
     pub tones: Vec<ToneMeta>,
+    /// Payload bytes as read. Written back, together with the PACK bytes as read, while no
+    /// tone was added, removed or changed; see `GrpSection::raw`.
+    pub raw: Vec<u8>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -94,11 +108,40 @@ pub struct ToneMeta {
     pub unkvalues_pair_order: UnkvaluesPairOrder,
     pub unkending: Vec<i32>,
     pub end: Vec<i32>,
+    /// Empty while the PACK payload is on disk, unless the tone was replaced or added since
     pub payload: Vec<u8>,
     pub meta_size: u32,
     pub removed: bool,
 }
 
+/// Entry of a MARK/BUSC pointer table. The field layout of these entries is not known, so
+/// the bytes are kept as-is and only the name is decoded when the entry starts with the
+/// `hash, unk1, len-prefixed name` header shared by TONE and DTON entries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableEntry {
+    /// Offset from the end of the entry count, as stored in the pointer table
+    pub offset: u32,
+    pub size: u32,
+    pub name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// MARK section (marker/cue tables), read-only
+#[derive(Clone, Debug, Default)]
+pub struct MarkSection {
+    pub entries: Vec<TableEntry>,
+    /// Payload bytes as read; always written back unchanged.
+    pub raw: Vec<u8>,
+}
+
+/// BUSC section (bus configuration), read-only
+#[derive(Clone, Debug, Default)]
+pub struct BuscSection {
+    pub entries: Vec<TableEntry>,
+    /// Payload bytes as read; always written back unchanged.
+    pub raw: Vec<u8>,
+}
+
 #[derive(Clone, Debug)]
 pub struct JunkSection {
     /// JUNK payload bytes (size varies across files).
@@ -109,6 +152,12 @@ pub struct JunkSection {
 pub struct PackSection {
     /// PACK payload bytes (excluding the section header). This is optional because we rebuild from `ToneMeta.payload`.
     pub data: Vec<u8>,
+    /// Where the payload starts in the file it was parsed from
+    pub file_offset: u64,
+    /// Payload length, also while it is on disk
+    pub size: u64,
+    /// The payload was not read into `data`; see `Nus3bankFile::read_track_payload`
+    pub on_disk: bool,
 }
 
 /// Supported audio formats (WAV-focused UI, but payload bytes may be non-WAV).
@@ -134,7 +183,7 @@ pub struct AudioTrack {
     pub size: u32,
     /// Metadata size from the TONE pointer table (used for filtering/round-trip)
     pub metadata_size: u32,
-    pub audio_data: Option<Vec<u8>>,
+    /// `Unknown` while the payload is on disk
     pub audio_format: AudioFormat,
     /// Index into `ToneSection.tones`
     pub tone_index: usize,
@@ -154,6 +203,8 @@ pub struct Nus3bankFile {
     pub binf: Option<BinfSection>,
     pub grp: Option<GrpSection>,
     pub dton: Option<DtonSection>,
+    pub mark: Option<MarkSection>,
+    pub busc: Option<BuscSection>,
     pub tone: ToneSection,
     pub junk: Option<JunkSection>,
     pub pack: PackSection,
@@ -174,6 +225,67 @@ impl Nus3bankFile {
         super::writer::Nus3bankWriter::write_file(self, path)
     }
 
+    /// Serialize the NUS3BANK file into memory (same output as `save`).
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Nus3bankError> {
+        super::writer::Nus3bankWriter::write_bytes(self)
+    }
+
+    /// Audio payload of the track at `index` in `tracks`. Read from the file when the PACK
+    /// payload was left on disk and the track was not replaced in memory.
+    pub fn read_track_payload(&self, index: usize) -> Result<Vec<u8>, Nus3bankError> {
+        let track = self.tracks.get(index).ok_or_else(|| Nus3bankError::InvalidFormat {
+            reason: format!("Track index {} out of bounds", index),
+        })?;
+        let tone = self.tone.tones.get(track.tone_index).ok_or_else(|| Nus3bankError::InvalidFormat {
+            reason: format!("Tone index out of bounds for track {}", track.hex_id),
+        })?;
+        if !self.pack.on_disk || !tone.payload.is_empty() || tone.offset < 0 || tone.size <= 0 {
+            return Ok(tone.payload.clone());
+        }
+
+        let mut payload = vec![0u8; tone.size as usize];
+        let mut file = File::open(&self.file_path)?;
+        file.seek(SeekFrom::Start(self.pack.file_offset + tone.offset as u64))?;
+        file.read_exact(&mut payload)?;
+        Ok(payload)
+    }
+
+    /// Read the PACK payload into memory and fill in the payload of every tone not replaced
+    /// since parsing, as rebuilding the file needs them all.
+    pub fn load_payloads(&mut self) -> Result<(), Nus3bankError> {
+        if !self.pack.on_disk {
+            return Ok(());
+        }
+        let mut data = vec![0u8; self.pack.size as usize];
+        let mut file = File::open(&self.file_path)?;
+        file.seek(SeekFrom::Start(self.pack.file_offset))?;
+        file.read_exact(&mut data)?;
+
+        for tone in self.tone.tones.iter_mut().filter(|t| !t.removed && t.payload.is_empty()) {
+            if tone.offset < 0 || tone.size < 0 {
+                continue;
+            }
+            let start = tone.offset as usize;
+            if let Some(payload) = data.get(start..start + tone.size as usize) {
+                tone.payload = payload.to_vec();
+            }
+        }
+        self.pack.data = data;
+        self.pack.on_disk = false;
+        self.rebuild_tracks_view();
+        Ok(())
+    }
+
+    /// This file with every payload in memory, copied only if the PACK payload is on disk
+    pub fn with_payloads(&self) -> Result<Cow<'_, Self>, Nus3bankError> {
+        if !self.pack.on_disk {
+            return Ok(Cow::Borrowed(self));
+        }
+        let mut loaded = self.clone();
+        loaded.load_payloads()?;
+        Ok(Cow::Owned(loaded))
+    }
+
     pub fn get_track_by_hex_id(&self, hex_id: &str) -> Option<&AudioTrack> {
         self.tracks.iter().find(|t| t.hex_id == hex_id)
     }
@@ -213,7 +325,6 @@ impl Nus3bankFile {
                 .ok_or_else(|| Nus3bankError::InvalidFormat {
                     reason: "Track index out of bounds".to_string(),
                 })?;
-            track.audio_data = Some(new_data.clone());
             track.size = new_data.len() as u32;
             track.audio_format = if new_data.starts_with(b"RIFF") {
                 AudioFormat::Wav
@@ -299,6 +410,43 @@ impl Nus3bankFile {
         Ok(hex_id)
     }
 
+    /// Append a copy of a track, its tone metadata and payload included, under `name`.
+    /// Returns the hex ID of the copy, the next slot in the TONE list.
+    pub fn duplicate_track(&mut self, hex_id: &str, name: String) -> Result<String, Nus3bankError> {
+        if name.is_empty() {
+            return Err(Nus3bankError::InvalidFormat {
+                reason: "Track name cannot be empty".to_string(),
+            });
+        }
+        if self.tracks.iter().any(|t| t.name == name) {
+            return Err(Nus3bankError::InvalidFormat {
+                reason: format!("Track with name '{}' already exists", name),
+            });
+        }
+        let track_pos = self
+            .tracks
+            .iter()
+            .position(|t| t.hex_id == hex_id)
+            .ok_or_else(|| Nus3bankError::TrackNotFound {
+                hex_id: hex_id.to_string(),
+            })?;
+        let payload = self.read_track_payload(track_pos)?;
+        let tone_index = self.tracks[track_pos].tone_index;
+
+        let mut copy = self.tone.tones[tone_index].clone();
+        copy.name = name;
+        copy.size = payload.len() as i32;
+        copy.payload = payload;
+        copy.offset = 0;
+        copy.meta_size = 0;
+
+        let new_hex_id = format!("0x{:x}", self.tone.tones.len() as u32);
+        self.tone.tones.push(copy);
+        self.rebuild_tracks_view();
+
+        Ok(new_hex_id)
+    }
+
     pub fn remove_track(&mut self, hex_id: &str) -> Result<(), Nus3bankError> {
         let track = self
             .get_track_by_hex_id(hex_id)
@@ -325,6 +473,26 @@ impl Nus3bankFile {
         Ok(())
     }
 
+    /// Put the TONE entries in a new order: slot `i` takes the tone now at `order[i]`. A
+    /// tone's ID is its slot, so this reassigns IDs; the writer rebuilds the pointer table in
+    /// the new order. Tones past the end of `order`, such as ones added since, keep their
+    /// place after the others.
+    pub fn reorder_tones(&mut self, order: &[usize]) -> Result<(), Nus3bankError> {
+        let mut seen = vec![false; order.len()];
+        for &from in order {
+            if from >= order.len() || from >= self.tone.tones.len() || std::mem::replace(&mut seen[from], true) {
+                return Err(Nus3bankError::InvalidFormat {
+                    reason: format!("Tone order is not a permutation of the first {} tones", order.len()),
+                });
+            }
+        }
+        let mut tones: Vec<ToneMeta> = order.iter().map(|&from| self.tone.tones[from].clone()).collect();
+        tones.extend(self.tone.tones.drain(order.len()..));
+        self.tone.tones = tones;
+        self.rebuild_tracks_view();
+        Ok(())
+    }
+
     pub(crate) fn rebuild_tracks_view(&mut self) {
         let mut tracks = Vec::new();
         for (i, tone) in self.tone.tones.iter().enumerate() {
@@ -333,12 +501,6 @@ impl Nus3bankFile {
             }
 
             let hex_id = format!("0x{:x}", i as u32);
-            let audio_data = if tone.payload.is_empty() {
-                None
-            } else {
-                Some(tone.payload.clone())
-            };
-
             let audio_format = if tone.payload.starts_with(b"RIFF") {
                 AudioFormat::Wav
             } else {
@@ -353,7 +515,6 @@ impl Nus3bankFile {
                 pack_offset: tone.offset.max(0) as u32,
                 size: tone.size.max(0) as u32,
                 metadata_size: tone.meta_size,
-                audio_data,
                 audio_format,
                 tone_index: i,
             });
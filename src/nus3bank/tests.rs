@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use super::replace::Nus3bankReplacer;
 use super::structures::{
     BinfSection, DtonSection, GrpSection, JunkSection, Nus3bankFile, PropLayout, PropSection,
     TocEntry, ToneMeta, ToneSection, UnkvaluesPairOrder,
@@ -44,6 +45,7 @@ fn make_sample_file() -> Nus3bankFile {
         unk2: 0x3,
         unk3: 0x8,
         layout: PropLayout::Extended,
+        raw: Vec::new(),
     };
 
     let binf = BinfSection {
@@ -55,6 +57,7 @@ fn make_sample_file() -> Nus3bankFile {
 
     let grp = GrpSection {
         names: vec!["group_a".to_string(), "group_b".to_string()],
+        raw: Vec::new(),
     };
 
     let dton = DtonSection::default();
@@ -109,6 +112,7 @@ fn make_sample_file() -> Nus3bankFile {
 
     let tone = ToneSection {
         tones: vec![tone0, tone1],
+        raw: Vec::new(),
     };
 
     Nus3bankFile {
@@ -117,6 +121,8 @@ fn make_sample_file() -> Nus3bankFile {
         binf: Some(binf),
         grp: Some(grp),
         dton: Some(dton),
+        mark: None,
+        busc: None,
         tone,
         junk: Some(JunkSection { data: vec![0, 0, 0, 0] }),
         pack: Default::default(),
@@ -126,6 +132,39 @@ fn make_sample_file() -> Nus3bankFile {
     }
 }
 
+/// Sections of a BANKTOC file as (magic, payload)
+fn split_sections(bytes: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+    let count = u32::from_le_bytes(bytes[0x14..0x18].try_into().unwrap()) as usize;
+    let mut pos = 0x18 + count * 8;
+    let mut sections = Vec::new();
+    for _ in 0..count {
+        let magic: [u8; 4] = bytes[pos..pos + 4].try_into().unwrap();
+        let size = u32::from_le_bytes(bytes[pos + 4..pos + 8].try_into().unwrap()) as usize;
+        sections.push((magic, bytes[pos + 8..pos + 8 + size].to_vec()));
+        pos += 8 + size;
+    }
+    sections
+}
+
+/// BANKTOC file from (magic, payload) sections
+fn assemble_sections(sections: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let mut out = b"NUS3\0\0\0\0BANKTOC ".to_vec();
+    out.extend_from_slice(&(4 + sections.len() as u32 * 8).to_le_bytes());
+    out.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+    for (magic, payload) in sections {
+        out.extend_from_slice(magic);
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    }
+    for (magic, payload) in sections {
+        out.extend_from_slice(magic);
+        out.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        out.extend_from_slice(payload);
+    }
+    let total = out.len() as u32 - 8;
+    out[4..8].copy_from_slice(&total.to_le_bytes());
+    out
+}
+
 fn declared_section_total_len(section: &[u8]) -> usize {
     assert!(section.len() >= 8);
     let declared_size = u32::from_le_bytes([section[4], section[5], section[6], section[7]]) as usize;
@@ -255,7 +294,8 @@ fn roundtrip_sections_and_tracks() {
     assert_eq!(parsed.binf.as_ref().unwrap().flag, 0x05);
     assert_eq!(parsed.tracks.len(), 2);
     assert_eq!(parsed.tracks[0].name, "track_a");
-    assert!(parsed.tracks[0].audio_data.as_ref().unwrap().starts_with(b"RIFF"));
+    assert!(parsed.pack.on_disk && parsed.tone.tones[0].payload.is_empty());
+    assert!(parsed.read_track_payload(0).unwrap().starts_with(b"RIFF"));
     assert_eq!(parsed.tone.tones.len(), 2);
     assert_eq!(parsed.tone.tones[0].name, "track_a");
     assert_eq!(parsed.tone.tones[1].name, "track_b");
@@ -279,7 +319,9 @@ fn mutate_replace_and_save_updates_payload() {
 
     let reparsed = Nus3bankFile::open(&out_path2).unwrap();
     assert_eq!(reparsed.tracks.len(), 2);
-    assert_eq!(reparsed.tracks[0].audio_data.as_ref().unwrap(), &new_wav);
+    assert_eq!(reparsed.read_track_payload(0).unwrap(), new_wav);
+    // The untouched track was still on disk when the file was saved
+    assert_eq!(reparsed.read_track_payload(1).unwrap(), file.tone.tones[1].payload);
 }
 
 #[test]
@@ -321,3 +363,356 @@ fn mutate_add_and_save_appends_track() {
     assert_eq!(reparsed.tracks[2].name, "track_c");
 }
 
+#[test]
+fn mutate_reorder_and_save_swaps_ids() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+
+    let out_path = unique_temp_path("reorder_in.nus3bank");
+    file.save(&out_path).unwrap();
+
+    let mut parsed = Nus3bankFile::open(&out_path).unwrap();
+    let payload_b = parsed.read_track_payload(1).unwrap();
+    assert!(parsed.reorder_tones(&[0, 0]).is_err());
+    parsed.reorder_tones(&[1, 0]).unwrap();
+    assert_eq!(parsed.get_track_by_hex_id("0x0").unwrap().name, "track_b");
+
+    let out_path2 = unique_temp_path("reorder_out.nus3bank");
+    parsed.save(&out_path2).unwrap();
+
+    let reparsed = Nus3bankFile::open(&out_path2).unwrap();
+    let names: Vec<&str> = reparsed.tracks.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["track_b", "track_a"]);
+    assert_eq!(reparsed.read_track_payload(0).unwrap(), payload_b);
+}
+
+#[test]
+fn mutate_duplicate_and_save_copies_tone() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+
+    let out_path = unique_temp_path("dup_in.nus3bank");
+    file.save(&out_path).unwrap();
+
+    let mut parsed = Nus3bankFile::open(&out_path).unwrap();
+    assert!(parsed.duplicate_track("0x1", "track_a".to_string()).is_err());
+    let new_id = parsed.duplicate_track("0x1", "track_b_2".to_string()).unwrap();
+    assert_eq!(new_id, "0x2");
+
+    let out_path2 = unique_temp_path("dup_out.nus3bank");
+    parsed.save(&out_path2).unwrap();
+
+    let reparsed = Nus3bankFile::open(&out_path2).unwrap();
+    assert_eq!(reparsed.tracks.len(), 3);
+    assert_eq!(reparsed.tracks[2].name, "track_b_2");
+    assert_eq!(reparsed.read_track_payload(2).unwrap(), reparsed.read_track_payload(1).unwrap());
+    assert_eq!(reparsed.tone.tones[2].param, reparsed.tone.tones[1].param);
+    assert_eq!(reparsed.tone.tones[2].unk1, reparsed.tone.tones[1].unk1);
+}
+
+
+#[test]
+fn dry_run_reports_without_writing() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    file.remove_track("0x1").unwrap();
+
+    let out_path = unique_temp_path("dry_run.nus3bank");
+    let report = file.dry_run(&out_path.to_string_lossy()).unwrap();
+    assert!(report.is_valid(), "{:?}", report.problems);
+    assert!(!report.destination_exists);
+    assert_eq!(report.track_count, 1);
+    assert_eq!(report.output_size, file.to_bytes().unwrap().len());
+    assert!(!out_path.exists());
+}
+
+#[test]
+fn layered_replacements_leave_stored_operations_alone() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let path = unique_temp_path("layered.nus3bank");
+    file.save(&path).unwrap();
+    let path = path.to_string_lossy().to_string();
+
+    let mut new_wav = minimal_wav_bytes();
+    new_wav.extend_from_slice(b"ABCD");
+    let replacements = std::collections::HashMap::from([("0x0".to_string(), new_wav.clone())]);
+    let mut parsed = Nus3bankFile::open(&path).unwrap();
+    Nus3bankReplacer::apply_to_file_with(&path, replacements, &mut parsed).unwrap();
+
+    assert_eq!(parsed.tone.tones[0].payload, new_wav);
+    assert!(Nus3bankReplacer::get_operations(&path).is_empty());
+}
+
+#[test]
+fn unedited_grp_dton_mark_busc_are_saved_byte_exact() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let mut sections = split_sections(&file.to_bytes().unwrap());
+
+    // GRP with a last-entry size the rebuild would not reproduce
+    let grp = &mut sections.iter_mut().find(|(m, _)| m == b"GRP ").unwrap().1;
+    let last_size_pos = 4 + 8 + 4;
+    let size = u32::from_le_bytes(grp[last_size_pos..last_size_pos + 4].try_into().unwrap());
+    grp[last_size_pos..last_size_pos + 4].copy_from_slice(&(size + 4).to_le_bytes());
+    // Real DTON extract
+    let dton_bin: &[u8] = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/dton_2.bin"));
+    sections.iter_mut().find(|(m, _)| m == b"DTON").unwrap().1 = dton_bin[8..].to_vec();
+    // MARK with one named entry, BUSC whose pointer table does not fit
+    let mut mark = vec![1, 0, 0, 0, 12, 0, 0, 0, 16, 0, 0, 0, 0, 0, 0, 0];
+    mark.extend_from_slice(&[7, 0, 0, 0, 0, 0, 0, 0, 4, b'm', b'k', b'1', 0, 0, 0, 0]);
+    sections.insert(4, (*b"MARK", mark));
+    sections.insert(5, (*b"BUSC", vec![5, 0, 0, 0, 9, 9, 9, 9]));
+    let bytes = assemble_sections(&sections);
+
+    let mut parsed = super::parser::Nus3bankParser::parse_bytes(&bytes, "in_memory".to_string()).unwrap();
+    let mark = parsed.mark.as_ref().unwrap();
+    assert_eq!(mark.entries.len(), 1);
+    assert_eq!(mark.entries[0].name.as_deref(), Some("mk1"));
+    assert!(parsed.busc.is_none());
+    assert_eq!(parsed.unknown_sections[0].magic, *b"BUSC");
+    assert_eq!(parsed.to_bytes().unwrap(), bytes);
+
+    let json = parsed.to_debug_json_value(&Default::default());
+    assert_eq!(json["sections"]["mark"]["entries"][0]["name"], "mk1");
+
+    // Edited sections are rebuilt
+    parsed.grp.as_mut().unwrap().names[0] = "renamed".to_string();
+    let rebuilt = parsed.to_bytes().unwrap();
+    assert_ne!(rebuilt, bytes);
+    let reparsed = super::parser::Nus3bankParser::parse_bytes(&rebuilt, "in_memory".to_string()).unwrap();
+    assert_eq!(reparsed.grp.unwrap().names, vec!["renamed", "group_b"]);
+    assert_eq!(reparsed.mark.unwrap().raw, parsed.mark.unwrap().raw);
+}
+
+#[test]
+fn parse_bytes_matches_parse_file() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+
+    let out_path = unique_temp_path("parse_bytes.nus3bank");
+    file.save(&out_path).unwrap();
+
+    let bytes = std::fs::read(&out_path).unwrap();
+    assert_eq!(bytes, file.to_bytes().unwrap());
+
+    let from_bytes = super::parser::Nus3bankParser::parse_bytes(&bytes, "in_memory".to_string()).unwrap();
+    let from_file = Nus3bankFile::open(&out_path).unwrap();
+    assert_eq!(from_bytes.tracks.len(), from_file.tracks.len());
+    assert_eq!(from_bytes.tracks[1].name, from_file.tracks[1].name);
+}
+
+#[test]
+fn parse_reports_progress_and_rejects_truncated_sections() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let bytes = file.to_bytes().unwrap();
+
+    let out_path = unique_temp_path("progress.nus3bank");
+    std::fs::write(&out_path, &bytes).unwrap();
+    let mut last = (0, 0);
+    let parsed = super::parser::Nus3bankParser::parse_file_with_progress(&out_path, &mut |read, total| {
+        assert!(read >= last.0);
+        last = (read, total);
+    })
+    .unwrap();
+    assert_eq!(parsed.tracks.len(), file.tracks.len());
+    assert_eq!(last, (bytes.len() as u64, bytes.len() as u64));
+
+    let truncated = &bytes[..bytes.len() - 4];
+    assert!(super::parser::Nus3bankParser::parse_bytes(truncated, "truncated".to_string()).is_err());
+}
+
+#[test]
+fn unedited_real_banks_rebuild_byte_exact() {
+    let banks = [
+        "gvs_rx78.nus3bank",
+        "output.nus3bank",
+        "output_nook.nus3bank",
+        "output_ok.nus3bank",
+        "sample2.nus3bank",
+        "se_chr_001gundam_001gundam_001.nus3bank",
+        "se_chr_021destny_001strkfr_001.nus3bank",
+        "se_chr_654gexvs2_003glfunl_001.nus3bank",
+    ];
+    for bank in banks {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(bank);
+        let Ok(original) = std::fs::read(&path) else {
+            continue;
+        };
+        let parsed = super::parser::Nus3bankParser::parse_bytes(&original, bank.to_string()).unwrap();
+        let rebuilt = super::writer::Nus3bankWriter::rebuild(&parsed).unwrap();
+        assert!(rebuilt == original, "{} does not round-trip", bank);
+    }
+}
+
+#[test]
+fn rebuild_grows_pack_and_recomputes_offsets() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let bytes = file.to_bytes().unwrap();
+    let mut parsed = super::parser::Nus3bankParser::parse_bytes(&bytes, "in_memory".to_string()).unwrap();
+
+    let mut larger = minimal_wav_bytes();
+    larger.extend(std::iter::repeat(7u8).take(1001));
+    parsed.replace_track_data("0x0", larger.clone()).unwrap();
+    let rebuilt = super::writer::Nus3bankWriter::rebuild(&parsed).unwrap();
+    assert!(rebuilt.len() > bytes.len());
+
+    let reparsed = super::parser::Nus3bankParser::parse_bytes(&rebuilt, "in_memory".to_string()).unwrap();
+    let tones = &reparsed.tone.tones;
+    assert_eq!(tones[0].payload, larger);
+    assert_eq!(tones[1].payload, parsed.tone.tones[1].payload);
+    assert_eq!(tones[1].offset as usize, (larger.len() + 3) & !3);
+    let pack = split_sections(&rebuilt).into_iter().find(|(m, _)| m == b"PACK").unwrap().1;
+    assert_eq!(pack.len(), reparsed.pack.data.len());
+    assert_eq!(&pack[tones[1].offset as usize..][..tones[1].size as usize], tones[1].payload.as_slice());
+}
+
+#[test]
+fn verify_reports_shifted_ids_after_removal() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let report = file.verify().unwrap();
+    assert!(report.is_ok(), "{:?}", report.mismatches);
+    assert_eq!(report.tracks_checked, 2);
+
+    file.remove_track("0x0").unwrap();
+    let report = file.verify().unwrap();
+    assert_eq!(report.tracks_checked, 1);
+    assert_eq!(report.mismatches, vec!["'track_b' moves from ID 0x1 to 0x0".to_string()]);
+}
+
+#[test]
+fn script_replays_edits_and_reports_the_failing_line() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let dir = unique_temp_path("script");
+    std::fs::create_dir_all(&dir).unwrap();
+    file.save(dir.join("in.nus3bank")).unwrap();
+    let wav = crate::audio_processing::splice::write_pcm16(1, 8000, b"ABCD");
+    std::fs::write(dir.join("new.wav"), &wav).unwrap();
+
+    let script = r#"
+        # replace one track, drop the other, and check the saved bank
+        {"op": "open", "path": "in.nus3bank"}
+        {"op": "replace", "track": "track_a", "wav": "new.wav"}
+        {"op": "remove", "track": "0x1"}
+        {"op": "save", "path": "out.nus3bank"}
+        {"op": "validate"}
+        {"op": "expect", "tracks": 1, "track": "track_a", "size": 48, "payload": "new.wav"}
+        {"op": "expect", "track": "track_b", "exists": true}
+    "#;
+    let steps = super::script::parse_script(script).unwrap();
+    let report = super::script::run_script(&steps, &dir);
+    assert_eq!(report.steps_passed, 6);
+    assert!(report.failure.unwrap().starts_with("Line 9:"));
+
+    let reopen = r#"
+        {"op": "open", "path": "in.nus3bank"}
+        {"op": "open", "path": "in.nus3bank"}
+    "#;
+    let report = super::script::run_script(&super::script::parse_script(reopen).unwrap(), &dir);
+    assert_eq!(report.steps_passed, 1);
+    assert!(report.failure.unwrap().contains("open is only allowed as the first step"));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn script_add_writes_loop_points() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let dir = unique_temp_path("script_add_loop");
+    std::fs::create_dir_all(&dir).unwrap();
+    file.save(dir.join("in.nus3bank")).unwrap();
+    let wav = crate::audio_processing::splice::write_pcm16(1, 8000, &[0u8; 200]);
+    std::fs::write(dir.join("new.wav"), &wav).unwrap();
+
+    let script = r#"
+        {"op": "open", "path": "in.nus3bank"}
+        {"op": "add", "name": "looped", "wav": "new.wav", "loop_start": 10}
+        {"op": "add", "name": "plain", "wav": "new.wav"}
+        {"op": "save", "path": "out.nus3bank"}
+        {"op": "expect", "tracks": 4, "track": "plain", "payload": "new.wav"}
+    "#;
+    let steps = super::script::parse_script(script).unwrap();
+    let report = super::script::run_script(&steps, &dir);
+    assert!(report.is_ok(), "{}", report.describe());
+
+    let saved = Nus3bankFile::open(dir.join("out.nus3bank")).unwrap();
+    let index = saved.tracks.iter().position(|t| t.name == "looped").unwrap();
+    let payload = saved.read_track_payload(index).unwrap();
+    let lp = crate::audio_processing::smpl::read_loop(&payload).unwrap();
+    assert_eq!((lp.start, lp.end), (10, 99));
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn saved_order_check_allows_removals_and_catches_moves() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let out_path = unique_temp_path("order_check.nus3bank");
+
+    file.remove_track("0x0").unwrap();
+    file.save(&out_path).unwrap();
+    let saved = Nus3bankFile::open(&out_path).unwrap();
+    assert!(file.check_saved_order(&saved).is_empty());
+
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    file.save(&out_path).unwrap();
+    let mut moved = Nus3bankFile::open(&out_path).unwrap();
+    assert!(file.check_saved_order(&moved).is_empty());
+    moved.reorder_tones(&[1, 0]).unwrap();
+    let problems = file.check_saved_order(&moved);
+    assert_eq!(problems.len(), 2);
+    assert_eq!(problems[0], "ID 0x0 should be 'track_a' but 'track_b' was written as 0x0");
+}
+
+#[test]
+fn manifest_replaces_by_name_and_hex_id() {
+    let mut file = make_sample_file();
+    file.rebuild_tracks_view();
+    let dir = unique_temp_path("manifest_apply");
+    std::fs::create_dir_all(&dir).unwrap();
+    file.save(dir.join("in.nus3bank")).unwrap();
+    let wav = crate::audio_processing::splice::write_pcm16(1, 8000, &[0u8; 16000]);
+    std::fs::write(dir.join("new.wav"), &wav).unwrap();
+
+    let csv = "track,wav,loop_start,loop_end,gain_db,action\n\
+               track_a,\"new.wav\",0.5,,-3,\n\
+               0x1,new.wav,,,,replace\n\
+               track_b,new.wav,,,,silence\n";
+    let manifest = super::manifest::ReplacementManifest::parse_csv(csv).unwrap();
+    assert_eq!(manifest.rows.len(), 3);
+    assert!(manifest.rows[0].looping());
+    assert!(!manifest.rows[1].looping());
+    let upper = super::manifest::ManifestRow { track: Some("0X1".to_string()), ..Default::default() };
+    assert!(upper.matches("track_b", "1", Some("0x1")));
+
+    std::fs::write(dir.join("bad.csv"), "track,wav\nmissing,new.wav\ntrack_a,new.wav\n").unwrap();
+    let report = crate::apply_manifest_file(&dir.join("in.nus3bank"), &dir.join("bad.csv"), &dir.join("bad.nus3bank")).unwrap();
+    assert_eq!((report.applied, report.errors.len()), (1, 1));
+    assert!(!dir.join("bad.nus3bank").exists());
+
+    std::fs::write(dir.join("pack.csv"), csv).unwrap();
+    let (report, dry_run) =
+        crate::dry_run_manifest_file(&dir.join("in.nus3bank"), &dir.join("pack.csv"), &dir.join("out.nus3bank")).unwrap();
+    assert_eq!((report.applied, report.skipped), (2, 1));
+    let dry_run = dry_run.unwrap();
+    assert!(dry_run.is_valid(), "{}", dry_run.describe());
+    assert!(!dry_run.destination_exists);
+    assert!(!dir.join("out.nus3bank").exists());
+    let (_, dry_run) = crate::dry_run_manifest_file(&dir.join("in.nus3bank"), &dir.join("bad.csv"), &dir.join("bad.nus3bank")).unwrap();
+    assert!(dry_run.is_none());
+
+    let report = crate::apply_manifest_file(&dir.join("in.nus3bank"), &dir.join("pack.csv"), &dir.join("out.nus3bank")).unwrap();
+    assert!(report.is_ok(), "{}", report.describe());
+    assert_eq!((report.applied, report.skipped), (2, 1));
+
+    let saved = Nus3bankFile::open(dir.join("out.nus3bank")).unwrap();
+    let lp = crate::audio_processing::smpl::read_loop(&saved.read_track_payload(0).unwrap()).unwrap();
+    assert_eq!((lp.start, lp.end), (4000, 7999));
+    assert_eq!(saved.read_track_payload(1).unwrap(), wav);
+    let _ = std::fs::remove_dir_all(&dir);
+}
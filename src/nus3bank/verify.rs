@@ -0,0 +1,139 @@
+//! Round-trip verification of NUS3BANK saves
+//!
+//! Saves a bank into memory, parses it back and compares the result track by track.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use super::error::Nus3bankError;
+use super::parser::Nus3bankParser;
+use super::structures::Nus3bankFile;
+
+/// Result of saving a bank into memory and comparing the re-parsed output track by track.
+#[derive(Clone, Debug)]
+pub struct VerifyReport {
+    /// Size of the serialized output
+    pub output_size: usize,
+    /// Tracks compared (the smaller of both counts)
+    pub tracks_checked: usize,
+    /// Differences between the in-memory bank and the re-parsed output (empty = identical)
+    pub mismatches: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Human readable multi-line description used by both the GUI and the CLI.
+    pub fn describe(&self) -> String {
+        let mut lines = vec![
+            format!("Output size: {} bytes", self.output_size),
+            format!("Tracks checked: {}", self.tracks_checked),
+        ];
+        if self.mismatches.is_empty() {
+            lines.push("Verify: OK, the saved bank reads back identical".to_string());
+        } else {
+            lines.push(format!("Verify: {} mismatch(es)", self.mismatches.len()));
+            for mismatch in &self.mismatches {
+                lines.push(format!("  - {}", mismatch));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+fn payload_hash(payload: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    payload.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Nus3bankFile {
+    /// Serialize into memory, re-parse the result and compare track counts, names, IDs,
+    /// sizes and payload hashes against this file. Nothing is written to disk.
+    pub fn verify(&self) -> Result<VerifyReport, Nus3bankError> {
+        let file = self.with_payloads()?;
+        let bytes = file.to_bytes()?;
+        let mut mismatches = Vec::new();
+
+        let reparsed = match Nus3bankParser::parse_bytes(&bytes, file.file_path.clone()) {
+            Ok(reparsed) => reparsed,
+            Err(e) => {
+                return Ok(VerifyReport {
+                    output_size: bytes.len(),
+                    tracks_checked: 0,
+                    mismatches: vec![format!("Output does not re-parse: {}", e)],
+                })
+            }
+        };
+
+        if reparsed.tracks.len() != file.tracks.len() {
+            mismatches.push(format!(
+                "Track count mismatch: {} in memory, {} after save",
+                file.tracks.len(),
+                reparsed.tracks.len()
+            ));
+        }
+
+        for (track, saved) in file.tracks.iter().zip(&reparsed.tracks) {
+            if track.hex_id != saved.hex_id {
+                mismatches.push(format!(
+                    "'{}' moves from ID {} to {}",
+                    track.name, track.hex_id, saved.hex_id
+                ));
+            }
+            if track.name != saved.name {
+                mismatches.push(format!(
+                    "Track {} name mismatch: '{}' in memory, '{}' after save",
+                    track.hex_id, track.name, saved.name
+                ));
+            }
+
+            let payload = file.tone.tones.get(track.tone_index).map(|t| t.payload.as_slice()).unwrap_or_default();
+            let saved_payload =
+                reparsed.tone.tones.get(saved.tone_index).map(|t| t.payload.as_slice()).unwrap_or_default();
+            if payload.len() != saved_payload.len() {
+                mismatches.push(format!(
+                    "Track {} size mismatch: {} bytes in memory, {} after save",
+                    track.hex_id,
+                    payload.len(),
+                    saved_payload.len()
+                ));
+            } else if payload_hash(payload) != payload_hash(saved_payload) {
+                mismatches.push(format!("Track {} payload differs after save", track.hex_id));
+            }
+        }
+
+        Ok(VerifyReport {
+            output_size: bytes.len(),
+            tracks_checked: file.tracks.len().min(reparsed.tracks.len()),
+            mismatches,
+        })
+    }
+
+    /// Compare the tracks of `saved`, this bank as read back after saving, with the order
+    /// this bank has. Removed tones are dropped by the writer, so the IDs after them are
+    /// expected to shift down; any other difference means cue IDs moved. Returns one line
+    /// per difference, empty when the order held.
+    pub fn check_saved_order(&self, saved: &Nus3bankFile) -> Vec<String> {
+        let mut problems = Vec::new();
+        if saved.tracks.len() != self.tracks.len() {
+            problems.push(format!(
+                "{} track(s) expected, {} written",
+                self.tracks.len(),
+                saved.tracks.len()
+            ));
+        }
+        for (i, (track, written)) in self.tracks.iter().zip(&saved.tracks).enumerate() {
+            let expected_id = format!("0x{:x}", i);
+            if written.name != track.name || written.hex_id != expected_id {
+                problems.push(format!(
+                    "ID {} should be '{}' but '{}' was written as {}",
+                    expected_id, track.name, written.name, written.hex_id
+                ));
+            }
+        }
+        problems
+    }
+}
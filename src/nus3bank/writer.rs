@@ -3,9 +3,10 @@ use std::fs;
 use super::{
     binary_utils::BinaryReader,
     error::Nus3bankError,
+    parser::Nus3bankParser,
     structures::{
         BinfSection, DtonSection, GrpSection, Nus3bankFile, PropLayout, PropSection, RawSection,
-        TocEntry, ToneMeta,
+        TocEntry, ToneDes, ToneMeta,
     },
 };
 
@@ -14,17 +15,41 @@ pub struct Nus3bankWriter;
 
 impl Nus3bankWriter {
     pub fn write_file<P: AsRef<std::path::Path>>(file: &Nus3bankFile, path: P) -> Result<(), Nus3bankError> {
-        // Build active tones (skip removed).
-        let mut active_tones: Vec<ToneMeta> = file
-            .tone
-            .tones
-            .iter()
-            .filter(|t| !t.removed)
-            .cloned()
-            .collect();
-
-        // Rebuild PACK and update each tone's offset/size (offset is relative to PACK payload start).
-        let pack_payload = Self::build_pack_payload(&mut active_tones);
+        let out = Self::rebuild(file)?;
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    /// Serialize the file and check the result before handing it out: section sizes and the
+    /// TOC agree, and every track's PACK range holds exactly its payload. A file nothing was
+    /// changed in comes back byte for byte.
+    pub fn rebuild(file: &Nus3bankFile) -> Result<Vec<u8>, Nus3bankError> {
+        let file = file.with_payloads()?;
+        let out = Self::write_bytes(&file)?;
+        Self::validate(&file, &out)?;
+        Ok(out)
+    }
+
+    /// Serialize the file into an in-memory buffer without touching disk.
+    pub fn write_bytes(file: &Nus3bankFile) -> Result<Vec<u8>, Nus3bankError> {
+        let file = &*file.with_payloads()?;
+        let (tone_payload, pack_payload) = match Self::preserved_tone_and_pack(file) {
+            Some(preserved) => preserved,
+            None => {
+                // Build active tones (skip removed).
+                let mut active_tones: Vec<ToneMeta> = file
+                    .tone
+                    .tones
+                    .iter()
+                    .filter(|t| !t.removed)
+                    .cloned()
+                    .collect();
+
+                // Rebuild PACK and update each tone's offset/size (offset is relative to PACK payload start).
+                let pack_payload = Self::build_pack_payload(&mut active_tones);
+                (Self::build_tone(&active_tones)?, pack_payload)
+            }
+        };
 
         // Rebuild sections in TOC order.
         let toc = file.toc.clone();
@@ -35,7 +60,7 @@ impl Nus3bankWriter {
                     let prop = file.prop.as_ref().ok_or_else(|| Nus3bankError::SectionValidation {
                         section: "PROP section missing".to_string(),
                     })?;
-                    Self::build_prop(prop)
+                    Self::preserved_prop(prop).unwrap_or_else(|| Self::build_prop(prop))
                 }
                 b"BINF" => {
                     let binf = file.binf.as_ref().ok_or_else(|| Nus3bankError::SectionValidation {
@@ -47,15 +72,23 @@ impl Nus3bankWriter {
                     let grp = file.grp.as_ref().ok_or_else(|| Nus3bankError::SectionValidation {
                         section: "GRP section missing".to_string(),
                     })?;
-                    Self::build_grp(grp)
+                    Self::preserved_grp(grp).unwrap_or_else(|| Self::build_grp(grp))
                 }
                 b"DTON" => {
                     let dton = file.dton.as_ref().ok_or_else(|| Nus3bankError::SectionValidation {
                         section: "DTON section missing".to_string(),
                     })?;
-                    Self::build_dton(dton)
+                    Self::preserved_dton(dton).unwrap_or_else(|| Self::build_dton(dton))
                 }
-                b"TONE" => Self::build_tone(&active_tones)?,
+                b"MARK" => match &file.mark {
+                    Some(mark) => mark.raw.clone(),
+                    None => Self::find_unknown_section(file, *magic)?.data.clone(),
+                },
+                b"BUSC" => match &file.busc {
+                    Some(busc) => busc.raw.clone(),
+                    None => Self::find_unknown_section(file, *magic)?.data.clone(),
+                },
+                b"TONE" => tone_payload.clone(),
                 b"JUNK" => {
                     let junk = file.junk.as_ref().ok_or_else(|| Nus3bankError::SectionValidation {
                         section: "JUNK section missing".to_string(),
@@ -98,8 +131,7 @@ impl Nus3bankWriter {
         let total_size = out.len().saturating_sub(8) as u32;
         out[4..8].copy_from_slice(&BinaryReader::write_u32_le(total_size));
 
-        fs::write(path, out)?;
-        Ok(())
+        Ok(out)
     }
 
     fn find_unknown_section(file: &Nus3bankFile, magic: [u8; 4]) -> Result<&RawSection, Nus3bankError> {
@@ -110,6 +142,161 @@ impl Nus3bankWriter {
         })
     }
 
+    /// Header + payload, as the parser expects a section
+    fn section_with_header(magic: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut section = Vec::with_capacity(8 + payload.len());
+        section.extend_from_slice(magic);
+        section.extend_from_slice(&BinaryReader::write_u32_le(payload.len() as u32));
+        section.extend_from_slice(payload);
+        section
+    }
+
+    /// The GRP bytes as read, if they still parse to the current names
+    fn preserved_grp(grp: &GrpSection) -> Option<Vec<u8>> {
+        if grp.raw.is_empty() {
+            return None;
+        }
+        let parsed = Nus3bankParser::parse_grp(&Self::section_with_header(b"GRP ", &grp.raw)).ok()?;
+        (parsed.names == grp.names).then(|| grp.raw.clone())
+    }
+
+    /// The DTON bytes as read, if they still parse to the current tones. Floats are compared
+    /// bitwise so NaN values do not force a rebuild.
+    fn preserved_dton(dton: &DtonSection) -> Option<Vec<u8>> {
+        if dton.raw.is_empty() {
+            return None;
+        }
+        let parsed = Nus3bankParser::parse_dton(&Self::section_with_header(b"DTON", &dton.raw)).ok()?;
+        let same = |a: &ToneDes, b: &ToneDes| {
+            a.hash == b.hash
+                && a.unk1 == b.unk1
+                && a.name == b.name
+                && a.data.len() == b.data.len()
+                && a.data.iter().zip(&b.data).all(|(x, y)| x.to_bits() == y.to_bits())
+        };
+        let unchanged = parsed.tones.len() == dton.tones.len()
+            && parsed.tones.iter().zip(&dton.tones).all(|(a, b)| same(a, b));
+        unchanged.then(|| dton.raw.clone())
+    }
+
+    /// The PROP bytes as read, if they still parse to the current fields
+    fn preserved_prop(prop: &PropSection) -> Option<Vec<u8>> {
+        if prop.raw.is_empty() {
+            return None;
+        }
+        let parsed = Nus3bankParser::parse_prop(&Self::section_with_header(b"PROP", &prop.raw)).ok()?;
+        let unchanged = parsed.project == prop.project
+            && parsed.timestamp == prop.timestamp
+            && parsed.unk1 == prop.unk1
+            && parsed.reserved_u16 == prop.reserved_u16
+            && parsed.unk2 == prop.unk2
+            && parsed.unk3 == prop.unk3
+            && parsed.layout == prop.layout;
+        unchanged.then(|| prop.raw.clone())
+    }
+
+    /// The TONE and PACK bytes as read, if no tone was added, removed or changed. Both go
+    /// together: the TONE entries point into the PACK layout as read, which the rebuild
+    /// would not reproduce (shared payloads, stub entries, original padding).
+    fn preserved_tone_and_pack(file: &Nus3bankFile) -> Option<(Vec<u8>, Vec<u8>)> {
+        let tone = &file.tone;
+        if tone.raw.is_empty() {
+            return None;
+        }
+        let parsed = Nus3bankParser::parse_tone(&Self::section_with_header(b"TONE", &tone.raw)).ok()?;
+        if parsed.tones.len() != tone.tones.len() {
+            return None;
+        }
+        let floats_eq = |a: &[f32], b: &[f32]| a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.to_bits() == y.to_bits());
+        for (read, current) in parsed.tones.iter().zip(&tone.tones) {
+            let same_meta = read.removed == current.removed
+                && read.meta_prefix == current.meta_prefix
+                && read.hash == current.hash
+                && read.unk1 == current.unk1
+                && read.name == current.name
+                && read.reserved0 == current.reserved0
+                && read.reserved8 == current.reserved8
+                && read.offset == current.offset
+                && read.size == current.size
+                && floats_eq(&read.param, &current.param)
+                && read.offsets == current.offsets
+                && floats_eq(&read.unkvalues, &current.unkvalues)
+                && read.unkvalues_pair_order == current.unkvalues_pair_order
+                && read.unkending == current.unkending
+                && read.end == current.end
+                && read.meta_size == current.meta_size;
+            if !same_meta {
+                return None;
+            }
+            let read_payload: &[u8] = if read.removed || read.offset < 0 || read.size < 0 {
+                &[]
+            } else {
+                let start = read.offset as usize;
+                file.pack.data.get(start..start + read.size as usize)?
+            };
+            if read_payload != current.payload.as_slice() {
+                return None;
+            }
+        }
+        Some((tone.raw.clone(), file.pack.data.clone()))
+    }
+
+    /// Check serialized bytes against the file they were built from
+    fn validate(file: &Nus3bankFile, out: &[u8]) -> Result<(), Nus3bankError> {
+        let fail = |reason: String| Err(Nus3bankError::Reconstruction { reason });
+        let u32_at = |pos: usize| -> Option<u32> {
+            out.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        };
+
+        if out.len() < 0x14 || &out[0..4] != b"NUS3" || &out[8..16] != b"BANKTOC " {
+            return fail("missing NUS3/BANKTOC header".to_string());
+        }
+        if u32_at(4) != Some(out.len() as u32 - 8) {
+            return fail("header size does not match the file length".to_string());
+        }
+        let toc_size = u32_at(16).unwrap_or(0) as usize;
+        let count = u32_at(20).unwrap_or(0) as usize;
+        if toc_size != 4 + count * 8 {
+            return fail("BANKTOC size does not match its entry count".to_string());
+        }
+
+        // Sections must follow the TOC in order and end exactly at the end of the file.
+        let mut pos = 0x14 + toc_size;
+        for i in 0..count {
+            let entry = 0x18 + i * 8;
+            let (magic, size) = (&out[entry..entry + 4], u32_at(entry + 4).unwrap_or(0));
+            if out.get(pos..pos + 4) != Some(magic) || u32_at(pos + 4) != Some(size) {
+                return fail(format!(
+                    "section {} does not match its TOC entry",
+                    String::from_utf8_lossy(magic)
+                ));
+            }
+            pos += 8 + size as usize;
+        }
+        if pos != out.len() {
+            return fail(format!("sections end at {} of {} bytes", pos, out.len()));
+        }
+
+        // Every track must read back with the payload it was given.
+        let reparsed = Nus3bankParser::parse_bytes(out, file.file_path.clone())?;
+        let expected: Vec<&ToneMeta> = file.tone.tones.iter().filter(|t| !t.removed).collect();
+        let actual: Vec<&ToneMeta> = reparsed.tone.tones.iter().filter(|t| !t.removed).collect();
+        if expected.len() != actual.len() {
+            return fail(format!("{} tracks written, {} read back", expected.len(), actual.len()));
+        }
+        for (expected, actual) in expected.iter().zip(&actual) {
+            // Some banks point payload-less tones at offset -1
+            let in_pack = actual.payload.is_empty()
+                || actual.offset >= 0
+                && actual.size >= 0
+                && (actual.offset as usize + actual.size as usize) <= reparsed.pack.data.len();
+            if !in_pack || actual.payload != expected.payload {
+                return fail(format!("payload of \"{}\" does not read back", expected.name));
+            }
+        }
+        Ok(())
+    }
+
     fn build_pack_payload(tones: &mut [ToneMeta]) -> Vec<u8> {
         let mut pack: Vec<u8> = Vec::new();
         for t in tones.iter_mut() {
@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+/// Name eframe stores the app state under, shared with `run_native` in main.rs
+pub const APP_NAME: &str = "EXVS2 Audio Editor";
+
+const MARKER_FILE: &str = "session.lock";
+
+/// Marks a running session on disk. The marker is removed when the app is dropped on a
+/// normal exit, so finding it at startup means the last session crashed or was killed.
+pub struct SessionGuard {
+    path: PathBuf,
+}
+
+impl SessionGuard {
+    /// Start a session; also returns whether the previous one ended without cleaning up
+    pub fn start() -> (Option<Self>, bool) {
+        let Some(path) = marker_path() else {
+            return (None, false);
+        };
+        let crashed = path.exists();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        match std::fs::write(&path, std::process::id().to_string()) {
+            Ok(()) => (Some(Self { path }), crashed),
+            Err(e) => {
+                println!("Failed to write session marker {}: {}", path.display(), e);
+                (None, crashed)
+            }
+        }
+    }
+}
+
+impl Drop for SessionGuard {
+    fn drop(&mut self) {
+        // A panic unwinding through the app is a crash, keep the marker for the next launch
+        if !std::thread::panicking() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn marker_path() -> Option<PathBuf> {
+    eframe::storage_dir(APP_NAME).map(|dir| dir.join(MARKER_FILE))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn marker_path() -> Option<PathBuf> {
+    None
+}
+
+/// Storage holding only the raw app state put aside by safe mode, so it can still be
+/// decoded with `eframe::get_value` if the user asks for it back
+pub struct SavedState(pub String);
+
+impl eframe::Storage for SavedState {
+    fn get_string(&self, key: &str) -> Option<String> {
+        (key == eframe::APP_KEY).then(|| self.0.clone())
+    }
+
+    fn set_string(&mut self, _key: &str, _value: String) {}
+
+    fn flush(&mut self) {}
+}
@@ -0,0 +1,328 @@
+use once_cell::sync::Lazy;
+use std::cell::RefCell;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::Mutex;
+
+/// File name of the vgmstream command-line decoder on this platform
+#[cfg(windows)]
+pub const VGMSTREAM_BINARY: &str = "vgmstream-cli.exe";
+#[cfg(not(windows))]
+pub const VGMSTREAM_BINARY: &str = "vgmstream-cli";
+
+/// File name of ffmpeg on this platform
+#[cfg(windows)]
+pub const FFMPEG_BINARY: &str = "ffmpeg.exe";
+#[cfg(not(windows))]
+pub const FFMPEG_BINARY: &str = "ffmpeg";
+
+// Path chosen in Settings; overrides discovery when set
+static CONFIGURED_VGMSTREAM_PATH: Lazy<Mutex<Option<PathBuf>>> = Lazy::new(|| Mutex::new(None));
+
+thread_local! {
+    // Log entries collected by `capture_log` on this thread; None when nothing captures
+    static CAPTURED_LOG: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// Run `f` and return, with its result, the command line and output of every tool it ran
+/// and the warnings it logged. Captures nest: an outer capture sees the inner entries too.
+pub fn capture_log<R>(f: impl FnOnce() -> R) -> (R, Vec<String>) {
+    let outer = CAPTURED_LOG.with(|log| log.replace(Some(Vec::new())));
+    let result = f();
+    let entries = CAPTURED_LOG.with(|log| log.replace(outer)).unwrap_or_default();
+    CAPTURED_LOG.with(|log| {
+        if let Some(outer) = log.borrow_mut().as_mut() {
+            outer.extend(entries.iter().cloned());
+        }
+    });
+    (result, entries)
+}
+
+/// Print a warning and keep it in the log being captured, if any
+pub fn log_warning(message: &str) {
+    println!("Warning: {}", message);
+    push_log_entry(format!("Warning: {}", message));
+}
+
+fn push_log_entry(entry: String) {
+    CAPTURED_LOG.with(|log| {
+        if let Some(entries) = log.borrow_mut().as_mut() {
+            entries.push(entry);
+        }
+    });
+}
+
+/// Keep a tool run in the log being captured: command line, exit status, stdout and stderr.
+/// The runners call it themselves; code that runs their `command()` directly calls it too.
+pub fn log_run<S: AsRef<std::ffi::OsStr>>(program: &Path, args: &[S], output: &std::io::Result<Output>) {
+    let mut entry = format!("$ {}", program.file_name().unwrap_or_default().to_string_lossy());
+    for arg in args {
+        entry.push(' ');
+        entry.push_str(&arg.as_ref().to_string_lossy());
+    }
+    match output {
+        Ok(output) => {
+            entry.push_str(&format!("\n{}", output.status));
+            for (name, stream) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+                let text = String::from_utf8_lossy(stream);
+                if !text.trim().is_empty() {
+                    entry.push_str(&format!("\n{}:\n{}", name, text.trim_end()));
+                }
+            }
+        }
+        Err(e) => entry.push_str(&format!("\nfailed to start: {}", e)),
+    }
+    push_log_entry(entry);
+}
+
+/// Locates vgmstream-cli and runs it without a console window.
+///
+/// The binary is looked up in this order: the path configured in Settings, `tools/` in the
+/// working directory, `tools/` next to the executable, then every directory on `PATH`.
+#[derive(Debug, Clone)]
+pub struct VgmstreamRunner {
+    path: PathBuf,
+}
+
+impl VgmstreamRunner {
+    /// Use a specific binary, or go back to discovery with `None`
+    pub fn set_configured_path(path: Option<PathBuf>) {
+        if let Ok(mut configured) = CONFIGURED_VGMSTREAM_PATH.lock() {
+            *configured = path;
+        }
+    }
+
+    pub fn configured_path() -> Option<PathBuf> {
+        CONFIGURED_VGMSTREAM_PATH.lock().ok().and_then(|p| p.clone())
+    }
+
+    /// Find the binary. A configured path that does not exist is an error rather than
+    /// silently falling back to another copy.
+    pub fn locate() -> Result<Self, String> {
+        if let Some(path) = Self::configured_path() {
+            return if path.is_file() {
+                Ok(Self { path })
+            } else {
+                Err(format!("vgmstream-cli not found at configured path {:?}", path))
+            };
+        }
+
+        find_in_dirs(&search_dirs(), VGMSTREAM_BINARY)
+            .map(|path| Self { path })
+            .ok_or_else(|| {
+                format!(
+                    "vgmstream-cli not found. Put {} in the tools folder, install it on PATH or set its location in Settings",
+                    VGMSTREAM_BINARY
+                )
+            })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A command for the binary, with the console window suppressed on Windows
+    pub fn command(&self) -> Command {
+        hidden_command(&self.path)
+    }
+
+    /// Run with `args` and wait. A non-zero exit is turned into an error carrying stderr.
+    pub fn run<S: AsRef<std::ffi::OsStr>>(&self, args: &[S]) -> Result<Output, String> {
+        let output = self.command().args(args).output();
+        log_run(&self.path, args, &output);
+        let output = output.map_err(|e| format!("Failed to run vgmstream-cli: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "vgmstream-cli error: {}",
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+        Ok(output)
+    }
+}
+
+/// Locates ffmpeg, used to encode exports to OGG Vorbis and MP3, which have no encoder in
+/// the app itself. It is searched for like vgmstream-cli, without a Settings override.
+#[derive(Debug, Clone)]
+pub struct FfmpegRunner {
+    path: PathBuf,
+}
+
+impl FfmpegRunner {
+    pub fn locate() -> Result<Self, String> {
+        find_in_dirs(&search_dirs(), FFMPEG_BINARY)
+            .map(|path| Self { path })
+            .ok_or_else(|| {
+                format!(
+                    "ffmpeg not found. Put {} in the tools folder or install it on PATH",
+                    FFMPEG_BINARY
+                )
+            })
+    }
+
+    /// Run with `args` and wait, without a console window on Windows
+    pub fn run<S: AsRef<std::ffi::OsStr>>(&self, args: &[S]) -> Result<Output, String> {
+        let output = hidden_command(&self.path).args(args).output();
+        log_run(&self.path, args, &output);
+        let output = output.map_err(|e| format!("Failed to run ffmpeg: {}", e))?;
+        if !output.status.success() {
+            return Err(format!("ffmpeg error: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(output)
+    }
+}
+
+/// Runs a program named on a user-configured command line, such as an encoder chain. A bare
+/// program name is searched for like vgmstream-cli; paths are used as given.
+#[derive(Debug, Clone)]
+pub struct ExternalRunner {
+    path: PathBuf,
+}
+
+impl ExternalRunner {
+    pub fn locate(program: &str) -> Result<Self, String> {
+        let given = Path::new(program);
+        if given.components().count() > 1 || given.is_absolute() {
+            return if given.is_file() {
+                Ok(Self { path: given.to_path_buf() })
+            } else {
+                Err(format!("{} not found", program))
+            };
+        }
+        let mut names = vec![program.to_string()];
+        if cfg!(windows) && given.extension().is_none() {
+            names.insert(0, format!("{}.exe", program));
+        }
+        let dirs = search_dirs();
+        names
+            .iter()
+            .find_map(|name| find_in_dirs(&dirs, name))
+            .map(|path| Self { path })
+            .ok_or_else(|| format!("{} not found. Put it in the tools folder or install it on PATH", program))
+    }
+
+    /// Run with `args` and wait, without a console window on Windows
+    pub fn run<S: AsRef<std::ffi::OsStr>>(&self, args: &[S]) -> Result<Output, String> {
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let output = hidden_command(&self.path).args(args).output();
+        log_run(&self.path, args, &output);
+        let output = output.map_err(|e| format!("Failed to run {}: {}", name, e))?;
+        if !output.status.success() {
+            let mut message = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if message.is_empty() {
+                message = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            }
+            return Err(format!("{} error: {}", name, message));
+        }
+        Ok(output)
+    }
+}
+
+/// A command for `program`, with the console window suppressed on Windows
+pub(crate) fn hidden_command(program: &Path) -> Command {
+    #[cfg_attr(not(windows), allow(unused_mut))]
+    let mut command = Command::new(program);
+    #[cfg(windows)]
+    {
+        use winapi::um::winbase::CREATE_NO_WINDOW;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    command
+}
+
+/// Split a command line into arguments; double quotes group words and are removed
+pub fn split_command(template: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    for c in template.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_arg = true;
+            }
+        }
+    }
+    if in_quotes {
+        return Err("Unclosed quote in command".to_string());
+    }
+    if has_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// `tools/` in the working directory, `tools/` next to the executable, then `PATH`
+fn search_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from("tools")];
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        dirs.push(exe_dir.join("tools"));
+    }
+    if let Some(path_var) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path_var));
+    }
+    dirs
+}
+
+/// First `dir/name` that is an existing file
+fn find_in_dirs(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter().map(|dir| dir.join(name)).find(|candidate| candidate.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_binary_in_first_matching_dir() {
+        let root = std::env::temp_dir().join(format!("vgmstream_runner_test_{}", std::process::id()));
+        let (empty, first, second) = (root.join("empty"), root.join("first"), root.join("second"));
+        for dir in [&empty, &first, &second] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        std::fs::write(first.join(VGMSTREAM_BINARY), b"").unwrap();
+        std::fs::write(second.join(VGMSTREAM_BINARY), b"").unwrap();
+        // A directory with the binary's name is not a match
+        std::fs::create_dir_all(empty.join(VGMSTREAM_BINARY)).unwrap();
+
+        let dirs = vec![empty.clone(), first.clone(), second.clone()];
+        assert_eq!(find_in_dirs(&dirs, VGMSTREAM_BINARY), Some(first.join(VGMSTREAM_BINARY)));
+        assert_eq!(find_in_dirs(&dirs[..1], VGMSTREAM_BINARY), None);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn splits_command_templates() {
+        assert_eq!(
+            split_command(r#"tools/enc -c "{input}" {output}  --opusheader namco"#).unwrap(),
+            vec!["tools/enc", "-c", "{input}", "{output}", "--opusheader", "namco"]
+        );
+        assert_eq!(split_command(r#"a "" b"#).unwrap(), vec!["a", "", "b"]);
+        assert!(split_command(r#"a "b"#).is_err());
+    }
+
+    #[test]
+    fn captured_logs_nest_and_stop_with_the_capture() {
+        log_warning("not captured");
+        let ((_, inner), outer) = capture_log(|| {
+            log_warning("outer");
+            capture_log(|| log_warning("inner"))
+        });
+        assert_eq!(inner, vec!["Warning: inner"]);
+        assert_eq!(outer, vec!["Warning: outer", "Warning: inner"]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fmt;
+
+/// Identity of a track in the pending-change stores (replacements, loop settings, additions,
+/// removals) shared by the editor UI, the NUS3BANK replacer and the audio player.
+///
+/// NUS3AUDIO tracks are identified by name and numeric id, NUS3BANK tracks by hex id and
+/// name. Tracks queued by Add Audio in a NUS3BANK get their own variant so their playback
+/// data never collides with a replacement of an existing track.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TrackKey {
+    Audio { name: String, id: String },
+    Bank { hex_id: String, name: String },
+    Added { hex_id: String, name: String },
+}
+
+impl TrackKey {
+    pub fn audio(name: &str, id: &str) -> Self {
+        TrackKey::Audio {
+            name: name.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn bank(hex_id: &str, name: &str) -> Self {
+        TrackKey::Bank {
+            hex_id: hex_id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn added(hex_id: &str, name: &str) -> Self {
+        TrackKey::Added {
+            hex_id: hex_id.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            TrackKey::Audio { name, .. } | TrackKey::Bank { name, .. } | TrackKey::Added { name, .. } => name,
+        }
+    }
+
+    /// Hex id of a NUS3BANK track (existing or added)
+    pub fn hex_id(&self) -> Option<&str> {
+        match self {
+            TrackKey::Bank { hex_id, .. } | TrackKey::Added { hex_id, .. } => Some(hex_id),
+            TrackKey::Audio { .. } => None,
+        }
+    }
+
+    pub fn is_added(&self) -> bool {
+        matches!(self, TrackKey::Added { .. })
+    }
+
+    /// Parse a key written in one of the older string formats: `name:id` (NUS3AUDIO),
+    /// `hex_id:name` or `name:hex_id` (NUS3BANK), optionally prefixed with `ADD_`
+    pub fn parse_legacy(key: &str) -> Option<Self> {
+        if let Some(rest) = key.strip_prefix("ADD_") {
+            return match Self::parse_legacy(rest)? {
+                TrackKey::Bank { hex_id, name } => Some(TrackKey::Added { hex_id, name }),
+                _ => None,
+            };
+        }
+        if key.starts_with("0x") {
+            let (hex_id, name) = key.split_once(':')?;
+            return Some(Self::bank(hex_id, name));
+        }
+        let (left, right) = key.rsplit_once(':')?;
+        if right.starts_with("0x") {
+            Some(Self::bank(right, left))
+        } else {
+            Some(Self::audio(left, right))
+        }
+    }
+}
+
+/// Formats the key in its legacy string form, so logs and anything still keyed by strings
+/// see the same text as before
+impl fmt::Display for TrackKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackKey::Audio { name, id } => write!(f, "{}:{}", name, id),
+            TrackKey::Bank { hex_id, name } => write!(f, "{}:{}", hex_id, name),
+            TrackKey::Added { hex_id, name } => write!(f, "ADD_{}:{}", hex_id, name),
+        }
+    }
+}
+
+/// Stored in its string form, so maps keyed by track stay JSON objects
+impl serde::Serialize for TrackKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for TrackKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        Self::parse_legacy(&key).ok_or_else(|| serde::de::Error::custom(format!("invalid track key: {}", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_keys_round_trip() {
+        for key in [
+            TrackKey::audio("bgm_01", "3"),
+            TrackKey::bank("0xb2", "se_hit"),
+            TrackKey::added("0x1f", "new_voice"),
+        ] {
+            assert_eq!(TrackKey::parse_legacy(&key.to_string()), Some(key));
+        }
+        // NUS3BANK keys were also written name-first in some places
+        assert_eq!(TrackKey::parse_legacy("se_hit:0xb2"), Some(TrackKey::bank("0xb2", "se_hit")));
+        assert_eq!(TrackKey::parse_legacy("ADD_3:bgm"), None);
+        assert_eq!(TrackKey::parse_legacy("no_separator"), None);
+
+        let json = serde_json::to_string(&TrackKey::bank("0xb2", "se_hit")).unwrap();
+        assert_eq!(json, "\"0xb2:se_hit\"");
+        assert_eq!(serde_json::from_str::<TrackKey>(&json).unwrap(), TrackKey::bank("0xb2", "se_hit"));
+    }
+}
@@ -1,6 +1,8 @@
+use std::cell::Cell;
 use std::fs::File;
 use std::io::Read;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 
 use kira::{
     AudioManager,
@@ -8,23 +10,115 @@ use kira::{
     DefaultBackend,
     Tween,
     sound::FromFileError,
+    sound::PlaybackState,
+    sound::Region,
+    sound::static_sound::{StaticSoundData, StaticSoundHandle},
     sound::streaming::{StreamingSoundData, StreamingSoundHandle},
 };
 
 use crate::ui::audio_player::audio_backend::trait_def::AudioBackend;
+use crate::ui::audio_player::loudness;
+use crate::ui::main_area::decode_pool::{self, Priority};
+
+/// Previews up to this many frames are decoded into memory on the decode pool while they
+/// stream, so seeking within them becomes instant and gapless; longer ones only stream.
+/// kira holds every frame as two f32s, so the decoded PCM stays under 128 MB.
+const MAX_DECODED_FRAMES: usize = 16 * 1024 * 1024;
+
+/// File identity for the decoded PCM cache: path, size and modification time, so a
+/// preview file rewritten in place is decoded again
+type DecodedKey = (String, u64, Option<SystemTime>);
+
+/// Result of a decode running on the pool, filled in when it finishes
+type PendingDecode = Arc<Mutex<Option<Result<StaticSoundData, String>>>>;
+
+/// The preview sound, decoded in memory or streamed
+enum PreviewHandle {
+    Decoded(StaticSoundHandle),
+    Streaming(StreamingSoundHandle<FromFileError>),
+}
+
+impl PreviewHandle {
+    fn position(&self) -> f64 {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.position(),
+            PreviewHandle::Streaming(handle) => handle.position(),
+        }
+    }
+
+    fn state(&self) -> PlaybackState {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.state(),
+            PreviewHandle::Streaming(handle) => handle.state(),
+        }
+    }
+
+    fn seek_to(&mut self, position: f64) {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.seek_to(position),
+            PreviewHandle::Streaming(handle) => handle.seek_to(position),
+        }
+    }
+
+    fn set_volume(&mut self, volume_db: f32) {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.set_volume(volume_db, Tween::default()),
+            PreviewHandle::Streaming(handle) => handle.set_volume(volume_db, Tween::default()),
+        }
+    }
+
+    fn set_loop_region(&mut self, region: Option<Region>) {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.set_loop_region(region),
+            PreviewHandle::Streaming(handle) => handle.set_loop_region(region),
+        }
+    }
+
+    fn pause(&mut self) {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.pause(Tween::default()),
+            PreviewHandle::Streaming(handle) => handle.pause(Tween::default()),
+        }
+    }
+
+    fn resume(&mut self) {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.resume(Tween::default()),
+            PreviewHandle::Streaming(handle) => handle.resume(Tween::default()),
+        }
+    }
+
+    fn stop(&mut self) {
+        match self {
+            PreviewHandle::Decoded(handle) => handle.stop(Tween::default()),
+            PreviewHandle::Streaming(handle) => handle.stop(Tween::default()),
+        }
+    }
+}
 
 /// Native audio backend implementation using kira
 pub struct NativeAudioBackend {
     /// Audio manager for playback
     manager: Option<AudioManager<DefaultBackend>>,
     /// Handle to the currently playing sound
-    sound_handle: Option<StreamingSoundHandle<FromFileError>>,
-    /// Current position in seconds
+    sound_handle: Option<PreviewHandle>,
+    /// Earlier previews left to finish while the current one plays, oldest first
+    overlapping: Vec<PreviewHandle>,
+    /// Most previews sounding at once, the current one included
+    max_instances: usize,
+    /// PCM of the last file decoded into memory, reused when it is played again
+    decoded: Option<(DecodedKey, StaticSoundData)>,
+    /// Decode of the previewed file still running on the pool
+    decoding: Option<(DecodedKey, PendingDecode)>,
+    /// File of the current preview
+    current_key: Option<DecodedKey>,
+    /// Last known position in seconds (used when no sound is loaded)
     current_position: f32,
-    /// Start time of playback for position tracking
-    playback_start_time: Option<Instant>,
-    /// Position when playback started
-    playback_start_position: f32,
+    /// Seek sent to the audio thread that kira has not reported back yet.
+    /// Reported positions lag one audio block behind a seek, so the target is shown until then.
+    pending_seek: Cell<Option<(f32, Instant)>>,
+    /// Active loop region in seconds
+    loop_region: Option<(f32, Option<f32>)>,
     /// Audio duration in seconds
     duration: f32,
     /// Whether audio is currently loaded
@@ -35,6 +129,10 @@ pub struct NativeAudioBackend {
     initialized: bool,
     /// Current volume level (0.0 - 1.0)
     volume: f32,
+    /// Decoded trigger pad sounds by slot
+    pads: Vec<Option<StaticSoundData>>,
+    /// Currently sounding pad instances by slot
+    pad_handles: Vec<Option<StaticSoundHandle>>,
 }
 
 impl NativeAudioBackend {
@@ -43,14 +141,21 @@ impl NativeAudioBackend {
         Self {
             manager: None,
             sound_handle: None,
+            overlapping: Vec::new(),
+            max_instances: 1,
+            decoded: None,
+            decoding: None,
+            current_key: None,
             current_position: 0.0,
-            playback_start_time: None,
-            playback_start_position: 0.0,
+            pending_seek: Cell::new(None),
+            loop_region: None,
             duration: 0.0,
             audio_loaded: false,
             is_playing: false,
             initialized: false,
             volume: 1.0, // Default volume is 100%
+            pads: Vec::new(),
+            pad_handles: Vec::new(),
         }
     }
 
@@ -109,8 +214,90 @@ impl NativeAudioBackend {
         }
     }
 
+    fn file_key(file_path: &str) -> Result<DecodedKey, String> {
+        let metadata = std::fs::metadata(file_path).map_err(|e| format!("Failed to load audio file: {}", e))?;
+        Ok((file_path.to_string(), metadata.len(), metadata.modified().ok()))
+    }
+
+    /// Decoded PCM of a file, when it is in the cache
+    fn cached_sound(&self, key: &DecodedKey) -> Option<StaticSoundData> {
+        self.decoded.as_ref().filter(|(cached, _)| cached == key).map(|(_, data)| data.clone())
+    }
+
+    /// Decode a file into memory on the decode pool, unless that is already under way
+    fn start_decode(&mut self, key: DecodedKey) {
+        if self.decoding.as_ref().is_some_and(|(decoding, _)| *decoding == key) {
+            return;
+        }
+        let pending: PendingDecode = Arc::new(Mutex::new(None));
+        let result = pending.clone();
+        let path = key.0.clone();
+        decode_pool::spawn(Priority::Preview, move || {
+            let data = StaticSoundData::from_file(&path).map_err(|e| e.to_string());
+            if let Ok(mut result) = result.lock() {
+                *result = Some(data);
+            }
+        });
+        self.decoding = Some((key, pending));
+    }
+
+    /// Move a finished decode into the cache
+    fn collect_decoded(&mut self) {
+        let Some((key, pending)) = &self.decoding else {
+            return;
+        };
+        let Some(result) = pending.lock().ok().and_then(|mut p| p.take()) else {
+            return;
+        };
+        match result {
+            Ok(data) => self.decoded = Some((key.clone(), data)),
+            Err(e) => println!("Preview stays streamed, decoding failed: {}", e),
+        }
+        self.decoding = None;
+    }
+
+    /// Carry on a playing stream from the decoded PCM once it is ready, starting at
+    /// `position`. Returns whether the preview switched.
+    fn switch_to_decoded(&mut self, position: f32) -> bool {
+        self.collect_decoded();
+        let Some(mut data) = self.current_key.as_ref().and_then(|key| self.cached_sound(key)) else {
+            return false;
+        };
+        data = data.start_position(position as f64);
+        if let Some((start, end)) = self.loop_region {
+            data = data.loop_region(Self::to_region(start, end));
+        }
+        let Some(manager) = self.manager.as_mut() else {
+            return false;
+        };
+        let Ok(handle) = manager.play(data) else {
+            return false;
+        };
+        let mut handle = PreviewHandle::Decoded(handle);
+        handle.set_volume(Self::volume_to_decibels(self.volume));
+        if let Some(mut stream) = self.sound_handle.replace(handle) {
+            stream.stop();
+        }
+        true
+    }
+
+    /// Position reported by the audio thread, based on the frames it has actually played
+    fn handle_position(&self) -> Option<f32> {
+        self.sound_handle
+            .as_ref()
+            .map(|handle| handle.position() as f32)
+    }
+
+    fn to_region(start: f32, end: Option<f32>) -> Region {
+        match end {
+            Some(end) if end > start => Region::from(start as f64..end as f64),
+            _ => Region::from(start as f64..),
+        }
+    }
+
     fn volume_to_decibels(volume: f32) -> f32 {
-        let clamped = volume.clamp(0.0, 1.0);
+        // Above 1.0 only when preview normalization boosts a quiet file
+        let clamped = volume.clamp(0.0, 4.0);
         if clamped <= 0.0 {
             -80.0
         } else {
@@ -121,6 +308,15 @@ impl NativeAudioBackend {
 
 impl AudioBackend for NativeAudioBackend {
     fn init(&mut self) -> Result<(), String> {
+        // Re-initializing drops the old output stream and any sound playing on it
+        self.sound_handle = None;
+        self.overlapping.clear();
+        self.pad_handles.clear();
+        self.manager = None;
+        self.audio_loaded = false;
+        self.is_playing = false;
+        self.pending_seek.set(None);
+
         match AudioManager::<DefaultBackend>::new(AudioManagerSettings::default()) {
             Ok(manager) => {
                 self.manager = Some(manager);
@@ -139,42 +335,65 @@ impl AudioBackend for NativeAudioBackend {
             return Err("Audio backend not initialized".to_string());
         }
 
-        let manager = self.manager.as_mut().ok_or_else(|| "Audio manager not available".to_string())?;
-
         if let Some(mut handle) = self.sound_handle.take() {
-            handle.stop(Tween::default());
+            handle.stop();
         }
 
-        let sound_data = StreamingSoundData::from_file(file_path)
-            .map_err(|e| format!("Failed to load audio file: {}", e))?;
+        let key = Self::file_key(file_path)?;
+        self.collect_decoded();
+        let region = self.loop_region.map(|(start, end)| Self::to_region(start, end));
+        let (decoded_duration, mut handle) = match self.cached_sound(&key) {
+            Some(mut sound_data) => {
+                if let Some(region) = region {
+                    sound_data = sound_data.loop_region(region);
+                }
+                let duration = sound_data.duration().as_secs_f32();
+                let manager = self.manager.as_mut().ok_or_else(|| "Audio manager not available".to_string())?;
+                let handle = manager.play(sound_data)
+                    .map_err(|e| format!("Failed to start audio playback: {}", e))?;
+                (duration, PreviewHandle::Decoded(handle))
+            }
+            None => {
+                let mut sound_data = StreamingSoundData::from_file(file_path)
+                    .map_err(|e| format!("Failed to load audio file: {}", e))?;
+                if sound_data.num_frames() <= MAX_DECODED_FRAMES {
+                    self.start_decode(key.clone());
+                }
+                if let Some(region) = region {
+                    sound_data = sound_data.loop_region(region);
+                }
+                let duration = sound_data.duration().as_secs_f32();
+                let manager = self.manager.as_mut().ok_or_else(|| "Audio manager not available".to_string())?;
+                let handle = manager.play(sound_data)
+                    .map_err(|e| format!("Failed to start audio playback: {}", e))?;
+                (duration, PreviewHandle::Streaming(handle))
+            }
+        };
 
-        let mut handle = manager.play(sound_data)
-            .map_err(|e| format!("Failed to start audio playback: {}", e))?;
+        // Frame count from the decoder is exact; the WAV header estimate is a fallback
+        self.duration = if decoded_duration > 0.0 {
+            decoded_duration
+        } else {
+            self.estimate_wav_duration_from_file(file_path)
+        };
 
-        self.duration = self.estimate_wav_duration_from_file(file_path);
+        self.current_key = Some(key);
         self.current_position = 0.0;
-        self.playback_start_time = Some(Instant::now());
-        self.playback_start_position = 0.0;
+        self.pending_seek.set(None);
         self.audio_loaded = true;
         self.is_playing = true;
 
-        let volume_db = Self::volume_to_decibels(self.volume);
-        let _ = handle.set_volume(volume_db, Tween::default());
+        handle.set_volume(Self::volume_to_decibels(self.volume));
 
         self.sound_handle = Some(handle);
         Ok(())
     }
 
     fn pause(&mut self) -> Result<(), String> {
+        let position = self.get_position();
         if let Some(handle) = &mut self.sound_handle {
-            if self.is_playing {
-                if let Some(start_time) = self.playback_start_time {
-                    let elapsed = start_time.elapsed().as_secs_f32();
-                    self.current_position = self.playback_start_position + elapsed;
-                }
-            }
-
-            handle.pause(Tween::default());
+            self.current_position = position;
+            handle.pause();
             self.is_playing = false;
             Ok(())
         } else {
@@ -184,12 +403,8 @@ impl AudioBackend for NativeAudioBackend {
 
     fn resume(&mut self) -> Result<(), String> {
         if let Some(handle) = &mut self.sound_handle {
-            let volume_db = Self::volume_to_decibels(self.volume);
-            let _ = handle.set_volume(volume_db, Tween::default());
-            handle.resume(Tween::default());
-
-            self.playback_start_time = Some(Instant::now());
-            self.playback_start_position = self.current_position;
+            handle.set_volume(Self::volume_to_decibels(self.volume));
+            handle.resume();
             self.is_playing = true;
             Ok(())
         } else {
@@ -200,11 +415,19 @@ impl AudioBackend for NativeAudioBackend {
     fn stop(&mut self) -> Result<(), String> {
         if let Some(mut handle) = self.sound_handle.take() {
             self.current_position = 0.0;
-            self.playback_start_position = 0.0;
-            self.playback_start_time = None;
+            self.pending_seek.set(None);
             self.is_playing = false;
 
-            handle.stop(Tween::default());
+            if self.max_instances > 1 && handle.state() == PlaybackState::Playing {
+                self.release_finished();
+                self.overlapping.push(handle);
+                // The current sound counts towards the limit too
+                while self.overlapping.len() >= self.max_instances {
+                    self.overlapping.remove(0).stop();
+                }
+            } else {
+                handle.stop();
+            }
             Ok(())
         } else {
             Err("No audio playing".to_string())
@@ -218,30 +441,52 @@ impl AudioBackend for NativeAudioBackend {
 
         let clamped_position = position_secs.clamp(0.0, self.duration);
         self.current_position = clamped_position;
-        self.playback_start_position = clamped_position;
+
+        // A playing stream hands over to its decoded PCM at the first seek after decoding
+        let streaming = matches!(self.sound_handle, Some(PreviewHandle::Streaming(_)));
+        if streaming && self.is_playing && self.switch_to_decoded(clamped_position) {
+            self.pending_seek.set(Some((clamped_position, Instant::now())));
+            return Ok(());
+        }
 
         if let Some(handle) = &mut self.sound_handle {
             handle.seek_to(clamped_position as f64);
-            if self.is_playing {
-                self.playback_start_time = Some(Instant::now());
-            }
+            self.pending_seek.set(Some((clamped_position, Instant::now())));
             Ok(())
         } else {
             Err("No audio handle available".to_string())
         }
     }
 
+    fn stop_all(&mut self) {
+        for mut handle in self.overlapping.drain(..) {
+            handle.stop();
+        }
+        let _ = self.stop();
+    }
+
+    fn set_max_instances(&mut self, max: usize) {
+        self.max_instances = max.max(1);
+        while self.overlapping.len() >= self.max_instances {
+            self.overlapping.remove(0).stop();
+        }
+    }
+
+    fn release_finished(&mut self) {
+        self.overlapping.retain(|handle| handle.state() != PlaybackState::Stopped);
+    }
+
     fn set_volume(&mut self, volume: f32) -> Result<(), String> {
         self.volume = volume;
-        if let Some(handle) = &mut self.sound_handle {
-            let volume_db = Self::volume_to_decibels(self.volume);
-            let _ = handle.set_volume(volume_db, Tween::default());
+        let volume_db = Self::volume_to_decibels(self.volume);
+        for handle in self.sound_handle.iter_mut().chain(self.overlapping.iter_mut()) {
+            handle.set_volume(volume_db);
         }
         Ok(())
     }
 
     fn is_playing(&self) -> bool {
-        if !self.audio_loaded {
+        if !self.audio_loaded || self.has_finished() {
             return false;
         }
 
@@ -253,17 +498,104 @@ impl AudioBackend for NativeAudioBackend {
     }
 
     fn get_position(&self) -> f32 {
-        if !self.is_playing {
+        let Some(position) = self.handle_position() else {
             return self.current_position;
+        };
+
+        if let Some((target, sent_at)) = self.pending_seek.get() {
+            // Keep showing the seek target until the audio thread has applied it
+            if (position - target).abs() > 0.25 && sent_at.elapsed() < Duration::from_millis(500) {
+                return target;
+            }
+            self.pending_seek.set(None);
         }
 
-        if let Some(start_time) = self.playback_start_time {
-            let elapsed = start_time.elapsed().as_secs_f32();
-            let position = self.playback_start_position + elapsed;
+        if self.duration > 0.0 {
             position.min(self.duration)
         } else {
-            self.current_position
+            position
+        }
+    }
+
+    fn set_loop_region(&mut self, start: Option<f32>, end: Option<f32>) -> Result<(), String> {
+        self.loop_region = start.map(|s| (s.max(0.0), end)).or(end.map(|e| (0.0, Some(e))));
+        if let Some(handle) = &mut self.sound_handle {
+            handle.set_loop_region(self.loop_region.map(|(start, end)| Self::to_region(start, end)));
+        }
+        Ok(())
+    }
+
+    fn measure_loudness(&self, file_path: &str) -> Option<f32> {
+        let data = StaticSoundData::from_file(file_path).ok()?;
+        let mono: Vec<f32> = data
+            .frames
+            .iter()
+            .map(|frame| (frame.left + frame.right) * 0.5)
+            .collect();
+        loudness::gated_rms_dbfs(&mono, data.sample_rate)
+    }
+
+    fn load_pad(&mut self, slot: usize, file_path: &str) -> Result<(), String> {
+        let data = StaticSoundData::from_file(file_path)
+            .map_err(|e| format!("Failed to decode pad audio: {}", e))?;
+        if self.pads.len() <= slot {
+            self.pads.resize(slot + 1, None);
         }
+        self.pads[slot] = Some(data);
+        Ok(())
+    }
+
+    fn trigger_pad(&mut self, slot: usize) -> Result<(), String> {
+        let data = self
+            .pads
+            .get(slot)
+            .cloned()
+            .flatten()
+            .ok_or_else(|| format!("Pad {} is empty", slot + 1))?;
+        let manager = self.manager.as_mut().ok_or_else(|| "Audio manager not available".to_string())?;
+
+        if let Some(mut previous) = self.pad_handles.get_mut(slot).and_then(|h| h.take()) {
+            previous.stop(Tween::default());
+        }
+
+        let data = data.volume(Self::volume_to_decibels(self.volume));
+        let handle = manager
+            .play(data)
+            .map_err(|e| format!("Failed to trigger pad: {}", e))?;
+        if self.pad_handles.len() <= slot {
+            self.pad_handles.resize_with(slot + 1, || None);
+        }
+        self.pad_handles[slot] = Some(handle);
+        Ok(())
+    }
+
+    fn clear_pad(&mut self, slot: usize) {
+        if let Some(mut handle) = self.pad_handles.get_mut(slot).and_then(|h| h.take()) {
+            handle.stop(Tween::default());
+        }
+        if let Some(pad) = self.pads.get_mut(slot) {
+            *pad = None;
+        }
+    }
+
+    fn take_stream_error(&mut self) -> Option<String> {
+        let manager = self.manager.as_mut()?;
+        let mut last_error = None;
+        while let Some(error) = manager.backend_mut().pop_error() {
+            last_error = Some(error.to_string());
+        }
+        last_error
+    }
+
+    fn has_finished(&self) -> bool {
+        // kira moves a stream to Stopped once its last frame has played
+        self.audio_loaded
+            && self.is_playing
+            && self
+                .sound_handle
+                .as_ref()
+                .map(|handle| handle.state() == PlaybackState::Stopped)
+                .unwrap_or(false)
     }
 
     fn get_duration(&self) -> f32 {
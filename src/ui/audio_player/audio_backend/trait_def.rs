@@ -14,8 +14,18 @@ pub trait AudioBackend: std::fmt::Debug {
     /// Resume audio playback
     fn resume(&mut self) -> Result<(), String>;
     
-    /// Stop audio playback
+    /// Stop audio playback. When more than one preview may sound at once, a sound that is
+    /// still playing is left to finish alongside the next one instead.
     fn stop(&mut self) -> Result<(), String>;
+
+    /// Stop the current sound and every earlier preview still sounding
+    fn stop_all(&mut self);
+
+    /// Most previews sounding at once, the current one included; 1 plays one at a time
+    fn set_max_instances(&mut self, max: usize);
+
+    /// Drop earlier previews that have finished playing
+    fn release_finished(&mut self);
     
     /// Set the playback position in seconds
     fn set_position(&mut self, position_secs: f32) -> Result<(), String>;
@@ -28,6 +38,29 @@ pub trait AudioBackend: std::fmt::Debug {
     
     /// Get the current playback position in seconds
     fn get_position(&self) -> f32;
+
+    /// Loop playback between `start` and `end` (seconds) sample-accurately; both `None` disables looping.
+    /// Applies to the current sound and to sounds played afterwards.
+    fn set_loop_region(&mut self, start: Option<f32>, end: Option<f32>) -> Result<(), String>;
+
+    /// Decode a file and measure its gated RMS level in dBFS (`None` if it cannot be decoded or is silent)
+    fn measure_loudness(&self, file_path: &str) -> Option<f32>;
+
+    /// Decode a file fully into memory and keep it in a trigger pad slot
+    fn load_pad(&mut self, slot: usize, file_path: &str) -> Result<(), String>;
+
+    /// Play a pad immediately, restarting it if it is still sounding. Pads play alongside the main preview.
+    fn trigger_pad(&mut self, slot: usize) -> Result<(), String>;
+
+    /// Stop and forget a pad
+    fn clear_pad(&mut self, slot: usize);
+
+    /// Drain errors reported by the output stream (e.g. the device was unplugged).
+    /// Returns the last error, if any occurred since the previous call.
+    fn take_stream_error(&mut self) -> Option<String>;
+
+    /// Whether the current sound reached its end by itself (not paused or stopped by the user)
+    fn has_finished(&self) -> bool;
     
     /// Get the duration of the current audio in seconds
     fn get_duration(&self) -> f32;
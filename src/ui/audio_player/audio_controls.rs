@@ -1,18 +1,36 @@
-use super::audio_state::{AudioState, LoopMode};
-use egui::{Align, Color32, CornerRadius, Frame, Layout, RichText, Ui, widgets::Slider};
+use super::audio_state::{AudioFile, AudioState, DialogDuckMode, LoopMode};
+use super::track_view::TrackView;
+use crate::ui::main_area::{ExportUtils, FileTypeRegistry};
+use egui::{Align, Color32, CornerRadius, DragValue, Frame, Layout, RichText, Ui, widgets::Slider};
 use egui_phosphor::regular;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Repaint interval while playing, enough for a smooth playhead and timer without
+/// redrawing at the full monitor rate
+const PLAYBACK_REPAINT_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Tracks listed in the up-next hover
+const UP_NEXT_SHOWN: usize = 8;
 
 /// Audio player controls component
 pub struct AudioControls {
     /// Reference to the audio state
     audio_state: Arc<Mutex<AudioState>>,
+    /// Result of the last region export
+    export_status: Option<(String, Color32)>,
+    /// Waveform and spectrogram strip under the progress slider
+    track_view: TrackView,
 }
 
 impl AudioControls {
     /// Create a new audio controls component
     pub fn new(audio_state: Arc<Mutex<AudioState>>) -> Self {
-        Self { audio_state }
+        Self {
+            audio_state,
+            export_status: None,
+            track_view: TrackView::new(),
+        }
     }
 
     /// Render the audio controls UI
@@ -21,9 +39,9 @@ impl AudioControls {
         let state_copy = {
             let state = self.audio_state.lock().unwrap();
 
-            // Request continuous repainting if we're playing to update timers
+            // Keep the timers moving while playing; an idle player repaints only on input
             if state.is_playing {
-                ui.ctx().request_repaint();
+                ui.ctx().request_repaint_after(PLAYBACK_REPAINT_INTERVAL);
             }
 
             state.clone()
@@ -53,16 +71,12 @@ impl AudioControls {
                                     ui.horizontal(|ui| {
                                         // Track Icon
                                         let icon = match audio.file_type.as_str() {
-                                            "OPUS Audio" => regular::MUSIC_NOTE,
-                                            "IDSP Audio" => regular::HEADPHONES,
+                                            "OPUS" => regular::MUSIC_NOTE,
+                                            "IDSP" => regular::HEADPHONES,
                                             _ => regular::FILE_AUDIO,
                                         };
 
-                                        let type_color = match audio.file_type.as_str() {
-                                            "OPUS Audio" => Color32::from_rgb(100, 200, 100),
-                                            "IDSP Audio" => Color32::from_rgb(100, 150, 255),
-                                            _ => Color32::from_rgb(200, 150, 100),
-                                        };
+                                        let type_color = FileTypeRegistry::lookup(&audio.file_type).color32();
 
                                         ui.label(
                                             RichText::new(icon.to_string())
@@ -78,11 +92,14 @@ impl AudioControls {
                                                     .strong(),
                                             );
 
-                                            ui.label(
-                                                RichText::new(&audio.file_type)
-                                                    .color(type_color)
-                                                    .size(11.0),
-                                            );
+                                            ui.horizontal(|ui| {
+                                                ui.label(
+                                                    RichText::new(&audio.file_type)
+                                                        .color(type_color)
+                                                        .size(11.0),
+                                                );
+                                                Self::render_up_next(ui, &state_copy);
+                                            });
                                         });
                                     });
                                 } else {
@@ -120,6 +137,52 @@ impl AudioControls {
                                 .color(ui.visuals().weak_text_color()),
                         );
 
+                        // Preview normalization toggle
+                        let normalize_text = if state_copy.normalize_preview {
+                            format!(
+                                "Preview loudness normalize: on ({:+.1} dB on this track)\nOnly affects playback, not saved data",
+                                state_copy.preview_gain_db
+                            )
+                        } else {
+                            "Preview loudness normalize: off\nLevel-match previews without changing saved data".to_string()
+                        };
+                        let normalize_color = if state_copy.normalize_preview {
+                            Color32::from_rgb(100, 150, 255)
+                        } else {
+                            Color32::from_gray(150)
+                        };
+                        let normalize_btn = ui.add(
+                            egui::Button::new(
+                                RichText::new(regular::WAVEFORM.to_string())
+                                    .size(18.0)
+                                    .color(normalize_color),
+                            )
+                            .frame(false),
+                        );
+                        if normalize_btn.on_hover_text(normalize_text).clicked() {
+                            self.audio_state
+                                .lock()
+                                .unwrap()
+                                .set_normalize_preview(!state_copy.normalize_preview);
+                        }
+
+                        let view_color = if self.track_view.open {
+                            Color32::from_rgb(100, 150, 255)
+                        } else {
+                            Color32::from_gray(150)
+                        };
+                        let view_btn = ui.add(
+                            egui::Button::new(
+                                RichText::new(regular::WAVE_SINE.to_string())
+                                    .size(18.0)
+                                    .color(view_color),
+                            )
+                            .frame(false),
+                        );
+                        if view_btn.on_hover_text("Show waveform and spectrogram").clicked() {
+                            self.track_view.open = !self.track_view.open;
+                        }
+
                         let mut progress = state_copy.progress();
                         let slider_width = ui.available_width() - 50.0;
 
@@ -131,6 +194,20 @@ impl AudioControls {
                                 .text(""),
                         );
 
+                        // Shade the region selected for export
+                        if let Some((start, end)) = state_copy.region() {
+                            if state_copy.total_duration > 0.0 {
+                                let rect = slider_response.rect;
+                                let x = |t: f32| rect.left() + rect.width() * (t / state_copy.total_duration).clamp(0.0, 1.0);
+                                let region_rect = egui::Rect::from_x_y_ranges(x(start)..=x(end), rect.y_range());
+                                ui.painter().rect_filled(
+                                    region_rect,
+                                    2.0,
+                                    Color32::from_rgba_unmultiplied(100, 150, 255, 50),
+                                );
+                            }
+                        }
+
                         if slider_response.drag_stopped() && has_audio {
                             let mut state = self.audio_state.lock().unwrap();
                             let new_position = progress * state.total_duration;
@@ -145,6 +222,18 @@ impl AudioControls {
                         );
                     });
 
+                    // Waveform / spectrogram of the loaded track
+                    if self.track_view.open {
+                        if let Some(position) = self.track_view.show(ui, &state_copy) {
+                            self.audio_state.lock().unwrap().set_position(position);
+                        }
+                    }
+
+                    // Region Row: select part of the track and export it
+                    if has_audio {
+                        self.render_region_controls(ui, &state_copy);
+                    }
+
                     ui.add_space(6.0);
 
                     // Bottom Row: Playback Controls
@@ -250,6 +339,9 @@ impl AudioControls {
                                     LoopMode::Single => {
                                         (regular::REPEAT_ONCE, accent_color, "Loop: One")
                                     }
+                                    LoopMode::Next => {
+                                        (regular::ARROW_DOWN, accent_color, "Loop: Off, advance to next row")
+                                    }
                                 };
 
                             let loop_btn = ui.add(
@@ -260,9 +352,23 @@ impl AudioControls {
                                 )
                                 .frame(false),
                             );
-                            if loop_btn.on_hover_text(loop_text).clicked() {
+                            let loop_btn = loop_btn
+                                .on_hover_text(format!("{} (right-click for end-of-track options)", loop_text));
+                            if loop_btn.clicked() {
                                 self.audio_state.lock().unwrap().next_loop_mode();
                             }
+                            loop_btn.context_menu(|ui| {
+                                ui.label("When a track ends:");
+                                for mode in [LoopMode::None, LoopMode::Single, LoopMode::Next, LoopMode::All] {
+                                    if ui
+                                        .radio(state_copy.loop_mode == mode, mode.end_of_track_label())
+                                        .clicked()
+                                    {
+                                        self.audio_state.lock().unwrap().set_loop_mode(mode);
+                                        ui.close();
+                                    }
+                                }
+                            });
 
                             // Stop Button
                             let stop_btn = ui.add(
@@ -284,6 +390,117 @@ impl AudioControls {
             });
     }
 
+    fn render_region_controls(&mut self, ui: &mut Ui, state_copy: &AudioState) {
+        ui.horizontal(|ui| {
+            let weak = ui.visuals().weak_text_color();
+            ui.label(RichText::new("Region").size(11.0).color(weak));
+
+            if ui
+                .small_button(format!("{} In", regular::BRACKETS_SQUARE))
+                .on_hover_text("Set the region start at the current position")
+                .clicked()
+            {
+                self.audio_state.lock().unwrap().set_region_start();
+            }
+            if ui
+                .small_button(format!("Out {}", regular::BRACKETS_SQUARE))
+                .on_hover_text("Set the region end at the current position")
+                .clicked()
+            {
+                self.audio_state.lock().unwrap().set_region_end();
+            }
+
+            let start_text = state_copy.region_start.map_or("--:--.---".to_string(), AudioState::format_precise);
+            let end_text = state_copy.region_end.map_or("--:--.---".to_string(), AudioState::format_precise);
+            ui.label(
+                RichText::new(format!("{} - {}", start_text, end_text))
+                    .monospace()
+                    .size(11.0)
+                    .color(weak),
+            );
+
+            let has_marks = state_copy.region_start.is_some() || state_copy.region_end.is_some();
+            if has_marks && ui.small_button("Clear").clicked() {
+                self.audio_state.lock().unwrap().clear_region();
+            }
+
+            let region = state_copy.region();
+            if ui
+                .add_enabled(
+                    region.is_some(),
+                    egui::Button::new(format!("{} Export Region...", regular::SCISSORS)).small(),
+                )
+                .on_hover_text("Save the selected region of what is currently playing as WAV")
+                .clicked()
+            {
+                if let (Some((start, end)), Some(audio)) = (region, &state_copy.current_audio) {
+                    if let Some(status) = Self::export_region(audio, start, end) {
+                        self.export_status = Some(status);
+                    }
+                }
+            }
+
+            if let Some((message, color)) = &self.export_status {
+                ui.label(RichText::new(message).size(11.0).color(*color));
+            }
+        });
+    }
+
+    /// Ask for a destination and write the region of the current playback file.
+    /// Returns `None` when the dialog was cancelled.
+    fn export_region(audio: &AudioFile, start: f32, end: f32) -> Option<(String, Color32)> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let Some(source) = audio.playback_path.as_deref() else {
+                return Some(("Nothing to export".to_string(), Color32::RED));
+            };
+            let default_name = format!(
+                "{}_{:.3}-{:.3}.wav",
+                audio.name.trim_end_matches(".wav"),
+                start,
+                end
+            );
+            let path = rfd::FileDialog::new()
+                .add_filter("WAV", &["wav"])
+                .set_file_name(&default_name)
+                .save_file()?;
+            Some(match ExportUtils::export_wav_region(source, &path.to_string_lossy(), start, end) {
+                Ok(out) => (format!("Region saved to {}", out), Color32::GREEN),
+                Err(e) => (format!("Region export failed: {}", e), Color32::RED),
+            })
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (audio, start, end);
+            Some(("Region export is not available in the browser".to_string(), Color32::RED))
+        }
+    }
+
+    /// Position in the playlist and the next track, with the rest of the queue on hover
+    fn render_up_next(ui: &mut Ui, state_copy: &AudioState) {
+        let (Some(index), Some(next)) = (state_copy.current_track_index, state_copy.up_next(1).first().copied()) else {
+            return;
+        };
+        let queue: Vec<String> = state_copy
+            .up_next(UP_NEXT_SHOWN)
+            .iter()
+            .enumerate()
+            .map(|(i, track)| format!("{}. {}", i + 1, track.name))
+            .collect();
+        ui.label(
+            RichText::new(format!(
+                "{} / {}  {} {}",
+                index + 1,
+                state_copy.playlist.len(),
+                regular::ARROW_RIGHT,
+                next.name
+            ))
+            .size(11.0)
+            .color(ui.visuals().weak_text_color()),
+        )
+        .on_hover_text(format!("Up next:\n{}", queue.join("\n")));
+    }
+
     fn render_volume_controls(&mut self, ui: &mut Ui, state_copy: &AudioState) {
         let mut volume = state_copy.volume * 100.0;
 
@@ -311,11 +528,37 @@ impl AudioControls {
                 self.audio_state.lock().unwrap().toggle_mute();
             }
 
+            volume_btn.context_menu(|ui| {
+                ui.label("While dialogs are open:");
+                for mode in [DialogDuckMode::Off, DialogDuckMode::Duck, DialogDuckMode::Pause] {
+                    if ui
+                        .radio(state_copy.dialog_duck_mode == mode, mode.label())
+                        .clicked()
+                    {
+                        self.audio_state.lock().unwrap().dialog_duck_mode = mode;
+                        ui.close();
+                    }
+                }
+                ui.separator();
+                let mut instances = state_copy.max_preview_instances.max(1);
+                let response = ui
+                    .horizontal(|ui| {
+                        ui.label("Previews at once:");
+                        ui.add(DragValue::new(&mut instances).range(1..=8))
+                    })
+                    .inner
+                    .on_hover_text("1 stops the previous preview when another starts; more lets earlier ones finish, like overlapping sound effects in game");
+                if response.changed() {
+                    self.audio_state.lock().unwrap().set_max_preview_instances(instances);
+                }
+            });
+
             let slider_response = ui.add(
                 Slider::new(&mut volume, 0.0..=100.0)
                     .show_value(false)
                     .text(""),
-            );
+            )
+            .on_hover_text(format!("Master volume: {:.0}%\nApplies to previews and pads, and is kept between sessions", volume));
 
             if slider_response.changed() {
                 self.audio_state.lock().unwrap().set_volume(volume / 100.0);
@@ -99,8 +99,9 @@ impl AudioPlayer {
             });
     }
 
-    /// Load audio from file info
-    pub fn load_audio(&mut self, file_info: &AudioFileInfo, file_path: &str) -> Result<(), String> {
+    /// Write the audio that should be heard for a track to a playable temporary file:
+    /// the pending replacement, pending added data, or the original converted to WAV
+    pub fn prepare_playback_path(file_info: &AudioFileInfo, file_path: &str) -> Result<String, String> {
         // Check if there's a replacement audio data in memory first (unified method for both file types)
         let replacement_audio_data = ReplaceUtils::get_replacement_data_unified(file_info);
 
@@ -111,14 +112,14 @@ impl AudioPlayer {
         // Determine which audio data to use (replacement or original)
         let playback_path = if let Some(replacement_data) = replacement_audio_data {
             log::info!("Using replacement audio data for: {}", file_info.name);
-            crate::ui::main_area::ExportUtils::write_temp_audio_bytes(
+            crate::ui::main_area::ExportUtils::write_playable_temp_audio(
                 file_info,
                 &replacement_data,
                 "replacement",
             )?
         } else if let Some(added_data) = pending_added_data {
             log::info!("Using pending added audio data for: {}", file_info.name);
-            crate::ui::main_area::ExportUtils::write_temp_audio_bytes(
+            crate::ui::main_area::ExportUtils::write_playable_temp_audio(
                 file_info,
                 &added_data,
                 "pending",
@@ -128,55 +129,79 @@ impl AudioPlayer {
                 "No replacement/added data found, using original file for: {}",
                 file_info.name
             );
+            Self::original_playback_path(file_info, file_path)?
+        };
 
-            // Check if this is a NUS3BANK or NUS3AUDIO file
-            if file_info.is_nus3bank {
-                log::info!(
-                    "Processing NUS3BANK file for: {} (hex_id: {})",
-                    file_info.name,
-                    file_info.hex_id.as_ref().unwrap_or(&file_info.id)
-                );
-                crate::ui::main_area::ExportUtils::convert_to_wav_temp_path(file_info, file_path)
-                    .map_err(|e| {
-                        log::error!(
-                            "Failed to convert NUS3BANK audio to WAV format for track '{}' ({}): {}",
-                            file_info.name,
-                            file_info.hex_id.as_ref().unwrap_or(&file_info.id),
-                            e
-                        );
-                        format!("Failed to convert NUS3BANK audio to WAV format: {}", e)
-                    })?
-            } else {
-                log::info!("Processing NUS3AUDIO file for: {}", file_info.name);
-                match crate::ui::main_area::ExportUtils::convert_to_wav_temp_path(file_info, file_path) {
-                    Ok(temp_path) => temp_path,
-                    Err(e) => {
-                        log::warn!(
-                            "Failed to convert NUS3AUDIO audio to WAV format: {}. Using original format instead.",
-                            e
-                        );
-                        let nus3_file = Nus3audioFile::open(file_path)
-                            .map_err(|err| format!("Failed to open NUS3AUDIO file: {}", err))?;
-                        let audio_file = nus3_file
-                            .files
-                            .iter()
-                            .find(|f| f.name == file_info.name)
-                            .ok_or_else(|| {
-                                format!(
-                                    "Audio file '{}' not found in NUS3AUDIO file",
-                                    file_info.name
-                                )
-                            })?;
-                        crate::ui::main_area::ExportUtils::write_temp_audio_bytes(
-                            file_info,
-                            &audio_file.data,
-                            "fallback",
-                        )?
-                    }
+        Ok(playback_path)
+    }
+
+    /// Write the track as stored in the file on disk to a playable temporary file, ignoring
+    /// any pending replacement
+    pub fn original_playback_path(file_info: &AudioFileInfo, file_path: &str) -> Result<String, String> {
+        // Check if this is a NUS3BANK or NUS3AUDIO file
+        let playback_path = if file_info.is_nus3bank {
+            log::info!(
+                "Processing NUS3BANK file for: {} (hex_id: {})",
+                file_info.name,
+                file_info.hex_id.as_ref().unwrap_or(&file_info.id)
+            );
+            crate::ui::main_area::ExportUtils::convert_to_wav_temp_path(file_info, file_path)
+                .map_err(|e| {
+                    log::error!(
+                        "Failed to convert NUS3BANK audio to WAV format for track '{}' ({}): {}",
+                        file_info.name,
+                        file_info.hex_id.as_ref().unwrap_or(&file_info.id),
+                        e
+                    );
+                    format!("Failed to convert NUS3BANK audio to WAV format: {}", e)
+                })?
+        } else {
+            log::info!("Processing NUS3AUDIO file for: {}", file_info.name);
+            match crate::ui::main_area::ExportUtils::convert_to_wav_temp_path(file_info, file_path) {
+                Ok(temp_path) => temp_path,
+                Err(e) => {
+                    log::warn!(
+                        "Failed to convert NUS3AUDIO audio to WAV format: {}. Using original format instead.",
+                        e
+                    );
+                    let nus3_file = Nus3audioFile::open(file_path)
+                        .map_err(|err| format!("Failed to open NUS3AUDIO file: {}", err))?;
+                    let audio_file = nus3_file
+                        .files
+                        .iter()
+                        .find(|f| f.name == file_info.name)
+                        .ok_or_else(|| {
+                            format!(
+                                "Audio file '{}' not found in NUS3AUDIO file",
+                                file_info.name
+                            )
+                        })?;
+                    crate::ui::main_area::ExportUtils::write_playable_temp_audio(
+                        file_info,
+                        &audio_file.data,
+                        "fallback",
+                    )?
                 }
             }
         };
 
+        Ok(playback_path)
+    }
+
+    /// Load audio from file info
+    pub fn load_audio(&mut self, file_info: &AudioFileInfo, file_path: &str) -> Result<(), String> {
+        let playback_path = Self::prepare_playback_path(file_info, file_path)?;
+        self.load_playback_path(file_info, file_path, playback_path)
+    }
+
+    /// Load a track as stored in the file on disk, even when it has a pending replacement
+    pub fn load_original(&mut self, file_info: &AudioFileInfo, file_path: &str) -> Result<(), String> {
+        let playback_path = Self::original_playback_path(file_info, file_path)?;
+        self.load_playback_path(file_info, file_path, playback_path)
+    }
+
+    fn load_playback_path(&mut self, file_info: &AudioFileInfo, file_path: &str, playback_path: String) -> Result<(), String> {
+
         // Create an audio file struct
         let audio = AudioFile {
             file_path: file_path.to_string(),
@@ -203,9 +228,11 @@ impl AudioPlayer {
         state.set_loop_points(None, None, false);
 
         // Apply audio-specific loop settings if present
-        let key = format!("{}:{}", file_info.name, file_info.id);
+        let key = file_info.track_key();
         if let Ok(settings_map) = crate::ui::main_area::ReplaceUtils::get_loop_settings() {
-            if let Some(&(start, end, use_custom)) = settings_map.get(&key) {
+            if let Some(settings) = settings_map.get(&key) {
+                let (start, end, use_custom) =
+                    (settings.loop_start, settings.loop_end, settings.use_custom_loop);
                 // Apply loop settings for this audio
                 log::info!(
                     "Applied custom loop settings for {}: start={:?}, end={:?}, use_custom={}",
@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::time::{Duration, Instant};
 
 use super::audio_backend::{AudioBackend, PlatformAudioBackend};
+use super::loudness;
 use crate::ui::main_area::AudioFileInfo;
 
 /// Audio loop mode
@@ -14,6 +17,67 @@ pub enum LoopMode {
     Single,
     /// Loop the entire playlist
     All,
+    /// Advance to the next row and stop after the last one
+    Next,
+}
+
+impl LoopMode {
+    /// What happens when a track finishes in this mode
+    pub fn end_of_track_label(&self) -> &'static str {
+        match self {
+            LoopMode::None => "Stop",
+            LoopMode::Single => "Repeat track",
+            LoopMode::Next => "Advance to next row",
+            LoopMode::All => "Advance, loop playlist",
+        }
+    }
+}
+
+/// What happens to playback while a modal dialog is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DialogDuckMode {
+    /// Keep playing at the normal volume
+    Off,
+    /// Lower the volume until the dialog closes
+    #[default]
+    Duck,
+    /// Pause and resume when the dialog closes
+    Pause,
+}
+
+impl DialogDuckMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DialogDuckMode::Off => "Keep playing",
+            DialogDuckMode::Duck => "Lower volume",
+            DialogDuckMode::Pause => "Pause",
+        }
+    }
+}
+
+/// How often to retry opening an output device while none is available
+const DEVICE_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Volume factor applied while ducked
+const DUCK_FACTOR: f32 = 0.2;
+
+/// Volume sent to the backend: the user volume with the preview normalization gain,
+/// lowered while ducked for a dialog
+fn effective_volume(volume: f32, ducked: bool, preview_gain_db: f32) -> f32 {
+    let volume = volume * 10f32.powf(preview_gain_db / 20.0);
+    if ducked { volume * DUCK_FACTOR } else { volume }
+}
+
+/// Normalization gain for a playback file, measured once per file and cached
+fn preview_gain_for(
+    backend: &dyn AudioBackend,
+    cache: &mut HashMap<String, Option<f32>>,
+    playback_path: &str,
+) -> f32 {
+    let measured = *cache
+        .entry(playback_path.to_string())
+        .or_insert_with(|| backend.measure_loudness(playback_path));
+    measured.map(loudness::preview_gain_db).unwrap_or(0.0)
 }
 
 /// Persisted audio player settings
@@ -24,6 +88,13 @@ pub struct AudioPlayerSettings {
     pub previous_volume: f32,
     pub loop_mode: LoopMode,
     pub shuffle: bool,
+    #[serde(default)]
+    pub dialog_duck_mode: DialogDuckMode,
+    #[serde(default)]
+    pub normalize_preview: bool,
+    /// 0 and 1 both play one preview at a time
+    #[serde(default)]
+    pub max_preview_instances: usize,
 }
 
 impl Default for AudioPlayerSettings {
@@ -34,6 +105,9 @@ impl Default for AudioPlayerSettings {
             previous_volume: 0.80,
             loop_mode: LoopMode::None,
             shuffle: false,
+            dialog_duck_mode: DialogDuckMode::default(),
+            normalize_preview: false,
+            max_preview_instances: 1,
         }
     }
 }
@@ -85,6 +159,42 @@ pub struct AudioState {
     
     /// Whether shuffle mode is enabled
     pub shuffle: bool,
+
+    /// Playback behavior while a modal dialog is open
+    #[serde(default)]
+    pub dialog_duck_mode: DialogDuckMode,
+
+    /// Level-match previews regardless of their loudness (playback only, saved data is untouched)
+    #[serde(default)]
+    pub normalize_preview: bool,
+
+    /// Most previews sounding at once; earlier ones finish while the next plays
+    #[serde(default)]
+    pub max_preview_instances: usize,
+
+    /// Gain in dB applied to the current preview by normalization
+    #[serde(skip)]
+    pub preview_gain_db: f32,
+
+    /// Measured level per playback file (`None` = could not be measured)
+    #[serde(skip)]
+    loudness_cache: HashMap<String, Option<f32>>,
+
+    /// Start of the time region selected for export (seconds)
+    #[serde(skip)]
+    pub region_start: Option<f32>,
+
+    /// End of the time region selected for export (seconds)
+    #[serde(skip)]
+    pub region_end: Option<f32>,
+
+    /// Whether the volume is currently lowered for an open dialog
+    #[serde(skip)]
+    ducked: bool,
+
+    /// Whether playback was paused because a dialog opened
+    #[serde(skip)]
+    paused_for_dialog: bool,
     
     /// Current playlist
     #[serde(skip)]
@@ -105,6 +215,10 @@ pub struct AudioState {
     /// Audio backend for playback
     #[serde(skip)]
     audio_backend: Option<Box<dyn AudioBackend>>,
+
+    /// Last time the backend was (re)initialized, used to pace retries
+    #[serde(skip)]
+    last_backend_init: Option<Instant>,
 }
 
 // Manual Debug implementation since dyn AudioBackend doesn't implement Debug
@@ -140,11 +254,21 @@ impl Clone for AudioState {
             use_custom_loop: self.use_custom_loop,
             loop_mode: self.loop_mode,
             shuffle: self.shuffle,
+            dialog_duck_mode: self.dialog_duck_mode,
+            normalize_preview: self.normalize_preview,
+            max_preview_instances: self.max_preview_instances,
+            preview_gain_db: self.preview_gain_db,
+            loudness_cache: self.loudness_cache.clone(),
+            region_start: self.region_start,
+            region_end: self.region_end,
+            ducked: self.ducked,
+            paused_for_dialog: self.paused_for_dialog,
             playlist: self.playlist.clone(),
             current_track_index: self.current_track_index,
             should_play_next: self.should_play_next,
             should_play_previous: self.should_play_previous,
             audio_backend: None, // Don't clone the audio backend
+            last_backend_init: None,
         }
     }
 }
@@ -191,11 +315,21 @@ impl Default for AudioState {
             use_custom_loop: false,
             loop_mode: LoopMode::None,
             shuffle: false,
+            dialog_duck_mode: DialogDuckMode::default(),
+            normalize_preview: false,
+            max_preview_instances: 1,
+            preview_gain_db: 0.0,
+            loudness_cache: HashMap::new(),
+            region_start: None,
+            region_end: None,
+            ducked: false,
+            paused_for_dialog: false,
             playlist: Vec::new(),
             current_track_index: None,
             should_play_next: false,
             should_play_previous: false,
             audio_backend: None,
+            last_backend_init: None,
         };
         
         // Try to initialize the audio backend
@@ -216,6 +350,8 @@ impl AudioState {
     
     /// Initialize the audio backend
     fn init_audio_backend(&mut self) -> Result<(), String> {
+        self.last_backend_init = Some(Instant::now());
+
         // Create a new platform-specific audio backend
         let mut backend = Box::new(PlatformAudioBackend::new());
         
@@ -227,6 +363,80 @@ impl AudioState {
         
         Ok(())
     }
+
+    /// Recover from a lost output device. When the stream reports an error the backend is
+    /// re-initialized on the current default device and playback resumes at the same position.
+    /// Without any device, initialization is retried periodically.
+    fn check_audio_device(&mut self) {
+        let retry_due = self
+            .last_backend_init
+            .map(|t| t.elapsed() >= DEVICE_RETRY_INTERVAL)
+            .unwrap_or(true);
+
+        let stream_error = match &mut self.audio_backend {
+            Some(backend) if backend.is_available() => backend.take_stream_error(),
+            Some(_) => {
+                if !retry_due {
+                    return;
+                }
+                Some("audio device unavailable".to_string())
+            }
+            None => {
+                if retry_due && self.init_audio_backend().is_ok() {
+                    log::info!("Audio backend initialized after an earlier failure");
+                    self.restore_backend_settings();
+                }
+                return;
+            }
+        };
+
+        let Some(error) = stream_error else {
+            return;
+        };
+
+        log::warn!("Audio output stream error ({}), reinitializing backend", error);
+        let was_playing = self.is_playing;
+        let position = self
+            .audio_backend
+            .as_ref()
+            .map(|b| b.get_position())
+            .unwrap_or(self.current_position);
+
+        self.last_backend_init = Some(Instant::now());
+        let result = match &mut self.audio_backend {
+            Some(backend) => backend.init(),
+            None => return,
+        };
+
+        match result {
+            Ok(()) => {
+                log::info!("Audio backend reinitialized on the current output device");
+                self.restore_backend_settings();
+                self.is_playing = false;
+                self.current_position = position;
+                if was_playing && self.current_audio.is_some() {
+                    // toggle_play replays the file and seeks back to current_position
+                    self.toggle_play();
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to reinitialize audio backend: {}", e);
+                self.is_playing = false;
+                self.current_position = position;
+            }
+        }
+    }
+
+    /// Re-apply volume and loop region after the backend was recreated
+    fn restore_backend_settings(&mut self) {
+        if let Some(backend) = &mut self.audio_backend {
+            backend.set_max_instances(self.max_preview_instances);
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
+                log::error!("Failed to restore volume: {}", e);
+            }
+        }
+        self.apply_loop_region();
+    }
     
     /// Play or pause the audio
     pub fn toggle_play(&mut self) {
@@ -257,8 +467,14 @@ impl AudioState {
                         // Get actual duration from backend
                         self.total_duration = backend.get_duration();
 
+                        self.preview_gain_db = if self.normalize_preview {
+                            preview_gain_for(backend.as_ref(), &mut self.loudness_cache, playback_path)
+                        } else {
+                            0.0
+                        };
+
                         // Apply current volume setting
-                        if let Err(e) = backend.set_volume(self.volume) {
+                        if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
                             log::error!("Failed to apply volume: {}", e);
                         }
 
@@ -284,6 +500,14 @@ impl AudioState {
     
     /// Stop the audio playback
     pub fn stop(&mut self) {
+        self.stop_current();
+        if let Some(backend) = &mut self.audio_backend {
+            backend.stop_all();
+        }
+    }
+
+    /// Stop the current preview; with overlapping previews allowed it keeps sounding
+    fn stop_current(&mut self) {
         self.is_playing = false;
         self.current_position = 0.0;
         
@@ -314,7 +538,7 @@ impl AudioState {
         
         // Update backend volume
         if let Some(backend) = &mut self.audio_backend {
-            if let Err(e) = backend.set_volume(self.volume) {
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
                 log::error!("Failed to set audio volume: {}", e);
             }
         }
@@ -322,17 +546,18 @@ impl AudioState {
     
     /// Set a new audio file for playback
     pub fn set_audio(&mut self, audio: AudioFile) {
-        // Stop any current playback
-        self.stop();
+        // Stop any current playback, or leave it to finish when previews may overlap
+        self.stop_current();
 
         self.cleanup_temp_audio();
 
         // Set new audio file
         self.current_audio = Some(audio);
+        self.clear_region();
         
         // Apply current volume setting to the audio backend
         if let Some(backend) = &mut self.audio_backend {
-            if let Err(e) = backend.set_volume(self.volume) {
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
                 log::error!("Failed to apply volume to new audio: {}", e);
             }
         }
@@ -377,7 +602,7 @@ impl AudioState {
         
         // Update backend volume
         if let Some(backend) = &mut self.audio_backend {
-            if let Err(e) = backend.set_volume(self.volume) {
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
                 log::error!("Failed to set audio volume: {}", e);
             }
         }
@@ -391,13 +616,19 @@ impl AudioState {
             previous_volume: self.previous_volume,
             loop_mode: self.loop_mode,
             shuffle: self.shuffle,
+            dialog_duck_mode: self.dialog_duck_mode,
+            normalize_preview: self.normalize_preview,
+            max_preview_instances: self.max_preview_instances,
         }
     }
 
     /// Apply persisted audio settings
     pub fn apply_settings(&mut self, settings: &AudioPlayerSettings) {
-        self.loop_mode = settings.loop_mode;
+        self.set_loop_mode(settings.loop_mode);
         self.shuffle = settings.shuffle;
+        self.dialog_duck_mode = settings.dialog_duck_mode;
+        self.normalize_preview = settings.normalize_preview;
+        self.set_max_preview_instances(settings.max_preview_instances);
         self.previous_volume = settings.previous_volume.clamp(0.0, 1.0);
 
         let mut volume = settings.volume.clamp(0.0, 1.0);
@@ -414,7 +645,7 @@ impl AudioState {
         self.is_muted = settings.is_muted;
 
         if let Some(backend) = &mut self.audio_backend {
-            if let Err(e) = backend.set_volume(self.volume) {
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
                 log::error!("Failed to apply persisted volume: {}", e);
             }
         }
@@ -422,32 +653,51 @@ impl AudioState {
     
     /// Update playback state from backend
     pub fn update_from_backend(&mut self) {
+        self.check_audio_device();
+
         if let Some(backend) = &mut self.audio_backend {
+            backend.release_finished();
+
             // Update position
             if self.is_playing {
                 self.current_position = backend.get_position();
 
-                // Check if track has finished
-                if self.current_position >= self.total_duration - 0.1 && self.total_duration > 0.0 {
+                // Check if track has finished. The backend reports completion directly;
+                // the duration check covers files whose duration is known up front.
+                // A custom loop region never ends; the backend wraps it sample-accurately.
+                let custom_loop_active = self.use_custom_loop && self.loop_mode == LoopMode::Single;
+                let reached_end = backend.has_finished()
+                    || (!custom_loop_active
+                        && self.current_position >= self.total_duration - 0.1
+                        && self.total_duration > 0.0);
+                if reached_end {
                     match self.loop_mode {
                         LoopMode::Single => {
-                            // Restart current track
+                            // Restart current track (a finished stream cannot be seeked, so replay it)
                             self.current_position = 0.0;
-                            if let Err(e) = backend.set_position(0.0) {
-                                log::error!("Failed to restart track: {}", e);
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if let Some(audio) = &self.current_audio {
+                                let playback_path = audio
+                                    .playback_path
+                                    .as_deref()
+                                    .unwrap_or(&audio.file_path);
+                                if let Err(e) = backend.play_audio(playback_path) {
+                                    log::error!("Failed to restart track: {}", e);
+                                }
                             }
                         }
-                        LoopMode::All => {
-                            // Signal to play next track (will loop back to first track if at end)
+                        LoopMode::All | LoopMode::Next => {
+                            // Signal to play next track; All wraps to the first track, Next stops after the last
+                            let _ = backend.stop();
                             self.is_playing = false;
                             self.current_position = 0.0;
                             self.should_play_next = true;
                         }
                         LoopMode::None => {
-                            // Stop playback at the end of the track
+                            // Stop playback at the end of the track and release the finished stream
+                            let _ = backend.stop();
                             self.is_playing = false;
                             self.current_position = 0.0;
-                            // In None mode, just stop playing, don't auto-play next track
                         }
                     }
                 }
@@ -474,15 +724,125 @@ impl AudioState {
         self.loop_start = start;
         self.loop_end = end;
         self.use_custom_loop = use_custom;
+        self.apply_loop_region();
     }
 
     /// Toggle loop mode
     pub fn next_loop_mode(&mut self) {
-        self.loop_mode = match self.loop_mode {
+        let next = match self.loop_mode {
             LoopMode::None => LoopMode::Single,
-            LoopMode::Single => LoopMode::All,
+            LoopMode::Single => LoopMode::Next,
+            LoopMode::Next => LoopMode::All,
             LoopMode::All => LoopMode::None,
         };
+        self.set_loop_mode(next);
+    }
+
+    /// Set the loop mode
+    pub fn set_loop_mode(&mut self, mode: LoopMode) {
+        self.loop_mode = mode;
+        self.apply_loop_region();
+    }
+
+    /// Hand the custom loop points to the backend while repeating a single track
+    fn apply_loop_region(&mut self) {
+        let (start, end) = if self.use_custom_loop && self.loop_mode == LoopMode::Single {
+            (self.loop_start, self.loop_end)
+        } else {
+            (None, None)
+        };
+        if let Some(backend) = &mut self.audio_backend {
+            if let Err(e) = backend.set_loop_region(start, end) {
+                log::error!("Failed to set loop region: {}", e);
+            }
+        }
+    }
+
+    /// Load a playable file into a trigger pad slot
+    pub fn load_pad(&mut self, slot: usize, file_path: &str) -> Result<(), String> {
+        match &mut self.audio_backend {
+            Some(backend) => backend.load_pad(slot, file_path),
+            None => Err("Audio backend not available".to_string()),
+        }
+    }
+
+    /// Trigger (or retrigger) a pad
+    pub fn trigger_pad(&mut self, slot: usize) -> Result<(), String> {
+        match &mut self.audio_backend {
+            Some(backend) => backend.trigger_pad(slot),
+            None => Err("Audio backend not available".to_string()),
+        }
+    }
+
+    /// Stop and empty a pad
+    pub fn clear_pad(&mut self, slot: usize) {
+        if let Some(backend) = &mut self.audio_backend {
+            backend.clear_pad(slot);
+        }
+    }
+
+    /// Allow `max` previews to sound at once (at least one)
+    pub fn set_max_preview_instances(&mut self, max: usize) {
+        self.max_preview_instances = max.max(1);
+        if let Some(backend) = &mut self.audio_backend {
+            backend.set_max_instances(self.max_preview_instances);
+        }
+    }
+
+    /// Turn preview loudness normalization on or off and re-level the current preview
+    pub fn set_normalize_preview(&mut self, enabled: bool) {
+        self.normalize_preview = enabled;
+        self.preview_gain_db = 0.0;
+
+        if let Some(backend) = &mut self.audio_backend {
+            #[cfg(not(target_arch = "wasm32"))]
+            if enabled {
+                if let Some(audio) = &self.current_audio {
+                    let playback_path = audio.playback_path.as_deref().unwrap_or(&audio.file_path);
+                    self.preview_gain_db =
+                        preview_gain_for(backend.as_ref(), &mut self.loudness_cache, playback_path);
+                }
+            }
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
+                log::error!("Failed to apply preview normalization: {}", e);
+            }
+        }
+    }
+
+    /// Called every frame with whether a modal dialog is open.
+    /// Ducks or pauses playback when a dialog opens and restores it once all dialogs close.
+    pub fn set_dialog_open(&mut self, dialog_open: bool) {
+        let active = self.ducked || self.paused_for_dialog;
+        if dialog_open == active {
+            return;
+        }
+
+        if dialog_open {
+            match self.dialog_duck_mode {
+                DialogDuckMode::Off => return,
+                DialogDuckMode::Duck => self.ducked = true,
+                DialogDuckMode::Pause => {
+                    if !self.is_playing {
+                        return;
+                    }
+                    self.paused_for_dialog = true;
+                    self.toggle_play();
+                }
+            }
+        } else {
+            self.ducked = false;
+            // Resume only if the user did not start or stop playback in the meantime
+            if self.paused_for_dialog && !self.is_playing && self.current_audio.is_some() {
+                self.toggle_play();
+            }
+            self.paused_for_dialog = false;
+        }
+
+        if let Some(backend) = &mut self.audio_backend {
+            if let Err(e) = backend.set_volume(effective_volume(self.volume, self.ducked, self.preview_gain_db)) {
+                log::error!("Failed to apply dialog ducking volume: {}", e);
+            }
+        }
     }
 
     /// Toggle shuffle mode
@@ -500,6 +860,33 @@ impl AudioState {
         self.should_play_previous = true;
     }
 
+    /// Play through `playlist` in the given order from its first track, advancing at the end
+    /// of each track. A loop mode that would not advance is switched to advancing row by row.
+    pub fn start_playlist(&mut self, playlist: Vec<AudioFileInfo>) {
+        self.playlist = playlist;
+        self.current_track_index = (!self.playlist.is_empty()).then_some(0);
+        if matches!(self.loop_mode, LoopMode::None | LoopMode::Single) {
+            self.set_loop_mode(LoopMode::Next);
+        }
+    }
+
+    /// Tracks that will play after the current one when it ends, up to `count`.
+    /// Empty when playback does not advance or is shuffled.
+    pub fn up_next(&self, count: usize) -> Vec<&AudioFileInfo> {
+        let Some(current) = self.current_track_index else {
+            return Vec::new();
+        };
+        if self.shuffle || !matches!(self.loop_mode, LoopMode::Next | LoopMode::All) {
+            return Vec::new();
+        }
+        let after = self.playlist.iter().skip(current + 1);
+        if self.loop_mode == LoopMode::All {
+            after.chain(self.playlist.iter().take(current)).take(count).collect()
+        } else {
+            after.take(count).collect()
+        }
+    }
+
     /// Update playlist and current index
     pub fn update_playlist(&mut self, playlist: Vec<AudioFileInfo>, current_name: &str, current_id: &str) {
         // Sort playlist by ID (from small to large)
@@ -517,6 +904,40 @@ impl AudioState {
         self.current_track_index = self.playlist.iter().position(|f| f.name == current_name && f.id == current_id);
     }
     
+    /// Mark the start of the export region at the current position
+    pub fn set_region_start(&mut self) {
+        self.region_start = Some(self.current_position);
+        if self.region_end.is_some_and(|end| end <= self.current_position) {
+            self.region_end = None;
+        }
+    }
+
+    /// Mark the end of the export region at the current position
+    pub fn set_region_end(&mut self) {
+        self.region_end = Some(self.current_position);
+        if self.region_start.is_some_and(|start| start >= self.current_position) {
+            self.region_start = None;
+        }
+    }
+
+    pub fn clear_region(&mut self) {
+        self.region_start = None;
+        self.region_end = None;
+    }
+
+    /// The selected region, when both ends are set. A missing start means the track start.
+    pub fn region(&self) -> Option<(f32, f32)> {
+        let end = self.region_end?;
+        let start = self.region_start.unwrap_or(0.0);
+        (end > start).then_some((start, end))
+    }
+
+    /// Format seconds as MM:SS.mmm
+    pub fn format_precise(seconds: f32) -> String {
+        let minutes = (seconds / 60.0).floor() as u32;
+        format!("{:02}:{:06.3}", minutes, seconds % 60.0)
+    }
+
     /// Get formatted current position (MM:SS)
     pub fn format_position(&self) -> String {
         let minutes = (self.current_position / 60.0).floor() as u32;
@@ -0,0 +1,70 @@
+/// Level that preview normalization aims for (RMS, dBFS)
+pub const PREVIEW_TARGET_DBFS: f32 = -20.0;
+
+/// Largest correction preview normalization applies in either direction
+pub const PREVIEW_MAX_GAIN_DB: f32 = 12.0;
+
+/// Blocks quieter than this are ignored so pauses and silence don't drag the level down
+const SILENCE_GATE_DBFS: f32 = -60.0;
+
+/// Block length used for gating, in seconds
+const BLOCK_SECONDS: f32 = 0.4;
+
+fn to_dbfs(mean_square: f64) -> f32 {
+    if mean_square <= 0.0 {
+        f32::NEG_INFINITY
+    } else {
+        (10.0 * mean_square.log10()) as f32
+    }
+}
+
+/// Gated RMS level of interleaved-to-mono samples in dBFS. Returns `None` for silence.
+pub fn gated_rms_dbfs(samples: &[f32], sample_rate: u32) -> Option<f32> {
+    let block_len = ((sample_rate as f32 * BLOCK_SECONDS) as usize).max(1);
+    let mut total = 0.0f64;
+    let mut counted = 0usize;
+
+    for block in samples.chunks(block_len) {
+        let sum: f64 = block.iter().map(|s| (*s as f64) * (*s as f64)).sum();
+        let mean_square = sum / block.len() as f64;
+        if to_dbfs(mean_square) >= SILENCE_GATE_DBFS {
+            total += sum;
+            counted += block.len();
+        }
+    }
+
+    if counted == 0 {
+        None
+    } else {
+        Some(to_dbfs(total / counted as f64))
+    }
+}
+
+/// Gain in dB that brings a measured level to the preview target
+pub fn preview_gain_db(measured_dbfs: f32) -> f32 {
+    (PREVIEW_TARGET_DBFS - measured_dbfs).clamp(-PREVIEW_MAX_GAIN_DB, PREVIEW_MAX_GAIN_DB)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_scale_square_wave_is_zero_dbfs() {
+        let samples: Vec<f32> = (0..48000).map(|i| if i % 2 == 0 { 1.0 } else { -1.0 }).collect();
+        let level = gated_rms_dbfs(&samples, 48000).unwrap();
+        assert!(level.abs() < 0.01);
+        assert_eq!(preview_gain_db(level), -PREVIEW_MAX_GAIN_DB);
+    }
+
+    #[test]
+    fn silence_is_gated_out() {
+        let mut samples = vec![0.0f32; 57600];
+        assert_eq!(gated_rms_dbfs(&samples, 48000), None);
+
+        // Half a second of -20 dBFS tone after 1.2 s of silence still measures -20 dBFS
+        samples.extend((0..24000).map(|i| if i % 2 == 0 { 0.1 } else { -0.1 }));
+        let level = gated_rms_dbfs(&samples, 48000).unwrap();
+        assert!((level - -20.0).abs() < 0.5);
+    }
+}
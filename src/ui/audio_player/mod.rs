@@ -3,7 +3,11 @@ mod audio_player_component;
 mod audio_controls;
 mod audio_state;
 mod audio_backend;
+mod loudness;
+mod spectrogram;
+mod track_view;
 
 // Re-export the main components
 pub use audio_player_component::{AudioPlayer, AudioPlayerAction};
 pub use audio_state::{AudioFile, AudioPlayerSettings, LoopMode};
+pub use loudness::gated_rms_dbfs;
@@ -0,0 +1,201 @@
+use egui::{Color32, ColorImage};
+
+/// Samples per FFT frame; 512 bins is fine enough to see a 16 kHz shelf at 48 kHz
+const FFT_SIZE: usize = 1024;
+
+/// Levels below this are drawn black
+const FLOOR_DB: f32 = -100.0;
+
+/// Average level a bin must reach to count as content when looking for a low-pass cutoff
+const CONTENT_DB: f32 = -85.0;
+
+/// Content ending below this share of the band means the audio was low-pass filtered,
+/// as lossy rips usually are
+const LOWPASS_SHARE: f32 = 0.9;
+
+/// Consecutive full-scale samples that count as one clipped run
+const CLIP_RUN: usize = 3;
+
+/// Short-time spectrum of a track: `columns` FFT frames spread over the whole track
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    pub columns: usize,
+    pub bins: usize,
+    pub sample_rate: u32,
+    /// Level in dBFS of every bin, column after column
+    levels: Vec<f32>,
+    /// Runs of full-scale samples, a sign the audio was clipped
+    pub clipped_runs: usize,
+}
+
+impl Spectrogram {
+    /// Spectrum of mono samples in -1.0..=1.0
+    pub fn compute(samples: &[f32], sample_rate: u32, columns: usize) -> Self {
+        let bins = FFT_SIZE / 2;
+        let columns = columns.clamp(1, samples.len().div_ceil(FFT_SIZE / 4).max(1));
+        let hop = samples.len().saturating_sub(FFT_SIZE) as f32 / columns.saturating_sub(1).max(1) as f32;
+        let window: Vec<f32> = (0..FFT_SIZE)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / FFT_SIZE as f32).cos())
+            .collect();
+        // A full-scale sine peaks at N/4 with a Hann window
+        let scale = 4.0 / FFT_SIZE as f32;
+
+        let mut levels = Vec::with_capacity(columns * bins);
+        let mut re = vec![0.0f32; FFT_SIZE];
+        let mut im = vec![0.0f32; FFT_SIZE];
+        for column in 0..columns {
+            let start = (column as f32 * hop) as usize;
+            for i in 0..FFT_SIZE {
+                re[i] = samples.get(start + i).copied().unwrap_or(0.0) * window[i];
+                im[i] = 0.0;
+            }
+            fft(&mut re, &mut im);
+            levels.extend((0..bins).map(|bin| {
+                let magnitude = (re[bin] * re[bin] + im[bin] * im[bin]).sqrt() * scale;
+                (20.0 * magnitude.max(1e-9).log10()).max(FLOOR_DB)
+            }));
+        }
+
+        Self {
+            columns,
+            bins,
+            sample_rate,
+            levels,
+            clipped_runs: count_clipped_runs(samples),
+        }
+    }
+
+    /// Level of one bin in dBFS; the floor outside the spectrum
+    pub fn level(&self, column: usize, bin: usize) -> f32 {
+        if bin >= self.bins {
+            return FLOOR_DB;
+        }
+        self.levels.get(column * self.bins + bin).copied().unwrap_or(FLOOR_DB)
+    }
+
+    pub fn bin_hz(&self, bin: usize) -> f32 {
+        bin as f32 * self.sample_rate as f32 / FFT_SIZE as f32
+    }
+
+    /// Highest frequency with content on average over the track. `None` for silence.
+    pub fn content_limit_hz(&self) -> Option<f32> {
+        (0..self.bins)
+            .rev()
+            .find(|&bin| {
+                let sum: f32 = (0..self.columns).map(|c| self.level(c, bin)).sum();
+                sum / self.columns as f32 >= CONTENT_DB
+            })
+            .map(|bin| self.bin_hz(bin))
+    }
+
+    /// Cutoff of a low-pass filter the audio went through, when content stops well short
+    /// of the top of the band
+    pub fn lowpass_hz(&self) -> Option<f32> {
+        let nyquist = self.sample_rate as f32 / 2.0;
+        self.content_limit_hz().filter(|&hz| hz < nyquist * LOWPASS_SHARE)
+    }
+
+    /// Image with time left to right and frequency bottom to top
+    pub fn to_image(&self) -> ColorImage {
+        let mut pixels = Vec::with_capacity(self.columns * self.bins);
+        for bin in (0..self.bins).rev() {
+            for column in 0..self.columns {
+                pixels.push(heat((self.level(column, bin) - FLOOR_DB) / -FLOOR_DB));
+            }
+        }
+        ColorImage::new([self.columns, self.bins], pixels)
+    }
+}
+
+/// Dark blue through magenta and orange to pale yellow
+fn heat(t: f32) -> Color32 {
+    const STOPS: [(f32, [f32; 3]); 5] = [
+        (0.0, [0.0, 0.0, 0.0]),
+        (0.3, [30.0, 10.0, 90.0]),
+        (0.55, [170.0, 30.0, 120.0]),
+        (0.8, [250.0, 130.0, 30.0]),
+        (1.0, [255.0, 250.0, 190.0]),
+    ];
+    let t = t.clamp(0.0, 1.0);
+    let upper = STOPS.iter().position(|(at, _)| *at >= t).unwrap_or(STOPS.len() - 1).max(1);
+    let (a, ca) = STOPS[upper - 1];
+    let (b, cb) = STOPS[upper];
+    let f = (t - a) / (b - a);
+    let channel = |i: usize| (ca[i] + (cb[i] - ca[i]) * f) as u8;
+    Color32::from_rgb(channel(0), channel(1), channel(2))
+}
+
+fn count_clipped_runs(samples: &[f32]) -> usize {
+    let mut runs = 0;
+    let mut length = 0;
+    for s in samples {
+        if s.abs() >= 0.999 {
+            length += 1;
+            if length == CLIP_RUN {
+                runs += 1;
+            }
+        } else {
+            length = 0;
+        }
+    }
+    runs
+}
+
+/// In-place radix-2 FFT; the length must be a power of two
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * std::f32::consts::PI / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let tr = re[b] * cos - im[b] * sin;
+                let ti = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_tone_and_the_band_limit() {
+        let rate = 48000;
+        let tone: Vec<f32> = (0..rate)
+            .map(|i| 0.5 * (2.0 * std::f32::consts::PI * 1500.0 * i as f32 / rate as f32).sin())
+            .collect();
+        let spectrogram = Spectrogram::compute(&tone, rate as u32, 50);
+        assert_eq!(spectrogram.columns, 50);
+        // 1500 Hz falls exactly on bin 32
+        assert!((spectrogram.level(10, 32) - -6.0).abs() < 0.5);
+        let limit = spectrogram.lowpass_hz().unwrap();
+        assert!((1500.0..1700.0).contains(&limit));
+        assert_eq!(spectrogram.clipped_runs, 0);
+
+        let clipped: Vec<f32> = tone.iter().map(|s| (s * 4.0).clamp(-1.0, 1.0)).collect();
+        assert!(Spectrogram::compute(&clipped, rate as u32, 50).clipped_runs > 100);
+        assert_eq!(Spectrogram::compute(&[0.0; 4096], rate as u32, 8).content_limit_hz(), None);
+    }
+}
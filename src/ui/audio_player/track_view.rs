@@ -0,0 +1,245 @@
+use egui::{Color32, Pos2, RichText, Sense, Stroke, TextureHandle, TextureOptions, Ui, Vec2};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::audio_state::AudioState;
+use super::spectrogram::Spectrogram;
+use crate::ui::main_area::decode_pool::{self, Priority};
+use crate::ui::main_area::{PeakCache, Waveform};
+
+/// Height of the waveform and spectrogram strip
+const VIEW_HEIGHT: f32 = 90.0;
+
+/// How often to look for finished analysis results
+const ANALYSIS_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Peak columns and FFT frames computed per track
+const COLUMNS: usize = 600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ViewTab {
+    Waveform,
+    Spectrogram,
+}
+
+struct Analysis {
+    waveform: Waveform,
+    /// Missing while the waveform came from the peak cache, until the Spectrogram tab asks
+    spectrogram: Option<Spectrogram>,
+}
+
+type PendingAnalysis = Arc<Mutex<Option<Result<Analysis, String>>>>;
+
+/// Waveform and spectrogram of the loaded track, analyzed in the background once per
+/// playback file. Waveforms seen before are read back from the peak cache.
+pub struct TrackView {
+    pub open: bool,
+    tab: ViewTab,
+    /// Playback file the analysis belongs to
+    source: Option<String>,
+    pending: Option<PendingAnalysis>,
+    analysis: Option<Analysis>,
+    texture: Option<TextureHandle>,
+    error: Option<String>,
+}
+
+impl Default for TrackView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrackView {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            tab: ViewTab::Waveform,
+            source: None,
+            pending: None,
+            analysis: None,
+            texture: None,
+            error: None,
+        }
+    }
+
+    /// Start analyzing `path` unless it is the file already shown
+    fn analyze(&mut self, path: Option<String>) {
+        if path == self.source {
+            return;
+        }
+        self.source = path.clone();
+        self.analysis = None;
+        self.texture = None;
+        self.error = None;
+        self.pending = None;
+        if let Some(path) = path {
+            self.start(path, false);
+        }
+    }
+
+    /// Analyze `path` on the decode pool. The peak cache stands in for decoding unless the
+    /// spectrogram is wanted.
+    fn start(&mut self, path: String, with_spectrogram: bool) {
+        let pending: PendingAnalysis = Arc::new(Mutex::new(None));
+        self.pending = Some(pending.clone());
+        decode_pool::spawn(Priority::Preview, move || {
+            let hash = PeakCache::hash_of(Path::new(&path));
+            let cached = hash.filter(|_| !with_spectrogram).and_then(PeakCache::load);
+            let result = match cached {
+                Some(waveform) => Ok(Analysis { waveform, spectrogram: None }),
+                None => Waveform::load(Path::new(&path)).map(|waveform| {
+                    if let Some(hash) = hash {
+                        if let Err(e) = PeakCache::store(hash, &waveform) {
+                            println!("Peak cache: {}", e);
+                        }
+                    }
+                    let spectrogram = Spectrogram::compute(waveform.samples(), waveform.sample_rate, COLUMNS);
+                    Analysis { waveform, spectrogram: Some(spectrogram) }
+                }),
+            };
+            if let Ok(mut p) = pending.lock() {
+                *p = Some(result);
+            }
+        });
+    }
+
+    fn poll(&mut self) {
+        let finished = self.pending.as_ref().and_then(|p| p.lock().ok().and_then(|mut p| p.take()));
+        if let Some(result) = finished {
+            self.pending = None;
+            match result {
+                Ok(analysis) => {
+                    self.analysis = Some(analysis);
+                    self.texture = None;
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+
+    /// Draw the strip for the track in `state`. Returns the position in seconds the user
+    /// clicked to seek to.
+    pub fn show(&mut self, ui: &mut Ui, state: &AudioState) -> Option<f32> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let path = state.current_audio.as_ref().and_then(|a| a.playback_path.clone());
+        #[cfg(target_arch = "wasm32")]
+        let path: Option<String> = None;
+        self.analyze(path);
+        self.poll();
+        // A failed re-analysis is not retried until the track changes
+        let needs_spectrogram = self.analysis.as_ref().is_some_and(|a| a.spectrogram.is_none());
+        if self.tab == ViewTab::Spectrogram && needs_spectrogram && self.pending.is_none() && self.error.is_none() {
+            if let Some(path) = self.source.clone() {
+                self.start(path, true);
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tab, ViewTab::Waveform, "Waveform");
+            ui.selectable_value(&mut self.tab, ViewTab::Spectrogram, "Spectrogram");
+            if let (ViewTab::Spectrogram, Some(spectrogram)) =
+                (self.tab, self.analysis.as_ref().and_then(|a| a.spectrogram.as_ref()))
+            {
+                Self::show_findings(ui, spectrogram);
+            }
+        });
+
+        let Some(analysis) = &self.analysis else {
+            let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), VIEW_HEIGHT), Sense::hover());
+            ui.painter().rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+            let text = if self.pending.is_some() {
+                ui.ctx().request_repaint_after(ANALYSIS_POLL_INTERVAL);
+                "Analyzing..."
+            } else if let Some(error) = &self.error {
+                error.as_str()
+            } else {
+                "No track loaded"
+            };
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                text,
+                egui::FontId::proportional(12.0),
+                ui.visuals().weak_text_color(),
+            );
+            return None;
+        };
+
+        let loop_region = match (state.use_custom_loop, state.loop_start, state.loop_end) {
+            (true, Some(start), Some(end)) => Some((start, end)),
+            _ => None,
+        };
+        let rect = match self.tab {
+            ViewTab::Waveform => {
+                ui.scope(|ui| analysis.waveform.show(ui, VIEW_HEIGHT, loop_region)).response.rect
+            }
+            ViewTab::Spectrogram => {
+                let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), VIEW_HEIGHT), Sense::hover());
+                let Some(spectrogram) = &analysis.spectrogram else {
+                    let text = match &self.error {
+                        Some(error) => error.as_str(),
+                        None => {
+                            ui.ctx().request_repaint_after(ANALYSIS_POLL_INTERVAL);
+                            "Analyzing..."
+                        }
+                    };
+                    ui.painter().rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+                    ui.painter().text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        text,
+                        egui::FontId::proportional(12.0),
+                        ui.visuals().weak_text_color(),
+                    );
+                    return None;
+                };
+                let texture = self.texture.get_or_insert_with(|| {
+                    ui.ctx().load_texture("track_spectrogram", spectrogram.to_image(), TextureOptions::LINEAR)
+                });
+                ui.painter().image(
+                    texture.id(),
+                    rect,
+                    egui::Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                    Color32::WHITE,
+                );
+                rect
+            }
+        };
+
+        let duration = analysis.waveform.duration_secs;
+        if duration > 0.0 {
+            let x = rect.left() + rect.width() * (state.current_position / duration).clamp(0.0, 1.0);
+            ui.painter()
+                .line_segment([Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())], Stroke::new(1.5, Color32::WHITE));
+        }
+        let response = ui.interact(rect, ui.id().with("track_view_seek"), Sense::click()).on_hover_text("Click to seek");
+        if !response.clicked() || duration <= 0.0 {
+            return None;
+        }
+        response
+            .interact_pointer_pos()
+            .map(|pos| (pos.x - rect.left()) / rect.width() * duration)
+    }
+
+    fn show_findings(ui: &mut Ui, spectrogram: &Spectrogram) {
+        let nyquist_khz = spectrogram.sample_rate as f32 / 2000.0;
+        match spectrogram.lowpass_hz() {
+            Some(hz) => {
+                ui.colored_label(Color32::GOLD, format!("Content stops at {:.1} kHz", hz / 1000.0))
+                    .on_hover_text(format!(
+                        "Nothing above {:.1} kHz of the {:.1} kHz band: the audio was low-pass filtered, as lossy rips are",
+                        hz / 1000.0,
+                        nyquist_khz
+                    ));
+            }
+            None => {
+                ui.label(RichText::new(format!("Full band ({:.1} kHz)", nyquist_khz)).weak());
+            }
+        }
+        if spectrogram.clipped_runs > 0 {
+            ui.colored_label(Color32::from_rgb(255, 100, 100), format!("{} clipped run(s)", spectrogram.clipped_runs))
+                .on_hover_text("Stretches of full-scale samples where the waveform was cut off");
+        }
+    }
+}
@@ -55,6 +55,16 @@ impl FileList {
         self.update_selection();
     }
 
+    /// Select a file, adding it to the list first when it is not there yet
+    pub fn select_file(&mut self, path: String) {
+        if self.files.iter().any(|f| f.path == path) {
+            self.selected_file = Some(path);
+            self.update_selection();
+        } else {
+            self.add_file(path);
+        }
+    }
+
     /// Remove a file from the list
     pub fn remove_file(&mut self, path: &str) {
         // Find index of file to remove
@@ -1,4 +1,9 @@
 use super::audio_file_info::AudioFileInfo;
+use super::file_drop;
+use super::loop_editor::LoopEditor;
+use super::loop_settings_modal::LoopSettings;
+use super::track_naming::NamingRule;
+use super::waveform::Waveform;
 use egui::{Context, ScrollArea, Ui, Window};
 use std::fs;
 use std::path::Path;
@@ -16,6 +21,8 @@ pub struct AddAudioSettings {
     pub estimated_duration: f32,
     /// Selected file path
     pub file_path: Option<String>,
+    /// Loop and gain written into the added WAV, as for a replacement
+    pub loop_settings: LoopSettings,
 }
 
 /// Modal window for adding new audio files
@@ -32,6 +39,12 @@ pub struct AddAudioModal {
     pub error: Option<String>,
     /// Existing audio files (to check for duplicates)
     pub existing_audio_files: Option<Vec<AudioFileInfo>>,
+    /// Peak preview of the selected file, if it could be decoded
+    waveform: Option<Waveform>,
+    /// How the name is derived from the selected file
+    pub naming: NamingRule,
+    /// Zoomable view for placing the custom loop points
+    loop_editor: LoopEditor,
 }
 
 impl Default for AddAudioModal {
@@ -50,6 +63,9 @@ impl AddAudioModal {
             file_data: None,
             error: None,
             existing_audio_files: None,
+            waveform: None,
+            naming: NamingRule::default(),
+            loop_editor: LoopEditor::new(),
         }
     }
 
@@ -97,20 +113,18 @@ impl AddAudioModal {
         self.existing_audio_files = existing_audio_files;
         self.settings.file_path = Some(file_path.to_string());
         self.error = None;
+        self.waveform = None;
+        self.loop_editor.reset_view();
+        // Added tracks did not loop before these options existed, so that stays the default
+        self.settings.loop_settings = LoopSettings { enable_loop: false, ..LoopSettings::default() };
         
         // Try to read the file data
         match fs::read(file_path) {
             Ok(data) => {
                 self.file_data = Some(data);
                 
-                // Set a default name based on the filename
-                let default_name = Path::new(file_path)
-                    .file_stem()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-                
-                self.settings.name = default_name;
+                // Set a default name based on the file, following the naming rule
+                self.settings.name = self.naming.name_for(Path::new(file_path));
                 
                 // Generate a unique ID considering all effective audio files (after pending changes)
                 use super::nus3audio_file_utils::Nus3audioFileUtils;
@@ -132,8 +146,15 @@ impl AddAudioModal {
                     "1000".to_string()
                 };
                 
+                self.waveform = Waveform::load(Path::new(file_path))
+                    .map_err(|e| println!("No waveform preview for {}: {}", file_path, e))
+                    .ok();
+
                 // Get file duration
-                let duration = match self.get_actual_audio_duration(file_path) {
+                let duration = match self
+                    .get_actual_audio_duration(file_path)
+                    .or(self.waveform.as_ref().map(|w| w.duration_secs))
+                {
                     Some(actual_duration) => {
                         println!(
                             "Using actual duration for new audio: {:.2}s",
@@ -154,6 +175,7 @@ impl AddAudioModal {
                 };
                 
                 self.settings.estimated_duration = duration;
+                self.settings.loop_settings.estimated_duration = duration;
             }
             Err(e) => {
                 println!("Failed to read audio file: {}", e);
@@ -166,11 +188,37 @@ impl AddAudioModal {
         self.confirmed = false;
     }
 
+    /// Use a dropped file instead of the selected one. A name still derived from the old
+    /// file follows the new one; a name typed by the user, the ID and whether to loop and
+    /// the gain are kept.
+    pub fn set_source(&mut self, file_path: &str) {
+        println!("Add audio source changed to: {}", file_path);
+        let keep_name = self
+            .settings
+            .file_path
+            .as_deref()
+            .is_some_and(|old| self.file_data.is_some() && self.naming.name_for(Path::new(old)) != self.settings.name);
+        let keep_id = self.file_data.is_some();
+        let (name, id) = (self.settings.name.clone(), self.settings.id.clone());
+        let (enable_loop, gain_db) = (self.settings.loop_settings.enable_loop, self.settings.loop_settings.gain_db);
+
+        self.open_with_file(file_path, self.existing_audio_files.clone());
+        self.settings.loop_settings.enable_loop = enable_loop;
+        self.settings.loop_settings.gain_db = gain_db;
+        if keep_name {
+            self.settings.name = name;
+        }
+        if keep_id {
+            self.settings.id = id;
+        }
+    }
+
     /// Close the modal
     pub fn close(&mut self) {
         self.open = false;
         // Clear file data to free memory
         self.file_data = None;
+        self.waveform = None;
     }
 
     /// Reset the confirmed flag
@@ -187,14 +235,21 @@ impl AddAudioModal {
         let min_width = available_rect.width() * 0.5;
         let min_height = available_rect.height() * 0.5;
 
-        Window::new("Add New Audio File")
+        let response = Window::new("Add New Audio File")
             .min_width(min_width)
             .min_height(min_height)
             .resizable(true)
             .collapsible(false)
             .show(ctx, |ui| {
                 self.render_content(ui);
+                file_drop::paint_drop_target(ui, ui.max_rect());
             });
+
+        if response.is_some() {
+            if let Some(path) = file_drop::dropped_audio_file(ctx) {
+                self.set_source(&path.to_string_lossy());
+            }
+        }
     }
 
     /// Render modal content
@@ -237,6 +292,16 @@ impl AddAudioModal {
                     ui.label(format!("{:.2} seconds", self.settings.estimated_duration));
                 });
 
+                if let Some(waveform) = &self.waveform {
+                    ui.add_space(6.0);
+                    waveform.show(ui, 64.0, None);
+                }
+                ui.label(
+                    egui::RichText::new("Drop an audio file onto this window to use it instead")
+                        .size(11.0)
+                        .weak(),
+                );
+
                 ui.add_space(20.0);
                 ui.separator();
                 ui.add_space(10.0);
@@ -252,6 +317,13 @@ impl AddAudioModal {
                     ui.label("Name:");
                     ui.text_edit_singleline(&mut self.settings.name);
                 });
+                egui::CollapsingHeader::new("Auto naming").show(ui, |ui| {
+                    if self.naming.show_controls(ui) {
+                        if let Some(path) = &self.settings.file_path {
+                            self.settings.name = self.naming.name_for(Path::new(path));
+                        }
+                    }
+                });
 
                 // Show error if name already exists (check effective audio list)
                 let name_exists = if !self.settings.name.is_empty() {
@@ -285,6 +357,11 @@ impl AddAudioModal {
                     ui.colored_label(egui::Color32::RED, "Error: ID already exists!");
                 }
 
+                ui.add_space(20.0);
+                ui.separator();
+                ui.add_space(10.0);
+                self.render_loop_and_gain(ui);
+
                 ui.add_space(20.0);
             });
 
@@ -327,7 +404,7 @@ impl AddAudioModal {
             });
         } else {
             // No file data
-            ui.label("No audio file loaded. Please select a valid audio file.");
+            ui.label("No audio file loaded. Please select a valid audio file or drop one here.");
             
             ui.add_space(20.0);
             ui.separator();
@@ -344,6 +421,71 @@ impl AddAudioModal {
         }
     }
 
+    /// Loop and gain controls, the same ones offered when replacing a track
+    fn render_loop_and_gain(&mut self, ui: &mut Ui) {
+        ui.vertical_centered(|ui| {
+            ui.heading("Loop and Gain");
+            ui.add_space(8.0);
+        });
+
+        let settings = &mut self.settings.loop_settings;
+        let sample_rate = self.waveform.as_ref().map(|w| w.sample_rate);
+        ui.checkbox(&mut settings.enable_loop, "Enable loop functionality");
+        if !settings.enable_loop {
+            settings.use_custom_loop = false;
+        }
+        ui.add_enabled_ui(settings.enable_loop, |ui| {
+            ui.checkbox(&mut settings.use_custom_loop, "Use custom loop points");
+        });
+
+        if settings.enable_loop && settings.use_custom_loop {
+            if let Some(waveform) = self.waveform.as_ref().filter(|w| w.frames() > 0) {
+                let last = waveform.frames() - 1;
+                let mut start = settings.loop_start_sample.map_or(0, |s| s as usize).min(last);
+                let mut end = settings.loop_end_sample.map_or(last, |e| e as usize).clamp(start, last);
+                if self.loop_editor.show(ui, waveform, &mut start, &mut end) {
+                    settings.set_loop_frames(waveform.sample_rate, start as u32, end as u32);
+                }
+                ui.add_space(6.0);
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Loop Start (seconds):");
+                let mut start = settings.loop_start.unwrap_or(0.0);
+                let drag = egui::DragValue::new(&mut start).speed(0.1).range(0.0..=settings.estimated_duration).suffix("s");
+                if ui.add(drag).changed() {
+                    settings.loop_start = Some(start);
+                    if settings.loop_end.is_some_and(|end| start > end) {
+                        settings.loop_end = Some(start);
+                    }
+                    settings.sync_frames(sample_rate);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Loop End (seconds):");
+                let mut end = settings.loop_end.unwrap_or(settings.estimated_duration);
+                let range = settings.loop_start.unwrap_or(0.0)..=settings.estimated_duration;
+                if ui.add(egui::DragValue::new(&mut end).speed(0.1).range(range).suffix("s")).changed() {
+                    settings.loop_end = Some(end);
+                    settings.sync_frames(sample_rate);
+                }
+            });
+        } else if settings.enable_loop {
+            ui.label("Audio will loop from beginning to end");
+        } else {
+            ui.label("Loop functionality is disabled");
+        }
+
+        ui.add_space(8.0);
+        ui.horizontal(|ui| {
+            ui.label("Gain (dB):");
+            ui.add(egui::Slider::new(&mut settings.gain_db, -24.0..=24.0).suffix(" dB"));
+            if ui.button("Reset").clicked() {
+                settings.gain_db = 0.0;
+            }
+        });
+    }
+
     /// Estimate audio duration from file size (rough approximation)
     fn estimate_duration_from_size(size_bytes: usize) -> f32 {
         // Very rough estimate: Assuming ~16KB per second for compressed audio
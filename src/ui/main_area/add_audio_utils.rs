@@ -1,31 +1,41 @@
 use super::add_audio_modal::AddAudioModal;
 use super::audio_file_info::AudioFileInfo;
+use super::path_utils;
+use super::replace_utils::ReplaceUtils;
 use rfd::FileDialog;
 use std::fs;
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
+use crate::audio_processing::decode;
+use crate::tools::VgmstreamRunner;
 use std::path::Path;
-use std::process::Command;
 
 /// Utility functions for adding new audio files
 pub struct AddAudioUtils;
 
 impl AddAudioUtils {
-    /// Convert selected audio file to WAV format using vgmstream
+    /// Convert selected audio file to WAV format, in memory for common formats and with
+    /// vgmstream for the rest
     pub fn convert_to_wav(file_path: &str) -> Result<Vec<u8>, String> {
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
-        if !vgmstream_path.exists() {
-            return Err(format!("vgmstream-cli not found at {:?}", vgmstream_path));
+        if decode::handles_extension(file_path) {
+            let data = fs::read(file_path).map_err(|e| format!("Failed to read audio file: {}", e))?;
+            let extension = Path::new(file_path).extension().and_then(|e| e.to_str());
+            match decode::decode_to_pcm16_wav(&data, extension) {
+                Ok(wav_data) => {
+                    println!("Decoded {:?} in memory: {} bytes", file_path, wav_data.len());
+                    return Ok(wav_data);
+                }
+                Err(e) => println!("In-memory decode of {:?} failed, using vgmstream-cli: {}", file_path, e),
+            }
         }
 
-        // Create a temporary output file path
-        let temp_dir = std::env::temp_dir();
+        let vgmstream = VgmstreamRunner::locate()?;
+
+        // Create a temporary output file path (ASCII-only so vgmstream can open it)
+        let temp_dir = path_utils::safe_temp_dir();
         let original_filename = Path::new(file_path)
             .file_name()
             .unwrap_or_default()
             .to_string_lossy();
-        let temp_filename = format!("temp_convert_{}.wav", original_filename);
+        let temp_filename = format!("temp_convert_{}.wav", path_utils::ascii_file_name(&original_filename));
         let temp_output_path = temp_dir.join(&temp_filename);
         let temp_output_path_str = temp_output_path.to_string_lossy().to_string();
 
@@ -35,23 +45,20 @@ impl AddAudioUtils {
         );
 
         // Run vgmstream-cli to convert audio to WAV
-        let mut command = Command::new(&vgmstream_path);
+        let mut command = vgmstream.command();
 
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
+        // Unicode or overlong source paths are copied to a safe temp name first
+        let tool_input = path_utils::ToolInput::prepare(Path::new(file_path))?;
 
         println!(
             "Running command: {:?} -o {} {}",
-            vgmstream_path,
+            vgmstream.path(),
             temp_output_path_str,
-            file_path
+            tool_input.arg()
         );
 
         let result = command
-            .args(["-o", &temp_output_path_str, file_path])
+            .args(["-o", &temp_output_path_str, &tool_input.arg()])
             .output();
         println!("vgmstream-cli command result: {:?}", result);
 
@@ -81,6 +88,17 @@ impl AddAudioUtils {
         }
     }
 
+    /// WAV data queued for the file picked in the modal, with its loop and gain applied the
+    /// way a replacement's are. Without either the file is only converted.
+    pub fn added_audio_data(add_audio_modal: &AddAudioModal) -> Result<Vec<u8>, String> {
+        let file_path = add_audio_modal.settings.file_path.as_deref().ok_or("No audio file path available")?;
+        let settings = &add_audio_modal.settings.loop_settings;
+        if !settings.enable_loop && settings.gain_db.abs() <= f32::EPSILON {
+            return Self::convert_to_wav(file_path);
+        }
+        ReplaceUtils::process_source(Path::new(file_path), settings)
+    }
+
     /// Show file dialog to select a new audio file and open the add audio modal
     pub fn add_with_file_dialog(
         add_audio_modal: &mut AddAudioModal,
@@ -120,7 +138,7 @@ impl AddAudioUtils {
         };
 
         // Convert the audio file to WAV format using vgmstream
-        let file_data = match Self::convert_to_wav(file_path) {
+        let file_data = match Self::added_audio_data(add_audio_modal) {
             Ok(wav_data) => wav_data,
             Err(e) => {
                 println!("Warning: Failed to convert to WAV: {}", e);
@@ -133,10 +151,23 @@ impl AddAudioUtils {
             }
         };
 
-        // Get name and ID from settings
-        let name = add_audio_modal.settings.name.clone();
-        let id = add_audio_modal.settings.id.clone();
+        Self::new_audio_info(
+            file_path,
+            add_audio_modal.settings.name.clone(),
+            &add_audio_modal.settings.id,
+            file_data.len(),
+            is_nus3bank,
+        )
+    }
 
+    /// Track info for a new track made from `file_path`, whose queued data is `size` bytes
+    pub fn new_audio_info(
+        file_path: &str,
+        name: String,
+        id: &str,
+        size: usize,
+        is_nus3bank: bool,
+    ) -> Result<AudioFileInfo, String> {
         if name.is_empty() || id.is_empty() {
             return Err("Name and ID cannot be empty".to_string());
         }
@@ -167,7 +198,7 @@ impl AddAudioUtils {
         let new_audio_info = AudioFileInfo {
             name,
             id: id_val.to_string(),
-            size: file_data.len(),
+            size,
             filename: format!(
                 "{}.wav",
                 Path::new(&filename)
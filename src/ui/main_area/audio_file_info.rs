@@ -1,3 +1,5 @@
+use crate::track_key::TrackKey;
+
 /// Structure to hold audio file information
 #[derive(Clone, Debug)]
 pub struct AudioFileInfo {
@@ -43,4 +45,22 @@ impl AudioFileInfo {
     pub fn effective_id(&self) -> &str {
         self.hex_id.as_ref().unwrap_or(&self.id)
     }
+
+    /// Key of this track in the pending-change stores
+    pub fn track_key(&self) -> TrackKey {
+        if self.is_nus3bank {
+            TrackKey::bank(self.effective_id(), &self.name)
+        } else {
+            TrackKey::audio(&self.name, &self.id)
+        }
+    }
+
+    /// Key of this track while it is queued by Add Audio
+    pub fn added_track_key(&self) -> TrackKey {
+        if self.is_nus3bank {
+            TrackKey::added(self.effective_id(), &self.name)
+        } else {
+            TrackKey::audio(&self.name, &self.id)
+        }
+    }
 }
@@ -0,0 +1,116 @@
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::audio_file_info::AudioFileInfo;
+use super::wav_metadata::utc_date_time;
+
+// Every edit action of the session, oldest first. Entries are only ever appended.
+static LOG: Lazy<Mutex<Vec<AuditEntry>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+// Bank the edits recorded without an explicit bank belong to; follows the opened file
+static CURRENT_BANK: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new(String::new()));
+
+/// One recorded edit action
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// UTC date as YYYY-MM-DD
+    pub date: String,
+    /// UTC time as HH:MM:SS
+    pub time: String,
+    /// Path of the bank the action applied to
+    pub bank: String,
+    pub action: String,
+}
+
+impl AuditEntry {
+    /// File name of the bank, for display
+    pub fn bank_name(&self) -> String {
+        Path::new(&self.bank)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.bank.clone())
+    }
+}
+
+/// Set the bank that following edits are recorded against
+pub fn set_bank(bank_path: Option<&str>) {
+    if let Ok(mut bank) = CURRENT_BANK.lock() {
+        *bank = bank_path.unwrap_or_default().to_string();
+    }
+}
+
+/// Record an edit of the opened bank
+pub fn record(action: impl Into<String>) {
+    let bank = CURRENT_BANK.lock().map(|b| b.clone()).unwrap_or_default();
+    record_for(&bank, action);
+}
+
+/// Record an edit of `bank_path`
+pub fn record_for(bank_path: &str, action: impl Into<String>) {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (date, time) = utc_date_time(secs);
+    if let Ok(mut log) = LOG.lock() {
+        log.push(AuditEntry {
+            date,
+            time,
+            bank: bank_path.to_string(),
+            action: action.into(),
+        });
+    }
+}
+
+/// A track as it appears in the log, e.g. `bgm_title (0x12)`
+pub fn describe(track: &AudioFileInfo) -> String {
+    format!("{} ({})", track.name, track.effective_id())
+}
+
+/// Copy of all entries, oldest first
+pub fn entries() -> Vec<AuditEntry> {
+    LOG.lock().map(|log| log.clone()).unwrap_or_default()
+}
+
+/// Entries as plain text, one line each
+pub fn to_text(entries: &[AuditEntry]) -> String {
+    entries
+        .iter()
+        .map(|e| format!("{} {} UTC  {}  {}\n", e.date, e.time, e.bank_name(), e.action))
+        .collect()
+}
+
+/// Entries as CSV with a header row
+pub fn to_csv(entries: &[AuditEntry]) -> String {
+    let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+    let mut out = String::from("date,time_utc,bank,action\n");
+    for e in entries {
+        out.push_str(&format!("{},{},{},{}\n", e.date, e.time, quote(&e.bank), quote(&e.action)));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_quoted_csv() {
+        let entries = vec![AuditEntry {
+            date: "2026-10-15".to_string(),
+            time: "09:30:00".to_string(),
+            bank: "/mods/se_unit.nus3bank".to_string(),
+            action: "Replaced \"bgm\" (0x12) with foo.wav".to_string(),
+        }];
+        assert_eq!(
+            to_csv(&entries),
+            "date,time_utc,bank,action\n2026-10-15,09:30:00,\"/mods/se_unit.nus3bank\",\"Replaced \"\"bgm\"\" (0x12) with foo.wav\"\n"
+        );
+        assert_eq!(
+            to_text(&entries),
+            "2026-10-15 09:30:00 UTC  se_unit.nus3bank  Replaced \"bgm\" (0x12) with foo.wav\n"
+        );
+    }
+}
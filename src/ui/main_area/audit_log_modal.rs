@@ -0,0 +1,125 @@
+use egui::{Color32, Context, RichText, Window};
+use egui_phosphor::regular;
+
+use super::audit_log::{self, AuditEntry};
+
+/// Shows the edit log of the session and exports it as text or CSV
+pub struct AuditLogModal {
+    pub open: bool,
+    /// Bank shown when `only_open_bank` is set
+    bank_path: Option<String>,
+    only_open_bank: bool,
+    status: Option<Result<String, String>>,
+}
+
+impl Default for AuditLogModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuditLogModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            bank_path: None,
+            only_open_bank: true,
+            status: None,
+        }
+    }
+
+    pub fn open_for(&mut self, bank_path: Option<&str>) {
+        self.bank_path = bank_path.map(str::to_string);
+        self.status = None;
+        self.open = true;
+    }
+
+    fn visible_entries(&self) -> Vec<AuditEntry> {
+        let entries = audit_log::entries();
+        match (&self.bank_path, self.only_open_bank) {
+            (Some(bank), true) => entries.into_iter().filter(|e| &e.bank == bank).collect(),
+            _ => entries,
+        }
+    }
+
+    fn export(entries: &[AuditEntry]) -> Option<Result<String, String>> {
+        let path = rfd::FileDialog::new()
+            .add_filter("Text", &["txt"])
+            .add_filter("CSV", &["csv"])
+            .set_file_name("edit_log.txt")
+            .save_file()?;
+        let is_csv = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let contents = if is_csv { audit_log::to_csv(entries) } else { audit_log::to_text(entries) };
+        Some(
+            std::fs::write(&path, contents)
+                .map(|_| format!("Exported {} entries to {}", entries.len(), path.display()))
+                .map_err(|e| format!("Failed to export the log: {}", e)),
+        )
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let entries = self.visible_entries();
+        let mut open = self.open;
+        Window::new("Edit Log")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(620.0)
+            .default_height(380.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add_enabled(
+                        self.bank_path.is_some(),
+                        egui::Checkbox::new(&mut self.only_open_bank, "Only the open file"),
+                    );
+                    ui.label(RichText::new(format!("{} entries", entries.len())).weak());
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .add_enabled(!entries.is_empty(), egui::Button::new(format!("{} Export...", regular::EXPORT)))
+                            .on_hover_text("Save the entries shown as text, or as CSV with a .csv name")
+                            .clicked()
+                        {
+                            if let Some(status) = Self::export(&entries) {
+                                self.status = Some(status);
+                            }
+                        }
+                    });
+                });
+                match &self.status {
+                    Some(Ok(message)) => {
+                        ui.colored_label(Color32::GREEN, message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(Color32::RED, message);
+                    }
+                    None => {}
+                }
+                ui.separator();
+
+                if entries.is_empty() {
+                    ui.label(RichText::new("No edits recorded yet").weak());
+                    return;
+                }
+                egui::ScrollArea::vertical().auto_shrink([false, false]).stick_to_bottom(true).show(ui, |ui| {
+                    egui::Grid::new("audit_log_grid").num_columns(3).striped(true).spacing([12.0, 4.0]).show(ui, |ui| {
+                        for entry in &entries {
+                            ui.label(RichText::new(format!("{} {}", entry.date, entry.time)).monospace().size(11.0));
+                            ui.label(RichText::new(entry.bank_name()).weak()).on_hover_text(&entry.bank);
+                            ui.label(&entry.action);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::wav_metadata::utc_date_time;
+
+/// How many timestamped backups are kept next to each file
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub retention: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self { retention: 5 }
+    }
+}
+
+/// A `.bak` copy found next to a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// UTC time of the copy as `YYYY-MM-DD HH:MM:SS.mmm`; backups made before milliseconds
+    /// were recorded have none
+    pub taken: String,
+    pub size: u64,
+}
+
+/// `bank.nus3bank` -> `bank.nus3bank.20261015-134901-250.bak`. Milliseconds keep two saves
+/// in the same second from writing the same backup.
+fn backup_path(file_path: &Path, millis: u64) -> PathBuf {
+    let (date, time) = utc_date_time(millis / 1000);
+    let stamp = format!("{}-{}-{:03}", date.replace('-', ""), time.replace(':', ""), millis % 1000);
+    let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{}.bak", stamp));
+    file_path.with_file_name(name)
+}
+
+/// Backups of `file_path`, newest first
+pub fn list(file_path: &Path) -> Vec<Backup> {
+    let Some(name) = file_path.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Vec::new();
+    };
+    let dir = match file_path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+
+    let mut backups: Vec<Backup> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let entry_name = entry.file_name().to_string_lossy().to_string();
+            let stamp = entry_name.strip_prefix(&name)?.strip_prefix('.')?.strip_suffix(".bak")?;
+            let (date, time) = stamp.split_once('-')?;
+            let (time, millis) = match time.split_once('-') {
+                Some((time, millis)) if millis.len() == 3 => (time, Some(millis)),
+                Some(_) => return None,
+                None => (time, None),
+            };
+            let mut digits = date.chars().chain(time.chars()).chain(millis.unwrap_or_default().chars());
+            if date.len() != 8 || time.len() != 6 || !digits.all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            Some(Backup {
+                path: entry.path(),
+                taken: format!(
+                    "{}-{}-{} {}:{}:{}{}",
+                    &date[0..4],
+                    &date[4..6],
+                    &date[6..8],
+                    &time[0..2],
+                    &time[2..4],
+                    &time[4..6],
+                    millis.map(|ms| format!(".{}", ms)).unwrap_or_default()
+                ),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.taken.cmp(&a.taken));
+    backups
+}
+
+/// Copy `file_path` to a timestamped `.bak` next to it, then delete the oldest backups
+/// beyond `retention`
+pub fn create(file_path: &Path, retention: usize) -> Result<PathBuf, String> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let path = backup_path(file_path, millis);
+    std::fs::copy(file_path, &path).map_err(|e| format!("Failed to create backup: {}", e))?;
+    println!("Created backup at {}", path.display());
+    prune(file_path, retention.max(1));
+    Ok(path)
+}
+
+/// Delete all but the newest `keep` backups
+pub fn prune(file_path: &Path, keep: usize) {
+    for old in list(file_path).into_iter().skip(keep) {
+        if let Err(e) = std::fs::remove_file(&old.path) {
+            println!("Warning: Failed to delete old backup {}: {}", old.path.display(), e);
+        }
+    }
+}
+
+/// Put `backup` back in place of `file_path`. The current file is backed up first so the
+/// restore can be undone.
+pub fn restore(backup: &Path, file_path: &Path, retention: usize) -> Result<(), String> {
+    // Read first: backing up the current file may prune the one being restored
+    let data = std::fs::read(backup).map_err(|e| format!("Failed to read backup: {}", e))?;
+    if file_path.exists() {
+        create(file_path, retention)?;
+    }
+    std::fs::write(file_path, data).map_err(|e| format!("Failed to restore backup: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_newest_first_and_prunes() {
+        let dir = std::env::temp_dir().join(format!("exvs2_backups_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bank = dir.join("se_test.nus3bank");
+        std::fs::write(&bank, b"bank").unwrap();
+        for millis in [1_700_000_000_000, 1_700_000_060_000, 1_700_000_120_000, 1_700_000_120_250] {
+            std::fs::copy(&bank, backup_path(&bank, millis)).unwrap();
+        }
+        // Written before backups recorded milliseconds
+        std::fs::write(dir.join("se_test.nus3bank.20231114-221300.bak"), b"bank").unwrap();
+        std::fs::write(dir.join("se_test.nus3bank.notes.bak"), b"x").unwrap();
+
+        let found = list(&bank);
+        assert_eq!(found.len(), 5);
+        assert_eq!(found[0].taken, "2023-11-14 22:15:20.250");
+        assert_eq!(found[0].path.file_name().unwrap(), "se_test.nus3bank.20231114-221520-250.bak");
+        assert_eq!(found[1].taken, "2023-11-14 22:15:20.000");
+        assert_eq!(found[4].taken, "2023-11-14 22:13:00");
+
+        prune(&bank, 3);
+        assert_eq!(list(&bank).len(), 3);
+        assert_eq!(list(&bank)[2].taken, "2023-11-14 22:14:20.000");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Color32, Context, RichText, Window};
+use egui_phosphor::regular;
+
+use super::backups::{self, Backup, BackupSettings};
+use super::save_summary::format_size;
+
+/// Lists the timestamped backups of the open file and restores one of them
+pub struct BackupsModal {
+    pub open: bool,
+    file_path: String,
+    backups: Vec<Backup>,
+    settings: BackupSettings,
+    /// Backup waiting for the second click
+    confirm: Option<usize>,
+    restore_request: Option<PathBuf>,
+    saved: Option<BackupSettings>,
+}
+
+impl Default for BackupsModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BackupsModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            file_path: String::new(),
+            backups: Vec::new(),
+            settings: BackupSettings::default(),
+            confirm: None,
+            restore_request: None,
+            saved: None,
+        }
+    }
+
+    pub fn open_for(&mut self, file_path: &str, settings: &BackupSettings) {
+        self.file_path = file_path.to_string();
+        self.backups = backups::list(Path::new(file_path));
+        self.settings = settings.clone();
+        self.confirm = None;
+        self.restore_request = None;
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Backup to put back, if one was confirmed since the last call
+    pub fn take_restore_request(&mut self) -> Option<PathBuf> {
+        self.restore_request.take()
+    }
+
+    /// Settings edited since the last call
+    pub fn take_saved(&mut self) -> Option<BackupSettings> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        Window::new("Restore Backup")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.file_path).weak().size(11.0));
+                ui.horizontal(|ui| {
+                    ui.label("Keep the last");
+                    if ui.add(egui::DragValue::new(&mut self.settings.retention).range(1..=50)).changed() {
+                        self.saved = Some(self.settings.clone());
+                    }
+                    ui.label("backups of each file");
+                });
+                ui.separator();
+
+                if self.backups.is_empty() {
+                    ui.label(RichText::new("No backups yet. One is written each time a file is saved in place.").weak());
+                    return;
+                }
+                egui::Grid::new("backups_grid").num_columns(3).striped(true).spacing([12.0, 4.0]).show(ui, |ui| {
+                    for (index, backup) in self.backups.iter().enumerate() {
+                        ui.label(format!("{} UTC", backup.taken)).on_hover_text(backup.path.to_string_lossy());
+                        ui.label(format_size(backup.size as usize));
+                        if self.confirm == Some(index) {
+                            if ui
+                                .button(RichText::new(format!("{} Overwrite the file", regular::WARNING)).color(Color32::from_rgb(255, 180, 60)))
+                                .on_hover_text("The current file is backed up first")
+                                .clicked()
+                            {
+                                self.restore_request = Some(backup.path.clone());
+                                self.confirm = None;
+                            }
+                        } else if ui.button("Restore").clicked() {
+                            self.confirm = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+
+        if self.restore_request.is_some() {
+            self.open = false;
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
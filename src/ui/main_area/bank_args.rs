@@ -0,0 +1,89 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::tools::split_command;
+
+// Overrides of the banks, keyed by bank path; mirrored from the persisted main area settings
+static OVERRIDES: Lazy<Mutex<HashMap<String, BankArgOverrides>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Extra command-line arguments remembered for one bank, for banks that only convert
+/// correctly with non-default flags
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct BankArgOverrides {
+    /// Added to every vgmstream-cli call that reads the bank, before the input file
+    pub vgmstream: String,
+    /// Appended to the LOPUS encoder command for replacements in the bank
+    pub encoder: String,
+}
+
+impl BankArgOverrides {
+    pub fn is_default(&self) -> bool {
+        self.vgmstream.trim().is_empty() && self.encoder.trim().is_empty()
+    }
+
+    /// Why the arguments cannot be used, if they cannot
+    pub fn validate(&self) -> Result<(), String> {
+        split_command(&self.vgmstream).map_err(|e| format!("vgmstream arguments: {}", e))?;
+        split_command(&self.encoder).map_err(|e| format!("Encoder arguments: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Replace the overrides used by conversions
+pub fn set_all(overrides: &HashMap<String, BankArgOverrides>) {
+    if let Ok(mut current) = OVERRIDES.lock() {
+        *current = overrides.clone();
+    }
+}
+
+/// Overrides of a bank, if it has any that are not default
+pub fn get(bank_path: &str) -> Option<BankArgOverrides> {
+    OVERRIDES.lock().ok()?.get(bank_path).filter(|o| !o.is_default()).cloned()
+}
+
+/// Extra encoder arguments for a bank, as template text for the encoder command
+pub fn encoder_args(bank_path: &str) -> String {
+    get(bank_path).map(|o| o.encoder.trim().to_string()).unwrap_or_default()
+}
+
+/// Insert the vgmstream-cli overrides of a bank before the input file, the last argument
+pub fn apply_vgmstream(args: &mut Vec<String>, bank_path: &str) {
+    let Some(overrides) = get(bank_path) else {
+        return;
+    };
+    match split_command(&overrides.vgmstream) {
+        Ok(extra) if !extra.is_empty() => {
+            println!("Using vgmstream-cli overrides for {}: {}", bank_path, extra.join(" "));
+            let at = args.len().saturating_sub(1);
+            args.splice(at..at, extra);
+        }
+        Ok(_) => {}
+        Err(e) => println!("Ignoring vgmstream-cli overrides for {}: {}", bank_path, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserts_overrides_before_input() {
+        let bank = "/test/bank_args/se_unit.nus3bank";
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            bank.to_string(),
+            BankArgOverrides { vgmstream: r#"-l 1 -f "0.5""#.to_string(), encoder: String::new() },
+        );
+        set_all(&overrides);
+
+        let mut args: Vec<String> = ["-o", "out.wav", "-s", "3", "in.nus3bank"].map(String::from).to_vec();
+        apply_vgmstream(&mut args, bank);
+        assert_eq!(args, ["-o", "out.wav", "-s", "3", "-l", "1", "-f", "0.5", "in.nus3bank"]);
+
+        let mut untouched = vec!["in.nus3bank".to_string()];
+        apply_vgmstream(&mut untouched, "/other.nus3bank");
+        assert_eq!(untouched, ["in.nus3bank"]);
+    }
+}
@@ -0,0 +1,110 @@
+use egui::{Color32, Context, RichText, Window};
+
+use super::bank_args::BankArgOverrides;
+
+/// Edits the extra vgmstream-cli and encoder arguments remembered for the open bank
+pub struct BankArgsModal {
+    pub open: bool,
+    bank_path: String,
+    draft: BankArgOverrides,
+    saved: Option<(String, BankArgOverrides)>,
+}
+
+impl Default for BankArgsModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BankArgsModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            bank_path: String::new(),
+            draft: BankArgOverrides::default(),
+            saved: None,
+        }
+    }
+
+    pub fn open_for(&mut self, bank_path: &str, current: BankArgOverrides) {
+        self.bank_path = bank_path.to_string();
+        self.draft = current;
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Bank path and overrides saved since the last call; default overrides mean removal
+    pub fn take_saved(&mut self) -> Option<(String, BankArgOverrides)> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut save = false;
+        Window::new("Conversion Arguments")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.bank_path).weak().size(11.0));
+                ui.add_space(6.0);
+                ui.label("Extra arguments used whenever this bank is converted. Leave empty for the defaults.");
+                ui.add_space(6.0);
+
+                egui::Grid::new("bank_args_grid").num_columns(2).spacing([10.0, 8.0]).show(ui, |ui| {
+                    ui.label("vgmstream-cli:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.draft.vgmstream)
+                            .hint_text("e.g. -l 1 -f 0")
+                            .desired_width(360.0),
+                    )
+                    .on_hover_text("Inserted before the input file of every decode, export and probe of this bank");
+                    ui.end_row();
+
+                    ui.label("LOPUS encoder:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.draft.encoder)
+                            .hint_text("e.g. --bitrate 96000")
+                            .desired_width(360.0),
+                    )
+                    .on_hover_text("Appended to the encoder command for replacements in this bank");
+                    ui.end_row();
+                });
+
+                let problem = self.draft.validate().err();
+                if let Some(problem) = &problem {
+                    ui.colored_label(Color32::RED, problem);
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Cancel").clicked() {
+                            self.open = false;
+                        }
+                        if ui.add_enabled(problem.is_none(), egui::Button::new("Save")).clicked() {
+                            save = true;
+                        }
+                        if ui
+                            .add_enabled(!self.draft.is_default(), egui::Button::new("Clear"))
+                            .clicked()
+                        {
+                            self.draft = BankArgOverrides::default();
+                        }
+                    });
+                });
+            });
+
+        if save {
+            self.saved = Some((self.bank_path.clone(), self.draft.clone()));
+            open = false;
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
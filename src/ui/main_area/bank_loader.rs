@@ -0,0 +1,147 @@
+use nus3audio::Nus3audioFile;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::audio_file_info::AudioFileInfo;
+use super::empty_tracks::{self, EmptyKind};
+use super::file_types::FileTypeRegistry;
+use super::quick_filters;
+use super::track_format::{self, TrackFormat};
+use crate::nus3bank::Nus3bankFile;
+use crate::track_key::TrackKey;
+
+/// Banks at least this large are parsed on a worker thread when opened from the UI
+pub const BACKGROUND_LOAD_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Rows of a NUS3BANK as the table lists them
+pub struct LoadedBank {
+    pub audio_files: Vec<AudioFileInfo>,
+    /// Empty tracks by track
+    pub empty_tracks: HashMap<TrackKey, EmptyKind>,
+    /// Tracks whose payload has loop points
+    pub looped: HashSet<TrackKey>,
+    /// Header formats by track
+    pub formats: HashMap<TrackKey, TrackFormat>,
+    /// Tone names by the slot they were read at
+    pub tone_names: Vec<String>,
+}
+
+/// Parse a NUS3BANK into table rows, calling `progress` with the bytes read and the file size.
+/// Payloads are read one track at a time to tell their type, so only one is in memory at once.
+pub fn read_nus3bank(path: &str, progress: &mut dyn FnMut(u64, u64)) -> Result<LoadedBank, String> {
+    let bank = Nus3bankFile::open(path).map_err(|e| format!("Error loading NUS3BANK file: {}", e))?;
+    let total = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let mut loaded = LoadedBank {
+        audio_files: Vec::with_capacity(bank.tracks.len()),
+        empty_tracks: HashMap::new(),
+        looped: HashSet::new(),
+        formats: HashMap::new(),
+        tone_names: bank.tone.tones.iter().map(|tone| tone.name.clone()).collect(),
+    };
+    for track in &bank.tracks {
+        let mut info = AudioFileInfo::from_nus3bank_track(
+            track.name.clone(),
+            track.index as u32,
+            track.hex_id.clone(),
+            track.size as usize,
+            track.filename(),
+        );
+        if let Ok(data) = bank.read_track_payload(track.index) {
+            info.file_type = FileTypeRegistry::type_name_of(&data, None);
+            if let Some(kind) = empty_tracks::check(&data) {
+                loaded.empty_tracks.insert(info.track_key(), kind);
+            }
+            if quick_filters::payload_loops(&data) {
+                loaded.looped.insert(info.track_key());
+            }
+            if let Some(format) = track_format::parse(&data) {
+                loaded.formats.insert(info.track_key(), format);
+            }
+        }
+        progress(bank.pack.file_offset + track.pack_offset as u64 + track.size as u64, total);
+        loaded.audio_files.push(info);
+    }
+    Ok(loaded)
+}
+
+/// Whether `path` is large enough to be worth loading in the background
+pub fn wants_background(path: &str) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| m.len() >= BACKGROUND_LOAD_BYTES)
+}
+
+/// Payload bytes of each track
+pub(super) fn load_payloads(file_path: &str, tracks: &[AudioFileInfo]) -> Result<HashMap<TrackKey, Vec<u8>>, String> {
+    let mut payloads = HashMap::new();
+    if tracks.iter().any(|t| t.is_nus3bank) {
+        let bank = Nus3bankFile::open(file_path).map_err(|e| format!("Failed to open NUS3BANK file: {}", e))?;
+        for info in tracks {
+            let data = info
+                .hex_id
+                .as_deref()
+                .and_then(|hex_id| bank.get_track_by_hex_id(hex_id))
+                .and_then(|track| bank.read_track_payload(track.index).ok());
+            if let Some(data) = data {
+                payloads.insert(info.track_key(), data);
+            }
+        }
+    } else {
+        let nus3 = Nus3audioFile::open(file_path).map_err(|e| format!("Failed to open NUS3AUDIO file: {}", e))?;
+        for info in tracks {
+            if let Some(file) = nus3.files.iter().find(|f| f.id.to_string() == info.id) {
+                payloads.insert(info.track_key(), file.data.clone());
+            }
+        }
+    }
+    Ok(payloads)
+}
+
+/// Progress of a background load, shared with the worker thread
+#[derive(Default)]
+struct LoadProgress {
+    read: u64,
+    total: u64,
+    result: Option<Result<LoadedBank, String>>,
+}
+
+/// A NUS3BANK being parsed on a worker thread. Dropping it discards the result.
+pub struct BankLoader {
+    path: String,
+    progress: Arc<Mutex<LoadProgress>>,
+}
+
+impl BankLoader {
+    pub fn start(path: &str) -> Self {
+        let progress = Arc::new(Mutex::new(LoadProgress::default()));
+        let shared = progress.clone();
+        let file_path = path.to_string();
+        std::thread::spawn(move || {
+            let result = read_nus3bank(&file_path, &mut |read, total| {
+                if let Ok(mut p) = shared.lock() {
+                    p.read = read;
+                    p.total = total;
+                }
+            });
+            if let Ok(mut p) = shared.lock() {
+                p.result = Some(result);
+            }
+        });
+        Self {
+            path: path.to_string(),
+            progress,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Bytes read so far and the file size
+    pub fn progress(&self) -> (u64, u64) {
+        self.progress.lock().map(|p| (p.read, p.total)).unwrap_or_default()
+    }
+
+    /// The parsed bank once the worker is done
+    pub fn poll(&self) -> Option<Result<LoadedBank, String>> {
+        self.progress.lock().ok().and_then(|mut p| p.result.take())
+    }
+}
@@ -0,0 +1,193 @@
+use super::audio_file_info::AudioFileInfo;
+use crate::ui::audio_player::gated_rms_dbfs;
+use crate::wav::riff::Riff;
+
+/// Number of tracks listed as largest, loudest and quietest
+pub const TOP_COUNT: usize = 10;
+
+/// Upper bounds of the size histogram buckets, in bytes; the last bucket is open-ended
+const SIZE_BUCKET_LIMITS: [usize; 5] = [16 << 10, 64 << 10, 256 << 10, 1 << 20, 4 << 20];
+
+/// Duration and level of one track, as far as they could be measured
+#[derive(Debug, Clone)]
+pub struct TrackMeasurement {
+    pub info: AudioFileInfo,
+    pub duration_secs: Option<f64>,
+    /// Gated RMS level in dBFS; `None` for silence or undecodable audio
+    pub level_dbfs: Option<f32>,
+}
+
+/// One bar of the size histogram
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeBucket {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Summary of a whole bank
+#[derive(Debug, Clone, Default)]
+pub struct BankStats {
+    pub track_count: usize,
+    pub total_size: usize,
+    /// Track count per codec, most common first
+    pub codec_counts: Vec<(String, usize)>,
+    pub total_duration_secs: f64,
+    /// Tracks whose duration is included in the total
+    pub measured_count: usize,
+    pub size_histogram: Vec<SizeBucket>,
+    pub largest: Vec<AudioFileInfo>,
+    pub loudest: Vec<(AudioFileInfo, f32)>,
+    pub quietest: Vec<(AudioFileInfo, f32)>,
+}
+
+fn size_label(bytes: usize) -> String {
+    if bytes >= 1 << 20 {
+        format!("{} MB", bytes >> 20)
+    } else {
+        format!("{} KB", bytes >> 10)
+    }
+}
+
+impl BankStats {
+    pub fn summarize(tracks: &[TrackMeasurement]) -> Self {
+        let mut codec_counts: Vec<(String, usize)> = Vec::new();
+        for track in tracks {
+            match codec_counts.iter_mut().find(|(codec, _)| *codec == track.info.file_type) {
+                Some((_, count)) => *count += 1,
+                None => codec_counts.push((track.info.file_type.clone(), 1)),
+            }
+        }
+        codec_counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut size_histogram: Vec<SizeBucket> = Vec::with_capacity(SIZE_BUCKET_LIMITS.len() + 1);
+        let mut lower = 0;
+        for limit in SIZE_BUCKET_LIMITS {
+            let label = if lower == 0 {
+                format!("< {}", size_label(limit))
+            } else {
+                format!("{} - {}", size_label(lower), size_label(limit))
+            };
+            let count = tracks.iter().filter(|t| t.info.size >= lower && t.info.size < limit).count();
+            size_histogram.push(SizeBucket { label, count });
+            lower = limit;
+        }
+        size_histogram.push(SizeBucket {
+            label: format!(">= {}", size_label(lower)),
+            count: tracks.iter().filter(|t| t.info.size >= lower).count(),
+        });
+
+        let mut largest: Vec<AudioFileInfo> = tracks.iter().map(|t| t.info.clone()).collect();
+        largest.sort_by(|a, b| b.size.cmp(&a.size));
+        largest.truncate(TOP_COUNT);
+
+        let mut levels: Vec<(AudioFileInfo, f32)> = tracks
+            .iter()
+            .filter_map(|t| t.level_dbfs.map(|level| (t.info.clone(), level)))
+            .collect();
+        levels.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let loudest = levels.iter().take(TOP_COUNT).cloned().collect();
+        let quietest = levels.iter().rev().take(TOP_COUNT).cloned().collect();
+
+        let durations: Vec<f64> = tracks.iter().filter_map(|t| t.duration_secs).collect();
+
+        Self {
+            track_count: tracks.len(),
+            total_size: tracks.iter().map(|t| t.info.size).sum(),
+            codec_counts,
+            total_duration_secs: durations.iter().sum(),
+            measured_count: durations.len(),
+            size_histogram,
+            largest,
+            loudest,
+            quietest,
+        }
+    }
+}
+
+/// Duration and level of a WAV payload. The duration works for any WAV codec with a byte
+/// rate; the level is only measured for PCM16.
+pub fn measure_wav(data: &[u8]) -> (Option<f64>, Option<f32>) {
+    // Truncated data chunks are measured up to the end of the buffer
+    let Ok(riff) = Riff::parse_wav(data) else {
+        return (None, None);
+    };
+    let samples = riff.get(b"data").and_then(|c| c.borrowed());
+    let (Some(fmt), Some(samples)) = (riff.fmt(), samples) else {
+        return (None, None);
+    };
+
+    let channels = fmt.channels.max(1) as usize;
+    let sample_rate = fmt.sample_rate;
+    let duration = (fmt.byte_rate > 0).then(|| samples.len() as f64 / fmt.byte_rate as f64);
+    let level = if fmt.tag == 1 && fmt.bits_per_sample == 16 && sample_rate > 0 {
+        let mono: Vec<f32> = samples
+            .chunks_exact(2 * channels)
+            .map(|frame| {
+                let sum: f32 = frame
+                    .chunks_exact(2)
+                    .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)
+                    .sum();
+                sum / channels as f32
+            })
+            .collect();
+        gated_rms_dbfs(&mono, sample_rate)
+    } else {
+        None
+    };
+    (duration, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(name: &str, size: usize, file_type: &str, level: Option<f32>) -> TrackMeasurement {
+        let info = AudioFileInfo::from_nus3audio(name.to_string(), name.to_string(), size, String::new(), file_type.to_string());
+        TrackMeasurement { info, duration_secs: Some(1.5), level_dbfs: level }
+    }
+
+    #[test]
+    fn summary_counts_codecs_sizes_and_levels() {
+        let tracks = vec![
+            track("a", 1000, "OPUS", Some(-12.0)),
+            track("b", 100_000, "WAV", Some(-30.0)),
+            track("c", 5 << 20, "OPUS", None),
+        ];
+        let stats = BankStats::summarize(&tracks);
+
+        assert_eq!(stats.codec_counts, vec![("OPUS".to_string(), 2), ("WAV".to_string(), 1)]);
+        assert_eq!(stats.measured_count, 3);
+        assert!((stats.total_duration_secs - 4.5).abs() < 1e-9);
+        let counts: Vec<usize> = stats.size_histogram.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 0, 1, 0, 0, 1]);
+        assert_eq!(stats.size_histogram[0].label, "< 16 KB");
+        assert_eq!(stats.size_histogram[5].label, ">= 4 MB");
+        assert_eq!(stats.largest[0].name, "c");
+        assert_eq!(stats.loudest[0].0.name, "a");
+        assert_eq!(stats.quietest[0].0.name, "b");
+    }
+
+    #[test]
+    fn measures_pcm16_duration_and_level() {
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        // Two seconds of a full-scale square wave, identical in both channels
+        for i in 0..16000 {
+            let s = if i % 2 == 0 { i16::MAX } else { -i16::MAX };
+            writer.write_sample(s).unwrap();
+            writer.write_sample(s).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let (duration, level) = measure_wav(&cursor.into_inner());
+        assert!((duration.unwrap() - 2.0).abs() < 1e-9);
+        assert!(level.unwrap().abs() < 0.01);
+        assert_eq!(measure_wav(b"OPUS\0\0\0\0"), (None, None));
+    }
+}
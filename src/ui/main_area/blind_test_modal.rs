@@ -0,0 +1,310 @@
+use egui::{Color32, Context, RichText, ScrollArea, Window};
+use std::fs;
+
+use super::audio_file_info::AudioFileInfo;
+
+/// Which audio a blind test slot actually plays
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlindSource {
+    Original,
+    Replacement,
+}
+
+impl BlindSource {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BlindSource::Original => "Original",
+            BlindSource::Replacement => "Replacement",
+        }
+    }
+}
+
+/// Answer to one blind test question
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlindChoice {
+    A,
+    B,
+    Same,
+}
+
+/// One answered trial
+#[derive(Debug, Clone)]
+pub struct BlindTrialResult {
+    pub track_name: String,
+    pub track_id: String,
+    /// File name of the replacement take that was compared
+    pub take: String,
+    pub trial: usize,
+    pub better: Option<BlindSource>,
+    pub louder: Option<BlindSource>,
+}
+
+/// Playback requested by the modal; the owner hands it to the audio player
+pub struct BlindPlayRequest {
+    pub audio_info: AudioFileInfo,
+    pub slot: &'static str,
+    pub data: Vec<u8>,
+}
+
+/// Blind A/B comparison between the original track and its pending replacement
+pub struct BlindTestModal {
+    pub open: bool,
+    audio_info: Option<AudioFileInfo>,
+    take: String,
+    original_data: Vec<u8>,
+    replacement_data: Vec<u8>,
+    /// Hidden assignment: true when slot A plays the replacement
+    a_is_replacement: bool,
+    better: Option<BlindChoice>,
+    louder: Option<BlindChoice>,
+    revealed: bool,
+    trial: usize,
+    play_request: Option<BlindPlayRequest>,
+    /// Answers of every trial in this session
+    pub log: Vec<BlindTrialResult>,
+    status: Option<(String, Color32)>,
+}
+
+impl Default for BlindTestModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlindTestModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            audio_info: None,
+            take: String::new(),
+            original_data: Vec::new(),
+            replacement_data: Vec::new(),
+            a_is_replacement: false,
+            better: None,
+            louder: None,
+            revealed: false,
+            trial: 0,
+            play_request: None,
+            log: Vec::new(),
+            status: None,
+        }
+    }
+
+    /// Start a blind test for a track. `take` identifies the replacement file being compared.
+    pub fn open(
+        &mut self,
+        audio_info: AudioFileInfo,
+        take: String,
+        original_data: Vec<u8>,
+        replacement_data: Vec<u8>,
+    ) {
+        self.trial = self
+            .log
+            .iter()
+            .filter(|r| r.track_name == audio_info.name && r.track_id == audio_info.id)
+            .count();
+        self.audio_info = Some(audio_info);
+        self.take = take;
+        self.original_data = original_data;
+        self.replacement_data = replacement_data;
+        self.status = None;
+        self.open = true;
+        self.new_trial();
+    }
+
+    /// Re-randomize the A/B assignment and clear the answers
+    fn new_trial(&mut self) {
+        self.a_is_replacement = rand::random::<bool>();
+        self.better = None;
+        self.louder = None;
+        self.revealed = false;
+        self.trial += 1;
+    }
+
+    fn source_of(&self, choice: BlindChoice) -> Option<BlindSource> {
+        let a = if self.a_is_replacement {
+            BlindSource::Replacement
+        } else {
+            BlindSource::Original
+        };
+        let b = if self.a_is_replacement {
+            BlindSource::Original
+        } else {
+            BlindSource::Replacement
+        };
+        match choice {
+            BlindChoice::A => Some(a),
+            BlindChoice::B => Some(b),
+            BlindChoice::Same => None,
+        }
+    }
+
+    /// Take the pending playback request, if any
+    pub fn take_play_request(&mut self) -> Option<BlindPlayRequest> {
+        self.play_request.take()
+    }
+
+    fn request_play(&mut self, slot: &'static str) {
+        let Some(audio_info) = &self.audio_info else {
+            return;
+        };
+        let play_replacement = (slot == "A") == self.a_is_replacement;
+        let data = if play_replacement {
+            self.replacement_data.clone()
+        } else {
+            self.original_data.clone()
+        };
+        self.play_request = Some(BlindPlayRequest {
+            audio_info: audio_info.clone(),
+            slot,
+            data,
+        });
+    }
+
+    fn submit(&mut self) {
+        let Some(audio_info) = &self.audio_info else {
+            return;
+        };
+        let result = BlindTrialResult {
+            track_name: audio_info.name.clone(),
+            track_id: audio_info.id.clone(),
+            take: self.take.clone(),
+            trial: self.trial,
+            better: self.better.and_then(|c| self.source_of(c)),
+            louder: self.louder.and_then(|c| self.source_of(c)),
+        };
+        println!(
+            "Blind test {} trial {} (take {}): better={}, louder={}",
+            result.track_name,
+            result.trial,
+            result.take,
+            result.better.map_or("no preference", |s| s.label()),
+            result.louder.map_or("same", |s| s.label())
+        );
+        self.log.push(result);
+        self.revealed = true;
+    }
+
+    /// Write the session log as CSV
+    pub fn export_log_csv(&self, path: &str) -> Result<(), String> {
+        let mut csv = String::from("track,id,take,trial,better,louder\n");
+        for r in &self.log {
+            csv.push_str(&format!(
+                "\"{}\",{},\"{}\",{},{},{}\n",
+                r.track_name.replace('"', "\"\""),
+                r.track_id,
+                r.take.replace('"', "\"\""),
+                r.trial,
+                r.better.map_or("No preference", |s| s.label()),
+                r.louder.map_or("Same", |s| s.label())
+            ));
+        }
+        fs::write(path, csv).map_err(|e| format!("Failed to write blind test log: {}", e))
+    }
+
+    fn choice_row(ui: &mut egui::Ui, value: &mut Option<BlindChoice>, same_label: &str, enabled: bool) {
+        ui.add_enabled_ui(enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.radio_value(value, Some(BlindChoice::A), "A");
+                ui.radio_value(value, Some(BlindChoice::B), "B");
+                ui.radio_value(value, Some(BlindChoice::Same), same_label);
+            });
+        });
+    }
+
+    /// Show the blind test window
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        let Some(audio_info) = self.audio_info.clone() else {
+            self.open = false;
+            return;
+        };
+
+        let mut open = self.open;
+        Window::new(format!("A/B Blind Test - {}", audio_info.name))
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(format!("Trial {} · take: {}", self.trial, self.take));
+                ui.label(
+                    RichText::new("A and B are the original and the replacement in random order.")
+                        .color(Color32::GRAY),
+                );
+                ui.add_space(8.0);
+
+                ui.horizontal(|ui| {
+                    if ui.button(RichText::new("▶ Play A").size(16.0)).clicked() {
+                        self.request_play("A");
+                    }
+                    if ui.button(RichText::new("▶ Play B").size(16.0)).clicked() {
+                        self.request_play("B");
+                    }
+                });
+
+                ui.add_space(8.0);
+                let answering = !self.revealed;
+                ui.label("Which sounds better?");
+                Self::choice_row(ui, &mut self.better, "No preference", answering);
+                ui.label("Which is louder?");
+                Self::choice_row(ui, &mut self.louder, "Same", answering);
+
+                ui.add_space(8.0);
+                if self.revealed {
+                    let a = self.source_of(BlindChoice::A).map_or("", |s| s.label());
+                    let b = self.source_of(BlindChoice::B).map_or("", |s| s.label());
+                    ui.label(RichText::new(format!("A = {}, B = {}", a, b)).strong());
+                    if ui.button("Next Trial").clicked() {
+                        self.new_trial();
+                    }
+                } else {
+                    let ready = self.better.is_some() && self.louder.is_some();
+                    if ui.add_enabled(ready, egui::Button::new("Submit & Reveal")).clicked() {
+                        self.submit();
+                    }
+                }
+
+                if !self.log.is_empty() {
+                    ui.add_space(8.0);
+                    ui.separator();
+                    egui::CollapsingHeader::new(format!("Session log ({})", self.log.len()))
+                        .default_open(false)
+                        .show(ui, |ui| {
+                            ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                                for r in &self.log {
+                                    ui.label(format!(
+                                        "{} #{} ({}): better {}, louder {}",
+                                        r.track_name,
+                                        r.trial,
+                                        r.take,
+                                        r.better.map_or("no preference", |s| s.label()),
+                                        r.louder.map_or("same", |s| s.label())
+                                    ));
+                                }
+                            });
+                        });
+
+                    if ui.button("Export Log (CSV)...").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("blind_test_log.csv")
+                            .save_file()
+                        {
+                            self.status = Some(match self.export_log_csv(&path.to_string_lossy()) {
+                                Ok(()) => (format!("Log written to {}", path.display()), Color32::GREEN),
+                                Err(e) => (e, Color32::RED),
+                            });
+                        }
+                    }
+                }
+
+                if let Some((message, color)) = &self.status {
+                    ui.colored_label(*color, message);
+                }
+            });
+        self.open = open;
+    }
+}
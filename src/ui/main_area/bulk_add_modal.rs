@@ -0,0 +1,197 @@
+use egui::{Color32, Context, Grid, RichText, ScrollArea, Window};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use super::audio_file_info::AudioFileInfo;
+use super::file_drop;
+use super::nus3audio_file_utils::Nus3audioFileUtils;
+use super::track_naming::NamingRule;
+
+/// One dropped file and the track it becomes
+#[derive(Debug, Clone)]
+pub struct BulkAddEntry {
+    pub path: PathBuf,
+    pub name: String,
+    pub id: String,
+    pub include: bool,
+}
+
+/// Names and IDs for several audio files dropped onto the table at once, added together
+pub struct BulkAddModal {
+    pub open: bool,
+    pub entries: Vec<BulkAddEntry>,
+    existing_audio_files: Option<Vec<AudioFileInfo>>,
+    confirmed: Option<Vec<BulkAddEntry>>,
+    /// How entry names are derived from their files
+    pub naming: NamingRule,
+}
+
+impl Default for BulkAddModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BulkAddModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            entries: Vec::new(),
+            existing_audio_files: None,
+            confirmed: None,
+            naming: NamingRule::default(),
+        }
+    }
+
+    /// Open with one entry per file, named after the file and numbered after the highest
+    /// ID in use, like a single Add
+    pub fn open_with_files(&mut self, paths: Vec<PathBuf>, existing_audio_files: Option<Vec<AudioFileInfo>>) {
+        self.existing_audio_files = existing_audio_files;
+        self.entries.clear();
+        self.confirmed = None;
+        self.add_files(paths);
+        self.open = true;
+    }
+
+    /// Append files, continuing the numbering of the entries already listed
+    fn add_files(&mut self, paths: Vec<PathBuf>) {
+        let effective = Nus3audioFileUtils::get_effective_audio_list(self.existing_audio_files.as_ref());
+        let max_id = effective
+            .iter()
+            .map(|(id, _)| id.as_str())
+            .chain(self.entries.iter().map(|e| e.id.as_str()))
+            .filter_map(|id| id.parse::<u32>().ok())
+            .max();
+        let mut next_id = max_id.map_or(1000, |id| id + 1);
+        for path in paths {
+            if self.entries.iter().any(|e| e.path == path) {
+                continue;
+            }
+            let name = self.naming.name_for(&path);
+            self.entries.push(BulkAddEntry {
+                path,
+                name,
+                id: next_id.to_string(),
+                include: true,
+            });
+            next_id += 1;
+        }
+    }
+
+    /// Entries confirmed since the last call
+    pub fn take_confirmed(&mut self) -> Option<Vec<BulkAddEntry>> {
+        self.confirmed.take()
+    }
+
+    /// Why each included entry cannot be added, if it cannot
+    fn problems(&self) -> Vec<Option<String>> {
+        let effective = Nus3audioFileUtils::get_effective_audio_list(self.existing_audio_files.as_ref());
+        let mut names: HashSet<&str> = effective.iter().map(|(_, name)| name.as_str()).collect();
+        let mut ids: HashSet<&str> = effective.iter().map(|(id, _)| id.as_str()).collect();
+        self.entries
+            .iter()
+            .map(|entry| {
+                if !entry.include {
+                    return None;
+                }
+                if entry.name.is_empty() {
+                    Some("Name is empty".to_string())
+                } else if entry.id.parse::<u32>().is_err() {
+                    Some("ID must be a number".to_string())
+                } else if !names.insert(&entry.name) {
+                    Some("Name already exists".to_string())
+                } else if !ids.insert(&entry.id) {
+                    Some("ID already exists".to_string())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut confirm = false;
+        let response = Window::new("Add Dropped Audio Files")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                let problems = self.problems();
+                let included = self.entries.iter().filter(|e| e.include).count();
+                ui.label(format!("{} file(s) will be added as new tracks.", included));
+                egui::CollapsingHeader::new("Auto naming").show(ui, |ui| {
+                    if self.naming.show_controls(ui) {
+                        for entry in &mut self.entries {
+                            entry.name = self.naming.name_for(&entry.path);
+                        }
+                    }
+                });
+                ui.add_space(6.0);
+
+                ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    Grid::new("bulk_add_grid").num_columns(4).striped(true).spacing([10.0, 6.0]).show(ui, |ui| {
+                        ui.label(RichText::new("File").strong());
+                        ui.label(RichText::new("Name").strong());
+                        ui.label(RichText::new("ID").strong());
+                        ui.label("");
+                        ui.end_row();
+
+                        for (entry, problem) in self.entries.iter_mut().zip(&problems) {
+                            let file_name = entry.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                            ui.checkbox(&mut entry.include, file_name)
+                                .on_hover_text(entry.path.display().to_string());
+                            ui.add_enabled(entry.include, egui::TextEdit::singleline(&mut entry.name).desired_width(200.0));
+                            ui.add_enabled(entry.include, egui::TextEdit::singleline(&mut entry.id).desired_width(80.0));
+                            match problem {
+                                Some(problem) => ui.colored_label(Color32::RED, problem),
+                                None => ui.label(""),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                ui.label(
+                    RichText::new("Drop more audio files onto this window to add them to the list")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Cancel").clicked() {
+                            self.open = false;
+                        }
+                        let valid = included > 0 && problems.iter().all(Option::is_none);
+                        if ui
+                            .add_enabled(valid, egui::Button::new(format!("Add {} Track(s)", included)))
+                            .clicked()
+                        {
+                            confirm = true;
+                        }
+                    });
+                });
+                file_drop::paint_drop_target(ui, ui.max_rect());
+            });
+
+        if response.is_some() {
+            let dropped = file_drop::dropped_audio_files(ctx);
+            if !dropped.is_empty() {
+                self.add_files(dropped);
+            }
+        }
+        if confirm {
+            self.confirmed = Some(self.entries.drain(..).filter(|e| e.include).collect());
+            open = false;
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use egui::Color32;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::track_key::TrackKey;
+
+/// Finder-style color tag of a table row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum ColorLabel {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl ColorLabel {
+    pub const ALL: [ColorLabel; 7] = [
+        ColorLabel::Red,
+        ColorLabel::Orange,
+        ColorLabel::Yellow,
+        ColorLabel::Green,
+        ColorLabel::Blue,
+        ColorLabel::Purple,
+        ColorLabel::Gray,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorLabel::Red => "Red",
+            ColorLabel::Orange => "Orange",
+            ColorLabel::Yellow => "Yellow",
+            ColorLabel::Green => "Green",
+            ColorLabel::Blue => "Blue",
+            ColorLabel::Purple => "Purple",
+            ColorLabel::Gray => "Gray",
+        }
+    }
+
+    pub fn color32(&self) -> Color32 {
+        match self {
+            ColorLabel::Red => Color32::from_rgb(235, 80, 80),
+            ColorLabel::Orange => Color32::from_rgb(240, 150, 50),
+            ColorLabel::Yellow => Color32::from_rgb(230, 200, 50),
+            ColorLabel::Green => Color32::from_rgb(90, 190, 90),
+            ColorLabel::Blue => Color32::from_rgb(80, 140, 240),
+            ColorLabel::Purple => Color32::from_rgb(170, 100, 220),
+            ColorLabel::Gray => Color32::from_rgb(150, 150, 150),
+        }
+    }
+}
+
+/// Labels of every bank the user has tagged, keyed by bank file name and then by track.
+/// Banks are matched by file name, so labels follow a bank that is copied between mod
+/// folders.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct ColorLabels {
+    banks: BTreeMap<String, BTreeMap<TrackKey, ColorLabel>>,
+}
+
+/// Key of a bank in per-bank row stores: its lowercase file name
+pub(super) fn bank_key(bank_path: &str) -> String {
+    Path::new(bank_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| bank_path.to_lowercase())
+}
+
+impl ColorLabels {
+    pub fn get(&self, bank_path: &str, key: &TrackKey) -> Option<ColorLabel> {
+        self.banks.get(&bank_key(bank_path))?.get(key).copied()
+    }
+
+    /// Set or clear (`None`) the label of a track
+    pub fn set(&mut self, bank_path: &str, key: &TrackKey, label: Option<ColorLabel>) {
+        let bank = bank_key(bank_path);
+        match label {
+            Some(label) => {
+                self.banks.entry(bank).or_default().insert(key.clone(), label);
+            }
+            None => {
+                if let Some(rows) = self.banks.get_mut(&bank) {
+                    rows.remove(key);
+                    if rows.is_empty() {
+                        self.banks.remove(&bank);
+                    }
+                }
+            }
+        }
+    }
+
+    /// All labels of one bank, by track
+    pub fn for_bank(&self, bank_path: &str) -> BTreeMap<TrackKey, ColorLabel> {
+        self.banks.get(&bank_key(bank_path)).cloned().unwrap_or_default()
+    }
+
+    /// Number of rows per label in one bank
+    pub fn count(&self, bank_path: &str, label: ColorLabel) -> usize {
+        self.banks
+            .get(&bank_key(bank_path))
+            .map(|rows| rows.values().filter(|l| **l == label).count())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_are_kept_per_bank_file_name() {
+        let (intro, outro) = (TrackKey::bank("0x0", "intro"), TrackKey::bank("0x1", "loop"));
+        let mut labels = ColorLabels::default();
+        labels.set("C:/mods/a/BGM_Stage.nus3bank", &intro, Some(ColorLabel::Red));
+        labels.set("C:/mods/a/BGM_Stage.nus3bank", &outro, Some(ColorLabel::Blue));
+        labels.set("C:/mods/b/other.nus3bank", &intro, Some(ColorLabel::Green));
+
+        assert_eq!(labels.get("D:/backup/bgm_stage.nus3bank", &intro), Some(ColorLabel::Red));
+        assert_eq!(labels.get("C:/mods/b/other.nus3bank", &intro), Some(ColorLabel::Green));
+        assert_eq!(labels.count("C:/mods/a/BGM_Stage.nus3bank", ColorLabel::Blue), 1);
+
+        labels.set("C:/mods/a/BGM_Stage.nus3bank", &intro, None);
+        labels.set("C:/mods/a/BGM_Stage.nus3bank", &outro, None);
+        assert!(labels.for_bank("C:/mods/a/BGM_Stage.nus3bank").is_empty());
+        assert_eq!(labels.banks.len(), 1);
+
+        let json = serde_json::to_string(&labels).unwrap();
+        let restored: ColorLabels = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get("other.nus3bank", &intro), Some(ColorLabel::Green));
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::track_key::TrackKey;
+
+// Tool output and warnings of the conversions behind each pending replacement, kept until
+// the replacements are cleared so a bad result can be explained after the fact
+static CONVERSION_LOGS: Lazy<Mutex<HashMap<TrackKey, Vec<String>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Start the log of a new replacement of `key` with `entries`, dropping the old one
+pub fn set(key: TrackKey, entries: Vec<String>) {
+    if let Ok(mut logs) = CONVERSION_LOGS.lock() {
+        if entries.is_empty() {
+            logs.remove(&key);
+        } else {
+            logs.insert(key, entries);
+        }
+    }
+}
+
+/// Add the entries of a later step, such as encoding, to the log of `key`
+pub fn append(key: TrackKey, entries: Vec<String>) {
+    if entries.is_empty() {
+        return;
+    }
+    if let Ok(mut logs) = CONVERSION_LOGS.lock() {
+        logs.entry(key).or_default().extend(entries);
+    }
+}
+
+/// Log of the track shown as `name` and `id` in a change list, as one text
+pub fn text_for(name: &str, id: &str) -> Option<String> {
+    let logs = CONVERSION_LOGS.lock().ok()?;
+    logs.iter()
+        .find(|(key, _)| {
+            let key_id = match key {
+                TrackKey::Audio { id, .. } => Some(id.as_str()),
+                _ => key.hex_id(),
+            };
+            key.name() == name && key_id == Some(id)
+        })
+        .map(|(_, entries)| entries.join("\n\n"))
+}
+
+pub fn clear() {
+    if let Ok(mut logs) = CONVERSION_LOGS.lock() {
+        logs.clear();
+    }
+}
+
+/// Copy of every log, for a snapshot of the pending replacements
+pub fn capture() -> HashMap<TrackKey, Vec<String>> {
+    CONVERSION_LOGS.lock().map(|logs| logs.clone()).unwrap_or_default()
+}
+
+/// Replace every log with a copy taken by `capture`
+pub fn restore(state: HashMap<TrackKey, Vec<String>>) {
+    if let Ok(mut logs) = CONVERSION_LOGS.lock() {
+        *logs = state;
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::VecDeque;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+
+use once_cell::sync::Lazy;
+
+/// Most decode threads the setting allows
+pub const MAX_THREADS: usize = 16;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Which queue a job waits in. Preview jobs are picked before any background job, so a track
+/// the user is looking at is not stuck behind a whole-bank analysis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    Preview,
+    Background,
+}
+
+#[derive(Default)]
+struct Queue {
+    preview: VecDeque<Job>,
+    background: VecDeque<Job>,
+    /// Threads wanted; 0 until the pool is first used or configured
+    threads: usize,
+    running: usize,
+}
+
+// Decoding, waveform and analysis work shared by every window. The pool is kept below the
+// core count so the UI thread always has a core to itself.
+static POOL: Lazy<(Mutex<Queue>, Condvar)> = Lazy::new(|| (Mutex::new(Queue::default()), Condvar::new()));
+
+/// Threads used when the setting is left on automatic: one core is left for the UI
+pub fn automatic_threads() -> usize {
+    std::thread::available_parallelism()
+        .map_or(2, |n| n.get())
+        .saturating_sub(1)
+        .clamp(1, MAX_THREADS)
+}
+
+/// Resize the pool; 0 means automatic. Surplus threads stop after their current job.
+pub fn set_threads(threads: usize) {
+    let threads = if threads == 0 { automatic_threads() } else { threads.min(MAX_THREADS) };
+    let (lock, wake) = &*POOL;
+    let Ok(mut queue) = lock.lock() else {
+        return;
+    };
+    queue.threads = threads;
+    while queue.running < queue.threads {
+        queue.running += 1;
+        let id = queue.running;
+        let spawned = std::thread::Builder::new()
+            .name(format!("decode-{}", id))
+            .spawn(worker);
+        if let Err(e) = spawned {
+            println!("Failed to start decode thread: {}", e);
+            queue.running -= 1;
+            break;
+        }
+    }
+    wake.notify_all();
+}
+
+/// Run `job` on the pool
+pub fn spawn(priority: Priority, job: impl FnOnce() + Send + 'static) {
+    let needs_threads = POOL.0.lock().is_ok_and(|q| q.threads == 0);
+    if needs_threads {
+        set_threads(0);
+    }
+    let (lock, wake) = &*POOL;
+    if let Ok(mut queue) = lock.lock() {
+        match priority {
+            Priority::Preview => queue.preview.push_back(Box::new(job)),
+            Priority::Background => queue.background.push_back(Box::new(job)),
+        }
+        wake.notify_one();
+    }
+}
+
+/// Apply `f` to every item on the pool at background priority and wait for the results, in
+/// the order of `items`. Must not be called from a pool thread.
+pub fn map<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+    F: Fn(T) -> R + Send + Sync + 'static,
+{
+    let count = items.len();
+    let f = Arc::new(f);
+    let (sender, receiver) = mpsc::channel();
+    for (index, item) in items.into_iter().enumerate() {
+        let (f, sender) = (f.clone(), sender.clone());
+        spawn(Priority::Background, move || {
+            let _ = sender.send((index, f(item)));
+        });
+    }
+    drop(sender);
+    let mut results: Vec<Option<R>> = (0..count).map(|_| None).collect();
+    for (index, result) in receiver {
+        results[index] = Some(result);
+    }
+    // A job that panicked leaves no result
+    results.into_iter().flatten().collect()
+}
+
+fn worker() {
+    let (lock, wake) = &*POOL;
+    loop {
+        let job = {
+            let Ok(mut queue) = lock.lock() else {
+                return;
+            };
+            loop {
+                if queue.running > queue.threads {
+                    queue.running -= 1;
+                    return;
+                }
+                if let Some(job) = queue.preview.pop_front().or_else(|| queue.background.pop_front()) {
+                    break job;
+                }
+                queue = match wake.wait(queue) {
+                    Ok(queue) => queue,
+                    Err(_) => return,
+                };
+            }
+        };
+        if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+            println!("A decode job panicked");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_keeps_the_order_of_items() {
+        set_threads(3);
+        let doubled = map((0..50).collect(), |n: u32| n * 2);
+        assert_eq!(doubled, (0..50).map(|n| n * 2).collect::<Vec<_>>());
+    }
+}
@@ -2,7 +2,7 @@ use egui::{Color32, Context, ScrollArea, Ui, Window};
 
 use crate::nus3bank::structures::{DtonSection, Nus3bankFile, ToneDes};
 
-use super::dton_pending;
+use super::{audit_log, dton_pending};
 
 pub struct DtonTonesModal {
     pub open: bool,
@@ -20,6 +20,8 @@ pub struct DtonTonesModal {
     data_parse_error: Option<String>,
     error: Option<String>,
     dirty: bool,
+    /// Pending edits were stored since the dialog opened; logged when it closes
+    edited: bool,
 }
 
 impl Default for DtonTonesModal {
@@ -43,6 +45,7 @@ impl DtonTonesModal {
             data_parse_error: None,
             error: None,
             dirty: false,
+            edited: false,
         }
     }
 
@@ -51,6 +54,7 @@ impl DtonTonesModal {
         self.error = None;
         self.data_parse_error = None;
         self.dirty = false;
+        self.edited = false;
 
         match self.load_tones_for_file(file_path) {
             Ok(tones) => {
@@ -93,6 +97,11 @@ impl DtonTonesModal {
         self.open = open;
         if was_open && !self.open {
             self.flush_pending();
+            if std::mem::take(&mut self.edited) {
+                if let Some(path) = self.file_path.as_deref() {
+                    audit_log::record_for(path, format!("Edited DTON tones ({} entries)", self.tones.len()));
+                }
+            }
         }
     }
 
@@ -403,6 +412,7 @@ impl DtonTonesModal {
             self.error = Some(e);
             return;
         }
+        self.edited = true;
         self.error = None;
         self.dirty = false;
     }
@@ -447,7 +457,9 @@ pub fn apply_dton_tones_to_file(file: &mut Nus3bankFile, tones: Vec<ToneDes>) {
         file.dton = None;
         return;
     }
-    file.dton = Some(DtonSection { tones });
+    // Keep the bytes as read, so saving unchanged tones writes them back untouched
+    let raw = file.dton.take().map(|d| d.raw).unwrap_or_default();
+    file.dton = Some(DtonSection { tones, raw });
 }
 
 fn parse_f32_list(text: &str) -> Result<Vec<f32>, String> {
@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use nus3audio::Nus3audioFile;
+
+use super::audio_file_info::AudioFileInfo;
+use super::file_types::FileTypeRegistry;
+use crate::nus3bank::Nus3bankFile;
+use crate::track_key::TrackKey;
+
+/// How tracks copied from the side pane find the track they replace in the open bank
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairBy {
+    /// The track of the same name
+    Name,
+    /// The n-th selected source replaces the n-th selected track of the open bank
+    SelectionOrder,
+}
+
+impl PairBy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PairBy::Name => "Same name",
+            PairBy::SelectionOrder => "Selection order",
+        }
+    }
+}
+
+/// A second bank shown next to the open one to port cues from. Read-only: its own edits
+/// live in its tab.
+pub struct DualPane {
+    pub open: bool,
+    pub pair_by: PairBy,
+    path: Option<String>,
+    tracks: Vec<AudioFileInfo>,
+    /// Keys of the selected tracks
+    selected: HashSet<TrackKey>,
+    error: Option<String>,
+}
+
+impl Default for DualPane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tracks of a bank as they are on disk
+pub fn list_tracks(path: &str) -> Result<Vec<AudioFileInfo>, String> {
+    if path.to_lowercase().ends_with(".nus3bank") {
+        let bank = Nus3bankFile::open(path).map_err(|e| format!("Error loading NUS3BANK file: {}", e))?;
+        Ok(bank
+            .tracks
+            .iter()
+            .map(|track| {
+                let mut info = AudioFileInfo::from_nus3bank_track(
+                    track.name.clone(),
+                    track.index as u32,
+                    track.hex_id.clone(),
+                    track.size as usize,
+                    track.filename(),
+                );
+                if let Ok(data) = bank.read_track_payload(track.index) {
+                    info.file_type = FileTypeRegistry::type_name_of(&data, None);
+                }
+                info
+            })
+            .collect())
+    } else {
+        let nus3 = Nus3audioFile::open(path).map_err(|e| format!("Error loading NUS3AUDIO file: {}", e))?;
+        Ok(nus3
+            .files
+            .iter()
+            .map(|file| {
+                let filename = FileTypeRegistry::file_name_of(&file.name, &file.data);
+                let file_type = FileTypeRegistry::type_name_of(&file.data, Some(&filename));
+                AudioFileInfo::from_nus3audio(file.name.clone(), file.id.to_string(), file.data.len(), filename, file_type)
+            })
+            .collect())
+    }
+}
+
+/// Pair each source with the target it replaces. Returns the pairs and the names of the
+/// sources left without a target.
+pub fn pair_tracks(
+    sources: &[AudioFileInfo],
+    targets: &[AudioFileInfo],
+    selected_targets: &[AudioFileInfo],
+    pair_by: PairBy,
+) -> (Vec<(AudioFileInfo, AudioFileInfo)>, Vec<String>) {
+    let mut pairs = Vec::new();
+    let mut unmatched = Vec::new();
+    for (index, source) in sources.iter().enumerate() {
+        let target = match pair_by {
+            PairBy::Name => targets.iter().find(|t| t.name == source.name),
+            PairBy::SelectionOrder => selected_targets.get(index),
+        };
+        match target {
+            Some(target) => pairs.push((source.clone(), target.clone())),
+            None => unmatched.push(source.name.clone()),
+        }
+    }
+    (pairs, unmatched)
+}
+
+impl DualPane {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            pair_by: PairBy::Name,
+            path: None,
+            tracks: Vec::new(),
+            selected: HashSet::new(),
+            error: None,
+        }
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Show `path` in the pane, dropping the previous selection
+    pub fn load(&mut self, path: &str) {
+        self.selected.clear();
+        match list_tracks(path) {
+            Ok(tracks) => {
+                self.tracks = tracks;
+                self.error = None;
+            }
+            Err(e) => {
+                self.tracks.clear();
+                self.error = Some(e);
+            }
+        }
+        self.path = Some(path.to_string());
+    }
+
+    /// Tracks matching the search of the main table, which must be lowercase
+    pub fn visible(&self, query: &str) -> Vec<&AudioFileInfo> {
+        self.tracks
+            .iter()
+            .filter(|t| {
+                query.is_empty()
+                    || t.name.to_lowercase().contains(query)
+                    || t.id.to_lowercase().contains(query)
+                    || t.file_type.to_lowercase().contains(query)
+            })
+            .collect()
+    }
+
+    pub fn is_selected(&self, info: &AudioFileInfo) -> bool {
+        self.selected.contains(&info.track_key())
+    }
+
+    pub fn toggle(&mut self, info: &AudioFileInfo) {
+        let key = info.track_key();
+        if !self.selected.remove(&key) {
+            self.selected.insert(key);
+        }
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+    }
+
+    /// Selected tracks in bank order
+    pub fn selected_tracks(&self) -> Vec<AudioFileInfo> {
+        self.tracks.iter().filter(|t| self.is_selected(t)).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_by_name_or_selection_order() {
+        let track = |name: &str, id: &str| AudioFileInfo::from_nus3audio(name.to_string(), id.to_string(), 0, String::new(), String::new());
+        let sources = vec![track("vo_win", "3"), track("vo_lose", "4")];
+        let targets = vec![track("vo_lose", "0"), track("vo_start", "1"), track("vo_hit", "2")];
+
+        let (pairs, unmatched) = pair_tracks(&sources, &targets, &[], PairBy::Name);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].0.id.as_str(), pairs[0].1.id.as_str()), ("4", "0"));
+        assert_eq!(unmatched, vec!["vo_win"]);
+
+        let selected = [targets[2].clone(), targets[1].clone()];
+        let (pairs, unmatched) = pair_tracks(&sources, &targets, &selected, PairBy::SelectionOrder);
+        assert_eq!(pairs.iter().map(|(s, t)| (s.name.as_str(), t.name.as_str())).collect::<Vec<_>>(), vec![("vo_win", "vo_hit"), ("vo_lose", "vo_start")]);
+        assert!(unmatched.is_empty());
+    }
+}
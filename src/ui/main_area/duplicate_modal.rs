@@ -0,0 +1,114 @@
+use egui::{Color32, Context, RichText, TextEdit, Window};
+
+use super::audio_file_info::AudioFileInfo;
+
+/// Asks for the name of a copy of a track; the copy takes the next free ID
+pub struct DuplicateModal {
+    pub open: bool,
+    track: Option<AudioFileInfo>,
+    name: String,
+    /// ID the copy gets, as shown in the table; NUS3BANK IDs are only known once saved
+    new_id: Option<String>,
+    /// Names already used in the file, pending additions included
+    taken: Vec<String>,
+    saved: Option<(AudioFileInfo, String)>,
+}
+
+impl Default for DuplicateModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DuplicateModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            track: None,
+            name: String::new(),
+            new_id: None,
+            taken: Vec::new(),
+            saved: None,
+        }
+    }
+
+    pub fn open_with(&mut self, track: &AudioFileInfo, new_id: Option<String>, taken: Vec<String>) {
+        self.name = (2..)
+            .map(|n| format!("{}_{}", track.name, n))
+            .find(|name| !taken.contains(name))
+            .unwrap_or_default();
+        self.track = Some(track.clone());
+        self.new_id = new_id;
+        self.taken = taken;
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Track to copy and the name of the copy
+    pub fn take_saved(&mut self) -> Option<(AudioFileInfo, String)> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        let Some(track) = self.track.clone() else {
+            self.open = false;
+            return;
+        };
+
+        let mut open = self.open;
+        let mut close = false;
+        Window::new("Duplicate Track")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                match &self.new_id {
+                    Some(id) => ui.label(format!("Copy {} to a new track with ID {}", track.name, id)),
+                    None => ui.label(format!(
+                        "Copy {} to a new track; its ID is assigned when the file is saved",
+                        track.name
+                    )),
+                };
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.add(TextEdit::singleline(&mut self.name).desired_width(200.0));
+                });
+
+                let name = self.name.trim();
+                let error = if name.is_empty() {
+                    Some("Enter a name")
+                } else if self.taken.iter().any(|taken| taken == name) {
+                    Some("Error: Name already exists!")
+                } else {
+                    None
+                };
+                if let Some(error) = error {
+                    ui.colored_label(Color32::RED, error);
+                }
+                let detail = if track.is_nus3bank {
+                    "The copy keeps the audio and tone settings of the track, and its pending replacement."
+                } else {
+                    "The copy keeps the audio of the track, and its pending replacement."
+                };
+                ui.label(RichText::new(detail).size(11.0).weak());
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(error.is_none(), egui::Button::new("Duplicate")).clicked() {
+                        self.saved = Some((track.clone(), name.to_string()));
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if !open || close {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::audio_processing::smpl;
+use crate::wav::riff::Riff;
+
+/// Samples this close to zero still count as silence (dither left by editors)
+const SILENCE_THRESHOLD: i16 = 2;
+
+/// Why a payload counts as empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyKind {
+    /// No bytes at all, or a WAV header without sample data (the 44-byte stub)
+    Stub,
+    /// A PCM16 WAV whose samples are all silence
+    Silent,
+    /// A WAV that ends before its chunks say it should, cut short by a bad rip or tool
+    Truncated,
+}
+
+impl EmptyKind {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            EmptyKind::Stub => "Empty: no audio data (header-only stub)",
+            EmptyKind::Silent => "Empty: every sample is silence",
+            EmptyKind::Truncated => "Truncated: the payload ends before its header says it should",
+        }
+    }
+
+    /// Badge shown after the type in the table
+    pub fn badge(&self) -> &'static str {
+        match self {
+            EmptyKind::Stub | EmptyKind::Silent => "Empty",
+            EmptyKind::Truncated => "Truncated",
+        }
+    }
+
+    /// Why Play is disabled, for kinds the decoder cannot play
+    pub fn unplayable_reason(&self) -> Option<&'static str> {
+        match self {
+            EmptyKind::Stub => Some("Nothing to play: the track is an empty placeholder"),
+            EmptyKind::Truncated => Some("Cannot play: the track's payload is cut short"),
+            EmptyKind::Silent => None,
+        }
+    }
+}
+
+/// Whether a track payload was nulled out or cut short. Only WAV payloads can be checked
+/// without decoding; other codecs are never flagged.
+pub fn check(data: &[u8]) -> Option<EmptyKind> {
+    if data.is_empty() {
+        return Some(EmptyKind::Stub);
+    }
+    if !data.starts_with(b"RIFF") {
+        return None;
+    }
+    if data.len() <= 44 {
+        return Some(EmptyKind::Stub);
+    }
+    if chunk_overruns(data) {
+        return Some(EmptyKind::Truncated);
+    }
+    let (_, samples) = smpl::pcm16_samples(data)?;
+    if samples.len() < 2 {
+        return Some(EmptyKind::Stub);
+    }
+    samples
+        .chunks_exact(2)
+        .all(|s| i16::from_le_bytes([s[0], s[1]]).abs() <= SILENCE_THRESHOLD)
+        .then_some(EmptyKind::Silent)
+}
+
+/// Whether a WAV chunk claims more bytes than the payload has left
+fn chunk_overruns(data: &[u8]) -> bool {
+    Riff::parse_wav(data).is_ok() && Riff::parse_wav_exact(data).is_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_processing::splice;
+
+    #[test]
+    fn flags_stubs_and_silence_only() {
+        assert_eq!(check(&splice::write_pcm16(2, 48000, &[])), Some(EmptyKind::Stub));
+        assert_eq!(check(&[]), Some(EmptyKind::Stub));
+
+        let quiet: Vec<u8> = [0i16, 1, -2, 0].iter().flat_map(|s| s.to_le_bytes()).collect();
+        assert_eq!(check(&splice::write_pcm16(1, 48000, &quiet.repeat(10))), Some(EmptyKind::Silent));
+
+        let tone: Vec<u8> = [0i16, 400, -400, 0].iter().flat_map(|s| s.to_le_bytes()).collect();
+        assert_eq!(check(&splice::write_pcm16(1, 48000, &tone.repeat(10))), None);
+        assert_eq!(check(b"OPUS\0\0\0\0"), None);
+
+        let cut = splice::write_pcm16(1, 48000, &tone.repeat(10));
+        assert_eq!(check(&cut[..cut.len() - 10]), Some(EmptyKind::Truncated));
+    }
+}
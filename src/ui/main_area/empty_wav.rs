@@ -0,0 +1,143 @@
+use crate::wav::riff::Riff;
+
+/// Default length of the silence written for Replace with Empty, in milliseconds
+pub const DEFAULT_MIN_SILENCE_MS: u32 = 100;
+
+/// Upper bound for the configurable silence length
+pub const MAX_MIN_SILENCE_MS: u32 = 10_000;
+
+/// Format used when the original track's format cannot be read
+pub const FALLBACK_FORMAT: SilentFormat = SilentFormat {
+    sample_rate: 48000,
+    channels: 2,
+};
+
+/// Sample rate and channel layout of a silent PCM16 replacement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SilentFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl SilentFormat {
+    fn block_align(&self) -> u32 {
+        self.channels as u32 * 2
+    }
+}
+
+/// Read the format of a RIFF/WAVE buffer from its `fmt ` chunk
+pub fn format_from_wav(data: &[u8]) -> Option<SilentFormat> {
+    let riff = Riff::parse_wav_exact(data).ok()?;
+    let fmt = riff.fmt()?;
+    if fmt.channels == 0 || fmt.sample_rate == 0 {
+        return None;
+    }
+    Some(SilentFormat { sample_rate: fmt.sample_rate, channels: fmt.channels })
+}
+
+/// Number of frames needed for at least `min_ms` of audio, never zero
+pub fn frames_for(sample_rate: u32, min_ms: u32) -> u32 {
+    let frames = (sample_rate as u64 * min_ms as u64).div_ceil(1000);
+    frames.clamp(1, u32::MAX as u64) as u32
+}
+
+/// Build a PCM16 WAV of silence in `format` lasting at least `min_ms`
+pub fn build_silent_wav(format: SilentFormat, min_ms: u32) -> Vec<u8> {
+    let block_align = format.block_align();
+    let data_len = frames_for(format.sample_rate, min_ms) * block_align;
+
+    let mut out = Vec::with_capacity(44 + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    out.extend_from_slice(&format.channels.to_le_bytes());
+    out.extend_from_slice(&format.sample_rate.to_le_bytes());
+    out.extend_from_slice(&(format.sample_rate * block_align).to_le_bytes());
+    out.extend_from_slice(&(block_align as u16).to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    out.resize(out.len() + data_len as usize, 0);
+    out
+}
+
+/// Check that a silent replacement will be accepted by the bank: a PCM16 RIFF/WAVE whose
+/// header sizes agree with the buffer and whose data chunk holds at least one whole frame
+pub fn validate(data: &[u8]) -> Result<(), String> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return Err("not a RIFF/WAVE buffer".to_string());
+    }
+    let riff_size = u32::from_le_bytes(data[4..8].try_into().unwrap_or_default()) as usize;
+    if riff_size + 8 != data.len() {
+        return Err(format!("RIFF size {} does not match buffer length {}", riff_size, data.len()));
+    }
+    let riff = Riff::parse_wav_exact(data)?;
+    riff.get(b"fmt ").ok_or("missing fmt chunk")?;
+    let fmt = riff.fmt().ok_or("fmt chunk is too short")?;
+    let block_align = fmt.block_align as usize;
+    if fmt.tag != 1 || fmt.bits_per_sample != 16 {
+        return Err(format!(
+            "expected PCM16, found format 0x{:04X} with {} bits",
+            fmt.tag, fmt.bits_per_sample
+        ));
+    }
+    if format_from_wav(data).is_none() || block_align == 0 {
+        return Err("fmt chunk has zero channels, sample rate or block align".to_string());
+    }
+    let data_len = riff.get(b"data").ok_or("missing data chunk")?.data.len();
+    if data_len == 0 {
+        return Err("data chunk is empty".to_string());
+    }
+    if data_len % block_align != 0 {
+        return Err(format!("data length {} is not a multiple of block align {}", data_len, block_align));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silent_wav_matches_format_and_length() {
+        let format = SilentFormat { sample_rate: 32000, channels: 2 };
+        let wav = build_silent_wav(format, 100);
+        assert!(validate(&wav).is_ok());
+        assert_eq!(format_from_wav(&wav), Some(format));
+
+        let reader = hound::WavReader::new(std::io::Cursor::new(wav)).unwrap();
+        assert_eq!(reader.spec().sample_rate, 32000);
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.duration(), 3200);
+
+        // Zero milliseconds still produces one frame
+        assert_eq!(frames_for(44100, 0), 1);
+        assert_eq!(frames_for(44100, 1), 45);
+    }
+
+    #[test]
+    fn legacy_header_only_wav_is_rejected() {
+        let mut wav = build_silent_wav(SilentFormat { sample_rate: 8000, channels: 1 }, 0);
+        wav.truncate(44);
+        wav[4..8].copy_from_slice(&36u32.to_le_bytes());
+        wav[40..44].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(validate(&wav), Err("data chunk is empty".to_string()));
+
+        let mut odd = build_silent_wav(FALLBACK_FORMAT, 10);
+        odd.push(0);
+        let riff_size = (odd.len() - 8) as u32;
+        odd[4..8].copy_from_slice(&riff_size.to_le_bytes());
+        let data_len = (odd.len() - 44) as u32;
+        odd[40..44].copy_from_slice(&data_len.to_le_bytes());
+        assert!(validate(&odd).unwrap_err().contains("block align"));
+
+        // A data chunk claiming more than the buffer holds is cut off, not accepted
+        let mut cut = build_silent_wav(FALLBACK_FORMAT, 10);
+        let data_len = u32::from_le_bytes(cut[40..44].try_into().unwrap()) + 4;
+        cut[40..44].copy_from_slice(&data_len.to_le_bytes());
+        assert_eq!(validate(&cut), Err("data chunk runs past the end of the file".to_string()));
+    }
+}
@@ -0,0 +1,187 @@
+use egui::{Button, Color32, Context, RichText, Window};
+
+use super::audio_file_info::AudioFileInfo;
+
+/// Which tracks Export All writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    All,
+    Filtered,
+    Selected,
+    /// Every bank open in a tab, as it is on disk
+    OpenedBanks,
+}
+
+impl ExportScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportScope::All => "All tracks",
+            ExportScope::Filtered => "Filtered tracks",
+            ExportScope::Selected => "Selected tracks",
+            ExportScope::OpenedBanks => "All opened banks",
+        }
+    }
+}
+
+/// Confirm dialog for Export All with a choice of scope
+pub struct ExportAllModal {
+    pub open: bool,
+    pub scope: ExportScope,
+    total: usize,
+    /// Tracks shown by the current search/filter, in table order
+    filtered: Vec<AudioFileInfo>,
+    /// Selected tracks, in table order
+    selected: Vec<AudioFileInfo>,
+    /// Paths of the banks open in tabs
+    banks: Vec<String>,
+    confirmed: Option<ExportScope>,
+}
+
+impl Default for ExportAllModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportAllModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            scope: ExportScope::All,
+            total: 0,
+            filtered: Vec::new(),
+            selected: Vec::new(),
+            banks: Vec::new(),
+            confirmed: None,
+        }
+    }
+
+    /// Open the dialog. The narrowest non-empty scope is preselected: the selection if there
+    /// is one, otherwise the filtered list when a filter hides tracks.
+    pub fn open(
+        &mut self,
+        total: usize,
+        filtered: Vec<AudioFileInfo>,
+        selected: Vec<AudioFileInfo>,
+        banks: Vec<String>,
+    ) {
+        self.scope = if !selected.is_empty() {
+            ExportScope::Selected
+        } else if filtered.len() < total {
+            ExportScope::Filtered
+        } else {
+            ExportScope::All
+        };
+        self.total = total;
+        self.filtered = filtered;
+        self.selected = selected;
+        self.banks = banks;
+        self.confirmed = None;
+        self.open = true;
+    }
+
+    fn count(&self, scope: ExportScope) -> usize {
+        match scope {
+            ExportScope::All => self.total,
+            ExportScope::Filtered => self.filtered.len(),
+            ExportScope::Selected => self.selected.len(),
+            ExportScope::OpenedBanks => self.banks.len(),
+        }
+    }
+
+    /// Take the confirmed export: `None` for the whole file, `Some(tracks)` for a subset
+    pub fn take_confirmed(&mut self) -> Option<Option<Vec<AudioFileInfo>>> {
+        let scope = self.confirmed.filter(|s| *s != ExportScope::OpenedBanks)?;
+        self.confirmed = None;
+        let tracks = match scope {
+            // Opened banks are handed out by `take_confirmed_banks`
+            ExportScope::All | ExportScope::OpenedBanks => None,
+            ExportScope::Filtered => Some(std::mem::take(&mut self.filtered)),
+            ExportScope::Selected => Some(std::mem::take(&mut self.selected)),
+        };
+        self.selected.clear();
+        self.filtered.clear();
+        Some(tracks)
+    }
+
+    /// Take the confirmed export of every opened bank
+    pub fn take_confirmed_banks(&mut self) -> Option<Vec<String>> {
+        if self.confirmed != Some(ExportScope::OpenedBanks) {
+            return None;
+        }
+        self.confirmed = None;
+        self.selected.clear();
+        self.filtered.clear();
+        Some(std::mem::take(&mut self.banks))
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        Window::new("Confirm Export All")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("Export which tracks to WAV?");
+                ui.add_space(6.0);
+                for scope in [ExportScope::All, ExportScope::Filtered, ExportScope::Selected] {
+                    let count = self.count(scope);
+                    // The filtered scope is only meaningful while a filter hides something
+                    let enabled = count > 0 && (scope != ExportScope::Filtered || count < self.total);
+                    ui.add_enabled_ui(enabled, |ui| {
+                        ui.radio_value(&mut self.scope, scope, format!("{} ({})", scope.label(), count));
+                    });
+                }
+                if self.banks.len() > 1 {
+                    let scope = ExportScope::OpenedBanks;
+                    ui.radio_value(&mut self.scope, scope, format!("{} ({} banks)", scope.label(), self.banks.len()))
+                        .on_hover_text("Each bank goes to its own subfolder, mirroring the folders the banks were opened from");
+                    if self.scope == scope {
+                        ui.label(
+                            RichText::new("Banks are exported as saved on disk, without pending edits.")
+                                .size(11.0)
+                                .color(Color32::GRAY),
+                        );
+                    }
+                }
+                ui.add_space(6.0);
+                ui.label(
+                    RichText::new("Large exports may take some time.")
+                        .size(11.0)
+                        .color(Color32::GRAY),
+                );
+                ui.add_space(10.0);
+
+                let count = self.count(self.scope);
+                let button_text = if self.scope == ExportScope::OpenedBanks {
+                    format!("Export {} banks", count)
+                } else {
+                    format!("Export {}", count)
+                };
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            count > 0,
+                            Button::new(RichText::new(button_text).color(Color32::WHITE))
+                                .fill(Color32::from_rgb(220, 50, 50)),
+                        )
+                        .clicked()
+                    {
+                        self.confirmed = Some(self.scope);
+                        self.open = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.open = false;
+                    }
+                });
+            });
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::Path;
+
+use super::path_utils::{ToolInput, ToolOutput};
+use crate::audio_processing::flac;
+use crate::tools::FfmpegRunner;
+
+/// MP3 bitrates offered in the output settings, in kbit/s
+pub const MP3_BITRATES: [u32; 7] = [96, 128, 160, 192, 224, 256, 320];
+
+/// File format exports are written in. Every export is decoded to PCM16 WAV first and then
+/// encoded: FLAC in-process, OGG Vorbis and MP3 with ffmpeg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+pub enum ExportFormat {
+    #[default]
+    Wav,
+    Flac,
+    OggVorbis,
+    Mp3,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Wav,
+        ExportFormat::Flac,
+        ExportFormat::OggVorbis,
+        ExportFormat::Mp3,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "WAV (PCM16)",
+            ExportFormat::Flac => "FLAC",
+            ExportFormat::OggVorbis => "OGG Vorbis",
+            ExportFormat::Mp3 => "MP3",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Wav => "wav",
+            ExportFormat::Flac => "flac",
+            ExportFormat::OggVorbis => "ogg",
+            ExportFormat::Mp3 => "mp3",
+        }
+    }
+
+    /// Whether encoding needs ffmpeg
+    pub fn needs_ffmpeg(&self) -> bool {
+        matches!(self, ExportFormat::OggVorbis | ExportFormat::Mp3)
+    }
+}
+
+/// Export format with the settings of each encoder, kept even while another format is selected
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct ExportFormatSettings {
+    pub format: ExportFormat,
+    /// 0 (fastest) to `flac::MAX_LEVEL` (smallest)
+    pub flac_level: u8,
+    /// Vorbis VBR quality, -1 to 10
+    pub vorbis_quality: f32,
+    /// Constant MP3 bitrate in kbit/s
+    pub mp3_bitrate_kbps: u32,
+}
+
+impl Default for ExportFormatSettings {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Wav,
+            flac_level: 5,
+            vorbis_quality: 6.0,
+            mp3_bitrate_kbps: 192,
+        }
+    }
+}
+
+impl ExportFormatSettings {
+    /// Arguments that make ffmpeg encode `input` into `output` in the selected format
+    fn ffmpeg_args(&self, input: &str, output: &str) -> Vec<String> {
+        let mut args: Vec<String> = ["-y", "-hide_banner", "-loglevel", "error", "-i", input]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        match self.format {
+            ExportFormat::OggVorbis => args.extend([
+                "-c:a".to_string(),
+                "libvorbis".to_string(),
+                "-q:a".to_string(),
+                format!("{:.1}", self.vorbis_quality.clamp(-1.0, 10.0)),
+            ]),
+            ExportFormat::Mp3 => args.extend([
+                "-c:a".to_string(),
+                "libmp3lame".to_string(),
+                "-b:a".to_string(),
+                format!("{}k", self.mp3_bitrate_kbps),
+            ]),
+            ExportFormat::Wav | ExportFormat::Flac => {}
+        }
+        args.push(output.to_string());
+        args
+    }
+
+    /// Encode an exported WAV into the selected format next to it and remove the WAV.
+    /// Returns the path of the file that is kept.
+    pub fn encode_exported(&self, wav_path: &str) -> Result<String, String> {
+        if self.format == ExportFormat::Wav {
+            return Ok(wav_path.to_string());
+        }
+        let output = Path::new(wav_path).with_extension(self.format.extension());
+
+        if self.format == ExportFormat::Flac {
+            let wav = fs::read(wav_path).map_err(|e| format!("Failed to read {}: {}", wav_path, e))?;
+            let encoded = flac::encode_wav(&wav, self.flac_level)?;
+            fs::write(&output, encoded).map_err(|e| format!("Failed to write {:?}: {}", output, e))?;
+        } else {
+            let ffmpeg = FfmpegRunner::locate()?;
+            let tool_input = ToolInput::prepare(Path::new(wav_path))?;
+            let tool_output = ToolOutput::new(&output);
+            ffmpeg.run(&self.ffmpeg_args(&tool_input.arg(), &tool_output.arg()))?;
+            tool_output.finish()?;
+        }
+
+        let _ = fs::remove_file(wav_path);
+        Ok(output.to_string_lossy().to_string())
+    }
+
+    /// Encode every exported WAV. Files that fail stay as WAV and their errors are returned.
+    pub fn encode_all_exported(&self, wav_paths: Vec<String>) -> (Vec<String>, Vec<String>) {
+        let mut paths = Vec::with_capacity(wav_paths.len());
+        let mut errors = Vec::new();
+        for path in wav_paths {
+            match self.encode_exported(&path) {
+                Ok(encoded) => paths.push(encoded),
+                Err(e) => {
+                    errors.push(format!("{}: {}", path, e));
+                    paths.push(path);
+                }
+            }
+        }
+        (paths, errors)
+    }
+}
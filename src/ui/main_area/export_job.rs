@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::audio_file_info::AudioFileInfo;
+use super::dual_pane;
+use super::export_format::ExportFormatSettings;
+use super::export_journal::ExportJournal;
+use super::export_utils::ExportUtils;
+use super::lip_sync;
+use super::wav_metadata::{self, ExportMetadata};
+use crate::file_names;
+
+/// Most tracks exported at once; every track runs its own vgmstream-cli process
+const MAX_WORKERS: usize = 8;
+
+/// What an Export All writes and where
+#[derive(Debug, Clone)]
+pub struct ExportRequest {
+    pub file_path: String,
+    pub output_dir: String,
+    /// `None` exports every track of the file as it is on disk
+    pub tracks: Option<Vec<AudioFileInfo>>,
+    pub embed_metadata: bool,
+    pub lip_sync_timestamps: bool,
+    pub format: ExportFormatSettings,
+    /// Skip tracks an interrupted run of the same export already wrote
+    pub resume: bool,
+}
+
+/// Progress shared with the worker threads
+#[derive(Default)]
+struct JobState {
+    total: usize,
+    done: usize,
+    /// Tracks being exported right now
+    current: Vec<String>,
+    paths: Vec<String>,
+    failures: Vec<String>,
+    renamed: Vec<String>,
+    /// Tracks left from an interrupted run
+    skipped: usize,
+    finished: bool,
+}
+
+/// Outcome of a finished or cancelled export
+#[derive(Debug, Clone)]
+pub struct ExportSummary {
+    pub output_dir: String,
+    pub total: usize,
+    /// Written files, after encoding
+    pub paths: Vec<String>,
+    pub failures: Vec<String>,
+    /// Tracks whose names could not be used as file names as they are, with the name written
+    pub renamed: Vec<String>,
+    /// Tracks an interrupted run had already written
+    pub skipped: usize,
+    pub cancelled: bool,
+    pub elapsed: Duration,
+}
+
+/// Export All running on a pool of worker threads
+pub struct ExportJob {
+    state: Arc<Mutex<JobState>>,
+    cancel: Arc<AtomicBool>,
+    started: Instant,
+    output_dir: String,
+}
+
+impl ExportJob {
+    pub fn start(request: ExportRequest) -> Self {
+        let output_dir = request.output_dir.clone();
+        Self::start_all(output_dir, vec![request])
+    }
+
+    /// Export several files one after another, each into its own output folder
+    pub fn start_all(output_dir: String, requests: Vec<ExportRequest>) -> Self {
+        let state = Arc::new(Mutex::new(JobState::default()));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (shared, stop) = (state.clone(), cancel.clone());
+        std::thread::spawn(move || {
+            for request in &requests {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                run(request, &shared, &stop);
+            }
+            if let Ok(mut s) = shared.lock() {
+                s.finished = true;
+            }
+        });
+        Self {
+            state,
+            cancel,
+            started: Instant::now(),
+            output_dir,
+        }
+    }
+
+    /// Stop after the tracks being exported now
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Tracks done, the track count and the tracks being exported now
+    pub fn progress(&self) -> (usize, usize, Vec<String>) {
+        self.state
+            .lock()
+            .map(|s| (s.done, s.total, s.current.clone()))
+            .unwrap_or_default()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// The summary once every worker has stopped
+    pub fn poll(&self) -> Option<ExportSummary> {
+        let mut state = self.state.lock().ok()?;
+        if !state.finished {
+            return None;
+        }
+        Some(ExportSummary {
+            output_dir: self.output_dir.clone(),
+            total: state.total,
+            paths: std::mem::take(&mut state.paths),
+            failures: std::mem::take(&mut state.failures),
+            renamed: std::mem::take(&mut state.renamed),
+            skipped: state.skipped,
+            cancelled: self.is_cancelled(),
+            elapsed: self.elapsed(),
+        })
+    }
+}
+
+fn run(request: &ExportRequest, state: &Mutex<JobState>, cancel: &AtomicBool) {
+    let fail = |e: String| {
+        if let Ok(mut s) = state.lock() {
+            let file_name = Path::new(&request.file_path).file_name().unwrap_or_default().to_string_lossy();
+            s.failures.push(format!("{}: {}", file_name, e));
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&request.output_dir) {
+        return fail(format!("Failed to create {}: {}", request.output_dir, e));
+    }
+    let whole_file = request.tracks.is_none();
+    let tracks = match &request.tracks {
+        Some(tracks) => tracks.clone(),
+        None => match dual_pane::list_tracks(&request.file_path) {
+            Ok(tracks) => tracks,
+            Err(e) => return fail(e),
+        },
+    };
+    // The whole-file export takes codecs from the payloads rather than the table
+    let metadata = if whole_file && request.embed_metadata {
+        match ExportUtils::export_metadata_by_file_name(&request.file_path) {
+            Ok(metadata) => Some(metadata),
+            Err(e) => return fail(e),
+        }
+    } else {
+        None
+    };
+    if let Ok(mut s) = state.lock() {
+        s.total += tracks.len();
+    }
+
+    let mut journal = ExportJournal::for_request(request, tracks.len());
+    if request.resume {
+        if let Some(previous) = ExportJournal::load(&request.output_dir).filter(|j| j.same_export(&journal)) {
+            journal.completed = previous.completed;
+        }
+    }
+    if let Err(e) = journal.save(&request.output_dir) {
+        println!("{}", e);
+    }
+    let journal = Mutex::new(journal);
+
+    let next = AtomicUsize::new(0);
+    let workers = std::thread::available_parallelism()
+        .map_or(2, |n| n.get())
+        .min(MAX_WORKERS)
+        .min(tracks.len())
+        .max(1);
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !cancel.load(Ordering::Relaxed) {
+                    let Some(track) = tracks.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                        break;
+                    };
+                    let key = track.track_key();
+                    let written = journal.lock().ok().and_then(|j| j.written(&key).map(str::to_string));
+                    if let Some(path) = written {
+                        if let Ok(mut s) = state.lock() {
+                            s.done += 1;
+                            s.skipped += 1;
+                            s.paths.push(path);
+                        }
+                        continue;
+                    }
+                    if let Ok(mut s) = state.lock() {
+                        s.current.push(track.name.clone());
+                    }
+                    let result = export_track(request, track, metadata.as_ref());
+                    if let (Ok((path, _)), Ok(mut j)) = (&result, journal.lock()) {
+                        j.record(key, path);
+                        if let Err(e) = j.save(&request.output_dir) {
+                            println!("{}", e);
+                        }
+                    }
+                    let Ok(mut s) = state.lock() else {
+                        break;
+                    };
+                    if let Some(pos) = s.current.iter().position(|n| *n == track.name) {
+                        s.current.remove(pos);
+                    }
+                    s.done += 1;
+                    match result {
+                        Ok((path, problems)) => {
+                            if file_names::safe_file_name(&track.name) != track.name {
+                                let written = Path::new(&path).file_name().unwrap_or_default().to_string_lossy();
+                                s.renamed.push(format!("{:?} written as {}", track.name, written));
+                            }
+                            s.paths.push(path);
+                            s.failures.extend(problems);
+                        }
+                        Err(e) => {
+                            println!("Failed to export {}: {}", track.name, e);
+                            s.failures.push(format!("{}: {}", track.name, e));
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    // Only an interrupted export leaves its journal behind
+    if !cancel.load(Ordering::Relaxed) {
+        ExportJournal::remove(&request.output_dir);
+    }
+}
+
+/// Export one track and run the follow-up steps. Problems after the WAV was written come
+/// back next to its path.
+fn export_track(
+    request: &ExportRequest,
+    track: &AudioFileInfo,
+    metadata: Option<&HashMap<String, ExportMetadata>>,
+) -> Result<(String, Vec<String>), String> {
+    let path = if request.tracks.is_none() {
+        let path = ExportUtils::export_track_as_in_file(track, &request.file_path, &request.output_dir)?;
+        let file_name = Path::new(&path).file_name().map(|n| n.to_string_lossy().to_string());
+        if let Some(meta) = metadata.zip(file_name).and_then(|(m, name)| m.get(&name)) {
+            wav_metadata::embed_in_file(&path, meta)?;
+        }
+        path
+    } else {
+        ExportUtils::export_to_wav_with_custom_dir_unified(
+            track,
+            &request.file_path,
+            &request.output_dir,
+            request.embed_metadata,
+        )?
+    };
+
+    let mut problems = Vec::new();
+    if request.lip_sync_timestamps {
+        if let Err(e) = lip_sync::write_for_wav(&path) {
+            problems.push(format!("{}: timestamps failed: {}", track.name, e));
+        }
+    }
+    let path = match request.format.encode_exported(&path) {
+        Ok(encoded) => encoded,
+        Err(e) => {
+            problems.push(format!(
+                "{}: {} encoding failed, kept as WAV: {}",
+                track.name,
+                request.format.format.name(),
+                e
+            ));
+            path
+        }
+    };
+    Ok((path, problems))
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use super::export_format::ExportFormatSettings;
+use super::export_job::ExportRequest;
+use super::snapshots;
+use crate::track_key::TrackKey;
+
+/// Journal kept in the output folder while an Export All runs
+const JOURNAL_FILE: &str = ".exvs2_export_progress.json";
+
+/// A file an export wrote, with the hash of its contents at the time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WrittenFile {
+    pub path: String,
+    pub hash: u64,
+}
+
+/// Tracks an Export All has finished so far, written after every track. A finished export
+/// removes it; a cancelled or crashed one leaves it for the next run to resume from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportJournal {
+    pub file_path: String,
+    /// Size and modification time of the source file, so an edited bank starts over
+    pub source_stamp: (u64, u64),
+    /// Keys of the exported tracks; `None` for the whole file
+    pub tracks: Option<Vec<TrackKey>>,
+    pub embed_metadata: bool,
+    pub lip_sync_timestamps: bool,
+    pub format: ExportFormatSettings,
+    pub total: usize,
+    /// Written files by track
+    pub completed: BTreeMap<TrackKey, WrittenFile>,
+}
+
+impl ExportJournal {
+    /// Empty journal for an export of `total` tracks
+    pub fn for_request(request: &ExportRequest, total: usize) -> Self {
+        Self {
+            file_path: request.file_path.clone(),
+            source_stamp: source_stamp(&request.file_path),
+            tracks: request
+                .tracks
+                .as_ref()
+                .map(|tracks| tracks.iter().map(|t| t.track_key()).collect()),
+            embed_metadata: request.embed_metadata,
+            lip_sync_timestamps: request.lip_sync_timestamps,
+            format: request.format.clone(),
+            total,
+            completed: BTreeMap::new(),
+        }
+    }
+
+    /// Whether both journals describe the same export of the same source
+    pub fn same_export(&self, other: &Self) -> bool {
+        self.file_path == other.file_path
+            && self.source_stamp == other.source_stamp
+            && self.tracks == other.tracks
+            && self.embed_metadata == other.embed_metadata
+            && self.lip_sync_timestamps == other.lip_sync_timestamps
+            && self.format == other.format
+    }
+
+    /// Journal left in `output_dir` by an earlier export, if any
+    pub fn load(output_dir: &str) -> Option<Self> {
+        let text = std::fs::read_to_string(journal_path(output_dir)).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    pub fn save(&self, output_dir: &str) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to write export journal: {}", e))?;
+        std::fs::write(journal_path(output_dir), text).map_err(|e| format!("Failed to write export journal: {}", e))
+    }
+
+    pub fn remove(output_dir: &str) {
+        let _ = std::fs::remove_file(journal_path(output_dir));
+    }
+
+    pub fn record(&mut self, key: TrackKey, path: &str) {
+        if let Some(hash) = snapshots::hash_file(path) {
+            self.completed.insert(key, WrittenFile { path: path.to_string(), hash });
+        }
+    }
+
+    /// Path of a track written earlier, if the file is still there unchanged
+    pub fn written(&self, key: &TrackKey) -> Option<&str> {
+        let file = self.completed.get(key)?;
+        (snapshots::hash_file(&file.path) == Some(file.hash)).then_some(file.path.as_str())
+    }
+}
+
+/// Tracks done and the track count of an interrupted export that `request` would resume
+pub fn resumable(request: &ExportRequest) -> Option<(usize, usize)> {
+    let journal = ExportJournal::load(&request.output_dir)?;
+    (journal.same_export(&ExportJournal::for_request(request, 0)) && !journal.completed.is_empty())
+        .then_some((journal.completed.len(), journal.total))
+}
+
+fn journal_path(output_dir: &str) -> PathBuf {
+    Path::new(output_dir).join(JOURNAL_FILE)
+}
+
+fn source_stamp(path: &str) -> (u64, u64) {
+    std::fs::metadata(path)
+        .map(|m| {
+            let modified = m
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+            (m.len(), modified)
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_only_unchanged_written_files() {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!("exvs2_export_journal_test_{}", nonce));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir_str = dir.to_string_lossy().to_string();
+        let wav = dir.join("bgm_01.wav").to_string_lossy().to_string();
+        std::fs::write(&wav, b"RIFF exported").unwrap();
+
+        let request = ExportRequest {
+            file_path: wav.clone(),
+            output_dir: dir_str.clone(),
+            tracks: None,
+            embed_metadata: false,
+            lip_sync_timestamps: false,
+            format: ExportFormatSettings::default(),
+            resume: true,
+        };
+        let mut journal = ExportJournal::for_request(&request, 2);
+        let key = TrackKey::audio("bgm_01", "0");
+        journal.record(key.clone(), &wav);
+        journal.save(&dir_str).unwrap();
+
+        assert_eq!(resumable(&request), Some((1, 2)));
+        let loaded = ExportJournal::load(&dir_str).unwrap();
+        assert_eq!(loaded.written(&key), Some(wav.as_str()));
+        std::fs::write(&wav, b"RIFF changed").unwrap();
+        assert_eq!(loaded.written(&key), None);
+
+        ExportJournal::remove(&dir_str);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
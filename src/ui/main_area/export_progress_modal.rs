@@ -0,0 +1,204 @@
+use egui::{Color32, Context, ProgressBar, RichText, ScrollArea, Window};
+
+use super::export_job::{ExportJob, ExportRequest, ExportSummary};
+
+/// Progress of a running Export All, then a summary of what was written and what failed
+pub struct ExportProgressModal {
+    pub open: bool,
+    job: Option<ExportJob>,
+    summary: Option<ExportSummary>,
+    /// Export waiting for the user to resume or restart it, with the tracks done and total
+    resume_offer: Option<(ExportRequest, usize, usize)>,
+}
+
+impl Default for ExportProgressModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportProgressModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            job: None,
+            summary: None,
+            resume_offer: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Start exporting in the background and show the progress
+    pub fn start(&mut self, request: ExportRequest) {
+        self.summary = None;
+        self.job = Some(ExportJob::start(request));
+        self.open = true;
+    }
+
+    /// Ask whether to pick up an interrupted run of `request` before starting it
+    pub fn offer_resume(&mut self, request: ExportRequest, done: usize, total: usize) {
+        self.summary = None;
+        self.resume_offer = Some((request, done, total));
+        self.open = true;
+    }
+
+    fn show_resume_offer(&mut self, ctx: &Context) {
+        let Some((request, done, total)) = &self.resume_offer else {
+            return;
+        };
+        let mut choice = None;
+        Window::new("Resume Export?")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "An earlier export with the same settings stopped after {} of {} tracks.",
+                    done, total
+                ));
+                ui.label(RichText::new(&request.output_dir).size(11.0).weak());
+                ui.label("Resuming skips the files it wrote, unless they were changed since.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Resume").clicked() {
+                        choice = Some(true);
+                    }
+                    if ui.button("Start over").clicked() {
+                        choice = Some(false);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.open = false;
+                    }
+                });
+            });
+        if !self.open {
+            self.resume_offer = None;
+        } else if let Some(resume) = choice {
+            if let Some((mut request, _, _)) = self.resume_offer.take() {
+                request.resume = resume;
+                self.start(request);
+            }
+        }
+    }
+
+    /// Start exporting several files into their own folders under `output_dir`
+    pub fn start_all(&mut self, output_dir: String, requests: Vec<ExportRequest>) {
+        self.summary = None;
+        self.job = Some(ExportJob::start_all(output_dir, requests));
+        self.open = true;
+    }
+
+    fn poll(&mut self) {
+        if let Some(summary) = self.job.as_ref().and_then(ExportJob::poll) {
+            self.job = None;
+            self.summary = Some(summary);
+            self.open = true;
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        self.poll();
+        if !self.open {
+            return;
+        }
+        if self.resume_offer.is_some() {
+            self.show_resume_offer(ctx);
+            return;
+        }
+
+        if let Some(job) = &self.job {
+            let (done, total, current) = job.progress();
+            let cancelled = job.is_cancelled();
+            let mut cancel = false;
+            Window::new("Exporting")
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .show(ctx, |ui| {
+                    ui.label(format!("{} / {} tracks, {}", done, total, format_elapsed(job.elapsed().as_secs())));
+                    ui.add(ProgressBar::new(done as f32 / total.max(1) as f32).show_percentage());
+                    let current = if cancelled {
+                        "Stopping after the tracks in progress...".to_string()
+                    } else {
+                        current.join(", ")
+                    };
+                    ui.label(RichText::new(current).size(11.0).weak());
+                    ui.add_space(6.0);
+                    if ui.add_enabled(!cancelled, egui::Button::new("Cancel")).clicked() {
+                        cancel = true;
+                    }
+                });
+            if cancel {
+                job.cancel();
+            }
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        }
+
+        let Some(summary) = &self.summary else {
+            self.open = false;
+            return;
+        };
+        let mut open = self.open;
+        let mut close = false;
+        Window::new("Export Finished")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                let verb = if summary.cancelled { "Cancelled after exporting" } else { "Exported" };
+                ui.label(format!(
+                    "{} {} of {} tracks in {}",
+                    verb,
+                    summary.paths.len(),
+                    summary.total,
+                    format_elapsed(summary.elapsed.as_secs())
+                ));
+                if summary.skipped > 0 {
+                    ui.label(format!("{} written by the earlier run were kept", summary.skipped));
+                }
+                ui.label(RichText::new(&summary.output_dir).size(11.0).weak());
+                if !summary.renamed.is_empty() {
+                    ui.add_space(6.0);
+                    ui.colored_label(
+                        Color32::GOLD,
+                        format!("{} track name(s) were not safe as file names and were renamed:", summary.renamed.len()),
+                    );
+                    ScrollArea::vertical().id_salt("export_renamed").max_height(120.0).show(ui, |ui| {
+                        for renamed in &summary.renamed {
+                            ui.label(RichText::new(renamed).size(11.0));
+                        }
+                    });
+                }
+                if !summary.failures.is_empty() {
+                    ui.add_space(6.0);
+                    ui.colored_label(
+                        Color32::from_rgb(255, 100, 100),
+                        format!("{} problem(s):", summary.failures.len()),
+                    );
+                    ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                        for failure in &summary.failures {
+                            ui.label(RichText::new(failure).size(11.0));
+                        }
+                    });
+                }
+                ui.add_space(6.0);
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        if !open || close {
+            self.open = false;
+            self.summary = None;
+        }
+    }
+}
+
+/// "1:05" for 65 seconds
+fn format_elapsed(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
@@ -1,12 +1,16 @@
 use super::audio_file_info::AudioFileInfo;
+use super::path_utils::{self, ToolInput, ToolOutput};
+use super::wav_metadata::{self, ExportMetadata};
+use super::sample_rate_check;
+use super::bank_args;
+use super::file_types::{DecodeStrategy, FileTypeRegistry};
 use nus3audio::Nus3audioFile;
+use crate::file_names;
 use crate::nus3bank::Nus3bankExporter;
+use crate::tools::VgmstreamRunner;
 use std::fs;
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -22,7 +26,7 @@ pub struct ExportUtils;
 
 impl ExportUtils {
     fn build_temp_audio_path(base_name: &str, extension: &str) -> PathBuf {
-        let temp_dir = std::env::temp_dir();
+        let temp_dir = path_utils::safe_temp_dir();
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
@@ -65,6 +69,42 @@ impl ExportUtils {
             .map_err(|e| format!("Failed to write temporary audio file: {}", e))?;
         Ok(temp_output_path_str)
     }
+
+    /// Write audio bytes to a temporary file the player can open. Payloads the type table
+    /// leaves to vgmstream-cli, such as lopus and IDSP, are decoded to WAV first.
+    pub fn write_playable_temp_audio(
+        audio_file_info: &AudioFileInfo,
+        audio_bytes: &[u8],
+        tag: &str,
+    ) -> Result<String, String> {
+        let (strategy, extension) = FileTypeRegistry::decode_strategy_of(audio_bytes, Some(&audio_file_info.filename));
+        if strategy == DecodeStrategy::Native || Self::detect_audio_extension(audio_bytes) != "bin" {
+            return Self::write_temp_audio_bytes(audio_file_info, audio_bytes, tag);
+        }
+
+        let base_name = format!("temp_audio_{}_{}", audio_file_info.id, tag);
+        let raw_path = Self::build_temp_audio_path(&base_name, extension.as_deref().unwrap_or("bin"));
+        fs::write(&raw_path, audio_bytes).map_err(|e| format!("Failed to write temporary audio file: {}", e))?;
+        let result = Self::decode_with_vgmstream(&raw_path, &base_name);
+        let _ = fs::remove_file(&raw_path);
+        result
+    }
+
+    /// Decode a standalone audio file to a temporary WAV with vgmstream-cli
+    fn decode_with_vgmstream(input: &Path, base_name: &str) -> Result<String, String> {
+        let vgmstream = VgmstreamRunner::locate()?;
+        let output_path = Self::build_temp_audio_path(&format!("{}_decoded", base_name), "wav");
+        let output_path_str = output_path.to_string_lossy().to_string();
+        let args = ["-o".to_string(), output_path_str.clone(), input.to_string_lossy().to_string()];
+        let result = vgmstream.command().args(&args).output();
+        crate::tools::log_run(vgmstream.path(), &args, &result);
+        match result {
+            Ok(output) if output.status.success() => Ok(output_path_str),
+            Ok(output) => Err(format!("vgmstream-cli error: {}", String::from_utf8_lossy(&output.stderr))),
+            Err(e) => Err(format!("Failed to run vgmstream-cli: {}", e)),
+        }
+    }
+
     /// Determine the correct vgmstream index based on the nus3audio file's indexing pattern
     /// 
     /// This function analyzes the nus3audio file to detect whether it uses:
@@ -145,6 +185,53 @@ impl ExportUtils {
         }
     }
 
+    /// Read the sample rate of a track (or of a standalone file when `audio_file_info` is `None`)
+    /// from vgmstream-cli metadata output, without decoding
+    pub fn probe_sample_rate(
+        audio_file_info: Option<&AudioFileInfo>,
+        file_path: &str,
+    ) -> Result<u32, String> {
+        let metadata = Self::probe_metadata(audio_file_info, file_path)?;
+        sample_rate_check::parse_vgmstream_sample_rate(&metadata)
+            .ok_or_else(|| "vgmstream-cli did not report a sample rate".to_string())
+    }
+
+    /// Raw `vgmstream-cli -m` output for a track, or for a standalone file when
+    /// `audio_file_info` is `None`
+    pub fn probe_metadata(
+        audio_file_info: Option<&AudioFileInfo>,
+        file_path: &str,
+    ) -> Result<String, String> {
+        let vgmstream = VgmstreamRunner::locate()?;
+        let mut command = vgmstream.command();
+
+        let tool_input = ToolInput::prepare(Path::new(file_path))?;
+        let mut args_vec: Vec<String> = vec!["-m".to_string()];
+        if let Some(info) = audio_file_info {
+            let index = if info.is_nus3bank {
+                let id_num = info.id.parse::<u32>()
+                    .map_err(|_| format!("Invalid audio file ID: {}", info.id))?;
+                (id_num + 1).to_string()
+            } else {
+                Self::get_vgmstream_index(&info.id, file_path)?
+            };
+            args_vec.push("-s".to_string());
+            args_vec.push(index);
+        }
+        args_vec.push(tool_input.arg());
+        bank_args::apply_vgmstream(&mut args_vec, file_path);
+
+        let output = command
+            .args(&args_vec)
+            .output()
+            .map_err(|e| format!("Failed to run vgmstream-cli: {}", e))?;
+        if !output.status.success() {
+            let error = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("vgmstream-cli error: {}", error));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     /// Convert audio to WAV format using vgmstream-cli and return the temp file path
     /// Supports both NUS3AUDIO and NUS3BANK files
     pub fn convert_to_wav_temp_path(
@@ -157,8 +244,7 @@ impl ExportUtils {
         }
         
         // Original NUS3AUDIO implementation
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
+        let vgmstream = VgmstreamRunner::locate()?;
 
         // Create a temporary output file path
         let temp_output_path = Self::build_temp_audio_path(
@@ -168,13 +254,7 @@ impl ExportUtils {
         let temp_output_path_str = temp_output_path.to_string_lossy().to_string();
 
         // Run vgmstream-cli to convert audio to WAV
-        let mut command = Command::new(&vgmstream_path);
-
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
+        let mut command = vgmstream.command();
 
         // Get the correct vgmstream index using intelligent detection
         let vgmstream_index = Self::get_vgmstream_index(&audio_file_info.id, original_file_path)?;
@@ -182,18 +262,21 @@ impl ExportUtils {
         // println!("Original ID: {}, Detected vgmstream index: {}", audio_file_info.id, vgmstream_index);
         // println!("Temp output path: {:?}", temp_output_path);
         
+        let tool_input = ToolInput::prepare(Path::new(original_file_path))?;
+
         // Build args vector so we can print full command before execution
-        let args_vec: Vec<String> = vec![
+        let mut args_vec: Vec<String> = vec![
             "-i".to_string(),
             "-o".to_string(),
             temp_output_path_str.clone(),
             "-s".to_string(),
             vgmstream_index.clone(),
-            original_file_path.to_string(),
+            tool_input.arg(),
         ];
+        bank_args::apply_vgmstream(&mut args_vec, original_file_path);
         // println!(
         //     "Running command: {:?} {}",
-        //     vgmstream_path,
+        //     vgmstream.path(),
         //     args_vec.join(" ")
         // );
 
@@ -224,38 +307,35 @@ impl ExportUtils {
     ) -> Result<String, String> {
         // Create output file path in the custom directory
         let output_dir_path = Path::new(output_dir);
-        let output_filename = format!("{}.wav", audio_file_info.name);
+        let output_filename = format!("{}.wav", file_names::safe_file_name(&audio_file_info.name));
         let output_path = output_dir_path.join(output_filename);
         let output_path_str = output_path.to_string_lossy().to_string();
 
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
+        let vgmstream = VgmstreamRunner::locate()?;
 
         // Run vgmstream-cli to convert audio to WAV
-        let mut command = Command::new(&vgmstream_path);
-
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
+        let mut command = vgmstream.command();
 
         // Get the correct vgmstream index using intelligent detection
         let vgmstream_index = Self::get_vgmstream_index(&audio_file_info.id, original_file_path)?;
         
         // println!("Original ID: {}, Detected vgmstream index: {}", audio_file_info.id, vgmstream_index);
 
-        let args_vec: Vec<String> = vec![
+        let tool_input = ToolInput::prepare(Path::new(original_file_path))?;
+        let tool_output = ToolOutput::new(&output_path);
+
+        let mut args_vec: Vec<String> = vec![
             "-i".to_string(),
             "-o".to_string(),
-            output_path_str.clone(),
+            tool_output.arg(),
             "-s".to_string(),
             vgmstream_index.clone(),
-            original_file_path.to_string(),
+            tool_input.arg(),
         ];
+        bank_args::apply_vgmstream(&mut args_vec, original_file_path);
         // println!(
         //     "Running command: {:?} {}",
-        //     vgmstream_path,
+        //     vgmstream.path(),
         //     args_vec.join(" ")
         // );
 
@@ -268,6 +348,7 @@ impl ExportUtils {
             Ok(output) => {
                 if output.status.success() {
                     // println!("Successfully exported WAV file to: {:?}", output_path);
+                    tool_output.finish()?;
                     Ok(output_path_str)
                 } else {
                     let error = String::from_utf8_lossy(&output.stderr);
@@ -278,101 +359,73 @@ impl ExportUtils {
         }
     }
 
-    /// Export all audio files in a file to WAV files with custom output directory using vgmstream-cli
-    pub fn export_all_to_wav(
+    /// Export one entry of a NUS3AUDIO file the way Export All of the whole file does: named
+    /// after the entry and decoded with the loops vgmstream-cli plays by default
+    fn export_nus3audio_entry_to_wav(
+        vgmstream: &VgmstreamRunner,
+        tool_input: &ToolInput,
+        name: &str,
+        id: &str,
         original_file_path: &str,
         output_dir: &str,
-    ) -> Result<Vec<String>, String> {
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
-
-        // First, load the nus3audio file to get audio file information
-        let nus3audio_file = match Nus3audioFile::open(original_file_path) {
-            Ok(file) => file,
-            Err(e) => return Err(format!("Failed to load nus3audio file: {}", e)),
+    ) -> Result<String, String> {
+        // Get the name for this audio file
+        let audio_name = if name.is_empty() {
+            format!("audio_{}", id)
+        } else {
+            name.to_string()
         };
 
-        // println!(
-        //     "Loaded nus3audio file with {} audio files",
-        //     nus3audio_file.files.len()
-        // );
-
-        let mut exported_paths = Vec::new();
-        let output_dir_path = Path::new(output_dir);
-
-        // Export each audio file directly using vgmstream-cli
-        for audio_file in nus3audio_file.files.iter() {
-            // Get the name for this audio file
-            let audio_name = if audio_file.name.is_empty() {
-                format!("audio_{}", audio_file.id)
-            } else {
-                audio_file.name.clone()
-            };
+        // Create output file path with the audio file name
+        let output_filename = format!("{}.wav", file_names::safe_file_name(&audio_name));
+        let output_path = Path::new(output_dir).join(output_filename);
+        let output_path_str = output_path.to_string_lossy().to_string();
 
-            // Create output file path with the audio file name
-            let output_filename = format!("{}.wav", audio_name);
-            let output_path = output_dir_path.join(output_filename);
-            let output_path_str = output_path.to_string_lossy().to_string();
+        // Convert to WAV using vgmstream-cli with the subsong index
+        let mut command = vgmstream.command();
 
-            // Convert to WAV using vgmstream-cli with the subsong index
-            let mut command = Command::new(&vgmstream_path);
+        // Get the correct vgmstream index using intelligent detection
+        let vgmstream_index = Self::get_vgmstream_index(id, original_file_path)
+            .map_err(|e| format!("Failed to determine vgmstream index for audio file {}: {}", id, e))?;
 
-            #[cfg(windows)]
-            {
-                use winapi::um::winbase::CREATE_NO_WINDOW;
-                command.creation_flags(CREATE_NO_WINDOW);
-            }
+        let tool_output = ToolOutput::new(&output_path);
+        let mut args_vec: Vec<String> = vec![
+            "-o".to_string(),
+            tool_output.arg(),
+            "-s".to_string(),
+            vgmstream_index,
+            tool_input.arg(),
+        ];
+        bank_args::apply_vgmstream(&mut args_vec, original_file_path);
 
-            // Get the correct vgmstream index using intelligent detection
-            let vgmstream_index = match Self::get_vgmstream_index(&audio_file.id.to_string(), original_file_path) {
-                Ok(index) => index,
-                Err(e) => {
-                    return Err(format!("Failed to determine vgmstream index for audio file {}: {}", audio_file.id, e));
-                }
-            };
-            
-            // println!("Original ID: {}, Detected vgmstream index: {}", audio_file.id, vgmstream_index);
-
-            let args_vec: Vec<String> = vec![
-                "-o".to_string(),
-                output_path_str.clone(),
-                "-s".to_string(),
-                vgmstream_index.clone(),
-                original_file_path.to_string(),
-            ];
-            // println!(
-            //     "Running command: {:?} {}",
-            //     vgmstream_path,
-            //     args_vec.join(" ")
-            // );
-
-            let result = command
-                .args(&args_vec)
-                .output();
-
-            match result {
-                Ok(output) => {
-                    if output.status.success() {
-                        // println!("Successfully exported WAV file to: {:?}", output_path);
-                        exported_paths.push(output_path_str);
-                    } else {
-                        let error = String::from_utf8_lossy(&output.stderr);
-                        return Err(format!(
-                            "vgmstream-cli error on audio file {}: {}",
-                            audio_file.id, error
-                        ));
-                    }
-                }
-                Err(e) => {
-                    return Err(format!(
-                        "Failed to run vgmstream-cli for audio file {}: {}",
-                        audio_file.id, e
-                    ));
+        match command.args(&args_vec).output() {
+            Ok(output) => {
+                if output.status.success() {
+                    tool_output.finish()?;
+                    Ok(output_path_str)
+                } else {
+                    let error = String::from_utf8_lossy(&output.stderr);
+                    Err(format!("vgmstream-cli error on audio file {}: {}", id, error))
                 }
             }
+            Err(e) => Err(format!("Failed to run vgmstream-cli for audio file {}: {}", id, e)),
         }
+    }
 
-        Ok(exported_paths)
+    /// Export one track of the whole-file Export All: bank payloads are written as stored,
+    /// NUS3AUDIO entries are decoded. Metadata is not embedded.
+    pub fn export_track_as_in_file(
+        track: &AudioFileInfo,
+        original_file_path: &str,
+        output_dir: &str,
+    ) -> Result<String, String> {
+        if track.is_nus3bank {
+            let hex_id = track.hex_id.as_deref().ok_or("Track has no hex ID")?;
+            return Nus3bankExporter::export_track(original_file_path, hex_id, output_dir);
+        }
+        let vgmstream = VgmstreamRunner::locate()?;
+        let tool_input = ToolInput::prepare(Path::new(original_file_path))?;
+        Self::export_nus3audio_entry_to_wav(&vgmstream, &tool_input, &track.name, &track.id, original_file_path, output_dir)
     }
     
     /// Convert NUS3BANK track to WAV format and return the temp file path
@@ -386,8 +439,7 @@ impl ExportUtils {
             .map_err(|_| format!("Invalid audio file ID: {}", audio_file_info.id))?;
         let vgmstream_index = id_num + 1;
 
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
+        let vgmstream = VgmstreamRunner::locate()?;
 
         // Create a temporary output file path
         let temp_output_path = Self::build_temp_audio_path(
@@ -397,21 +449,18 @@ impl ExportUtils {
         let temp_output_path_str = temp_output_path.to_string_lossy().to_string();
 
         // Run vgmstream-cli to convert audio to WAV
-        let mut command = Command::new(&vgmstream_path);
+        let mut command = vgmstream.command();
 
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
+        let tool_input = ToolInput::prepare(Path::new(original_file_path))?;
 
-        let args_vec: Vec<String> = vec![
+        let mut args_vec: Vec<String> = vec![
             "-o".to_string(),
             temp_output_path_str.clone(),
             "-s".to_string(),
             vgmstream_index.to_string(),
-            original_file_path.to_string(),
+            tool_input.arg(),
         ];
+        bank_args::apply_vgmstream(&mut args_vec, original_file_path);
 
         let result = command
             .args(&args_vec)
@@ -443,32 +492,29 @@ impl ExportUtils {
 
         // Create output file path in the custom directory
         let output_dir_path = Path::new(output_dir);
-        let output_filename = format!("{}.wav", audio_file_info.name);
+        let output_filename = format!("{}.wav", file_names::safe_file_name(&audio_file_info.name));
         let output_path = output_dir_path.join(output_filename);
         let output_path_str = output_path.to_string_lossy().to_string();
 
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
+        let vgmstream = VgmstreamRunner::locate()?;
 
         // Run vgmstream-cli to convert audio to WAV
-        let mut command = Command::new(&vgmstream_path);
+        let mut command = vgmstream.command();
 
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
+        let tool_input = ToolInput::prepare(Path::new(original_file_path))?;
+        let tool_output = ToolOutput::new(&output_path);
 
-        let args_vec: Vec<String> = vec![
+        let mut args_vec: Vec<String> = vec![
             "-o".to_string(),
-            output_path_str.clone(),
+            tool_output.arg(),
             "-s".to_string(),
             vgmstream_index.to_string(),
-            original_file_path.to_string(),
+            tool_input.arg(),
         ];
+        bank_args::apply_vgmstream(&mut args_vec, original_file_path);
         // println!(
         //     "Running command: {:?} {}",
-        //     vgmstream_path,
+        //     vgmstream.path(),
         //     args_vec.join(" ")
         // );
 
@@ -481,6 +527,7 @@ impl ExportUtils {
             Ok(output) => {
                 if output.status.success() {
                     // println!("Successfully exported WAV file to: {:?}", output_path);
+                    tool_output.finish()?;
                     Ok(output_path_str)
                 } else {
                     let error = String::from_utf8_lossy(&output.stderr);
@@ -491,36 +538,175 @@ impl ExportUtils {
         }
     }
     
-    /// Export all tracks from NUS3BANK file
-    pub fn export_all_nus3bank_to_wav(
-        original_file_path: &str,
-        output_dir: &str,
-    ) -> Result<Vec<String>, String> {
-        Nus3bankExporter::export_all_tracks(original_file_path, output_dir)
-    }
-    
-    /// Unified export method that works with both NUS3AUDIO and NUS3BANK files
+    /// Unified export method that works with both NUS3AUDIO and NUS3BANK files.
+    /// With `embed_metadata`, BWF/INFO chunks describing the source are added to the WAV.
     pub fn export_to_wav_with_custom_dir_unified(
         audio_file_info: &AudioFileInfo,
         original_file_path: &str,
         output_dir: &str,
+        embed_metadata: bool,
     ) -> Result<String, String> {
-        if audio_file_info.is_nus3bank {
-            Self::export_nus3bank_to_wav_with_custom_dir(audio_file_info, original_file_path, output_dir)
+        let output_path = if audio_file_info.is_nus3bank {
+            Self::export_nus3bank_to_wav_with_custom_dir(audio_file_info, original_file_path, output_dir)?
         } else {
-            Self::export_to_wav_with_custom_dir(audio_file_info, original_file_path, output_dir)
+            Self::export_to_wav_with_custom_dir(audio_file_info, original_file_path, output_dir)?
+        };
+
+        if embed_metadata {
+            let mut meta = ExportMetadata::for_track(audio_file_info, original_file_path);
+            if audio_file_info.is_nus3bank {
+                // The table shows every bank track as WAV; read the real codec from the payload
+                if let Some(hex_id) = &audio_file_info.hex_id {
+                    if let Ok(data) = Nus3bankExporter::export_track_to_memory(original_file_path, hex_id) {
+                        meta.codec = wav_metadata::codec_from_header(&data);
+                    }
+                }
+            }
+            wav_metadata::embed_in_file(&output_path, &meta)?;
         }
+        Ok(output_path)
     }
     
-    /// Unified export all method that works with both file types
-    pub fn export_all_to_wav_unified(
-        original_file_path: &str,
-        output_dir: &str,
-    ) -> Result<Vec<String>, String> {
+    /// Copy the `start..end` seconds of a WAV file into a new WAV with the same format
+    pub fn export_wav_region(
+        source_wav: &str,
+        output_path: &str,
+        start: f32,
+        end: f32,
+    ) -> Result<String, String> {
+        if end <= start {
+            return Err("The region end must be after the region start".to_string());
+        }
+
+        let mut reader = hound::WavReader::open(source_wav)
+            .map_err(|e| format!("Failed to open WAV for region export: {}", e))?;
+        let spec = reader.spec();
+        let channels = spec.channels.max(1) as usize;
+        let total_frames = reader.duration() as usize;
+        let start_frame = ((start.max(0.0) * spec.sample_rate as f32).round() as usize).min(total_frames);
+        let end_frame = ((end * spec.sample_rate as f32).round() as usize).min(total_frames);
+        if end_frame <= start_frame {
+            return Err("The region is outside the track".to_string());
+        }
+        let skip = start_frame * channels;
+        let take = (end_frame - start_frame) * channels;
+
+        let output = path_utils::to_extended_length(Path::new(output_path));
+        let mut writer = hound::WavWriter::create(&output, spec)
+            .map_err(|e| format!("Failed to create output WAV: {}", e))?;
+
+        match spec.sample_format {
+            hound::SampleFormat::Int => {
+                for s in reader.samples::<i32>().skip(skip).take(take) {
+                    let v = s.map_err(|e| format!("Read sample error: {}", e))?;
+                    writer
+                        .write_sample(v)
+                        .map_err(|e| format!("Write sample error: {}", e))?;
+                }
+            }
+            hound::SampleFormat::Float => {
+                for s in reader.samples::<f32>().skip(skip).take(take) {
+                    let v = s.map_err(|e| format!("Read sample error: {}", e))?;
+                    writer
+                        .write_sample(v)
+                        .map_err(|e| format!("Write sample error: {}", e))?;
+                }
+            }
+        }
+
+        writer
+            .finalize()
+            .map_err(|e| format!("Finalize WAV error: {}", e))?;
+        Ok(output_path.to_string())
+    }
+
+    /// Metadata for every track of a container, keyed by the file name Export All writes
+    pub fn export_metadata_by_file_name(original_file_path: &str) -> Result<HashMap<String, ExportMetadata>, String> {
+        let mut metadata = HashMap::new();
         if original_file_path.to_lowercase().ends_with(".nus3bank") {
-            Self::export_all_nus3bank_to_wav(original_file_path, output_dir)
+            let bank = crate::nus3bank::Nus3bankFile::open(original_file_path)
+                .map_err(|e| format!("Failed to open NUS3BANK file: {}", e))?;
+            for track in &bank.tracks {
+                let codec = bank
+                    .read_track_payload(track.index)
+                    .ok()
+                    .map(|data| wav_metadata::codec_from_header(&data))
+                    .unwrap_or_else(|| "Unknown".to_string());
+                metadata.insert(
+                    file_names::safe_file_name(&track.filename()),
+                    ExportMetadata::new(original_file_path, &track.name, &track.hex_id, &codec),
+                );
+            }
         } else {
-            Self::export_all_to_wav(original_file_path, output_dir)
+            let nus3audio_file = Nus3audioFile::open(original_file_path)
+                .map_err(|e| format!("Failed to load nus3audio file: {}", e))?;
+            for audio_file in &nus3audio_file.files {
+                let audio_name = if audio_file.name.is_empty() {
+                    format!("audio_{}", audio_file.id)
+                } else {
+                    audio_file.name.clone()
+                };
+                metadata.insert(
+                    format!("{}.wav", file_names::safe_file_name(&audio_name)),
+                    ExportMetadata::new(
+                        original_file_path,
+                        &audio_name,
+                        &audio_file.id.to_string(),
+                        &wav_metadata::codec_from_header(&audio_file.data),
+                    ),
+                );
+            }
+        }
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn unique_temp_path(name: &str) -> PathBuf {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("exvs2_audio_editor_test_{}_{}.wav", name, nonce))
+    }
+
+    #[test]
+    fn region_export_keeps_format_and_slices_frames() {
+        let source = unique_temp_path("region_source");
+        let output = unique_temp_path("region_output");
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 1000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&source, spec).unwrap();
+        for frame in 0..2000i16 {
+            writer.write_sample(frame).unwrap();
+            writer.write_sample(-frame).unwrap();
         }
+        writer.finalize().unwrap();
+
+        ExportUtils::export_wav_region(
+            &source.to_string_lossy(),
+            &output.to_string_lossy(),
+            0.5,
+            1.25,
+        )
+        .unwrap();
+
+        let mut reader = hound::WavReader::open(&output).unwrap();
+        assert_eq!(reader.spec(), spec);
+        assert_eq!(reader.duration(), 750);
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(&samples[..2], &[500, -500]);
+        assert_eq!(samples[samples.len() - 2], 1249);
+
+        let _ = fs::remove_file(&source);
+        let _ = fs::remove_file(&output);
     }
 }
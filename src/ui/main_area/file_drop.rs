@@ -0,0 +1,99 @@
+use egui::{Align2, Color32, Context, FontId, Id, LayerId, Order, Pos2, Rect, Stroke, StrokeKind, Ui};
+use std::path::{Path, PathBuf};
+
+/// Extensions accepted when audio files are dropped onto the window
+pub const AUDIO_EXTENSIONS: [&str; 7] = ["wav", "mp3", "flac", "ogg", "lopus", "idsp", "bin"];
+
+/// Files the editor opens, as opposed to audio that goes into them
+pub const BANK_EXTENSIONS: [&str; 2] = ["nus3audio", "nus3bank"];
+
+/// Outline of drop targets while a file is dragged over them
+const DROP_TARGET_COLOR: Color32 = Color32::from_rgb(90, 170, 255);
+
+pub fn is_audio_file(path: &Path) -> bool {
+    has_extension(path, &AUDIO_EXTENSIONS)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| extensions.iter().any(|a| a.eq_ignore_ascii_case(e)))
+}
+
+pub fn is_bank_file(path: &Path) -> bool {
+    has_extension(path, &BANK_EXTENSIONS)
+}
+
+/// .nus3audio and .nus3bank files dropped onto the window this frame
+pub fn dropped_bank_files(ctx: &Context) -> Vec<String> {
+    ctx.input(|i| {
+        i.raw
+            .dropped_files
+            .iter()
+            .filter_map(|f| f.path.as_deref())
+            .filter(|p| is_bank_file(p))
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    })
+}
+
+/// Cover the window with a hint while a bank file is dragged over it. Platforms that do not
+/// report the dragged paths get no hint, but the drop still works.
+pub fn paint_bank_drop_hint(ctx: &Context) {
+    let name = ctx.input(|i| {
+        i.raw
+            .hovered_files
+            .iter()
+            .filter_map(|f| f.path.as_deref())
+            .find(|p| is_bank_file(p))
+            .map(|p| p.file_name().unwrap_or_default().to_string_lossy().to_string())
+    });
+    let Some(name) = name else {
+        return;
+    };
+    let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("bank_drop_hint")));
+    let rect = ctx.content_rect();
+    painter.rect_filled(rect, 0.0, Color32::from_black_alpha(160));
+    painter.rect_stroke(rect.shrink(8.0), 8.0, Stroke::new(2.0, DROP_TARGET_COLOR), StrokeKind::Inside);
+    painter.text(
+        rect.center(),
+        Align2::CENTER_CENTER,
+        format!("Drop to open {}", name),
+        FontId::proportional(22.0),
+        Color32::WHITE,
+    );
+}
+
+/// Audio files dropped onto the window this frame, in the order they were dropped
+pub fn dropped_audio_files(ctx: &Context) -> Vec<PathBuf> {
+    ctx.input(|i| {
+        i.raw
+            .dropped_files
+            .iter()
+            .filter_map(|f| f.path.clone())
+            .filter(|p| is_audio_file(p))
+            .collect()
+    })
+}
+
+/// First audio file dropped onto the window this frame
+pub fn dropped_audio_file(ctx: &Context) -> Option<PathBuf> {
+    dropped_audio_files(ctx).into_iter().next()
+}
+
+/// Whether a file is being dragged over the window
+pub fn is_hovering(ctx: &Context) -> bool {
+    ctx.input(|i| !i.raw.hovered_files.is_empty())
+}
+
+/// Last known pointer position; while a file is dragged in from outside it may lag behind
+pub fn pointer_pos(ctx: &Context) -> Option<Pos2> {
+    ctx.input(|i| i.pointer.latest_pos())
+}
+
+/// Outline `rect` while a file is dragged over the window, so the drop target is obvious
+pub fn paint_drop_target(ui: &Ui, rect: Rect) {
+    if is_hovering(ui.ctx()) {
+        ui.painter().rect_stroke(rect.shrink(2.0), 6.0, Stroke::new(2.0, DROP_TARGET_COLOR), StrokeKind::Inside);
+    }
+}
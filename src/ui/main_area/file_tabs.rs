@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use super::audio_file_info::AudioFileInfo;
+use super::snapshots::Snapshot;
+
+/// A file open in a tab. Only the active tab's edits live in the pending stores; the others
+/// keep theirs parked in a snapshot until they are switched back to.
+pub struct FileTab {
+    pub path: String,
+    parked: Option<Snapshot>,
+}
+
+impl FileTab {
+    /// File name shown on the tab
+    pub fn title(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    /// Tracks listed when the tab was parked; `None` for the active tab
+    pub fn parked_rows(&self) -> Option<&[AudioFileInfo]> {
+        self.parked.as_ref()?.audio_files()
+    }
+
+    /// Pending edits parked with the tab; zero for the active tab
+    pub fn parked_edits(&self) -> usize {
+        self.parked.as_ref().map_or(0, |s| s.edit_count())
+    }
+}
+
+/// The files open side by side, in the order they were opened
+#[derive(Default)]
+pub struct FileTabs {
+    tabs: Vec<FileTab>,
+    active: Option<usize>,
+}
+
+impl FileTabs {
+    pub fn tabs(&self) -> &[FileTab] {
+        &self.tabs
+    }
+
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    pub fn index_of(&self, path: &str) -> Option<usize> {
+        self.tabs.iter().position(|t| t.path == path)
+    }
+
+    /// Index of the tab of `path`, adding one at the end when the file is not open yet
+    pub fn open(&mut self, path: &str) -> usize {
+        self.index_of(path).unwrap_or_else(|| {
+            self.tabs.push(FileTab {
+                path: path.to_string(),
+                parked: None,
+            });
+            self.tabs.len() - 1
+        })
+    }
+
+    pub fn set_active(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.active = Some(index);
+        }
+    }
+
+    /// Keep the edits of a tab that is being switched away from
+    pub fn park(&mut self, index: usize, snapshot: Snapshot) {
+        if let Some(tab) = self.tabs.get_mut(index) {
+            tab.parked = Some(snapshot);
+        }
+    }
+
+    /// Take back the edits parked with a tab
+    pub fn unpark(&mut self, index: usize) -> Option<Snapshot> {
+        self.tabs.get_mut(index)?.parked.take()
+    }
+
+    /// Close a tab. Returns the closed tab and, when it was the active one, the neighbour
+    /// that should become active next.
+    pub fn close(&mut self, index: usize) -> Option<(FileTab, Option<usize>)> {
+        if index >= self.tabs.len() {
+            return None;
+        }
+        let tab = self.tabs.remove(index);
+        let next = match self.active {
+            Some(active) if active == index => {
+                self.active = None;
+                (!self.tabs.is_empty()).then(|| index.min(self.tabs.len() - 1))
+            }
+            Some(active) if active > index => {
+                self.active = Some(active - 1);
+                None
+            }
+            _ => None,
+        };
+        Some((tab, next))
+    }
+}
+
+/// A track copied from one tab, kept as a decoded WAV until it is pasted into another
+#[derive(Debug, Clone)]
+pub struct CopiedTrack {
+    pub name: String,
+    pub source_file: String,
+    pub wav_path: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closing_tabs_picks_a_neighbour() {
+        let mut tabs = FileTabs::default();
+        for path in ["a.nus3bank", "b.nus3bank", "c.nus3audio"] {
+            let index = tabs.open(path);
+            tabs.set_active(index);
+        }
+        assert_eq!(tabs.open("b.nus3bank"), 1);
+        assert_eq!(tabs.tabs().len(), 3);
+
+        // Closing a tab before the active one keeps the same file active
+        let (closed, next) = tabs.close(0).unwrap();
+        assert_eq!((closed.path.as_str(), next), ("a.nus3bank", None));
+        assert_eq!(tabs.active(), Some(1));
+
+        // Closing the last, active tab moves to the one before it
+        let (_, next) = tabs.close(1).unwrap();
+        assert_eq!((next, tabs.active()), (Some(0), None));
+        assert_eq!(tabs.tabs()[0].title(), "b.nus3bank");
+    }
+}
@@ -0,0 +1,284 @@
+use egui::Color32;
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// User additions to the type table, read from the working directory (next to `tools/`).
+///
+/// ```json
+/// { "types": [
+///     { "name": "ADPCM", "magic": "hex:41445043", "extensions": ["adpcm"],
+///       "color": [230, 120, 200], "decode": "vgmstream" }
+/// ] }
+/// ```
+///
+/// `magic` is matched at offset 0, either as ASCII or as `hex:` bytes. User entries are
+/// checked before the built-in ones, so they can also recolor or rename a built-in type.
+pub const USER_FILE_TYPES_PATH: &str = "file_types.json";
+
+/// Color of types that nothing in the table matches
+const UNKNOWN_COLOR: [u8; 3] = [200, 150, 100];
+
+/// How a payload of this type is best turned into PCM
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DecodeStrategy {
+    /// Played and exported by the editor itself
+    Native,
+    /// Needs vgmstream-cli
+    Vgmstream,
+}
+
+impl DecodeStrategy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DecodeStrategy::Native => "Decoded natively",
+            DecodeStrategy::Vgmstream => "Decoded with vgmstream-cli",
+        }
+    }
+}
+
+/// One row of the type table
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileType {
+    pub name: String,
+    pub magic: Vec<u8>,
+    /// Lowercase, without the dot
+    pub extensions: Vec<String>,
+    pub color: [u8; 3],
+    pub decode: DecodeStrategy,
+}
+
+impl FileType {
+    fn builtin(name: &str, magic: &[u8], extension: &str, color: [u8; 3], decode: DecodeStrategy) -> Self {
+        Self {
+            name: name.to_string(),
+            magic: magic.to_vec(),
+            extensions: vec![extension.to_string()],
+            color,
+            decode,
+        }
+    }
+
+    fn unknown() -> Self {
+        Self {
+            name: "Unknown".to_string(),
+            magic: Vec::new(),
+            extensions: Vec::new(),
+            color: UNKNOWN_COLOR,
+            decode: DecodeStrategy::Vgmstream,
+        }
+    }
+
+    pub fn color32(&self) -> Color32 {
+        Color32::from_rgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct UserFileTypes {
+    types: Vec<UserFileType>,
+}
+
+#[derive(serde::Deserialize)]
+struct UserFileType {
+    name: String,
+    #[serde(default)]
+    magic: Option<String>,
+    #[serde(default)]
+    extensions: Vec<String>,
+    #[serde(default)]
+    color: Option<[u8; 3]>,
+    #[serde(default)]
+    decode: Option<DecodeStrategy>,
+}
+
+impl UserFileType {
+    fn into_file_type(self) -> Result<FileType, String> {
+        let magic = match self.magic.as_deref() {
+            None | Some("") => Vec::new(),
+            Some(text) => match text.strip_prefix("hex:") {
+                Some(hex) => parse_hex(hex).ok_or_else(|| format!("{}: invalid hex magic {:?}", self.name, hex))?,
+                None => text.as_bytes().to_vec(),
+            },
+        };
+        if magic.is_empty() && self.extensions.is_empty() {
+            return Err(format!("{}: needs a magic or at least one extension", self.name));
+        }
+        Ok(FileType {
+            name: self.name,
+            magic,
+            extensions: self
+                .extensions
+                .iter()
+                .map(|e| e.trim_start_matches('.').to_lowercase())
+                .collect(),
+            color: self.color.unwrap_or(UNKNOWN_COLOR),
+            decode: self.decode.unwrap_or(DecodeStrategy::Vgmstream),
+        })
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Vec<u8>> {
+    let hex: String = hex.chars().filter(|c| !c.is_whitespace()).collect();
+    if hex.is_empty() || !hex.is_ascii() || hex.len() % 2 != 0 {
+        return None;
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// Maps payload magics and file extensions to a type name, color and decode strategy
+#[derive(Debug, Clone)]
+pub struct FileTypeRegistry {
+    types: Vec<FileType>,
+    unknown: FileType,
+}
+
+impl FileTypeRegistry {
+    pub fn builtin() -> Self {
+        Self {
+            types: vec![
+                FileType::builtin("OPUS", b"OPUS", "lopus", [100, 200, 100], DecodeStrategy::Vgmstream),
+                FileType::builtin("IDSP", b"IDSP", "idsp", [100, 150, 255], DecodeStrategy::Vgmstream),
+                FileType::builtin("WAV", b"RIFF", "wav", UNKNOWN_COLOR, DecodeStrategy::Native),
+                FileType::builtin("BNSF", b"BNSF", "bnsf", [220, 190, 90], DecodeStrategy::Vgmstream),
+            ],
+            unknown: FileType::unknown(),
+        }
+    }
+
+    /// Built-in table with the entries of a user JSON file in front of it
+    pub fn with_user_types(json: &str) -> Result<Self, String> {
+        let user: UserFileTypes = serde_json::from_str(json).map_err(|e| format!("Invalid file type table: {}", e))?;
+        let mut registry = Self::builtin();
+        let mut types = user
+            .types
+            .into_iter()
+            .map(UserFileType::into_file_type)
+            .collect::<Result<Vec<_>, _>>()?;
+        types.append(&mut registry.types);
+        registry.types = types;
+        Ok(registry)
+    }
+
+    /// Built-in table plus `file_types.json` when present. A broken file is reported and
+    /// ignored so detection keeps working.
+    pub fn load() -> Self {
+        let path = Path::new(USER_FILE_TYPES_PATH);
+        let Ok(json) = std::fs::read_to_string(path) else {
+            return Self::builtin();
+        };
+        match Self::with_user_types(&json) {
+            Ok(registry) => registry,
+            Err(e) => {
+                println!("Ignoring {:?}: {}", path, e);
+                Self::builtin()
+            }
+        }
+    }
+
+    /// Type of a payload: by magic first, then by the extension of `file_name`
+    pub fn detect(&self, data: &[u8], file_name: Option<&str>) -> &FileType {
+        if let Some(found) = self.types.iter().find(|t| !t.magic.is_empty() && data.starts_with(&t.magic)) {
+            return found;
+        }
+        let extension = file_name
+            .and_then(|name| Path::new(name).extension())
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        extension
+            .and_then(|ext| self.types.iter().find(|t| t.extensions.contains(&ext)))
+            .unwrap_or(&self.unknown)
+    }
+
+    /// Entry for a type name shown in the table, or the unknown entry
+    pub fn by_name(&self, name: &str) -> &FileType {
+        self.types
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+            .unwrap_or(&self.unknown)
+    }
+}
+
+// Shared table used by the file loaders, the track table and the player
+static REGISTRY: Lazy<Mutex<FileTypeRegistry>> = Lazy::new(|| Mutex::new(FileTypeRegistry::load()));
+
+impl FileTypeRegistry {
+    /// Re-read `file_types.json`, so edits apply to the next opened file
+    pub fn reload() {
+        if let Ok(mut registry) = REGISTRY.lock() {
+            *registry = FileTypeRegistry::load();
+        }
+    }
+
+    /// Type name of a payload in the shared table
+    pub fn type_name_of(data: &[u8], file_name: Option<&str>) -> String {
+        match REGISTRY.lock() {
+            Ok(registry) => registry.detect(data, file_name).name.clone(),
+            Err(_) => FileType::unknown().name,
+        }
+    }
+
+    /// File name of a .nus3audio entry: its name with the extension of the type its payload
+    /// magic is detected as, or .bin. Unlike `nus3audio::AudioFile::filename` this knows
+    /// WAV, BNSF and the user's types.
+    pub fn file_name_of(name: &str, data: &[u8]) -> String {
+        let extension = match REGISTRY.lock() {
+            Ok(registry) => registry.detect(data, None).extensions.first().cloned(),
+            Err(_) => None,
+        };
+        format!("{}.{}", name, extension.unwrap_or_else(|| "bin".to_string()))
+    }
+
+    /// How the shared table says a payload should be decoded
+    pub fn decode_strategy_of(data: &[u8], file_name: Option<&str>) -> (DecodeStrategy, Option<String>) {
+        match REGISTRY.lock() {
+            Ok(registry) => {
+                let file_type = registry.detect(data, file_name);
+                (file_type.decode, file_type.extensions.first().cloned())
+            }
+            Err(_) => (DecodeStrategy::Vgmstream, None),
+        }
+    }
+
+    /// Shared table entry for a type name
+    pub fn lookup(name: &str) -> FileType {
+        match REGISTRY.lock() {
+            Ok(registry) => registry.by_name(name).clone(),
+            Err(_) => FileType::unknown(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn user_types_extend_and_override_builtins() {
+        let registry = FileTypeRegistry::with_user_types(
+            r#"{ "types": [
+                { "name": "ADPCM", "magic": "hex:41 44 50 43", "color": [1, 2, 3] },
+                { "name": "Opus (loop)", "magic": "OPUS", "decode": "native" },
+                { "name": "HCA", "extensions": [".HCA"] }
+            ] }"#,
+        )
+        .unwrap();
+
+        let adpcm = registry.detect(b"ADPC\x00\x01", None);
+        assert_eq!((adpcm.name.as_str(), adpcm.color), ("ADPCM", [1, 2, 3]));
+        assert_eq!(adpcm.decode, DecodeStrategy::Vgmstream);
+        let opus = registry.detect(b"OPUS....", Some("a.lopus"));
+        assert_eq!((opus.name.as_str(), opus.decode), ("Opus (loop)", DecodeStrategy::Native));
+        assert_eq!(registry.detect(b"RIFF....", None).name, "WAV");
+        assert_eq!(registry.detect(b"????", Some("voice.hca")).name, "HCA");
+        assert_eq!(registry.detect(b"????", Some("voice.xyz")).name, "Unknown");
+        assert_eq!(registry.by_name("idsp").color, [100, 150, 255]);
+
+        assert!(FileTypeRegistry::with_user_types(r#"{ "types": [{ "name": "Bad", "magic": "hex:4G" }] }"#).is_err());
+        assert!(FileTypeRegistry::with_user_types(r#"{ "types": [{ "name": "Bad", "magic": "hex:aé1" }] }"#).is_err());
+        assert!(FileTypeRegistry::with_user_types(r#"{ "types": [{ "name": "Empty" }] }"#).is_err());
+    }
+}
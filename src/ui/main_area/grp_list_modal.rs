@@ -2,7 +2,7 @@ use egui::{Color32, Context, ScrollArea, Ui, Window};
 
 use crate::nus3bank::structures::{GrpSection, Nus3bankFile};
 
-use super::{grp_pending, grp_template};
+use super::{audit_log, grp_pending, grp_template};
 
 pub struct GrpListModal {
     pub open: bool,
@@ -13,6 +13,8 @@ pub struct GrpListModal {
     replace_text: String,
     error: Option<String>,
     dirty: bool,
+    /// Pending edits were stored since the dialog opened; logged when it closes
+    edited: bool,
     
     // Cache for performance
     visible_indices_cache: Vec<usize>,
@@ -37,6 +39,7 @@ impl GrpListModal {
             replace_text: String::new(),
             error: None,
             dirty: false,
+            edited: false,
             visible_indices_cache: Vec::new(),
             last_search_query: String::new(),
             scroll_offset: 0.0,
@@ -47,6 +50,7 @@ impl GrpListModal {
         self.file_path = Some(file_path.to_string());
         self.error = None;
         self.dirty = false;
+        self.edited = false;
         self.search_query.clear();
         self.last_search_query.clear();
         self.visible_indices_cache.clear();
@@ -88,6 +92,11 @@ impl GrpListModal {
         if was_open && !self.open {
             // If the user closed the window via the X button, persist any pending changes.
             self.flush_pending();
+            if std::mem::take(&mut self.edited) {
+                if let Some(path) = self.file_path.as_deref() {
+                    audit_log::record_for(path, format!("Edited GRP names ({} entries)", self.names.len()));
+                }
+            }
         }
     }
 
@@ -295,6 +304,7 @@ impl GrpListModal {
             self.error = Some(e);
             return;
         }
+        self.edited = true;
 
         self.error = None;
         self.dirty = false;
@@ -344,6 +354,8 @@ pub fn apply_grp_names_to_file(file: &mut Nus3bankFile, names: Vec<String>) {
         file.grp = None;
         return;
     }
-    file.grp = Some(GrpSection { names });
+    // Keep the bytes as read, so saving unchanged names writes them back untouched
+    let raw = file.grp.take().map(|g| g.raw).unwrap_or_default();
+    file.grp = Some(GrpSection { names, raw });
 }
 
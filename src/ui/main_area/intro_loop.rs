@@ -0,0 +1,86 @@
+use super::audio_file_info::AudioFileInfo;
+
+/// Name suffixes of intro/loop track pairs, as (intro, loop). Matched case-insensitively
+/// after a `_` or `-` separator.
+pub const PAIR_SUFFIXES: [(&str, &str); 3] = [("intro", "loop"), ("intro", "main"), ("start", "loop")];
+
+/// Tracks that play one after the other: the intro once, then the loop part forever
+#[derive(Debug, Clone)]
+pub struct IntroLoopPair {
+    /// Shared name without the suffix
+    pub base: String,
+    pub intro: AudioFileInfo,
+    pub body: AudioFileInfo,
+}
+
+/// `name` without `_suffix` / `-suffix`, if it ends with one
+fn strip_suffix<'a>(name: &'a str, suffix: &str) -> Option<&'a str> {
+    let lower = name.to_ascii_lowercase();
+    if !lower.ends_with(suffix) || name.len() <= suffix.len() + 1 {
+        return None;
+    }
+    let base = &name[..name.len() - suffix.len()];
+    base.strip_suffix('_').or_else(|| base.strip_suffix('-'))
+}
+
+/// Pair tracks by the suffix conventions in `PAIR_SUFFIXES`, in table order of the intro.
+/// A track is used in at most one pair.
+pub fn find_pairs(files: &[AudioFileInfo]) -> Vec<IntroLoopPair> {
+    let mut pairs: Vec<IntroLoopPair> = Vec::new();
+    for intro in files {
+        for (intro_suffix, loop_suffix) in PAIR_SUFFIXES {
+            let Some(base) = strip_suffix(&intro.name, intro_suffix) else {
+                continue;
+            };
+            let body = files.iter().find(|f| {
+                strip_suffix(&f.name, loop_suffix).is_some_and(|b| b.eq_ignore_ascii_case(base))
+            });
+            let Some(body) = body else {
+                continue;
+            };
+            let taken = pairs.iter().any(|p| {
+                p.body.name == body.name && p.body.id == body.id
+                    || p.intro.name == intro.name && p.intro.id == intro.id
+            });
+            if !taken {
+                pairs.push(IntroLoopPair {
+                    base: base.to_string(),
+                    intro: intro.clone(),
+                    body: body.clone(),
+                });
+            }
+            break;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(name: &str, id: &str) -> AudioFileInfo {
+        AudioFileInfo::from_nus3audio(name.to_string(), id.to_string(), 0, String::new(), "OPUS".to_string())
+    }
+
+    #[test]
+    fn pairs_tracks_by_suffix() {
+        let files = vec![
+            track("bgm_stage01_intro", "0"),
+            track("bgm_stage01_loop", "1"),
+            track("BGM_Boss-Start", "2"),
+            track("bgm_boss-LOOP", "3"),
+            track("bgm_title_intro", "4"),
+            track("se_loop", "5"),
+            track("_intro", "6"),
+        ];
+        let pairs = find_pairs(&files);
+        let found: Vec<(&str, &str, &str)> = pairs
+            .iter()
+            .map(|p| (p.base.as_str(), p.intro.id.as_str(), p.body.id.as_str()))
+            .collect();
+        assert_eq!(found, vec![("bgm_stage01", "0", "1"), ("BGM_Boss", "2", "3")]);
+        assert_eq!(strip_suffix("intro", "intro"), None);
+        assert_eq!(strip_suffix("bgm_mainintro", "intro"), None);
+    }
+}
@@ -0,0 +1,238 @@
+use egui::{Color32, Context, Grid, RichText, ScrollArea, Window};
+use rfd::FileDialog;
+use std::fs;
+use std::path::PathBuf;
+
+use super::add_audio_utils::AddAudioUtils;
+use super::audio_file_info::AudioFileInfo;
+use super::intro_loop::{self, IntroLoopPair};
+use super::path_utils;
+use crate::audio_processing::smpl::{self, Pcm16Info};
+use crate::audio_processing::splice;
+
+/// What the owner should do for a pair
+pub enum IntroLoopAction {
+    /// Play the intro once, then repeat the loop part
+    Preview(IntroLoopPair),
+    /// Replace both tracks with the two halves of one source, already written to disk
+    JointReplace {
+        pair: IntroLoopPair,
+        intro_path: PathBuf,
+        loop_path: PathBuf,
+    },
+}
+
+/// Source picked for a joint replace, decoded to PCM16
+struct JointSource {
+    pair: IntroLoopPair,
+    path: PathBuf,
+    wav: Vec<u8>,
+    info: Pcm16Info,
+    split_secs: f32,
+}
+
+/// Lists intro/loop track pairs with a combined preview and a joint replace from one file
+pub struct IntroLoopModal {
+    pub open: bool,
+    pairs: Vec<IntroLoopPair>,
+    source: Option<JointSource>,
+    error: Option<String>,
+    action: Option<IntroLoopAction>,
+}
+
+impl Default for IntroLoopModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IntroLoopModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            pairs: Vec::new(),
+            source: None,
+            error: None,
+            action: None,
+        }
+    }
+
+    pub fn open_for(&mut self, files: &[AudioFileInfo]) {
+        self.pairs = intro_loop::find_pairs(files);
+        self.source = None;
+        self.error = None;
+        self.action = None;
+        self.open = true;
+    }
+
+    pub fn take_action(&mut self) -> Option<IntroLoopAction> {
+        self.action.take()
+    }
+
+    /// Ask for a source file and decode it. The split point starts at the file's own loop
+    /// start when it has one.
+    fn pick_source(&mut self, pair: IntroLoopPair) {
+        let Some(path) = FileDialog::new()
+            .add_filter("Audio Files", &["wav", "mp3", "flac", "ogg", "lopus", "idsp", "bin"])
+            .add_filter("All Files", &["*"])
+            .set_title(format!("Select Source for {}", pair.base))
+            .pick_file()
+        else {
+            return;
+        };
+
+        let wav = match fs::read(&path) {
+            Ok(data) if smpl::pcm16_info(&data).is_some() => data,
+            Ok(_) => match AddAudioUtils::convert_to_wav(&path.to_string_lossy()) {
+                Ok(data) => data,
+                Err(e) => {
+                    self.error = Some(e);
+                    return;
+                }
+            },
+            Err(e) => {
+                self.error = Some(format!("Failed to read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let Some(info) = smpl::pcm16_info(&wav) else {
+            self.error = Some("The source could not be decoded to PCM16 WAV".to_string());
+            return;
+        };
+        let split_secs = smpl::read_loop(&wav)
+            .map(|lp| lp.start as f32 / info.sample_rate as f32)
+            .unwrap_or(0.0);
+        self.error = None;
+        self.source = Some(JointSource { pair, path, wav, info, split_secs });
+    }
+
+    /// Split the source and write both halves next to the other temporary files
+    fn split_source(source: &JointSource) -> Result<(PathBuf, PathBuf), String> {
+        let frame = (source.split_secs * source.info.sample_rate as f32).round() as u32;
+        let (intro, body) = splice::split_pcm16(&source.wav, frame)?;
+        let stem = source.path.file_stem().unwrap_or_default().to_string_lossy();
+        let stem = path_utils::ascii_file_name(&stem);
+        let dir = path_utils::safe_temp_dir();
+        let intro_path = dir.join(format!("intro_{}.wav", stem));
+        let loop_path = dir.join(format!("loop_{}.wav", stem));
+        fs::write(&intro_path, intro).map_err(|e| format!("Failed to write intro part: {}", e))?;
+        fs::write(&loop_path, body).map_err(|e| format!("Failed to write loop part: {}", e))?;
+        Ok((intro_path, loop_path))
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        Window::new("Intro / Loop Pairs")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), error);
+                    ui.add_space(6.0);
+                }
+
+                if let Some(source) = &mut self.source {
+                    ui.label(RichText::new(format!("Joint replace: {}", source.pair.base)).strong());
+                    ui.label(format!(
+                        "Source: {}",
+                        source.path.file_name().unwrap_or_default().to_string_lossy()
+                    ));
+                    let duration = source.info.frames as f32 / source.info.sample_rate as f32;
+                    ui.horizontal(|ui| {
+                        ui.label("Loop starts at:");
+                        ui.add(
+                            egui::DragValue::new(&mut source.split_secs)
+                                .range(0.0..=duration)
+                                .speed(0.01)
+                                .suffix(" s"),
+                        );
+                        ui.label(RichText::new(format!("of {:.2} s", duration)).weak());
+                    });
+                    ui.label(
+                        RichText::new(format!(
+                            "Audio before this point replaces {}, the rest replaces {} and loops.",
+                            source.pair.intro.name, source.pair.body.name
+                        ))
+                        .size(11.0)
+                        .weak(),
+                    );
+                    ui.add_space(6.0);
+                    let mut close_source = false;
+                    ui.horizontal(|ui| {
+                        if ui.button("Replace Both").clicked() {
+                            match Self::split_source(source) {
+                                Ok((intro_path, loop_path)) => {
+                                    self.action = Some(IntroLoopAction::JointReplace {
+                                        pair: source.pair.clone(),
+                                        intro_path,
+                                        loop_path,
+                                    });
+                                    close_source = true;
+                                }
+                                Err(e) => self.error = Some(e),
+                            }
+                        }
+                        if ui.button("Back").clicked() {
+                            close_source = true;
+                        }
+                    });
+                    if close_source {
+                        self.source = None;
+                    }
+                    return;
+                }
+
+                if self.pairs.is_empty() {
+                    let conventions: Vec<String> = intro_loop::PAIR_SUFFIXES
+                        .iter()
+                        .map(|(intro, body)| format!("_{} / _{}", intro, body))
+                        .collect();
+                    ui.label("No intro/loop pairs found in this file.");
+                    ui.label(
+                        RichText::new(format!("Tracks are paired by name: {}", conventions.join(", ")))
+                            .size(11.0)
+                            .weak(),
+                    );
+                    return;
+                }
+
+                let mut pick = None;
+                ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    Grid::new("intro_loop_pairs").num_columns(4).striped(true).show(ui, |ui| {
+                        ui.label(RichText::new("Intro").strong());
+                        ui.label(RichText::new("Loop").strong());
+                        ui.label("");
+                        ui.label("");
+                        ui.end_row();
+                        for pair in &self.pairs {
+                            ui.label(&pair.intro.name);
+                            ui.label(&pair.body.name);
+                            if ui.button("Preview").on_hover_text("Play the intro, then repeat the loop part").clicked() {
+                                self.action = Some(IntroLoopAction::Preview(pair.clone()));
+                            }
+                            if ui
+                                .button("Replace Both...")
+                                .on_hover_text("Split one file at the loop start into both tracks")
+                                .clicked()
+                            {
+                                pick = Some(pair.clone());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some(pair) = pick {
+                    self.pick_source(pair);
+                }
+            });
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::Path;
+
+use super::bank_stats;
+use crate::audio_processing::smpl;
+
+/// Game frame rate the cue lengths are counted in
+pub const LIP_SYNC_FPS: u32 = 60;
+
+/// Samples quieter than this (about -50 dBFS) count as silence when finding the audible range
+const SILENCE_THRESHOLD: i16 = 104;
+
+/// Frames a cue of `samples` occupies. A partly covered last frame counts as a whole one,
+/// because the mouth is still moving during it.
+pub fn frames_for_samples(samples: u64, sample_rate: u32, fps: u32) -> u64 {
+    if sample_rate == 0 {
+        return 0;
+    }
+    (samples * fps as u64).div_ceil(sample_rate as u64)
+}
+
+/// `mm:ss:ff` timecode of a frame count
+pub fn timecode(frames: u64, fps: u32) -> String {
+    let fps = fps.max(1) as u64;
+    let secs = frames / fps;
+    format!("{:02}:{:02}:{:02}", secs / 60, secs % 60, frames % fps)
+}
+
+/// First and last frame (at `LIP_SYNC_FPS`) holding a sample above the silence threshold
+fn audible_frames(samples: &[u8], channels: u16, sample_rate: u32) -> Option<(u64, u64)> {
+    let frame_len = 2 * channels.max(1) as usize;
+    let loud = |frame: &[u8]| {
+        frame
+            .chunks_exact(2)
+            .any(|s| i16::from_le_bytes([s[0], s[1]]).unsigned_abs() > SILENCE_THRESHOLD as u16)
+    };
+    let frames: Vec<&[u8]> = samples.chunks_exact(frame_len).collect();
+    let first = frames.iter().position(|f| loud(f))? as u64;
+    let last = frames.iter().rposition(|f| loud(f))? as u64;
+    let to_game_frame = |sample: u64| sample * LIP_SYNC_FPS as u64 / sample_rate.max(1) as u64;
+    Some((to_game_frame(first), to_game_frame(last)))
+}
+
+/// Timestamp sheet of one exported WAV
+pub fn timestamps_text(track_name: &str, wav: &[u8]) -> Result<String, String> {
+    let mut text = format!("Track: {}\n", track_name);
+    if let Some((info, samples)) = smpl::pcm16_samples(wav) {
+        let frames = frames_for_samples(info.frames as u64, info.sample_rate, LIP_SYNC_FPS);
+        text.push_str(&format!("Sample rate: {} Hz\n", info.sample_rate));
+        text.push_str(&format!("Samples: {}\n", info.frames));
+        text.push_str(&format!(
+            "Duration: {:.3} s\n",
+            info.frames as f64 / info.sample_rate.max(1) as f64
+        ));
+        text.push_str(&format!(
+            "Length: {} frames at {} fps ({})\n",
+            frames,
+            LIP_SYNC_FPS,
+            timecode(frames, LIP_SYNC_FPS)
+        ));
+        match audible_frames(samples, info.channels, info.sample_rate) {
+            Some((first, last)) => text.push_str(&format!(
+                "Audible: frame {} to {} ({} to {})\n",
+                first,
+                last,
+                timecode(first, LIP_SYNC_FPS),
+                timecode(last, LIP_SYNC_FPS)
+            )),
+            None => text.push_str("Audible: silent\n"),
+        }
+        return Ok(text);
+    }
+
+    // Other WAV codecs only give a duration
+    let (Some(duration), _) = bank_stats::measure_wav(wav) else {
+        return Err("Not a WAV file with a known duration".to_string());
+    };
+    let frames = (duration * LIP_SYNC_FPS as f64).ceil() as u64;
+    text.push_str(&format!("Duration: {:.3} s\n", duration));
+    text.push_str(&format!(
+        "Length: {} frames at {} fps ({})\n",
+        frames,
+        LIP_SYNC_FPS,
+        timecode(frames, LIP_SYNC_FPS)
+    ));
+    Ok(text)
+}
+
+/// Write `<name>.timestamps.txt` next to an exported WAV and return its path
+pub fn write_for_wav(wav_path: &str) -> Result<String, String> {
+    let path = Path::new(wav_path);
+    let wav = fs::read(path).map_err(|e| format!("Failed to read {} for timestamps: {}", wav_path, e))?;
+    let track_name = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let text = timestamps_text(&track_name, &wav).map_err(|e| format!("{}: {}", track_name, e))?;
+    let output = path.with_file_name(format!("{}.timestamps.txt", track_name));
+    fs::write(&output, text).map_err(|e| format!("Failed to write {:?}: {}", output, e))?;
+    Ok(output.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_processing::splice;
+
+    #[test]
+    fn counts_partial_frames_and_audible_range() {
+        assert_eq!(frames_for_samples(48000, 48000, 60), 60);
+        assert_eq!(frames_for_samples(48001, 48000, 60), 61);
+        assert_eq!(frames_for_samples(0, 48000, 60), 0);
+        assert_eq!(timecode(3725, 60), "01:02:05");
+
+        // Mono 600 Hz: 0.1 s of silence, 0.5 s of sound, 0.4 s of silence
+        let mut samples = Vec::new();
+        for i in 0..600 {
+            let s: i16 = if (60..360).contains(&i) { 8000 } else { 0 };
+            samples.extend_from_slice(&s.to_le_bytes());
+        }
+        let wav = splice::write_pcm16(1, 600, &samples);
+        let text = timestamps_text("vc_test", &wav).unwrap();
+        assert!(text.contains("Length: 60 frames at 60 fps (00:01:00)"));
+        assert!(text.contains("Audible: frame 6 to 35"));
+        assert!(timestamps_text("bad", b"OPUS").is_err());
+    }
+}
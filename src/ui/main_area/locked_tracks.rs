@@ -0,0 +1,56 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::color_labels::bank_key;
+use crate::track_key::TrackKey;
+
+/// Tracks the user marked as final, keyed by bank file name and then by track. Batch
+/// replace, clear, remove and normalize skip them; single-row actions still work.
+#[derive(Debug, Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct LockedTracks {
+    banks: BTreeMap<String, BTreeSet<TrackKey>>,
+}
+
+impl LockedTracks {
+    pub fn is_locked(&self, bank_path: &str, key: &TrackKey) -> bool {
+        self.banks.get(&bank_key(bank_path)).is_some_and(|rows| rows.contains(key))
+    }
+
+    pub fn set(&mut self, bank_path: &str, key: &TrackKey, locked: bool) {
+        let bank = bank_key(bank_path);
+        if locked {
+            self.banks.entry(bank).or_default().insert(key.clone());
+        } else if let Some(rows) = self.banks.get_mut(&bank) {
+            rows.remove(key);
+            if rows.is_empty() {
+                self.banks.remove(&bank);
+            }
+        }
+    }
+
+    /// Locked tracks of one bank
+    pub fn for_bank(&self, bank_path: &str) -> BTreeSet<TrackKey> {
+        self.banks.get(&bank_key(bank_path)).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locks_are_kept_per_bank_file_name() {
+        let (intro, outro) = (TrackKey::bank("0x0", "intro"), TrackKey::bank("0x1", "loop"));
+        let mut locked = LockedTracks::default();
+        locked.set("C:/mods/BGM_Stage.nus3bank", &intro, true);
+        locked.set("C:/mods/BGM_Stage.nus3bank", &outro, true);
+        locked.set("C:/mods/BGM_Stage.nus3bank", &outro, false);
+
+        assert!(locked.is_locked("D:/backup/bgm_stage.nus3bank", &intro));
+        assert!(!locked.is_locked("D:/backup/bgm_stage.nus3bank", &outro));
+        assert_eq!(locked.for_bank("C:/mods/BGM_Stage.nus3bank").len(), 1);
+        assert!(!locked.is_locked("other.nus3bank", &intro));
+
+        locked.set("C:/mods/BGM_Stage.nus3bank", &intro, false);
+        assert!(locked.banks.is_empty());
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+
+use super::loop_settings_modal::LoopSettings;
+
+// Loop settings copied by the user. Lives for the session only and is never saved.
+static LOOP_CLIPBOARD: Lazy<Mutex<Option<LoopSettings>>> = Lazy::new(|| Mutex::new(None));
+
+pub fn copy(settings: &LoopSettings) -> Result<(), String> {
+    *LOOP_CLIPBOARD
+        .lock()
+        .map_err(|e| format!("Failed to lock loop clipboard: {}", e))? = Some(settings.clone());
+    Ok(())
+}
+
+pub fn get() -> Option<LoopSettings> {
+    LOOP_CLIPBOARD.lock().ok()?.clone()
+}
+
+pub fn has_settings() -> bool {
+    LOOP_CLIPBOARD.lock().map(|c| c.is_some()).unwrap_or(false)
+}
+
+/// Short description of the clipboard content for menus and toasts
+pub fn describe(settings: &LoopSettings) -> String {
+    if !settings.enable_loop {
+        return "loop disabled".to_string();
+    }
+    let loop_text = if settings.use_custom_loop {
+        let start_str = settings.loop_start.map_or("start".to_string(), |s| format!("{:.2}s", s));
+        let end_str = settings.loop_end.map_or("end".to_string(), |e| format!("{:.2}s", e));
+        format!("{} to {}", start_str, end_str)
+    } else {
+        "full track".to_string()
+    };
+    if settings.gain_db.abs() > f32::EPSILON {
+        format!("{}, {:+.1} dB", loop_text, settings.gain_db)
+    } else {
+        loop_text
+    }
+}
+
+/// Apply copied settings to `target`, keeping the target's own duration.
+/// Loop points beyond the target duration are clamped.
+pub fn paste_into(target: &mut LoopSettings, copied: &LoopSettings) {
+    let duration = target.estimated_duration;
+    let clamp = |v: f32| if duration > 0.0 { v.clamp(0.0, duration) } else { v.max(0.0) };
+
+    target.enable_loop = copied.enable_loop;
+    target.use_custom_loop = copied.use_custom_loop;
+    target.loop_start = copied.loop_start.map(clamp);
+    target.loop_end = copied.loop_end.map(clamp);
+    target.gain_db = copied.gain_db;
+    // Exact frames belong to the copied track's source; the target recomputes its own
+    target.loop_start_sample = None;
+    target.loop_end_sample = None;
+
+    if let (Some(start), Some(end)) = (target.loop_start, target.loop_end) {
+        if start > end {
+            target.loop_end = Some(start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_clamps_to_target_duration() {
+        let copied = LoopSettings {
+            loop_start: Some(12.0),
+            loop_end: Some(90.0),
+            use_custom_loop: true,
+            enable_loop: true,
+            estimated_duration: 120.0,
+            gain_db: -3.0,
+            loop_start_sample: Some(576000),
+            loop_end_sample: Some(4320000),
+        };
+        let mut target = LoopSettings {
+            estimated_duration: 60.0,
+            ..LoopSettings::default()
+        };
+
+        paste_into(&mut target, &copied);
+
+        assert_eq!(target.loop_start, Some(12.0));
+        assert_eq!(target.loop_end, Some(60.0));
+        assert!(target.use_custom_loop);
+        assert_eq!(target.gain_db, -3.0);
+        assert_eq!(target.estimated_duration, 60.0);
+        assert_eq!(target.loop_start_sample, None);
+    }
+}
@@ -0,0 +1,232 @@
+use egui::{Align2, Color32, CursorIcon, FontId, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+
+use super::waveform::Waveform;
+
+/// Distance in points within which a click grabs a loop handle
+const GRAB_DISTANCE: f32 = 6.0;
+
+/// How far, in frames, snapping looks for a zero crossing
+const SNAP_RADIUS: usize = 2048;
+
+/// Fewest frames the view can be zoomed in to
+const MIN_VIEW_FRAMES: f64 = 64.0;
+
+const START_COLOR: Color32 = Color32::from_rgb(80, 200, 120);
+const END_COLOR: Color32 = Color32::from_rgb(240, 90, 90);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    Start,
+    End,
+}
+
+/// Zoomable waveform with draggable loop start and end handles, editing frame positions
+#[derive(Debug, Clone)]
+pub struct LoopEditor {
+    /// First visible frame
+    view_start: f64,
+    /// Number of visible frames; 0 shows the whole file
+    view_frames: f64,
+    pub snap_to_zero: bool,
+    dragging: Option<Handle>,
+}
+
+impl Default for LoopEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoopEditor {
+    pub fn new() -> Self {
+        Self {
+            view_start: 0.0,
+            view_frames: 0.0,
+            snap_to_zero: true,
+            dragging: None,
+        }
+    }
+
+    /// Show the whole file again, e.g. after the source changed
+    pub fn reset_view(&mut self) {
+        self.view_start = 0.0;
+        self.view_frames = 0.0;
+        self.dragging = None;
+    }
+
+    /// Zoom in on `frame`, showing `span` frames around it
+    fn focus(&mut self, frame: usize, span: f64, total: f64) {
+        self.view_frames = span.clamp(MIN_VIEW_FRAMES.min(total), total);
+        self.view_start = (frame as f64 - self.view_frames / 2.0).clamp(0.0, total - self.view_frames);
+    }
+
+    /// Draw the controls and the waveform; returns whether a handle moved.
+    /// `start` and `end` are frames, `end` being the last frame of the loop.
+    pub fn show(&mut self, ui: &mut Ui, waveform: &Waveform, start: &mut usize, end: &mut usize) -> bool {
+        let total = waveform.frames() as f64;
+        if total < 1.0 {
+            return false;
+        }
+        if self.view_frames <= 0.0 || self.view_frames > total {
+            self.view_start = 0.0;
+            self.view_frames = total;
+        }
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.snap_to_zero, "Snap to zero crossing")
+                .on_hover_text("Move dragged handles to the nearest zero crossing to avoid a click at the loop seam");
+            ui.separator();
+            if ui.button("Fit").clicked() {
+                self.view_start = 0.0;
+                self.view_frames = total;
+            }
+            // Half a second either side is enough to judge the seam
+            let span = waveform.sample_rate as f64;
+            if ui.button("Zoom to Start").clicked() {
+                self.focus(*start, span, total);
+            }
+            if ui.button("Zoom to End").clicked() {
+                self.focus(*end, span, total);
+            }
+            ui.label(egui::RichText::new("Scroll to zoom, drag the background to pan").size(11.0).weak());
+        });
+
+        let (rect, response) = ui.allocate_exact_size(Vec2::new(ui.available_width(), 110.0), Sense::click_and_drag());
+        let to_x = |frame: f64, view_start: f64, view_frames: f64| {
+            rect.left() + ((frame - view_start) / view_frames) as f32 * rect.width()
+        };
+        let x_to_frame = |x: f32, view_start: f64, view_frames: f64| {
+            (view_start + ((x - rect.left()) / rect.width()) as f64 * view_frames).clamp(0.0, total - 1.0)
+        };
+
+        // Zoom around the pointer with the scroll wheel
+        if let Some(pointer) = response.hover_pos() {
+            // Take the scroll so the dialog does not scroll at the same time
+            let scroll = ui.input_mut(|i| std::mem::take(&mut i.smooth_scroll_delta).y);
+            if scroll != 0.0 {
+                let anchor = x_to_frame(pointer.x, self.view_start, self.view_frames);
+                let factor = (-scroll as f64 / 200.0).exp();
+                self.view_frames = (self.view_frames * factor).clamp(MIN_VIEW_FRAMES.min(total), total);
+                let ratio = ((pointer.x - rect.left()) / rect.width()) as f64;
+                self.view_start = (anchor - ratio * self.view_frames).clamp(0.0, total - self.view_frames);
+            }
+        }
+
+        if response.drag_started() {
+            let (view_start, view_frames) = (self.view_start, self.view_frames);
+            self.dragging = response.interact_pointer_pos().and_then(|pos| {
+                let near = |frame: usize| (to_x(frame as f64, view_start, view_frames) - pos.x).abs() <= GRAB_DISTANCE;
+                if near(*start) {
+                    Some(Handle::Start)
+                } else if near(*end) {
+                    Some(Handle::End)
+                } else {
+                    None
+                }
+            });
+        }
+        if response.dragged() {
+            match (self.dragging, response.interact_pointer_pos()) {
+                (Some(handle), Some(pos)) => {
+                    let mut frame = x_to_frame(pos.x, self.view_start, self.view_frames).round() as usize;
+                    if self.snap_to_zero {
+                        frame = waveform.nearest_zero_crossing(frame, SNAP_RADIUS);
+                    }
+                    match handle {
+                        Handle::Start => {
+                            *start = frame.min(*end);
+                        }
+                        Handle::End => {
+                            *end = frame.max(*start);
+                        }
+                    }
+                    changed = true;
+                }
+                (None, _) => {
+                    let frames_per_point = self.view_frames / rect.width() as f64;
+                    self.view_start = (self.view_start - response.drag_delta().x as f64 * frames_per_point)
+                        .clamp(0.0, total - self.view_frames);
+                }
+                _ => {}
+            }
+        }
+        if response.drag_stopped() {
+            self.dragging = None;
+        }
+
+        let (view_start, view_frames) = (self.view_start, self.view_frames);
+        let frame_to_x = |frame: f64| to_x(frame, view_start, view_frames);
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+
+        let loop_rect = Rect::from_x_y_ranges(
+            frame_to_x(*start as f64).max(rect.left())..=frame_to_x(*end as f64).min(rect.right()),
+            rect.y_range(),
+        );
+        if loop_rect.width() > 0.0 {
+            painter.rect_filled(loop_rect, 0.0, Color32::from_rgba_unmultiplied(90, 170, 255, 40));
+        }
+
+        let stroke = Stroke::new(1.0, ui.visuals().widgets.active.fg_stroke.color);
+        let mid = rect.center().y;
+        let half_height = rect.height() * 0.5;
+        let columns = rect.width().max(1.0) as usize;
+        for column in 0..columns {
+            let from = self.view_start + self.view_frames * column as f64 / columns as f64;
+            let to = self.view_start + self.view_frames * (column + 1) as f64 / columns as f64;
+            let (lo, hi) = waveform.range_extent(from as usize, (to.ceil() as usize).max(from as usize + 1));
+            let x = rect.left() + column as f32 + 0.5;
+            painter.line_segment(
+                [
+                    Pos2::new(x, mid - (hi.clamp(-1.0, 1.0) * half_height).max(0.5)),
+                    Pos2::new(x, mid - (lo.clamp(-1.0, 1.0) * half_height).min(-0.5)),
+                ],
+                stroke,
+            );
+        }
+        painter.line_segment(
+            [Pos2::new(rect.left(), mid), Pos2::new(rect.right(), mid)],
+            Stroke::new(1.0, ui.visuals().weak_text_color().gamma_multiply(0.4)),
+        );
+
+        for (frame, color, label, align) in [
+            (*start, START_COLOR, "S", Align2::LEFT_TOP),
+            (*end, END_COLOR, "E", Align2::RIGHT_TOP),
+        ] {
+            let x = frame_to_x(frame as f64);
+            if x < rect.left() - 1.0 || x > rect.right() + 1.0 {
+                continue;
+            }
+            painter.line_segment([Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())], Stroke::new(2.0, color));
+            let offset = if align == Align2::LEFT_TOP { 3.0 } else { -3.0 };
+            painter.text(Pos2::new(x + offset, rect.top() + 2.0), align, label, FontId::monospace(11.0), color);
+        }
+
+        // Show where the view is within the file
+        let rate = waveform.sample_rate.max(1) as f64;
+        painter.text(
+            rect.left_bottom() + Vec2::new(4.0, -2.0),
+            Align2::LEFT_BOTTOM,
+            format!("{:.3}s", self.view_start / rate),
+            FontId::monospace(10.0),
+            ui.visuals().weak_text_color(),
+        );
+        painter.text(
+            rect.right_bottom() + Vec2::new(-4.0, -2.0),
+            Align2::RIGHT_BOTTOM,
+            format!("{:.3}s", (self.view_start + self.view_frames) / rate),
+            FontId::monospace(10.0),
+            ui.visuals().weak_text_color(),
+        );
+
+        if let Some(pos) = response.hover_pos() {
+            let near = |frame: usize| (frame_to_x(frame as f64) - pos.x).abs() <= GRAB_DISTANCE;
+            if self.dragging.is_some() || near(*start) || near(*end) {
+                ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+            }
+        }
+
+        changed
+    }
+}
@@ -1,10 +1,20 @@
 use super::audio_file_info::AudioFileInfo;
-use egui::{Context, ScrollArea, Ui, Window};
+use super::file_drop;
+use super::loop_clipboard;
+use super::lopus_encoder::{self, EncodeFormat, EncoderSettings, DEFAULT_IDSP_COMMAND, DEFAULT_LOPUS_COMMAND};
+use super::replace_utils::ReplaceUtils;
+use super::sample_rate_check::{BatchRateReport, RateCheck};
+use super::track_format::TrackFormat;
+use super::loop_editor::LoopEditor;
+use super::waveform::Waveform;
+use egui::{Context, RichText, ScrollArea, Ui, Window};
+use std::path::{Path, PathBuf};
 use mp3_duration;
 use hound;
 
 /// Structure to hold loop settings
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct LoopSettings {
     /// Loop start point in seconds
     pub loop_start: Option<f32>,
@@ -18,6 +28,11 @@ pub struct LoopSettings {
     pub estimated_duration: f32,
     /// Gain in decibels to apply after import
     pub gain_db: f32,
+    /// Exact loop start in frames of the source, set alongside `loop_start` when the
+    /// sample rate is known; written to the smpl chunk as is
+    pub loop_start_sample: Option<u32>,
+    /// Exact last frame of the loop, the counterpart of `loop_end`
+    pub loop_end_sample: Option<u32>,
 }
 
 impl Default for LoopSettings {
@@ -29,10 +44,41 @@ impl Default for LoopSettings {
             enable_loop: true,
             estimated_duration: 0.0,
             gain_db: 0.0,
+            loop_start_sample: None,
+            loop_end_sample: None,
         }
     }
 }
 
+impl LoopSettings {
+    /// Set the loop to exact frames, keeping the seconds in step
+    pub fn set_loop_frames(&mut self, sample_rate: u32, start: u32, end: u32) {
+        let rate = sample_rate.max(1) as f64;
+        self.loop_start_sample = Some(start);
+        self.loop_end_sample = Some(end);
+        self.loop_start = Some((start as f64 / rate) as f32);
+        self.loop_end = Some((end as f64 / rate) as f32);
+    }
+
+    /// Recompute the exact frames from the seconds, after those were typed or the source
+    /// changed. Without a sample rate the frames are dropped and the seconds are used.
+    pub fn sync_frames(&mut self, sample_rate: Option<u32>) {
+        let to_frame = |seconds: f32| sample_rate.map(|rate| (seconds as f64 * rate as f64).round() as u32);
+        self.loop_start_sample = self.loop_start.and_then(to_frame);
+        self.loop_end_sample = self.loop_end.and_then(to_frame);
+    }
+}
+
+/// Playback the modal asks the main area for while auditioning loop points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoopPreview {
+    /// Play the source from the loop start, repeating between the points
+    Play { start: Option<f32>, end: Option<f32> },
+    /// Move the loop points of the preview that is playing
+    Region { start: Option<f32>, end: Option<f32> },
+    Stop,
+}
+
 /// Modal window for loop settings
 pub struct LoopSettingsModal {
     /// Is the modal open
@@ -43,6 +89,29 @@ pub struct LoopSettingsModal {
     pub settings: LoopSettings,
     /// Whether settings were changed and confirmed by the user
     pub confirmed: bool,
+    /// Sample rate comparison shown when one file replaces several tracks
+    pub rate_report: Option<BatchRateReport>,
+    /// Comparison still running for `rate_report`
+    pub rate_check: Option<RateCheck>,
+    /// Replacement source file
+    pub source_path: Option<PathBuf>,
+    /// Peak preview of the source, if it could be decoded
+    waveform: Option<Waveform>,
+    /// Zoomable view for placing the custom loop points
+    loop_editor: LoopEditor,
+    /// Set when a file dropped onto the modal replaced the source
+    source_changed: bool,
+    /// Codec the replacement is encoded to before queuing; picked from the target entry
+    /// and only offered for .nus3audio tracks
+    pub encode_format: EncodeFormat,
+    /// Encoder chain, shared with the persisted settings of the main area
+    pub lopus_encoder: EncoderSettings,
+    /// Loop points of the preview that is playing, if one is
+    previewing: Option<(Option<f32>, Option<f32>)>,
+    preview_request: Option<LoopPreview>,
+    /// Header format of the track being replaced, kept up to date by the main area; its
+    /// loop can be mapped onto the new source
+    pub original_format: Option<TrackFormat>,
 }
 
 impl Default for LoopSettingsModal {
@@ -59,6 +128,17 @@ impl LoopSettingsModal {
             audio_info: None,
             settings: LoopSettings::default(),
             confirmed: false,
+            rate_report: None,
+            rate_check: None,
+            source_path: None,
+            waveform: None,
+            loop_editor: LoopEditor::new(),
+            source_changed: false,
+            encode_format: EncodeFormat::Wav,
+            lopus_encoder: EncoderSettings::default(),
+            previewing: None,
+            preview_request: None,
+            original_format: None,
         }
     }
 
@@ -105,8 +185,16 @@ impl LoopSettingsModal {
         println!("Selected replacement file: {}", file_path);
         
         self.audio_info = Some(audio_info.clone());
+        self.rate_report = None;
+        self.rate_check = None;
+        self.source_changed = false;
+        self.encode_format = lopus_encoder::default_format(&audio_info, &self.lopus_encoder);
+        self.load_waveform(Path::new(file_path));
         // First try to get the actual duration from the audio file
-        let duration = match self.get_actual_audio_duration(file_path) {
+        let duration = match self
+            .get_actual_audio_duration(file_path)
+            .or(self.waveform.as_ref().map(|w| w.duration_secs))
+        {
             Some(actual_duration) => {
                 println!(
                     "Using actual duration for {}: {:.2}s",
@@ -132,15 +220,98 @@ impl LoopSettingsModal {
             enable_loop: true,
             estimated_duration: duration,
             gain_db: 0.0,
+            loop_start_sample: None,
+            loop_end_sample: None,
         };
+        self.loop_editor.reset_view();
 
         self.open = true;
         self.confirmed = false;
     }
 
+    /// Open the modal on a queued replacement with the settings it was made with, so its
+    /// loop and gain can be changed without picking the source again
+    pub fn open_for_edit(&mut self, audio_info: AudioFileInfo, source: &Path, settings: LoopSettings) {
+        self.open_with_audio(audio_info, &source.to_string_lossy());
+        let duration = self.settings.estimated_duration;
+        self.settings = LoopSettings {
+            estimated_duration: duration,
+            ..settings
+        };
+        if self.settings.loop_start_sample.is_none() {
+            self.settings.sync_frames(self.waveform.as_ref().map(|w| w.sample_rate));
+        }
+    }
+
+    fn load_waveform(&mut self, path: &Path) {
+        self.source_path = Some(path.to_path_buf());
+        self.waveform = match Waveform::load(path) {
+            Ok(waveform) => Some(waveform),
+            Err(e) => {
+                println!("No waveform preview for {}: {}", path.display(), e);
+                None
+            }
+        };
+    }
+
+    /// Use a dropped file as the replacement source, keeping loop and gain settings
+    /// except where the loop points no longer fit the new duration
+    pub fn set_source(&mut self, path: PathBuf) {
+        let Some(audio_info) = self.audio_info.as_mut() else {
+            return;
+        };
+        println!("Replacement source changed to: {}", path.display());
+        ReplaceUtils::set_replacement_path(audio_info, path.clone());
+        audio_info.filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let size = audio_info.size;
+
+        self.load_waveform(&path);
+        let duration = self
+            .get_actual_audio_duration(&path.to_string_lossy())
+            .or(self.waveform.as_ref().map(|w| w.duration_secs))
+            .unwrap_or_else(|| Self::estimate_duration_from_size(size));
+        self.settings.estimated_duration = duration;
+        self.settings.loop_start = self.settings.loop_start.map(|s| s.min(duration));
+        self.settings.loop_end = self.settings.loop_end.map(|e| e.min(duration));
+        self.settings.sync_frames(self.waveform.as_ref().map(|w| w.sample_rate));
+        self.loop_editor.reset_view();
+        self.rate_report = None;
+        self.rate_check = None;
+        self.source_changed = true;
+    }
+
+    /// Whether the source was changed by a drop since the last call
+    pub fn take_source_changed(&mut self) -> bool {
+        std::mem::take(&mut self.source_changed)
+    }
+
     /// Close the modal
     pub fn close(&mut self) {
         self.open = false;
+        self.stop_preview();
+    }
+
+    /// Playback requested since the last call
+    pub fn take_preview_request(&mut self) -> Option<LoopPreview> {
+        self.preview_request.take()
+    }
+
+    /// Loop points the preview repeats between; `None` ends mean the file start and end
+    fn preview_region(settings: &LoopSettings) -> (Option<f32>, Option<f32>) {
+        if settings.use_custom_loop {
+            (settings.loop_start, settings.loop_end)
+        } else {
+            (None, None)
+        }
+    }
+
+    fn stop_preview(&mut self) {
+        if self.previewing.take().is_some() {
+            self.preview_request = Some(LoopPreview::Stop);
+        }
     }
 
     /// Reset the confirmed flag
@@ -153,6 +324,7 @@ impl LoopSettingsModal {
         if !self.open {
             return;
         }
+        self.poll_rate_check(ctx);
 
         if let Some(audio_info) = &self.audio_info {
             let title = format!("Loop Settings - {}", audio_info.name);
@@ -160,7 +332,7 @@ impl LoopSettingsModal {
             let min_width = available_rect.width() * 0.5;
             let min_height = available_rect.height() * 0.5;
 
-            Window::new(&title)
+            let response = Window::new(&title)
                 .min_width(min_width)
                 .min_height(min_height)
                 .resizable(true)
@@ -168,7 +340,26 @@ impl LoopSettingsModal {
                 .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
                 .show(ctx, |ui| {
                     self.render_content(ui);
+                    file_drop::paint_drop_target(ui, ui.max_rect());
                 });
+
+            if response.is_some() {
+                if let Some(path) = file_drop::dropped_audio_file(ctx) {
+                    self.set_source(path);
+                    self.stop_preview();
+                }
+            }
+
+            if !self.open || !self.settings.enable_loop {
+                self.stop_preview();
+            } else if let Some(playing) = self.previewing {
+                // Follow edits of the loop points without restarting the preview
+                let (start, end) = Self::preview_region(&self.settings);
+                if playing != (start, end) {
+                    self.previewing = Some((start, end));
+                    self.preview_request = Some(LoopPreview::Region { start, end });
+                }
+            }
         }
     }
 
@@ -191,8 +382,35 @@ impl LoopSettingsModal {
                         ui.label("Name:");
                         ui.label(&audio_info.name);
                         ui.end_row();
+
+                        ui.label("Source:");
+                        ui.label(&audio_info.filename)
+                            .on_hover_text(self.source_path.as_ref().map(|p| p.display().to_string()).unwrap_or_default());
+                        ui.end_row();
+
+                        ui.label("Duration:");
+                        ui.label(format!("{:.2} seconds", self.settings.estimated_duration));
+                        ui.end_row();
                     });
 
+                ui.add_space(6.0);
+                if let Some(waveform) = &self.waveform {
+                    let loop_region = if self.settings.enable_loop {
+                        Some((
+                            self.settings.loop_start.unwrap_or(0.0),
+                            self.settings.loop_end.unwrap_or(self.settings.estimated_duration),
+                        ))
+                    } else {
+                        None
+                    };
+                    waveform.show(ui, 64.0, loop_region);
+                }
+                ui.label(
+                    egui::RichText::new("Drop an audio file onto this window to change the source")
+                        .size(11.0)
+                        .weak(),
+                );
+
                 ui.add_space(20.0);
 
                 // Loop settings section
@@ -201,6 +419,58 @@ impl LoopSettingsModal {
                     ui.add_space(10.0);
                 });
 
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Copy loop settings")
+                        .on_hover_text("Copy loop points and gain for pasting onto other tracks")
+                        .clicked()
+                    {
+                        let _ = loop_clipboard::copy(&self.settings);
+                    }
+                    let copied = loop_clipboard::get();
+                    let paste_hint = copied
+                        .as_ref()
+                        .map(|s| format!("Use copied settings: {}", loop_clipboard::describe(s)))
+                        .unwrap_or_else(|| "Nothing copied yet".to_string());
+                    if ui
+                        .add_enabled(copied.is_some(), egui::Button::new("Paste loop settings"))
+                        .on_hover_text(paste_hint)
+                        .clicked()
+                    {
+                        if let Some(copied) = copied {
+                            loop_clipboard::paste_into(&mut self.settings, &copied);
+                            self.settings.sync_frames(self.waveform.as_ref().map(|w| w.sample_rate));
+                        }
+                    }
+
+                    let duration = self.settings.estimated_duration as f64;
+                    let mapped = self.original_format.as_ref().and_then(|f| f.loop_mapped_onto(duration));
+                    let map_hint = match (self.original_format.as_ref().and_then(|f| f.loop_secs), mapped) {
+                        (Some((start, end)), Some((new_start, new_end))) => format!(
+                            "The original loops {:.2}s to {:.2}s; scaled to this source's length that is {:.2}s to {:.2}s",
+                            start, end, new_start, new_end
+                        ),
+                        (Some(_), None) => "The length of the original or of this source is unknown".to_string(),
+                        (None, _) => "The track being replaced has no loop".to_string(),
+                    };
+                    if ui
+                        .add_enabled(mapped.is_some(), egui::Button::new("Map original loop"))
+                        .on_hover_text(&map_hint)
+                        .on_disabled_hover_text(&map_hint)
+                        .clicked()
+                    {
+                        if let Some((start, end)) = mapped {
+                            self.settings.enable_loop = true;
+                            self.settings.use_custom_loop = true;
+                            self.settings.loop_start = Some(start as f32);
+                            self.settings.loop_end = Some(end as f32);
+                            self.settings.sync_frames(self.waveform.as_ref().map(|w| w.sample_rate));
+                        }
+                    }
+                });
+
+                ui.add_space(5.0);
+
                 ui.checkbox(&mut self.settings.enable_loop, "Enable loop functionality");
                 
                 ui.add_space(5.0);
@@ -217,6 +487,18 @@ impl LoopSettingsModal {
 
                 if self.settings.enable_loop && self.settings.use_custom_loop {
                     ui.add_space(10.0);
+                    let sample_rate = self.waveform.as_ref().map(|w| w.sample_rate);
+
+                    // Drag the loop points on the waveform, frame by frame when zoomed in
+                    if let Some(waveform) = self.waveform.as_ref().filter(|w| w.frames() > 0) {
+                        let last = waveform.frames() - 1;
+                        let mut start = (self.settings.loop_start_sample.map_or(0, |s| s as usize)).min(last);
+                        let mut end = (self.settings.loop_end_sample.map_or(last, |e| e as usize)).clamp(start, last);
+                        if self.loop_editor.show(ui, waveform, &mut start, &mut end) {
+                            self.settings.set_loop_frames(waveform.sample_rate, start as u32, end as u32);
+                        }
+                        ui.add_space(6.0);
+                    }
 
                     // Loop start input
                     ui.horizontal(|ui| {
@@ -239,7 +521,9 @@ impl LoopSettingsModal {
                                     self.settings.loop_end = Some(start_value);
                                 }
                             }
+                            self.settings.sync_frames(sample_rate);
                         }
+                        Self::render_frame_input(ui, &mut self.settings, sample_rate, self.waveform.as_ref(), true);
                     });
 
                     // Loop end input
@@ -262,7 +546,9 @@ impl LoopSettingsModal {
                             .changed()
                         {
                             self.settings.loop_end = Some(end_value);
+                            self.settings.sync_frames(sample_rate);
                         }
+                        Self::render_frame_input(ui, &mut self.settings, sample_rate, self.waveform.as_ref(), false);
                     });
 
                     // Show loop duration
@@ -273,8 +559,22 @@ impl LoopSettingsModal {
 
                     ui.add_space(10.0);
                     ui.label(format!("Loop Duration: {:.2} seconds", loop_duration));
+                    Self::render_preview_button(
+                        ui,
+                        self.source_path.is_some(),
+                        &self.settings,
+                        &mut self.previewing,
+                        &mut self.preview_request,
+                    );
                 } else if self.settings.enable_loop {
                     ui.label("Audio will loop from beginning to end");
+                    Self::render_preview_button(
+                        ui,
+                        self.source_path.is_some(),
+                        &self.settings,
+                        &mut self.previewing,
+                        &mut self.preview_request,
+                    );
                 } else {
                     ui.label("Loop functionality is disabled");
                 }
@@ -310,10 +610,30 @@ impl LoopSettingsModal {
 
                 let linear_factor = 10f32.powf(self.settings.gain_db / 20.0);
                 ui.label(format!("Linear factor: {:.3}", linear_factor));
+                ui.label(
+                    RichText::new("Normalize, fades and downmix: right-click the track and pick Mix...")
+                        .weak()
+                        .size(11.0),
+                );
 
                 ui.add_space(20.0);
             });
 
+            if !audio_info.is_nus3bank {
+                let target = lopus_encoder::target_format(audio_info);
+                Self::render_encoding(ui, target, &mut self.encode_format, &mut self.lopus_encoder);
+            }
+
+            if self.rate_check.is_some() {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Checking the sample rates of the selected tracks...");
+                });
+            } else if let Some(report) = &self.rate_report {
+                Self::render_rate_report(ui, report);
+            }
+
             ui.separator();
             ui.add_space(10.0);
 
@@ -333,6 +653,165 @@ impl LoopSettingsModal {
         }
     }
 
+    /// Exact frame of the loop start or end, editable when the sample rate is known
+    fn render_frame_input(
+        ui: &mut Ui,
+        settings: &mut LoopSettings,
+        sample_rate: Option<u32>,
+        waveform: Option<&Waveform>,
+        is_start: bool,
+    ) {
+        let (Some(rate), Some(waveform)) = (sample_rate, waveform) else {
+            return;
+        };
+        let last = waveform.frames().saturating_sub(1) as u32;
+        let mut start = settings.loop_start_sample.unwrap_or(0).min(last);
+        let mut end = settings.loop_end_sample.unwrap_or(last).clamp(start, last);
+        let (value, range) = if is_start { (&mut start, 0..=last) } else { (&mut end, start..=last) };
+        ui.label("Sample:");
+        if ui.add(egui::DragValue::new(value).speed(1.0).range(range)).changed() {
+            end = end.max(start);
+            settings.set_loop_frames(rate, start, end);
+        }
+    }
+
+    /// Start or stop playing the source looped between the current points
+    fn render_preview_button(
+        ui: &mut Ui,
+        has_source: bool,
+        settings: &LoopSettings,
+        previewing: &mut Option<(Option<f32>, Option<f32>)>,
+        request: &mut Option<LoopPreview>,
+    ) {
+        ui.add_space(5.0);
+        if previewing.is_some() {
+            if ui.button("Stop Preview").clicked() {
+                *previewing = None;
+                *request = Some(LoopPreview::Stop);
+            }
+        } else if ui
+            .add_enabled(has_source, egui::Button::new("Preview Loop"))
+            .on_hover_text("Play the replacement from the loop start and wrap back at the loop end")
+            .clicked()
+        {
+            let (start, end) = Self::preview_region(settings);
+            *previewing = Some((start, end));
+            *request = Some(LoopPreview::Play { start, end });
+        }
+    }
+
+    /// Offer the external encoder chains, with the codec of the target entry picked
+    fn render_encoding(ui: &mut Ui, target: EncodeFormat, format: &mut EncodeFormat, encoder: &mut EncoderSettings) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Queue as:");
+            egui::ComboBox::from_id_salt("replacement_encode_format")
+                .selected_text(format.label())
+                .show_ui(ui, |ui| {
+                    for option in EncodeFormat::ALL {
+                        let text = if option == target { format!("{} (original)", option.label()) } else { option.label().to_string() };
+                        ui.selectable_value(format, option, text);
+                    }
+                })
+                .response
+                .on_hover_text("The processed WAV is encoded with the command below and the result's header is checked before it is queued.");
+        });
+        if *format != target {
+            let warning = match target {
+                EncodeFormat::Wav => "The original entry is not LOPUS or IDSP; check the game reads the chosen codec here.".to_string(),
+                _ => format!("The game expects {} in this slot and may not play {}.", target.label(), format.label()),
+            };
+            ui.colored_label(egui::Color32::GOLD, warning);
+        }
+        egui::CollapsingHeader::new("Encoder commands")
+            .id_salt("lopus_encoder_command")
+            .show(ui, |ui| {
+                for (label, command, default) in [
+                    ("LOPUS", &mut encoder.command, DEFAULT_LOPUS_COMMAND),
+                    ("IDSP", &mut encoder.idsp_command, DEFAULT_IDSP_COMMAND),
+                ] {
+                    ui.horizontal(|ui| {
+                        ui.label(label);
+                        ui.add(
+                            egui::TextEdit::singleline(command)
+                                .desired_width(ui.available_width() - 70.0)
+                                .font(egui::TextStyle::Monospace),
+                        );
+                        if ui.add_enabled(command != default, egui::Button::new("Reset")).clicked() {
+                            *command = default.to_string();
+                        }
+                    });
+                }
+                ui.label(
+                    egui::RichText::new(
+                        "{input} is the processed WAV and {output} the .lopus or .idsp to write. A bare program name is looked up in the tools folder and on PATH.",
+                    )
+                    .size(11.0)
+                    .weak(),
+                );
+                ui.checkbox(&mut encoder.encode_by_default, "Encode LOPUS and IDSP targets by default");
+            });
+        ui.add_space(6.0);
+    }
+
+    /// Take the sample rate comparison once its worker has finished
+    fn poll_rate_check(&mut self, ctx: &Context) {
+        let Some(check) = &self.rate_check else {
+            return;
+        };
+        match check.poll() {
+            Some(Ok(report)) => self.rate_report = Some(report),
+            Some(Err(e)) => log::warn!("Sample rate check skipped: {}", e),
+            None => {
+                ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                return;
+            }
+        }
+        self.rate_check = None;
+    }
+
+    /// Warn about batch targets whose original sample rate differs from the replacement
+    fn render_rate_report(ui: &mut Ui, report: &BatchRateReport) {
+        if !report.has_warnings() && report.unknown.is_empty() {
+            return;
+        }
+        ui.separator();
+        if report.has_warnings() {
+            let rates: Vec<String> = report.differing_rates().iter().map(|r| format!("{} Hz", r)).collect();
+            ui.colored_label(
+                egui::Color32::GOLD,
+                format!(
+                    "{} of {} targets were {} originally; the replacement is {} Hz. Their sample rate will change to {} Hz.",
+                    report.mismatches.len(),
+                    report.target_count,
+                    rates.join(", "),
+                    report.source_rate,
+                    report.source_rate
+                ),
+            );
+            egui::CollapsingHeader::new("Affected tracks")
+                .default_open(report.mismatches.len() <= 8)
+                .show(ui, |ui| {
+                    ScrollArea::vertical().max_height(120.0).id_salt("rate_mismatch_list").show(ui, |ui| {
+                        for m in &report.mismatches {
+                            ui.label(format!("{}: {} Hz -> {} Hz", m.track, m.original_rate, report.source_rate));
+                        }
+                    });
+                });
+        }
+        if !report.unknown.is_empty() {
+            ui.label(
+                egui::RichText::new(format!(
+                    "Original sample rate unknown for {} track(s): {}",
+                    report.unknown.len(),
+                    report.unknown.join(", ")
+                ))
+                .color(egui::Color32::GRAY),
+            );
+        }
+        ui.add_space(6.0);
+    }
+
     /// Estimate audio duration from file size (rough approximation)
     fn estimate_duration_from_size(size_bytes: usize) -> f32 {
         // Very rough estimate: Assuming ~16KB per second for compressed audio
@@ -0,0 +1,300 @@
+use std::fs;
+use std::path::Path;
+
+use super::audio_file_info::AudioFileInfo;
+use super::bank_args;
+use super::conversion_log;
+use super::path_utils;
+use super::replace_utils::ReplaceUtils;
+use crate::tools::{self, split_command, ExternalRunner, VgmstreamRunner};
+
+/// Encoder chain used until the user sets another one; VGAudio writes the Namco OPUS
+/// header that .nus3audio files expect
+pub const DEFAULT_LOPUS_COMMAND: &str = "VGAudioCli -c {input} {output} --opusheader namco";
+
+/// IDSP counterpart of `DEFAULT_LOPUS_COMMAND`; VGAudio picks the container from the
+/// .idsp extension of the output
+pub const DEFAULT_IDSP_COMMAND: &str = "VGAudioCli -c {input} {output}";
+
+/// Sample rates an Opus stream can be coded at
+const OPUS_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+/// Type of the Switch Opus stream header that follows the Namco header
+const SWITCH_OPUS_HEADER: u32 = 0x8000_0001;
+
+/// Codec a replacement is queued in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeFormat {
+    /// The processed WAV as it is
+    Wav,
+    Lopus,
+    Idsp,
+}
+
+impl EncodeFormat {
+    pub const ALL: [EncodeFormat; 3] = [EncodeFormat::Lopus, EncodeFormat::Idsp, EncodeFormat::Wav];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EncodeFormat::Wav => "WAV (no encoding)",
+            EncodeFormat::Lopus => "LOPUS",
+            EncodeFormat::Idsp => "IDSP",
+        }
+    }
+
+    /// File type shown for a track holding this format
+    fn file_type(&self) -> &'static str {
+        match self {
+            EncodeFormat::Wav => "WAV",
+            EncodeFormat::Lopus => "OPUS",
+            EncodeFormat::Idsp => "IDSP",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            EncodeFormat::Wav => "wav",
+            EncodeFormat::Lopus => "lopus",
+            EncodeFormat::Idsp => "idsp",
+        }
+    }
+}
+
+/// External commands that turn the processed WAV of a replacement into a .lopus or .idsp
+/// stream. `{input}` and `{output}` in the templates are replaced with the file paths.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct EncoderSettings {
+    pub command: String,
+    pub idsp_command: String,
+    /// Whether replacing a LOPUS or IDSP track offers encoding switched on
+    pub encode_by_default: bool,
+}
+
+impl Default for EncoderSettings {
+    fn default() -> Self {
+        Self {
+            command: DEFAULT_LOPUS_COMMAND.to_string(),
+            idsp_command: DEFAULT_IDSP_COMMAND.to_string(),
+            encode_by_default: true,
+        }
+    }
+}
+
+/// What the header of an encoded stream says about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamHeader {
+    pub channels: u8,
+    pub sample_rate: u32,
+}
+
+/// Codec a replacement of `info` has to be in for the game to play it, that of the entry
+/// it replaces. NUS3BANK tracks take WAV.
+pub fn target_format(info: &AudioFileInfo) -> EncodeFormat {
+    let filename = info.filename.to_lowercase();
+    if info.is_nus3bank {
+        EncodeFormat::Wav
+    } else if info.file_type == "OPUS" || filename.ends_with(".lopus") {
+        EncodeFormat::Lopus
+    } else if info.file_type == "IDSP" || filename.ends_with(".idsp") {
+        EncodeFormat::Idsp
+    } else {
+        EncodeFormat::Wav
+    }
+}
+
+/// Format a replacement of `info` is queued in unless the user picks another
+pub fn default_format(info: &AudioFileInfo, settings: &EncoderSettings) -> EncodeFormat {
+    if settings.encode_by_default { target_format(info) } else { EncodeFormat::Wav }
+}
+
+/// Check that `data` is a stream of `format` and read its channels and rate
+pub fn validate_stream(data: &[u8], format: EncodeFormat) -> Result<StreamHeader, String> {
+    match format {
+        EncodeFormat::Lopus => validate_header(data),
+        EncodeFormat::Idsp => validate_idsp_header(data),
+        EncodeFormat::Wav => Err("WAV is not encoded".to_string()),
+    }
+}
+
+/// Check that `data` is a Namco IDSP stream: big-endian channel count and sample rate
+/// follow the magic
+fn validate_idsp_header(data: &[u8]) -> Result<StreamHeader, String> {
+    if data.starts_with(b"RIFF") {
+        return Err("Encoder produced a WAV file, not IDSP".to_string());
+    }
+    if !data.starts_with(b"IDSP") {
+        return Err("Encoder output has no IDSP header".to_string());
+    }
+    let read_u32 = |at: usize| data.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let channels = read_u32(0x08).ok_or("IDSP header is truncated")?;
+    let sample_rate = read_u32(0x0c).ok_or("IDSP header is truncated")?;
+    if channels == 0 || channels > 8 {
+        return Err(format!("IDSP header has {} channels", channels));
+    }
+    if !(4000..=96000).contains(&sample_rate) {
+        return Err(format!("IDSP header has an invalid sample rate of {} Hz", sample_rate));
+    }
+    Ok(StreamHeader { channels: channels as u8, sample_rate })
+}
+
+/// Check that `data` is a Namco OPUS stream and read its format. Raw WAV, or anything an
+/// encoder wrote without the header the engine looks for, is refused.
+pub fn validate_header(data: &[u8]) -> Result<StreamHeader, String> {
+    if data.starts_with(b"RIFF") {
+        return Err("Encoder produced a WAV file, not LOPUS".to_string());
+    }
+    if !data.starts_with(b"OPUS") {
+        return Err("Encoder output has no OPUS header".to_string());
+    }
+    let read_u32 = |at: usize| data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let stream = (4..data.len().min(0x100))
+        .step_by(4)
+        .find(|&at| read_u32(at) == Some(SWITCH_OPUS_HEADER))
+        .ok_or("OPUS header has no Opus stream header")?;
+    let channels = *data.get(stream + 9).ok_or("Opus stream header is truncated")?;
+    let sample_rate = read_u32(stream + 0x0c).ok_or("Opus stream header is truncated")?;
+    if channels == 0 || channels > 8 {
+        return Err(format!("Opus stream header has {} channels", channels));
+    }
+    if !OPUS_RATES.contains(&sample_rate) {
+        return Err(format!("Opus stream header has an invalid sample rate of {} Hz", sample_rate));
+    }
+    Ok(StreamHeader { channels, sample_rate })
+}
+
+impl EncoderSettings {
+    /// These settings with the encoder arguments remembered for `bank_path` appended
+    pub fn for_bank(&self, bank_path: &str) -> Self {
+        let extra = bank_args::encoder_args(bank_path);
+        if extra.is_empty() {
+            return self.clone();
+        }
+        Self {
+            command: format!("{} {}", self.command, extra),
+            idsp_command: format!("{} {}", self.idsp_command, extra),
+            ..self.clone()
+        }
+    }
+
+    /// Program and arguments of the command for one input and output file
+    fn command_line(&self, format: EncodeFormat, input: &str, output: &str) -> Result<(String, Vec<String>), String> {
+        let command = match format {
+            EncodeFormat::Lopus => &self.command,
+            EncodeFormat::Idsp => &self.idsp_command,
+            EncodeFormat::Wav => return Err("WAV is not encoded".to_string()),
+        };
+        if !command.contains("{input}") || !command.contains("{output}") {
+            return Err("Encoder command must contain {input} and {output}".to_string());
+        }
+        let mut args = split_command(command)?
+            .into_iter()
+            .map(|arg| arg.replace("{input}", input).replace("{output}", output));
+        let program = args.next().ok_or("Encoder command is empty")?;
+        Ok((program, args.collect()))
+    }
+
+    /// Encode a WAV held in memory, returning the validated .lopus or .idsp bytes. When
+    /// vgmstream is available the result must also decode with it.
+    pub fn encode_wav(&self, wav: &[u8], format: EncodeFormat) -> Result<(Vec<u8>, StreamHeader), String> {
+        let temp_dir = path_utils::safe_temp_dir();
+        let stamp = format!(
+            "{}_{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default()
+        );
+        let input = temp_dir.join(format!("encode_in_{}.wav", stamp));
+        let output = temp_dir.join(format!("encode_out_{}.{}", stamp, format.extension()));
+        fs::write(&input, wav).map_err(|e| format!("Failed to write encoder input: {}", e))?;
+
+        let result = self.run_and_validate(format, &input, &output);
+        let _ = fs::remove_file(&input);
+        let _ = fs::remove_file(&output);
+        result
+    }
+
+    /// Encode the replacement just queued for `info` to `format`; WAV leaves it as it is.
+    /// If encoding fails the queued WAV is withdrawn and `previous`, the replacement
+    /// pending before, is put back.
+    pub fn encode_queued(
+        &self,
+        info: &AudioFileInfo,
+        format: EncodeFormat,
+        previous: Option<Vec<u8>>,
+    ) -> Result<AudioFileInfo, String> {
+        if format == EncodeFormat::Wav {
+            return Ok(info.clone());
+        }
+        let queued = ReplaceUtils::get_replacement_data_unified(info).ok_or("No queued replacement to encode")?;
+        let (encoded, entries) = tools::capture_log(|| self.encode_wav(&queued, format));
+        match encoded {
+            Ok((data, header)) => {
+                conversion_log::append(info.track_key(), entries);
+                println!(
+                    "Encoded {} to {}: {} channel(s), {} Hz, {} bytes",
+                    info.name, format.label(), header.channels, header.sample_rate, data.len()
+                );
+                let size = data.len();
+                ReplaceUtils::store_audio_data_for_playback(info.track_key(), data)?;
+                let mut encoded_info = AudioFileInfo { size, ..info.clone() };
+                if target_format(info) != format {
+                    // The entry now holds another codec than it was loaded with
+                    encoded_info.file_type = format.file_type().to_string();
+                    encoded_info.filename = Path::new(&info.filename)
+                        .with_extension(format.extension())
+                        .to_string_lossy()
+                        .into_owned();
+                }
+                Ok(encoded_info)
+            }
+            Err(e) => {
+                ReplaceUtils::restore_replacement_data(info, previous);
+                Err(format!("{} encoding failed, replacement not queued: {}", format.label(), e))
+            }
+        }
+    }
+
+    fn run_and_validate(&self, format: EncodeFormat, input: &Path, output: &Path) -> Result<(Vec<u8>, StreamHeader), String> {
+        let (program, args) = self.command_line(format, &input.to_string_lossy(), &output.to_string_lossy())?;
+        println!("Encoding {}: {} {}", format.label(), program, args.join(" "));
+        ExternalRunner::locate(&program)?.run(&args)?;
+
+        let data = fs::read(output).map_err(|_| "Encoder did not write the output file".to_string())?;
+        let header = validate_stream(&data, format)?;
+        if let Ok(vgmstream) = VgmstreamRunner::locate() {
+            vgmstream
+                .run(&["-m", &output.to_string_lossy()])
+                .map_err(|e| format!("Encoded {} does not decode: {}", format.label(), e))?;
+        }
+        Ok((data, header))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_opus_headers() {
+        let mut lopus = b"OPUS\0\0\0\0\0\0\0\0".to_vec();
+        lopus.extend_from_slice(&SWITCH_OPUS_HEADER.to_le_bytes());
+        lopus.extend_from_slice(&[0x18, 0, 0, 0, 0, 2, 0, 0]);
+        lopus.extend_from_slice(&48000u32.to_le_bytes());
+        lopus.extend_from_slice(&[0; 16]);
+        assert_eq!(validate_header(&lopus), Ok(StreamHeader { channels: 2, sample_rate: 48000 }));
+        assert!(validate_header(b"RIFF\0\0\0\0WAVE").is_err());
+        assert!(validate_header(&lopus[..20]).is_err());
+        lopus[12 + 0x0c] = 0x45;
+        assert!(validate_header(&lopus).is_err());
+
+        let mut idsp = b"IDSP\0\0\0\0".to_vec();
+        idsp.extend_from_slice(&1u32.to_be_bytes());
+        idsp.extend_from_slice(&32000u32.to_be_bytes());
+        assert_eq!(validate_stream(&idsp, EncodeFormat::Idsp), Ok(StreamHeader { channels: 1, sample_rate: 32000 }));
+        assert!(validate_stream(&idsp, EncodeFormat::Lopus).is_err());
+        assert!(validate_stream(&idsp[..12], EncodeFormat::Idsp).is_err());
+    }
+}
@@ -1,26 +1,124 @@
-use egui::Color32;
-use std::collections::HashSet;
+use egui::{Color32, Context};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::Instant;
 
 use super::{
-    add_audio_modal::AddAudioModal, audio_file_info::AudioFileInfo, confirm_modal::ConfirmModal,
+    add_audio_modal::AddAudioModal, audio_file_info::AudioFileInfo, blind_test_modal::BlindTestModal,
+    audit_log_modal::AuditLogModal,
+    bank_args::{self, BankArgOverrides},
+    bank_args_modal::BankArgsModal,
+    backups::BackupSettings,
+    backups_modal::BackupsModal,
+    bulk_add_modal::BulkAddModal,
+    color_labels::{ColorLabel, ColorLabels},
+    locked_tracks::LockedTracks,
+    track_naming::NamingRule,
+    confirm_modal::ConfirmModal,
     dton_tones_modal::DtonTonesModal,
+    export_all_modal::ExportAllModal,
+    export_progress_modal::ExportProgressModal,
+    manifest_import_modal::ManifestImportModal,
+    export_format::ExportFormatSettings,
+    file_tabs::{CopiedTrack, FileTabs},
     grp_list_modal::GrpListModal,
+    intro_loop_modal::IntroLoopModal,
+    lopus_encoder::EncoderSettings,
+    memory_footprint::{self, Footprint, MemoryBudget, TrackPayload},
+    memory_modal::MemoryModal,
+    shortcuts::ShortcutSettings,
+    shortcuts_modal::ShortcutsModal,
+    track_categories::TrackCategories,
+    track_categories_modal::TrackCategoriesModal,
+    nus3audio_layout_modal::Nus3audioLayoutModal,
+    tone_id_modal::ToneIdModal,
+    duplicate_modal::DuplicateModal,
+    bank_loader::BankLoader,
+    dual_pane::DualPane,
+    empty_tracks::EmptyKind,
+    quick_filters::QuickFilter,
+    track_format::{MetadataColumn, TrackFormat},
+    mix_modal::MixModal,
+    normalize_all_modal::NormalizeAllModal,
+    post_save_hook::PostSaveHook,
+    decode_pool,
     loop_settings_modal::LoopSettingsModal, 
+    notification_center::NotificationCenter,
+    pad_grid::PadGrid,
+    url_import::UrlImportModal,
     prop_edit_modal::PropEditModal,
+    replace_utils::ReplaceUtils,
+    save_summary_modal::SaveSummaryModal,
     search_column::SearchColumn, sort_column::SortColumn,
-    toast_message::ToastMessage,
+    snapshots_modal::SnapshotsModal,
+    stats_modal::StatsModal,
+    verify_modal::VerifyModal,
+    review_modal::ReviewModal,
+    toast_message::{self, ToastMessage},
+    transcript_modal::TranscriptModal,
+    transcripts::Transcripts,
 };
-use crate::ui::audio_player::{AudioPlayer, AudioPlayerSettings};
+use crate::labels::LabelFile;
+use crate::tools::VgmstreamRunner;
+use crate::track_key::TrackKey;
+use crate::ui::audio_player::{AudioPlayer, AudioPlayerSettings, LoopMode};
 
 /// Main editing area component
 #[derive(serde::Deserialize, serde::Serialize)]
 pub struct MainArea {
     #[serde(skip)]
     pub selected_file: Option<String>,
+    // Files open in tabs; the active one is selected_file
+    #[serde(skip)]
+    pub file_tabs: FileTabs,
+    // Tab whose close button was clicked once while it had pending edits
+    #[serde(skip)]
+    pub tab_close_confirm: Option<usize>,
+    // Track copied from one tab to paste into another
+    #[serde(skip)]
+    pub copied_track: Option<CopiedTrack>,
+    // Pending replacements whose source file was re-exported after it was queued
+    #[serde(skip)]
+    pub stale_sources: Vec<(TrackKey, PathBuf)>,
+    #[serde(skip)]
+    pub last_source_check: Option<Instant>,
     #[serde(skip)]
     pub file_count: Option<usize>,
     #[serde(skip)]
     pub audio_files: Option<Vec<AudioFileInfo>>,
+    // Companion label file found next to the opened bank
+    #[serde(skip)]
+    pub track_labels: Option<LabelFile>,
+    // What is said in the voice cues of the opened bank, from its transcript sidecar
+    #[serde(skip)]
+    pub transcripts: Transcripts,
+    // Tracks of the opened file whose payload is a stub or all silence
+    #[serde(skip)]
+    pub empty_tracks: HashMap<TrackKey, EmptyKind>,
+    // Show only the tracks in `empty_tracks`
+    #[serde(skip)]
+    pub empty_filter: bool,
+    // Quick filter chips turned on above the table
+    #[serde(skip)]
+    pub quick_filters: BTreeSet<QuickFilter>,
+    // Tracks of the opened file whose payload has loop points
+    #[serde(skip)]
+    pub looped_tracks: HashSet<TrackKey>,
+    // Formats read from the payload headers of the opened file, by track
+    #[serde(skip)]
+    pub track_formats: HashMap<TrackKey, TrackFormat>,
+    // Tone names of the opened NUS3BANK by the slot they were read at
+    #[serde(skip)]
+    pub tone_names: Vec<String>,
+    // Optional metadata columns shown after Type
+    #[serde(default)]
+    pub metadata_columns: Vec<MetadataColumn>,
+    // Large bank being parsed on a worker thread
+    #[serde(skip)]
+    pub bank_loader: Option<BankLoader>,
+    // Second bank shown beside the table to port tracks from
+    #[serde(skip)]
+    pub dual_pane: DualPane,
     #[serde(skip)]
     pub error_message: Option<String>,
     // Table configuration
@@ -39,6 +137,18 @@ pub struct MainArea {
     pub search_query: String,
     pub search_column: SearchColumn,
     pub show_advanced_search: bool,
+    // Color tags of table rows, per bank
+    #[serde(default)]
+    pub color_labels: ColorLabels,
+    // Show only rows with this color label
+    #[serde(default)]
+    pub label_filter: Option<ColorLabel>,
+    // Rows protected from batch operations, per bank
+    #[serde(default)]
+    pub locked_tracks: LockedTracks,
+    // How added tracks are named
+    #[serde(default)]
+    pub naming_rule: NamingRule,
     // Sorting functionality
     pub sort_column: SortColumn,
     pub sort_ascending: bool,
@@ -49,9 +159,54 @@ pub struct MainArea {
     pub audio_settings: AudioPlayerSettings,
     // Output path configuration
     pub output_path: Option<String>,
+    // Write BWF/INFO chunks (bank, ID, codec, export time) into exported WAVs
+    #[serde(default = "default_true")]
+    pub embed_export_metadata: bool,
+    // Write a <name>.timestamps.txt with the cue length in 60 fps frames next to each export
+    #[serde(default)]
+    pub export_lip_sync_timestamps: bool,
+    // Format exports are encoded to after decoding, with the settings of each encoder
+    #[serde(default)]
+    pub export_format: ExportFormatSettings,
+    // Queue PCM16 conversions of legacy WAV payloads when a .nus3bank is opened
+    #[serde(default)]
+    pub auto_normalize_on_open: bool,
+    // External encoder chain for replacements of LOPUS tracks in .nus3audio files
+    #[serde(default)]
+    pub lopus_encoder: EncoderSettings,
+    // Command run after every successful save, set up in Settings
+    #[serde(default)]
+    pub post_save_hook: PostSaveHook,
+    // Extra vgmstream-cli and encoder arguments per bank path, for banks that need other flags
+    #[serde(default)]
+    pub bank_arg_overrides: HashMap<String, BankArgOverrides>,
+    // Settings of the runtime memory estimate
+    #[serde(default)]
+    pub memory_budget: MemoryBudget,
+    // Retention of the timestamped backups written by in-place saves
+    #[serde(default)]
+    pub backup_settings: BackupSettings,
+    // Length of the silence written by Replace with Empty
+    #[serde(default = "default_empty_wav_min_ms")]
+    pub empty_wav_min_ms: u32,
+    // vgmstream-cli chosen in Settings; discovered automatically when None
+    #[serde(default)]
+    pub vgmstream_path: Option<String>,
+    // Threads of the decode pool; 0 picks one fewer than the core count
+    #[serde(default)]
+    pub decode_threads: usize,
+    // Key bindings edited in Settings > Keyboard Shortcuts
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+    // Name patterns behind the category icons, edited in Settings > Track Icons
+    #[serde(default)]
+    pub track_categories: TrackCategories,
     // Toast notifications
     #[serde(skip)]
     pub(crate) toast_messages: Vec<ToastMessage>,
+    // History of every toast, opened from summary toasts and the More menu
+    #[serde(skip)]
+    pub notification_center: NotificationCenter,
 
     // Loop settings modal window
     #[serde(skip)]
@@ -61,6 +216,10 @@ pub struct MainArea {
     #[serde(skip)]
     pub add_audio_modal: AddAudioModal,
 
+    // Names and IDs for several audio files dropped onto the table
+    #[serde(skip)]
+    pub bulk_add_modal: BulkAddModal,
+
     // Confirm dialog modal window
     #[serde(skip)]
     pub confirm_modal: ConfirmModal,
@@ -77,13 +236,125 @@ pub struct MainArea {
     #[serde(skip)]
     pub prop_edit_modal: PropEditModal,
 
+    // Save summary modal window (shown before any save writes)
+    #[serde(skip)]
+    pub save_summary_modal: SaveSummaryModal,
+
+    // A/B blind test modal window
+    #[serde(skip)]
+    pub blind_test_modal: BlindTestModal,
+
+    // Trigger pads for auditioning sound effects
+    #[serde(skip)]
+    pub pad_grid: PadGrid,
+
+    // Download replacement or new audio from a URL
+    #[serde(skip)]
+    pub url_import_modal: UrlImportModal,
+
+    // Bank statistics window
+    #[serde(skip)]
+    pub stats_modal: StatsModal,
+
+    // Intro/loop track pairs with combined preview and joint replace
+    #[serde(skip)]
+    pub intro_loop_modal: IntroLoopModal,
+
+    // Editor for the conversion arguments of the open bank
+    #[serde(skip)]
+    pub bank_args_modal: BankArgsModal,
+
+    // Named snapshots of the pending edits, kept for the session
+    #[serde(skip)]
+    pub snapshots_modal: SnapshotsModal,
+
+    // Log of the edit actions of the session
+    #[serde(skip)]
+    pub audit_log_modal: AuditLogModal,
+
+    // Estimated runtime memory of the open bank
+    #[serde(skip)]
+    pub memory_modal: MemoryModal,
+
+    // Lists and rebinds the keyboard shortcuts
+    #[serde(skip)]
+    pub shortcuts_modal: ShortcutsModal,
+
+    // Edits the category icon rules
+    #[serde(skip)]
+    pub track_categories_modal: TrackCategoriesModal,
+
+    // Sections, string table and entry order of the open .nus3audio
+    #[serde(skip)]
+    pub nus3audio_layout_modal: Nus3audioLayoutModal,
+
+    // Asks for the name of a duplicated track
+    #[serde(skip)]
+    pub duplicate_modal: DuplicateModal,
+
+    // Reassigns the ID of a NUS3BANK track
+    #[serde(skip)]
+    pub tone_id_modal: ToneIdModal,
+
+    // Result of the last round-trip check of the open bank
+    #[serde(skip)]
+    pub verify_modal: VerifyModal,
+
+    // Timestamped backups of the open file
+    #[serde(skip)]
+    pub backups_modal: BackupsModal,
+
+    // Batch listen queue with keyboard grading; plays audio itself so it does not duck
+    #[serde(skip)]
+    pub review_modal: ReviewModal,
+
+    // Transcript editor of a voice track; does not duck the preview so the cue can be heard
+    #[serde(skip)]
+    pub transcript_modal: TranscriptModal,
+
+    // Per-track gain, normalize, fades and downmix
+    #[serde(skip)]
+    pub mix_modal: MixModal,
+
+    // Loudness analysis of the whole bank, gaining every WAV track to one target
+    #[serde(skip)]
+    pub normalize_all_modal: NormalizeAllModal,
+
+    // Player loop mode to restore once the loop settings preview stops
+    #[serde(skip)]
+    pub loop_preview_mode: Option<LoopMode>,
+
+    // Track to scroll into view on the next frame
+    #[serde(skip)]
+    pub scroll_to_key: Option<TrackKey>,
+
+    // Also search the other open tabs and list their matches by file
+    #[serde(default)]
+    pub search_all_tabs: bool,
+
+    // Track picked in the workspace results, revealed once its file has loaded
+    #[serde(skip)]
+    pub pending_reveal: Option<(String, AudioFileInfo)>,
+
+    // Rows to move the row selection by on the next frame, from the arrow shortcuts
+    #[serde(skip)]
+    pub pending_row_step: Option<isize>,
+
     // Pending remove action data
     #[serde(skip)]
     pub pending_remove_audio: Option<AudioFileInfo>,
 
-    // Pending export all action flag
+    // Export All confirm dialog with scope choice
+    #[serde(skip)]
+    pub export_all_modal: ExportAllModal,
+
+    // Progress and summary of a running Export All
     #[serde(skip)]
-    pub pending_export_all: bool,
+    pub export_progress_modal: ExportProgressModal,
+
+    // Progress and report of a replacement manifest import
+    #[serde(skip)]
+    pub manifest_import_modal: ManifestImportModal,
 
     // Pending replace-with-empty-wav action flag
     #[serde(skip)]
@@ -106,6 +377,14 @@ pub struct MainArea {
     pub pending_debug_convert_all_wav: bool,
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_empty_wav_min_ms() -> u32 {
+    super::empty_wav::DEFAULT_MIN_SILENCE_MS
+}
+
 impl Default for MainArea {
     fn default() -> Self {
         Self::new()
@@ -119,8 +398,24 @@ impl MainArea {
 
         Self {
             selected_file: None,
+            file_tabs: FileTabs::default(),
+            tab_close_confirm: None,
+            copied_track: None,
+            stale_sources: Vec::new(),
+            last_source_check: None,
             file_count: None,
             audio_files: None,
+            track_labels: None,
+            transcripts: Transcripts::default(),
+            empty_tracks: HashMap::new(),
+            empty_filter: false,
+            quick_filters: BTreeSet::new(),
+            looped_tracks: HashSet::new(),
+            track_formats: HashMap::new(),
+            tone_names: Vec::new(),
+            metadata_columns: Vec::new(),
+            bank_loader: None,
+            dual_pane: DualPane::new(),
             error_message: None,
             // Set default table style
             striped: true,
@@ -133,6 +428,10 @@ impl MainArea {
             search_query: String::new(),
             search_column: SearchColumn::All,
             show_advanced_search: false,
+            color_labels: ColorLabels::default(),
+            locked_tracks: LockedTracks::default(),
+            naming_rule: NamingRule::default(),
+            label_filter: None,
             // Initialize with no sorting
             sort_column: SortColumn::None,
             sort_ascending: true,
@@ -141,14 +440,30 @@ impl MainArea {
             audio_settings: AudioPlayerSettings::default(),
             // Initialize output path as None
             output_path: None,
+            embed_export_metadata: true,
+            export_lip_sync_timestamps: false,
+            auto_normalize_on_open: false,
+            export_format: ExportFormatSettings::default(),
+            lopus_encoder: EncoderSettings::default(),
+            post_save_hook: PostSaveHook::default(),
+            bank_arg_overrides: HashMap::new(),
+            memory_budget: MemoryBudget::default(),
+            backup_settings: BackupSettings::default(),
+            empty_wav_min_ms: default_empty_wav_min_ms(),
+            vgmstream_path: None,
+            decode_threads: 0,
+            shortcuts: ShortcutSettings::default(),
+            track_categories: TrackCategories::default(),
             // Initialize toast messages
             toast_messages: Vec::new(),
+            notification_center: NotificationCenter::new(),
 
             // Initialize loop settings modal
             loop_settings_modal: LoopSettingsModal::new(),
 
             // Initialize add audio modal
             add_audio_modal: AddAudioModal::new(),
+            bulk_add_modal: BulkAddModal::new(),
 
             // Initialize confirm modal
             confirm_modal: ConfirmModal::new(),
@@ -162,11 +477,40 @@ impl MainArea {
             // Initialize PROP edit modal
             prop_edit_modal: PropEditModal::new(),
 
+            // Initialize save summary modal
+            save_summary_modal: SaveSummaryModal::new(),
+            blind_test_modal: BlindTestModal::new(),
+            pad_grid: PadGrid::new(),
+            url_import_modal: UrlImportModal::new(),
+            stats_modal: StatsModal::new(),
+            intro_loop_modal: IntroLoopModal::new(),
+            bank_args_modal: BankArgsModal::new(),
+            snapshots_modal: SnapshotsModal::new(),
+            audit_log_modal: AuditLogModal::new(),
+            memory_modal: MemoryModal::new(),
+            shortcuts_modal: ShortcutsModal::new(),
+            track_categories_modal: TrackCategoriesModal::new(),
+            nus3audio_layout_modal: Nus3audioLayoutModal::new(),
+            tone_id_modal: ToneIdModal::new(),
+            duplicate_modal: DuplicateModal::new(),
+            verify_modal: VerifyModal::new(),
+            backups_modal: BackupsModal::new(),
+            transcript_modal: TranscriptModal::new(),
+            mix_modal: MixModal::new(),
+            normalize_all_modal: NormalizeAllModal::new(),
+            review_modal: ReviewModal::new(),
+            loop_preview_mode: None,
+            scroll_to_key: None,
+            search_all_tabs: false,
+            pending_reveal: None,
+            pending_row_step: None,
+
             // Initialize pending remove audio
             pending_remove_audio: None,
 
-            // Initialize pending export all
-            pending_export_all: false,
+            export_all_modal: ExportAllModal::new(),
+            export_progress_modal: ExportProgressModal::new(),
+            manifest_import_modal: ManifestImportModal::new(),
 
             // Initialize pending replace with empty wav
             pending_replace_empty: false,
@@ -183,10 +527,11 @@ impl MainArea {
         }
     }
 
-    /// Add a toast notification
+    /// Add a toast notification. Toasts of one color arriving in quick succession are
+    /// merged into a summary that opens the notification center.
     pub fn add_toast(&mut self, message: String, color: Color32) {
-        let toast = ToastMessage::new(message, color, 3); // Display for 3 seconds
-        self.toast_messages.push(toast);
+        self.notification_center.record(&message, color);
+        toast_message::push_toast(&mut self.toast_messages, message, color, 3); // Display for 3 seconds
     }
 
     /// Ensure that the audio player is initialized
@@ -207,6 +552,136 @@ impl MainArea {
         }
     }
 
+    /// Play a listed track from the file on disk, ignoring its pending changes. Used to hear
+    /// a track marked for removal one last time before saving.
+    pub fn audition_original(&mut self, name: &str, id: &str) -> Result<(), String> {
+        let info = self
+            .audio_files
+            .as_ref()
+            .and_then(|files| {
+                files
+                    .iter()
+                    .find(|f| f.name == name && (f.id == id || f.hex_id.as_deref() == Some(id)))
+            })
+            .cloned()
+            .ok_or_else(|| format!("'{}' is not in the open file", name))?;
+        let path = self.selected_file.clone().ok_or("No file is open")?;
+        let audio_player = self.audio_player.as_mut().ok_or("Audio player is not ready")?;
+        audio_player.load_original(&info, &path)?;
+        let state = audio_player.get_audio_state();
+        let mut state = state.lock().unwrap();
+        if !state.is_playing {
+            state.toggle_play();
+        }
+        Ok(())
+    }
+
+    /// Whether a modal dialog that should quiet the preview is open.
+    /// The blind test and review windows are excluded because they play audio themselves,
+    /// as is the save summary while it auditions a removed track.
+    pub fn is_modal_dialog_open(&self) -> bool {
+        self.loop_settings_modal.open
+            || self.add_audio_modal.open
+            || self.bulk_add_modal.open
+            || self.confirm_modal.open
+            || self.export_all_modal.open
+            || self.export_progress_modal.open
+            || self.manifest_import_modal.open
+            || self.grp_list_modal.open
+            || self.dton_tones_modal.open
+            || self.prop_edit_modal.open
+            || (self.save_summary_modal.open && !self.save_summary_modal.auditioning)
+            || self.url_import_modal.open
+            || self.bank_args_modal.open
+            || self.snapshots_modal.open
+            || self.backups_modal.open
+    }
+
+    /// Estimated runtime memory of the listed tracks, pending replacements and additions
+    /// counted with the codec of their new payload
+    pub fn memory_footprint(&self) -> Option<Footprint> {
+        let files = self.audio_files.as_ref()?;
+        let pending = ReplaceUtils::replacement_codecs();
+        let tracks = files.iter().map(|file| {
+            let codec = pending.get(&file.track_key()).or_else(|| pending.get(&file.added_track_key()));
+            TrackPayload {
+                codec: codec.unwrap_or(&file.file_type),
+                size: file.size,
+                pending: codec.is_some(),
+            }
+        });
+        Some(memory_footprint::estimate(tracks, &self.memory_budget))
+    }
+
+    /// Tell the audio player whether a dialog is open so it can duck or pause playback
+    pub fn update_dialog_ducking(&mut self) {
+        let dialog_open = self.is_modal_dialog_open();
+        if let Some(audio_player) = &self.audio_player {
+            let audio_state = audio_player.get_audio_state();
+            let mut state = audio_state.lock().unwrap();
+            state.set_dialog_open(dialog_open);
+        }
+    }
+
+    /// Set (or clear) the vgmstream-cli location and make every tool call use it
+    pub fn set_vgmstream_path(&mut self, path: Option<String>) {
+        VgmstreamRunner::set_configured_path(path.as_ref().map(PathBuf::from));
+        self.vgmstream_path = path;
+    }
+
+    /// Set the decode pool size; 0 for automatic
+    pub fn set_decode_threads(&mut self, threads: usize) {
+        decode_pool::set_threads(threads);
+        self.decode_threads = threads;
+    }
+
+    /// Remember (or, with default overrides, forget) the conversion arguments of a bank and
+    /// make every conversion of it use them
+    pub fn set_bank_arg_overrides(&mut self, bank_path: &str, overrides: BankArgOverrides) {
+        if overrides.is_default() {
+            self.bank_arg_overrides.remove(bank_path);
+        } else {
+            self.bank_arg_overrides.insert(bank_path.to_string(), overrides);
+        }
+        bank_args::set_all(&self.bank_arg_overrides);
+    }
+
+    /// Hand the persisted overrides to the conversion code, after loading settings
+    pub fn sync_bank_arg_overrides(&self) {
+        bank_args::set_all(&self.bank_arg_overrides);
+    }
+
+    /// Whether shortcuts without Ctrl or Alt may fire: not while typing or with a dialog open
+    pub fn plain_shortcuts_allowed(&self, ctx: &Context) -> bool {
+        !ctx.wants_keyboard_input()
+            && !self.is_modal_dialog_open()
+            && !self.review_modal.open
+            && !self.shortcuts_modal.open
+    }
+
+    /// Start or pause the preview of the loaded track
+    pub fn toggle_playback(&mut self) {
+        if let Some(audio_player) = &self.audio_player {
+            let audio_state = audio_player.get_audio_state();
+            let mut state = audio_state.lock().unwrap();
+            if state.current_audio.is_some() {
+                state.toggle_play();
+            }
+        }
+    }
+
+    /// Select a track and scroll the table to it, clearing a search that would hide it
+    pub fn reveal_track(&mut self, info: &AudioFileInfo) {
+        self.search_query.clear();
+        self.label_filter = None;
+        self.empty_filter = false;
+        self.quick_filters.clear();
+        self.selected_rows.clear();
+        self.selected_items.clear();
+        self.selected_items.insert(format!("{}:{}", info.name, info.id));
+        self.scroll_to_key = Some(info.track_key());
+    }
+
     /// Persist current audio settings into state
     pub fn sync_audio_settings_from_player(&mut self) {
         if let Some(audio_player) = &self.audio_player {
@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use egui::{Button, Color32, RichText, ScrollArea, Ui};
+use egui_phosphor::regular;
+
+use super::{
+    audio_file_info::AudioFileInfo,
+    dual_pane::{self, PairBy},
+    main_area_core::MainArea,
+    replace_utils::ReplaceUtils,
+};
+
+impl MainArea {
+    /// Render the side pane with a second bank to port tracks from. It follows the search of
+    /// the main table.
+    pub fn render_dual_pane(&mut self, ui: &mut Ui) {
+        let mut play = None;
+        let mut toggle = None;
+        let mut port = false;
+
+        ui.horizontal(|ui| {
+            let title = self
+                .dual_pane
+                .path()
+                .and_then(|p| Path::new(p).file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "No bank".to_string());
+            ui.label(RichText::new(title).strong())
+                .on_hover_text(self.dual_pane.path().unwrap_or("Open a bank to port tracks from"));
+            if ui
+                .small_button(regular::FOLDER_OPEN.to_string())
+                .on_hover_text("Open a bank in this pane")
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Audio Banks", &["nus3audio", "nus3bank"])
+                    .pick_file()
+                {
+                    self.dual_pane.load(&path.to_string_lossy());
+                }
+            }
+            let others: Vec<String> = self
+                .file_tabs
+                .tabs()
+                .iter()
+                .map(|t| t.path.clone())
+                .filter(|p| self.selected_file.as_deref() != Some(p.as_str()))
+                .collect();
+            ui.add_enabled_ui(!others.is_empty(), |ui| {
+                ui.menu_button("Tabs", |ui| {
+                    for path in others {
+                        let name = Path::new(&path).file_name().unwrap_or_default().to_string_lossy().to_string();
+                        if ui.button(name).on_hover_text(&path).clicked() {
+                            self.dual_pane.load(&path);
+                            ui.close();
+                        }
+                    }
+                })
+                .response
+                .on_hover_text("Show another open tab, as saved on disk");
+            });
+        });
+        if let Some(error) = self.dual_pane.error() {
+            ui.colored_label(Color32::from_rgb(255, 100, 100), error);
+        }
+
+        let selected = self.dual_pane.selected_tracks().len();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("dual_pane_pair_by")
+                .selected_text(self.dual_pane.pair_by.name())
+                .width(120.0)
+                .show_ui(ui, |ui| {
+                    for pair_by in [PairBy::Name, PairBy::SelectionOrder] {
+                        ui.selectable_value(&mut self.dual_pane.pair_by, pair_by, pair_by.name());
+                    }
+                })
+                .response
+                .on_hover_text("Which track of the open bank each copied track replaces");
+            if ui
+                .add_enabled(selected > 0, Button::new(format!("Copy {} selected {}", selected, regular::ARROW_RIGHT)))
+                .on_hover_text("Queue the selected tracks as replacements in the open bank, loops included")
+                .clicked()
+            {
+                port = true;
+            }
+        });
+        ui.separator();
+
+        let query = self.search_query.to_lowercase();
+        ScrollArea::vertical().id_salt("dual_pane_tracks").show(ui, |ui| {
+            for track in self.dual_pane.visible(&query) {
+                ui.horizontal(|ui| {
+                    if ui.small_button(regular::PLAY.to_string()).on_hover_text("Play").clicked() {
+                        play = Some(track.clone());
+                    }
+                    let selected = self.dual_pane.is_selected(track);
+                    if ui.selectable_label(selected, &track.name).clicked() {
+                        toggle = Some(track.clone());
+                    }
+                    ui.label(RichText::new(&track.file_type).weak().size(11.0));
+                });
+            }
+        });
+        if let Some(track) = toggle {
+            self.dual_pane.toggle(&track);
+        }
+
+        if let Some(track) = play {
+            if let Err(e) = self.play_pane_track(&track) {
+                self.add_toast(format!("Failed to play {}: {}", track.name, e), Color32::RED);
+            }
+        }
+        if port {
+            self.port_from_pane();
+        }
+    }
+
+    fn play_pane_track(&mut self, info: &AudioFileInfo) -> Result<(), String> {
+        let path = self.dual_pane.path().ok_or("No bank in the pane")?.to_string();
+        let audio_player = self.audio_player.as_mut().ok_or("Audio player is not ready")?;
+        audio_player.load_original(info, &path)?;
+        let state = audio_player.get_audio_state();
+        let mut state = state.lock().unwrap();
+        if !state.is_playing {
+            state.toggle_play();
+        }
+        Ok(())
+    }
+
+    /// Replace tracks of the open bank with the tracks selected in the pane
+    pub fn port_from_pane(&mut self) {
+        let Some(source_bank) = self.dual_pane.path().map(str::to_string) else {
+            return;
+        };
+        if self.selected_file.as_deref() == Some(source_bank.as_str()) {
+            self.add_toast("The pane shows the open bank itself".to_string(), Color32::GOLD);
+            return;
+        }
+        let targets = self.audio_files.clone().unwrap_or_default();
+        let selected_targets: Vec<AudioFileInfo> = targets
+            .iter()
+            .filter(|f| self.selected_items.contains(&format!("{}:{}", f.name, f.id)))
+            .cloned()
+            .collect();
+        let (pairs, unmatched) = dual_pane::pair_tracks(
+            &self.dual_pane.selected_tracks(),
+            &targets,
+            &selected_targets,
+            self.dual_pane.pair_by,
+        );
+
+        let (pairs, locked): (Vec<_>, Vec<_>) = pairs.into_iter().partition(|(_, target)| !self.is_locked(target));
+        if !locked.is_empty() {
+            self.add_toast(format!("Skipped {} locked track(s)", locked.len()), Color32::GOLD);
+        }
+
+        let mut ported = 0;
+        for (source, target) in pairs {
+            match ReplaceUtils::port_track(&source, &source_bank, &target) {
+                Ok(new_info) => {
+                    if let Some(listed) = self
+                        .audio_files
+                        .iter_mut()
+                        .flatten()
+                        .find(|f| f.name == target.name && f.id == target.id)
+                    {
+                        *listed = new_info;
+                    }
+                    ported += 1;
+                }
+                Err(e) => self.add_toast(format!("Failed to copy {}: {}", source.name, e), Color32::RED),
+            }
+        }
+        if ported > 0 {
+            self.add_toast(format!("Copied {} track(s) into the open bank", ported), Color32::GREEN);
+            self.dual_pane.clear_selection();
+        }
+        if !unmatched.is_empty() {
+            let hint = match self.dual_pane.pair_by {
+                PairBy::Name => "no track of that name",
+                PairBy::SelectionOrder => "select as many tracks in the table",
+            };
+            self.add_toast(
+                format!("{} track(s) not copied ({}): {}", unmatched.len(), hint, unmatched.join(", ")),
+                Color32::GOLD,
+            );
+        }
+    }
+}
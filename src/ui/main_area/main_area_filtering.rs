@@ -2,11 +2,20 @@ use nus3audio::Nus3audioFile;
 use super::{
     main_area_core::MainArea,
     audio_file_info::AudioFileInfo,
-    search_column::SearchColumn,
+    audit_log,
+    bank_loader::{self, BankLoader},
+    empty_tracks,
+    file_types::FileTypeRegistry,
+    quick_filters::{self, QuickFilter},
+    track_format::{self, MetadataColumn, TrackFormat},
+    search_column::{DurationQuery, SearchColumn},
     sort_column::SortColumn,
+    transcripts::Transcripts,
     replace_utils::ReplaceUtils
 };
-use crate::nus3bank::Nus3bankFile;
+use crate::labels::LabelFile;
+use crate::track_key::TrackKey;
+use std::path::Path;
 
 impl MainArea {
     /// Get filtered audio files based on search query and column, then sort them
@@ -28,19 +37,45 @@ impl MainArea {
                                 file.id.to_lowercase().contains(&query) ||
                                 self.size_matches(file.size, &query) ||
                                 file.filename.to_lowercase().contains(&query) ||
-                                file.file_type.to_lowercase().contains(&query)
+                                file.file_type.to_lowercase().contains(&query) ||
+                                (DurationQuery::is_comparison(&query) && self.duration_matches(file, &query)) ||
+                                self.empty_matches(file, &query) ||
+                                self.track_label(file).is_some_and(|label| label.to_lowercase().contains(&query)) ||
+                                self.transcripts.matches(&file.track_key(), &query)
                             },
                             SearchColumn::Name => file.name.to_lowercase().contains(&query),
                             SearchColumn::Id => file.id.to_lowercase().contains(&query),
                             SearchColumn::Size => self.size_matches(file.size, &query),
                             SearchColumn::Filename => file.filename.to_lowercase().contains(&query),
-                            SearchColumn::Type => file.file_type.to_lowercase().contains(&query),
+                            SearchColumn::Type => {
+                                file.file_type.to_lowercase().contains(&query) || self.empty_matches(file, &query)
+                            }
+                            SearchColumn::Duration => self.duration_matches(file, &query),
+                            SearchColumn::Transcript => {
+                                self.transcripts.matches(&file.track_key(), &query)
+                            }
                         }
                     })
                     .cloned()
                     .collect()
             };
             
+            // Keep only rows with the chosen color label
+            if let (Some(label), Some(bank)) = (self.label_filter, &self.selected_file) {
+                filtered_files.retain(|file| {
+                    self.color_labels.get(bank, &file.track_key()) == Some(label)
+                });
+            }
+
+            if self.empty_filter {
+                filtered_files.retain(|file| self.empty_tracks.contains_key(&file.track_key()));
+            }
+
+            if !self.quick_filters.is_empty() {
+                let modified: Vec<_> = ReplaceUtils::get_replacement_sizes().into_iter().map(|(key, _)| key).collect();
+                filtered_files.retain(|file| self.quick_filters_match(file, &modified));
+            }
+
             // Then sort the filtered files based on sort column and direction
             if self.sort_column != SortColumn::None {
                 filtered_files.sort_by(|a, b| {
@@ -64,6 +99,18 @@ impl MainArea {
                         SortColumn::Size => a.size.cmp(&b.size),
                         SortColumn::Filename => a.filename.to_lowercase().cmp(&b.filename.to_lowercase()),
                         SortColumn::Type => a.file_type.to_lowercase().cmp(&b.file_type.to_lowercase()),
+                        SortColumn::SampleRate
+                        | SortColumn::Channels
+                        | SortColumn::BitDepth
+                        | SortColumn::Duration
+                        | SortColumn::Entropy
+                        | SortColumn::WavRatio => {
+                            let column = MetadataColumn::of_sort_column(self.sort_column);
+                            let key = |f: &AudioFileInfo| {
+                                column.map_or(0.0, |c| c.sort_key(self.track_format(f)))
+                            };
+                            key(a).total_cmp(&key(b))
+                        }
                         SortColumn::None => std::cmp::Ordering::Equal,
                     };
                     
@@ -97,15 +144,99 @@ impl MainArea {
         size_raw.contains(query)
     }
 
+    /// Whether a track's header duration passes a query like ">30s", or shows the query text
+    fn duration_matches(&self, file: &AudioFileInfo, query: &str) -> bool {
+        let format = self.track_format(file);
+        let Some(secs) = format.and_then(|f| f.duration_secs) else {
+            return false;
+        };
+        match DurationQuery::parse(query) {
+            Some(duration) => duration.matches(secs),
+            None => MetadataColumn::Duration.cell(format).contains(query),
+        }
+    }
+
+    /// Whether `query` finds the Empty badge of a track
+    fn empty_matches(&self, file: &AudioFileInfo, query: &str) -> bool {
+        "empty".contains(query) && self.empty_tracks.contains_key(&file.track_key())
+    }
+
     /// Update the selected file and load NUS3AUDIO info if applicable
     pub fn update_selected_file(&mut self, file_path: Option<String>) {
+        self.load_selected_file(file_path, false);
+    }
+
+    /// Like `update_selected_file`, but a large NUS3BANK is parsed on a worker thread while
+    /// the table shows progress; its rows arrive in `poll_bank_loader`
+    pub fn update_selected_file_in_background(&mut self, file_path: Option<String>) {
+        self.load_selected_file(file_path, true);
+    }
+
+    /// Take the rows of a background load once it has finished
+    pub fn poll_bank_loader(&mut self) {
+        let Some(result) = self.bank_loader.as_ref().and_then(BankLoader::poll) else {
+            return;
+        };
+        self.bank_loader = None;
+        match result {
+            Ok(bank) => {
+                self.empty_tracks = bank.empty_tracks;
+                self.looped_tracks = bank.looped;
+                self.track_formats = bank.formats;
+                self.tone_names = bank.tone_names;
+                // A tab switched back to keeps the rows it was parked with
+                if self.audio_files.is_none() {
+                    self.file_count = Some(bank.audio_files.len());
+                    self.audio_files = Some(bank.audio_files);
+                    self.auto_normalize_on_open();
+                }
+            }
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    /// Whether a row passes the quick filter chips: any chosen category, and every other chip
+    fn quick_filters_match(&self, file: &AudioFileInfo, modified: &[TrackKey]) -> bool {
+        let categories: Vec<QuickFilter> = self.quick_filters.iter().copied().filter(QuickFilter::is_category).collect();
+        if !categories.is_empty() && !quick_filters::name_category(&file.name).is_some_and(|c| categories.contains(&c)) {
+            return false;
+        }
+        self.quick_filters.iter().filter(|f| !f.is_category()).all(|filter| match filter {
+            QuickFilter::Modified => modified.contains(&file.track_key()),
+            QuickFilter::HasLoop => {
+                self.looped_tracks.contains(&file.track_key())
+                    || ReplaceUtils::get_pending_loop_settings(file).is_some_and(|s| s.enable_loop)
+            }
+            QuickFilter::NoLoop => {
+                !self.looped_tracks.contains(&file.track_key())
+                    && !ReplaceUtils::get_pending_loop_settings(file).is_some_and(|s| s.enable_loop)
+            }
+            QuickFilter::UnknownFormat => file.file_type == "Unknown",
+            _ => true,
+        })
+    }
+
+    fn load_selected_file(&mut self, file_path: Option<String>, background: bool) {
         // Clear any previously replaced audio data in memory
         ReplaceUtils::clear_replacements();
         
         self.selected_file = file_path;
+        audit_log::set_bank(self.selected_file.as_deref());
         self.file_count = None;
         self.audio_files = None;
+        self.track_labels = None;
+        self.transcripts = Transcripts::default();
+        self.empty_tracks.clear();
+        self.empty_filter = false;
+        self.looped_tracks.clear();
+        self.track_formats.clear();
+        self.tone_names.clear();
+        self.stale_sources.clear();
         self.error_message = None;
+        self.bank_loader = None;
+
+        // Pick up edits to the user file type table
+        FileTypeRegistry::reload();
 
         // If file is selected, determine type and load accordingly
         if let Some(file_name) = &self.selected_file {
@@ -113,10 +244,43 @@ impl MainArea {
             if file_name.to_lowercase().ends_with(".nus3audio") {
                 self.load_nus3audio_file(&file_name);
             } else if file_name.to_lowercase().ends_with(".nus3bank") {
-                self.load_nus3bank_file(&file_name);
+                if background && bank_loader::wants_background(&file_name) {
+                    self.bank_loader = Some(BankLoader::start(&file_name));
+                } else {
+                    self.load_nus3bank_file(&file_name);
+                }
+            }
+
+            // Show labels from a companion file; a broken one only costs the Labels column
+            match LabelFile::load_for(Path::new(&file_name)) {
+                Ok(labels) => self.track_labels = labels,
+                Err(e) => {
+                    println!("Ignoring label file: {}", e);
+                    self.add_toast(format!("Ignoring label file: {}", e), egui::Color32::GOLD);
+                }
+            }
+
+            match Transcripts::load_for(Path::new(&file_name)) {
+                Ok(transcripts) => self.transcripts = transcripts,
+                Err(e) => {
+                    println!("Ignoring transcript file: {}", e);
+                    self.add_toast(format!("Ignoring transcript file: {}", e), egui::Color32::GOLD);
+                }
             }
         }
     }
+
+    /// Format read from the payload header of a track when the file was opened
+    pub fn track_format(&self, file: &AudioFileInfo) -> Option<&TrackFormat> {
+        self.track_formats.get(&file.track_key())
+    }
+
+    /// Label of a track from the companion label file
+    pub fn track_label(&self, file: &AudioFileInfo) -> Option<&str> {
+        self.track_labels
+            .as_ref()?
+            .label_for(&file.name, &file.id, file.hex_id.as_deref())
+    }
     
     /// Load NUS3AUDIO file (existing implementation)
     fn load_nus3audio_file(&mut self, file_name: &str) {
@@ -129,26 +293,27 @@ impl MainArea {
                         let mut audio_files = Vec::new();
 
                         for audio_file in nus3_file.files.iter() {
-                            // Attempt to detect file type based on header
-                            let file_type = if audio_file.data.len() >= 4 {
-                                match &audio_file.data[..4] {
-                                    b"OPUS" => "OPUS",
-                                    b"IDSP" => "IDSP",
-                                    b"RIFF" => "WAV",
-                                    b"BNSF" => "BNSF",
-                                    _ => "Unknown",
-                                }
-                            } else {
-                                "Unknown"
-                            };
+                            // Detect the file type from its header, then its extension
+                            let filename = FileTypeRegistry::file_name_of(&audio_file.name, &audio_file.data);
+                            let file_type = FileTypeRegistry::type_name_of(&audio_file.data, Some(&filename));
 
-                            audio_files.push(AudioFileInfo::from_nus3audio(
+                            let info = AudioFileInfo::from_nus3audio(
                                 audio_file.name.clone(),
                                 audio_file.id.to_string(),
                                 audio_file.data.len(),
-                                audio_file.filename(),
-                                file_type.to_string(),
-                            ));
+                                filename,
+                                file_type,
+                            );
+                            if let Some(kind) = empty_tracks::check(&audio_file.data) {
+                                self.empty_tracks.insert(info.track_key(), kind);
+                            }
+                            if quick_filters::payload_loops(&audio_file.data) {
+                                self.looped_tracks.insert(info.track_key());
+                            }
+                            if let Some(format) = track_format::parse(&audio_file.data) {
+                                self.track_formats.insert(info.track_key(), format);
+                            }
+                            audio_files.push(info);
                         }
 
                         self.audio_files = Some(audio_files);
@@ -161,26 +326,17 @@ impl MainArea {
     
     /// Load NUS3BANK file (new implementation)
     fn load_nus3bank_file(&mut self, file_name: &str) {
-        match Nus3bankFile::open(file_name) {
-            Ok(nus3bank_file) => {
-                self.file_count = Some(nus3bank_file.tracks.len());
-                let mut audio_files = Vec::new();
-
-                for track in nus3bank_file.tracks.iter() {
-                    audio_files.push(AudioFileInfo::from_nus3bank_track(
-                        track.name.clone(),
-                        track.index as u32,
-                        track.hex_id.clone(),
-                        track.size as usize,
-                        track.filename(),
-                    ));
-                }
-
-                self.audio_files = Some(audio_files);
-            }
-            Err(e) => {
-                self.error_message = Some(format!("Error loading NUS3BANK file: {}", e));
+        match bank_loader::read_nus3bank(file_name, &mut |_, _| {}) {
+            Ok(bank) => {
+                self.file_count = Some(bank.audio_files.len());
+                self.empty_tracks = bank.empty_tracks;
+                self.looped_tracks = bank.looped;
+                self.track_formats = bank.formats;
+                self.tone_names = bank.tone_names;
+                self.audio_files = Some(bank.audio_files);
+                self.auto_normalize_on_open();
             }
+            Err(e) => self.error_message = Some(e),
         }
     }
 }
@@ -1,6 +1,9 @@
 use egui::{Color32, Ui, RichText};
 use egui_phosphor::regular;
 
+use super::export_format::{ExportFormat, MP3_BITRATES};
+use super::lip_sync::LIP_SYNC_FPS;
+use crate::audio_processing::flac;
 use super::main_area_core::MainArea;
 
 impl MainArea {
@@ -61,9 +64,63 @@ impl MainArea {
                     self.output_path = None;
                 }
             }
+
+            self.render_export_format(ui);
+            ui.checkbox(&mut self.embed_export_metadata, "Embed metadata")
+                .on_hover_text("Write bank name, track ID, original codec and export time into exported WAVs (BWF bext and INFO chunks). WAV format only.");
+            ui.checkbox(&mut self.export_lip_sync_timestamps, "Lip-sync timestamps")
+                .on_hover_text(format!(
+                    "Also write <name>.timestamps.txt with the cue length and audible range in frames at {} fps",
+                    LIP_SYNC_FPS
+                ));
         });
     }
 
+    /// Export format selector followed by the setting of the selected encoder
+    fn render_export_format(&mut self, ui: &mut Ui) {
+        let settings = &mut self.export_format;
+        egui::ComboBox::from_id_salt("export_format")
+            .selected_text(settings.format.name())
+            .show_ui(ui, |ui| {
+                for format in ExportFormat::ALL {
+                    ui.selectable_value(&mut settings.format, format, format.name());
+                }
+            })
+            .response
+            .on_hover_text("Format of exported files. OGG Vorbis and MP3 are encoded with ffmpeg from the tools folder or PATH.");
+
+        match settings.format {
+            ExportFormat::Wav => {}
+            ExportFormat::Flac => {
+                ui.add(
+                    egui::DragValue::new(&mut settings.flac_level)
+                        .range(0..=flac::MAX_LEVEL)
+                        .prefix("Level "),
+                )
+                .on_hover_text("Compression level: higher is smaller and slower, the audio is identical");
+            }
+            ExportFormat::OggVorbis => {
+                ui.add(
+                    egui::DragValue::new(&mut settings.vorbis_quality)
+                        .range(-1.0..=10.0)
+                        .speed(0.1)
+                        .fixed_decimals(1)
+                        .prefix("Quality "),
+                )
+                .on_hover_text("Vorbis VBR quality from -1 to 10; 6 is about 192 kbit/s");
+            }
+            ExportFormat::Mp3 => {
+                egui::ComboBox::from_id_salt("export_mp3_bitrate")
+                    .selected_text(format!("{} kbit/s", settings.mp3_bitrate_kbps))
+                    .show_ui(ui, |ui| {
+                        for bitrate in MP3_BITRATES {
+                            ui.selectable_value(&mut settings.mp3_bitrate_kbps, bitrate, format!("{} kbit/s", bitrate));
+                        }
+                    });
+            }
+        }
+    }
+
     /// Render output path selection (deprecated/legacy)
     pub fn render_output_path(&mut self, ui: &mut Ui) {
         self.render_output_path_compact(ui);
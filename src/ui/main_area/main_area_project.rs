@@ -0,0 +1,284 @@
+use std::path::{Path, PathBuf};
+
+use crate::nus3bank::manifest::ReplacementManifest;
+use crate::track_key::TrackKey;
+
+use super::{
+    audio_file_info::AudioFileInfo,
+    audit_log,
+    backups,
+    lopus_encoder,
+    main_area_core::MainArea,
+    nus3audio_file_utils::Nus3audioFileUtils,
+    path_utils,
+    project_file::{ProjectAddition, ProjectEmptyReplacement, ProjectFile, ProjectReplacement, ProjectTrack},
+    replace_utils::ReplaceUtils,
+    snapshots::Snapshot,
+    source_manifest::SourceManifest,
+    track_list::{TrackList, TrackListRow, TrackStatus},
+};
+
+impl MainArea {
+    /// Take or restore the snapshot asked for in the snapshots dialog
+    pub fn handle_snapshot_requests(&mut self) {
+        if let Some(name) = self.snapshots_modal.take_take_request() {
+            let Some(bank) = self.selected_file.clone() else {
+                return;
+            };
+            let snapshot = Snapshot::take(&name, &bank, self.audio_files.clone());
+            let edits = snapshot.edit_count();
+            self.snapshots_modal.add(snapshot);
+            self.add_toast(format!("Took snapshot \"{}\" ({} edits)", name, edits), egui::Color32::GREEN);
+        }
+
+        if let Some(snapshot) = self.snapshots_modal.take_restore_request() {
+            if self.selected_file.as_deref() != Some(snapshot.bank_path.as_str()) {
+                self.add_toast("The snapshot belongs to another file".to_string(), egui::Color32::RED);
+                return;
+            }
+            match snapshot.restore() {
+                Ok(audio_files) => {
+                    if audio_files.is_some() {
+                        self.audio_files = audio_files;
+                    }
+                    self.selected_rows.clear();
+                    audit_log::record(format!("Restored snapshot \"{}\"", snapshot.name));
+                    self.add_toast(format!("Restored snapshot \"{}\"", snapshot.name), egui::Color32::GREEN);
+                }
+                Err(e) => self.add_toast(format!("Failed to restore snapshot: {}", e), egui::Color32::RED),
+            }
+        }
+    }
+
+    /// Put back the backup picked in the backups dialog and reload the file
+    pub fn handle_backup_restore(&mut self) {
+        let Some(backup) = self.backups_modal.take_restore_request() else {
+            return;
+        };
+        let Some(file_path) = self.selected_file.clone() else {
+            return;
+        };
+        match backups::restore(&backup, Path::new(&file_path), self.backup_settings.retention) {
+            Ok(()) => {
+                let name = backup.file_name().unwrap_or_default().to_string_lossy().to_string();
+                audit_log::record(format!("Restored backup {}", name));
+                self.update_selected_file(Some(file_path));
+                self.add_toast(format!("Restored {}", name), egui::Color32::GREEN);
+            }
+            Err(e) => self.add_toast(e, egui::Color32::RED),
+        }
+    }
+
+    /// Pending edits of the opened file as a project
+    pub fn project_snapshot(&self) -> Result<ProjectFile, String> {
+        let bank = self.selected_file.as_ref().ok_or("No file is open")?;
+        let listed: &[AudioFileInfo] = self.audio_files.as_deref().unwrap_or_default();
+        let (added, removed) = Nus3audioFileUtils::change_records();
+
+        let mut project = ProjectFile::new(PathBuf::from(bank));
+        for (key, source, loop_settings) in ReplaceUtils::pending_replacement_sources() {
+            // A replaced track that was removed afterwards is no longer listed
+            let Some(track) = listed.iter().chain(removed.iter()).find(|f| f.track_key() == key) else {
+                continue;
+            };
+            project.replacements.push(ProjectReplacement {
+                track: ProjectTrack::from(track),
+                source,
+                loop_settings,
+            });
+        }
+        for (track, min_ms) in ReplaceUtils::pending_empty_replacements() {
+            project.empty_replacements.push(ProjectEmptyReplacement {
+                track: ProjectTrack::from(&track),
+                min_ms,
+            });
+        }
+        for (track, source) in added {
+            project.additions.push(ProjectAddition {
+                track: ProjectTrack::from(&track),
+                source,
+            });
+        }
+        project.removals = removed.iter().map(ProjectTrack::from).collect();
+        project.transcripts = self.transcripts.to_strings();
+        Ok(project)
+    }
+
+    /// Save the pending edits to `path`. Returns the number of edits saved and how many of
+    /// them reference temporary files, which may be gone after a restart.
+    pub fn save_project(&self, path: &Path) -> Result<(usize, usize), String> {
+        let project = self.project_snapshot()?;
+        project.save(path)?;
+        let temp_dirs = [std::env::temp_dir(), path_utils::safe_temp_dir()];
+        let temporary = project
+            .replacements
+            .iter()
+            .map(|r| &r.source)
+            .chain(project.additions.iter().map(|a| &a.source))
+            .filter(|source| temp_dirs.iter().any(|dir| source.starts_with(dir)))
+            .count();
+        Ok((project.change_count(), temporary))
+    }
+
+    /// Write the pending sources and their parameters to `path` as JSON or CSV. Returns the
+    /// number of entries written.
+    pub fn export_source_manifest(&self, path: &Path) -> Result<usize, String> {
+        let manifest = SourceManifest::from_project(&self.project_snapshot()?);
+        manifest.write(path)?;
+        Ok(manifest.entries.len())
+    }
+
+    /// Write the rows the table shows, in its order, to `path` as JSON or CSV. Returns the
+    /// number of rows written.
+    pub fn export_track_list(&self, path: &Path) -> Result<usize, String> {
+        let file = self.selected_file.clone().ok_or("No file is open")?;
+        let replaced: std::collections::HashSet<TrackKey> =
+            ReplaceUtils::get_replacement_sizes().into_iter().map(|(key, _)| key).collect();
+        let status = |info: &AudioFileInfo| {
+            let added = if info.is_nus3bank {
+                replaced.contains(&info.added_track_key())
+            } else {
+                Nus3audioFileUtils::get_pending_added_data(&info.name, &info.id).is_some()
+            };
+            if added {
+                TrackStatus::Added
+            } else if replaced.contains(&info.track_key()) {
+                TrackStatus::Replaced
+            } else {
+                TrackStatus::Unchanged
+            }
+        };
+        let tracks: Vec<TrackListRow> = self
+            .filtered_audio_files()
+            .iter()
+            .map(|info| TrackListRow::new(info, self.track_format(info), status(info)))
+            .collect();
+        let list = TrackList { file: PathBuf::from(file), tracks };
+        list.write(path)?;
+        Ok(list.tracks.len())
+    }
+
+    fn find_project_track(&self, track: &ProjectTrack) -> Option<AudioFileInfo> {
+        self.audio_files.as_ref()?.iter().find(|f| track.matches(f)).cloned()
+    }
+
+    fn replace_listed_track(&mut self, old: &AudioFileInfo, new: AudioFileInfo) {
+        if let Some(files) = self.audio_files.as_mut() {
+            if let Some(listed) = files.iter_mut().find(|f| f.name == old.name && f.id == old.id) {
+                *listed = new;
+            }
+        }
+    }
+
+    /// Register the edits of a project against the opened file, which must be the project's
+    /// bank. Returns the number of edits applied and one message per edit that failed.
+    pub fn apply_project(&mut self, project: &ProjectFile) -> (usize, Vec<String>) {
+        let Some(bank) = self.selected_file.clone() else {
+            return (0, vec!["No file is open".to_string()]);
+        };
+        Nus3audioFileUtils::clear_changes();
+        let mut applied = 0;
+        let mut errors = Vec::new();
+
+        for replacement in &project.replacements {
+            let Some(info) = self.find_project_track(&replacement.track) else {
+                errors.push(format!("{}: track not found", replacement.track.name));
+                continue;
+            };
+            // LOPUS and IDSP targets are encoded when that is the default, as the dialog would offer
+            let format = lopus_encoder::default_format(&info, &self.lopus_encoder);
+            let previous = ReplaceUtils::get_replacement_data_unified(&info);
+            match ReplaceUtils::process_replacement_with_settings(&info, Some(&replacement.source), &replacement.loop_settings)
+            .and_then(|queued| self.lopus_encoder.for_bank(&bank).encode_queued(&queued, format, previous))
+            {
+                Ok(new_info) => {
+                    self.replace_listed_track(&info, new_info);
+                    applied += 1;
+                }
+                Err(e) => errors.push(format!("{}: {}", info.name, e)),
+            }
+        }
+
+        for empty in &project.empty_replacements {
+            let Some(info) = self.find_project_track(&empty.track) else {
+                errors.push(format!("{}: track not found", empty.track.name));
+                continue;
+            };
+            match ReplaceUtils::replace_with_empty_wav_in_memory(&info, &bank, empty.min_ms) {
+                Ok(new_info) => {
+                    self.replace_listed_track(&info, new_info);
+                    applied += 1;
+                }
+                Err(e) => errors.push(format!("{}: {}", info.name, e)),
+            }
+        }
+
+        for addition in &project.additions {
+            let info = addition.track.to_info();
+            let name = info.name.clone();
+            match self.queue_addition(info, &addition.source) {
+                Ok(()) => applied += 1,
+                Err(e) => errors.push(format!("{}: {}", name, e)),
+            }
+        }
+
+        for removal in &project.removals {
+            let Some(info) = self.find_project_track(removal) else {
+                errors.push(format!("{}: track not found", removal.name));
+                continue;
+            };
+            match Nus3audioFileUtils::register_remove(&info, Some(&bank)) {
+                Ok(()) => {
+                    if let Some(files) = self.audio_files.as_mut() {
+                        files.retain(|f| !(f.name == info.name && f.id == info.id));
+                    }
+                    self.selected_items.remove(&format!("{}:{}", info.name, info.id));
+                    applied += 1;
+                }
+                Err(e) => errors.push(format!("{}: {}", info.name, e)),
+            }
+        }
+
+        // Transcripts carried by the project go into the bank's sidecar
+        if !project.transcripts.is_empty() {
+            self.transcripts.set_all(&project.transcripts);
+            if let Err(e) = self.transcripts.save() {
+                errors.push(e);
+            }
+        }
+
+        self.file_count = self.audio_files.as_ref().map(Vec::len);
+        (applied, errors)
+    }
+
+    /// Start queueing the replacements listed in the manifest at `path` as pending edits of
+    /// the opened file. Sources are read relative to the manifest; locked rows are left alone.
+    pub fn import_replacement_manifest(&mut self, path: &Path) -> Result<(), String> {
+        let bank = self.selected_file.clone().ok_or("No file is open")?;
+        if self.manifest_import_modal.is_running() {
+            return Err("A manifest is already being imported".to_string());
+        }
+        let manifest = ReplacementManifest::read(path)?;
+        let tracks = self.audio_files.clone().unwrap_or_default();
+        let locked = tracks.iter().filter(|info| self.is_locked(info)).map(AudioFileInfo::track_key).collect();
+        let encoder = self.lopus_encoder.for_bank(&bank);
+        self.manifest_import_modal.start(path.to_path_buf(), manifest, tracks, locked, encoder);
+        Ok(())
+    }
+
+    /// List the tracks a finished manifest import replaced
+    pub fn handle_manifest_import(&mut self) {
+        let Some((path, replaced)) = self.manifest_import_modal.take_finished() else {
+            return;
+        };
+        let count = replaced.len();
+        for (info, new_info) in replaced {
+            self.replace_listed_track(&info, new_info);
+        }
+        audit_log::record(format!(
+            "Imported replacement manifest {} ({} replaced)",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            count
+        ));
+    }
+}
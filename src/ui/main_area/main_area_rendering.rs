@@ -2,18 +2,49 @@ use egui::{Align, Align2, Color32, Context, Layout, RichText, Ui};
 use egui_phosphor::regular;
 
 use super::main_area_core::MainArea;
+use super::post_save_hook;
+use super::replace_utils::ReplaceUtils;
+use super::save_summary::format_size;
 
 impl MainArea {
     /// Display the main editing area
     pub fn show(&mut self, ctx: &Context) {
         // Show the loop settings modal if open
-        self.loop_settings_modal.show(ctx);
+        // The modal edits the encoder settings while open and picks up the saved ones otherwise
+        if self.loop_settings_modal.open {
+            self.loop_settings_modal.original_format =
+                self.loop_settings_modal.audio_info.as_ref().and_then(|info| self.track_format(info)).copied();
+            self.loop_settings_modal.show(ctx);
+            self.lopus_encoder = self.loop_settings_modal.lopus_encoder.clone();
+        } else {
+            self.loop_settings_modal.lopus_encoder = self.lopus_encoder.clone();
+        }
+        self.handle_replacement_source_drop();
+        self.handle_loop_preview();
         
-        // Show the add audio modal if open
-        self.add_audio_modal.show(ctx);
+        // Show the add audio modal if open; both add dialogs share the saved naming rule
+        if self.add_audio_modal.open {
+            self.add_audio_modal.show(ctx);
+            self.naming_rule = self.add_audio_modal.naming.clone();
+        } else {
+            self.add_audio_modal.naming = self.naming_rule.clone();
+        }
+
+        // Show the bulk add dialog for several dropped files and add what it confirms
+        if self.bulk_add_modal.open {
+            self.bulk_add_modal.show(ctx);
+            self.naming_rule = self.bulk_add_modal.naming.clone();
+        } else {
+            self.bulk_add_modal.naming = self.naming_rule.clone();
+        }
+        self.handle_bulk_add();
         
         // Show the confirm modal if open
         self.confirm_modal.show(ctx);
+        self.export_all_modal.show(ctx);
+        self.export_progress_modal.show(ctx);
+        self.manifest_import_modal.show(ctx);
+        self.handle_manifest_import();
 
         // Show the GRP list modal if open
         self.grp_list_modal.show(ctx);
@@ -23,6 +54,139 @@ impl MainArea {
 
         // Show the PROP edit modal if open
         self.prop_edit_modal.show(ctx);
+
+        // Show the blind test modal if open and forward its playback requests
+        self.blind_test_modal.show(ctx);
+        self.play_blind_test_request();
+
+        // Report after-save commands that finished in the background
+        for result in post_save_hook::take_finished() {
+            match result {
+                Ok(message) => self.add_toast(message, Color32::GREEN),
+                Err(message) => self.add_toast(message, Color32::RED),
+            }
+        }
+
+        // Show the URL import window and queue finished downloads
+        self.url_import_modal.show(ctx);
+        self.handle_url_import();
+
+        // List the search matches of every open tab and jump to the one picked
+        self.show_workspace_results(ctx);
+
+        // Show the bank statistics and jump to tracks picked there
+        self.stats_modal.show(ctx);
+        if let Some(info) = self.stats_modal.take_jump() {
+            self.reveal_track(&info);
+        }
+
+        // Show the intro/loop pairs and run their previews and joint replaces
+        self.intro_loop_modal.show(ctx);
+        self.handle_intro_loop_action();
+
+        // Show the conversion argument editor and remember what it saved
+        self.bank_args_modal.show(ctx);
+        if let Some((bank, overrides)) = self.bank_args_modal.take_saved() {
+            self.set_bank_arg_overrides(&bank, overrides);
+        }
+
+        // Show the snapshots and take or restore what was asked for
+        self.snapshots_modal.show(ctx);
+        self.handle_snapshot_requests();
+
+        // Show the edit log
+        self.audit_log_modal.show(ctx);
+
+        // Show the result of the last verify
+        self.verify_modal.show(ctx);
+
+        // Show the backups and put back the one picked
+        self.backups_modal.show(ctx);
+        if let Some(settings) = self.backups_modal.take_saved() {
+            self.backup_settings = settings;
+        }
+        self.handle_backup_restore();
+
+        // Show the memory estimate and keep the settings edited there
+        if self.memory_modal.open {
+            let footprint = self.memory_footprint();
+            self.memory_modal.show(ctx, footprint.as_ref());
+            if let Some(budget) = self.memory_modal.take_saved() {
+                self.memory_budget = budget;
+            }
+        }
+
+        // Show the shortcut settings and keep the bindings edited there
+        self.shortcuts_modal.show(ctx);
+        if let Some(shortcuts) = self.shortcuts_modal.take_saved() {
+            self.shortcuts = shortcuts;
+        }
+
+        // Show the category icon rules and keep what was edited there
+        self.track_categories_modal.show(ctx);
+        if let Some(categories) = self.track_categories_modal.take_saved() {
+            self.track_categories = categories;
+        }
+
+        // Show the .nus3audio structure and queue the entry order applied there
+        self.nus3audio_layout_modal.show(ctx);
+        if let Some(order) = self.nus3audio_layout_modal.take_saved() {
+            self.apply_entry_order(order);
+        }
+
+        // Show the track ID editor and swap the IDs it confirmed
+        self.tone_id_modal.show(ctx);
+        if let Some((track, id)) = self.tone_id_modal.take_saved() {
+            match self.move_tone(&track, id) {
+                Ok(_) => self.add_toast(format!("{} gets ID 0x{:x} on the next save", track.name, id), Color32::GREEN),
+                Err(e) => self.add_toast(format!("Cannot change the ID of {}: {}", track.name, e), Color32::RED),
+            }
+        }
+
+        // Show the Duplicate dialog and queue the copy it confirmed
+        self.duplicate_modal.show(ctx);
+        if let Some((track, name)) = self.duplicate_modal.take_saved() {
+            match self.duplicate_track(&track, &name) {
+                Ok(copy) => self.add_toast(format!("Duplicated {} as {}", track.name, copy.name), Color32::GREEN),
+                Err(e) => self.add_toast(format!("Failed to duplicate {}: {}", track.name, e), Color32::RED),
+            }
+        }
+
+        // Show the transcript editor and write what it saved to the sidecar
+        self.transcript_modal.show(ctx);
+        if let Some((key, text)) = self.transcript_modal.take_saved() {
+            self.transcripts.set(&key, &text);
+            if let Err(e) = self.transcripts.save() {
+                self.add_toast(e, Color32::RED);
+            }
+        }
+
+        // Show the mixing panel and queue the processed audio
+        self.mix_modal.show(ctx);
+        if let Some((info, settings)) = self.mix_modal.take_apply() {
+            self.apply_mix(&info, &settings);
+        }
+        self.normalize_all_modal.show(ctx);
+        if let Some((tracks, target)) = self.normalize_all_modal.take_apply() {
+            self.normalize_all(&tracks, target);
+        }
+
+        // Show the review queue and play the track it moved to
+        self.review_modal.show(ctx);
+        self.handle_review_requests();
+
+        // Show the notification history
+        self.notification_center.show(ctx);
+
+        // Show the trigger pads and play what was hit
+        self.pad_grid.show(ctx);
+        self.handle_pad_events();
+
+        // Notice replacement sources re-exported from a DAW
+        self.check_replacement_sources();
+
+        // Duck or pause the preview while a dialog is open
+        self.update_dialog_ducking();
         
         egui::CentralPanel::default()
             .frame(egui::Frame::new()
@@ -37,12 +201,17 @@ impl MainArea {
     pub fn render(&mut self, ui: &mut Ui) {
         // First, clean up expired toast messages
         self.toast_messages.retain(|toast| !toast.has_expired());
+        self.poll_bank_loader();
         let available_height = ui.available_height();
         let available_width = ui.available_width();
 
         // Render toast messages at the top (overlay)
         self.render_toasts(ui);
 
+        if !self.file_tabs.tabs().is_empty() {
+            self.render_tab_bar(ui);
+        }
+
         if let Some(selected) = self.selected_file.clone() {
             ui.vertical(|ui| {
                 // 1. Header Area - Professional look with breadcrumbs/file info
@@ -51,6 +220,18 @@ impl MainArea {
                 // 2. Toolbar Area - Search and Output path
                 self.render_toolbar(ui);
 
+                if !self.stale_sources.is_empty() {
+                    self.render_stale_sources_banner(ui);
+                }
+
+                if self.dual_pane.open {
+                    egui::SidePanel::left("dual_pane")
+                        .resizable(true)
+                        .default_width(available_width * 0.35)
+                        .show_inside(ui, |ui| self.render_dual_pane(ui));
+                }
+                let available_width = ui.available_width();
+
                 // 3. Main Content Area - The Table
                 if let Some(_audio_files) = &self.audio_files {
                     // Get filtered and sorted audio files
@@ -69,6 +250,22 @@ impl MainArea {
                     ui.centered_and_justified(|ui| {
                         ui.colored_label(Color32::RED, error);
                     });
+                } else if let Some(loader) = &self.bank_loader {
+                    let (read, total) = loader.progress();
+                    ui.vertical_centered(|ui| {
+                        ui.add_space(available_height * 0.3);
+                        ui.label(format!(
+                            "Loading {}...",
+                            std::path::Path::new(loader.path()).file_name().unwrap_or_default().to_string_lossy()
+                        ));
+                        ui.add_space(6.0);
+                        ui.add(
+                            egui::ProgressBar::new(read as f32 / total.max(1) as f32)
+                                .desired_width(320.0)
+                                .text(format!("{:.1} / {:.1} MB", read as f64 / 1048576.0, total as f64 / 1048576.0)),
+                        );
+                    });
+                    ui.ctx().request_repaint_after(std::time::Duration::from_millis(50));
                 } else {
                     ui.centered_and_justified(|ui| {
                         ui.vertical_centered(|ui| {
@@ -95,6 +292,111 @@ impl MainArea {
         }
     }
 
+    /// Render one tab per open file. Closing a tab with pending edits takes a second click.
+    fn render_tab_bar(&mut self, ui: &mut Ui) {
+        let mut switch_to = None;
+        let mut close = None;
+        egui::Frame::new()
+            .fill(ui.visuals().extreme_bg_color)
+            .inner_margin(egui::Margin::symmetric(8, 4))
+            .show(ui, |ui| {
+                egui::ScrollArea::horizontal().id_salt("file_tabs").show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        for (index, tab) in self.file_tabs.tabs().iter().enumerate() {
+                            let active = self.file_tabs.active() == Some(index);
+                            let edits = self.tab_edit_count(index);
+                            let title = if edits > 0 {
+                                format!("{} \u{25CF}", tab.title())
+                            } else {
+                                tab.title()
+                            };
+                            let text = if active { RichText::new(title).strong() } else { RichText::new(title) };
+                            let hint = if edits > 0 {
+                                format!("{}\n{} pending edit(s)", tab.path, edits)
+                            } else {
+                                tab.path.clone()
+                            };
+                            if ui.selectable_label(active, text).on_hover_text(hint).clicked() {
+                                switch_to = Some(index);
+                            }
+
+                            let confirming = self.tab_close_confirm == Some(index);
+                            let close_text = if confirming {
+                                RichText::new(regular::WARNING.to_string()).color(Color32::from_rgb(255, 180, 60))
+                            } else {
+                                RichText::new(regular::X.to_string()).weak()
+                            };
+                            let close_hint = if confirming {
+                                format!("Click again to discard {} pending edit(s)", edits)
+                            } else {
+                                "Close".to_string()
+                            };
+                            if ui.small_button(close_text).on_hover_text(close_hint).clicked() {
+                                if edits == 0 || confirming {
+                                    close = Some(index);
+                                } else {
+                                    self.tab_close_confirm = Some(index);
+                                }
+                            }
+                            ui.separator();
+                        }
+                    });
+                });
+            });
+
+        if let Some(index) = close {
+            self.tab_close_confirm = None;
+            self.close_tab(index);
+        } else if let Some(index) = switch_to {
+            self.tab_close_confirm = None;
+            self.switch_tab(index);
+        }
+    }
+
+    /// Offer to re-process replacements whose source file changed on disk
+    fn render_stale_sources_banner(&mut self, ui: &mut Ui) {
+        let mut refresh = false;
+        let mut keep = false;
+        egui::Frame::new()
+            .fill(Color32::from_rgb(70, 55, 20))
+            .inner_margin(egui::Margin::symmetric(16, 6))
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let names: Vec<&str> = self.stale_sources.iter().map(|(key, _)| key.name()).collect();
+                    ui.colored_label(
+                        Color32::from_rgb(255, 200, 100),
+                        format!("{} Source changed on disk: {}", regular::WARNING, names.join(", ")),
+                    )
+                    .on_hover_text(
+                        self.stale_sources
+                            .iter()
+                            .map(|(_, path)| path.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    );
+                    if ui
+                        .button(format!("{} Refresh", regular::ARROWS_CLOCKWISE))
+                        .on_hover_text("Queue the new audio again with the same loop and gain")
+                        .clicked()
+                    {
+                        refresh = true;
+                    }
+                    if ui
+                        .button("Keep queued audio")
+                        .on_hover_text("Ignore this change; a later one is reported again")
+                        .clicked()
+                    {
+                        keep = true;
+                    }
+                });
+            });
+        if refresh {
+            self.refresh_stale_sources();
+        } else if keep {
+            self.keep_stale_sources();
+        }
+    }
+
     /// Render header with file information
     fn render_header(&mut self, ui: &mut Ui, selected: &str) {
         egui::Frame::new()
@@ -150,9 +452,62 @@ impl MainArea {
                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
                         if ui.button(RichText::new(format!("{} Refresh", regular::ARROWS_CLOCKWISE))).clicked() {
                             if let Some(path) = self.selected_file.clone() {
-                                self.update_selected_file(Some(path));
+                                self.update_selected_file_in_background(Some(path));
                             }
                         }
+
+                        // Non-default conversion flags are flagged so odd output is not a mystery
+                        let overrides = self.bank_arg_overrides.get(selected).filter(|o| !o.is_default()).cloned();
+                        let button = match &overrides {
+                            Some(_) => egui::Button::new(
+                                RichText::new(format!("{} Custom Args", regular::WARNING)).color(Color32::from_rgb(255, 180, 60)),
+                            ),
+                            None => egui::Button::new(format!("{} Args", regular::TERMINAL)),
+                        };
+                        let hint = match &overrides {
+                            Some(o) => format!("Non-default conversion flags are active\nvgmstream-cli: {}\nEncoder: {}", o.vgmstream, o.encoder),
+                            None => "Extra vgmstream-cli and encoder arguments for this bank".to_string(),
+                        };
+                        if ui.add(button).on_hover_text(hint).clicked() {
+                            self.bank_args_modal.open_for(selected, overrides.unwrap_or_default());
+                        }
+
+                        // Flag banks whose pending changes push them over the memory limit
+                        let over_limit = self.memory_footprint().filter(|f| f.over_limit());
+                        let button = match &over_limit {
+                            Some(_) => egui::Button::new(
+                                RichText::new(format!("{} Memory", regular::WARNING)).color(Color32::from_rgb(255, 180, 60)),
+                            ),
+                            None => egui::Button::new(format!("{} Memory", regular::MEMORY)),
+                        };
+                        let hint = match &over_limit {
+                            Some(f) => format!(
+                                "Estimated {} of sound memory, over the {} limit",
+                                format_size(f.total_bytes as usize),
+                                format_size(f.limit_bytes as usize)
+                            ),
+                            None => "Estimated sound memory of the bank in game".to_string(),
+                        };
+                        if ui.add(button).on_hover_text(hint).clicked() {
+                            self.memory_modal.open_with(&self.memory_budget);
+                        }
+
+                        if ui
+                            .button(format!("{} Snapshots", regular::CAMERA))
+                            .on_hover_text("Save the pending edits under a name and restore them later")
+                            .clicked()
+                        {
+                            self.snapshots_modal.open_for(selected);
+                        }
+
+                        if selected.to_lowercase().ends_with(".nus3bank")
+                            && ui
+                                .button(format!("{} Verify", regular::CHECK_CIRCLE))
+                                .on_hover_text("Save the edited bank into memory, read it back and compare every track")
+                                .clicked()
+                        {
+                            self.verify_modal.open_with(selected, ReplaceUtils::verify_unified(selected));
+                        }
                     });
                 });
             });
@@ -184,6 +539,15 @@ impl MainArea {
                         if ui.button(format!("{} {}", icon, text)).clicked() {
                             self.show_advanced_search = !self.show_advanced_search;
                         }
+                        ui.toggle_value(&mut self.dual_pane.open, format!("{} Split View", regular::COLUMNS))
+                            .on_hover_text("Show a second bank beside this one to copy tracks across");
+                        if ui
+                            .add_enabled(self.audio_files.is_some(), egui::Button::new(format!("{} Play All", regular::PLAYLIST)))
+                            .on_hover_text("Play the listed tracks in table order, one after another")
+                            .clicked()
+                        {
+                            self.play_all();
+                        }
                     });
                 });
 
@@ -216,17 +580,22 @@ impl MainArea {
     }
     
     /// Render toast notifications
-    pub fn render_toasts(&self, ui: &mut Ui) {
+    pub fn render_toasts(&mut self, ui: &mut Ui) {
         if self.toast_messages.is_empty() {
             return;
         }
+        // Wake up to take down the next toast even if nothing else happens
+        if let Some(next) = self.toast_messages.iter().map(|t| t.expires_at).min() {
+            ui.ctx().request_repaint_after(next.saturating_duration_since(std::time::Instant::now()));
+        }
         
         // Calculate spacing from top
         let available_rect = ui.ctx().available_rect();
         let spacing = available_rect.height() * 0.08;
         let toast_offset = available_rect.height() * 0.06;
         
-        // Show toast messages
+        // Show toast messages; a summary toast opens its details in the notification center
+        let mut open_details = None;
         for (i, toast) in self.toast_messages.iter().enumerate() {
             // Create a toast window at the top center of the screen
             let window_id = egui::Id::new("toast_message").with(i);
@@ -244,9 +613,21 @@ impl MainArea {
                 ])
                 .show(ui.ctx(), |ui| {
                     ui.vertical_centered(|ui| {
-                        ui.colored_label(toast.color, &toast.message);
+                        if toast.is_summary() {
+                            let link = ui
+                                .link(RichText::new(&toast.message).color(toast.color))
+                                .on_hover_text(toast.details.last().cloned().unwrap_or_default());
+                            if link.clicked() {
+                                open_details = Some(toast.details.clone());
+                            }
+                        } else {
+                            ui.colored_label(toast.color, &toast.message);
+                        }
                     });
                 });
         }
+        if let Some(details) = open_details {
+            self.notification_center.open_with(details);
+        }
     }
 }
@@ -1,27 +1,181 @@
 use egui::{RichText, Ui, Color32};
 use egui_phosphor::regular;
 
+use super::color_labels::ColorLabel;
 use super::main_area_core::MainArea;
+use super::quick_filters::QuickFilter;
+use super::workspace_search::{self, BankMatches};
+
+const SEARCH_BOX_ID: &str = "main_area_search_box";
 
 impl MainArea {
+    /// Put the keyboard focus in the search box
+    pub fn focus_search(&self, ctx: &egui::Context) {
+        ctx.memory_mut(|m| m.request_focus(egui::Id::new(SEARCH_BOX_ID)));
+    }
+
     /// Render search box in a compact way for the toolbar
     pub fn render_search_box_compact(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
             let response = ui.add(
                 egui::TextEdit::singleline(&mut self.search_query)
+                    .id(egui::Id::new(SEARCH_BOX_ID))
                     .hint_text("Search audio files...")
                     .desired_width(250.0)
             );
-            let _ = response; // Avoid unused variable warning
+            response.on_hover_text("Durations can be compared too: >30s, <2s, >=1m30s");
             
             if !self.search_query.is_empty() {
                 if ui.button(RichText::new(regular::X.to_string()).color(Color32::GRAY)).on_hover_text("Clear search").clicked() {
                     self.search_query.clear();
                 }
             }
+
+            if self.file_tabs.tabs().len() > 1 {
+                ui.toggle_value(&mut self.search_all_tabs, "All tabs")
+                    .on_hover_text("Also search the names and IDs of the other open files");
+            }
+
+            self.render_label_filter(ui);
+
+            if !self.empty_tracks.is_empty() || self.empty_filter {
+                ui.toggle_value(&mut self.empty_filter, format!("Empty ({})", self.empty_tracks.len()))
+                    .on_hover_text("Show only tracks whose payload is a header-only stub, cut short or all silence");
+            }
+
+            ui.separator();
+            for filter in QuickFilter::ALL {
+                let mut on = self.quick_filters.contains(&filter);
+                if ui.toggle_value(&mut on, filter.name()).on_hover_text(filter.hover()).changed() {
+                    if on {
+                        self.quick_filters.insert(filter);
+                    } else {
+                        self.quick_filters.remove(&filter);
+                    }
+                }
+            }
         });
     }
 
+    /// Matches of the search in every open tab, the active one first
+    fn workspace_matches(&self) -> Vec<BankMatches> {
+        let active = self.file_tabs.active();
+        let mut order: Vec<usize> = (0..self.file_tabs.tabs().len()).collect();
+        order.sort_by_key(|index| Some(*index) != active);
+        let banks = order.into_iter().map(|index| {
+            let tab = &self.file_tabs.tabs()[index];
+            let rows = if Some(index) == active {
+                self.audio_files.as_deref()
+            } else {
+                tab.parked_rows()
+            };
+            (tab.path.clone(), tab.title(), rows)
+        });
+        workspace_search::search(banks, &self.search_query)
+    }
+
+    /// Window listing the matches in all open tabs, grouped by file, with jump buttons
+    pub fn show_workspace_results(&mut self, ctx: &egui::Context) {
+        self.apply_pending_reveal();
+        if !self.search_all_tabs || self.search_query.trim().is_empty() || self.file_tabs.tabs().len() < 2 {
+            return;
+        }
+
+        let matches = self.workspace_matches();
+        let mut jump = None;
+        let mut open = true;
+        egui::Window::new("Search Results in Open Files")
+            .open(&mut open)
+            .default_width(360.0)
+            .default_height(320.0)
+            .show(ctx, |ui| {
+                if matches.is_empty() {
+                    ui.label(RichText::new(format!("No track in the open files matches \"{}\"", self.search_query.trim())).weak());
+                    return;
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for bank in &matches {
+                        let is_active = self.selected_file.as_deref() == Some(bank.path.as_str());
+                        let header = format!("{} ({}){}", bank.title, bank.tracks.len(), if is_active { " - active" } else { "" });
+                        egui::CollapsingHeader::new(header)
+                            .id_salt(("workspace_results", &bank.path))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                for track in &bank.tracks {
+                                    ui.horizontal(|ui| {
+                                        if ui
+                                            .small_button(regular::ARROW_SQUARE_OUT.to_string())
+                                            .on_hover_text("Open this file and select the track")
+                                            .clicked()
+                                        {
+                                            jump = Some((bank.path.clone(), track.clone()));
+                                        }
+                                        ui.label(&track.name);
+                                        ui.label(RichText::new(&track.id).weak().monospace());
+                                    });
+                                }
+                            })
+                            .header_response
+                            .on_hover_text(&bank.path);
+                    }
+                });
+            });
+
+        if !open {
+            self.search_all_tabs = false;
+        }
+        if let Some((path, track)) = jump {
+            if let Some(index) = self.file_tabs.index_of(&path) {
+                self.switch_tab(index);
+            }
+            self.pending_reveal = Some((path, track));
+            self.apply_pending_reveal();
+        }
+    }
+
+    /// Select the track picked in the workspace results once its file is loaded
+    fn apply_pending_reveal(&mut self) {
+        let Some((path, track)) = &self.pending_reveal else {
+            return;
+        };
+        if self.selected_file.as_deref() != Some(path.as_str()) {
+            // Another tab was picked before the file finished loading
+            self.pending_reveal = None;
+            return;
+        }
+        if self.bank_loader.is_some() || self.audio_files.is_none() {
+            return;
+        }
+        let track = track.clone();
+        self.pending_reveal = None;
+        self.reveal_track(&track);
+    }
+
+    /// Color label filter next to the search box
+    fn render_label_filter(&mut self, ui: &mut Ui) {
+        let bank = self.selected_file.clone().unwrap_or_default();
+        let selected_text = match self.label_filter {
+            Some(label) => RichText::new(format!("\u{25CF} {}", label.name())).color(label.color32()),
+            None => RichText::new("All labels"),
+        };
+        egui::ComboBox::from_id_salt("color_label_filter")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.label_filter, None, "All labels");
+                for label in ColorLabel::ALL {
+                    let text = RichText::new(format!(
+                        "\u{25CF} {} ({})",
+                        label.name(),
+                        self.color_labels.count(&bank, label)
+                    ))
+                    .color(label.color32());
+                    ui.selectable_value(&mut self.label_filter, Some(label), text);
+                }
+            })
+            .response
+            .on_hover_text("Show only tracks with a color label");
+    }
+
     /// Render search box (deprecated/legacy)
     pub fn render_search_box(&mut self, ui: &mut Ui) {
         self.render_search_box_compact(ui);
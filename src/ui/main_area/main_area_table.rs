@@ -1,10 +1,15 @@
+use std::collections::HashMap;
+
 use egui::{Color32, Ui, RichText};
 use egui_phosphor::regular;
 
 use super::{
-    audio_file_info::AudioFileInfo, export_utils::ExportUtils, main_area_core::MainArea,
-    replace_utils::ReplaceUtils, table_renderer::TableRenderer, add_audio_utils::AddAudioUtils, nus3audio_file_utils::Nus3audioFileUtils,
+    audio_file_info::AudioFileInfo, export_job::ExportRequest, export_journal, sort_column::SortColumn, track_format::MetadataColumn, export_utils::ExportUtils, main_area_core::MainArea,
+    replace_utils::ReplaceUtils, table_renderer::{RowMenuAction, TableRenderer}, loop_clipboard, pad_grid::PadEvent, url_import::UrlImportTarget, intro_loop::IntroLoopPair, intro_loop_modal::IntroLoopAction, lip_sync, sample_rate_check::RateCheck, table_view::TableView, empty_wav, path_utils, add_audio_utils::AddAudioUtils, nus3audio_file_utils::Nus3audioFileUtils, lopus_encoder, file_drop, loop_settings_modal::LoopPreview, review_modal::ReviewOutcome, audit_log,
 };
+use crate::audio_processing::mix::{MixSettings, Normalize};
+use crate::audio_processing::{smpl, splice};
+use crate::track_key::TrackKey;
 use crate::ui::audio_player::{AudioPlayerAction, LoopMode};
 
 impl MainArea {
@@ -26,6 +31,7 @@ impl MainArea {
             play_index: Option<usize>,
             replace_index: Option<usize>,
             remove_index: Option<usize>,
+            row_menu: Option<(usize, RowMenuAction)>,
             export_all_confirm: bool,
             add_audio: bool,
             edit_grp_list: bool,
@@ -34,6 +40,7 @@ impl MainArea {
             replace_new: bool,
             replace_empty: bool,
             remove_selected: bool,
+            review_selected: bool,
             debug_convert_all_wav: bool,
         }
 
@@ -42,6 +49,7 @@ impl MainArea {
             play_index: None,
             replace_index: None,
             remove_index: None,
+            row_menu: None,
             export_all_confirm: false,
             add_audio: false,
             edit_grp_list: false,
@@ -50,6 +58,7 @@ impl MainArea {
             replace_new: false,
             replace_empty: false,
             remove_selected: false,
+            review_selected: false,
             debug_convert_all_wav: false,
         };
 
@@ -92,18 +101,175 @@ impl MainArea {
                 if ui.button(RichText::new(format!("{} Replace", regular::FILE_ARROW_UP))).on_hover_text("Replace selected with new audio").clicked() {
                     action_data.replace_new = true;
                 }
-                if ui.button(RichText::new(format!("{} Clear", regular::ERASER))).on_hover_text("Replace selected with empty WAV").clicked() {
+                let clear_button = ui
+                    .button(RichText::new(format!("{} Clear", regular::ERASER)))
+                    .on_hover_text(format!(
+                        "Replace selected with {} ms of silence (right-click to change)",
+                        self.empty_wav_min_ms
+                    ));
+                if clear_button.clicked() {
                     action_data.replace_empty = true;
                 }
+                clear_button.context_menu(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Minimum silence:");
+                        ui.add(
+                            egui::DragValue::new(&mut self.empty_wav_min_ms)
+                                .range(0..=empty_wav::MAX_MIN_SILENCE_MS)
+                                .suffix(" ms"),
+                        );
+                    });
+                    ui.label(
+                        RichText::new("The silence is matched to each track's sample rate and channels on save.")
+                            .size(11.0)
+                            .weak(),
+                    );
+                });
                 if ui.button(RichText::new(format!("{} Remove", regular::TRASH))).on_hover_text("Remove selected items").clicked() {
                     action_data.remove_selected = true;
                 }
+                if ui
+                    .button(RichText::new(format!("{} Review", regular::HEADPHONES)))
+                    .on_hover_text("Play the selected tracks one by one and grade each with a key")
+                    .clicked()
+                {
+                    action_data.review_selected = true;
+                }
             });
             
             ui.separator();
 
+            // Column chooser for the optional metadata columns
+            ui.menu_button("Columns", |ui| {
+                for column in MetadataColumn::ALL {
+                    let mut shown = self.metadata_columns.contains(&column);
+                    if ui.checkbox(&mut shown, column.long_name()).changed() {
+                        self.metadata_columns.retain(|c| *c != column);
+                        if shown {
+                            self.metadata_columns.push(column);
+                            self.metadata_columns.sort();
+                        } else if MetadataColumn::of_sort_column(self.sort_column) == Some(column) {
+                            self.sort_column = SortColumn::None;
+                        }
+                    }
+                }
+                ui.label(
+                    RichText::new("Read from each payload's WAV, OPUS or IDSP header; entropy from its bytes")
+                        .size(11.0)
+                        .weak(),
+                );
+            });
+
             // More Actions
             ui.menu_button("More", |ui| {
+                if ui.button("Add Audio from URL...").on_hover_text("Download a new audio file from an http(s) link").clicked() {
+                    self.url_import_modal.open_for(UrlImportTarget::Add);
+                    ui.close();
+                }
+                let paste_label = match &self.copied_track {
+                    Some(copied) => format!(
+                        "Paste Track \"{}\" from {}",
+                        copied.name,
+                        std::path::Path::new(&copied.source_file)
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy()
+                    ),
+                    None => "Paste Track".to_string(),
+                };
+                if ui
+                    .add_enabled(self.copied_track.is_some(), egui::Button::new(paste_label))
+                    .on_hover_text("Add the track copied from a row's menu as a new track")
+                    .clicked()
+                {
+                    self.paste_track();
+                    ui.close();
+                }
+                if ui.button("Trigger Pads").on_hover_text("Play assigned tracks with the number keys 1-9").clicked() {
+                    self.pad_grid.open = true;
+                    ui.close();
+                }
+                let is_nus3audio = self.selected_file.as_deref().is_some_and(|p| p.to_lowercase().ends_with(".nus3audio"));
+                if ui
+                    .add_enabled(is_nus3audio, egui::Button::new("File Structure..."))
+                    .on_hover_text("Sections, name string table and entry order of the .nus3audio")
+                    .clicked()
+                {
+                    if let Some(path) = self.selected_file.clone() {
+                        self.nus3audio_layout_modal.open_with(&path, Nus3audioFileUtils::pending_order());
+                    }
+                    ui.close();
+                }
+                if ui.button("Bank Statistics...").on_hover_text("Codecs, total duration, sizes and levels of the opened file").clicked() {
+                    if let (Some(path), Some(files)) = (self.selected_file.clone(), self.audio_files.clone()) {
+                        self.stats_modal.open_for(&path, files);
+                    }
+                    ui.close();
+                }
+                if ui
+                    .button("Normalize All...")
+                    .on_hover_text("Measure the loudness of every WAV track and gain them all to one LUFS target")
+                    .clicked()
+                {
+                    if let (Some(path), Some(mut files)) = (self.selected_file.clone(), self.audio_files.clone()) {
+                        files.retain(|f| !self.is_locked(f));
+                        self.normalize_all_modal.open_for(&path, files);
+                    }
+                    ui.close();
+                }
+                if ui.button("Intro/Loop Pairs...").on_hover_text("Preview and replace tracks split into an intro and a loop part").clicked() {
+                    if let Some(files) = &self.audio_files {
+                        self.intro_loop_modal.open_for(files);
+                    }
+                    ui.close();
+                }
+                ui.separator();
+                if ui
+                    .button("Copy Table to Clipboard")
+                    .on_hover_text("Copy the rows and columns shown, in their current order, as tab-separated text")
+                    .clicked()
+                {
+                    let view = TableView::of(&filtered_audio_files, self.track_labels.as_ref());
+                    ui.ctx().copy_text(view.to_tsv());
+                    self.add_toast(format!("Copied {} rows to the clipboard", view.rows.len()), Color32::GREEN);
+                    ui.close();
+                }
+                if ui
+                    .button("Export View as CSV...")
+                    .on_hover_text("Save the rows and columns shown, in their current order, as CSV")
+                    .clicked()
+                {
+                    let stem = self
+                        .selected_file
+                        .as_deref()
+                        .and_then(|f| std::path::Path::new(f).file_stem())
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_else(|| "table".to_string());
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .set_file_name(format!("{}_view.csv", stem))
+                        .set_directory(self.output_path.clone().unwrap_or_else(|| ".".to_string()))
+                        .save_file()
+                    {
+                        let view = TableView::of(&filtered_audio_files, self.track_labels.as_ref());
+                        match view.write_csv(&path.to_string_lossy()) {
+                            Ok(()) => self.add_toast(
+                                format!("Wrote {} rows to {}", view.rows.len(), path.display()),
+                                Color32::GREEN,
+                            ),
+                            Err(e) => self.add_toast(e, Color32::RED),
+                        }
+                    }
+                    ui.close();
+                }
+                if ui
+                    .button(format!("Notifications ({})", self.notification_center.len()))
+                    .on_hover_text("Every message shown this session, including ones merged into summaries")
+                    .clicked()
+                {
+                    self.notification_center.open = true;
+                    ui.close();
+                }
                 if ui.button("Debug: Convert All to WAV").on_hover_text("Convert all tracks to PCM16 WAV in memory (NUS3BANK only)").clicked() {
                     action_data.debug_convert_all_wav = true;
                     ui.close();
@@ -120,7 +286,7 @@ impl MainArea {
                     );
                 }
                 
-                if !self.search_query.is_empty() {
+                if !self.search_query.is_empty() || self.label_filter.is_some() || self.empty_filter || !self.quick_filters.is_empty() {
                     ui.label(RichText::new(format!("Found {} / {}", files_count, self.file_count.unwrap_or(0))).weak());
                 }
             });
@@ -137,6 +303,31 @@ impl MainArea {
                 .map(|audio| format!("{}:{}", audio.name, audio.id))
         });
 
+        if let Some(step) = self.pending_row_step.take() {
+            self.step_row_selection(&filtered_audio_files, step);
+        }
+
+        let scroll_to_row = self
+            .scroll_to_key
+            .take()
+            .and_then(|key| filtered_audio_files.iter().position(|f| f.track_key() == key));
+
+        let row_labels = self
+            .selected_file
+            .as_deref()
+            .map(|bank| self.color_labels.for_bank(bank))
+            .unwrap_or_default();
+        let locked_rows = self
+            .selected_file
+            .as_deref()
+            .map(|bank| self.locked_tracks.for_bank(bank))
+            .unwrap_or_default();
+
+        let unplayable = self.unplayable_rows(&filtered_audio_files);
+
+        // Dialogs take dropped files themselves while they are open
+        let accept_drops = !self.is_modal_dialog_open();
+
         // The actual table rendering - capture actions but don't execute them yet
         TableRenderer::render_table(
             ui,
@@ -144,9 +335,20 @@ impl MainArea {
             &mut self.selected_rows,
             &mut self.selected_items,
             now_playing_key.as_deref(),
+            scroll_to_row,
+            &row_labels,
+            &locked_rows,
+            self.track_labels.as_ref(),
+            &self.transcripts,
+            &self.track_categories,
+            &self.empty_tracks,
+            &unplayable,
+            &self.track_formats,
+            &self.metadata_columns,
             self.striped,
             self.clickable,
             self.show_grid_lines,
+            accept_drops,
             available_height - 40.0, // Account for actions bar
             available_width,
             &mut |index| {
@@ -161,15 +363,42 @@ impl MainArea {
             &mut |index| {
                 action_data.remove_index = Some(index);
             },
+            &mut |index, action| {
+                action_data.row_menu = Some((index, action));
+            },
             &mut self.sort_column,
             &mut self.sort_ascending,
         );
 
+        let dropped = if accept_drops { file_drop::dropped_audio_files(ui.ctx()) } else { Vec::new() };
+        if !dropped.is_empty() {
+            let row = match action_data.row_menu {
+                Some((idx, RowMenuAction::FilesDropped)) => filtered_audio_files.get(idx).cloned(),
+                _ => None,
+            };
+            self.handle_dropped_files(dropped, row);
+        }
+
+        // Review the selected tracks in table order; selected rows hidden by the search go last
+        if action_data.review_selected {
+            let is_selected = |f: &AudioFileInfo| self.selected_items.contains(&format!("{}:{}", f.name, f.id));
+            let mut tracks: Vec<AudioFileInfo> = filtered_audio_files.iter().filter(|f| is_selected(f)).cloned().collect();
+            for file in self.audio_files.iter().flatten().filter(|f| is_selected(f)) {
+                if !tracks.iter().any(|t| t.name == file.name && t.id == file.id) {
+                    tracks.push(file.clone());
+                }
+            }
+            self.review_modal.open_for(tracks);
+        }
+
         // Map captured actions to class members for processing
         if action_data.replace_new {
-            if let Some(ref audio_files) = self.audio_files {
+            let (unlocked, locked) = self.unlocked_selection();
+            if unlocked.is_empty() && locked > 0 {
+                self.add_toast(format!("All {} selected track(s) are locked", locked), Color32::GOLD);
+            } else if let Some(ref audio_files) = self.audio_files {
                 let mut representative: Option<AudioFileInfo> = None;
-                for key in self.selected_items.iter() {
+                for key in unlocked.iter() {
                     if let Some((name, id)) = key.split_once(':') {
                         if let Some(info) = audio_files.iter().find(|f| f.name == name && f.id == id) {
                             representative = Some(info.clone());
@@ -182,6 +411,7 @@ impl MainArea {
                     match ReplaceUtils::replace_with_file_dialog(&rep, &mut self.loop_settings_modal) {
                         Ok(_) => {
                             self.pending_replace_new = true;
+                            self.check_batch_sample_rates(&rep);
                         }
                         Err(e) => {
                             self.add_toast(format!("Replace failed: {}", e), Color32::RED);
@@ -192,25 +422,21 @@ impl MainArea {
         }
         
         if action_data.replace_empty {
+            let (unlocked, locked) = self.unlocked_selection();
             self.pending_replace_empty = true;
             self.confirm_modal.open(
                 "Confirm Replace with Empty WAV",
                 &format!(
-                    "This will replace the audio data of {} selected file(s) with empty WAV. Names and IDs will be preserved. Continue?",
-                    selected_count
+                    "This will replace the audio data of {} selected file(s) with {} ms of silence. Names and IDs will be preserved.{} Continue?",
+                    unlocked.len(),
+                    self.empty_wav_min_ms,
+                    locked_note(locked)
                 ),
             );
         }
 
         if action_data.remove_selected {
-            self.pending_remove_selected = true;
-            self.confirm_modal.open(
-                "Confirm Remove Selected",
-                &format!(
-                    "This will mark {} selected item(s) for deletion (in memory only). Continue?",
-                    selected_count
-                ),
-            );
+            self.request_remove_selected();
         }
 
         if action_data.debug_convert_all_wav {
@@ -307,19 +533,24 @@ impl MainArea {
 
         // Handle "Export All" confirm dialog if clicked
         if action_data.export_all_confirm {
-            let file_count = if let Some(ref audio_files) = self.audio_files {
-                audio_files.len()
-            } else {
-                0
-            };
-            
-            // Set the pending export all flag
-            self.pending_export_all = true;
-            
-            self.confirm_modal.open(
-                "Confirm Export All",
-                &format!("Are you sure you want to export all {} audio files? This may take some time.", file_count)
-            );
+            let file_count = self.audio_files.as_ref().map_or(0, |files| files.len());
+            let selected: Vec<AudioFileInfo> = self
+                .audio_files
+                .iter()
+                .flatten()
+                .filter(|f| self.selected_items.contains(&format!("{}:{}", f.name, f.id)))
+                .cloned()
+                .collect();
+            let banks = self.file_tabs.tabs().iter().map(|tab| tab.path.clone()).collect();
+            self.export_all_modal
+                .open(file_count, filtered_audio_files.clone(), selected, banks);
+        }
+
+        if let Some(banks) = self.export_all_modal.take_confirmed_banks() {
+            self.run_export_opened_banks(banks, &mut toasts_to_add);
+        }
+        if let Some(tracks) = self.export_all_modal.take_confirmed() {
+            self.run_export_all(tracks, &mut toasts_to_add);
         }
 
         // Handle "Export" action for a specific file if clicked
@@ -332,13 +563,33 @@ impl MainArea {
                 if let Some(file_path) = &selected_file {
                     if let Some(output_dir) = &output_path {
                         match ExportUtils::export_to_wav_with_custom_dir_unified(
-                            audio_info, file_path, output_dir,
+                            audio_info,
+                            file_path,
+                            output_dir,
+                            self.embed_export_metadata,
                         ) {
                             Ok(path) => {
-                                toasts_to_add.push((
-                                    format!("Successfully exported to: {}", path),
-                                    Color32::GREEN,
-                                ));
+                                // Timestamps are measured on the WAV before it is encoded
+                                if self.export_lip_sync_timestamps {
+                                    if let Err(e) = lip_sync::write_for_wav(&path) {
+                                        toasts_to_add.push((format!("Timestamps failed: {}", e), Color32::RED));
+                                    }
+                                }
+                                match self.export_format.encode_exported(&path) {
+                                    Ok(path) => toasts_to_add.push((
+                                        format!("Successfully exported to: {}", path),
+                                        Color32::GREEN,
+                                    )),
+                                    Err(e) => toasts_to_add.push((
+                                        format!(
+                                            "Exported {} but {} encoding failed: {}",
+                                            path,
+                                            self.export_format.format.name(),
+                                            e
+                                        ),
+                                        Color32::RED,
+                                    )),
+                                }
                             }
                             Err(e) => {
                                 toasts_to_add.push((format!("Export failed: {}", e), Color32::RED));
@@ -356,7 +607,12 @@ impl MainArea {
 
         // Handle "Play" action if clicked
         if let Some(idx) = action_data.play_index {
-            if idx < filtered_audio_files.len() {
+            let reason = filtered_audio_files
+                .get(idx)
+                .and_then(|info| unplayable.get(&info.track_key()));
+            if let Some(reason) = reason {
+                toasts_to_add.push((reason.to_string(), Color32::GOLD));
+            } else if idx < filtered_audio_files.len() {
                 let audio_info = &filtered_audio_files[idx];
                 let audio_name = audio_info.name.clone();
                 let file_path = self.selected_file.clone();
@@ -450,6 +706,145 @@ impl MainArea {
             }
         }
 
+        // Handle loop settings clipboard actions from the row menu
+        if let Some((idx, action)) = action_data.row_menu {
+            if idx < filtered_audio_files.len() {
+                let audio_info = filtered_audio_files[idx].clone();
+                match action {
+                    RowMenuAction::CopyLoopSettings => {
+                        match ReplaceUtils::get_pending_loop_settings(&audio_info) {
+                            Some(settings) => match loop_clipboard::copy(&settings) {
+                                Ok(_) => toasts_to_add.push((
+                                    format!(
+                                        "Copied loop settings of {} ({})",
+                                        audio_info.name,
+                                        loop_clipboard::describe(&settings)
+                                    ),
+                                    Color32::GREEN,
+                                )),
+                                Err(e) => toasts_to_add.push((e, Color32::RED)),
+                            },
+                            None => toasts_to_add.push((
+                                format!(
+                                    "{} has no pending replacement; loop settings can only be copied from replaced tracks",
+                                    audio_info.name
+                                ),
+                                Color32::GOLD,
+                            )),
+                        }
+                    }
+                    RowMenuAction::PasteLoopSettings => {
+                        self.paste_loop_settings(&audio_info, &mut toasts_to_add);
+                    }
+                    RowMenuAction::EditTranscript => {
+                        let current = self.transcripts.get(&audio_info.track_key());
+                        self.transcript_modal.open_for(&audio_info, current);
+                    }
+                    RowMenuAction::Mix => self.mix_modal.open_for(&audio_info),
+                    RowMenuAction::EditReplacement => match (
+                        ReplaceUtils::get_replacement_path(&audio_info),
+                        ReplaceUtils::get_pending_loop_settings(&audio_info),
+                    ) {
+                        (Some(source), Some(settings)) => {
+                            // Confirming re-runs the single replace flow from the stored source
+                            self.pending_replace_new = false;
+                            self.loop_settings_modal.open_for_edit(audio_info.clone(), &source, settings);
+                        }
+                        _ => toasts_to_add.push((
+                            format!("{} has no queued replacement settings to edit", audio_info.name),
+                            Color32::GOLD,
+                        )),
+                    },
+                    RowMenuAction::BlindTest => {
+                        if let Err(e) = self.open_blind_test(&audio_info) {
+                            toasts_to_add.push((format!("Cannot start blind test: {}", e), Color32::RED));
+                        }
+                    }
+                    RowMenuAction::CopyTrack => match self.copy_track(&audio_info) {
+                        Ok(()) => toasts_to_add.push((
+                            format!("Copied {}; paste it into another file from More > Paste Track", audio_info.name),
+                            Color32::GREEN,
+                        )),
+                        Err(e) => toasts_to_add.push((format!("Failed to copy {}: {}", audio_info.name, e), Color32::RED)),
+                    },
+                    RowMenuAction::Duplicate => self.open_duplicate(&audio_info),
+                    RowMenuAction::ReplaceFromUrl => {
+                        self.url_import_modal.open_for(UrlImportTarget::Replace(audio_info.clone()));
+                    }
+                    RowMenuAction::RevealReplacementSource | RowMenuAction::OpenReplacementSource => {
+                        match ReplaceUtils::get_replacement_path(&audio_info) {
+                            Some(source) => {
+                                let result = if action == RowMenuAction::RevealReplacementSource {
+                                    path_utils::reveal_in_file_manager(&source)
+                                } else {
+                                    path_utils::open_with_default_app(&source)
+                                };
+                                if let Err(e) = result {
+                                    toasts_to_add.push((e, Color32::RED));
+                                }
+                            }
+                            None => toasts_to_add.push((
+                                format!("{} has no pending replacement from a file", audio_info.name),
+                                Color32::GOLD,
+                            )),
+                        }
+                    }
+                    RowMenuAction::SetColorLabel(label) => {
+                        if let Some(bank) = self.selected_file.clone() {
+                            for info in self.clicked_rows(&audio_info) {
+                                self.color_labels.set(&bank, &info.track_key(), label);
+                            }
+                        }
+                    }
+                    RowMenuAction::SetLocked(locked) => {
+                        if let Some(bank) = self.selected_file.clone() {
+                            for info in self.clicked_rows(&audio_info) {
+                                self.locked_tracks.set(&bank, &info.track_key(), locked);
+                            }
+                        }
+                    }
+                    RowMenuAction::AssignPad(slot) => match self.assign_pad(slot, &audio_info) {
+                        Ok(()) => toasts_to_add.push((
+                            format!("Assigned {} to pad {}", audio_info.name, slot + 1),
+                            Color32::GREEN,
+                        )),
+                        Err(e) => toasts_to_add.push((format!("Cannot assign pad: {}", e), Color32::RED)),
+                    },
+                    RowMenuAction::MoveUp | RowMenuAction::MoveDown => {
+                        let moved = self.tone_slots().and_then(|(_, order, _)| {
+                            let current = Self::tone_position(&audio_info, &order)?;
+                            let target = if action == RowMenuAction::MoveUp {
+                                current.checked_sub(1)
+                            } else {
+                                Some(current + 1).filter(|&slot| slot < order.len())
+                            };
+                            match target {
+                                Some(slot) => self.move_tone(&audio_info, slot).map(Some),
+                                None => Ok(None),
+                            }
+                        });
+                        match moved {
+                            Ok(Some(slot)) => toasts_to_add.push((
+                                format!("{} gets ID 0x{:x} on the next save", audio_info.name, slot),
+                                Color32::GREEN,
+                            )),
+                            Ok(None) => {}
+                            Err(e) => toasts_to_add.push((format!("Cannot move {}: {}", audio_info.name, e), Color32::RED)),
+                        }
+                    }
+                    RowMenuAction::EditId => match self.tone_slots() {
+                        Ok((_, order, names)) => match Self::tone_position(&audio_info, &order) {
+                            Ok(current) => self.tone_id_modal.open_with(&audio_info, current, names),
+                            Err(e) => toasts_to_add.push((e, Color32::RED)),
+                        },
+                        Err(e) => toasts_to_add.push((format!("Cannot edit the ID of {}: {}", audio_info.name, e), Color32::RED)),
+                    },
+                    // Handled together with the dropped files above
+                    RowMenuAction::FilesDropped => {}
+                }
+            }
+        }
+
         // Handle "Remove" action if clicked
         if let Some(idx) = action_data.remove_index {
             if idx < filtered_audio_files.len() {
@@ -480,43 +875,11 @@ impl MainArea {
             // Reset the confirmed state
             self.confirm_modal.reset_state();
             
-            // If there is a pending export all action, perform the export
-            if self.pending_export_all {
-                self.pending_export_all = false;
-                
-                let selected_file = self.selected_file.clone();
-                let output_path = self.output_path.clone();
-
-                if let Some(file_path) = &selected_file {
-                    if let Some(output_dir) = &output_path {
-                        // Use ExportUtils to export all files
-                        match ExportUtils::export_all_to_wav_unified(file_path, output_dir) {
-                            Ok(paths) => {
-                                toasts_to_add.push((
-                                    format!(
-                                        "Successfully exported {} files to: {}",
-                                        paths.len(),
-                                        output_dir
-                                    ),
-                                    Color32::GREEN,
-                                ));
-                            }
-                            Err(e) => {
-                                toasts_to_add.push((format!("Export failed: {}", e), Color32::RED));
-                            }
-                        }
-                    } else {
-                        toasts_to_add.push((
-                            "No output directory set. Please set an output directory.".to_string(),
-                            Color32::GOLD,
-                        ));
-                    }
-                }
-            }
             // If there is a pending replace with empty wav action, perform it
-            else if self.pending_replace_empty {
+            if self.pending_replace_empty {
                 self.pending_replace_empty = false;
 
+                let (unlocked, _) = self.unlocked_selection();
                 if let Some(file_path) = &self.selected_file {
                     // Replace for each selected item that exists in current full list
                     let mut replaced = 0usize;
@@ -528,10 +891,10 @@ impl MainArea {
                             index_by_key.insert(format!("{}:{}", f.name, f.id), i);
                         }
 
-                        for key in self.selected_items.clone().into_iter() {
+                        for key in unlocked {
                             if let Some(&idx) = index_by_key.get(&key) {
                                 let audio_info = audio_files[idx].clone();
-                                match ReplaceUtils::replace_with_empty_wav_in_memory(&audio_info, file_path) {
+                                match ReplaceUtils::replace_with_empty_wav_in_memory(&audio_info, file_path, self.empty_wav_min_ms) {
                                     Ok(new_info) => {
                                         audio_files[idx] = new_info;
                                         replaced += 1;
@@ -563,7 +926,7 @@ impl MainArea {
             else if self.pending_debug_convert_all_wav {
                 self.pending_debug_convert_all_wav = false;
 
-                let selected_file_path = match self.selected_file.as_deref() {
+                let selected_file_path = match self.selected_file.clone() {
                     Some(p) => p,
                     None => {
                         toasts_to_add.push(("No file selected".to_string(), Color32::GOLD));
@@ -579,80 +942,16 @@ impl MainArea {
                     return;
                 }
 
-                // Load current bank to read original payloads.
-                let bank = match crate::nus3bank::structures::Nus3bankFile::open(selected_file_path) {
-                    Ok(f) => f,
+                let (converted, skipped, failures) = match self.convert_tracks_to_pcm16(&selected_file_path, false) {
+                    Ok(result) => result,
                     Err(e) => {
-                        toasts_to_add.push((format!("Failed to open .nus3bank: {}", e), Color32::RED));
+                        toasts_to_add.push((e, Color32::RED));
                         return;
                     }
                 };
-
-                use std::collections::HashMap;
-                let mut payload_by_hex: HashMap<String, Vec<u8>> = HashMap::new();
-                for (i, tone) in bank.tone.tones.iter().enumerate() {
-                    if tone.removed {
-                        continue;
-                    }
-                    let hex_id = format!("0x{:x}", i as u32);
-                    payload_by_hex.insert(hex_id, tone.payload.clone());
-                }
-
-                let mut converted = 0usize;
-                let mut skipped = 0usize;
-                let mut failed = 0usize;
-
-                if let Some(ref mut audio_files) = self.audio_files {
-                    for info in audio_files.iter_mut() {
-                        if !info.is_nus3bank {
-                            continue;
-                        }
-                        let hex_id = match info.hex_id.as_deref() {
-                            Some(h) => h,
-                            None => {
-                                failed += 1;
-                                continue;
-                            }
-                        };
-
-                        let source = super::replace_utils::ReplaceUtils::get_replacement_data_unified(info)
-                            .or_else(|| payload_by_hex.get(hex_id).cloned());
-
-                        let Some(source_bytes) = source else {
-                            failed += 1;
-                            continue;
-                        };
-
-                        if super::replace_utils::ReplaceUtils::is_standard_pcm16_wav(&source_bytes) {
-                            skipped += 1;
-                            continue;
-                        }
-
-                        match super::replace_utils::ReplaceUtils::convert_audio_bytes_to_pcm_wav(&source_bytes) {
-                            Ok(wav_bytes) => {
-                                // Stage replacement for export/save.
-                                let _ = crate::nus3bank::replace::Nus3bankReplacer::replace_track_in_memory(
-                                    selected_file_path,
-                                    hex_id,
-                                    wav_bytes.clone(),
-                                );
-                                // Update playback replacement cache.
-                                let key = format!("{}:{}", hex_id, info.name);
-                                let _ = super::replace_utils::ReplaceUtils::store_audio_data_for_playback(
-                                    key,
-                                    wav_bytes.clone(),
-                                );
-
-                                info.size = wav_bytes.len();
-                                info.file_type = "WAV".to_string();
-                                converted += 1;
-                            }
-                            Err(e) => {
-                                failed += 1;
-                                toasts_to_add.push((format!("Convert failed for {}: {}", info.name, e), Color32::RED));
-                            }
-                        }
-                    }
+                let failed = failures.len();
+                for failure in failures {
+                    toasts_to_add.push((failure, Color32::RED));
                 }
 
                 toasts_to_add.push((
@@ -675,11 +974,11 @@ impl MainArea {
                     }
                 };
 
+                // Work on a stable snapshot of selected keys; locked rows stay
+                let selected_keys = self.unlocked_selection().0;
                 if let Some(ref mut audio_files) = self.audio_files {
                     use std::collections::HashSet;
 
-                    // Work on a stable snapshot of selected keys.
-                    let selected_keys: Vec<String> = self.selected_items.iter().cloned().collect();
                     let mut removed_keys: HashSet<String> = HashSet::new();
                     let mut removed_count = 0usize;
 
@@ -771,9 +1070,7 @@ impl MainArea {
             // Process the case of cancelling the action
             self.confirm_modal.reset_state();
             
-            if self.pending_export_all {
-                self.pending_export_all = false;
-            } else if self.pending_replace_empty {
+            if self.pending_replace_empty {
                 self.pending_replace_empty = false;
             } else if self.pending_debug_convert_all_wav {
                 self.pending_debug_convert_all_wav = false;
@@ -809,7 +1106,7 @@ impl MainArea {
                 match AddAudioUtils::process_new_audio(&self.add_audio_modal, is_nus3bank) {
                     Ok(new_audio_info) => {
                         // 3. 尝试将音频转换为WAV格式
-                        match AddAudioUtils::convert_to_wav(original_file_path) {
+                        match AddAudioUtils::added_audio_data(&self.add_audio_modal) {
                             Ok(wav_data) => {
                                 // 4. 使用转换后的WAV数据注册添加操作
                                 let register_result = if new_audio_info.is_nus3bank {
@@ -821,6 +1118,7 @@ impl MainArea {
                                 
                                 match register_result {
                                     Ok(_) => {
+                                        Nus3audioFileUtils::record_added_source(&new_audio_info, original_file_path.into());
                                         // 5. 更新内存中的音频文件列表
                                         if let Some(ref mut audio_files) = self.audio_files {
                                             audio_files.push(new_audio_info.clone());
@@ -851,6 +1149,7 @@ impl MainArea {
                                     
                                     match fallback_result {
                                         Ok(_) => {
+                                            Nus3audioFileUtils::record_added_source(&new_audio_info, original_file_path.into());
                                             if let Some(ref mut audio_files) = self.audio_files {
                                                 audio_files.push(new_audio_info.clone());
                                                 self.file_count = Some(audio_files.len());
@@ -898,20 +1197,20 @@ impl MainArea {
                     };
 
                     let use_custom_loop = self.loop_settings_modal.settings.use_custom_loop;
-                    let enable_loop = self.loop_settings_modal.settings.enable_loop;
 
                     if self.pending_replace_new {
                         // Batch replace for all selected items using the chosen file and loop settings
                         self.pending_replace_new = false;
 
                         // Retrieve the file path chosen during the dialog (from representative)
-                        let rep_path_opt = ReplaceUtils::get_replacement_path(&audio_info.name, &audio_info.id);
+                        let rep_path_opt = ReplaceUtils::get_replacement_path(&audio_info);
                         if rep_path_opt.is_none() {
                             toasts_to_add.push(("No replacement file path found".to_string(), Color32::RED));
                             return;
                         }
                         let rep_path = rep_path_opt.unwrap();
                         let rep_path_ref = rep_path.as_path();
+                        let (unlocked, _) = self.unlocked_selection();
 
                         if let Some(ref mut audio_files) = self.audio_files {
                             use std::collections::HashMap;
@@ -920,19 +1219,23 @@ impl MainArea {
                                 index_by_key.insert(format!("{}:{}", f.name, f.id), i);
                             }
 
+                            // Left at its default, the format follows each target's own codec; a
+                            // format picked in the dialog applies to every target
+                            let encode_format = self.loop_settings_modal.encode_format;
+                            let overridden = encode_format != lopus_encoder::default_format(&audio_info, &self.lopus_encoder);
                             let mut replaced_count: usize = 0;
-                            for key in self.selected_items.clone().into_iter() {
+                            for key in unlocked {
                                 if let Some(&idx) = index_by_key.get(&key) {
                                     let target_info = audio_files[idx].clone();
-                                    match ReplaceUtils::process_replacement_with_loop_settings(
+                                    let format = if overridden { encode_format } else { lopus_encoder::default_format(&target_info, &self.lopus_encoder) };
+                                    let previous = ReplaceUtils::get_replacement_data_unified(&target_info);
+                                    match ReplaceUtils::process_replacement_with_settings(
                                         &target_info,
                                         Some(rep_path_ref),
-                                        loop_start,
-                                        loop_end,
-                                        use_custom_loop,
-                                        enable_loop,
-                                        self.loop_settings_modal.settings.gain_db,
-                                    ) {
+                                        &self.loop_settings_modal.settings,
+                                    )
+                                    .and_then(|info| self.lopus_encoder.for_bank(self.selected_file.as_deref().unwrap_or_default()).encode_queued(&info, format, previous))
+                                    {
                                         Ok(new_audio_info) => {
                                             audio_files[idx] = new_audio_info;
                                             replaced_count += 1;
@@ -959,7 +1262,7 @@ impl MainArea {
                                 if let Some(replacement_data) =
                                     ReplaceUtils::get_replacement_data_unified(audio_info)
                                 {
-                                    match ExportUtils::write_temp_audio_bytes(
+                                    match ExportUtils::write_playable_temp_audio(
                                         audio_info,
                                         &replacement_data,
                                         "replacement",
@@ -1012,15 +1315,15 @@ impl MainArea {
 
                         // Use the stored file path instead of asking the user to reselect the file
                         // Process the replacement with the confirmed loop settings
-                        match ReplaceUtils::process_replacement_with_loop_settings(
+                        let format = self.loop_settings_modal.encode_format;
+                        let previous = ReplaceUtils::get_replacement_data_unified(audio_info);
+                        match ReplaceUtils::process_replacement_with_settings(
                             audio_info,
                             None, // Pass None to use the stored file path
-                            loop_start,
-                            loop_end,
-                            use_custom_loop,
-                            enable_loop,
-                            self.loop_settings_modal.settings.gain_db,
-                        ) {
+                            &self.loop_settings_modal.settings,
+                        )
+                        .and_then(|info| self.lopus_encoder.for_bank(self.selected_file.as_deref().unwrap_or_default()).encode_queued(&info, format, previous))
+                        {
                             Ok(new_audio_info) => {
                                 // Update the audio file in memory
                                 if let Some(ref mut audio_files) = self.audio_files {
@@ -1034,7 +1337,7 @@ impl MainArea {
                                         if let Some(replacement_data) =
                                             ReplaceUtils::get_replacement_data_unified(audio_info)
                                         {
-                                            match ExportUtils::write_temp_audio_bytes(
+                                            match ExportUtils::write_playable_temp_audio(
                                                 audio_info,
                                                 &replacement_data,
                                                 "replacement",
@@ -1112,6 +1415,782 @@ impl MainArea {
         }
     }
 
+    /// Paste the copied loop settings onto a track's pending replacement.
+    /// When the track is part of the selection, every selected track is updated.
+    fn paste_loop_settings(
+        &mut self,
+        audio_info: &AudioFileInfo,
+        toasts_to_add: &mut Vec<(String, Color32)>,
+    ) {
+        let copied = match loop_clipboard::get() {
+            Some(settings) => settings,
+            None => {
+                toasts_to_add.push(("No loop settings have been copied".to_string(), Color32::GOLD));
+                return;
+            }
+        };
+
+        let clicked_key = format!("{}:{}", audio_info.name, audio_info.id);
+        let target_keys: Vec<String> = if self.selected_items.contains(&clicked_key) {
+            let (unlocked, locked) = self.unlocked_selection();
+            if locked > 0 {
+                toasts_to_add.push((format!("Skipped {} locked track(s)", locked), Color32::GOLD));
+            }
+            unlocked
+        } else {
+            vec![clicked_key]
+        };
+
+        let audio_files = match self.audio_files.as_mut() {
+            Some(files) => files,
+            None => return,
+        };
+
+        let mut applied = 0usize;
+        let mut skipped = 0usize;
+        for key in target_keys {
+            let idx = match audio_files
+                .iter()
+                .position(|f| format!("{}:{}", f.name, f.id) == key)
+            {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let target_info = audio_files[idx].clone();
+            let mut settings = match ReplaceUtils::get_pending_loop_settings(&target_info) {
+                Some(settings) => settings,
+                None => {
+                    skipped += 1;
+                    continue;
+                }
+            };
+            loop_clipboard::paste_into(&mut settings, &copied);
+
+            match ReplaceUtils::reapply_loop_settings(&target_info, &settings) {
+                Ok(new_info) => {
+                    audio_files[idx] = new_info;
+                    applied += 1;
+                }
+                Err(e) => {
+                    toasts_to_add.push((
+                        format!("Failed to paste loop settings on {}: {}", target_info.name, e),
+                        Color32::RED,
+                    ));
+                }
+            }
+        }
+
+        if applied > 0 {
+            toasts_to_add.push((
+                format!(
+                    "Pasted loop settings ({}) on {} track(s)",
+                    loop_clipboard::describe(&copied),
+                    applied
+                ),
+                Color32::GREEN,
+            ));
+        }
+        if skipped > 0 {
+            toasts_to_add.push((
+                format!(
+                    "Skipped {} track(s) without a pending replacement; replace them first",
+                    skipped
+                ),
+                Color32::GOLD,
+            ));
+        }
+    }
+
+    /// Prepare the original and replacement audio of a track and open the blind test
+    fn open_blind_test(&mut self, audio_info: &AudioFileInfo) -> Result<(), String> {
+        let file_path = self.selected_file.clone().ok_or("No file selected")?;
+        let replacement_data = ReplaceUtils::get_replacement_data_unified(audio_info)
+            .ok_or_else(|| format!("{} has no pending replacement", audio_info.name))?;
+
+        let original_path = ExportUtils::convert_to_wav_temp_path(audio_info, &file_path)?;
+        let original_data = std::fs::read(&original_path)
+            .map_err(|e| format!("Failed to read converted original: {}", e));
+        let _ = std::fs::remove_file(&original_path);
+        let original_data = original_data?;
+
+        let take = ReplaceUtils::get_replacement_path(audio_info)
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .unwrap_or_else(|| audio_info.filename.clone());
+
+        self.blind_test_modal
+            .open(audio_info.clone(), take, original_data, replacement_data);
+        Ok(())
+    }
+
+    /// Start a confirmed Export All in the background: the whole file when `tracks` is `None`,
+    /// otherwise only those tracks
+    fn run_export_all(&mut self, tracks: Option<Vec<AudioFileInfo>>, toasts_to_add: &mut Vec<(String, Color32)>) {
+        let Some(file_path) = self.selected_file.clone() else {
+            return;
+        };
+        let Some(output_dir) = self.output_path.clone() else {
+            toasts_to_add.push((
+                "No output directory set. Please set an output directory.".to_string(),
+                Color32::GOLD,
+            ));
+            return;
+        };
+        if self.export_progress_modal.is_running() {
+            toasts_to_add.push(("An export is already running".to_string(), Color32::GOLD));
+            return;
+        }
+
+        let request = ExportRequest {
+            file_path,
+            output_dir,
+            tracks,
+            embed_metadata: self.embed_export_metadata,
+            lip_sync_timestamps: self.export_lip_sync_timestamps,
+            format: self.export_format.clone(),
+            resume: false,
+        };
+        match export_journal::resumable(&request) {
+            Some((done, total)) => self.export_progress_modal.offer_resume(request, done, total),
+            None => self.export_progress_modal.start(request),
+        }
+    }
+
+    /// Rows whose Play is disabled and why: stubs and truncated payloads, unless a pending
+    /// replacement gives them something to play
+    fn unplayable_rows(&self, files: &[AudioFileInfo]) -> HashMap<TrackKey, &'static str> {
+        let mut rows = HashMap::new();
+        if !self.empty_tracks.values().any(|kind| kind.unplayable_reason().is_some()) {
+            return rows;
+        }
+        let replaced: Vec<_> = ReplaceUtils::get_replacement_sizes().into_iter().map(|(key, _)| key).collect();
+        for file in files {
+            let key = file.track_key();
+            let reason = self.empty_tracks.get(&key).and_then(|kind| kind.unplayable_reason());
+            if let Some(reason) = reason.filter(|_| !replaced.contains(&key)) {
+                rows.insert(key, reason);
+            }
+        }
+        rows
+    }
+
+    /// Export every opened bank as saved, one subfolder per bank mirroring their source folders
+    fn run_export_opened_banks(&mut self, banks: Vec<String>, toasts_to_add: &mut Vec<(String, Color32)>) {
+        let Some(output_dir) = self.output_path.clone() else {
+            toasts_to_add.push((
+                "No output directory set. Please set an output directory.".to_string(),
+                Color32::GOLD,
+            ));
+            return;
+        };
+        if self.export_progress_modal.is_running() {
+            toasts_to_add.push(("An export is already running".to_string(), Color32::GOLD));
+            return;
+        }
+
+        let dirs = path_utils::mirrored_output_dirs(&banks, &output_dir);
+        let requests = banks
+            .into_iter()
+            .zip(dirs)
+            .map(|(file_path, dir)| ExportRequest {
+                file_path,
+                output_dir: dir.to_string_lossy().to_string(),
+                tracks: None,
+                embed_metadata: self.embed_export_metadata,
+                lip_sync_timestamps: self.export_lip_sync_timestamps,
+                format: self.export_format.clone(),
+                resume: false,
+            })
+            .collect();
+        self.export_progress_modal.start_all(output_dir, requests);
+    }
+
+    /// Decode the audio a track currently plays (including pending replacements) into a pad
+    fn assign_pad(&mut self, slot: usize, audio_info: &AudioFileInfo) -> Result<(), String> {
+        let file_path = self.selected_file.clone().ok_or("No file selected")?;
+        let audio_player = self.audio_player.as_mut().ok_or("Audio player not available")?;
+
+        let playback_path = crate::ui::audio_player::AudioPlayer::prepare_playback_path(audio_info, &file_path)?;
+        let result = {
+            let state = audio_player.get_audio_state();
+            let mut state = state.lock().unwrap();
+            state.load_pad(slot, &playback_path)
+        };
+        // The pad keeps the decoded samples, so the temp file is no longer needed
+        let _ = std::fs::remove_file(&playback_path);
+        result?;
+
+        self.pad_grid.assign(slot, audio_info.clone());
+        Ok(())
+    }
+
+    /// Compare the chosen batch replacement with the original sample rate of every selected track
+    /// and show the differences in the loop settings modal before confirming
+    fn check_batch_sample_rates(&mut self, representative: &AudioFileInfo) {
+        let (Some(file_path), Some(audio_files)) = (&self.selected_file, &self.audio_files) else {
+            return;
+        };
+        let Some(source) = ReplaceUtils::get_replacement_path(representative) else {
+            return;
+        };
+        let targets: Vec<AudioFileInfo> = audio_files
+            .iter()
+            .filter(|f| self.selected_items.contains(&format!("{}:{}", f.name, f.id)))
+            .cloned()
+            .collect();
+        self.loop_settings_modal.rate_report = None;
+        self.loop_settings_modal.rate_check = Some(RateCheck::start(targets, file_path.clone(), source));
+    }
+
+    /// Redo the batch sample rate check after a file was dropped onto the loop settings modal
+    pub fn handle_replacement_source_drop(&mut self) {
+        if !self.loop_settings_modal.take_source_changed() {
+            return;
+        }
+        if let Some(info) = self.loop_settings_modal.audio_info.clone() {
+            if self.pending_replace_new {
+                self.check_batch_sample_rates(&info);
+            }
+            self.add_toast(format!("Replacement source changed to {}", info.filename), Color32::GOLD);
+        }
+    }
+
+    /// Queue `info` as a new track made from `source`, converted to WAV when vgmstream can
+    /// read it and added as-is otherwise
+    pub fn queue_addition(&mut self, info: AudioFileInfo, source: &std::path::Path) -> Result<(), String> {
+        let bank = self.selected_file.clone().ok_or("No file is open")?;
+        let data = AddAudioUtils::convert_to_wav(&source.to_string_lossy())
+            .or_else(|_| std::fs::read(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e)))?;
+        if info.is_nus3bank {
+            Nus3audioFileUtils::register_add_nus3bank(&bank, &info, data)?;
+        } else {
+            Nus3audioFileUtils::register_add_audio(&info, data)?;
+        }
+        Nus3audioFileUtils::record_added_source(&info, source.to_path_buf());
+        if let Some(files) = self.audio_files.as_mut() {
+            files.push(info);
+            self.file_count = Some(files.len());
+        }
+        Ok(())
+    }
+
+    /// Add the tracks confirmed in the bulk add dialog
+    pub fn handle_bulk_add(&mut self) {
+        let Some(entries) = self.bulk_add_modal.take_confirmed() else {
+            return;
+        };
+        let is_nus3bank = self
+            .selected_file
+            .as_deref()
+            .is_some_and(|f| f.to_lowercase().ends_with(".nus3bank"));
+        let mut added = 0;
+        for entry in entries {
+            let source = entry.path.to_string_lossy().to_string();
+            let size = std::fs::metadata(&entry.path).map(|m| m.len() as usize).unwrap_or(0);
+            let result = AddAudioUtils::new_audio_info(&source, entry.name.clone(), &entry.id, size, is_nus3bank)
+                .and_then(|info| self.queue_addition(info, &entry.path));
+            match result {
+                Ok(()) => added += 1,
+                Err(e) => self.add_toast(format!("Failed to add {}: {}", entry.name, e), Color32::RED),
+            }
+        }
+        if added > 0 {
+            self.add_toast(format!("Added {} new track(s)", added), Color32::GREEN);
+        }
+    }
+
+    /// Route audio files dropped onto the table: one file on a row replaces that track,
+    /// one file elsewhere opens Add Audio and several files open the bulk add dialog
+    fn handle_dropped_files(&mut self, dropped: Vec<std::path::PathBuf>, row: Option<AudioFileInfo>) {
+        if self.selected_file.is_none() {
+            self.add_toast("Open a file before dropping audio onto it".to_string(), Color32::GOLD);
+            return;
+        }
+        match (dropped.as_slice(), row) {
+            ([path], Some(audio_info)) => {
+                self.pending_replace_new = false;
+                match ReplaceUtils::replace_with_path(&audio_info, path.clone(), &mut self.loop_settings_modal) {
+                    Ok(_) => self.add_toast(
+                        format!("Please configure loop settings for: {}", audio_info.name),
+                        Color32::GOLD,
+                    ),
+                    Err(e) => self.add_toast(format!("Replace failed: {}", e), Color32::RED),
+                }
+            }
+            ([path], None) => {
+                self.add_audio_modal
+                    .open_with_file(&path.to_string_lossy(), self.audio_files.clone());
+            }
+            _ => self.bulk_add_modal.open_with_files(dropped, self.audio_files.clone()),
+        }
+    }
+
+    /// Hand a finished URL download to the replace or add flow
+    pub fn handle_url_import(&mut self) {
+        let Some((target, path)) = self.url_import_modal.take_completed() else {
+            return;
+        };
+        match target {
+            UrlImportTarget::Replace(audio_info) => {
+                // Same as a single replace from the file dialog
+                self.pending_replace_new = false;
+                match ReplaceUtils::replace_with_path(&audio_info, path, &mut self.loop_settings_modal) {
+                    Ok(_) => self.add_toast(
+                        format!("Downloaded. Please configure loop settings for: {}", audio_info.name),
+                        Color32::GOLD,
+                    ),
+                    Err(e) => self.add_toast(format!("Replace failed: {}", e), Color32::RED),
+                }
+            }
+            UrlImportTarget::Add => {
+                if self.selected_file.is_none() {
+                    self.add_toast("Open a file before adding audio".to_string(), Color32::GOLD);
+                    return;
+                }
+                self.add_audio_modal
+                    .open_with_file(&path.to_string_lossy(), self.audio_files.clone());
+                self.add_toast(
+                    "Downloaded. Please configure settings for the new audio file".to_string(),
+                    Color32::GOLD,
+                );
+            }
+        }
+    }
+
+    /// Run a preview or joint replace requested in the intro/loop window
+    pub fn handle_intro_loop_action(&mut self) {
+        let Some(action) = self.intro_loop_modal.take_action() else {
+            return;
+        };
+        match action {
+            IntroLoopAction::Preview(pair) => {
+                if let Err(e) = self.preview_intro_loop(&pair) {
+                    self.add_toast(format!("Intro/loop preview failed: {}", e), Color32::RED);
+                }
+            }
+            IntroLoopAction::JointReplace { pair, intro_path, loop_path } => {
+                // The intro plays once, the loop part repeats from its start
+                let parts = [(&pair.intro, &intro_path, false), (&pair.body, &loop_path, true)];
+                for (audio_info, path, enable_loop) in parts {
+                    match ReplaceUtils::process_replacement_with_loop_settings(
+                        audio_info,
+                        Some(path),
+                        None,
+                        None,
+                        false,
+                        enable_loop,
+                        0.0,
+                    ) {
+                        Ok(new_audio_info) => {
+                            if let Some(audio_files) = &mut self.audio_files {
+                                if let Some(idx) = audio_files
+                                    .iter()
+                                    .position(|f| f.name == audio_info.name && f.id == audio_info.id)
+                                {
+                                    audio_files[idx] = new_audio_info;
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            self.add_toast(format!("Failed to replace {}: {}", audio_info.name, e), Color32::RED);
+                            return;
+                        }
+                    }
+                }
+                self.add_toast(
+                    format!("Replaced {} and {}", pair.intro.name, pair.body.name),
+                    Color32::GREEN,
+                );
+            }
+        }
+    }
+
+    /// Play the intro and loop part of a pair as one file that repeats from the join
+    fn preview_intro_loop(&mut self, pair: &IntroLoopPair) -> Result<(), String> {
+        let file_path = self.selected_file.clone().ok_or("No file selected")?;
+        let decode = |audio_info: &AudioFileInfo| -> Result<Vec<u8>, String> {
+            let playback_path = crate::ui::audio_player::AudioPlayer::prepare_playback_path(audio_info, &file_path)?;
+            let data = std::fs::read(&playback_path).map_err(|e| format!("Failed to read {}: {}", audio_info.name, e));
+            let _ = std::fs::remove_file(&playback_path);
+            let data = data?;
+            if smpl::pcm16_info(&data).is_some() {
+                Ok(data)
+            } else {
+                ReplaceUtils::convert_audio_bytes_to_pcm_wav(&data)
+            }
+        };
+        let intro = decode(&pair.intro)?;
+        let body = decode(&pair.body)?;
+        let (joined, join_frame) = splice::join_pcm16(&intro, &body)?;
+        let sample_rate = smpl::pcm16_info(&joined).map(|info| info.sample_rate).ok_or("Invalid joined audio")?;
+        let temp_path = ExportUtils::write_temp_audio_bytes(&pair.intro, &joined, "intro_loop")?;
+
+        let audio = crate::ui::audio_player::AudioFile {
+            file_path,
+            #[cfg(not(target_arch = "wasm32"))]
+            playback_path: Some(temp_path),
+            name: format!("{} (intro + loop)", pair.base),
+            file_type: "WAV".to_string(),
+            id: pair.intro.id.clone(),
+            #[cfg(target_arch = "wasm32")]
+            temp_url: None,
+        };
+        let audio_player = self.audio_player.as_mut().ok_or("Audio player not available")?;
+        {
+            let state = audio_player.get_audio_state();
+            let mut state = state.lock().unwrap();
+            state.set_loop_points(Some(join_frame as f32 / sample_rate as f32), None, true);
+            state.set_loop_mode(LoopMode::Single);
+            state.set_audio(audio);
+        }
+        self.add_toast(format!("Previewing {} (intro + loop)", pair.base), Color32::GREEN);
+        Ok(())
+    }
+
+    /// Forward pad hits and clears from the pad grid to the audio player
+    pub fn handle_pad_events(&mut self) {
+        let events = self.pad_grid.take_events();
+        if events.is_empty() {
+            return;
+        }
+        let Some(audio_player) = &mut self.audio_player else {
+            return;
+        };
+        let state = audio_player.get_audio_state();
+        let mut failures = Vec::new();
+        {
+            let mut state = state.lock().unwrap();
+            for event in events {
+                match event {
+                    PadEvent::Trigger(slot) => {
+                        if let Err(e) = state.trigger_pad(slot) {
+                            failures.push(e);
+                        }
+                    }
+                    PadEvent::Clear(slot) => state.clear_pad(slot),
+                }
+            }
+        }
+        for e in failures {
+            self.add_toast(format!("Pad playback failed: {}", e), Color32::RED);
+        }
+    }
+
+    /// Play, retarget or stop the loop preview requested by the loop settings modal
+    pub fn handle_loop_preview(&mut self) {
+        let Some(request) = self.loop_settings_modal.take_preview_request() else {
+            return;
+        };
+        let result = match request {
+            LoopPreview::Play { start, end } => self.start_loop_preview(start, end),
+            LoopPreview::Region { start, end } => {
+                if let Some(audio_player) = &self.audio_player {
+                    let state = audio_player.get_audio_state();
+                    state.lock().unwrap().set_loop_points(start, end, start.is_some() || end.is_some());
+                }
+                Ok(())
+            }
+            LoopPreview::Stop => {
+                if let Some(audio_player) = &self.audio_player {
+                    let state = audio_player.get_audio_state();
+                    let mut state = state.lock().unwrap();
+                    state.stop();
+                    state.set_loop_points(None, None, false);
+                    if let Some(mode) = self.loop_preview_mode.take() {
+                        state.set_loop_mode(mode);
+                    }
+                }
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            self.add_toast(format!("Loop preview failed: {}", e), Color32::RED);
+        }
+    }
+
+    /// Play the replacement source from the loop start, wrapping back at the loop end
+    fn start_loop_preview(&mut self, start: Option<f32>, end: Option<f32>) -> Result<(), String> {
+        let audio_info = self.loop_settings_modal.audio_info.clone().ok_or("No track to preview")?;
+        let source = self.loop_settings_modal.source_path.clone().ok_or("No replacement file")?;
+        let file_path = self.selected_file.clone().unwrap_or_default();
+
+        // Decode to PCM WAV so the preview plays whatever the replacement was made from
+        let data = std::fs::read(&source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        let data = if smpl::pcm16_info(&data).is_some() {
+            data
+        } else {
+            ReplaceUtils::convert_audio_bytes_to_pcm_wav(&data)?
+        };
+        let temp_path = ExportUtils::write_temp_audio_bytes(&audio_info, &data, "loop_preview")?;
+
+        let audio = crate::ui::audio_player::AudioFile {
+            file_path,
+            #[cfg(not(target_arch = "wasm32"))]
+            playback_path: Some(temp_path),
+            name: format!("{} (loop preview)", audio_info.name),
+            file_type: "WAV".to_string(),
+            id: audio_info.id.clone(),
+            #[cfg(target_arch = "wasm32")]
+            temp_url: None,
+        };
+        let audio_player = self.audio_player.as_mut().ok_or("Audio player not available")?;
+        let state = audio_player.get_audio_state();
+        let mut state = state.lock().unwrap();
+        if self.loop_preview_mode.is_none() {
+            self.loop_preview_mode = Some(state.loop_mode);
+        }
+        state.set_loop_points(start, end, start.is_some() || end.is_some());
+        state.set_loop_mode(LoopMode::Single);
+        state.set_audio(audio);
+        state.set_position(start.unwrap_or(0.0));
+        Ok(())
+    }
+
+    /// Look for replacement sources re-exported since they were queued, every few seconds
+    pub fn check_replacement_sources(&mut self) {
+        if self
+            .last_source_check
+            .is_some_and(|last| last.elapsed() < std::time::Duration::from_secs(2))
+        {
+            return;
+        }
+        self.last_source_check = Some(std::time::Instant::now());
+        self.stale_sources = ReplaceUtils::changed_sources();
+    }
+
+    /// Process the changed sources again with the loop and gain they were queued with
+    pub fn refresh_stale_sources(&mut self) {
+        let mut refreshed = 0;
+        for (key, _) in std::mem::take(&mut self.stale_sources) {
+            let Some(info) = self.audio_files.iter().flatten().find(|f| f.track_key() == key).cloned() else {
+                continue;
+            };
+            match ReplaceUtils::refresh_from_source(&info) {
+                Ok(new_info) => {
+                    if let Some(listed) = self
+                        .audio_files
+                        .iter_mut()
+                        .flatten()
+                        .find(|f| f.name == info.name && f.id == info.id)
+                    {
+                        *listed = new_info;
+                    }
+                    refreshed += 1;
+                }
+                Err(e) => self.add_toast(format!("Failed to refresh {}: {}", info.name, e), Color32::RED),
+            }
+        }
+        if refreshed > 0 {
+            self.add_toast(format!("Refreshed {} replacement(s) from their sources", refreshed), Color32::GREEN);
+        }
+    }
+
+    /// Queue the mixed audio of a track as its replacement
+    pub fn apply_mix(&mut self, info: &AudioFileInfo, settings: &MixSettings) {
+        let Some(file_path) = self.selected_file.clone() else {
+            return;
+        };
+        match ReplaceUtils::mix_track(info, &file_path, settings) {
+            Ok(new_info) => {
+                if let Some(listed) = self
+                    .audio_files
+                    .iter_mut()
+                    .flatten()
+                    .find(|f| f.name == info.name && f.id == info.id)
+                {
+                    *listed = new_info;
+                }
+                self.add_toast(format!("Mixed {}: {}", info.name, settings.describe()), Color32::GREEN);
+            }
+            Err(e) => self.add_toast(format!("Failed to mix {}: {}", info.name, e), Color32::RED),
+        }
+    }
+
+    /// Whether a row of the open bank is locked against batch operations
+    pub fn is_locked(&self, info: &AudioFileInfo) -> bool {
+        self.selected_file
+            .as_deref()
+            .is_some_and(|bank| self.locked_tracks.is_locked(bank, &info.track_key()))
+    }
+
+    /// The clicked row, or the whole selection when the clicked row is part of it
+    fn clicked_rows(&self, clicked: &AudioFileInfo) -> Vec<AudioFileInfo> {
+        if !self.selected_items.contains(&format!("{}:{}", clicked.name, clicked.id)) {
+            return vec![clicked.clone()];
+        }
+        self.audio_files
+            .iter()
+            .flatten()
+            .filter(|f| self.selected_items.contains(&format!("{}:{}", f.name, f.id)))
+            .cloned()
+            .collect()
+    }
+
+    /// Ask to mark the selected tracks for deletion; they are removed once confirmed
+    pub fn request_remove_selected(&mut self) {
+        let (unlocked, locked) = self.unlocked_selection();
+        self.pending_remove_selected = true;
+        self.confirm_modal.open(
+            "Confirm Remove Selected",
+            &format!(
+                "This will mark {} selected item(s) for deletion (in memory only).{} Continue?",
+                unlocked.len(),
+                locked_note(locked)
+            ),
+        );
+    }
+
+    /// Move the highlighted row `step` rows through the visible list and scroll to it
+    fn step_row_selection(&mut self, files: &[AudioFileInfo], step: isize) {
+        if files.is_empty() {
+            return;
+        }
+        let current = if step > 0 { self.selected_rows.iter().max() } else { self.selected_rows.iter().min() };
+        let row = match current {
+            Some(&row) => row.saturating_add_signed(step).min(files.len() - 1),
+            None if step > 0 => 0,
+            None => files.len() - 1,
+        };
+        self.selected_rows.clear();
+        self.selected_rows.insert(row);
+        self.scroll_to_key = Some(files[row].track_key());
+    }
+
+    /// Selected row keys a batch operation may touch, and the number of locked ones left out
+    fn unlocked_selection(&self) -> (Vec<String>, usize) {
+        let mut locked = 0;
+        let mut unlocked = Vec::new();
+        for info in self.audio_files.iter().flatten() {
+            let key = format!("{}:{}", info.name, info.id);
+            if !self.selected_items.contains(&key) {
+                continue;
+            }
+            if self.is_locked(info) {
+                locked += 1;
+            } else {
+                unlocked.push(key);
+            }
+        }
+        (unlocked, locked)
+    }
+
+    /// Queue every track gained to `target_lufs`
+    pub fn normalize_all(&mut self, tracks: &[AudioFileInfo], target_lufs: f32) {
+        let settings = MixSettings {
+            normalize: Normalize::Lufs(target_lufs),
+            ..Default::default()
+        };
+        let Some(file_path) = self.selected_file.clone() else {
+            return;
+        };
+        let mut normalized = 0;
+        for info in tracks {
+            match ReplaceUtils::mix_track(info, &file_path, &settings) {
+                Ok(new_info) => {
+                    if let Some(listed) = self
+                        .audio_files
+                        .iter_mut()
+                        .flatten()
+                        .find(|f| f.name == info.name && f.id == info.id)
+                    {
+                        *listed = new_info;
+                    }
+                    normalized += 1;
+                }
+                Err(e) => self.add_toast(format!("Failed to normalize {}: {}", info.name, e), Color32::RED),
+            }
+        }
+        if normalized > 0 {
+            self.add_toast(
+                format!("Normalized {} track(s) to {:.1} LUFS", normalized, target_lufs),
+                Color32::GREEN,
+            );
+        }
+    }
+
+    /// Keep the audio queued before the sources changed
+    pub fn keep_stale_sources(&mut self) {
+        for (key, _) in std::mem::take(&mut self.stale_sources) {
+            ReplaceUtils::keep_queued_source(&key);
+        }
+    }
+
+    /// Play the track the review window moved to and apply its worklist
+    pub fn handle_review_requests(&mut self) {
+        if let Some(info) = self.review_modal.take_play_request() {
+            let result = match (&mut self.audio_player, self.selected_file.clone()) {
+                (Some(audio_player), Some(path)) => audio_player.load_audio(&info, &path).map(|()| {
+                    let state = audio_player.get_audio_state();
+                    let mut state = state.lock().unwrap();
+                    if !state.is_playing {
+                        state.toggle_play();
+                    }
+                }),
+                _ => Err("No file is open".to_string()),
+            };
+            if let Err(e) = result {
+                self.add_toast(format!("Failed to play {}: {}", info.name, e), Color32::RED);
+            }
+        }
+
+        let Some(outcome) = self.review_modal.take_outcome() else {
+            return;
+        };
+        match outcome {
+            ReviewOutcome::Label => {
+                let Some(bank) = self.selected_file.clone() else {
+                    return;
+                };
+                let graded = self.review_modal.queue().graded();
+                let count = graded.len();
+                for (info, grade) in graded {
+                    self.color_labels.set(&bank, &info.track_key(), Some(grade.label()));
+                }
+                self.add_toast(format!("Labeled {} reviewed tracks", count), Color32::GREEN);
+            }
+            ReviewOutcome::Select(grade) => {
+                let worklist = self.review_modal.queue().worklist(grade);
+                self.selected_items = worklist.iter().map(|f| format!("{}:{}", f.name, f.id)).collect();
+                let count = self.selected_items.len();
+                self.add_toast(format!("Selected {} tracks graded {}", count, grade.name()), Color32::GREEN);
+            }
+        }
+    }
+
+    /// Hand a blind test playback request to the audio player
+    pub fn play_blind_test_request(&mut self) {
+        let Some(request) = self.blind_test_modal.take_play_request() else {
+            return;
+        };
+        let Some(file_path) = self.selected_file.clone() else {
+            return;
+        };
+        let tag = format!("blind_{}", request.slot);
+        match ExportUtils::write_playable_temp_audio(&request.audio_info, &request.data, &tag) {
+            Ok(temp_path) => {
+                let audio = crate::ui::audio_player::AudioFile {
+                    file_path,
+                    #[cfg(not(target_arch = "wasm32"))]
+                    playback_path: Some(temp_path),
+                    // Keep the slot name only so the player does not reveal the source
+                    name: format!("Blind test {}", request.slot),
+                    file_type: request.audio_info.file_type.clone(),
+                    id: request.audio_info.id.clone(),
+                    #[cfg(target_arch = "wasm32")]
+                    temp_url: None,
+                };
+                if let Some(audio_player) = &mut self.audio_player {
+                    let state = audio_player.get_audio_state();
+                    let mut state = state.lock().unwrap();
+                    state.set_loop_points(None, None, false);
+                    state.set_audio(audio);
+                }
+            }
+            Err(e) => {
+                self.add_toast(format!("Failed to prepare blind test audio: {}", e), Color32::RED);
+            }
+        }
+    }
+
     /// Handle actions from the audio player (next/previous track)
     pub fn handle_audio_player_action(&mut self, action: AudioPlayerAction) {
         match action {
@@ -1125,6 +2204,191 @@ impl MainArea {
         }
     }
 
+    /// Convert the tracks of the open NUS3BANK to PCM16 WAV in memory, as pending
+    /// replacements. With `legacy_wav_only` only WAV payloads that are not plain PCM16, such
+    /// as WAVEFORMATEXTENSIBLE ones the game cannot read, are converted. Returns the number
+    /// converted and skipped, and one message per track that failed.
+    pub(super) fn convert_tracks_to_pcm16(
+        &mut self,
+        bank_path: &str,
+        legacy_wav_only: bool,
+    ) -> Result<(usize, usize, Vec<String>), String> {
+        // Load current bank to read original payloads.
+        let bank = crate::nus3bank::structures::Nus3bankFile::open(bank_path)
+            .map_err(|e| format!("Failed to open .nus3bank: {}", e))?;
+
+        let mut converted = 0usize;
+        let mut skipped = 0usize;
+        let mut failures = Vec::new();
+
+        for info in self.audio_files.iter_mut().flatten() {
+            if !info.is_nus3bank {
+                continue;
+            }
+            let Some(hex_id) = info.hex_id.as_deref() else {
+                failures.push(format!("Convert failed for {}: no hex ID", info.name));
+                continue;
+            };
+
+            let source = ReplaceUtils::get_replacement_data_unified(info)
+                .or_else(|| bank.get_track_by_hex_id(hex_id).and_then(|t| bank.read_track_payload(t.index).ok()));
+
+            let Some(source_bytes) = source else {
+                failures.push(format!("Convert failed for {}: payload could not be read", info.name));
+                continue;
+            };
+
+            let flagged = !legacy_wav_only || source_bytes.starts_with(b"RIFF");
+            if !flagged || smpl::is_standard_pcm16_wav(&source_bytes) {
+                skipped += 1;
+                continue;
+            }
+
+            match ReplaceUtils::convert_audio_bytes_to_pcm_wav(&source_bytes) {
+                Ok(wav_bytes) => {
+                    // Stage replacement for export/save.
+                    let _ = crate::nus3bank::replace::Nus3bankReplacer::replace_track_by_key(
+                        bank_path,
+                        &info.track_key(),
+                        wav_bytes.clone(),
+                    );
+                    // Update playback replacement cache.
+                    let _ = ReplaceUtils::store_audio_data_for_playback(info.track_key(), wav_bytes.clone());
+
+                    info.size = wav_bytes.len();
+                    info.file_type = "WAV".to_string();
+                    converted += 1;
+                }
+                Err(e) => failures.push(format!("Convert failed for {}: {}", info.name, e)),
+            }
+        }
+
+        Ok((converted, skipped, failures))
+    }
+
+    /// Queue PCM16 conversions of the legacy WAV payloads of a NUS3BANK just opened, when
+    /// that is switched on in Settings
+    pub(super) fn auto_normalize_on_open(&mut self) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        if !self.auto_normalize_on_open || !path.to_lowercase().ends_with(".nus3bank") {
+            return;
+        }
+        match self.convert_tracks_to_pcm16(&path, true) {
+            Ok((0, _, failures)) if failures.is_empty() => {}
+            Ok((converted, _, failures)) => {
+                if converted > 0 {
+                    audit_log::record(format!("Converted {} legacy WAV track(s) to PCM16 on open", converted));
+                    self.add_toast(
+                        format!("Queued {} legacy WAV track(s) as PCM16; save to keep them", converted),
+                        Color32::GREEN,
+                    );
+                }
+                for failure in failures {
+                    self.add_toast(failure, Color32::RED);
+                }
+            }
+            Err(e) => self.add_toast(e, Color32::RED),
+        }
+    }
+
+    /// Queue a new order of the .nus3audio entries and list the tracks in it
+    pub(super) fn apply_entry_order(&mut self, order: Vec<(u32, String)>) {
+        if let Some(files) = self.audio_files.as_mut() {
+            let rank = |file: &AudioFileInfo| {
+                order
+                    .iter()
+                    .position(|(id, name)| id.to_string() == file.id && *name == file.name)
+                    .unwrap_or(order.len())
+            };
+            files.sort_by_key(rank);
+        }
+        audit_log::record(format!("Reordered {} entries", order.len()));
+        Nus3audioFileUtils::register_order(order);
+        self.add_toast("The new entry order is written on the next save".to_string(), Color32::GREEN);
+    }
+
+    /// Path, pending slot order and tone names (in that order) of the open NUS3BANK, from the
+    /// tone list read when it was loaded
+    pub(super) fn tone_slots(&self) -> Result<(String, Vec<usize>, Vec<String>), String> {
+        let path = self.selected_file.clone().ok_or("No file is open")?;
+        let tones = &self.tone_names;
+        if tones.is_empty() {
+            return Err("The TONE list of the file is not loaded".to_string());
+        }
+        let order = crate::nus3bank::replace::Nus3bankReplacer::get_tone_order(&path)
+            .filter(|order| order.len() == tones.len())
+            .unwrap_or_else(|| (0..tones.len()).collect());
+        let names = order.iter().map(|&slot| tones[slot].clone()).collect();
+        Ok((path, order, names))
+    }
+
+    /// Slot a row's tone takes on save. Rows keep the ID they were loaded with until then.
+    fn tone_position(info: &AudioFileInfo, order: &[usize]) -> Result<usize, String> {
+        let slot = info
+            .hex_id
+            .as_deref()
+            .and_then(|hex| usize::from_str_radix(hex.trim_start_matches("0x"), 16).ok())
+            .ok_or_else(|| format!("{} has no track ID", info.name))?;
+        order
+            .iter()
+            .position(|&s| s == slot)
+            .ok_or_else(|| format!("{} is not in the TONE list", info.name))
+    }
+
+    /// Give `info` the ID `to`; the track holding it takes the old ID of `info`. Returns `to`.
+    pub(super) fn move_tone(&mut self, info: &AudioFileInfo, to: usize) -> Result<usize, String> {
+        let (path, mut order, _) = self.tone_slots()?;
+        let from = Self::tone_position(info, &order)?;
+        if to >= order.len() {
+            return Err(format!("ID 0x{:x} is past the last track (0x{:x})", to, order.len().saturating_sub(1)));
+        }
+        order.swap(from, to);
+        crate::nus3bank::replace::Nus3bankReplacer::register_tone_order(&path, order.clone())?;
+        if let Some(files) = self.audio_files.as_mut() {
+            files.sort_by_key(|file| Self::tone_position(file, &order).unwrap_or(usize::MAX));
+        }
+        audit_log::record(format!("Moved {} from ID 0x{:x} to 0x{:x}", info.name, from, to));
+        Ok(to)
+    }
+
+    /// Play the rows shown in the table in their current order, advancing after each one.
+    /// Rows that cannot be played are left out.
+    pub fn play_all(&mut self) {
+        let Some(path) = self.selected_file.clone() else {
+            return;
+        };
+        let files = self.filtered_audio_files();
+        let unplayable = self.unplayable_rows(&files);
+        let playlist: Vec<AudioFileInfo> = files
+            .into_iter()
+            .filter(|info| !unplayable.contains_key(&info.track_key()))
+            .collect();
+        let Some(first) = playlist.first().cloned() else {
+            self.add_toast("No playable tracks in the list".to_string(), Color32::GOLD);
+            return;
+        };
+        self.ensure_audio_player_initialized();
+        let Some(player) = &mut self.audio_player else {
+            return;
+        };
+        match player.load_audio(&first, &path) {
+            Ok(()) => {
+                let count = playlist.len();
+                let state = player.get_audio_state();
+                let mut state = state.lock().unwrap();
+                state.start_playlist(playlist);
+                if !state.is_playing {
+                    state.toggle_play();
+                }
+                drop(state);
+                self.add_toast(format!("Playing all {} track(s), starting with {}", count, first.name), Color32::GREEN);
+            }
+            Err(e) => self.add_toast(format!("Failed to load audio '{}': {}", first.name, e), Color32::RED),
+        }
+    }
+
     fn play_next_track(&mut self) {
         let (next_index, playlist) = {
             let player = match self.audio_player.as_ref() {
@@ -1157,8 +2421,8 @@ impl MainArea {
                     // At the end of playlist
                     match state.loop_mode {
                         LoopMode::All => 0, // Loop back to first track
-                        LoopMode::None => {
-                            // In None mode, stop at the end when manually clicking next
+                        LoopMode::None | LoopMode::Next => {
+                            // Stop at the end when manually clicking next or advancing row by row
                             return;
                         }
                         LoopMode::Single => {
@@ -1207,7 +2471,7 @@ impl MainArea {
                 // At the beginning of playlist
                 match state.loop_mode {
                     LoopMode::All => state.playlist.len() - 1, // Loop to last track
-                    LoopMode::None | LoopMode::Single => {
+                    LoopMode::None | LoopMode::Single | LoopMode::Next => {
                         // Stay at first track
                         0
                     }
@@ -1236,3 +2500,12 @@ impl MainArea {
 }
 
 
+
+/// Sentence for confirmations of batch operations that leave locked rows alone
+fn locked_note(locked: usize) -> String {
+    if locked == 0 {
+        String::new()
+    } else {
+        format!(" {} locked track(s) will be skipped.", locked)
+    }
+}
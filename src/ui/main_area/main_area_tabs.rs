@@ -0,0 +1,213 @@
+use std::path::Path;
+
+use egui::Color32;
+
+use crate::ui::audio_player::AudioPlayer;
+
+use crate::nus3bank::replace::Nus3bankReplacer;
+
+use super::{
+    audio_file_info::AudioFileInfo,
+    audit_log,
+    bank_loader::load_payloads,
+    dton_pending,
+    file_tabs::CopiedTrack,
+    grp_pending,
+    main_area_core::MainArea,
+    nus3audio_file_utils::Nus3audioFileUtils,
+    path_utils,
+    prop_pending,
+    replace_utils::ReplaceUtils,
+    snapshots::{self, Snapshot},
+};
+
+impl MainArea {
+    /// Open a file in its own tab, or switch to its tab when it is already open. The edits of
+    /// the tab switched away from are kept until it is switched back to.
+    pub fn open_file(&mut self, path: String) {
+        if self.selected_file.as_deref() == Some(path.as_str()) && self.file_tabs.index_of(&path).is_some() {
+            return;
+        }
+        self.park_active_tab();
+        let index = self.file_tabs.open(&path);
+        self.activate_tab(index);
+    }
+
+    /// Switch to another open tab
+    pub fn switch_tab(&mut self, index: usize) {
+        if self.file_tabs.active() == Some(index) {
+            return;
+        }
+        self.park_active_tab();
+        self.activate_tab(index);
+    }
+
+    /// Close a tab and drop its pending edits. Closing the active tab opens its neighbour.
+    pub fn close_tab(&mut self, index: usize) {
+        let Some((tab, next)) = self.file_tabs.close(index) else {
+            return;
+        };
+        for result in [
+            grp_pending::clear(&tab.path),
+            dton_pending::clear(&tab.path),
+            prop_pending::clear(&tab.path),
+        ] {
+            if let Err(e) = result {
+                println!("Warning: Failed to drop edits of {}: {}", tab.path, e);
+            }
+        }
+        if self.selected_file.as_deref() != Some(tab.path.as_str()) {
+            return;
+        }
+
+        Nus3audioFileUtils::clear_changes();
+        match next {
+            Some(next) => self.activate_tab(next),
+            None => self.update_selected_file(None),
+        }
+    }
+
+    /// Pending edits of an open tab
+    pub fn tab_edit_count(&self, index: usize) -> usize {
+        if self.file_tabs.active() == Some(index) {
+            Nus3audioFileUtils::get_pending_changes_count()
+        } else {
+            self.file_tabs.tabs().get(index).map_or(0, |t| t.parked_edits())
+        }
+    }
+
+    fn park_active_tab(&mut self) {
+        let (Some(index), Some(path)) = (self.file_tabs.active(), self.selected_file.clone()) else {
+            return;
+        };
+        self.file_tabs
+            .park(index, Snapshot::take("Tab", &path, self.audio_files.clone()));
+    }
+
+    /// Load the file of a tab into the editor and put its parked edits back
+    fn activate_tab(&mut self, index: usize) {
+        let Some(path) = self.file_tabs.tabs().get(index).map(|t| t.path.clone()) else {
+            return;
+        };
+        let parked = self.file_tabs.unpark(index);
+        self.file_tabs.set_active(index);
+
+        // The pending stores only ever hold the active tab's edits
+        Nus3audioFileUtils::clear_changes();
+        self.update_selected_file_in_background(Some(path.clone()));
+        self.selected_rows.clear();
+
+        let Some(snapshot) = parked else {
+            return;
+        };
+        if snapshot.base_changed(snapshots::base_stamp(&path)) {
+            self.add_toast(
+                format!("{} changed on disk while its tab was in the background", path),
+                Color32::GOLD,
+            );
+        }
+        match snapshot.restore() {
+            Ok(Some(audio_files)) => {
+                // The parked rows stand in for the rows of a load that may still be running;
+                // the load still fills in the empty, loop and format data
+                self.file_count = Some(audio_files.len());
+                self.audio_files = Some(audio_files);
+            }
+            Ok(None) => {}
+            Err(e) => self.add_toast(format!("Failed to restore the edits of {}: {}", path, e), Color32::RED),
+        }
+    }
+
+    /// Decode a track, with its pending replacement, so it can be pasted into another tab
+    pub fn copy_track(&mut self, info: &AudioFileInfo) -> Result<(), String> {
+        let source_file = self.selected_file.clone().ok_or("No file is open")?;
+        let decoded = AudioPlayer::prepare_playback_path(info, &source_file)?;
+        let extension = Path::new(&decoded)
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_else(|| "wav".to_string());
+
+        let dir = path_utils::safe_temp_dir().join("exvs2_copied_tracks");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        // Named after the track so Add Audio suggests the same name
+        let wav_path = dir.join(format!("{}.{}", path_utils::ascii_file_name(&info.name), extension));
+        std::fs::copy(&decoded, &wav_path).map_err(|e| format!("Failed to keep the copied track: {}", e))?;
+
+        self.copied_track = Some(CopiedTrack {
+            name: info.name.clone(),
+            source_file,
+            wav_path: wav_path.to_string_lossy().to_string(),
+        });
+        Ok(())
+    }
+
+    /// Numeric ID after the highest one in the file, pending additions included
+    fn next_free_id(&self) -> u32 {
+        Nus3audioFileUtils::get_effective_audio_list(self.audio_files.as_ref())
+            .iter()
+            .filter_map(|(id, _)| id.parse::<u32>().ok())
+            .max()
+            .map_or(1000, |max| max + 1)
+    }
+
+    /// Ask for the name of a copy of `info`
+    pub fn open_duplicate(&mut self, info: &AudioFileInfo) {
+        let taken = Nus3audioFileUtils::get_effective_audio_list(self.audio_files.as_ref())
+            .into_iter()
+            .map(|(_, name)| name)
+            .collect();
+        // Pending adds are sorted by name and removals shift the tone IDs when a NUS3BANK is
+        // written, so its copy's ID is not known until then
+        let new_id = (!info.is_nus3bank).then(|| self.next_free_id().to_string());
+        self.duplicate_modal.open_with(info, new_id, taken);
+    }
+
+    /// Queue a copy of `info` named `name` under the next free ID. The copy has the track's
+    /// payload, with its pending replacement, and in a NUS3BANK its tone metadata.
+    pub fn duplicate_track(&mut self, info: &AudioFileInfo, name: &str) -> Result<AudioFileInfo, String> {
+        let path = self.selected_file.clone().ok_or("No file is open")?;
+        let original = load_payloads(&path, std::slice::from_ref(info))?.remove(&info.track_key());
+        if info.is_nus3bank && original.is_none() {
+            return Err(format!("{} is not saved yet; save the file before duplicating it", info.name));
+        }
+        let data = ReplaceUtils::get_replacement_data_unified(info)
+            .or_else(|| Nus3audioFileUtils::get_pending_added_data(&info.name, &info.id))
+            .or(original)
+            .ok_or_else(|| format!("{} has no audio data", info.name))?;
+
+        let mut copy = AudioFileInfo {
+            name: name.to_string(),
+            id: self.next_free_id().to_string(),
+            size: data.len(),
+            ..info.clone()
+        };
+        if info.is_nus3bank {
+            let source = info.hex_id.as_deref().ok_or_else(|| format!("{} has no track ID", info.name))?;
+            copy.hex_id = Some(Nus3bankReplacer::register_duplicate(&path, source, name)?);
+            ReplaceUtils::store_audio_data_for_playback(copy.added_track_key(), data)?;
+            audit_log::record_for(&path, format!("Duplicated {} as {}", info.name, name));
+        } else {
+            Nus3audioFileUtils::register_add_audio(&copy, data)?;
+        }
+
+        if let Some(files) = self.audio_files.as_mut() {
+            files.push(copy.clone());
+            self.file_count = Some(files.len());
+        }
+        Ok(copy)
+    }
+
+    /// Open Add Audio with the copied track for the open file
+    pub fn paste_track(&mut self) {
+        let Some(copied) = self.copied_track.clone() else {
+            return;
+        };
+        if !Path::new(&copied.wav_path).is_file() {
+            self.copied_track = None;
+            self.add_toast("The copied track is gone; copy it again".to_string(), Color32::RED);
+            return;
+        }
+        self.add_audio_modal
+            .open_with_file(&copied.wav_path, self.audio_files.clone());
+    }
+}
@@ -0,0 +1,119 @@
+//! Replacement manifests, run through the same steps as a replacement picked in the table:
+//! the source is decoded in memory or through vgmstream-cli, gained and looped, then encoded
+//! to the track's codec when the encoder settings ask for it. The GUI queues the result as a
+//! pending edit; `apply-manifest` on the command line writes it straight into the bank.
+
+use std::path::{Path, PathBuf};
+
+use super::audio_file_info::AudioFileInfo;
+use super::loop_settings_modal::LoopSettings;
+use super::lopus_encoder::{self, EncodeFormat, EncoderSettings};
+use super::replace_utils::ReplaceUtils;
+use crate::nus3bank::dry_run::DryRunReport;
+use crate::nus3bank::manifest::{ManifestReport, ManifestRow, ReplacementManifest};
+use crate::nus3bank::Nus3bankFile;
+
+/// Loop and gain the replace dialog would be confirmed with for `row`
+pub fn loop_settings(row: &ManifestRow) -> LoopSettings {
+    LoopSettings {
+        enable_loop: row.looping(),
+        use_custom_loop: row.loop_start.is_some() || row.loop_end.is_some(),
+        loop_start: row.loop_start,
+        loop_end: row.loop_end,
+        gain_db: row.gain_db,
+        ..LoopSettings::default()
+    }
+}
+
+/// Source file of `row`; relative paths are read from next to the manifest
+pub fn source_path(row: &ManifestRow, base: &Path) -> Result<PathBuf, String> {
+    row.source.as_ref().map(|source| base.join(source)).ok_or_else(|| "no source file".to_string())
+}
+
+/// Run every replace row of `manifest` against `tracks`, handing each to `replace` with the
+/// track it names. Other actions are skipped; rows that fail are reported and the rest still run.
+pub fn run(
+    manifest: &ReplacementManifest,
+    tracks: &[AudioFileInfo],
+    mut replace: impl FnMut(&AudioFileInfo, &ManifestRow) -> Result<(), String>,
+) -> ManifestReport {
+    let mut report = ManifestReport::default();
+    for row in &manifest.rows {
+        if !row.is_replace() {
+            report.skipped += 1;
+            continue;
+        }
+        let result = tracks
+            .iter()
+            .find(|f| row.matches(&f.name, &f.id, f.hex_id.as_deref()))
+            .ok_or_else(|| "track not found".to_string())
+            .and_then(|info| replace(info, row));
+        match result {
+            Ok(()) => report.applied += 1,
+            Err(e) => report.errors.push(format!("{}: {}", row.target(), e)),
+        }
+    }
+    report
+}
+
+/// The bytes that replace `info` for `row`, processed without queueing anything
+pub fn prepare(info: &AudioFileInfo, row: &ManifestRow, base: &Path, encoder: &EncoderSettings) -> Result<Vec<u8>, String> {
+    let wav = ReplaceUtils::process_source(&source_path(row, base)?, &loop_settings(row))?;
+    match lopus_encoder::default_format(info, encoder) {
+        EncodeFormat::Wav => Ok(wav),
+        format => encoder.encode_wav(&wav, format).map(|(data, _)| data),
+    }
+}
+
+/// The bank at `input` with the manifest at `manifest` applied in memory, with the default
+/// encoder settings
+fn apply_in_memory(input: &Path, manifest: &Path) -> Result<(Nus3bankFile, ManifestReport), String> {
+    let mut file = Nus3bankFile::open(input).map_err(|e| format!("open {}: {}", input.display(), e))?;
+    let rows = ReplacementManifest::read(manifest)?;
+    let base = manifest.parent().unwrap_or(Path::new(""));
+    let encoder = EncoderSettings::default().for_bank(&input.to_string_lossy());
+    let tracks: Vec<AudioFileInfo> = file
+        .tracks
+        .iter()
+        .map(|t| AudioFileInfo::from_nus3bank_track(t.name.clone(), t.index as u32, t.hex_id.clone(), t.size as usize, t.filename()))
+        .collect();
+
+    let report = run(&rows, &tracks, |info, row| {
+        let data = prepare(info, row, base, &encoder)?;
+        let hex_id = info.hex_id.as_deref().ok_or("track has no hex ID")?;
+        file.replace_track_data(hex_id, data).map_err(|e| e.to_string())
+    });
+    Ok((file, report))
+}
+
+/// Apply the manifest at `manifest` to the bank at `input` and save it to `output`, with the
+/// default encoder settings. Nothing is written when any row fails, so a pack is never half
+/// rebuilt.
+pub fn apply_manifest_file(input: &Path, manifest: &Path, output: &Path) -> Result<ManifestReport, String> {
+    let (file, report) = apply_in_memory(input, manifest)?;
+    if !report.is_ok() {
+        return Ok(report);
+    }
+    file.save(output).map_err(|e| format!("save {}: {}", output.display(), e))?;
+    let saved = Nus3bankFile::open(output).map_err(|e| format!("reopen {}: {}", output.display(), e))?;
+    let problems = file.check_saved_order(&saved);
+    if !problems.is_empty() {
+        return Err(format!("save {}: track order changed: {}", output.display(), problems.join("; ")));
+    }
+    Ok(report)
+}
+
+/// Run `apply_manifest_file` up to the save, then check the bank it would write to `output`
+/// without writing anything. The dry-run report is missing when a row failed.
+pub fn dry_run_manifest_file(
+    input: &Path,
+    manifest: &Path,
+    output: &Path,
+) -> Result<(ManifestReport, Option<DryRunReport>), String> {
+    let (file, report) = apply_in_memory(input, manifest)?;
+    if !report.is_ok() {
+        return Ok((report, None));
+    }
+    let dry_run = file.dry_run(&output.to_string_lossy()).map_err(|e| format!("serialize: {}", e))?;
+    Ok((report, Some(dry_run)))
+}
@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use egui::{Context, ProgressBar, RichText, ScrollArea, Window};
+
+use super::audio_file_info::AudioFileInfo;
+use super::lopus_encoder::{self, EncoderSettings};
+use super::manifest_import;
+use super::replace_utils::ReplaceUtils;
+use crate::nus3bank::manifest::{ManifestReport, ReplacementManifest};
+use crate::track_key::TrackKey;
+
+/// Tracks before and after their replacement, for the table
+pub type ReplacedTracks = Vec<(AudioFileInfo, AudioFileInfo)>;
+
+#[derive(Default)]
+struct ImportState {
+    done: usize,
+    current: String,
+    result: Option<(ManifestReport, ReplacedTracks)>,
+}
+
+/// A manifest import queueing its replacements on a worker thread
+struct ImportJob {
+    path: PathBuf,
+    total: usize,
+    state: Arc<Mutex<ImportState>>,
+}
+
+/// Progress of a manifest import, then the report of what it replaced and what failed
+#[derive(Default)]
+pub struct ManifestImportModal {
+    pub open: bool,
+    job: Option<ImportJob>,
+    report: Option<(PathBuf, ManifestReport)>,
+    finished: Option<(PathBuf, ReplacedTracks)>,
+}
+
+impl ManifestImportModal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Process the replace rows of `manifest` against `tracks` in the background. Sources are
+    /// read relative to the manifest at `path`; rows naming a track in `locked` fail.
+    pub fn start(
+        &mut self,
+        path: PathBuf,
+        manifest: ReplacementManifest,
+        tracks: Vec<AudioFileInfo>,
+        locked: HashSet<TrackKey>,
+        encoder: EncoderSettings,
+    ) {
+        let total = manifest.rows.iter().filter(|row| row.is_replace()).count();
+        let state = Arc::new(Mutex::new(ImportState::default()));
+        let shared = state.clone();
+        let base = path.parent().map(PathBuf::from).unwrap_or_default();
+        std::thread::spawn(move || {
+            let mut replaced = Vec::new();
+            let report = manifest_import::run(&manifest, &tracks, |info, row| {
+                if let Ok(mut s) = shared.lock() {
+                    s.current = info.name.clone();
+                }
+                let result = (|| {
+                    if locked.contains(&info.track_key()) {
+                        return Err("track is locked".to_string());
+                    }
+                    let source = manifest_import::source_path(row, &base)?;
+                    let format = lopus_encoder::default_format(info, &encoder);
+                    let previous = ReplaceUtils::get_replacement_data_unified(info);
+                    let settings = manifest_import::loop_settings(row);
+                    ReplaceUtils::process_replacement_with_settings(info, Some(&source), &settings)
+                        .and_then(|queued| encoder.encode_queued(&queued, format, previous))
+                })();
+                if let Ok(mut s) = shared.lock() {
+                    s.done += 1;
+                }
+                replaced.push((info.clone(), result?));
+                Ok(())
+            });
+            if let Ok(mut s) = shared.lock() {
+                s.result = Some((report, replaced));
+            }
+        });
+        self.report = None;
+        self.job = Some(ImportJob { path, total, state });
+        self.open = true;
+    }
+
+    /// Tracks replaced by an import that finished since the last call, with its manifest
+    pub fn take_finished(&mut self) -> Option<(PathBuf, ReplacedTracks)> {
+        self.finished.take()
+    }
+
+    fn poll(&mut self) {
+        let Some(job) = &self.job else {
+            return;
+        };
+        let Some((report, replaced)) = job.state.lock().ok().and_then(|mut s| s.result.take()) else {
+            return;
+        };
+        let path = job.path.clone();
+        self.job = None;
+        self.finished = Some((path.clone(), replaced));
+        self.report = Some((path, report));
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        self.poll();
+        if !self.open {
+            return;
+        }
+
+        if let Some(job) = &self.job {
+            let (done, current) = job.state.lock().map(|s| (s.done, s.current.clone())).unwrap_or_default();
+            Window::new("Importing Manifest")
+                .resizable(false)
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+                .show(ctx, |ui| {
+                    ui.label(format!("{} / {} rows", done, job.total));
+                    ui.add(ProgressBar::new(done as f32 / job.total.max(1) as f32).show_percentage());
+                    ui.label(RichText::new(current).size(11.0).weak());
+                });
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        }
+
+        let Some((path, report)) = &self.report else {
+            self.open = false;
+            return;
+        };
+        let mut open = self.open;
+        let mut close = false;
+        Window::new("Manifest Imported")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(path.display().to_string()).size(11.0).weak());
+                ui.add_space(6.0);
+                ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    ui.label(report.describe());
+                });
+                ui.add_space(6.0);
+                if ui.button("Close").clicked() {
+                    close = true;
+                }
+            });
+        if !open || close {
+            self.open = false;
+            self.report = None;
+        }
+    }
+}
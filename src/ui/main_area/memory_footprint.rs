@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+
+/// Settings of the runtime memory estimate: how much memory each codec's payload bytes
+/// take once the game has loaded them, plus a fixed cost per track
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct MemoryBudget {
+    /// Memory per payload byte, by codec name as shown in the table
+    pub multipliers: BTreeMap<String, f32>,
+    /// Multiplier of codecs without an entry
+    pub fallback_multiplier: f32,
+    /// Bookkeeping bytes per loaded track
+    pub track_overhead: u32,
+    /// Estimate above which the bank is flagged, in MB
+    pub limit_mb: f32,
+    /// Largest single payload of a preloaded bank before a save warns about it, in KB;
+    /// 0 turns the check off
+    pub preload_payload_limit_kb: u32,
+    /// File name prefixes of banks the game preloads whole, like SE banks
+    pub preload_bank_prefixes: Vec<String>,
+}
+
+impl Default for MemoryBudget {
+    fn default() -> Self {
+        let multipliers = [("OPUS", 1.0), ("IDSP", 1.0), ("WAV", 1.0), ("BNSF", 1.0)]
+            .into_iter()
+            .map(|(codec, factor)| (codec.to_string(), factor))
+            .collect();
+        Self {
+            multipliers,
+            fallback_multiplier: 1.0,
+            track_overhead: 256,
+            limit_mb: 32.0,
+            preload_payload_limit_kb: 512,
+            preload_bank_prefixes: vec!["se_".to_string()],
+        }
+    }
+}
+
+impl MemoryBudget {
+    pub fn multiplier(&self, codec: &str) -> f32 {
+        self.multipliers.get(codec).copied().unwrap_or(self.fallback_multiplier)
+    }
+
+    pub fn limit_bytes(&self) -> u64 {
+        (self.limit_mb.max(0.0) as f64 * 1024.0 * 1024.0) as u64
+    }
+
+    /// Largest payload a save should allow without a warning, if the bank is preloaded
+    pub fn payload_limit_for(&self, bank_path: &str) -> Option<usize> {
+        let name = std::path::Path::new(bank_path).file_name()?.to_string_lossy().to_lowercase();
+        let preloaded = self
+            .preload_bank_prefixes
+            .iter()
+            .any(|prefix| !prefix.is_empty() && name.starts_with(&prefix.to_lowercase()));
+        (preloaded && self.preload_payload_limit_kb > 0).then(|| self.preload_payload_limit_kb as usize * 1024)
+    }
+}
+
+/// Estimate of one codec
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodecFootprint {
+    pub codec: String,
+    pub tracks: usize,
+    pub payload_bytes: u64,
+    pub estimated_bytes: u64,
+}
+
+/// Estimated runtime memory of a bank
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Footprint {
+    /// Largest estimate first
+    pub by_codec: Vec<CodecFootprint>,
+    pub total_bytes: u64,
+    /// Part of the total taken by replaced or added tracks
+    pub pending_bytes: u64,
+    pub limit_bytes: u64,
+}
+
+impl Footprint {
+    pub fn over_limit(&self) -> bool {
+        self.limit_bytes > 0 && self.total_bytes > self.limit_bytes
+    }
+}
+
+/// One listed track as seen by the estimate
+pub struct TrackPayload<'a> {
+    pub codec: &'a str,
+    pub size: usize,
+    /// Replaced or added since the bank was opened
+    pub pending: bool,
+}
+
+/// Estimate the memory the tracks take at runtime
+pub fn estimate<'a>(tracks: impl IntoIterator<Item = TrackPayload<'a>>, budget: &MemoryBudget) -> Footprint {
+    let mut by_codec: Vec<CodecFootprint> = Vec::new();
+    let mut pending_bytes = 0;
+    for track in tracks {
+        let estimated = (track.size as f64 * budget.multiplier(track.codec) as f64).round() as u64
+            + budget.track_overhead as u64;
+        if track.pending {
+            pending_bytes += estimated;
+        }
+        let entry = match by_codec.iter().position(|c| c.codec == track.codec) {
+            Some(index) => &mut by_codec[index],
+            None => {
+                by_codec.push(CodecFootprint {
+                    codec: track.codec.to_string(),
+                    tracks: 0,
+                    payload_bytes: 0,
+                    estimated_bytes: 0,
+                });
+                by_codec.last_mut().unwrap()
+            }
+        };
+        entry.tracks += 1;
+        entry.payload_bytes += track.size as u64;
+        entry.estimated_bytes += estimated;
+    }
+    by_codec.sort_by(|a, b| b.estimated_bytes.cmp(&a.estimated_bytes));
+    Footprint {
+        total_bytes: by_codec.iter().map(|c| c.estimated_bytes).sum(),
+        by_codec,
+        pending_bytes,
+        limit_bytes: budget.limit_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applies_codec_multipliers_and_overhead() {
+        let mut budget = MemoryBudget { track_overhead: 10, limit_mb: 0.001, ..Default::default() };
+        budget.multipliers.insert("OPUS".to_string(), 4.0);
+        let tracks = [
+            TrackPayload { codec: "OPUS", size: 100, pending: false },
+            TrackPayload { codec: "WAV", size: 500, pending: true },
+            TrackPayload { codec: "OPUS", size: 50, pending: true },
+        ];
+        let footprint = estimate(tracks, &budget);
+
+        assert_eq!(footprint.by_codec[0].codec, "OPUS");
+        assert_eq!(footprint.by_codec[0].estimated_bytes, 4 * 150 + 20);
+        assert_eq!(footprint.total_bytes, 510 + 620);
+        assert_eq!(footprint.pending_bytes, 510 + 210);
+        assert!(footprint.over_limit());
+    }
+}
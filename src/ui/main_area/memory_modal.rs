@@ -0,0 +1,203 @@
+use egui::{Color32, Context, Grid, ProgressBar, RichText, Window};
+use egui_phosphor::regular;
+
+use super::memory_footprint::{Footprint, MemoryBudget};
+use super::save_summary::format_size;
+
+/// Shows the estimated runtime memory of the open bank and edits the estimate's settings
+pub struct MemoryModal {
+    pub open: bool,
+    draft: MemoryBudget,
+    new_codec: String,
+    /// `draft.preload_bank_prefixes` as typed, comma separated
+    prefixes: String,
+    saved: Option<MemoryBudget>,
+}
+
+impl Default for MemoryModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            draft: MemoryBudget::default(),
+            new_codec: String::new(),
+            prefixes: String::new(),
+            saved: None,
+        }
+    }
+
+    pub fn open_with(&mut self, budget: &MemoryBudget) {
+        self.draft = budget.clone();
+        self.new_codec.clear();
+        self.prefixes = budget.preload_bank_prefixes.join(", ");
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Settings edited since the last call
+    pub fn take_saved(&mut self) -> Option<MemoryBudget> {
+        self.saved.take()
+    }
+
+    /// `footprint` is the estimate with the saved settings
+    pub fn show(&mut self, ctx: &Context, footprint: Option<&Footprint>) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut changed = false;
+        Window::new("Memory Footprint")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new("A heuristic of the sound memory the bank takes in game, including pending changes.")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.add_space(6.0);
+
+                match footprint {
+                    Some(footprint) => Self::show_footprint(ui, footprint),
+                    None => {
+                        ui.label(RichText::new("No file is open").weak());
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.collapsing("Estimate settings", |ui| {
+                    Grid::new("memory_budget_grid").num_columns(3).spacing([10.0, 6.0]).show(ui, |ui| {
+                        let mut remove = None;
+                        for (codec, factor) in self.draft.multipliers.iter_mut() {
+                            ui.label(codec);
+                            changed |= ui
+                                .add(egui::DragValue::new(factor).speed(0.05).range(0.0..=64.0).suffix("x"))
+                                .changed();
+                            if ui.small_button(regular::X.to_string()).on_hover_text("Use the fallback").clicked() {
+                                remove = Some(codec.clone());
+                            }
+                            ui.end_row();
+                        }
+                        if let Some(codec) = remove {
+                            self.draft.multipliers.remove(&codec);
+                            changed = true;
+                        }
+
+                        ui.add(egui::TextEdit::singleline(&mut self.new_codec).hint_text("Codec").desired_width(80.0));
+                        let name = self.new_codec.trim().to_string();
+                        if ui
+                            .add_enabled(!name.is_empty() && !self.draft.multipliers.contains_key(&name), egui::Button::new("Add"))
+                            .clicked()
+                        {
+                            self.draft.multipliers.insert(name, self.draft.fallback_multiplier);
+                            self.new_codec.clear();
+                            changed = true;
+                        }
+                        ui.end_row();
+
+                        ui.label("Other codecs");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut self.draft.fallback_multiplier).speed(0.05).range(0.0..=64.0).suffix("x"))
+                            .changed();
+                        ui.end_row();
+                        ui.label("Per track");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut self.draft.track_overhead).range(0..=65536).suffix(" bytes"))
+                            .changed();
+                        ui.end_row();
+                        ui.label("Limit");
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut self.draft.limit_mb).speed(0.5).range(0.0..=4096.0).suffix(" MB"))
+                            .on_hover_text("0 turns the warning off")
+                            .changed();
+                        ui.end_row();
+                        ui.label("Payload limit");
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut self.draft.preload_payload_limit_kb)
+                                    .speed(8.0)
+                                    .range(0..=1_048_576)
+                                    .suffix(" KB"),
+                            )
+                            .on_hover_text("Largest single sound of a preloaded bank before saving warns; 0 turns it off")
+                            .changed();
+                        ui.end_row();
+                        ui.label("Preloaded banks");
+                        if ui
+                            .add(egui::TextEdit::singleline(&mut self.prefixes).hint_text("se_").desired_width(120.0))
+                            .on_hover_text("File name prefixes of banks the game loads whole, comma separated")
+                            .changed()
+                        {
+                            self.draft.preload_bank_prefixes = self
+                                .prefixes
+                                .split(',')
+                                .map(|p| p.trim().to_string())
+                                .filter(|p| !p.is_empty())
+                                .collect();
+                            changed = true;
+                        }
+                        ui.end_row();
+                    });
+                    if ui.button("Reset to Defaults").clicked() {
+                        self.draft = MemoryBudget::default();
+                        self.prefixes = self.draft.preload_bank_prefixes.join(", ");
+                        changed = true;
+                    }
+                });
+            });
+
+        if changed {
+            self.saved = Some(self.draft.clone());
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+
+    fn show_footprint(ui: &mut egui::Ui, footprint: &Footprint) {
+        Grid::new("memory_footprint_grid").num_columns(4).striped(true).spacing([12.0, 4.0]).show(ui, |ui| {
+            ui.label(RichText::new("Codec").strong());
+            ui.label(RichText::new("Tracks").strong());
+            ui.label(RichText::new("Payload").strong());
+            ui.label(RichText::new("Estimate").strong());
+            ui.end_row();
+            for codec in &footprint.by_codec {
+                ui.label(&codec.codec);
+                ui.label(codec.tracks.to_string());
+                ui.label(format_size(codec.payload_bytes as usize));
+                ui.label(format_size(codec.estimated_bytes as usize));
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(6.0);
+        ui.label(format!(
+            "Estimated total: {} ({} from pending changes)",
+            format_size(footprint.total_bytes as usize),
+            format_size(footprint.pending_bytes as usize)
+        ));
+        if footprint.limit_bytes > 0 {
+            let fraction = footprint.total_bytes as f32 / footprint.limit_bytes as f32;
+            let color = if footprint.over_limit() { Color32::from_rgb(220, 80, 80) } else { Color32::from_rgb(90, 170, 90) };
+            ui.add(
+                ProgressBar::new(fraction.min(1.0))
+                    .fill(color)
+                    .text(format!("{:.0}% of {}", fraction * 100.0, format_size(footprint.limit_bytes as usize))),
+            );
+            if footprint.over_limit() {
+                ui.colored_label(
+                    Color32::from_rgb(255, 180, 60),
+                    format!("{} The bank is over the typical limit; it may fail to load or evict other sounds.", regular::WARNING),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use egui::{Context, RichText, Window};
+
+use super::audio_file_info::AudioFileInfo;
+use crate::audio_processing::mix::{Downmix, MixSettings, Normalize};
+
+/// Default targets offered when a normalize mode is picked
+const DEFAULT_PEAK_DBFS: f32 = -1.0;
+const DEFAULT_LUFS: f32 = -16.0;
+
+/// Per-track processing: gain, normalize to peak or loudness, fades and downmix.
+/// Applies to the audio the track plays now, replaced or not.
+pub struct MixModal {
+    pub open: bool,
+    track: Option<AudioFileInfo>,
+    settings: MixSettings,
+    apply: Option<(AudioFileInfo, MixSettings)>,
+}
+
+impl Default for MixModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MixModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            track: None,
+            settings: MixSettings::default(),
+            apply: None,
+        }
+    }
+
+    /// Open for one track. The settings of the last mix stay, to apply them again quickly.
+    pub fn open_for(&mut self, info: &AudioFileInfo) {
+        self.track = Some(info.clone());
+        self.apply = None;
+        self.open = true;
+    }
+
+    /// Track and settings confirmed with Apply
+    pub fn take_apply(&mut self) -> Option<(AudioFileInfo, MixSettings)> {
+        self.apply.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        let Some(track) = self.track.clone() else {
+            self.open = false;
+            return;
+        };
+
+        let mut open = self.open;
+        let mut apply = false;
+        Window::new("Mix")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&track.name).strong());
+                ui.label(
+                    RichText::new("Processes what the track plays now and queues it as its replacement")
+                        .weak()
+                        .size(11.0),
+                );
+                ui.add_space(6.0);
+
+                egui::Grid::new("mix_settings").num_columns(2).spacing([12.0, 6.0]).show(ui, |ui| {
+                    ui.label("Normalize:");
+                    ui.horizontal(|ui| {
+                        let normalize = &mut self.settings.normalize;
+                        let is_peak = matches!(normalize, Normalize::Peak(_));
+                        let is_lufs = matches!(normalize, Normalize::Lufs(_));
+                        if ui.selectable_label(*normalize == Normalize::Off, "Off").clicked() {
+                            *normalize = Normalize::Off;
+                        }
+                        if ui.selectable_label(is_peak, "Peak").clicked() && !is_peak {
+                            *normalize = Normalize::Peak(DEFAULT_PEAK_DBFS);
+                        }
+                        if ui.selectable_label(is_lufs, "Loudness").clicked() && !is_lufs {
+                            *normalize = Normalize::Lufs(DEFAULT_LUFS);
+                        }
+                    });
+                    ui.end_row();
+
+                    match &mut self.settings.normalize {
+                        Normalize::Off => {}
+                        Normalize::Peak(target) => {
+                            ui.label("Peak target:");
+                            ui.add(egui::Slider::new(target, -24.0..=0.0).suffix(" dBFS"));
+                            ui.end_row();
+                        }
+                        Normalize::Lufs(target) => {
+                            ui.label("Loudness target:");
+                            ui.add(egui::Slider::new(target, -36.0..=-6.0).suffix(" LUFS"))
+                                .on_hover_text("Integrated loudness (ITU-R BS.1770). Loud results may clip.");
+                            ui.end_row();
+                        }
+                    }
+
+                    ui.label("Gain:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Slider::new(&mut self.settings.gain_db, -24.0..=24.0).suffix(" dB"))
+                            .on_hover_text("Applied after normalizing");
+                        if ui.small_button("Reset").clicked() {
+                            self.settings.gain_db = 0.0;
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("Fade in:");
+                    ui.add(egui::DragValue::new(&mut self.settings.fade_in_ms).range(0..=60_000).suffix(" ms"));
+                    ui.end_row();
+
+                    ui.label("Fade out:");
+                    ui.add(egui::DragValue::new(&mut self.settings.fade_out_ms).range(0..=60_000).suffix(" ms"));
+                    ui.end_row();
+
+                    ui.label("Channels:");
+                    egui::ComboBox::from_id_salt("mix_downmix")
+                        .selected_text(self.settings.downmix.name())
+                        .show_ui(ui, |ui| {
+                            for downmix in Downmix::ALL {
+                                ui.selectable_value(&mut self.settings.downmix, downmix, downmix.name());
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Cancel").clicked() {
+                            self.open = false;
+                        }
+                        if ui
+                            .add_enabled(!self.settings.is_neutral(), egui::Button::new("Apply"))
+                            .clicked()
+                        {
+                            apply = true;
+                        }
+                        if ui.button("Reset all").clicked() {
+                            self.settings = MixSettings::default();
+                        }
+                    });
+                });
+            });
+
+        if apply {
+            self.apply = Some((track, self.settings));
+            open = false;
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -16,9 +16,84 @@ mod dton_pending;
 mod dton_tones_modal;
 mod prop_pending;
 mod prop_edit_modal;
+mod save_summary;
+mod save_summary_modal;
+mod write_access;
+mod path_utils;
+mod loop_clipboard;
+mod blind_test_modal;
+mod pad_grid;
+mod wav_metadata;
+mod url_import;
+mod sample_rate_check;
+mod empty_wav;
+mod export_all_modal;
+mod export_job;
+mod export_progress_modal;
+mod export_journal;
+mod file_types;
+mod bank_stats;
+mod stats_modal;
+mod intro_loop;
+mod intro_loop_modal;
+mod lip_sync;
+mod color_labels;
+mod export_format;
+mod table_view;
+mod notification_center;
+mod project_file;
+mod file_drop;
+mod waveform;
+mod peak_cache;
+pub mod decode_pool;
+mod loop_editor;
+mod lopus_encoder;
+mod bulk_add_modal;
+mod post_save_hook;
+mod bank_args;
+mod bank_args_modal;
+mod snapshots;
+mod snapshots_modal;
+mod audit_log;
+mod audit_log_modal;
+mod memory_footprint;
+mod memory_modal;
+mod payload_limit;
+mod backups;
+mod backups_modal;
+mod verify_modal;
+mod file_tabs;
+mod workspace_search;
+mod transcripts;
+mod transcript_modal;
+mod review_queue;
+mod review_modal;
+mod source_stamp;
+mod source_manifest;
+pub mod manifest_import;
+mod manifest_import_modal;
+mod track_list;
+mod empty_tracks;
+mod quick_filters;
+mod track_format;
+mod bank_loader;
+mod locked_tracks;
+mod track_naming;
+mod dual_pane;
+mod mix_modal;
+mod normalize_all_modal;
 
 // New modular components
 mod sort_column;
+mod conversion_log;
+mod shortcuts;
+mod shortcuts_modal;
+mod track_categories;
+mod track_categories_modal;
+mod nus3audio_layout;
+mod nus3audio_layout_modal;
+mod tone_id_modal;
+mod duplicate_modal;
 mod toast_message;
 mod main_area_core;
 mod main_area_filtering;
@@ -26,12 +101,26 @@ mod main_area_rendering;
 mod main_area_search;
 mod main_area_output;
 mod main_area_table;
+mod main_area_project;
+mod main_area_tabs;
+mod main_area_dual_pane;
 mod main_component;
 
 // Re-export the main struct
 pub use main_component::MainArea;
 pub use audio_file_info::AudioFileInfo;
 pub use replace_utils::ReplaceUtils;
+pub use loop_settings_modal::LoopSettings;
 pub use export_utils::ExportUtils;
 pub use nus3audio_file_utils::Nus3audioFileUtils;
-pub use confirm_modal::ConfirmModal;
\ No newline at end of file
+pub use confirm_modal::ConfirmModal;
+pub use save_summary::SaveSummary;
+pub use save_summary_modal::SaveTarget;
+pub use write_access::check_destination;
+pub use file_types::FileTypeRegistry;
+pub use project_file::{ProjectFile, PROJECT_EXTENSION};
+pub use file_drop::{dropped_bank_files, paint_bank_drop_hint};
+pub use post_save_hook::PostSaveHook;
+pub use waveform::Waveform;
+pub use peak_cache::PeakCache;
+pub use shortcuts::ShortcutAction;
\ No newline at end of file
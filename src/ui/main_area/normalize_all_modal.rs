@@ -0,0 +1,300 @@
+use egui::{Color32, Context, Grid, ProgressBar, RichText, ScrollArea, Window};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::audio_file_info::AudioFileInfo;
+use super::bank_loader::load_payloads;
+use super::decode_pool;
+use super::replace_utils::ReplaceUtils;
+use crate::audio_processing::decode;
+use crate::audio_processing::mix;
+use crate::audio_processing::smpl;
+
+/// Loudness most game BGM and voice sits around
+const DEFAULT_TARGET_LUFS: f32 = -16.0;
+
+/// Tracks already this close to the target are left alone
+const TOLERANCE_LU: f32 = 0.5;
+
+/// Loudness of one WAV payload, pending replacement first
+#[derive(Debug, Clone)]
+struct LoudnessRow {
+    info: AudioFileInfo,
+    lufs: Option<f32>,
+    peak_dbfs: Option<f32>,
+}
+
+impl LoudnessRow {
+    fn gain_to(&self, target: f32) -> Option<f32> {
+        self.lufs.map(|lufs| target - lufs)
+    }
+
+    fn needs_gain(&self, target: f32) -> bool {
+        self.gain_to(target).is_some_and(|gain| gain.abs() > TOLERANCE_LU)
+    }
+}
+
+/// Progress of the background analysis, shared with the worker thread
+#[derive(Default)]
+struct AnalysisProgress {
+    done: usize,
+    total: usize,
+    result: Option<Result<Vec<LoudnessRow>, String>>,
+}
+
+/// Measures the integrated loudness (EBU R128) of every WAV payload in the bank and
+/// queues gain so all of them reach one target
+pub struct NormalizeAllModal {
+    pub open: bool,
+    target_lufs: f32,
+    progress: Option<Arc<Mutex<AnalysisProgress>>>,
+    cancel: Arc<AtomicBool>,
+    rows: Vec<LoudnessRow>,
+    skipped: usize,
+    error: Option<String>,
+    apply: Option<(Vec<AudioFileInfo>, f32)>,
+}
+
+impl Default for NormalizeAllModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NormalizeAllModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            target_lufs: DEFAULT_TARGET_LUFS,
+            progress: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            rows: Vec::new(),
+            skipped: 0,
+            error: None,
+            apply: None,
+        }
+    }
+
+    /// Open the window and analyze `tracks` of `file_path` in the background
+    pub fn open_for(&mut self, file_path: &str, tracks: Vec<AudioFileInfo>) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.rows.clear();
+        self.skipped = 0;
+        self.error = None;
+        self.apply = None;
+        self.open = true;
+
+        let progress = Arc::new(Mutex::new(AnalysisProgress {
+            total: tracks.len(),
+            ..Default::default()
+        }));
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.progress = Some(progress.clone());
+        let cancel = self.cancel.clone();
+        let file_path = file_path.to_string();
+
+        std::thread::spawn(move || {
+            let result = analyze_tracks(&file_path, tracks, &progress, &cancel);
+            if let Ok(mut p) = progress.lock() {
+                p.result = Some(result);
+            }
+        });
+    }
+
+    /// Tracks to normalize and the target loudness, once Apply was clicked
+    pub fn take_apply(&mut self) -> Option<(Vec<AudioFileInfo>, f32)> {
+        self.apply.take()
+    }
+
+    fn poll(&mut self) {
+        let finished = self
+            .progress
+            .as_ref()
+            .and_then(|p| p.lock().ok().and_then(|mut p| p.result.take()));
+        let Some(result) = finished else {
+            return;
+        };
+        self.progress = None;
+        match result {
+            Ok(rows) => {
+                let (measured, skipped): (Vec<_>, Vec<_>) = rows.into_iter().partition(|r| r.lufs.is_some());
+                self.rows = measured;
+                self.skipped = skipped.len();
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        self.poll();
+
+        let mut open = self.open;
+        Window::new("Normalize All")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new("Brings every WAV track to one integrated loudness. Other codecs are left as they are.")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.add_space(6.0);
+
+                if let Some(progress) = &self.progress {
+                    let (done, total) = progress.lock().map(|p| (p.done, p.total)).unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Measuring loudness {} / {}...", done, total));
+                        if ui.button("Stop").on_hover_text("Normalize only the tracks measured so far").clicked() {
+                            self.cancel.store(true, Ordering::Relaxed);
+                        }
+                    });
+                    ui.add(ProgressBar::new(done as f32 / total.max(1) as f32));
+                    ctx.request_repaint();
+                    return;
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), error);
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Target:");
+                    ui.add(egui::Slider::new(&mut self.target_lufs, -36.0..=-6.0).suffix(" LUFS"));
+                    for preset in [-23.0, -16.0, -14.0] {
+                        if ui.small_button(format!("{}", preset)).clicked() {
+                            self.target_lufs = preset;
+                        }
+                    }
+                });
+                if self.skipped > 0 {
+                    ui.label(
+                        RichText::new(format!("{} track(s) skipped: not WAV, silent or not measured", self.skipped))
+                            .weak(),
+                    );
+                }
+                ui.add_space(4.0);
+
+                let target = self.target_lufs;
+                ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    Grid::new("normalize_all_rows").num_columns(4).striped(true).show(ui, |ui| {
+                        ui.label(RichText::new("Track").strong());
+                        ui.label(RichText::new("Loudness").strong());
+                        ui.label(RichText::new("Gain").strong());
+                        ui.label("");
+                        ui.end_row();
+                        for row in &self.rows {
+                            let gain = row.gain_to(target).unwrap_or(0.0);
+                            ui.label(&row.info.name);
+                            ui.label(format!("{:.1} LUFS", row.lufs.unwrap_or_default()));
+                            if row.needs_gain(target) {
+                                ui.label(format!("{:+.1} dB", gain));
+                            } else {
+                                ui.label(RichText::new("on target").weak());
+                            }
+                            match row.peak_dbfs {
+                                Some(peak) if row.needs_gain(target) && peak + gain > 0.0 => {
+                                    ui.colored_label(Color32::GOLD, "clips")
+                                        .on_hover_text(format!("Peaks would reach {:+.1} dBFS and be clipped", peak + gain));
+                                }
+                                _ => {
+                                    ui.label("");
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                let to_apply: Vec<AudioFileInfo> = self
+                    .rows
+                    .iter()
+                    .filter(|r| r.needs_gain(target))
+                    .map(|r| r.info.clone())
+                    .collect();
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Close").clicked() {
+                            self.open = false;
+                        }
+                        if ui
+                            .add_enabled(!to_apply.is_empty(), egui::Button::new(format!("Normalize {} track(s)", to_apply.len())))
+                            .on_hover_text("Queue the gained audio as each track's replacement")
+                            .clicked()
+                        {
+                            self.apply = Some((to_apply, target));
+                            self.open = false;
+                        }
+                    });
+                });
+            });
+        if !open {
+            self.open = false;
+        }
+        if !self.open {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Measure every WAV payload; a pending replacement is measured instead of the original.
+/// Tracks left when `cancel` is set are returned unmeasured.
+fn analyze_tracks(
+    file_path: &str,
+    tracks: Vec<AudioFileInfo>,
+    progress: &Arc<Mutex<AnalysisProgress>>,
+    cancel: &Arc<AtomicBool>,
+) -> Result<Vec<LoudnessRow>, String> {
+    let mut payloads = load_payloads(file_path, &tracks)?;
+    let jobs: Vec<_> = tracks
+        .into_iter()
+        .map(|info| {
+            let data = ReplaceUtils::get_replacement_data_unified(&info).or_else(|| payloads.remove(&info.track_key()));
+            (info, data)
+        })
+        .collect();
+    let (progress, cancel) = (progress.clone(), cancel.clone());
+    Ok(decode_pool::map(jobs, move |(info, data)| {
+        let (lufs, peak_dbfs) = match data {
+            Some(data) if !cancel.load(Ordering::Relaxed) && data.starts_with(b"RIFF") => measure(&data),
+            _ => (None, None),
+        };
+        if let Ok(mut p) = progress.lock() {
+            p.done += 1;
+        }
+        LoudnessRow { info, lufs, peak_dbfs }
+    }))
+}
+
+/// Integrated loudness and sample peak of a WAV
+fn measure(data: &[u8]) -> (Option<f32>, Option<f32>) {
+    let decoded;
+    let pcm = if smpl::pcm16_info(data).is_some() {
+        data
+    } else {
+        match decode::decode_to_pcm16_wav(data, Some("wav")) {
+            Ok(pcm) => {
+                decoded = pcm;
+                &decoded
+            }
+            Err(_) => return (None, None),
+        }
+    };
+    let Some((info, samples)) = smpl::pcm16_samples(pcm) else {
+        return (None, None);
+    };
+    let floats: Vec<f32> = samples
+        .chunks_exact(2)
+        .map(|s| i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0)
+        .collect();
+    (
+        mix::integrated_lufs(&floats, info.channels as usize, info.sample_rate),
+        mix::peak_dbfs(&floats),
+    )
+}
@@ -0,0 +1,128 @@
+use egui::{Color32, Context, RichText, ScrollArea, Window};
+use std::time::Instant;
+
+/// Notifications kept for the session, beyond which the oldest are dropped
+const MAX_NOTIFICATIONS: usize = 500;
+
+struct Notification {
+    message: String,
+    color: Color32,
+    at: Instant,
+}
+
+/// Every toast of the session, newest first, so messages merged into a summary toast or
+/// pushed off screen can still be read
+pub struct NotificationCenter {
+    pub open: bool,
+    notifications: Vec<Notification>,
+    /// Messages of the summary toast the window was opened from
+    focused: Vec<String>,
+}
+
+impl Default for NotificationCenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NotificationCenter {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            notifications: Vec::new(),
+            focused: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, message: &str, color: Color32) {
+        self.notifications.push(Notification {
+            message: message.to_string(),
+            color,
+            at: Instant::now(),
+        });
+        if self.notifications.len() > MAX_NOTIFICATIONS {
+            self.notifications.remove(0);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.notifications.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notifications.is_empty()
+    }
+
+    /// Open with the messages of one summary toast listed first
+    pub fn open_with(&mut self, details: Vec<String>) {
+        self.focused = details;
+        self.open = true;
+    }
+
+    fn ago(at: Instant) -> String {
+        let secs = at.elapsed().as_secs();
+        if secs < 60 {
+            format!("{} s ago", secs)
+        } else if secs < 3600 {
+            format!("{} min ago", secs / 60)
+        } else {
+            format!("{} h ago", secs / 3600)
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        Window::new("Notifications")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                if !self.focused.is_empty() {
+                    ui.label(RichText::new(format!("Details ({})", self.focused.len())).strong());
+                    ScrollArea::vertical().id_salt("notification_details").max_height(200.0).show(ui, |ui| {
+                        for message in &self.focused {
+                            ui.label(message);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        if ui.button("Copy Details").clicked() {
+                            ui.ctx().copy_text(self.focused.join("\n"));
+                        }
+                        if ui.button("Show All").clicked() {
+                            self.focused.clear();
+                        }
+                    });
+                    ui.separator();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("All notifications ({})", self.notifications.len())).strong());
+                    if ui.add_enabled(!self.notifications.is_empty(), egui::Button::new("Clear")).clicked() {
+                        self.notifications.clear();
+                        self.focused.clear();
+                    }
+                });
+                if self.notifications.is_empty() {
+                    ui.label(RichText::new("No notifications yet.").weak());
+                    return;
+                }
+                ScrollArea::vertical().id_salt("notification_history").max_height(360.0).show(ui, |ui| {
+                    for notification in self.notifications.iter().rev() {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(Self::ago(notification.at)).size(11.0).weak());
+                            ui.colored_label(notification.color, &notification.message);
+                        });
+                    }
+                });
+            });
+        if !open {
+            self.open = false;
+            self.focused.clear();
+        }
+    }
+}
@@ -1,13 +1,31 @@
 use super::audio_file_info::AudioFileInfo;
+use super::audit_log;
+use crate::track_key::TrackKey;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Mutex;
 
 // Store temporary audio data for files that have been added, removed, or modified
-static FILE_CHANGES: Lazy<Mutex<HashMap<String, FileChangeType>>> =
+static FILE_CHANGES: Lazy<Mutex<HashMap<TrackKey, FileChangeType>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+// Track info of each addition and removal, with the file an addition was made from, so the
+// changes can be written to a project file and registered again from it
+static CHANGE_RECORDS: Lazy<Mutex<HashMap<TrackKey, ChangeRecord>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Order the entries of the open .nus3audio are written in, by (ID, name); IDs stay as they are
+static ENTRY_ORDER: Lazy<Mutex<Option<Vec<(u32, String)>>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone)]
+enum ChangeRecord {
+    Added { info: AudioFileInfo, source: PathBuf },
+    Removed(AudioFileInfo),
+}
+
 // Types of changes that can be made to files
+#[derive(Debug, Clone)]
 pub enum FileChangeType {
     // Added audio file with ID, name, and data
     Add(String, String, Vec<u8>),
@@ -15,13 +33,28 @@ pub enum FileChangeType {
     Remove(String, String),
 }
 
+/// Copy of the pending additions and removals, taken by a snapshot
+#[derive(Debug, Clone, Default)]
+pub struct FileChangeState {
+    changes: HashMap<TrackKey, FileChangeType>,
+    records: HashMap<TrackKey, ChangeRecord>,
+    order: Option<Vec<(u32, String)>>,
+}
+
+impl FileChangeState {
+    /// Number of additions and removals, plus one for a new entry order
+    pub fn len(&self) -> usize {
+        self.changes.len() + usize::from(self.order.is_some())
+    }
+}
+
 /// Utility functions for NUS3AUDIO file operations
 pub struct Nus3audioFileUtils;
 
 impl Nus3audioFileUtils {
     /// Register a file addition (in memory only)
     pub fn register_add(audio_info: &AudioFileInfo, data: Vec<u8>) -> Result<(), String> {
-        let key = format!("{}:{}", audio_info.name, audio_info.id);
+        let key = audio_info.track_key();
 
         if let Ok(mut changes) = FILE_CHANGES.lock() {
             changes.insert(
@@ -36,14 +69,8 @@ impl Nus3audioFileUtils {
 
     /// Register a file removal (in memory only)
     pub fn register_remove(audio_info: &AudioFileInfo, selected_file_path: Option<&str>) -> Result<(), String> {
-        // Use consistent key format based on file type to match replace_in_memory
-        let key = if audio_info.is_nus3bank {
-            // For NUS3BANK, use hex_id:name format (consistent with replace_in_memory)
-            format!("{}:{}", audio_info.hex_id.as_ref().unwrap_or(&audio_info.id), audio_info.name)
-        } else {
-            // For NUS3AUDIO, use original name:id format
-            format!("{}:{}", audio_info.name, audio_info.id)
-        };
+        // Same key as the track's replacement, so a removal shadows it
+        let key = audio_info.track_key();
 
         // Check if this is a NUS3BANK file (hex ID format)
         if audio_info.is_nus3bank || audio_info.id.starts_with("0x") {
@@ -53,11 +80,19 @@ impl Nus3audioFileUtils {
             crate::nus3bank::replace::Nus3bankReplacer::register_remove(file_path, hex_id)?;
         }
 
+        if let Ok(mut records) = CHANGE_RECORDS.lock() {
+            // Removing a track queued by Add Audio just drops the addition
+            if records.remove(&audio_info.added_track_key()).is_none() {
+                records.insert(key.clone(), ChangeRecord::Removed(audio_info.clone()));
+            }
+        }
+
         if let Ok(mut changes) = FILE_CHANGES.lock() {
             changes.insert(
                 key,
                 FileChangeType::Remove(audio_info.id.clone(), audio_info.name.clone()),
             );
+            audit_log::record(format!("Removed {}", audit_log::describe(audio_info)));
             Ok(())
         } else {
             Err("Failed to register file removal".to_string())
@@ -70,16 +105,81 @@ impl Nus3audioFileUtils {
             changes.clear();
             println!("Cleared all pending file changes");
         }
+        if let Ok(mut records) = CHANGE_RECORDS.lock() {
+            records.clear();
+        }
+        if let Ok(mut order) = ENTRY_ORDER.lock() {
+            *order = None;
+        }
+    }
+
+    /// Copy the pending additions and removals
+    pub fn capture_state() -> FileChangeState {
+        FileChangeState {
+            changes: FILE_CHANGES.lock().map(|c| c.clone()).unwrap_or_default(),
+            records: CHANGE_RECORDS.lock().map(|r| r.clone()).unwrap_or_default(),
+            order: ENTRY_ORDER.lock().map(|o| o.clone()).unwrap_or_default(),
+        }
+    }
+
+    /// Replace the pending additions and removals with a copy from `capture_state`
+    pub fn restore_state(state: FileChangeState) {
+        if let Ok(mut changes) = FILE_CHANGES.lock() {
+            *changes = state.changes;
+        }
+        if let Ok(mut records) = CHANGE_RECORDS.lock() {
+            *records = state.records;
+        }
+        if let Ok(mut order) = ENTRY_ORDER.lock() {
+            *order = state.order;
+        }
+    }
+
+    /// Write the entries of the open .nus3audio in this order of (ID, name) on save.
+    /// Entries missing from `order`, such as pending additions, follow in their own order.
+    pub fn register_order(order: Vec<(u32, String)>) {
+        if let Ok(mut pending) = ENTRY_ORDER.lock() {
+            *pending = Some(order);
+        }
     }
 
-    /// Save all pending changes to the file
-    pub fn save_changes_to_file(file_path: &str) -> Result<(), String> {
-        // Try to create a backup of the original file first
-        let backup_path = format!("{}.backup", file_path);
-        match std::fs::copy(file_path, &backup_path) {
-            Ok(_) => println!("Created backup at {}", backup_path),
-            Err(e) => println!("Warning: Failed to create backup: {}", e),
+    /// Entry order registered since the last save
+    pub fn pending_order() -> Option<Vec<(u32, String)>> {
+        ENTRY_ORDER.lock().ok().and_then(|order| order.clone())
+    }
+
+    /// Remember the file a registered addition was made from
+    pub fn record_added_source(audio_info: &AudioFileInfo, source: PathBuf) {
+        if let Ok(mut records) = CHANGE_RECORDS.lock() {
+            records.insert(
+                audio_info.added_track_key(),
+                ChangeRecord::Added { info: audio_info.clone(), source },
+            );
         }
+    }
+
+    /// Pending additions with their source files and pending removals, each sorted by key
+    pub fn change_records() -> (Vec<(AudioFileInfo, PathBuf)>, Vec<AudioFileInfo>) {
+        let Ok(records) = CHANGE_RECORDS.lock() else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut sorted: Vec<(&TrackKey, &ChangeRecord)> = records.iter().collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+        for (_, record) in sorted {
+            match record {
+                ChangeRecord::Added { info, source } => added.push((info.clone(), source.clone())),
+                ChangeRecord::Removed(info) => removed.push(info.clone()),
+            }
+        }
+        (added, removed)
+    }
+
+    /// Save all pending changes to the file, after copying it to a timestamped backup.
+    /// Nothing is written when the backup fails.
+    pub fn save_changes_to_file(file_path: &str, retention: usize) -> Result<(), String> {
+        super::backups::create(std::path::Path::new(file_path), retention)?;
 
         // Use unified method to apply all in-memory replacements and save the file (supports both NUS3AUDIO and NUS3BANK)
         match super::replace_utils::ReplaceUtils::apply_replacements_and_save_unified(file_path, file_path) {
@@ -107,7 +207,13 @@ impl Nus3audioFileUtils {
         // Check for NUS3BANK replacement data
         let has_nus3bank_replacements = crate::nus3bank::replace::Nus3bankReplacer::has_replacement_data();
         
-        has_file_changes || has_replacements || has_nus3bank_replacements
+        let has_tone_orders = crate::nus3bank::replace::Nus3bankReplacer::tone_order_count() > 0;
+
+        has_file_changes
+            || has_replacements
+            || has_nus3bank_replacements
+            || has_tone_orders
+            || Self::pending_order().is_some()
     }
 
     /// Get the number of pending changes
@@ -125,7 +231,11 @@ impl Nus3audioFileUtils {
         // Count NUS3BANK replacement data
         let nus3bank_replacements_count = crate::nus3bank::replace::Nus3bankReplacer::get_replacement_count();
         
-        file_changes_count + replacements_count + nus3bank_replacements_count
+        // A new entry order counts as one change, as does a new TONE order of each bank
+        let order_count = usize::from(Self::pending_order().is_some())
+            + crate::nus3bank::replace::Nus3bankReplacer::tone_order_count();
+
+        file_changes_count + replacements_count + nus3bank_replacements_count + order_count
     }
 
     /// Register an audio file to be added to the NUS3AUDIO file
@@ -139,8 +249,7 @@ impl Nus3audioFileUtils {
             Err(_) => return Err("ID must be a valid number".to_string()),
         };
 
-        // Create a key for the file change - use consistent format
-        let key = format!("{}:{}", audio_info.name, audio_info.id);
+        let key = audio_info.track_key();
 
         // Register the add operation
         if let Ok(mut changes) = FILE_CHANGES.lock() {
@@ -152,6 +261,7 @@ impl Nus3audioFileUtils {
                 "Registered audio file to be added: {} (ID: {})",
                 audio_info.name, audio_info.id
             );
+            audit_log::record(format!("Added {}", audit_log::describe(audio_info)));
             Ok(())
         } else {
             Err("Failed to register audio file addition".to_string())
@@ -173,11 +283,11 @@ impl Nus3audioFileUtils {
                 audio_data.clone()
             )?;
             
-            // Also store in ReplaceUtils for audio playback
-            // Use special prefix for Add operations to avoid conflict with Replace operations
-            let key = format!("ADD_{}:{}", audio_info.hex_id.as_ref().unwrap_or(&audio_info.id), audio_info.name);
-            super::replace_utils::ReplaceUtils::store_audio_data_for_playback(key, audio_data)?;
+            // Also store in ReplaceUtils for audio playback, under the added-track key so it
+            // does not conflict with Replace operations
+            super::replace_utils::ReplaceUtils::store_audio_data_for_playback(audio_info.added_track_key(), audio_data)?;
             println!("Stored audio data for playback: {} (NUS3BANK)", audio_info.name);
+            audit_log::record_for(selected_file_path, format!("Added {}", audio_info.name));
             
             return Ok(());
         }
@@ -188,7 +298,7 @@ impl Nus3audioFileUtils {
 
     /// Get pending added audio data for a specific audio file
     pub fn get_pending_added_data(audio_name: &str, audio_id: &str) -> Option<Vec<u8>> {
-        let key = format!("{}:{}", audio_name, audio_id);
+        let key = TrackKey::audio(audio_name, audio_id);
 
         if let Ok(changes) = FILE_CHANGES.lock() {
             if let Some(FileChangeType::Add(_, _, data)) = changes.get(&key) {
@@ -214,6 +324,21 @@ impl Nus3audioFileUtils {
         additions
     }
 
+    /// Get all pending removals as (id, name) pairs
+    pub fn get_pending_removals() -> Vec<(String, String)> {
+        let mut removals = Vec::new();
+
+        if let Ok(changes) = FILE_CHANGES.lock() {
+            for (_, change_type) in changes.iter() {
+                if let FileChangeType::Remove(id, name) = change_type {
+                    removals.push((id.clone(), name.clone()));
+                }
+            }
+        }
+
+        removals
+    }
+
     /// Get all currently valid IDs and names (considering pending changes)
     /// Returns (id, name) pairs that will exist after all pending changes are applied
     pub fn get_effective_audio_list(
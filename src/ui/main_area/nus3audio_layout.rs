@@ -0,0 +1,141 @@
+/// One section of a .nus3audio file, such as TNID or TNNM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub tag: String,
+    /// Offset of the section header in the file
+    pub offset: usize,
+    /// Size of the section body
+    pub size: usize,
+}
+
+/// An entry as the index tables describe it, in file order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayoutEntry {
+    pub id: u32,
+    pub name: String,
+    /// Where NMOF says the name starts in the TNNM string table
+    pub name_offset: usize,
+    pub data_offset: usize,
+    pub data_size: usize,
+}
+
+/// A name in the TNNM string table, with the entries pointing at it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableString {
+    pub offset: usize,
+    pub text: String,
+    /// Indexes into `Nus3audioLayout::entries`; empty for a string no entry uses
+    pub used_by: Vec<usize>,
+}
+
+/// Sections, index tables and string table of a .nus3audio file, read without the
+/// `nus3audio` crate so titles that lay the tables out differently can still be inspected
+#[derive(Debug, Clone, Default)]
+pub struct Nus3audioLayout {
+    pub chunks: Vec<Chunk>,
+    pub entries: Vec<LayoutEntry>,
+    pub strings: Vec<TableString>,
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u32_table(data: &[u8], chunk: Option<&Chunk>) -> Vec<u32> {
+    chunk
+        .map(|c| (0..c.size / 4).filter_map(|i| read_u32(data, c.offset + 8 + i * 4)).collect())
+        .unwrap_or_default()
+}
+
+/// Read the layout of a .nus3audio file
+pub fn parse(data: &[u8]) -> Result<Nus3audioLayout, String> {
+    if !data.starts_with(b"NUS3") {
+        return Err("Not a NUS3AUDIO file (no NUS3 header)".to_string());
+    }
+    if data.get(8..16) != Some(b"AUDIINDX".as_slice()) {
+        return Err("NUS3AUDIO file has no AUDIINDX section".to_string());
+    }
+    let count = read_u32(data, 20).ok_or("AUDIINDX section is truncated")? as usize;
+
+    let mut chunks = vec![Chunk { tag: "AUDIINDX".to_string(), offset: 8, size: 4 }];
+    let mut at = 24;
+    while at + 8 <= data.len() {
+        let tag = String::from_utf8_lossy(&data[at..at + 4]).into_owned();
+        let size = read_u32(data, at + 4).unwrap_or_default() as usize;
+        let end = match (at + 8).checked_add(size) {
+            Some(end) if end <= data.len() => end,
+            _ => return Err(format!("{} section at 0x{:X} runs past the end of the file", tag, at)),
+        };
+        chunks.push(Chunk { tag, offset: at, size });
+        at = end;
+    }
+
+    let find = |tag: &str| chunks.iter().find(|c| c.tag == tag);
+    let ids = u32_table(data, find("TNID"));
+    let name_offsets = u32_table(data, find("NMOF"));
+    let data_table = u32_table(data, find("ADOF"));
+    let read_name = |offset: usize| {
+        let bytes = data.get(offset..).unwrap_or_default();
+        let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..end]).into_owned()
+    };
+    // A corrupt AUDIINDX count can't describe more entries than the index tables hold
+    let count = count.min(ids.len().max(name_offsets.len()).max(data_table.len() / 2));
+    let entries: Vec<LayoutEntry> = (0..count)
+        .map(|i| {
+            let name_offset = name_offsets.get(i).copied().unwrap_or_default() as usize;
+            LayoutEntry {
+                id: ids.get(i).copied().unwrap_or(i as u32),
+                name: read_name(name_offset),
+                name_offset,
+                data_offset: data_table.get(i * 2).copied().unwrap_or_default() as usize,
+                data_size: data_table.get(i * 2 + 1).copied().unwrap_or_default() as usize,
+            }
+        })
+        .collect();
+
+    let mut strings = Vec::new();
+    if let Some(table) = find("TNNM") {
+        let start = table.offset + 8;
+        let mut offset = start;
+        for text in data[start..start + table.size].split(|&b| b == 0) {
+            if !text.is_empty() {
+                strings.push(TableString {
+                    offset,
+                    text: String::from_utf8_lossy(text).into_owned(),
+                    used_by: entries.iter().enumerate().filter(|(_, e)| e.name_offset == offset).map(|(i, _)| i).collect(),
+                });
+            }
+            offset += text.len() + 1;
+        }
+    }
+
+    Ok(Nus3audioLayout { chunks, entries, strings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sections_entries_and_string_table() {
+        let file: &[u8] = b"NUS3\x98\x00\x00\x00AUDIINDX\x04\x00\x00\x00\x02\x00\x00\x00TNID\x08\x00\x00\x00\x00\x00\x00\x00\x01\x00\x00\x00NMOF\x08\x00\x00\x00X\x00\x00\x00f\x00\x00\x00ADOF\x10\x00\x00\x00\x90\x00\x00\x00\x07\x00\x00\x00\x90\x00\x00\x00\x07\x00\x00\x00TNNM\x1c\x00\x00\x00repeat_test_1\x00repeat_test_2\x00JUNK\x0c\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00PACK\x10\x00\x00\x00repeat\n\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+        let layout = parse(file).unwrap();
+
+        let tags: Vec<&str> = layout.chunks.iter().map(|c| c.tag.as_str()).collect();
+        assert_eq!(tags, ["AUDIINDX", "TNID", "NMOF", "ADOF", "TNNM", "JUNK", "PACK"]);
+        assert_eq!(layout.entries.len(), 2);
+        assert_eq!(layout.entries[1].id, 1);
+        assert_eq!(layout.entries[1].name, "repeat_test_2");
+        assert_eq!((layout.entries[0].data_offset, layout.entries[0].data_size), (0x90, 7));
+        assert_eq!(layout.strings.len(), 2);
+        assert_eq!((layout.strings[1].offset, layout.strings[1].used_by.clone()), (0x66, vec![1]));
+
+        assert!(parse(b"RIFF").is_err());
+        assert!(parse(&file[..0x60]).is_err());
+
+        let mut corrupt = file.to_vec();
+        corrupt[20..24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(parse(&corrupt).unwrap().entries.len(), 2);
+    }
+}
@@ -0,0 +1,167 @@
+use egui::{CollapsingHeader, Context, Grid, RichText, ScrollArea, Window};
+use egui_phosphor::regular;
+
+use super::file_types::FileTypeRegistry;
+use super::nus3audio_layout::{self, Nus3audioLayout};
+use super::save_summary::format_size;
+
+/// Shows the sections, index tables and string table of the open .nus3audio, and lets the
+/// entries be put in another order with their IDs kept
+pub struct Nus3audioLayoutModal {
+    pub open: bool,
+    file_path: String,
+    layout: Result<Nus3audioLayout, String>,
+    /// Entries by (ID, name) in the order they will be written
+    order: Vec<(u32, String)>,
+    /// File name of each entry rebuilt from its payload, by (ID, name)
+    file_names: Vec<((u32, String), String)>,
+    saved: Option<Vec<(u32, String)>>,
+}
+
+impl Default for Nus3audioLayoutModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Nus3audioLayoutModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            file_path: String::new(),
+            layout: Ok(Nus3audioLayout::default()),
+            order: Vec::new(),
+            file_names: Vec::new(),
+            saved: None,
+        }
+    }
+
+    /// Read the layout of `file_path`, starting from `pending`, the order not saved yet
+    pub fn open_with(&mut self, file_path: &str, pending: Option<Vec<(u32, String)>>) {
+        self.file_path = file_path.to_string();
+        self.saved = None;
+        self.file_names.clear();
+        let data = std::fs::read(file_path).map_err(|e| format!("Failed to read {}: {}", file_path, e));
+        self.layout = data.as_deref().map_err(Clone::clone).and_then(nus3audio_layout::parse);
+        if let (Ok(layout), Ok(data)) = (&self.layout, &data) {
+            for entry in &layout.entries {
+                let payload = data.get(entry.data_offset..entry.data_offset + entry.data_size).unwrap_or_default();
+                self.file_names
+                    .push(((entry.id, entry.name.clone()), FileTypeRegistry::file_name_of(&entry.name, payload)));
+            }
+        }
+        self.order = pending.unwrap_or_else(|| self.file_names.iter().map(|(key, _)| key.clone()).collect());
+        self.open = true;
+    }
+
+    /// Entry order applied since the last call
+    pub fn take_saved(&mut self) -> Option<Vec<(u32, String)>> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        Window::new("File Structure")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(560.0)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.file_path).weak().size(11.0));
+                ui.add_space(6.0);
+                let layout = match &self.layout {
+                    Ok(layout) => layout,
+                    Err(e) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                        return;
+                    }
+                };
+
+                CollapsingHeader::new(format!("Sections ({})", layout.chunks.len()))
+                    .id_salt("nus3audio_sections")
+                    .show(ui, |ui| {
+                        Grid::new("nus3audio_sections_grid").num_columns(3).striped(true).show(ui, |ui| {
+                            for chunk in &layout.chunks {
+                                ui.monospace(&chunk.tag);
+                                ui.monospace(format!("0x{:08X}", chunk.offset));
+                                ui.label(format_size(chunk.size));
+                                ui.end_row();
+                            }
+                        });
+                    });
+
+                CollapsingHeader::new(format!("String table ({})", layout.strings.len()))
+                    .id_salt("nus3audio_strings")
+                    .show(ui, |ui| {
+                        ScrollArea::vertical().id_salt("nus3audio_strings_scroll").max_height(160.0).show(ui, |ui| {
+                            Grid::new("nus3audio_strings_grid").num_columns(3).striped(true).show(ui, |ui| {
+                                for string in &layout.strings {
+                                    ui.monospace(format!("0x{:08X}", string.offset));
+                                    ui.label(&string.text);
+                                    if string.used_by.is_empty() {
+                                        ui.label(RichText::new("unused").weak());
+                                    } else {
+                                        let ids: Vec<String> =
+                                            string.used_by.iter().map(|&i| layout.entries[i].id.to_string()).collect();
+                                        ui.label(format!("ID {}", ids.join(", ")));
+                                    }
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    });
+
+                ui.separator();
+                ui.label(format!("Entries ({}), in the order they are written", self.order.len()));
+                let mut swap = None;
+                ScrollArea::vertical().id_salt("nus3audio_entries_scroll").max_height(260.0).show(ui, |ui| {
+                    Grid::new("nus3audio_entries_grid").num_columns(4).striped(true).show(ui, |ui| {
+                        let last = self.order.len().saturating_sub(1);
+                        for (index, key) in self.order.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.add_enabled(index > 0, egui::Button::new(regular::ARROW_UP).small()).clicked() {
+                                    swap = Some((index, index - 1));
+                                }
+                                if ui.add_enabled(index < last, egui::Button::new(regular::ARROW_DOWN).small()).clicked() {
+                                    swap = Some((index, index + 1));
+                                }
+                            });
+                            ui.monospace(key.0.to_string());
+                            ui.label(&key.1);
+                            let file_name = self.file_names.iter().find(|(k, _)| k == key).map(|(_, n)| n.as_str());
+                            ui.label(RichText::new(file_name.unwrap_or_default()).weak());
+                            ui.end_row();
+                        }
+                    });
+                });
+                if let Some((a, b)) = swap {
+                    self.order.swap(a, b);
+                }
+
+                ui.add_space(6.0);
+                ui.label(
+                    RichText::new("IDs stay with their entries. Tracks added since the file was opened are written after these.")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.horizontal(|ui| {
+                    let file_order: Vec<(u32, String)> = self.file_names.iter().map(|(key, _)| key.clone()).collect();
+                    if ui.button("Apply Order").on_hover_text("Write the entries in this order on the next save").clicked() {
+                        self.saved = Some(self.order.clone());
+                    }
+                    if ui.add_enabled(self.order != file_order, egui::Button::new("Reset")).clicked() {
+                        self.order = file_order;
+                    }
+                });
+            });
+
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use egui::{Color32, Context, Key, RichText, Vec2, Window};
+
+use super::audio_file_info::AudioFileInfo;
+
+/// Number of trigger pads, mapped to the number keys 1-9
+pub const PAD_COUNT: usize = 9;
+
+const PAD_KEYS: [Key; PAD_COUNT] = [
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+];
+
+/// What the owner should do with the audio player after the grid was shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadEvent {
+    Trigger(usize),
+    Clear(usize),
+}
+
+/// Grid of pads that play assigned tracks instantly, for comparing short sound effects.
+/// Pads are triggered with the number keys while the window is open. Triggering a pad
+/// that is still sounding restarts it.
+pub struct PadGrid {
+    pub open: bool,
+    pads: [Option<AudioFileInfo>; PAD_COUNT],
+    events: Vec<PadEvent>,
+}
+
+impl Default for PadGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PadGrid {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            pads: Default::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Remember which track a pad plays. The audio itself is loaded by the owner.
+    pub fn assign(&mut self, slot: usize, audio_info: AudioFileInfo) {
+        if slot < PAD_COUNT {
+            self.pads[slot] = Some(audio_info);
+            self.open = true;
+        }
+    }
+
+    /// Take the events collected since the last call
+    pub fn take_events(&mut self) -> Vec<PadEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Show the pad window and read the number keys
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        // Number keys must still reach the search box and other text fields
+        if !ctx.wants_keyboard_input() {
+            ctx.input(|i| {
+                for (slot, key) in PAD_KEYS.iter().enumerate() {
+                    if i.key_pressed(*key) && self.pads[slot].is_some() {
+                        self.events.push(PadEvent::Trigger(slot));
+                    }
+                }
+            });
+        }
+
+        let mut open = self.open;
+        Window::new("Trigger Pads")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(true)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new("Press 1-9 to play a pad. Assign tracks from the table's right-click menu.")
+                        .color(Color32::GRAY),
+                );
+                ui.add_space(6.0);
+
+                egui::Grid::new("trigger_pad_grid")
+                    .spacing(Vec2::splat(6.0))
+                    .show(ui, |ui| {
+                        for slot in 0..PAD_COUNT {
+                            ui.vertical(|ui| {
+                                let text = match &self.pads[slot] {
+                                    Some(info) => format!("{}\n{}", slot + 1, info.name),
+                                    None => format!("{}\n(empty)", slot + 1),
+                                };
+                                let button = egui::Button::new(RichText::new(text).size(13.0))
+                                    .min_size(Vec2::new(120.0, 56.0))
+                                    .truncate();
+                                let assigned = self.pads[slot].is_some();
+                                if ui.add_enabled(assigned, button).clicked() {
+                                    self.events.push(PadEvent::Trigger(slot));
+                                }
+                                if assigned && ui.small_button("Clear").clicked() {
+                                    self.pads[slot] = None;
+                                    self.events.push(PadEvent::Clear(slot));
+                                }
+                            });
+                            if slot % 3 == 2 {
+                                ui.end_row();
+                            }
+                        }
+                    });
+            });
+        self.open = open;
+    }
+}
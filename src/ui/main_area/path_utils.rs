@@ -0,0 +1,282 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+// Paths longer than this get the extended-length prefix on Windows.
+// MAX_PATH is 260; leave headroom for names appended by external tools.
+const LONG_PATH_THRESHOLD: usize = 240;
+
+static SAFE_NAME_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Add the `\\?\` extended-length prefix to long absolute paths on Windows.
+/// Other platforms (and short paths) are returned unchanged.
+pub fn to_extended_length(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        let s = path.to_string_lossy();
+        if s.len() > LONG_PATH_THRESHOLD && path.is_absolute() && !s.starts_with(r"\\?\") {
+            let normalized = s.replace('/', "\\");
+            if let Some(unc) = normalized.strip_prefix(r"\\") {
+                return PathBuf::from(format!(r"\\?\UNC\{}", unc));
+            }
+            return PathBuf::from(format!(r"\\?\{}", normalized));
+        }
+    }
+    path.to_path_buf()
+}
+
+/// Whether a path can be handed to vgmstream-cli as a command line argument.
+/// The tool reads its arguments in the ANSI code page on Windows, so CJK or other
+/// non-ASCII characters and paths beyond MAX_PATH break it.
+pub fn is_tool_safe(path: &Path) -> bool {
+    let s = path.to_string_lossy();
+    s.is_ascii() && s.len() <= LONG_PATH_THRESHOLD
+}
+
+/// Replace every character that is not ASCII alphanumeric, '-', '_' or '.' with '_'
+pub fn ascii_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if cleaned.is_empty() {
+        "audio".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Output folder of each bank when several are exported at once: the banks' folders below
+/// their common parent are recreated under `output_dir`, with one subfolder per bank named
+/// after the file. Banks sharing a stem in one folder keep their extension in the name.
+pub fn mirrored_output_dirs(banks: &[String], output_dir: &str) -> Vec<PathBuf> {
+    let parents: Vec<PathBuf> = banks
+        .iter()
+        .map(|bank| Path::new(bank).parent().map(Path::to_path_buf).unwrap_or_default())
+        .collect();
+    let mut root = parents.first().cloned().unwrap_or_default();
+    for parent in &parents {
+        while !parent.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+
+    banks
+        .iter()
+        .zip(&parents)
+        .map(|(bank, parent)| {
+            let path = Path::new(bank);
+            let shares_stem = banks
+                .iter()
+                .filter(|b| Path::new(b).with_extension("") == path.with_extension(""))
+                .count()
+                > 1;
+            let name = if shares_stem {
+                path.file_name().map(|s| s.to_string_lossy().replace('.', "_"))
+            } else {
+                path.file_stem().map(|s| s.to_string_lossy().to_string())
+            };
+            let relative = parent.strip_prefix(&root).unwrap_or(Path::new(""));
+            Path::new(output_dir).join(relative).join(name.unwrap_or_default())
+        })
+        .collect()
+}
+
+/// Show a file selected in the system file manager (Explorer, Finder, or the folder on Linux)
+pub fn reveal_in_file_manager(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} no longer exists", path.display()));
+    }
+    #[cfg(windows)]
+    let result = std::process::Command::new("explorer")
+        .arg(format!("/select,{}", path.display()))
+        .spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg("-R").arg(path).spawn();
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open")
+        .arg(path.parent().unwrap_or(Path::new(".")))
+        .spawn();
+    result
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open file manager: {}", e))
+}
+
+/// Open a file with the application the system associates with it
+pub fn open_with_default_app(path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("{} no longer exists", path.display()));
+    }
+    // `start` runs inside cmd, which would flash a console window without hiding it
+    #[cfg(windows)]
+    let result = crate::tools::hidden_command(Path::new("cmd"))
+        .args(["/C", "start", ""])
+        .arg(path)
+        .spawn();
+    #[cfg(target_os = "macos")]
+    let result = std::process::Command::new("open").arg(path).spawn();
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let result = std::process::Command::new("xdg-open").arg(path).spawn();
+    result
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+/// Temp directory usable by external tools. Falls back to `tools/tmp` (relative, ASCII-only)
+/// when the system temp directory itself contains a non-ASCII user name.
+pub fn safe_temp_dir() -> PathBuf {
+    let temp_dir = std::env::temp_dir();
+    if is_tool_safe(&temp_dir) {
+        return temp_dir;
+    }
+    let fallback = Path::new("tools").join("tmp");
+    let _ = fs::create_dir_all(&fallback);
+    fallback
+}
+
+fn unique_safe_path(extension: &str) -> PathBuf {
+    let n = SAFE_NAME_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = format!("exvs2_tool_{}_{}.{}", std::process::id(), n, ascii_file_name(extension));
+    safe_temp_dir().join(name)
+}
+
+/// Input file for an external tool. Problematic paths are copied to a safe temp name,
+/// which is removed again when this value is dropped.
+pub struct ToolInput {
+    path: PathBuf,
+    is_temp_copy: bool,
+}
+
+impl ToolInput {
+    pub fn prepare(path: &Path) -> Result<Self, String> {
+        if is_tool_safe(path) {
+            return Ok(Self {
+                path: path.to_path_buf(),
+                is_temp_copy: false,
+            });
+        }
+
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_else(|| "bin".to_string());
+        let safe_path = unique_safe_path(&extension);
+        fs::copy(to_extended_length(path), &safe_path)
+            .map_err(|e| format!("Failed to copy {:?} to a safe temp path: {}", path, e))?;
+
+        Ok(Self {
+            path: safe_path,
+            is_temp_copy: true,
+        })
+    }
+
+    pub fn arg(&self) -> String {
+        self.path.to_string_lossy().to_string()
+    }
+}
+
+impl Drop for ToolInput {
+    fn drop(&mut self) {
+        if self.is_temp_copy {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Output file for an external tool. When the final destination is problematic the tool
+/// writes to a safe temp name and `finish` moves the result into place.
+pub struct ToolOutput {
+    final_path: PathBuf,
+    tool_path: PathBuf,
+}
+
+impl ToolOutput {
+    pub fn new(final_path: &Path) -> Self {
+        let tool_path = if is_tool_safe(final_path) {
+            final_path.to_path_buf()
+        } else {
+            let extension = final_path
+                .extension()
+                .map(|e| e.to_string_lossy().to_string())
+                .unwrap_or_else(|| "wav".to_string());
+            unique_safe_path(&extension)
+        };
+        Self {
+            final_path: final_path.to_path_buf(),
+            tool_path,
+        }
+    }
+
+    pub fn arg(&self) -> String {
+        self.tool_path.to_string_lossy().to_string()
+    }
+
+    /// Move the tool output to the final destination (no-op when they are the same)
+    pub fn finish(&self) -> Result<(), String> {
+        if self.tool_path == self.final_path {
+            return Ok(());
+        }
+        fs::copy(&self.tool_path, to_extended_length(&self.final_path))
+            .map_err(|e| format!("Failed to move tool output to {:?}: {}", self.final_path, e))?;
+        let _ = fs::remove_file(&self.tool_path);
+        Ok(())
+    }
+}
+
+impl Drop for ToolOutput {
+    fn drop(&mut self) {
+        if self.tool_path != self.final_path {
+            let _ = fs::remove_file(&self.tool_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_file_name_replaces_unsafe_chars() {
+        assert_eq!(ascii_file_name("bgm_01.wav"), "bgm_01.wav");
+        assert_eq!(ascii_file_name("ガンダム 01.wav"), "_____01.wav");
+        assert_eq!(ascii_file_name(""), "audio");
+    }
+
+    #[test]
+    fn mirrored_output_dirs_keep_the_folder_layout() {
+        let banks = vec![
+            "/game/sound/bgm/stage01.nus3bank".to_string(),
+            "/game/sound/voice/amuro.nus3bank".to_string(),
+            "/game/sound/voice/amuro.nus3audio".to_string(),
+        ];
+        let dirs = mirrored_output_dirs(&banks, "/out");
+        assert_eq!(dirs[0], Path::new("/out/bgm/stage01"));
+        assert_eq!(dirs[1], Path::new("/out/voice/amuro_nus3bank"));
+        assert_eq!(dirs[2], Path::new("/out/voice/amuro_nus3audio"));
+    }
+
+    #[test]
+    fn unicode_input_is_copied_to_safe_name() {
+        let dir = std::env::temp_dir().join("exvs2_path_utils_テスト");
+        fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("音声.wav");
+        fs::write(&src, b"RIFF").unwrap();
+
+        let input = ToolInput::prepare(&src).unwrap();
+        assert!(is_tool_safe(&input.path));
+        assert_eq!(fs::read(&input.path).unwrap(), b"RIFF");
+
+        let copy = input.path.clone();
+        drop(input);
+        assert!(!copy.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::audio_processing::smpl;
+use crate::track_key::TrackKey;
+
+use super::save_summary::format_size;
+
+/// Sample rates offered when a payload has to shrink, highest first
+const RATES: &[u32] = &[32000, 24000, 22050, 16000, 11025];
+
+/// A lower rate and/or mono mixdown that brings a PCM16 payload under the limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownsampleOption {
+    pub sample_rate: u32,
+    pub mono: bool,
+    pub estimated_size: usize,
+}
+
+impl DownsampleOption {
+    pub fn label(&self) -> String {
+        format!(
+            "{} Hz{} (~{})",
+            self.sample_rate,
+            if self.mono { " mono" } else { "" },
+            format_size(self.estimated_size)
+        )
+    }
+}
+
+/// A pending payload larger than the preload limit of its bank
+#[derive(Debug, Clone, PartialEq)]
+pub struct OversizedPayload {
+    pub key: TrackKey,
+    pub size: usize,
+    /// Empty when the payload is not PCM16 or nothing fits
+    pub options: Vec<DownsampleOption>,
+}
+
+/// Conversions of a PCM16 WAV that fit in `limit`, best quality first. At most three are
+/// offered; stereo payloads also get mono variants.
+pub fn downsample_options(data: &[u8], limit: usize) -> Vec<DownsampleOption> {
+    let Some(info) = smpl::pcm16_info(data) else {
+        return Vec::new();
+    };
+    let layouts: &[bool] = if info.channels > 1 { &[false, true] } else { &[false] };
+    let mut options: Vec<DownsampleOption> = RATES
+        .iter()
+        .filter(|rate| **rate < info.sample_rate)
+        .flat_map(|&sample_rate| {
+            layouts.iter().map(move |&mono| {
+                let channels = if mono { 1 } else { info.channels as usize };
+                let frames = info.frames as u64 * sample_rate as u64 / info.sample_rate as u64;
+                DownsampleOption {
+                    sample_rate,
+                    mono,
+                    estimated_size: 44 + frames as usize * channels * 2,
+                }
+            })
+        })
+        .filter(|option| option.estimated_size <= limit)
+        .collect();
+    options.sort_by(|a, b| b.estimated_size.cmp(&a.estimated_size));
+    options.truncate(3);
+    options
+}
+
+/// Payloads over `limit`, largest first
+pub fn check<'a>(payloads: impl IntoIterator<Item = (&'a TrackKey, &'a [u8])>, limit: usize) -> Vec<OversizedPayload> {
+    let mut oversized: Vec<OversizedPayload> = payloads
+        .into_iter()
+        .filter(|(_, data)| data.len() > limit)
+        .map(|(key, data)| OversizedPayload {
+            key: key.clone(),
+            size: data.len(),
+            options: downsample_options(data, limit),
+        })
+        .collect();
+    oversized.sort_by(|a, b| b.size.cmp(&a.size));
+    oversized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_processing::splice;
+
+    #[test]
+    fn flags_large_payloads_with_fitting_rates() {
+        // One second of 48 kHz stereo, ~188 KB
+        let wav = splice::write_pcm16(2, 48000, &vec![0u8; 48000 * 4]);
+        let small = splice::write_pcm16(1, 48000, &[0u8; 200]);
+        let big_key = TrackKey::bank("0x1", "se_big");
+        let small_key = TrackKey::bank("0x2", "se_small");
+        let found = check([(&big_key, wav.as_slice()), (&small_key, small.as_slice())], 100 * 1024);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].key, big_key);
+        let rates: Vec<(u32, bool)> = found[0].options.iter().map(|o| (o.sample_rate, o.mono)).collect();
+        assert_eq!(rates, vec![(24000, false), (22050, false), (32000, true)]);
+        assert!(found[0].options.iter().all(|o| o.estimated_size <= 100 * 1024));
+    }
+}
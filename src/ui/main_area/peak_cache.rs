@@ -0,0 +1,57 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+
+use super::path_utils;
+use super::waveform::Waveform;
+
+/// Folder under the temp directory holding the peak files
+const CACHE_DIR: &str = "exvs2_peaks";
+
+/// Peak files kept; the oldest are removed past this
+const MAX_PEAK_FILES: usize = 512;
+
+/// Peak files of decoded tracks on disk, so a waveform seen before draws without decoding
+pub struct PeakCache;
+
+impl PeakCache {
+    /// Hash of the audio a peak file was made from. A changed payload decodes to other bytes,
+    /// so its old peak file is simply never found again.
+    pub fn hash_of(path: &Path) -> Option<u64> {
+        let data = std::fs::read(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&data);
+        Some(hasher.finish())
+    }
+
+    /// Waveform saved for audio hashing to `hash`, if there is one
+    pub fn load(hash: u64) -> Option<Waveform> {
+        let data = std::fs::read(peak_path(hash)).ok()?;
+        Waveform::from_peak_file(&data)
+    }
+
+    /// Save the peaks of audio hashing to `hash`, dropping the oldest peak files past the limit
+    pub fn store(hash: u64, waveform: &Waveform) -> Result<(), String> {
+        let path = peak_path(hash);
+        let dir = path.parent().unwrap_or(Path::new("."));
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        std::fs::write(&path, waveform.to_peak_file()).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+        let mut files: Vec<_> = std::fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .flatten()
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        if files.len() > MAX_PEAK_FILES {
+            files.sort();
+            for (_, old) in &files[..files.len() - MAX_PEAK_FILES] {
+                let _ = std::fs::remove_file(old);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn peak_path(hash: u64) -> PathBuf {
+    path_utils::safe_temp_dir().join(CACHE_DIR).join(format!("{:016x}.peaks", hash))
+}
@@ -0,0 +1,102 @@
+use once_cell::sync::Lazy;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::tools::{split_command, ExternalRunner};
+
+// Outcomes of hooks that finished in the background, waiting to be shown as toasts
+static FINISHED_HOOKS: Lazy<Mutex<Vec<Result<String, String>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Command run after every successful save, e.g. to copy the file into the game folder,
+/// start the game or run a packer script
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct PostSaveHook {
+    pub enabled: bool,
+    pub command: String,
+}
+
+/// `template` with every placeholder filled in for a save of `original` to `saved`
+fn expand(template: &str, saved: &Path, original: &Path) -> String {
+    let text = |p: Option<&std::ffi::OsStr>| p.map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    template
+        .replace("{path}", &saved.to_string_lossy())
+        .replace("{dir}", &text(saved.parent().map(Path::as_os_str)))
+        .replace("{file}", &text(saved.file_name()))
+        .replace("{bank}", &text(saved.file_stem()))
+        .replace("{original}", &original.to_string_lossy())
+}
+
+impl PostSaveHook {
+    /// Placeholders understood in the command template, with what they stand for
+    pub const PLACEHOLDERS: [(&str, &str); 5] = [
+        ("{path}", "full path of the saved file"),
+        ("{dir}", "folder of the saved file"),
+        ("{file}", "file name of the saved file"),
+        ("{bank}", "saved file name without extension"),
+        ("{original}", "path of the file that was opened"),
+    ];
+
+    /// Program and arguments for a save of `original` to `saved`
+    pub fn command_line(&self, saved: &Path, original: &Path) -> Result<(String, Vec<String>), String> {
+        let mut args = split_command(&self.command)?
+            .into_iter()
+            .map(|arg| expand(&arg, saved, original));
+        let program = args.next().ok_or("The after-save command is empty")?;
+        Ok((program, args.collect()))
+    }
+
+    /// Start the command in the background if it is enabled. The outcome is collected with
+    /// `take_finished` so a long-running command, such as the game itself, does not block.
+    pub fn run(&self, saved: &Path, original: &Path) {
+        if !self.enabled || self.command.trim().is_empty() {
+            return;
+        }
+        let command_line = self.command_line(saved, original);
+        std::thread::spawn(move || {
+            let result = command_line.and_then(|(program, args)| {
+                println!("Running after-save command: {} {}", program, args.join(" "));
+                ExternalRunner::locate(&program)?.run(&args)?;
+                Ok(format!("After-save command finished: {}", program))
+            });
+            if let Ok(mut finished) = FINISHED_HOOKS.lock() {
+                finished.push(result.map_err(|e| format!("After-save command failed: {}", e)));
+            }
+        });
+    }
+}
+
+/// Outcomes of commands that finished since the last call
+pub fn take_finished() -> Vec<Result<String, String>> {
+    FINISHED_HOOKS.lock().map(|mut f| std::mem::take(&mut *f)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_placeholders_per_argument() {
+        let hook = PostSaveHook {
+            enabled: true,
+            command: r#"copy_to_game "{path}" "D:/Game Files/{file}" --name {bank} --from {original} {dir}"#.to_string(),
+        };
+        let saved = Path::new("/mods/out dir/bgm_boss.nus3audio");
+        let (program, args) = hook.command_line(saved, Path::new("/orig/bgm_boss.nus3audio")).unwrap();
+        assert_eq!(program, "copy_to_game");
+        assert_eq!(
+            args,
+            vec![
+                "/mods/out dir/bgm_boss.nus3audio",
+                "D:/Game Files/bgm_boss.nus3audio",
+                "--name",
+                "bgm_boss",
+                "--from",
+                "/orig/bgm_boss.nus3audio",
+                "/mods/out dir",
+            ]
+        );
+        let empty = PostSaveHook { enabled: true, command: "  ".to_string() };
+        assert!(empty.command_line(saved, saved).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::audio_file_info::AudioFileInfo;
+use super::loop_settings_modal::LoopSettings;
+
+/// File extension of project files
+pub const PROJECT_EXTENSION: &str = "eaeproj";
+
+/// Format version written by this build; newer files are refused rather than half-loaded
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A track as it is identified in a project file
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ProjectTrack {
+    pub name: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex_id: Option<String>,
+    #[serde(default)]
+    pub is_nus3bank: bool,
+    #[serde(default)]
+    pub filename: String,
+    #[serde(default)]
+    pub file_type: String,
+    #[serde(default)]
+    pub size: usize,
+}
+
+impl From<&AudioFileInfo> for ProjectTrack {
+    fn from(info: &AudioFileInfo) -> Self {
+        Self {
+            name: info.name.clone(),
+            id: info.id.clone(),
+            hex_id: info.hex_id.clone(),
+            is_nus3bank: info.is_nus3bank,
+            filename: info.filename.clone(),
+            file_type: info.file_type.clone(),
+            size: info.size,
+        }
+    }
+}
+
+impl ProjectTrack {
+    pub fn to_info(&self) -> AudioFileInfo {
+        AudioFileInfo {
+            name: self.name.clone(),
+            id: self.id.clone(),
+            size: self.size,
+            filename: self.filename.clone(),
+            file_type: self.file_type.clone(),
+            hex_id: self.hex_id.clone(),
+            is_nus3bank: self.is_nus3bank,
+        }
+    }
+
+    /// Whether `info` is this track in the opened bank
+    pub fn matches(&self, info: &AudioFileInfo) -> bool {
+        match (&self.hex_id, &info.hex_id) {
+            (Some(hex), Some(other)) => hex.eq_ignore_ascii_case(other) && self.name == info.name,
+            _ => self.name == info.name && self.id == info.id,
+        }
+    }
+}
+
+/// A replacement made from a file, re-run from that file when the project is opened
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectReplacement {
+    pub track: ProjectTrack,
+    pub source: PathBuf,
+    #[serde(default)]
+    pub loop_settings: LoopSettings,
+}
+
+/// A Replace with Empty
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectEmptyReplacement {
+    pub track: ProjectTrack,
+    pub min_ms: u32,
+}
+
+/// A track queued by Add Audio, with the file it was added from
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectAddition {
+    pub track: ProjectTrack,
+    pub source: PathBuf,
+}
+
+/// Pending edits of one bank, saved as `.eaeproj` JSON so a mod project survives restarts.
+/// Audio is referenced by path, not embedded; paths inside the project's folder are stored
+/// relative to it so the folder can be moved as a whole.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ProjectFile {
+    pub version: u32,
+    pub bank_path: PathBuf,
+    #[serde(default)]
+    pub replacements: Vec<ProjectReplacement>,
+    #[serde(default)]
+    pub empty_replacements: Vec<ProjectEmptyReplacement>,
+    #[serde(default)]
+    pub additions: Vec<ProjectAddition>,
+    #[serde(default)]
+    pub removals: Vec<ProjectTrack>,
+    /// Transcripts of the bank's voice cues by track key; not counted as edits
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub transcripts: BTreeMap<String, String>,
+}
+
+/// `path` relative to `base` when it lies inside it
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    path.strip_prefix(base).map(Path::to_path_buf).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl ProjectFile {
+    pub fn new(bank_path: PathBuf) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            bank_path,
+            replacements: Vec::new(),
+            empty_replacements: Vec::new(),
+            additions: Vec::new(),
+            removals: Vec::new(),
+            transcripts: BTreeMap::new(),
+        }
+    }
+
+    /// Number of pending edits in the project
+    pub fn change_count(&self) -> usize {
+        self.replacements.len() + self.empty_replacements.len() + self.additions.len() + self.removals.len()
+    }
+
+    /// Every referenced path, for rewriting them all at once
+    fn paths_mut(&mut self) -> impl Iterator<Item = &mut PathBuf> {
+        std::iter::once(&mut self.bank_path)
+            .chain(self.replacements.iter_mut().map(|r| &mut r.source))
+            .chain(self.additions.iter_mut().map(|a| &mut a.source))
+    }
+
+    /// Referenced audio files that no longer exist
+    pub fn missing_sources(&self) -> Vec<PathBuf> {
+        self.replacements
+            .iter()
+            .map(|r| &r.source)
+            .chain(self.additions.iter().map(|a| &a.source))
+            .filter(|p| !p.is_file())
+            .cloned()
+            .collect()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut stored = self.clone();
+        if let Some(base) = path.parent() {
+            for p in stored.paths_mut() {
+                *p = relative_to(p, base);
+            }
+        }
+        let json = serde_json::to_string_pretty(&stored).map_err(|e| format!("Failed to encode project: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut project: Self = serde_json::from_str(&text).map_err(|e| format!("Invalid project file: {}", e))?;
+        if project.version > PROJECT_VERSION {
+            return Err(format!(
+                "Project version {} needs a newer editor (this one reads up to {})",
+                project.version, PROJECT_VERSION
+            ));
+        }
+        if let Some(base) = path.parent() {
+            for p in project.paths_mut() {
+                if p.is_relative() {
+                    *p = base.join(&p);
+                }
+            }
+        }
+        Ok(project)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_with_paths_relative_to_the_project() {
+        let dir = std::env::temp_dir().join(format!("eaeproj_test_{}", std::process::id()));
+        fs::create_dir_all(dir.join("sources")).unwrap();
+        let source = dir.join("sources").join("boss.wav");
+        fs::write(&source, b"RIFF").unwrap();
+        let outside = std::env::temp_dir().join("elsewhere_missing.wav");
+
+        let track = AudioFileInfo::from_nus3bank_track("bgm_boss".to_string(), 3, "0x1f".to_string(), 10, "bgm_boss.wav".to_string());
+        let mut project = ProjectFile::new(dir.join("bgm.nus3bank"));
+        project.replacements.push(ProjectReplacement {
+            track: ProjectTrack::from(&track),
+            source: source.clone(),
+            loop_settings: LoopSettings { loop_start: Some(1.5), gain_db: -3.0, ..LoopSettings::default() },
+        });
+        project.additions.push(ProjectAddition { track: ProjectTrack::from(&track), source: outside.clone() });
+        project.removals.push(ProjectTrack::from(&track));
+
+        let path = dir.join("mod.eaeproj");
+        project.save(&path).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains("\"bank_path\": \"bgm.nus3bank\""));
+
+        let loaded = ProjectFile::load(&path).unwrap();
+        assert_eq!(loaded.bank_path, dir.join("bgm.nus3bank"));
+        assert_eq!(loaded.replacements[0].source, source);
+        assert_eq!(loaded.replacements[0].loop_settings.loop_start, Some(1.5));
+        assert!(loaded.replacements[0].track.matches(&track));
+        assert_eq!(loaded.change_count(), 3);
+        assert_eq!(loaded.missing_sources(), vec![outside]);
+
+        fs::write(&path, r#"{ "version": 99, "bank_path": "x.nus3bank" }"#).unwrap();
+        assert!(ProjectFile::load(&path).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
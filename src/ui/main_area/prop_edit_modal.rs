@@ -2,7 +2,7 @@ use egui::{Color32, Context, ScrollArea, Ui, Window};
 
 use crate::nus3bank::structures::{Nus3bankFile, PropLayout, PropSection};
 
-use super::prop_pending;
+use super::{audit_log, prop_pending};
 
 pub struct PropEditModal {
     pub open: bool,
@@ -10,6 +10,8 @@ pub struct PropEditModal {
     prop: Option<PropSection>,
     error: Option<String>,
     dirty: bool,
+    /// Pending edits were stored since the dialog opened; logged when it closes
+    edited: bool,
     
     // Preset values
     presets: Vec<DebugPreset>,
@@ -75,6 +77,7 @@ impl PropEditModal {
             prop: None,
             error: None,
             dirty: false,
+            edited: false,
             presets,
             selected_preset: None,
         }
@@ -84,6 +87,7 @@ impl PropEditModal {
         self.file_path = Some(file_path.to_string());
         self.error = None;
         self.dirty = false;
+        self.edited = false;
         self.selected_preset = None;
 
         match self.load_prop_for_file(file_path) {
@@ -119,6 +123,11 @@ impl PropEditModal {
         self.open = open;
         if was_open && !self.open {
             self.flush_pending();
+            if std::mem::take(&mut self.edited) {
+                if let Some(path) = self.file_path.as_deref() {
+                    audit_log::record_for(path, "Edited PROP section");
+                }
+            }
         }
     }
 
@@ -326,6 +335,7 @@ impl PropEditModal {
             unk2: preset.unk2,
             unk3: preset.unk3,
             layout: preset.layout,
+            raw: Vec::new(),
         });
         self.dirty = true;
         self.error = None;
@@ -369,6 +379,7 @@ impl PropEditModal {
             unk2: 3,
             unk3: 0,
             layout: PropLayout::Extended,
+            raw: Vec::new(),
         });
         self.dirty = true;
         self.error = None;
@@ -390,6 +401,7 @@ impl PropEditModal {
             self.error = Some(e);
             return;
         }
+        self.edited = true;
         self.error = None;
         self.dirty = false;
     }
@@ -0,0 +1,99 @@
+use crate::audio_processing::smpl;
+
+/// One-click filters above the track table. Categories match on the track name and
+/// widen each other; the others narrow the result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum QuickFilter {
+    Bgm,
+    Voice,
+    Se,
+    Modified,
+    HasLoop,
+    NoLoop,
+    UnknownFormat,
+}
+
+impl QuickFilter {
+    pub const ALL: [QuickFilter; 7] = [
+        QuickFilter::Bgm,
+        QuickFilter::Voice,
+        QuickFilter::Se,
+        QuickFilter::Modified,
+        QuickFilter::HasLoop,
+        QuickFilter::NoLoop,
+        QuickFilter::UnknownFormat,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            QuickFilter::Bgm => "BGM",
+            QuickFilter::Voice => "Voice",
+            QuickFilter::Se => "SE",
+            QuickFilter::Modified => "Modified",
+            QuickFilter::HasLoop => "Has loop",
+            QuickFilter::NoLoop => "No loop",
+            QuickFilter::UnknownFormat => "Unknown format",
+        }
+    }
+
+    pub fn hover(&self) -> &'static str {
+        match self {
+            QuickFilter::Bgm => "Tracks with \"bgm\" in the name",
+            QuickFilter::Voice => "Tracks named vo_/vc_/voice...",
+            QuickFilter::Se => "Tracks named se_...",
+            QuickFilter::Modified => "Tracks with a pending replacement",
+            QuickFilter::HasLoop => "Tracks whose payload or pending replacement loops",
+            QuickFilter::NoLoop => "Tracks that play once: jingles, voices and most effects",
+            QuickFilter::UnknownFormat => "Tracks whose payload type was not recognized",
+        }
+    }
+
+    /// Whether the filter picks tracks by their name
+    pub fn is_category(&self) -> bool {
+        matches!(self, QuickFilter::Bgm | QuickFilter::Voice | QuickFilter::Se)
+    }
+}
+
+/// Category of a track from its name, if it follows the usual prefixes
+pub fn name_category(name: &str) -> Option<QuickFilter> {
+    let name = name.to_lowercase();
+    if name.contains("bgm") {
+        Some(QuickFilter::Bgm)
+    } else if ["vo_", "vc_", "voice"].iter().any(|p| name.starts_with(p))
+        || name.contains("_vo_")
+        || name.contains("_vc_")
+    {
+        Some(QuickFilter::Voice)
+    } else if name.starts_with("se_") || name.contains("_se_") {
+        Some(QuickFilter::Se)
+    } else {
+        None
+    }
+}
+
+/// Whether a payload carries loop points: the `smpl` chunk of a WAV, or the loop end of a
+/// Switch OPUS header. Other codecs are never flagged.
+pub fn payload_loops(data: &[u8]) -> bool {
+    if data.starts_with(b"RIFF") {
+        return smpl::read_loop(data).is_some();
+    }
+    data.starts_with(b"OPUS")
+        && data
+            .get(0x18..0x1C)
+            .is_some_and(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn categories_follow_name_prefixes() {
+        assert_eq!(name_category("BGM_Stage01"), Some(QuickFilter::Bgm));
+        assert_eq!(name_category("vo_amuro_001"), Some(QuickFilter::Voice));
+        assert_eq!(name_category("rx78_vc_attack"), Some(QuickFilter::Voice));
+        assert_eq!(name_category("se_beam_rifle"), Some(QuickFilter::Se));
+        assert_eq!(name_category("jingle"), None);
+        assert!(!payload_loops(b"OPUS"));
+    }
+}
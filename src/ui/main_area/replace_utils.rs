@@ -1,19 +1,35 @@
 use super::audio_file_info::AudioFileInfo;
-use super::loop_settings_modal::LoopSettingsModal;
-use crate::nus3bank::replace::Nus3bankReplacer;
+use super::loop_settings_modal::{LoopSettings, LoopSettingsModal};
+use crate::nus3bank::dry_run::DryRunReport;
+use crate::nus3bank::verify::VerifyReport;
+use crate::nus3bank::replace::{Nus3bankReplacer, ReplaceOperation};
+use crate::audio_processing::decode;
+use crate::audio_processing::mix::{self, MixSettings};
+use crate::audio_processing::resample;
+use crate::audio_processing::smpl::{self, SmplLoop};
+use crate::audio_processing::splice;
+use crate::wav::riff::Riff;
+use crate::tools::{self, VgmstreamRunner};
+use crate::track_key::TrackKey;
 use hound;
 use nus3audio::{AudioFile, Nus3audioFile};
 use once_cell::sync::Lazy;
 use rfd::FileDialog;
 use std::collections::HashMap;
 use std::fs;
-#[cfg(windows)]
-use std::os::windows::process::CommandExt;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 use std::sync::Mutex;
 
+use super::audit_log;
+use super::conversion_log;
+use super::empty_wav::{self, SilentFormat};
+use super::file_types::FileTypeRegistry;
+use super::export_utils::ExportUtils;
 use super::grp_list_modal::apply_grp_names_to_file;
+use super::path_utils;
+use super::payload_limit::{self, DownsampleOption, OversizedPayload};
+use super::sample_rate_check;
+use super::source_stamp::{SourceChange, SourceStamp};
 use super::grp_pending;
 use super::dton_pending;
 use super::dton_tones_modal::apply_dton_tones_to_file;
@@ -21,112 +37,97 @@ use super::prop_pending;
 use super::prop_edit_modal::apply_prop_to_file;
 
 // Store replaced audio data in a static HashMap.
-// Key format: "file_path:audio_name"; value: replaced audio bytes.
-static REPLACED_AUDIO_DATA: Lazy<Mutex<HashMap<String, Vec<u8>>>> =
+// Key: the track's TrackKey (see `AudioFileInfo::track_key`); value: replaced audio bytes.
+static REPLACED_AUDIO_DATA: Lazy<Mutex<HashMap<TrackKey, Vec<u8>>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
 // Store loop settings in a static HashMap.
-// Key format: "file_path:audio_name"; value: (loop_start, loop_end, use_custom_loop).
-static LOOP_SETTINGS: Lazy<Mutex<HashMap<String, (Option<f32>, Option<f32>, bool)>>> =
+// Key: the track's TrackKey; value: loop and gain settings used for the replacement.
+static LOOP_SETTINGS: Lazy<Mutex<HashMap<TrackKey, LoopSettings>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
 // Store user-selected replacement file paths in a static HashMap.
-// Key format: "file_path:audio_name"; value: replacement file path.
-static REPLACEMENT_FILE_PATHS: Lazy<Mutex<HashMap<String, PathBuf>>> =
+// Key: the track's TrackKey; value: replacement file path.
+static REPLACEMENT_FILE_PATHS: Lazy<Mutex<HashMap<TrackKey, PathBuf>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
-/// Utility functions for replacing audio files
-pub struct ReplaceUtils;
+// Tracks replaced with silence, keyed like REPLACED_AUDIO_DATA. The silence is rebuilt on
+// save to match each target's original format.
+static EMPTY_REPLACEMENTS: Lazy<Mutex<HashMap<TrackKey, EmptyReplacement>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
-impl ReplaceUtils {
-    pub(crate) fn is_standard_pcm16_wav(data: &[u8]) -> bool {
-        // Minimal WAV header check:
-        // - RIFF/WAVE container
-        // - fmt chunk exists
-        // - wFormatTag == 1 (PCM)
-        // - fmt chunk size == 16
-        // - bits_per_sample == 16
-        if data.len() < 12 {
-            return false;
-        }
-        if &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
-            return false;
-        }
-
-        let mut p = 12usize;
-        while p + 8 <= data.len() {
-            let cid = &data[p..p + 4];
-            p += 4;
-            let clen = u32::from_le_bytes([data[p], data[p + 1], data[p + 2], data[p + 3]]) as usize;
-            p += 4;
-
-            if p + clen > data.len() {
-                return false;
-            }
+// How each replacement source file looked when it was processed, keyed like
+// REPLACEMENT_FILE_PATHS. Used to notice sources re-exported after they were queued.
+static SOURCE_STAMPS: Lazy<Mutex<HashMap<TrackKey, SourceStamp>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
 
-            if cid == b"fmt " {
-                if clen != 16 || clen < 16 {
-                    return false;
-                }
-                if p + 16 > data.len() {
-                    return false;
-                }
-                let w_format_tag = u16::from_le_bytes([data[p], data[p + 1]]);
-                let bits_per_sample = u16::from_le_bytes([data[p + 14], data[p + 15]]);
-                return w_format_tag == 1 && bits_per_sample == 16;
-            }
+/// A registered Replace with Empty, re-generated per target on save
+#[derive(Debug, Clone)]
+struct EmptyReplacement {
+    /// The track as it was before the replacement
+    original: AudioFileInfo,
+    min_ms: u32,
+    /// The buffer last stored in REPLACED_AUDIO_DATA; if the map holds something else the
+    /// track has been replaced again since
+    data: Vec<u8>,
+}
 
-            p += clen;
-            if clen % 2 != 0 {
-                p += 1;
-            }
-        }
+/// Copy of every pending replacement, taken by a snapshot and put back on restore
+#[derive(Clone, Default)]
+pub struct ReplacementState {
+    data: HashMap<TrackKey, Vec<u8>>,
+    loop_settings: HashMap<TrackKey, LoopSettings>,
+    paths: HashMap<TrackKey, PathBuf>,
+    empties: HashMap<TrackKey, EmptyReplacement>,
+    stamps: HashMap<TrackKey, SourceStamp>,
+    logs: HashMap<TrackKey, Vec<String>>,
+    bank_operations: HashMap<String, HashMap<String, ReplaceOperation>>,
+    tone_orders: HashMap<String, Vec<usize>>,
+}
 
-        false
+impl ReplacementState {
+    /// Number of replaced tracks
+    pub fn len(&self) -> usize {
+        self.data.len()
     }
+}
 
-    pub(crate) fn convert_audio_bytes_to_pcm_wav(data: &[u8]) -> Result<Vec<u8>, String> {
-        // Convert arbitrary audio bytes (including non-standard WAV) to a standard PCM WAV
-        // using vgmstream-cli. This is used to normalize legacy WAV payloads that the game
-        // cannot decode (e.g. WAVEFORMATEXTENSIBLE with a custom SubFormat GUID).
+/// Utility functions for replacing audio files
+pub struct ReplaceUtils;
 
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
-        if !vgmstream_path.exists() {
-            return Err(format!("vgmstream-cli not found at {:?}", vgmstream_path));
+impl ReplaceUtils {
+    pub(crate) fn convert_audio_bytes_to_pcm_wav(data: &[u8]) -> Result<Vec<u8>, String> {
+        // Convert arbitrary audio bytes (including non-standard WAV) to a standard PCM WAV.
+        // Common formats are decoded in memory; anything else goes through vgmstream-cli,
+        // which also normalizes legacy WAV payloads that the game cannot decode (e.g.
+        // WAVEFORMATEXTENSIBLE with a custom SubFormat GUID).
+        match decode::decode_to_pcm16_wav(data, None) {
+            Ok(wav) => return Ok(wav),
+            Err(e) => println!("In-memory decode failed, using vgmstream-cli: {}", e),
         }
 
-        let temp_dir = std::env::temp_dir();
+        let vgmstream = VgmstreamRunner::locate()?;
+
+        let temp_dir = path_utils::safe_temp_dir();
         let input_path = temp_dir.join("nus3bank_in.wav");
         let output_path = temp_dir.join("nus3bank_out_pcm.wav");
 
         std::fs::write(&input_path, data)
             .map_err(|e| format!("Failed to write temp input audio: {}", e))?;
 
-        let mut command = Command::new(&vgmstream_path);
-
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
-
         // -i: ignore any looping information (decode once)
         // -o: output WAV path
-        let result = command
-            .args([
-                "-i",
-                "-o",
-                &output_path.to_string_lossy(),
-                &input_path.to_string_lossy(),
-            ])
-            .output()
-            .map_err(|e| format!("Failed to run vgmstream-cli: {}", e))?;
-
-        if !result.status.success() {
-            let stderr = String::from_utf8_lossy(&result.stderr);
+        let result = vgmstream.run(&[
+            "-i",
+            "-o",
+            &output_path.to_string_lossy(),
+            &input_path.to_string_lossy(),
+        ]);
+
+        if let Err(e) = result {
             let _ = std::fs::remove_file(&input_path);
             let _ = std::fs::remove_file(&output_path);
-            return Err(format!("vgmstream-cli error: {}", stderr));
+            return Err(e);
         }
 
         let wav_data = std::fs::read(&output_path)
@@ -138,26 +139,15 @@ impl ReplaceUtils {
         Ok(wav_data)
     }
 
-    /// Replace audio data in memory only (does not modify the actual file on disk)
-    /// Supports both NUS3AUDIO and NUS3BANK files
-    pub fn replace_in_memory(
+    /// Register `replacement_data`, converted from `source`, as the replacement of a track
+    /// in memory only (the file on disk is not modified). Supports both NUS3AUDIO and NUS3BANK.
+    fn replace_in_memory_with_data(
         audio_file_info: &AudioFileInfo,
-        replacement_file_path: &str,
+        replacement_data: Vec<u8>,
+        source: &Path,
     ) -> Result<AudioFileInfo, String> {
-        // Load the replacement file data
-        let replacement_data = match fs::read(replacement_file_path) {
-            Ok(data) => data,
-            Err(e) => return Err(format!("Failed to read replacement file: {}", e)),
-        };
-
-        // Create the key based on file type
-        let key = if audio_file_info.is_nus3bank {
-            // For NUS3BANK, use hex_id:name format
-            format!("{}:{}", audio_file_info.hex_id.as_ref().unwrap_or(&audio_file_info.id), audio_file_info.name)
-        } else {
-            // For NUS3AUDIO, use original name:id format
-            format!("{}:{}", audio_file_info.name, audio_file_info.id)
-        };
+        let replacement_file_path = source.to_string_lossy();
+        let key = audio_file_info.track_key();
 
         // Store the replacement data in our static HashMap
         {
@@ -169,7 +159,7 @@ impl ReplaceUtils {
 
         // Store the replacement file path
         {
-            let path_buf = Path::new(replacement_file_path).to_path_buf();
+            let path_buf = source.to_path_buf();
             let map_result = REPLACEMENT_FILE_PATHS.lock();
             if let Ok(mut map) = map_result {
                 map.insert(key, path_buf);
@@ -177,7 +167,7 @@ impl ReplaceUtils {
         }
 
         // Get the filename for the new AudioFileInfo
-        let filename = Path::new(replacement_file_path)
+        let filename = Path::new(replacement_file_path.as_ref())
             .file_name()
             .unwrap_or_default()
             .to_string_lossy()
@@ -197,24 +187,34 @@ impl ReplaceUtils {
         Ok(new_audio_info)
     }
 
+    /// Custom loop of `settings` as first and last frame for a file of `frames` frames at
+    /// `sample_rate`. Exact frames from the loop editor are used as they are; seconds are
+    /// converted otherwise. `None` when no custom loop start is set.
+    fn custom_loop_frames(settings: &LoopSettings, sample_rate: u32, frames: u32) -> Option<(u32, u32)> {
+        if !(settings.enable_loop && settings.use_custom_loop) {
+            return None;
+        }
+        let last = frames.saturating_sub(1);
+        let to_sample = |seconds: f32| (seconds * sample_rate as f32) as u32;
+        let start = settings.loop_start_sample.or(settings.loop_start.map(to_sample))?;
+        let end = settings.loop_end_sample.or(settings.loop_end.map(to_sample)).unwrap_or(last);
+        Some((start.min(last), end.min(last)))
+    }
+
     /// Process audio file with vgmstream-cli to add loop points
-    pub fn process_with_vgmstream(
-        file_path: &Path,
-        loop_start: Option<f32>,
-        loop_end: Option<f32>,
-        use_custom_loop: bool,
-        enable_loop: bool,
-    ) -> Result<PathBuf, String> {
-        // Path to vgmstream-cli.exe in tools directory
-        let vgmstream_path = Path::new("tools").join("vgmstream-cli.exe");
-        if !vgmstream_path.exists() {
-            return Err(format!("vgmstream-cli not found at {:?}", vgmstream_path));
+    pub fn process_with_vgmstream(file_path: &Path, settings: &LoopSettings) -> Result<PathBuf, String> {
+        let enable_loop = settings.enable_loop;
+        // PCM16 WAVs only need the smpl chunk; no decoding, so no external tool
+        if let Some(path) = Self::process_pcm16_natively(file_path, settings)? {
+            return Ok(path);
         }
 
-        // Create a temporary output file path
-        let temp_dir = std::env::temp_dir();
+        let vgmstream = VgmstreamRunner::locate()?;
+
+        // Create a temporary output file path (ASCII-only so vgmstream can open it)
+        let temp_dir = path_utils::safe_temp_dir();
         let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
-        let temp_filename = format!("looping_{}.wav", stem);
+        let temp_filename = format!("looping_{}.wav", path_utils::ascii_file_name(&stem));
         let temp_output_path = temp_dir.join(&temp_filename);
         let temp_output_path_str = temp_output_path.to_string_lossy().to_string();
 
@@ -245,13 +245,7 @@ impl ReplaceUtils {
         //     -V: print version info and supported extensions as JSON
         //     -I: print requested file info as JSON
         //     -h: print all commands
-        let mut command = Command::new(&vgmstream_path);
-
-        #[cfg(windows)]
-        {
-            use winapi::um::winbase::CREATE_NO_WINDOW;
-            command.creation_flags(CREATE_NO_WINDOW);
-        }
+        let mut command = vgmstream.command();
 
         // Start building arguments
         let mut args: Vec<String> = vec!["-i".to_string()];
@@ -269,20 +263,21 @@ impl ReplaceUtils {
         args.push("-o".to_string());
         args.push(temp_output_path_str);
 
-        // 将路径转换为字符串，并将其所有权移入args向量
-        let file_path_str = file_path.to_string_lossy().into_owned();
-        args.push(file_path_str);
+        // Unicode or overlong source paths are copied to a safe temp name first
+        let tool_input = path_utils::ToolInput::prepare(file_path)?;
+        args.push(tool_input.arg());
 
         println!(
             "Running command: {:?} {}",
-            vgmstream_path,
+            vgmstream.path(),
             args.join(" ")
         );
 
         // 将Vec<String>转换为Vec<&str>以传递给command.args()
         let args_ref: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
 
-        let result = command.args(args_ref).output();
+        let result = command.args(&args_ref).output();
+        crate::tools::log_run(vgmstream.path(), &args_ref, &result);
         println!("vgmstream-cli command result: {:?}", result);
 
         match result {
@@ -294,22 +289,16 @@ impl ReplaceUtils {
                     );
                     
                     // Apply custom loop points if specified (after vgmstream processing)
-                    if use_custom_loop {
-                        if let Some(start) = loop_start {
-                            // Get the sample rate from the processed WAV file
-                            let sample_rate = Self::get_wav_sample_rate(&temp_output_path)?;
-                            let start_sample = (start * sample_rate as f32) as u32;
-                            
-                            let end_sample = if let Some(end) = loop_end {
-                                (end * sample_rate as f32) as u32
-                            } else {
-                                // If no end specified, use the total samples
-                                Self::get_wav_total_samples(&temp_output_path)?
-                            };
-                            
+                    if settings.use_custom_loop {
+                        // Get the sample rate from the processed WAV file
+                        let sample_rate = Self::get_wav_sample_rate(&temp_output_path)?;
+                        let total_samples = Self::get_wav_total_samples(&temp_output_path)?;
+                        if let Some((start_sample, end_sample)) =
+                            Self::custom_loop_frames(settings, sample_rate, total_samples)
+                        {
                             // Modify the WAV file's smpl chunk with custom loop points
                             Self::modify_wav_smpl_chunk(&temp_output_path, start_sample, end_sample)?;
-                            
+
                             println!("Applied custom loop points: start={} samples, end={} samples", start_sample, end_sample);
                         }
                     }
@@ -324,11 +313,87 @@ impl ReplaceUtils {
         }
     }
 
-    /// Get stored replacement file path for a specific audio key (name:id)
-    pub fn get_replacement_path(audio_name: &str, audio_id: &str) -> Option<PathBuf> {
-        let key = format!("{}:{}", audio_name, audio_id);
+    /// Write the looping WAV for a PCM16 WAV input without vgmstream-cli. Loop points are
+    /// the custom ones when set, otherwise the whole file (like `-e -L`). Returns `None`
+    /// for inputs that are not plain PCM16 WAV and need decoding.
+    fn process_pcm16_natively(file_path: &Path, settings: &LoopSettings) -> Result<Option<PathBuf>, String> {
+        let data = fs::read(file_path).map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+        if smpl::pcm16_info(&data).is_none() {
+            return Ok(None);
+        }
+
+        let output = Self::loop_pcm16(&data, settings)?;
+        let stem = file_path.file_stem().unwrap_or_default().to_string_lossy();
+        let output_path = path_utils::safe_temp_dir().join(format!("looping_{}.wav", path_utils::ascii_file_name(&stem)));
+        fs::write(&output_path, output).map_err(|e| format!("Failed to write looping WAV: {}", e))?;
+        println!("Wrote looping WAV natively: {:?}", output_path);
+        Ok(Some(output_path))
+    }
+
+    /// Rewrite a PCM16 WAV with the smpl loop of `settings`: the custom loop when set,
+    /// otherwise the whole file (like `-e -L`), or no loop when looping is disabled
+    fn loop_pcm16(data: &[u8], settings: &LoopSettings) -> Result<Vec<u8>, String> {
+        let info = smpl::pcm16_info(data).ok_or("Not a PCM16 WAV file")?;
+        let last = info.frames.saturating_sub(1);
+        let lp = if settings.enable_loop {
+            match Self::custom_loop_frames(settings, info.sample_rate, info.frames) {
+                Some((start, end)) => Some(SmplLoop { start, end }),
+                None => Some(SmplLoop { start: 0, end: last }),
+            }
+        } else {
+            None
+        };
+        if let Some(lp) = lp {
+            println!("Writing smpl loop {}..{}", lp.start, lp.end);
+        }
+        smpl::rewrite_pcm16(data, lp)
+    }
+
+    /// Decode, gain and loop a replacement entirely in memory. Returns `None` for sources
+    /// that are neither PCM16 WAV nor decodable here, which go through vgmstream-cli.
+    fn process_in_memory(file_path: &Path, settings: &LoopSettings) -> Result<Option<Vec<u8>>, String> {
+        let data = fs::read(file_path).map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+        let pcm = if smpl::pcm16_info(&data).is_some() {
+            data
+        } else if decode::handles_extension(&file_path.to_string_lossy()) {
+            let extension = file_path.extension().and_then(|e| e.to_str());
+            match decode::decode_to_pcm16_wav(&data, extension) {
+                Ok(pcm) => pcm,
+                Err(e) => {
+                    println!("In-memory decode of {:?} failed, using vgmstream-cli: {}", file_path, e);
+                    return Ok(None);
+                }
+            }
+        } else {
+            return Ok(None);
+        };
+
+        let pcm = if settings.gain_db.abs() > f32::EPSILON {
+            Self::apply_pcm16_gain(&pcm, settings.gain_db)?
+        } else {
+            pcm
+        };
+        Self::loop_pcm16(&pcm, settings).map(Some)
+    }
+
+    /// Apply gain in decibels to a PCM16 WAV in memory, dropping its other chunks
+    fn apply_pcm16_gain(data: &[u8], gain_db: f32) -> Result<Vec<u8>, String> {
+        let (info, samples) = smpl::pcm16_samples(data).ok_or("Not a PCM16 WAV file")?;
+        let gain = 10f32.powf(gain_db / 20.0);
+        let scaled: Vec<u8> = samples
+            .chunks_exact(2)
+            .flat_map(|s| {
+                let v = i16::from_le_bytes([s[0], s[1]]) as f32 / 32768.0;
+                (((v * gain).clamp(-1.0, 1.0) * 32767.0).round() as i16).to_le_bytes()
+            })
+            .collect();
+        Ok(splice::write_pcm16(info.channels, info.sample_rate, &scaled))
+    }
+
+    /// Get the stored replacement file path of a track
+    pub fn get_replacement_path(audio_file_info: &AudioFileInfo) -> Option<PathBuf> {
         if let Ok(map) = REPLACEMENT_FILE_PATHS.lock() {
-            map.get(&key).cloned()
+            map.get(&audio_file_info.track_key()).cloned()
         } else {
             None
         }
@@ -418,6 +483,23 @@ impl ReplaceUtils {
         // Make a clone so we own the path (prevents borrowing errors)
         let selected_path = result.unwrap().clone();
 
+        Self::replace_with_path(audio_file_info, selected_path, loop_settings_modal)
+    }
+
+    /// Point the pending replacement of a track at another source file
+    pub fn set_replacement_path(audio_file_info: &AudioFileInfo, path: PathBuf) {
+        if let Ok(mut map) = REPLACEMENT_FILE_PATHS.lock() {
+            map.insert(audio_file_info.track_key(), path);
+        }
+    }
+
+    /// Register `selected_path` as the replacement source and open the loop settings modal.
+    /// Used by the file dialog and by URL imports.
+    pub fn replace_with_path(
+        audio_file_info: &AudioFileInfo,
+        selected_path: PathBuf,
+        loop_settings_modal: &mut LoopSettingsModal,
+    ) -> Result<AudioFileInfo, String> {
         // Extract filename safely as a string
         let mut filename = String::from("unknown");
         if let Some(name) = selected_path.file_name() {
@@ -426,19 +508,12 @@ impl ReplaceUtils {
             }
         }
 
-        // Create key for hashmaps - Use the original audio name and ID
-        let map_key = format!("{}:{}", audio_file_info.name, audio_file_info.id);
-
         // Store file path only - no audio data is replaced yet
-        let replacement_path = selected_path.clone();
-        if let Ok(mut map) = REPLACEMENT_FILE_PATHS.lock() {
-            map.insert(map_key.clone(), replacement_path);
-        }
+        Self::set_replacement_path(audio_file_info, selected_path.clone());
 
         // Initialize with empty loop settings
-        let empty_loop_settings = (None, None, false);
         if let Ok(mut settings) = LOOP_SETTINGS.lock() {
-            settings.insert(map_key, empty_loop_settings);
+            settings.insert(audio_file_info.track_key(), LoopSettings::default());
         }
 
         // 创建一个新的AudioFileInfo，但保持原始的name和id
@@ -468,6 +543,41 @@ impl ReplaceUtils {
         use_custom_loop: bool,
         enable_loop: bool,
         gain_db: f32,
+    ) -> Result<AudioFileInfo, String> {
+        let settings = LoopSettings {
+            loop_start,
+            loop_end,
+            use_custom_loop,
+            enable_loop,
+            gain_db,
+            ..LoopSettings::default()
+        };
+        Self::process_replacement_with_settings(audio_file_info, file_path, &settings)
+    }
+
+    /// Process the replacement with the loop and gain of `settings`, including the exact
+    /// loop frames set in the loop editor. The tool output and warnings of the conversion
+    /// become the track's conversion log.
+    pub fn process_replacement_with_settings(
+        audio_file_info: &AudioFileInfo,
+        file_path: Option<&Path>,
+        settings: &LoopSettings,
+    ) -> Result<AudioFileInfo, String> {
+        let (result, mut entries) =
+            tools::capture_log(|| Self::convert_replacement(audio_file_info, file_path, settings));
+        if result.is_ok() {
+            if let Some(source) = Self::get_replacement_path(audio_file_info) {
+                entries.insert(0, format!("Source: {}", source.display()));
+            }
+            conversion_log::set(audio_file_info.track_key(), entries);
+        }
+        result
+    }
+
+    fn convert_replacement(
+        audio_file_info: &AudioFileInfo,
+        file_path: Option<&Path>,
+        settings: &LoopSettings,
     ) -> Result<AudioFileInfo, String> {
         // 打印调试信息
         println!(
@@ -475,8 +585,7 @@ impl ReplaceUtils {
             audio_file_info.name, audio_file_info.id
         );
 
-        // Create key for hashmaps - Use the original audio name and ID
-        let key = format!("{}:{}", audio_file_info.name, audio_file_info.id);
+        let key = audio_file_info.track_key();
         println!("Using hashmap key: {}", key);
 
         // Get the file path from the provided path or from the stored paths
@@ -506,56 +615,63 @@ impl ReplaceUtils {
 
         println!("Using actual file path: {:?}", actual_file_path);
 
+        let data = Self::process_source(&actual_file_path, settings)?;
+        let result = Self::replace_in_memory_with_data(audio_file_info, data, &actual_file_path);
+        if result.is_ok() {
+            Self::stamp_source(&key, &actual_file_path);
+            Self::store_loop_settings(key, settings);
+            Self::record_replacement(audio_file_info, &actual_file_path, settings);
+        }
+        result
+    }
+
+    /// Gain and loop `file_path` as `settings` say, returning the WAV to queue. Common
+    /// formats are handled in memory; the rest go through vgmstream-cli, falling back to
+    /// less processed audio when a step fails.
+    pub fn process_source(file_path: &Path, settings: &LoopSettings) -> Result<Vec<u8>, String> {
+        let gain_db = settings.gain_db;
+        // Common formats are decoded, gained and looped without temp files or vgmstream-cli
+        match Self::process_in_memory(file_path, settings) {
+            Ok(Some(data)) => return Ok(data),
+            Ok(None) => {}
+            Err(e) => tools::log_warning(&format!("In-memory processing failed: {}", e)),
+        }
+
         // Apply gain first if requested
         let gain_processed_path = if gain_db.abs() > std::f32::EPSILON {
-            match Self::apply_wav_gain(&actual_file_path, gain_db) {
+            match Self::apply_wav_gain(file_path, gain_db) {
                 Ok(p) => {
                     println!("Successfully applied gain to file: {:?}", p);
                     p
                 },
                 Err(e) => {
-                    println!(
-                        "Warning: Failed to apply gain: {}. Using original file.",
-                        e
-                    );
-                    actual_file_path.clone()
+                    tools::log_warning(&format!("Failed to apply gain: {}. Using original file.", e));
+                    file_path.to_path_buf()
                 }
             }
         } else {
-            actual_file_path.clone()
+            file_path.to_path_buf()
         };
 
         // Then process the gain-adjusted file with vgmstream to add loop points
-        let final_path = match Self::process_with_vgmstream(
-            &gain_processed_path,
-            loop_start,
-            loop_end,
-            use_custom_loop,
-            enable_loop,
-        ) {
+        let final_path = match Self::process_with_vgmstream(&gain_processed_path, settings) {
             Ok(path) => path,
             Err(e) => {
-                println!("Warning: Failed to process file with vgmstream: {}", e);
+                tools::log_warning(&format!("Failed to process file with vgmstream: {}", e));
                 println!("Falling back to gain-processed file");
                 // Fall back to the gain-processed file if vgmstream processing fails
                 gain_processed_path.clone()
             }
         };
 
-        // Replace the audio file with the final processed file (gain-applied then vgmstream-processed) in memory only
-        let result = Self::replace_in_memory(audio_file_info, final_path.to_str().unwrap());
-
-        // Store loop settings
-        if let Ok(mut settings) = LOOP_SETTINGS.lock() {
-            settings.insert(key, (loop_start, loop_end, use_custom_loop));
-        }
+        let result = fs::read(&final_path).map_err(|e| format!("Failed to read replacement file: {}", e));
 
         // Clean up temporary files if they are different from the original
-        if gain_processed_path != actual_file_path && gain_processed_path.exists() {
+        if gain_processed_path != file_path && gain_processed_path.exists() {
             let _ = fs::remove_file(&gain_processed_path);
             println!("Cleaned up temporary gain file: {:?}", gain_processed_path);
         }
-        if final_path != gain_processed_path && final_path != actual_file_path && final_path.exists() {
+        if final_path != gain_processed_path && final_path != file_path && final_path.exists() {
             let _ = fs::remove_file(&final_path);
             println!("Cleaned up temporary vgmstream file: {:?}", final_path);
         }
@@ -563,11 +679,214 @@ impl ReplaceUtils {
         result
     }
 
+    /// Remember what the source of a replacement looks like now
+    fn stamp_source(key: &TrackKey, source: &Path) {
+        if let (Some(stamp), Ok(mut stamps)) = (SourceStamp::read(source), SOURCE_STAMPS.lock()) {
+            stamps.insert(key.clone(), stamp);
+        }
+    }
+
+    /// Pending replacements whose source file got new content since it was processed.
+    /// Sources saved again unchanged are quietly re-stamped.
+    pub fn changed_sources() -> Vec<(TrackKey, PathBuf)> {
+        let sources = Self::pending_replacement_sources();
+        let Ok(mut stamps) = SOURCE_STAMPS.lock() else {
+            return Vec::new();
+        };
+        let mut changed = Vec::new();
+        for (key, path, _) in sources {
+            let Some(stamp) = stamps.get(&key) else {
+                continue;
+            };
+            match stamp.check(&path) {
+                SourceChange::Changed => changed.push((key, path)),
+                SourceChange::Touched(now) => {
+                    stamps.insert(key, now);
+                }
+                SourceChange::Unchanged | SourceChange::Missing => {}
+            }
+        }
+        changed
+    }
+
+    /// Keep the queued audio of a track although its source changed
+    pub fn keep_queued_source(key: &TrackKey) {
+        let path = REPLACEMENT_FILE_PATHS.lock().ok().and_then(|m| m.get(key).cloned());
+        if let Some(path) = path {
+            Self::stamp_source(key, &path);
+        }
+    }
+
+    /// Process a pending replacement again from its source file with the loop and gain it
+    /// was queued with
+    pub fn refresh_from_source(audio_file_info: &AudioFileInfo) -> Result<AudioFileInfo, String> {
+        let key = audio_file_info.track_key();
+        let settings = LOOP_SETTINGS
+            .lock()
+            .ok()
+            .and_then(|m| m.get(&key).cloned())
+            .unwrap_or_default();
+        Self::process_replacement_with_settings(audio_file_info, None, &settings)
+    }
+
+    /// Loop settings that reproduce the `smpl` loop of a PCM16 WAV, or no loop without one
+    fn loop_settings_of_wav(data: &[u8]) -> LoopSettings {
+        let lp = smpl::read_loop(data);
+        let rate = smpl::pcm16_info(data).map_or(1, |info| info.sample_rate) as f32;
+        LoopSettings {
+            loop_start: lp.map(|lp| lp.start as f32 / rate),
+            loop_end: lp.map(|lp| lp.end as f32 / rate),
+            enable_loop: lp.is_some(),
+            use_custom_loop: lp.is_some(),
+            loop_start_sample: lp.map(|lp| lp.start),
+            loop_end_sample: lp.map(|lp| lp.end),
+            ..Default::default()
+        }
+    }
+
+    /// Queue a track of another bank, as it is on disk there, as the replacement of `target`
+    /// in the open bank. Its loop comes along; the decoded WAV is kept in the temp folder as
+    /// the replacement source.
+    pub fn port_track(
+        source: &AudioFileInfo,
+        source_bank: &str,
+        target: &AudioFileInfo,
+    ) -> Result<AudioFileInfo, String> {
+        let wav_path = ExportUtils::convert_to_wav_temp_path(source, source_bank)?;
+        let data = fs::read(&wav_path).map_err(|e| format!("Failed to read {}: {}", wav_path, e));
+        let _ = fs::remove_file(&wav_path);
+        let data = data?;
+
+        let dir = path_utils::safe_temp_dir().join("exvs2_ported");
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let bank = Path::new(source_bank).file_stem().unwrap_or_default().to_string_lossy();
+        let kept = dir.join(format!(
+            "{}_{}.wav",
+            path_utils::ascii_file_name(&bank),
+            path_utils::ascii_file_name(&source.name)
+        ));
+        fs::write(&kept, &data).map_err(|e| format!("Failed to write {}: {}", kept.display(), e))?;
+
+        Self::process_replacement_with_settings(target, Some(&kept), &Self::loop_settings_of_wav(&data))
+    }
+
+    /// Run the mixing panel on the audio a track plays now (its pending replacement, or the
+    /// original) and queue the result as its replacement. The mixed WAV is kept in the temp
+    /// folder as the replacement source so projects and refreshes find it.
+    pub fn mix_track(
+        audio_file_info: &AudioFileInfo,
+        original_file_path: &str,
+        settings: &MixSettings,
+    ) -> Result<AudioFileInfo, String> {
+        let current = match Self::get_replacement_data_unified(audio_file_info) {
+            Some(data) => data,
+            None => {
+                let wav_path = ExportUtils::convert_to_wav_temp_path(audio_file_info, original_file_path)?;
+                fs::read(&wav_path).map_err(|e| format!("Failed to read {}: {}", wav_path, e))?
+            }
+        };
+        let pcm = if smpl::pcm16_info(&current).is_some() {
+            current
+        } else {
+            decode::decode_to_pcm16_wav(&current, None)?
+        };
+        let mixed = mix::apply_pcm16(&pcm, settings)?;
+
+        let source = path_utils::safe_temp_dir()
+            .join(format!("mixed_{}.wav", path_utils::ascii_file_name(&audio_file_info.name)));
+        fs::write(&source, &mixed).map_err(|e| format!("Failed to write {}: {}", source.display(), e))?;
+
+        // Re-processing the source must keep the loop the mixed audio already carries
+        let loop_settings = Self::loop_settings_of_wav(&mixed);
+
+        let key = audio_file_info.track_key();
+        let result = Self::replace_in_memory_with_data(audio_file_info, mixed, &source)?;
+        Self::stamp_source(&key, &source);
+        Self::store_loop_settings(key, &loop_settings);
+        audit_log::record(format!("Mixed {}: {}", audit_log::describe(audio_file_info), settings.describe()));
+        Ok(result)
+    }
+
+    /// Add a replacement to the edit log
+    fn record_replacement(audio_file_info: &AudioFileInfo, source: &Path, settings: &LoopSettings) {
+        let mut action = format!(
+            "Replaced {} with {}",
+            audit_log::describe(audio_file_info),
+            source.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if settings.enable_loop {
+            match (settings.use_custom_loop, settings.loop_start, settings.loop_end) {
+                (true, Some(start), Some(end)) => action.push_str(&format!(", loop {:.3}s-{:.3}s", start, end)),
+                (true, Some(start), None) => action.push_str(&format!(", loop from {:.3}s", start)),
+                _ => action.push_str(", looping"),
+            }
+        }
+        if settings.gain_db.abs() > f32::EPSILON {
+            action.push_str(&format!(", gain {:+.1} dB", settings.gain_db));
+        }
+        audit_log::record(action);
+    }
+
+    /// Codec name of every stored replacement or addition payload, detected from its header
+    pub fn replacement_codecs() -> HashMap<TrackKey, String> {
+        REPLACED_AUDIO_DATA
+            .lock()
+            .map(|map| {
+                map.iter()
+                    .map(|(key, data)| (key.clone(), FileTypeRegistry::type_name_of(data, None)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Stored replacements and additions larger than `limit` bytes. Additions get no
+    /// downsample options: their data lives in the queued Add operation.
+    pub fn oversized_payloads(limit: usize) -> Vec<OversizedPayload> {
+        let Ok(map) = REPLACED_AUDIO_DATA.lock() else {
+            return Vec::new();
+        };
+        let mut oversized = payload_limit::check(map.iter().map(|(key, data)| (key, data.as_slice())), limit);
+        for payload in oversized.iter_mut().filter(|p| p.key.is_added()) {
+            payload.options.clear();
+        }
+        oversized
+    }
+
+    /// Convert a stored PCM16 replacement to a lower rate and/or mono in place
+    pub fn downsample_replacement(key: &TrackKey, option: DownsampleOption) -> Result<(), String> {
+        let mut map = REPLACED_AUDIO_DATA.lock().map_err(|_| "Replacement store is locked".to_string())?;
+        let data = map.get_mut(key).ok_or_else(|| format!("No replacement stored for {}", key))?;
+        let old_size = data.len();
+        *data = resample::downsample_pcm16(data, option.sample_rate, option.mono)?;
+        audit_log::record(format!(
+            "Downsampled the replacement of {} to {} Hz{} ({} -> {} bytes)",
+            key.name(),
+            option.sample_rate,
+            if option.mono { " mono" } else { "" },
+            old_size,
+            data.len()
+        ));
+        Ok(())
+    }
+
+    /// Remember the loop and gain settings a track was replaced with
+    fn store_loop_settings(key: TrackKey, settings: &LoopSettings) {
+        if let Ok(mut stored_settings) = LOOP_SETTINGS.lock() {
+            let stored = stored_settings.entry(key).or_default();
+            stored.loop_start = settings.loop_start;
+            stored.loop_end = settings.loop_end;
+            stored.use_custom_loop = settings.use_custom_loop;
+            stored.enable_loop = settings.enable_loop;
+            stored.gain_db = settings.gain_db;
+            stored.loop_start_sample = settings.loop_start_sample;
+            stored.loop_end_sample = settings.loop_end_sample;
+        }
+    }
+
     /// Get the replacement audio data for a specific audio file
     pub fn get_replacement_data(audio_name: &str, audio_id: &str) -> Option<Vec<u8>> {
-        let key = format!("{}:{}", audio_name, audio_id);
         if let Ok(map) = REPLACED_AUDIO_DATA.lock() {
-            map.get(&key).cloned()
+            map.get(&TrackKey::audio(audio_name, audio_id)).cloned()
         } else {
             None
         }
@@ -575,25 +894,13 @@ impl ReplaceUtils {
 
     /// Get the replacement audio data for a specific audio file (unified for both file types)
     pub fn get_replacement_data_unified(audio_file_info: &AudioFileInfo) -> Option<Vec<u8>> {
-        // Create the correct key based on file type
-        let key = if audio_file_info.is_nus3bank {
-            // For NUS3BANK, use hex_id:name format
-            format!("{}:{}", audio_file_info.hex_id.as_ref().unwrap_or(&audio_file_info.id), audio_file_info.name)
-        } else {
-            // For NUS3AUDIO, use original name:id format
-            format!("{}:{}", audio_file_info.name, audio_file_info.id)
-        };
-        
-        // Also try with ADD_ prefix for NUS3BANK files (for newly added audio)
-        let add_key = if audio_file_info.is_nus3bank {
-            format!("ADD_{}:{}", audio_file_info.hex_id.as_ref().unwrap_or(&audio_file_info.id), audio_file_info.name)
-        } else {
-            key.clone()
-        };
+        // Newly added NUS3BANK audio is stored under its own key
+        let key = audio_file_info.track_key();
+        let add_key = audio_file_info.added_track_key();
         
         println!("Looking for replacement data with key: {} or {}", key, add_key);
         if let Ok(map) = REPLACED_AUDIO_DATA.lock() {
-            // Try regular key first, then ADD_ prefixed key
+            // Try regular key first, then the added-track key
             let result = map.get(&key).cloned().or_else(|| map.get(&add_key).cloned());
             if result.is_some() {
                 println!("Found replacement data for audio: {}", audio_file_info.name);
@@ -611,8 +918,58 @@ impl ReplaceUtils {
         }
     }
 
+    /// Source file and loop settings of every pending replacement made from a file, sorted
+    /// by key. Tracks cleared with Replace with Empty afterwards are left out.
+    pub fn pending_replacement_sources() -> Vec<(TrackKey, PathBuf, LoopSettings)> {
+        let (Ok(data), Ok(paths), Ok(settings), Ok(empties)) = (
+            REPLACED_AUDIO_DATA.lock(),
+            REPLACEMENT_FILE_PATHS.lock(),
+            LOOP_SETTINGS.lock(),
+            EMPTY_REPLACEMENTS.lock(),
+        ) else {
+            return Vec::new();
+        };
+        let mut sources: Vec<(TrackKey, PathBuf, LoopSettings)> = paths
+            .iter()
+            .filter(|(key, _)| !key.is_added() && data.contains_key(*key))
+            .filter(|(key, _)| empties.get(*key).is_none_or(|e| data.get(*key) != Some(&e.data)))
+            .map(|(key, path)| (key.clone(), path.clone(), settings.get(key).cloned().unwrap_or_default()))
+            .collect();
+        sources.sort_by(|a, b| a.0.cmp(&b.0));
+        sources
+    }
+
+    /// Every pending Replace with Empty as (track before the replacement, minimum length in ms)
+    pub fn pending_empty_replacements() -> Vec<(AudioFileInfo, u32)> {
+        let (Ok(data), Ok(empties)) = (REPLACED_AUDIO_DATA.lock(), EMPTY_REPLACEMENTS.lock()) else {
+            return Vec::new();
+        };
+        let mut pending: Vec<(TrackKey, AudioFileInfo, u32)> = empties
+            .iter()
+            .filter(|(key, entry)| data.get(*key) == Some(&entry.data))
+            .map(|(key, entry)| (key.clone(), entry.original.clone(), entry.min_ms))
+            .collect();
+        pending.sort_by(|a, b| a.0.cmp(&b.0));
+        pending.into_iter().map(|(_, info, min_ms)| (info, min_ms)).collect()
+    }
+
+    /// Put back the pending replacement a track had before, or withdraw it with `None`
+    pub fn restore_replacement_data(audio_file_info: &AudioFileInfo, previous: Option<Vec<u8>>) {
+        if let Ok(mut map) = REPLACED_AUDIO_DATA.lock() {
+            let key = audio_file_info.track_key();
+            match previous {
+                Some(data) => {
+                    map.insert(key, data);
+                }
+                None => {
+                    map.remove(&key);
+                }
+            }
+        }
+    }
+
     /// Store audio data for playback (used by NUS3BANK add operations)
-    pub fn store_audio_data_for_playback(key: String, audio_data: Vec<u8>) -> Result<(), String> {
+    pub fn store_audio_data_for_playback(key: TrackKey, audio_data: Vec<u8>) -> Result<(), String> {
         if let Ok(mut map) = REPLACED_AUDIO_DATA.lock() {
             map.insert(key, audio_data);
             Ok(())
@@ -639,9 +996,18 @@ impl ReplaceUtils {
         }
     }
 
+    /// Get the key and byte size of every replacement stored in memory
+    pub fn get_replacement_sizes() -> Vec<(TrackKey, usize)> {
+        if let Ok(map) = REPLACED_AUDIO_DATA.lock() {
+            map.iter().map(|(key, data)| (key.clone(), data.len())).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
     /// Get a reference to the loop settings map
     pub fn get_loop_settings() -> Result<
-        std::sync::MutexGuard<'static, HashMap<String, (Option<f32>, Option<f32>, bool)>>,
+        std::sync::MutexGuard<'static, HashMap<TrackKey, LoopSettings>>,
         String,
     > {
         if let Ok(settings) = LOOP_SETTINGS.lock() {
@@ -651,6 +1017,47 @@ impl ReplaceUtils {
         }
     }
 
+    /// Loop settings of a pending replacement, if the track has one
+    pub fn get_pending_loop_settings(audio_file_info: &AudioFileInfo) -> Option<LoopSettings> {
+        let key = audio_file_info.track_key();
+        if Self::get_replacement_path(audio_file_info).is_none()
+            || Self::get_replacement_data_unified(audio_file_info).is_none()
+        {
+            return None;
+        }
+        LOOP_SETTINGS.lock().ok()?.get(&key).cloned()
+    }
+
+    /// Re-run a pending replacement from its source file with different loop settings
+    pub fn reapply_loop_settings(
+        audio_file_info: &AudioFileInfo,
+        settings: &LoopSettings,
+    ) -> Result<AudioFileInfo, String> {
+        if Self::get_replacement_path(audio_file_info).is_none() {
+            return Err(format!(
+                "'{}' has no pending replacement from a file",
+                audio_file_info.name
+            ));
+        }
+
+        let use_custom_loop = settings.enable_loop && settings.use_custom_loop;
+        let (loop_start, loop_end) = if use_custom_loop {
+            (settings.loop_start, settings.loop_end)
+        } else {
+            (None, None)
+        };
+
+        Self::process_replacement_with_loop_settings(
+            audio_file_info,
+            None,
+            loop_start,
+            loop_end,
+            use_custom_loop,
+            settings.enable_loop,
+            settings.gain_db,
+        )
+    }
+
     /// Clear all replacement data from memory (unified for both file types)
     pub fn clear_replacements() {
         // Clear NUS3AUDIO replacements
@@ -668,16 +1075,73 @@ impl ReplaceUtils {
             paths.clear();
             println!("Cleared all replacement file paths from memory");
         }
+
+        if let Ok(mut empties) = EMPTY_REPLACEMENTS.lock() {
+            empties.clear();
+        }
+
+        if let Ok(mut stamps) = SOURCE_STAMPS.lock() {
+            stamps.clear();
+        }
         
+        conversion_log::clear();
+
         // Clear NUS3BANK replacements
         Nus3bankReplacer::clear_replacements();
     }
 
+    /// Copy all pending replacements of both file types
+    pub fn capture_state() -> ReplacementState {
+        ReplacementState {
+            data: REPLACED_AUDIO_DATA.lock().map(|m| m.clone()).unwrap_or_default(),
+            loop_settings: LOOP_SETTINGS.lock().map(|m| m.clone()).unwrap_or_default(),
+            paths: REPLACEMENT_FILE_PATHS.lock().map(|m| m.clone()).unwrap_or_default(),
+            empties: EMPTY_REPLACEMENTS.lock().map(|m| m.clone()).unwrap_or_default(),
+            stamps: SOURCE_STAMPS.lock().map(|m| m.clone()).unwrap_or_default(),
+            logs: conversion_log::capture(),
+            bank_operations: Nus3bankReplacer::capture_operations(),
+            tone_orders: Nus3bankReplacer::capture_tone_orders(),
+        }
+    }
+
+    /// Replace all pending replacements with a copy taken by `capture_state`
+    pub fn restore_state(state: ReplacementState) {
+        if let Ok(mut map) = REPLACED_AUDIO_DATA.lock() {
+            *map = state.data;
+        }
+        if let Ok(mut map) = LOOP_SETTINGS.lock() {
+            *map = state.loop_settings;
+        }
+        if let Ok(mut map) = REPLACEMENT_FILE_PATHS.lock() {
+            *map = state.paths;
+        }
+        if let Ok(mut map) = EMPTY_REPLACEMENTS.lock() {
+            *map = state.empties;
+        }
+        if let Ok(mut map) = SOURCE_STAMPS.lock() {
+            *map = state.stamps;
+        }
+        conversion_log::restore(state.logs);
+        Nus3bankReplacer::restore_operations(state.bank_operations);
+        Nus3bankReplacer::restore_tone_orders(state.tone_orders);
+    }
+
     /// Apply all in-memory replacements to a NUS3AUDIO file and save it
     pub fn apply_replacements_and_save(
         original_file_path: &str,
         save_path: &str,
     ) -> Result<(), String> {
+        let output_buffer = Self::build_nus3audio_bytes(original_file_path)?;
+
+        // Write the buffer to the save file
+        match fs::write(save_path, output_buffer) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Failed to write updated file: {}", e)),
+        }
+    }
+
+    /// Apply all in-memory replacements to a NUS3AUDIO file and return the serialized bytes
+    fn build_nus3audio_bytes(original_file_path: &str) -> Result<Vec<u8>, String> {
         // Load the original NUS3AUDIO file
         let mut nus3_file = match Nus3audioFile::open(original_file_path) {
             Ok(file) => file,
@@ -687,16 +1151,12 @@ impl ReplaceUtils {
         // Apply all replacements from our static HashMap
         if let Ok(map) = REPLACED_AUDIO_DATA.lock() {
             for (key, replacement_data) in map.iter() {
-                // Parse the key to get audio name and id
-                let parts: Vec<&str> = key.split(':').collect();
-                if parts.len() != 2 {
+                let TrackKey::Audio { name: audio_name, .. } = key else {
                     continue;
-                }
-
-                let audio_name = parts[0];
+                };
 
                 // Find the target audio file by name
-                let target_index = match nus3_file.files.iter().position(|f| f.name == audio_name) {
+                let target_index = match nus3_file.files.iter().position(|f| &f.name == audio_name) {
                     Some(index) => index,
                     None => continue, // Skip if not found
                 };
@@ -736,54 +1196,52 @@ impl ReplaceUtils {
             println!("Added audio file: {} (ID: {})", name, id);
         }
 
+        // Reorder the entries; the writer rebuilds the index tables from this order
+        if let Some(order) = Nus3audioFileUtils::pending_order() {
+            let rank = |file: &AudioFile| {
+                order.iter().position(|(id, name)| *id == file.id && *name == file.name).unwrap_or(order.len())
+            };
+            nus3_file.files.sort_by_key(rank);
+            println!("Reordered {} entries", order.len());
+        }
+
         // Create memory buffer for writing the updated NUS3AUDIO file
         let mut output_buffer = Vec::new();
 
         // Write the modified NUS3AUDIO data to memory buffer
         nus3_file.write(&mut output_buffer);
 
-        // Write the buffer to the save file
-        match fs::write(save_path, output_buffer) {
-            Ok(_) => Ok(()),
-            Err(e) => Err(format!("Failed to write updated file: {}", e)),
-        }
+        Ok(output_buffer)
     }
 
-    /// Replace target audio in memory with an empty WAV buffer, preserving name and id
+    /// Replace target audio in memory with silence lasting at least `min_ms`, preserving name
+    /// and id. The buffer stored now uses a generic format; `prepare_empty_replacements`
+    /// rebuilds it in the target's original format on save.
     pub fn replace_with_empty_wav_in_memory(
         audio_file_info: &AudioFileInfo,
         _nus3_path: &str,
+        min_ms: u32,
     ) -> Result<AudioFileInfo, String> {
-        // A minimal valid 44-byte WAV header with 0 data bytes (PCM mono 8kHz 16-bit)
-        // This is sufficient for representing an empty/near-empty WAV for our in-memory replacement use-case
-        const EMPTY_WAV_HEADER: [u8; 44] = [
-            0x52, 0x49, 0x46, 0x46, // 'RIFF'
-            0x24, 0x00, 0x00, 0x00, // Chunk size = 36 + data_size (0)
-            0x57, 0x41, 0x56, 0x45, // 'WAVE'
-            0x66, 0x6d, 0x74, 0x20, // 'fmt '
-            0x10, 0x00, 0x00, 0x00, // Subchunk1Size = 16
-            0x01, 0x00, // AudioFormat = PCM
-            0x01, 0x00, // NumChannels = 1
-            0x40, 0x1f, 0x00, 0x00, // SampleRate = 8000
-            0x80, 0x3e, 0x00, 0x00, // ByteRate = SampleRate * NumChannels * BitsPerSample/8
-            0x02, 0x00, // BlockAlign = NumChannels * BitsPerSample/8
-            0x10, 0x00, // BitsPerSample = 16
-            0x64, 0x61, 0x74, 0x61, // 'data'
-            0x00, 0x00, 0x00, 0x00, // Subchunk2Size = 0
-        ];
-
-        let replacement_data = EMPTY_WAV_HEADER.to_vec();
-
-        // Store into in-memory replacement map using consistent key format
-        let key = if audio_file_info.is_nus3bank {
-            // For NUS3BANK, use hex_id:name format (consistent with replace_in_memory)
-            format!("{}:{}", audio_file_info.hex_id.as_ref().unwrap_or(&audio_file_info.id), audio_file_info.name)
-        } else {
-            // For NUS3AUDIO, use original name:id format
-            format!("{}:{}", audio_file_info.name, audio_file_info.id)
-        };
+        let replacement_data = empty_wav::build_silent_wav(empty_wav::FALLBACK_FORMAT, min_ms);
+
+        let key = audio_file_info.track_key();
         if let Ok(mut map) = REPLACED_AUDIO_DATA.lock() {
-            map.insert(key, replacement_data.clone());
+            map.insert(key.clone(), replacement_data.clone());
+        }
+        if let Ok(mut empties) = EMPTY_REPLACEMENTS.lock() {
+            // Keep the pre-replacement track when a track is cleared twice
+            let original = empties
+                .get(&key)
+                .map(|e| e.original.clone())
+                .unwrap_or_else(|| audio_file_info.clone());
+            empties.insert(
+                key,
+                EmptyReplacement {
+                    original,
+                    min_ms,
+                    data: replacement_data.clone(),
+                },
+            );
         }
 
         // Create a new AudioFileInfo reflecting the empty wav size and filename
@@ -796,149 +1254,129 @@ impl ReplaceUtils {
             hex_id: audio_file_info.hex_id.clone(),
             is_nus3bank: audio_file_info.is_nus3bank,
         };
+        audit_log::record(format!("Replaced {} with silence ({} ms)", audit_log::describe(audio_file_info), min_ms));
 
         Ok(new_audio_info)
     }
 
-    /// Get the sample rate from a WAV file
-    fn get_wav_sample_rate(wav_path: &Path) -> Result<u32, String> {
-        let data = std::fs::read(wav_path)
-            .map_err(|e| format!("Failed to read WAV file: {}", e))?;
-        
-        // Check for RIFF header (52 49 46 46)
-        if data.len() < 44 || &data[0..4] != b"RIFF" {
-            return Err("Invalid WAV file: missing RIFF header".to_string());
-        }
-        
-        // Check for WAVE format
-        if &data[8..12] != b"WAVE" {
-            return Err("Invalid WAV file: not WAVE format".to_string());
+    /// Rebuild every registered empty replacement in its target's original sample rate and
+    /// channel count, then check each one will be accepted (PCM16 with a non-empty,
+    /// frame-aligned data chunk). Returns the number of empty replacements prepared.
+    pub fn prepare_empty_replacements(original_file_path: &str) -> Result<usize, String> {
+        let pending: Vec<(TrackKey, EmptyReplacement)> = {
+            let map = REPLACED_AUDIO_DATA.lock().map_err(|_| "Replacement store is locked".to_string())?;
+            let mut empties = EMPTY_REPLACEMENTS.lock().map_err(|_| "Replacement store is locked".to_string())?;
+            // Drop tracks that were replaced with real audio (or restored) after being cleared
+            empties.retain(|key, entry| map.get(key) == Some(&entry.data));
+            empties.iter().map(|(k, v)| (k.clone(), v.clone())).collect()
+        };
+        if pending.is_empty() {
+            return Ok(0);
         }
-        
-        // Find fmt chunk and extract sample rate
-        let mut offset = 12;
-        while offset + 8 <= data.len() {
-            let chunk_id = &data[offset..offset + 4];
-            let chunk_size = u32::from_le_bytes([
-                data[offset + 4], data[offset + 5], 
-                data[offset + 6], data[offset + 7]
-            ]);
-            
-            if chunk_id == b"fmt " {
-                if offset + 8 + 24 <= data.len() {
-                    // Sample rate is at offset 24 in fmt chunk (offset + 8 + 24 - 8 = offset + 24)
-                    let sample_rate = u32::from_le_bytes([
-                        data[offset + 8 + 4], data[offset + 8 + 5],
-                        data[offset + 8 + 6], data[offset + 8 + 7]
-                    ]);
-                    return Ok(sample_rate);
+
+        let bank = if original_file_path.to_lowercase().ends_with(".nus3bank") {
+            Some(
+                crate::nus3bank::structures::Nus3bankFile::open(original_file_path)
+                    .map_err(|e| format!("Failed to read NUS3BANK for empty replacements: {}", e))?,
+            )
+        } else {
+            None
+        };
+
+        let mut problems = Vec::new();
+        for (key, entry) in pending {
+            let original = &entry.original;
+            let format = Self::original_silent_format(original, original_file_path, bank.as_ref())
+                .unwrap_or_else(|e| {
+                    println!(
+                        "Format of {} unknown ({}), using {} Hz {} ch for its empty replacement",
+                        original.name, e, empty_wav::FALLBACK_FORMAT.sample_rate, empty_wav::FALLBACK_FORMAT.channels
+                    );
+                    empty_wav::FALLBACK_FORMAT
+                });
+            let data = empty_wav::build_silent_wav(format, entry.min_ms);
+            if let Err(e) = empty_wav::validate(&data) {
+                problems.push(format!("{}: {}", original.name, e));
+                continue;
+            }
+            println!(
+                "Empty replacement for {}: {} Hz, {} ch, {} ms ({} bytes)",
+                original.name, format.sample_rate, format.channels, entry.min_ms, data.len()
+            );
+
+            if let Ok(mut map) = REPLACED_AUDIO_DATA.lock() {
+                map.insert(key.clone(), data.clone());
+            }
+            if let Ok(mut empties) = EMPTY_REPLACEMENTS.lock() {
+                if let Some(e) = empties.get_mut(&key) {
+                    e.data = data;
                 }
-                break;
             }
-            
-            offset += 8 + chunk_size as usize;
-            // Ensure 16-bit alignment
-            if chunk_size % 2 != 0 {
-                offset += 1;
+        }
+
+        if problems.is_empty() {
+            Ok(EMPTY_REPLACEMENTS.lock().map(|e| e.len()).unwrap_or(0))
+        } else {
+            Err(format!("Empty replacements would be rejected: {}", problems.join("; ")))
+        }
+    }
+
+    /// Sample rate and channel count of a track before it was replaced
+    fn original_silent_format(
+        original: &AudioFileInfo,
+        original_file_path: &str,
+        bank: Option<&crate::nus3bank::structures::Nus3bankFile>,
+    ) -> Result<SilentFormat, String> {
+        if let (Some(bank), Some(hex_id)) = (bank, original.hex_id.as_ref()) {
+            let payload = bank
+                .get_track_by_hex_id(hex_id)
+                .and_then(|t| bank.read_track_payload(t.index).ok());
+            if let Some(format) = payload.and_then(|p| empty_wav::format_from_wav(&p)) {
+                return Ok(format);
             }
         }
-        
-        Err("Could not find fmt chunk in WAV file".to_string())
+        let metadata = ExportUtils::probe_metadata(Some(original), original_file_path)?;
+        let sample_rate = sample_rate_check::parse_vgmstream_sample_rate(&metadata)
+            .ok_or("vgmstream-cli did not report a sample rate")?;
+        let channels = sample_rate_check::parse_vgmstream_channels(&metadata)
+            .filter(|c| *c > 0)
+            .ok_or("vgmstream-cli did not report a channel count")?;
+        Ok(SilentFormat { sample_rate, channels })
     }
 
-    /// Get the total samples from a WAV file
-    fn get_wav_total_samples(wav_path: &Path) -> Result<u32, String> {
+    /// Read a WAV file into its chunks
+    fn read_wav_chunks(wav_path: &Path) -> Result<Riff<'static>, String> {
         let data = std::fs::read(wav_path)
             .map_err(|e| format!("Failed to read WAV file: {}", e))?;
-        
-        // Check for RIFF header
-        if data.len() < 44 || &data[0..4] != b"RIFF" {
-            return Err("Invalid WAV file: missing RIFF header".to_string());
-        }
-        
-        let mut fmt_chunk_info: Option<(u16, u32, u16)> = None; // (channels, sample_rate, bits_per_sample)
-        let mut data_chunk_size: Option<u32> = None;
-        
-        // Parse chunks to find fmt and data
-        let mut offset = 12; // Skip RIFF header
-        while offset + 8 <= data.len() {
-            let chunk_id = &data[offset..offset + 4];
-            let chunk_size = u32::from_le_bytes([
-                data[offset + 4], data[offset + 5], 
-                data[offset + 6], data[offset + 7]
-            ]);
-            
-            if chunk_id == b"fmt " && offset + 8 + 16 <= data.len() {
-                let channels = u16::from_le_bytes([data[offset + 8 + 2], data[offset + 8 + 3]]);
-                let sample_rate = u32::from_le_bytes([
-                    data[offset + 8 + 4], data[offset + 8 + 5],
-                    data[offset + 8 + 6], data[offset + 8 + 7]
-                ]);
-                let bits_per_sample = u16::from_le_bytes([data[offset + 8 + 14], data[offset + 8 + 15]]);
-                fmt_chunk_info = Some((channels, sample_rate, bits_per_sample));
-            } else if chunk_id == b"data" {
-                data_chunk_size = Some(chunk_size);
-            }
-            
-            offset += 8 + chunk_size as usize;
-            if chunk_size % 2 != 0 {
-                offset += 1;
-            }
-        }
-        
-        match (fmt_chunk_info, data_chunk_size) {
-            (Some((channels, _sample_rate, bits_per_sample)), Some(data_size)) => {
-                let bytes_per_sample = (bits_per_sample / 8) as u32;
-                let total_samples = data_size / (channels as u32 * bytes_per_sample);
-                Ok(total_samples)
-            }
-            _ => Err("Could not find required chunks to calculate total samples".to_string())
-        }
+        Riff::parse_wav(&data)
+            .map(Riff::into_owned)
+            .map_err(|e| format!("Invalid WAV file: {}", e))
     }
 
-    /// Modify the smpl chunk in a WAV file to set custom loop points
+    /// Get the sample rate from a WAV file
+    fn get_wav_sample_rate(wav_path: &Path) -> Result<u32, String> {
+        Self::read_wav_chunks(wav_path)?
+            .sample_rate()
+            .ok_or_else(|| "Could not find fmt chunk in WAV file".to_string())
+    }
+
+    /// Get the total samples from a WAV file
+    fn get_wav_total_samples(wav_path: &Path) -> Result<u32, String> {
+        Self::read_wav_chunks(wav_path)?
+            .frames()
+            .ok_or_else(|| "Could not find required chunks to calculate total samples".to_string())
+    }
+
+    /// Set the loop points of the smpl chunk in a WAV file, wherever the chunk is; one is
+    /// added when the file has none
     fn modify_wav_smpl_chunk(wav_path: &Path, start_sample: u32, end_sample: u32) -> Result<(), String> {
-        let mut data = std::fs::read(wav_path)
-            .map_err(|e| format!("Failed to read WAV file: {}", e))?;
-        
-        // Check for RIFF header
-        if data.len() < 12 || &data[0..4] != b"RIFF" {
-            return Err("Invalid WAV file: missing RIFF header".to_string());
-        }
-        
-        // Find smpl chunk at 0x24 offset
-        if data.len() < 0x24 + 4 {
-            return Err("WAV file too small to contain smpl chunk".to_string());
-        }
-        
-        // Check if smpl chunk exists at expected position (0x24)
-        if &data[0x24..0x24 + 4] != b"smpl" {
-            return Err("smpl chunk not found at expected position 0x24".to_string());
-        }
-        
-        // Verify we have enough space for the loop points
-        if data.len() < 0x58 + 8 {
-            return Err("WAV file too small to contain loop point data".to_string());
-        }
-        
-        // Write start_sample at 0x58
-        let start_bytes = start_sample.to_le_bytes();
-        data[0x58] = start_bytes[0];
-        data[0x59] = start_bytes[1];
-        data[0x5A] = start_bytes[2];
-        data[0x5B] = start_bytes[3];
-        
-        // Write end_sample at 0x5C
-        let end_bytes = end_sample.to_le_bytes();
-        data[0x5C] = end_bytes[0];
-        data[0x5D] = end_bytes[1];
-        data[0x5E] = end_bytes[2];
-        data[0x5F] = end_bytes[3];
-        
+        let mut riff = Self::read_wav_chunks(wav_path)?;
+        riff.set_smpl_loop(SmplLoop { start: start_sample, end: end_sample })?;
+
         // Save the modified WAV file
-        std::fs::write(wav_path, &data)
+        std::fs::write(wav_path, riff.to_bytes())
             .map_err(|e| format!("Failed to write modified WAV file: {}", e))?;
-        
+
         println!("Successfully modified smpl chunk: loop start={}, end={}", start_sample, end_sample);
         Ok(())
     }
@@ -948,64 +1386,28 @@ impl ReplaceUtils {
         original_file_path: &str,
         save_path: &str,
     ) -> Result<(), String> {
+        Self::prepare_empty_replacements(original_file_path)?;
         if original_file_path.to_lowercase().ends_with(".nus3bank") {
-            // Handle NUS3BANK files
-            // Bridge UI in-memory replacements into Nus3bankReplacer cache
-            // Handle both "hex_id:name" and "name:hex_id" key formats
-            // Skip ADD_ prefixed keys (handled by Add operations)
-            if let Ok(map) = REPLACED_AUDIO_DATA.lock() {
-                for (key, replacement_data) in map.iter() {
-                    // Skip ADD_ prefixed keys (these are handled by Add operations)
-                    if key.starts_with("ADD_") {
-                        println!("Skipping ADD_ prefixed key: {}", key);
-                        continue;
-                    }
-                    
-                    let parts: Vec<&str> = key.split(':').collect();
-                    if parts.len() != 2 { continue; }
-                    let left = parts[0];
-                    let right = parts[1];
-                    
-                    // Check both possible hex_id positions
-                    let hex_id = if left.starts_with("0x") {
-                        left
-                    } else if right.starts_with("0x") {
-                        right
-                    } else {
-                        continue; // Skip if no hex_id found
-                    };
-                    
-                    // Feed into Nus3bankReplacer using the current file path scope
-                    let _ = Nus3bankReplacer::replace_track_in_memory(
-                        original_file_path,
-                        hex_id,
-                        replacement_data.clone(),
-                    );
-                }
-            }
-
-            let mut nus3bank_file = crate::nus3bank::structures::Nus3bankFile::open(original_file_path)
-                .map_err(|e| format!("Failed to open NUS3BANK file: {}", e))?;
-
-            crate::nus3bank::replace::Nus3bankReplacer::apply_to_file(original_file_path, &mut nus3bank_file)
-                .map_err(|e| format!("Failed to apply NUS3BANK operations: {}", e))?;
-
-            if let Some(names) = grp_pending::get(original_file_path) {
-                apply_grp_names_to_file(&mut nus3bank_file, names);
-            }
-
-            if let Some(tones) = dton_pending::get(original_file_path) {
-                apply_dton_tones_to_file(&mut nus3bank_file, tones);
-            }
-
-            if let Some(prop) = prop_pending::get(original_file_path) {
-                apply_prop_to_file(&mut nus3bank_file, Some(prop));
-            }
+            let nus3bank_file = Self::build_nus3bank_file(original_file_path)?;
 
             nus3bank_file
                 .save(save_path)
                 .map_err(|e| format!("Failed to save NUS3BANK file: {}", e))?;
 
+            // Shifted cue IDs break every reference into the bank without any other sign,
+            // so read the written file back before the pending edits are dropped
+            let saved = crate::nus3bank::structures::Nus3bankFile::open(save_path)
+                .map_err(|e| format!("Saved NUS3BANK file does not read back: {}", e))?;
+            let problems = nus3bank_file.check_saved_order(&saved);
+            if !problems.is_empty() {
+                audit_log::record_for(original_file_path, format!("Track order check failed after saving to {}", save_path));
+                return Err(format!(
+                    "The file was written to {} but its track order does not match the edited bank, so cue IDs moved. Restore a backup before using it.\n{}",
+                    save_path,
+                    problems.join("\n")
+                ));
+            }
+
             crate::nus3bank::replace::Nus3bankReplacer::clear_for_file(original_file_path);
 
             if grp_pending::has(original_file_path) {
@@ -1015,12 +1417,87 @@ impl ReplaceUtils {
             if dton_pending::has(original_file_path) {
                 let _ = dton_pending::clear(original_file_path);
             }
-
-            Ok(())
         } else {
             // Handle NUS3AUDIO files (original implementation)
-            Self::apply_replacements_and_save(original_file_path, save_path)
+            Self::apply_replacements_and_save(original_file_path, save_path)?;
         }
+        audit_log::record_for(original_file_path, format!("Saved to {}", save_path));
+        Ok(())
+    }
+
+    /// Run the whole save pipeline in memory and validate the result without writing `save_path`
+    pub fn dry_run_save_unified(
+        original_file_path: &str,
+        save_path: &str,
+    ) -> Result<DryRunReport, String> {
+        Self::prepare_empty_replacements(original_file_path)?;
+        if original_file_path.to_lowercase().ends_with(".nus3bank") {
+            let nus3bank_file = Self::build_nus3bank_file(original_file_path)?;
+            nus3bank_file
+                .dry_run(save_path)
+                .map_err(|e| format!("Failed to serialize NUS3BANK file: {}", e))
+        } else {
+            let bytes = Self::build_nus3audio_bytes(original_file_path)?;
+            let mut problems = Vec::new();
+            let track_count = match Nus3audioFile::try_from_bytes(&bytes) {
+                Some(reparsed) => reparsed.files.len(),
+                None => {
+                    problems.push("Output does not re-parse as NUS3AUDIO".to_string());
+                    0
+                }
+            };
+            Ok(DryRunReport::new(save_path, bytes.len(), track_count, problems))
+        }
+    }
+
+    /// Save a NUS3BANK file with every pending operation into memory and compare the
+    /// re-parsed result against the edited bank
+    pub fn verify_unified(original_file_path: &str) -> Result<VerifyReport, String> {
+        if !original_file_path.to_lowercase().ends_with(".nus3bank") {
+            return Err("Verify is only available for NUS3BANK files".to_string());
+        }
+        Self::prepare_empty_replacements(original_file_path)?;
+        Self::build_nus3bank_file(original_file_path)?
+            .verify()
+            .map_err(|e| format!("Failed to serialize NUS3BANK file: {}", e))
+    }
+
+    /// Load a NUS3BANK file and apply every pending operation to it in memory
+    fn build_nus3bank_file(original_file_path: &str) -> Result<crate::nus3bank::structures::Nus3bankFile, String> {
+        // UI in-memory replacements are layered over the pending operations without being
+        // stored, so a dry run leaves no trace. Added tracks are skipped here: Add operations
+        // already carry their data.
+        let replacements: HashMap<String, Vec<u8>> = REPLACED_AUDIO_DATA
+            .lock()
+            .map(|map| {
+                map.iter()
+                    .filter_map(|(key, data)| match key {
+                        TrackKey::Bank { hex_id, .. } => Some((hex_id.clone(), data.clone())),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut nus3bank_file = crate::nus3bank::structures::Nus3bankFile::open(original_file_path)
+            .map_err(|e| format!("Failed to open NUS3BANK file: {}", e))?;
+
+        Nus3bankReplacer::apply_to_file_with(original_file_path, replacements, &mut nus3bank_file)
+            .map_err(|e| format!("Failed to apply NUS3BANK operations: {}", e))?;
+
+        if let Some(names) = grp_pending::get(original_file_path) {
+            apply_grp_names_to_file(&mut nus3bank_file, names);
+        }
+
+        if let Some(tones) = dton_pending::get(original_file_path) {
+            apply_dton_tones_to_file(&mut nus3bank_file, tones);
+        }
+
+        if let Some(prop) = prop_pending::get(original_file_path) {
+            apply_prop_to_file(&mut nus3bank_file, Some(prop));
+        }
+
+        Ok(nus3bank_file)
     }
     
     /// Replace audio data in memory for NUS3BANK files
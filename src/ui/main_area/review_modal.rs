@@ -0,0 +1,186 @@
+use egui::{Color32, Context, Key, RichText, Window};
+use egui_phosphor::regular;
+
+use super::audio_file_info::AudioFileInfo;
+use super::review_queue::{Grade, ReviewQueue};
+
+/// What to do with the grades once the review is done
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewOutcome {
+    /// Color the graded rows by grade
+    Label,
+    /// Select the rows given one grade, replacing the selection
+    Select(Grade),
+}
+
+/// Plays the queued tracks one by one and grades each with a single key
+pub struct ReviewModal {
+    pub open: bool,
+    queue: ReviewQueue,
+    play_request: Option<AudioFileInfo>,
+    outcome: Option<ReviewOutcome>,
+}
+
+impl Default for ReviewModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReviewModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            queue: ReviewQueue::default(),
+            play_request: None,
+            outcome: None,
+        }
+    }
+
+    /// Start reviewing `tracks` from the first one
+    pub fn open_for(&mut self, tracks: Vec<AudioFileInfo>) {
+        self.queue = ReviewQueue::new(tracks);
+        self.play_request = self.queue.current().cloned();
+        self.outcome = None;
+        self.open = true;
+    }
+
+    pub fn queue(&self) -> &ReviewQueue {
+        &self.queue
+    }
+
+    /// Track to play, asked for whenever the review moves or Space is pressed
+    pub fn take_play_request(&mut self) -> Option<AudioFileInfo> {
+        self.play_request.take()
+    }
+
+    pub fn take_outcome(&mut self) -> Option<ReviewOutcome> {
+        self.outcome.take()
+    }
+
+    fn moved(&mut self) {
+        self.play_request = self.queue.current().cloned();
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        // Single keys must still reach text fields
+        if !ctx.wants_keyboard_input() {
+            let (grade, replay, skip, back) = ctx.input(|i| {
+                (
+                    Grade::ALL.into_iter().find(|g| i.key_pressed(g.key())),
+                    i.key_pressed(Key::Space),
+                    i.key_pressed(Key::S),
+                    i.key_pressed(Key::Backspace),
+                )
+            });
+            if let Some(grade) = grade {
+                self.queue.grade(grade);
+                self.moved();
+            } else if skip {
+                self.queue.skip();
+                self.moved();
+            } else if back {
+                self.queue.back();
+                self.moved();
+            } else if replay {
+                self.moved();
+            }
+        }
+
+        let mut open = self.open;
+        Window::new("Review")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(true)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new("K keep, R replace, F flag, S skip, Backspace back, Space replay")
+                        .color(Color32::GRAY),
+                );
+                ui.add_space(6.0);
+
+                let total = self.queue.len();
+                match self.queue.current().cloned() {
+                    Some(info) => {
+                        ui.label(format!("Track {} of {}", self.queue.position() + 1, total));
+                        ui.heading(&info.name);
+                        if let Some(grade) = self.queue.grade_of(&info) {
+                            ui.label(RichText::new(format!("Graded {} before", grade.name())).weak());
+                        }
+                        ui.add_space(6.0);
+                        ui.horizontal(|ui| {
+                            for grade in Grade::ALL {
+                                let text = RichText::new(format!("{} ({:?})", grade.name(), grade.key()))
+                                    .color(grade.label().color32());
+                                if ui.button(text).clicked() {
+                                    self.queue.grade(grade);
+                                    self.moved();
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(self.queue.position() > 0, egui::Button::new(regular::SKIP_BACK.to_string()))
+                                .on_hover_text("Back")
+                                .clicked()
+                            {
+                                self.queue.back();
+                                self.moved();
+                            }
+                            if ui.button(format!("{} Replay", regular::PLAY)).clicked() {
+                                self.moved();
+                            }
+                            if ui.button("Skip").clicked() {
+                                self.queue.skip();
+                                self.moved();
+                            }
+                        });
+                    }
+                    None => {
+                        ui.colored_label(
+                            Color32::from_rgb(90, 170, 90),
+                            format!("{} All {} tracks reviewed", regular::CHECK_CIRCLE, total),
+                        );
+                        if total > 0 && ui.button("Review the last track again").clicked() {
+                            self.queue.back();
+                            self.moved();
+                        }
+                    }
+                }
+
+                ui.separator();
+                ui.label(RichText::new("Worklist").strong());
+                egui::Grid::new("review_worklist").num_columns(2).spacing([12.0, 4.0]).show(ui, |ui| {
+                    for grade in Grade::ALL {
+                        let count = self.queue.count(grade);
+                        ui.label(RichText::new(format!("{}: {}", grade.name(), count)).color(grade.label().color32()));
+                        if ui
+                            .add_enabled(count > 0, egui::Button::new("Select"))
+                            .on_hover_text(format!("Select the tracks graded {} in the table", grade.name()))
+                            .clicked()
+                        {
+                            self.outcome = Some(ReviewOutcome::Select(grade));
+                        }
+                        ui.end_row();
+                    }
+                });
+                let graded = Grade::ALL.iter().any(|g| self.queue.count(*g) > 0);
+                if ui
+                    .add_enabled(graded, egui::Button::new("Color-label graded tracks"))
+                    .on_hover_text("Keep green, Replace orange, Flag red; filter the table by label to work through them")
+                    .clicked()
+                {
+                    self.outcome = Some(ReviewOutcome::Label);
+                }
+            });
+
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use super::audio_file_info::AudioFileInfo;
+use super::color_labels::ColorLabel;
+use crate::track_key::TrackKey;
+
+/// Verdict given to a track in review mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Grade {
+    Keep,
+    Replace,
+    Flag,
+}
+
+impl Grade {
+    pub const ALL: [Grade; 3] = [Grade::Keep, Grade::Replace, Grade::Flag];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Grade::Keep => "Keep",
+            Grade::Replace => "Replace",
+            Grade::Flag => "Flag",
+        }
+    }
+
+    /// Key that gives this grade
+    pub fn key(&self) -> egui::Key {
+        match self {
+            Grade::Keep => egui::Key::K,
+            Grade::Replace => egui::Key::R,
+            Grade::Flag => egui::Key::F,
+        }
+    }
+
+    /// Color label a grade turns into in the table
+    pub fn label(&self) -> ColorLabel {
+        match self {
+            Grade::Keep => ColorLabel::Green,
+            Grade::Replace => ColorLabel::Orange,
+            Grade::Flag => ColorLabel::Red,
+        }
+    }
+}
+
+/// Tracks to listen to one after another, with the grade given to each
+#[derive(Debug, Clone, Default)]
+pub struct ReviewQueue {
+    tracks: Vec<AudioFileInfo>,
+    position: usize,
+    grades: HashMap<TrackKey, Grade>,
+}
+
+impl ReviewQueue {
+    pub fn new(tracks: Vec<AudioFileInfo>) -> Self {
+        Self {
+            tracks,
+            position: 0,
+            grades: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Track under review; None once every track has been graded or skipped
+    pub fn current(&self) -> Option<&AudioFileInfo> {
+        self.tracks.get(self.position)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.position >= self.tracks.len()
+    }
+
+    pub fn grade_of(&self, info: &AudioFileInfo) -> Option<Grade> {
+        self.grades.get(&info.track_key()).copied()
+    }
+
+    /// Grade the current track and move on to the next
+    pub fn grade(&mut self, grade: Grade) {
+        if let Some(info) = self.current() {
+            self.grades.insert(info.track_key(), grade);
+            self.position += 1;
+        }
+    }
+
+    /// Move on without grading
+    pub fn skip(&mut self) {
+        self.position = (self.position + 1).min(self.tracks.len());
+    }
+
+    /// Go back to the previous track to grade it again
+    pub fn back(&mut self) {
+        self.position = self.position.saturating_sub(1);
+    }
+
+    pub fn count(&self, grade: Grade) -> usize {
+        self.grades.values().filter(|g| **g == grade).count()
+    }
+
+    /// Tracks given `grade`, in review order
+    pub fn worklist(&self, grade: Grade) -> Vec<&AudioFileInfo> {
+        self.tracks.iter().filter(|info| self.grade_of(info) == Some(grade)).collect()
+    }
+
+    /// Every graded track with its grade, in review order
+    pub fn graded(&self) -> Vec<(&AudioFileInfo, Grade)> {
+        self.tracks.iter().filter_map(|info| self.grade_of(info).map(|g| (info, g))).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grading_advances_and_builds_worklists() {
+        let tracks: Vec<AudioFileInfo> = (0..4)
+            .map(|i| AudioFileInfo::from_nus3audio(format!("vo_{}", i), i.to_string(), 0, String::new(), String::new()))
+            .collect();
+        let mut queue = ReviewQueue::new(tracks);
+
+        queue.grade(Grade::Keep);
+        queue.grade(Grade::Flag);
+        queue.skip();
+        queue.back();
+        assert_eq!(queue.current().unwrap().name, "vo_2");
+        queue.back();
+        queue.grade(Grade::Replace);
+        queue.grade(Grade::Replace);
+        queue.grade(Grade::Keep);
+        assert!(queue.is_finished());
+
+        let names: Vec<&str> = queue.worklist(Grade::Replace).iter().map(|info| info.name.as_str()).collect();
+        assert_eq!(names, vec!["vo_1", "vo_2"]);
+        assert_eq!((queue.count(Grade::Keep), queue.count(Grade::Flag)), (2, 0));
+        assert_eq!(queue.graded().len(), 4);
+    }
+}
@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::audio_file_info::AudioFileInfo;
+use super::export_utils::ExportUtils;
+
+/// A batch replace target whose original sample rate differs from the replacement file
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateMismatch {
+    pub track: String,
+    pub original_rate: u32,
+}
+
+/// Sample rate comparison between one replacement file and every batch target
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRateReport {
+    pub source_rate: u32,
+    pub mismatches: Vec<RateMismatch>,
+    /// Targets whose original rate could not be read
+    pub unknown: Vec<String>,
+    pub target_count: usize,
+}
+
+impl BatchRateReport {
+    /// Compare `source_rate` with the original rate of each (track name, rate) pair
+    pub fn compare(source_rate: u32, originals: &[(String, Option<u32>)]) -> Self {
+        let mut mismatches = Vec::new();
+        let mut unknown = Vec::new();
+        for (track, rate) in originals {
+            match rate {
+                Some(rate) if *rate != source_rate => mismatches.push(RateMismatch {
+                    track: track.clone(),
+                    original_rate: *rate,
+                }),
+                Some(_) => {}
+                None => unknown.push(track.clone()),
+            }
+        }
+        mismatches.sort_by(|a, b| a.original_rate.cmp(&b.original_rate).then(a.track.cmp(&b.track)));
+        Self {
+            source_rate,
+            mismatches,
+            unknown,
+            target_count: originals.len(),
+        }
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.mismatches.is_empty()
+    }
+
+    /// The distinct original rates that differ from the replacement, ascending
+    pub fn differing_rates(&self) -> Vec<u32> {
+        let mut rates: Vec<u32> = self.mismatches.iter().map(|m| m.original_rate).collect();
+        rates.dedup();
+        rates
+    }
+}
+
+/// Parse the "sample rate: 48000 Hz" line of `vgmstream-cli -m`
+pub fn parse_vgmstream_sample_rate(output: &str) -> Option<u32> {
+    output.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("sample rate:")?;
+        rest.trim().trim_end_matches("Hz").trim().parse().ok()
+    })
+}
+
+/// Parse the "channels: 2" line of `vgmstream-cli -m`
+pub fn parse_vgmstream_channels(output: &str) -> Option<u16> {
+    output.lines().find_map(|line| line.trim().strip_prefix("channels:")?.trim().parse().ok())
+}
+
+/// Sample rate of a replacement file on disk
+pub fn source_sample_rate(path: &Path) -> Result<u32, String> {
+    let lower = path.to_string_lossy().to_lowercase();
+    if lower.ends_with(".wav") {
+        if let Ok(reader) = hound::WavReader::open(path) {
+            return Ok(reader.spec().sample_rate);
+        }
+    }
+    if lower.ends_with(".lopus") || lower.ends_with(".idsp") || lower.ends_with(".bin") {
+        return ExportUtils::probe_sample_rate(None, &path.to_string_lossy());
+    }
+    kira::sound::static_sound::StaticSoundData::from_file(path)
+        .map(|data| data.sample_rate)
+        .map_err(|e| format!("Failed to read sample rate: {}", e))
+}
+
+/// Check every batch target against the replacement file. Fails when the replacement's
+/// own rate cannot be determined.
+pub fn check_batch(targets: &[AudioFileInfo], container_path: &str, source: &Path) -> Result<BatchRateReport, String> {
+    let source_rate = source_sample_rate(source)?;
+    let originals: Vec<(String, Option<u32>)> = targets
+        .iter()
+        .map(|t| {
+            let rate = ExportUtils::probe_sample_rate(Some(t), container_path)
+                .map_err(|e| log::warn!("Could not read sample rate of {}: {}", t.name, e))
+                .ok();
+            (t.name.clone(), rate)
+        })
+        .collect();
+    Ok(BatchRateReport::compare(source_rate, &originals))
+}
+
+/// A `check_batch` running on a worker thread, since probing a target can start vgmstream-cli
+pub struct RateCheck {
+    result: Arc<Mutex<Option<Result<BatchRateReport, String>>>>,
+}
+
+impl RateCheck {
+    pub fn start(targets: Vec<AudioFileInfo>, container_path: String, source: PathBuf) -> Self {
+        let result = Arc::new(Mutex::new(None));
+        let shared = result.clone();
+        std::thread::spawn(move || {
+            let report = check_batch(&targets, &container_path, &source);
+            if let Ok(mut result) = shared.lock() {
+                *result = Some(report);
+            }
+        });
+        Self { result }
+    }
+
+    /// The outcome once the check has finished
+    pub fn poll(&self) -> Option<Result<BatchRateReport, String>> {
+        self.result.lock().ok().and_then(|mut result| result.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_vgmstream_metadata() {
+        let output = "encoding: Nintendo DSP 4-bit ADPCM\nsample rate: 32000 Hz\nchannels: 2\n";
+        assert_eq!(parse_vgmstream_sample_rate(output), Some(32000));
+        assert_eq!(parse_vgmstream_sample_rate("channels: 2"), None);
+        assert_eq!(parse_vgmstream_channels(output), Some(2));
+    }
+
+    #[test]
+    fn report_lists_only_differing_targets() {
+        let originals = vec![
+            ("se_hit".to_string(), Some(48000)),
+            ("se_slash".to_string(), Some(32000)),
+            ("se_beam".to_string(), Some(44100)),
+            ("se_boost".to_string(), Some(32000)),
+            ("se_unknown".to_string(), None),
+        ];
+        let report = BatchRateReport::compare(48000, &originals);
+        assert!(report.has_warnings());
+        assert_eq!(report.differing_rates(), vec![32000, 44100]);
+        assert_eq!(report.mismatches.len(), 3);
+        assert_eq!(report.unknown, vec!["se_unknown".to_string()]);
+        assert_eq!(report.target_count, 5);
+    }
+}
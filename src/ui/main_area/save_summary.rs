@@ -0,0 +1,313 @@
+use nus3audio::Nus3audioFile;
+use std::collections::HashMap;
+
+use super::conversion_log;
+use super::dton_pending;
+use super::grp_pending;
+use super::nus3audio_file_utils::Nus3audioFileUtils;
+use super::payload_limit::OversizedPayload;
+use super::prop_pending;
+use super::replace_utils::ReplaceUtils;
+use crate::nus3bank::replace::{Nus3bankReplacer, ReplaceOperation};
+use crate::nus3bank::structures::Nus3bankFile;
+use crate::track_key::TrackKey;
+
+/// Kind of change that will be written for a single track
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveChangeKind {
+    Replaced,
+    Added,
+    Removed,
+}
+
+impl SaveChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SaveChangeKind::Replaced => "Replaced",
+            SaveChangeKind::Added => "Added",
+            SaveChangeKind::Removed => "Removed",
+        }
+    }
+}
+
+/// A single pending track change
+#[derive(Debug, Clone)]
+pub struct SaveChangeEntry {
+    pub kind: SaveChangeKind,
+    pub name: String,
+    pub id: String,
+    /// Payload size in the original file (None for additions)
+    pub old_size: Option<usize>,
+    /// Payload size after saving (None for removals)
+    pub new_size: Option<usize>,
+    /// Tool output and warnings of the conversion that produced the new payload
+    pub log: Option<String>,
+}
+
+/// Summary of everything a save operation is about to write
+#[derive(Debug, Clone, Default)]
+pub struct SaveSummary {
+    pub entries: Vec<SaveChangeEntry>,
+    /// Non-track edits (GRP names, DTON tones, PROP)
+    pub section_edits: Vec<String>,
+    /// Size of the original file on disk
+    pub size_before: u64,
+    /// Estimated size of the written file
+    pub size_after: u64,
+    /// Largest payload the bank should get, when the game preloads it whole
+    pub payload_limit: Option<usize>,
+    /// Pending payloads over `payload_limit`
+    pub oversized: Vec<OversizedPayload>,
+    /// Tracks whose replacement source changed on disk after it was queued
+    pub stale_sources: Vec<String>,
+}
+
+impl SaveSummary {
+    /// Collect the pending changes for the given file
+    pub fn collect(file_path: &str) -> Result<Self, String> {
+        let size_before = std::fs::metadata(file_path)
+            .map_err(|e| format!("Failed to read file metadata: {}", e))?
+            .len();
+
+        let mut summary = Self {
+            size_before,
+            stale_sources: ReplaceUtils::changed_sources()
+                .into_iter()
+                .map(|(key, _)| key.name().to_string())
+                .collect(),
+            ..Default::default()
+        };
+
+        if file_path.to_lowercase().ends_with(".nus3bank") {
+            summary.collect_nus3bank(file_path)?;
+        } else {
+            summary.collect_nus3audio(file_path)?;
+        }
+
+        for entry in summary.entries.iter_mut().filter(|e| e.kind != SaveChangeKind::Removed) {
+            entry.log = conversion_log::text_for(&entry.name, &entry.id);
+        }
+
+        let delta: i64 = summary
+            .entries
+            .iter()
+            .map(|e| e.new_size.unwrap_or(0) as i64 - e.old_size.unwrap_or(0) as i64)
+            .sum();
+        summary.size_after = (size_before as i64 + delta).max(0) as u64;
+
+        Ok(summary)
+    }
+
+    fn collect_nus3audio(&mut self, file_path: &str) -> Result<(), String> {
+        let nus3_file = Nus3audioFile::open(file_path)
+            .map_err(|e| format!("Failed to open NUS3AUDIO file: {}", e))?;
+
+        // The save applies replacements by name
+        for (key, new_size) in ReplaceUtils::get_replacement_sizes() {
+            let TrackKey::Audio { name, id } = key else {
+                continue;
+            };
+            let Some(original) = nus3_file.files.iter().find(|f| f.name == name) else {
+                continue;
+            };
+            self.entries.push(SaveChangeEntry {
+                kind: SaveChangeKind::Replaced,
+                name,
+                id,
+                old_size: Some(original.data.len()),
+                new_size: Some(new_size),
+                log: None,
+            });
+        }
+
+        for (id, name, data) in Nus3audioFileUtils::get_pending_additions() {
+            self.entries.push(SaveChangeEntry {
+                kind: SaveChangeKind::Added,
+                name,
+                id,
+                old_size: None,
+                new_size: Some(data.len()),
+                log: None,
+            });
+        }
+
+        for (id, name) in Nus3audioFileUtils::get_pending_removals() {
+            let old_size = nus3_file
+                .files
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| f.data.len());
+            self.entries.push(SaveChangeEntry {
+                kind: SaveChangeKind::Removed,
+                name,
+                id,
+                old_size,
+                new_size: None,
+                log: None,
+            });
+        }
+
+        self.sort_entries();
+        Ok(())
+    }
+
+    fn collect_nus3bank(&mut self, file_path: &str) -> Result<(), String> {
+        let bank = Nus3bankFile::open(file_path)
+            .map_err(|e| format!("Failed to open NUS3BANK file: {}", e))?;
+        let track_info = |hex_id: &str| {
+            bank.get_track_by_hex_id(hex_id)
+                .map(|t| (t.name.clone(), t.size as usize))
+        };
+
+        // Replacements can live in both stores; UI replacements override at save time
+        let mut replaced: HashMap<String, usize> = HashMap::new();
+        let mut removed: Vec<String> = Vec::new();
+
+        for op in Nus3bankReplacer::get_operations(file_path) {
+            match op {
+                ReplaceOperation::Remove(hex_id) => removed.push(hex_id),
+                ReplaceOperation::Replace(hex_id, data) => {
+                    replaced.insert(hex_id, data.len());
+                }
+                ReplaceOperation::Add(name, temp_hex_id, data) => {
+                    self.entries.push(SaveChangeEntry {
+                        kind: SaveChangeKind::Added,
+                        name,
+                        id: temp_hex_id,
+                        old_size: None,
+                        new_size: Some(data.len()),
+                        log: None,
+                    });
+                }
+                ReplaceOperation::Duplicate(source, name, temp_hex_id) => {
+                    self.entries.push(SaveChangeEntry {
+                        kind: SaveChangeKind::Added,
+                        name,
+                        id: temp_hex_id,
+                        old_size: None,
+                        new_size: track_info(&source).map(|(_, size)| size),
+                        log: None,
+                    });
+                }
+            }
+        }
+
+        if let Some(order) = Nus3bankReplacer::get_tone_order(file_path) {
+            let moved = order.iter().enumerate().filter(|(slot, from)| slot != *from).count();
+            self.section_edits.push(format!("TONE order: {} track ID(s) reassigned", moved));
+        }
+
+        for (key, new_size) in ReplaceUtils::get_replacement_sizes() {
+            if let TrackKey::Bank { hex_id, .. } = key {
+                replaced.insert(hex_id, new_size);
+            }
+        }
+
+        for (hex_id, new_size) in replaced {
+            // A removal wins over a replacement when both are pending
+            if removed.contains(&hex_id) {
+                continue;
+            }
+            let Some((name, old_size)) = track_info(&hex_id) else {
+                continue;
+            };
+            self.entries.push(SaveChangeEntry {
+                kind: SaveChangeKind::Replaced,
+                name,
+                id: hex_id,
+                old_size: Some(old_size),
+                new_size: Some(new_size),
+                log: None,
+            });
+        }
+
+        for hex_id in removed {
+            let (name, old_size) = match track_info(&hex_id) {
+                Some((name, size)) => (name, Some(size)),
+                None => (String::new(), None),
+            };
+            self.entries.push(SaveChangeEntry {
+                kind: SaveChangeKind::Removed,
+                name,
+                id: hex_id,
+                old_size,
+                new_size: None,
+                log: None,
+            });
+        }
+
+        if grp_pending::has(file_path) {
+            self.section_edits.push("GRP names edited".to_string());
+        }
+        if dton_pending::has(file_path) {
+            self.section_edits.push("DTON tones edited".to_string());
+        }
+        if prop_pending::get(file_path).is_some() {
+            self.section_edits.push("PROP edited".to_string());
+        }
+
+        self.sort_entries();
+        Ok(())
+    }
+
+    /// Flag pending replacements and additions over `limit`, if the bank has one
+    pub fn check_payload_limit(&mut self, limit: Option<usize>) {
+        self.payload_limit = limit;
+        self.oversized = match limit {
+            Some(limit) => ReplaceUtils::oversized_payloads(limit)
+                .into_iter()
+                .filter(|p| {
+                    self.entries
+                        .iter()
+                        .any(|e| e.kind != SaveChangeKind::Removed && e.name == p.key.name())
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+    }
+
+    fn sort_entries(&mut self) {
+        self.entries
+            .sort_by(|a, b| (a.kind as u8).cmp(&(b.kind as u8)).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    /// Number of entries of the given kind
+    pub fn count(&self, kind: SaveChangeKind) -> usize {
+        self.entries.iter().filter(|e| e.kind == kind).count()
+    }
+
+    /// Whether the save would write anything different from the original
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.section_edits.is_empty()
+    }
+
+    /// One-line headline, e.g. "3 replaced, 1 added, 2 removed; size 42.1 MB → 43.8 MB"
+    pub fn headline(&self) -> String {
+        let mut text = format!(
+            "{} replaced, {} added, {} removed",
+            self.count(SaveChangeKind::Replaced),
+            self.count(SaveChangeKind::Added),
+            self.count(SaveChangeKind::Removed),
+        );
+        if !self.section_edits.is_empty() {
+            text.push_str(&format!(", {} section edit(s)", self.section_edits.len()));
+        }
+        text.push_str(&format!(
+            "; size {} → {}",
+            format_size(self.size_before as usize),
+            format_size(self.size_after as usize)
+        ));
+        text
+    }
+}
+
+/// Format a byte count the same way the audio table does
+pub fn format_size(size: usize) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f32 / 1024.0)
+    } else {
+        format!("{:.1} MB", size as f32 / (1024.0 * 1024.0))
+    }
+}
@@ -0,0 +1,369 @@
+use egui::{Button, Color32, Context, RichText, ScrollArea, Window};
+use egui_phosphor::regular;
+
+use super::payload_limit::DownsampleOption;
+use super::save_summary::{format_size, SaveChangeKind, SaveSummary};
+use crate::track_key::TrackKey;
+use super::write_access::WriteAccessIssue;
+
+/// Where a confirmed save will be written
+#[derive(Debug, Clone)]
+pub enum SaveTarget {
+    /// Overwrite the opened file (a timestamped .bak copy is made first)
+    InPlace(String),
+    /// Write the opened file with changes applied to a new path
+    SaveAs { original: String, destination: String },
+}
+
+impl SaveTarget {
+    pub fn original_path(&self) -> &str {
+        match self {
+            SaveTarget::InPlace(path) => path,
+            SaveTarget::SaveAs { original, .. } => original,
+        }
+    }
+
+    pub fn destination_path(&self) -> &str {
+        match self {
+            SaveTarget::InPlace(path) => path,
+            SaveTarget::SaveAs { destination, .. } => destination,
+        }
+    }
+}
+
+/// Final confirmation shown before any save writes to disk
+pub struct SaveSummaryModal {
+    pub open: bool,
+    pub summary: SaveSummary,
+    pub target: Option<SaveTarget>,
+    pub confirmed: bool,
+    pub cancelled: bool,
+    /// Set when the user asks for a dry run; the owner runs it and fills `dry_run_result`
+    pub dry_run_requested: bool,
+    /// Report (or error) of the last dry run
+    pub dry_run_result: Option<Result<String, String>>,
+    /// Set when the destination turned out not to be writable
+    pub access_issue: Option<WriteAccessIssue>,
+    /// Set when the user wants to pick a different destination
+    pub choose_other_location_requested: bool,
+    /// Oversized payload the user wants converted; the owner runs it and refreshes the summary
+    pub downsample_requested: Option<(TrackKey, DownsampleOption)>,
+    /// Outcome of the last downsample
+    pub downsample_result: Option<Result<String, String>>,
+    /// Entry whose original audio the user wants to hear; the owner plays it
+    pub audition_requested: Option<usize>,
+    /// Set while an audition started here plays, so the dialog does not duck it
+    pub auditioning: bool,
+    /// Entry whose conversion log is shown
+    log_shown: Option<usize>,
+}
+
+impl Default for SaveSummaryModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveSummaryModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            summary: SaveSummary::default(),
+            target: None,
+            confirmed: false,
+            cancelled: false,
+            dry_run_requested: false,
+            dry_run_result: None,
+            access_issue: None,
+            choose_other_location_requested: false,
+            downsample_requested: None,
+            downsample_result: None,
+            audition_requested: None,
+            auditioning: false,
+            log_shown: None,
+        }
+    }
+
+    /// Open the summary for a save target
+    pub fn open(&mut self, summary: SaveSummary, target: SaveTarget) {
+        self.summary = summary;
+        self.target = Some(target);
+        self.open = true;
+        self.confirmed = false;
+        self.cancelled = false;
+        self.dry_run_requested = false;
+        self.dry_run_result = None;
+        self.access_issue = None;
+        self.choose_other_location_requested = false;
+        self.downsample_requested = None;
+        self.downsample_result = None;
+        self.audition_requested = None;
+        self.auditioning = false;
+        self.log_shown = None;
+    }
+
+    /// Reset the confirmed and cancelled state and drop the target
+    pub fn reset_state(&mut self) {
+        self.confirmed = false;
+        self.cancelled = false;
+        self.target = None;
+        self.dry_run_requested = false;
+        self.dry_run_result = None;
+        self.access_issue = None;
+        self.choose_other_location_requested = false;
+        self.downsample_requested = None;
+        self.downsample_result = None;
+        self.audition_requested = None;
+        self.auditioning = false;
+    }
+
+    /// Re-open the dialog because the destination cannot be written
+    pub fn report_access_issue(&mut self, issue: WriteAccessIssue) {
+        self.access_issue = Some(issue);
+        self.confirmed = false;
+        self.open = true;
+    }
+
+    /// Show the summary dialog
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let available_rect = ctx.available_rect();
+        let min_width = available_rect.width() * 0.4;
+
+        Window::new("Confirm Save")
+            .min_width(min_width)
+            .resizable(true)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                if let Some(target) = &self.target {
+                    match target {
+                        SaveTarget::InPlace(path) => {
+                            ui.label(format!("Overwrite:\n{}", path));
+                        }
+                        SaveTarget::SaveAs { original, destination } => {
+                            ui.label(format!("Source:\n{}", original));
+                            ui.label(format!("Destination:\n{}", destination));
+                        }
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.label(RichText::new(self.summary.headline()).strong());
+
+                if self.summary.is_empty() {
+                    ui.label(
+                        RichText::new("No pending changes; the file will be written unchanged.")
+                            .color(Color32::GRAY),
+                    );
+                }
+
+                for edit in &self.summary.section_edits {
+                    ui.label(format!("• {}", edit));
+                }
+
+                if !self.summary.entries.is_empty() {
+                    ui.add_space(6.0);
+                    egui::CollapsingHeader::new(format!(
+                        "Track changes ({})",
+                        self.summary.entries.len()
+                    ))
+                    .default_open(false)
+                    .show(ui, |ui| {
+                        ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                            egui::Grid::new("save_summary_grid")
+                                .striped(true)
+                                .num_columns(4)
+                                .show(ui, |ui| {
+                                    ui.strong("Change");
+                                    ui.strong("ID");
+                                    ui.strong("Name");
+                                    ui.strong("Size");
+                                    ui.end_row();
+
+                                    for (index, entry) in self.summary.entries.iter().enumerate() {
+                                        let color = match entry.kind {
+                                            SaveChangeKind::Replaced => Color32::from_rgb(100, 180, 255),
+                                            SaveChangeKind::Added => Color32::from_rgb(100, 200, 100),
+                                            SaveChangeKind::Removed => Color32::from_rgb(255, 100, 100),
+                                        };
+                                        ui.horizontal(|ui| {
+                                            ui.label(RichText::new(entry.kind.label()).color(color));
+                                            // Last chance to hear what is about to be deleted
+                                            if entry.kind == SaveChangeKind::Removed
+                                                && ui
+                                                    .small_button(regular::PLAY.to_string())
+                                                    .on_hover_text("Play the original audio of this track")
+                                                    .clicked()
+                                            {
+                                                self.audition_requested = Some(index);
+                                            }
+                                            if entry.log.is_some()
+                                                && ui
+                                                    .small_button(regular::SCROLL.to_string())
+                                                    .on_hover_text("Show the tool output and warnings of the conversion")
+                                                    .clicked()
+                                            {
+                                                self.log_shown = (self.log_shown != Some(index)).then_some(index);
+                                            }
+                                        });
+                                        ui.label(&entry.id);
+                                        ui.label(&entry.name);
+                                        let size_text = match (entry.old_size, entry.new_size) {
+                                            (Some(old), Some(new)) => {
+                                                format!("{} → {}", format_size(old), format_size(new))
+                                            }
+                                            (None, Some(new)) => format!("+{}", format_size(new)),
+                                            (Some(old), None) => format!("-{}", format_size(old)),
+                                            (None, None) => "-".to_string(),
+                                        };
+                                        ui.label(size_text);
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    });
+                }
+
+                let shown = self.log_shown.and_then(|i| self.summary.entries.get(i));
+                if let Some((entry, log)) = shown.and_then(|e| e.log.as_ref().map(|log| (e, log))) {
+                    ui.add_space(6.0);
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(format!("Conversion log: {} ({})", entry.name, entry.id)).strong());
+                            if ui.small_button("Copy").clicked() {
+                                ui.ctx().copy_text(log.clone());
+                            }
+                        });
+                        ScrollArea::vertical().id_salt("conversion_log").max_height(180.0).show(ui, |ui| {
+                            ui.add(
+                                egui::TextEdit::multiline(&mut log.as_str())
+                                    .font(egui::TextStyle::Monospace)
+                                    .desired_width(f32::INFINITY),
+                            );
+                        });
+                    });
+                }
+
+                if let Some(limit) = self.summary.payload_limit.filter(|_| !self.summary.oversized.is_empty()) {
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.colored_label(
+                        Color32::from_rgb(255, 200, 100),
+                        format!(
+                            "{} payload(s) over {}; the game loads this bank whole, so large sounds stay in memory the whole match",
+                            self.summary.oversized.len(),
+                            format_size(limit)
+                        ),
+                    );
+                    for payload in &self.summary.oversized {
+                        ui.horizontal_wrapped(|ui| {
+                            ui.label(format!("• {} ({})", payload.key.name(), format_size(payload.size)));
+                            if payload.options.is_empty() {
+                                let hint = if payload.key.is_added() {
+                                    "add the file again at a lower rate"
+                                } else {
+                                    "no PCM16 conversion fits; re-encode the source"
+                                };
+                                ui.label(RichText::new(hint).weak());
+                            }
+                            for option in &payload.options {
+                                if ui
+                                    .small_button(option.label())
+                                    .on_hover_text("Resample the pending replacement")
+                                    .clicked()
+                                {
+                                    self.downsample_requested = Some((payload.key.clone(), *option));
+                                }
+                            }
+                        });
+                    }
+                }
+                if !self.summary.stale_sources.is_empty() {
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.colored_label(
+                        Color32::from_rgb(255, 200, 100),
+                        format!(
+                            "{} Source file(s) changed on disk since they were queued; saving now writes the audio queued before: {}",
+                            regular::WARNING,
+                            self.summary.stale_sources.join(", ")
+                        ),
+                    );
+                    ui.label(RichText::new("Refresh them from the banner above the track list first.").weak());
+                }
+                match &self.downsample_result {
+                    Some(Ok(message)) => {
+                        ui.colored_label(Color32::from_rgb(100, 200, 100), message);
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(Color32::RED, message);
+                    }
+                    None => {}
+                }
+
+                if let Some(result) = &self.dry_run_result {
+                    ui.add_space(8.0);
+                    ui.separator();
+                    match result {
+                        Ok(report) => {
+                            ui.label(RichText::new("Dry run (nothing was written)").strong());
+                            ui.label(report);
+                        }
+                        Err(e) => {
+                            ui.colored_label(Color32::RED, format!("Dry run failed: {}", e));
+                        }
+                    }
+                }
+
+                if let Some(issue) = &self.access_issue {
+                    ui.add_space(8.0);
+                    ui.separator();
+                    ui.colored_label(Color32::from_rgb(255, 200, 100), issue.message());
+                    ui.label(issue.guidance());
+                    ui.add_space(4.0);
+                    if ui.button("Save to Another Location...").clicked() {
+                        self.choose_other_location_requested = true;
+                    }
+                }
+
+                let save_label = if self.access_issue.is_some() { "Retry" } else { "Save" };
+
+                ui.add_space(12.0);
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .add(
+                                Button::new(RichText::new(save_label).color(Color32::WHITE))
+                                    .fill(Color32::from_rgb(220, 50, 50)),
+                            )
+                            .clicked()
+                        {
+                            self.confirmed = true;
+                            self.open = false;
+                        }
+
+                        ui.add_space(10.0);
+
+                        if ui.button("Cancel").clicked() {
+                            self.cancelled = true;
+                            self.open = false;
+                        }
+
+                        ui.add_space(10.0);
+
+                        if ui
+                            .button("Dry Run")
+                            .on_hover_text("Run the full save pipeline in memory and validate the output without writing")
+                            .clicked()
+                        {
+                            self.dry_run_requested = true;
+                        }
+                    });
+                });
+            });
+    }
+}
@@ -9,6 +9,8 @@ pub enum SearchColumn {
     Size,
     Filename,
     Type,
+    Duration,
+    Transcript,
 }
 
 impl SearchColumn {
@@ -20,6 +22,8 @@ impl SearchColumn {
             SearchColumn::Size => "Size",
             SearchColumn::Filename => "Filename",
             SearchColumn::Type => "Type",
+            SearchColumn::Duration => "Duration",
+            SearchColumn::Transcript => "Transcript",
         }
     }
     
@@ -31,6 +35,81 @@ impl SearchColumn {
             SearchColumn::Size,
             SearchColumn::Filename,
             SearchColumn::Type,
+            SearchColumn::Duration,
+            SearchColumn::Transcript,
         ]
     }
 }
+
+/// Duration comparison typed into the search box, such as ">30s", "<2s", ">=1m30s" or "1:05".
+/// A bare duration matches tracks within half a second of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DurationQuery {
+    op: &'static str,
+    secs: f64,
+}
+
+impl DurationQuery {
+    pub fn parse(query: &str) -> Option<Self> {
+        let query = query.trim();
+        let (op, rest) = ["<=", ">=", "<", ">", "="]
+            .into_iter()
+            .find_map(|op| query.strip_prefix(op).map(|rest| (op, rest)))
+            .unwrap_or(("=", query));
+        Some(Self { op, secs: parse_secs(rest.trim())? })
+    }
+
+    /// Whether a query is a comparison rather than a plain duration, so it cannot be meant
+    /// as text
+    pub fn is_comparison(query: &str) -> bool {
+        query.trim_start().starts_with(['<', '>']) && Self::parse(query).is_some()
+    }
+
+    pub fn matches(&self, secs: f64) -> bool {
+        match self.op {
+            "<" => secs < self.secs,
+            "<=" => secs <= self.secs,
+            ">" => secs > self.secs,
+            ">=" => secs >= self.secs,
+            _ => (secs - self.secs).abs() <= 0.5,
+        }
+    }
+}
+
+/// Seconds in "90", "90s", "1.5m", "1m30s", "250ms" or "1:30"
+fn parse_secs(text: &str) -> Option<f64> {
+    if text.is_empty() {
+        return None;
+    }
+    if let Some((minutes, seconds)) = text.split_once(':') {
+        return Some(minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()?);
+    }
+    if let Some(ms) = text.strip_suffix("ms") {
+        return ms.trim().parse::<f64>().ok().map(|ms| ms / 1000.0);
+    }
+    let (minutes, rest) = match text.split_once('m') {
+        Some((minutes, rest)) => (minutes.trim().parse::<f64>().ok()?, rest.trim_start_matches("in")),
+        None => (0.0, text),
+    };
+    let rest = rest.trim().trim_end_matches("sec").trim_end_matches('s').trim();
+    let seconds = if rest.is_empty() { 0.0 } else { rest.parse::<f64>().ok()? };
+    Some(minutes * 60.0 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_duration_comparisons() {
+        let longer = DurationQuery::parse(">30s").unwrap();
+        assert!(longer.matches(31.0) && !longer.matches(30.0));
+        assert!(DurationQuery::parse("<2s").unwrap().matches(1.2));
+        assert!(DurationQuery::parse(">=1m30s").unwrap().matches(90.0));
+        assert!(DurationQuery::parse("1:05").unwrap().matches(65.3));
+        assert!(DurationQuery::parse("<500ms").unwrap().matches(0.25));
+        assert!(DurationQuery::is_comparison("> 2min"));
+        assert!(!DurationQuery::is_comparison("bgm"));
+        assert_eq!(DurationQuery::parse("bgm"), None);
+    }
+}
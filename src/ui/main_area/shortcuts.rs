@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use egui::{Context, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// Something a key press can do anywhere in the editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    Open,
+    Save,
+    PlayPause,
+    RemoveSelected,
+    FocusSearch,
+    SelectPrevious,
+    SelectNext,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 7] = [
+        ShortcutAction::Open,
+        ShortcutAction::Save,
+        ShortcutAction::PlayPause,
+        ShortcutAction::RemoveSelected,
+        ShortcutAction::FocusSearch,
+        ShortcutAction::SelectPrevious,
+        ShortcutAction::SelectNext,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShortcutAction::Open => "Open file",
+            ShortcutAction::Save => "Save changes",
+            ShortcutAction::PlayPause => "Play / pause",
+            ShortcutAction::RemoveSelected => "Remove selected",
+            ShortcutAction::FocusSearch => "Focus search",
+            ShortcutAction::SelectPrevious => "Select previous row",
+            ShortcutAction::SelectNext => "Select next row",
+        }
+    }
+
+    fn default_shortcut(&self) -> Shortcut {
+        match self {
+            ShortcutAction::Open => Shortcut::command(Key::O),
+            ShortcutAction::Save => Shortcut::command(Key::S),
+            ShortcutAction::PlayPause => Shortcut::plain(Key::Space),
+            ShortcutAction::RemoveSelected => Shortcut::plain(Key::Delete),
+            ShortcutAction::FocusSearch => Shortcut::command(Key::F),
+            ShortcutAction::SelectPrevious => Shortcut::plain(Key::ArrowUp),
+            ShortcutAction::SelectNext => Shortcut::plain(Key::ArrowDown),
+        }
+    }
+}
+
+/// A key with its modifiers, stored by key name so the settings survive egui upgrades
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shortcut {
+    pub key: String,
+    /// Ctrl, or Cmd on macOS
+    pub command: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Shortcut {
+    pub fn plain(key: Key) -> Self {
+        Self { key: key.name().to_string(), command: false, shift: false, alt: false }
+    }
+
+    pub fn command(key: Key) -> Self {
+        Self { command: true, ..Self::plain(key) }
+    }
+
+    pub fn from_modifiers(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            key: key.name().to_string(),
+            command: modifiers.command,
+            shift: modifiers.shift,
+            alt: modifiers.alt,
+        }
+    }
+
+    fn keyboard_shortcut(&self) -> Option<KeyboardShortcut> {
+        let mut modifiers = Modifiers::NONE;
+        if self.command {
+            modifiers = modifiers | Modifiers::COMMAND;
+        }
+        if self.shift {
+            modifiers = modifiers | Modifiers::SHIFT;
+        }
+        if self.alt {
+            modifiers = modifiers | Modifiers::ALT;
+        }
+        Key::from_name(&self.key).map(|key| KeyboardShortcut::new(modifiers, key))
+    }
+
+    /// Keys without a modifier would fire while typing, so text fields and dialogs keep them
+    pub fn is_plain(&self) -> bool {
+        !self.command && !self.alt
+    }
+
+    /// "Ctrl+Shift+O" style text for the settings page and hover hints
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.command {
+            parts.push(if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" });
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        parts.push(&self.key);
+        parts.join("+")
+    }
+}
+
+/// Key bindings of every `ShortcutAction`. Actions missing from the map use their default,
+/// so settings saved before an action existed still bind it.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    /// `None` leaves the action unbound
+    pub bindings: BTreeMap<ShortcutAction, Option<Shortcut>>,
+}
+
+impl ShortcutSettings {
+    pub fn binding(&self, action: ShortcutAction) -> Option<Shortcut> {
+        match self.bindings.get(&action) {
+            Some(binding) => binding.clone(),
+            None => Some(action.default_shortcut()),
+        }
+    }
+
+    pub fn set(&mut self, action: ShortcutAction, shortcut: Option<Shortcut>) {
+        if shortcut.as_ref() == Some(&action.default_shortcut()) {
+            self.bindings.remove(&action);
+        } else {
+            self.bindings.insert(action, shortcut);
+        }
+    }
+
+    /// Action other than `action` already bound to `shortcut`
+    pub fn conflict(&self, action: ShortcutAction, shortcut: &Shortcut) -> Option<ShortcutAction> {
+        ShortcutAction::ALL
+            .into_iter()
+            .find(|other| *other != action && self.binding(*other).as_ref() == Some(shortcut))
+    }
+
+    /// Consume the bound keys pressed this frame and return their actions. With
+    /// `plain_keys` off, only shortcuts that hold Ctrl or Alt are considered.
+    pub fn pressed(&self, ctx: &Context, plain_keys: bool) -> Vec<ShortcutAction> {
+        ShortcutAction::ALL
+            .into_iter()
+            .filter(|action| {
+                let Some(shortcut) = self.binding(*action) else {
+                    return false;
+                };
+                if shortcut.is_plain() && !plain_keys {
+                    return false;
+                }
+                shortcut
+                    .keyboard_shortcut()
+                    .is_some_and(|shortcut| ctx.input_mut(|i| i.consume_shortcut(&shortcut)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_distinct_and_rebinding_back_forgets_the_override() {
+        let mut settings = ShortcutSettings::default();
+        for action in ShortcutAction::ALL {
+            let shortcut = settings.binding(action).unwrap();
+            assert!(shortcut.keyboard_shortcut().is_some(), "{}", shortcut.label());
+            assert_eq!(settings.conflict(action, &shortcut), None);
+        }
+        assert_eq!(settings.binding(ShortcutAction::Save).unwrap().label().rsplit('+').next(), Some("S"));
+
+        settings.set(ShortcutAction::PlayPause, Some(Shortcut::plain(Key::P)));
+        assert_eq!(settings.conflict(ShortcutAction::Open, &Shortcut::plain(Key::P)), Some(ShortcutAction::PlayPause));
+        settings.set(ShortcutAction::PlayPause, Some(Shortcut::plain(Key::Space)));
+        assert!(settings.bindings.is_empty());
+    }
+}
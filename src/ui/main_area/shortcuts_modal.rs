@@ -0,0 +1,137 @@
+use egui::{Color32, Context, Event, Grid, Key, Modifiers, RichText, Window};
+use egui_phosphor::regular;
+
+use super::shortcuts::{Shortcut, ShortcutAction, ShortcutSettings};
+
+/// Lists the keyboard shortcuts and rebinds them by pressing the new keys
+pub struct ShortcutsModal {
+    pub open: bool,
+    draft: ShortcutSettings,
+    /// Action waiting for its new keys
+    capturing: Option<ShortcutAction>,
+    message: Option<String>,
+    saved: Option<ShortcutSettings>,
+}
+
+impl Default for ShortcutsModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShortcutsModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            draft: ShortcutSettings::default(),
+            capturing: None,
+            message: None,
+            saved: None,
+        }
+    }
+
+    pub fn open_with(&mut self, settings: &ShortcutSettings) {
+        self.draft = settings.clone();
+        self.capturing = None;
+        self.message = None;
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Settings edited since the last call
+    pub fn take_saved(&mut self) -> Option<ShortcutSettings> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut changed = false;
+        if let Some(action) = self.capturing {
+            if let Some((key, modifiers)) = Self::captured_key(ctx) {
+                self.capturing = None;
+                let shortcut = Shortcut::from_modifiers(key, modifiers);
+                if key == Key::Escape {
+                    // Capture cancelled
+                } else if let Some(other) = self.draft.conflict(action, &shortcut) {
+                    self.message = Some(format!("{} is already used by \"{}\"", shortcut.label(), other.name()));
+                } else {
+                    self.draft.set(action, Some(shortcut));
+                    self.message = None;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut open = self.open;
+        Window::new("Keyboard Shortcuts")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(380.0)
+            .show(ctx, |ui| {
+                ui.label(
+                    RichText::new("Keys without Ctrl or Alt are ignored while typing in a field or with a dialog open.")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.add_space(6.0);
+
+                Grid::new("shortcuts_grid").num_columns(3).striped(true).spacing([12.0, 6.0]).show(ui, |ui| {
+                    for action in ShortcutAction::ALL {
+                        ui.label(action.name());
+                        let text = if self.capturing == Some(action) {
+                            RichText::new("Press keys...").color(Color32::GOLD)
+                        } else {
+                            match self.draft.binding(action) {
+                                Some(shortcut) => RichText::new(shortcut.label()).monospace(),
+                                None => RichText::new("Unbound").weak(),
+                            }
+                        };
+                        if ui.button(text).on_hover_text("Click, then press the new keys; Esc cancels").clicked() {
+                            self.capturing = Some(action);
+                            self.message = None;
+                        }
+                        if ui.small_button(regular::X.to_string()).on_hover_text("Unbind").clicked() {
+                            self.draft.set(action, None);
+                            changed = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                if let Some(message) = &self.message {
+                    ui.colored_label(Color32::from_rgb(255, 180, 60), message);
+                }
+                ui.add_space(6.0);
+                if ui.button("Reset to Defaults").clicked() {
+                    self.draft = ShortcutSettings::default();
+                    self.capturing = None;
+                    self.message = None;
+                    changed = true;
+                }
+            });
+
+        if changed {
+            self.saved = Some(self.draft.clone());
+        }
+        if !open {
+            self.open = false;
+            self.capturing = None;
+        }
+    }
+
+    /// Next key pressed with its modifiers, consumed so it does not reach the app
+    fn captured_key(ctx: &Context) -> Option<(Key, Modifiers)> {
+        ctx.input_mut(|i| {
+            let pressed = i.events.iter().find_map(|event| match event {
+                Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+                _ => None,
+            })?;
+            i.events.retain(|event| !matches!(event, Event::Key { .. }));
+            Some(pressed)
+        })
+    }
+}
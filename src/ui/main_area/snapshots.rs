@@ -0,0 +1,127 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::time::{Instant, SystemTime};
+
+use crate::nus3bank::structures::{PropSection, ToneDes};
+
+use super::audio_file_info::AudioFileInfo;
+use super::nus3audio_file_utils::{FileChangeState, Nus3audioFileUtils};
+use super::replace_utils::{ReplaceUtils, ReplacementState};
+use super::{dton_pending, grp_pending, prop_pending};
+
+/// A named copy of every pending edit of the open bank, kept for the session
+#[derive(Clone)]
+pub struct Snapshot {
+    pub name: String,
+    pub taken: Instant,
+    pub bank_path: String,
+    /// Size and modification time of the bank file on disk when the snapshot was taken
+    pub base_stamp: Option<BaseStamp>,
+    audio_files: Option<Vec<AudioFileInfo>>,
+    replacements: ReplacementState,
+    changes: FileChangeState,
+    grp_names: Option<Vec<String>>,
+    dton_tones: Option<Vec<ToneDes>>,
+    prop: Option<PropSection>,
+}
+
+impl Snapshot {
+    /// Capture the pending edits of `bank_path` along with the listed tracks
+    pub fn take(name: &str, bank_path: &str, audio_files: Option<Vec<AudioFileInfo>>) -> Self {
+        Self {
+            name: name.to_string(),
+            taken: Instant::now(),
+            bank_path: bank_path.to_string(),
+            base_stamp: base_stamp(bank_path),
+            audio_files,
+            replacements: ReplaceUtils::capture_state(),
+            changes: Nus3audioFileUtils::capture_state(),
+            grp_names: grp_pending::get(bank_path),
+            dton_tones: dton_pending::get(bank_path),
+            prop: prop_pending::get(bank_path),
+        }
+    }
+
+    /// Put the captured edits back, dropping everything done since. Returns the listed tracks
+    /// as they were.
+    pub fn restore(&self) -> Result<Option<Vec<AudioFileInfo>>, String> {
+        ReplaceUtils::restore_state(self.replacements.clone());
+        Nus3audioFileUtils::restore_state(self.changes.clone());
+        match &self.grp_names {
+            Some(names) => grp_pending::set(&self.bank_path, names.clone())?,
+            None => grp_pending::clear(&self.bank_path)?,
+        }
+        match &self.dton_tones {
+            Some(tones) => dton_pending::set(&self.bank_path, tones.clone())?,
+            None => dton_pending::clear(&self.bank_path)?,
+        }
+        match &self.prop {
+            Some(prop) => prop_pending::set(&self.bank_path, prop.clone())?,
+            None => prop_pending::clear(&self.bank_path)?,
+        }
+        Ok(self.audio_files.clone())
+    }
+
+    /// Tracks listed when the snapshot was taken
+    pub fn audio_files(&self) -> Option<&[AudioFileInfo]> {
+        self.audio_files.as_deref()
+    }
+
+    /// Number of replacements, additions and removals, plus one per edited section
+    pub fn edit_count(&self) -> usize {
+        self.replacements.len()
+            + self.changes.len()
+            + [self.grp_names.is_some(), self.dton_tones.is_some(), self.prop.is_some()]
+                .iter()
+                .filter(|edited| **edited)
+                .count()
+    }
+
+    /// Whether a bank file stamped `current` differs from the one the snapshot was taken on
+    pub fn base_changed(&self, current: Option<BaseStamp>) -> bool {
+        self.base_stamp != current
+    }
+}
+
+/// Size and modification time of a bank file. Banks run to hundreds of megabytes, so a
+/// rewrite is told from the metadata rather than by reading the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+/// Stamp of the file at `path`, if it exists
+pub fn base_stamp(path: &str) -> Option<BaseStamp> {
+    let meta = std::fs::metadata(path).ok()?;
+    Some(BaseStamp { len: meta.len(), modified: meta.modified().ok() })
+}
+
+fn hash_bytes(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(data);
+    hasher.finish()
+}
+
+/// Hash of a file's contents, if it can be read
+pub fn hash_file(path: &str) -> Option<u64> {
+    std::fs::read(path).ok().map(|data| hash_bytes(&data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_a_changed_base_file() {
+        let path = std::env::temp_dir().join(format!("exvs2_snapshot_base_test_{}.nus3bank", std::process::id()));
+        std::fs::write(&path, b"NUS3 original").unwrap();
+        let path_str = path.to_string_lossy().to_string();
+        let snapshot = Snapshot::take("before", &path_str, None);
+        assert!(!snapshot.base_changed(base_stamp(&path_str)));
+
+        std::fs::write(&path, b"NUS3 rebuilt").unwrap();
+        assert!(snapshot.base_changed(base_stamp(&path_str)));
+        let _ = std::fs::remove_file(&path);
+    }
+}
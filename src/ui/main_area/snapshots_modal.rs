@@ -0,0 +1,146 @@
+use egui::{Color32, Context, RichText, Window};
+use egui_phosphor::regular;
+
+use super::snapshots::{self, Snapshot};
+
+/// Lists the named snapshots of the open bank and takes, restores and deletes them
+pub struct SnapshotsModal {
+    pub open: bool,
+    bank_path: String,
+    /// Size and modification time of the bank file when the dialog was opened
+    current_stamp: Option<snapshots::BaseStamp>,
+    name: String,
+    snapshots: Vec<Snapshot>,
+    take_request: Option<String>,
+    restore_request: Option<usize>,
+}
+
+impl Default for SnapshotsModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapshotsModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            bank_path: String::new(),
+            current_stamp: None,
+            name: String::new(),
+            snapshots: Vec::new(),
+            take_request: None,
+            restore_request: None,
+        }
+    }
+
+    pub fn open_for(&mut self, bank_path: &str) {
+        self.bank_path = bank_path.to_string();
+        self.current_stamp = snapshots::base_stamp(bank_path);
+        self.take_request = None;
+        self.restore_request = None;
+        self.open = true;
+    }
+
+    /// Name of a snapshot to take, if one was asked for since the last call
+    pub fn take_take_request(&mut self) -> Option<String> {
+        self.take_request.take()
+    }
+
+    /// Snapshot to restore, if one was picked since the last call
+    pub fn take_restore_request(&mut self) -> Option<Snapshot> {
+        let index = self.restore_request.take()?;
+        self.snapshots.get(index).cloned()
+    }
+
+    pub fn add(&mut self, snapshot: Snapshot) {
+        self.snapshots.push(snapshot);
+    }
+
+    fn default_name(&self) -> String {
+        let count = self.snapshots.iter().filter(|s| s.bank_path == self.bank_path).count();
+        format!("Snapshot {}", count + 1)
+    }
+
+    fn ago(snapshot: &Snapshot) -> String {
+        let secs = snapshot.taken.elapsed().as_secs();
+        if secs < 60 {
+            format!("{} s ago", secs)
+        } else if secs < 3600 {
+            format!("{} min ago", secs / 60)
+        } else {
+            format!("{} h ago", secs / 3600)
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut delete = None;
+        Window::new("Snapshots")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.bank_path).weak().size(11.0));
+                ui.add_space(6.0);
+                ui.label("Save the pending edits under a name and go back to them at any time this session.");
+                ui.add_space(6.0);
+
+                ui.horizontal(|ui| {
+                    let hint = self.default_name();
+                    ui.add(egui::TextEdit::singleline(&mut self.name).hint_text(hint.as_str()).desired_width(260.0));
+                    if ui.button(format!("{} Take Snapshot", regular::CAMERA)).clicked() {
+                        let name = self.name.trim();
+                        self.take_request = Some(if name.is_empty() { hint } else { name.to_string() });
+                        self.name.clear();
+                    }
+                });
+                ui.separator();
+
+                let mut listed = false;
+                egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                    for (index, snapshot) in self.snapshots.iter().enumerate().rev() {
+                        if snapshot.bank_path != self.bank_path {
+                            continue;
+                        }
+                        listed = true;
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new(&snapshot.name).strong());
+                            ui.label(
+                                RichText::new(format!("{} edits, {}", snapshot.edit_count(), Self::ago(snapshot)))
+                                    .weak()
+                                    .size(11.0),
+                            );
+                            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                                if ui.button(regular::TRASH.to_string()).on_hover_text("Delete").clicked() {
+                                    delete = Some(index);
+                                }
+                                if ui.button("Restore").on_hover_text("Replace the pending edits with this snapshot").clicked() {
+                                    self.restore_request = Some(index);
+                                }
+                                if snapshot.base_changed(self.current_stamp) {
+                                    ui.label(RichText::new(regular::WARNING.to_string()).color(Color32::from_rgb(255, 180, 60)))
+                                        .on_hover_text("The bank file changed on disk since this snapshot was taken");
+                                }
+                            });
+                        });
+                    }
+                });
+                if !listed {
+                    ui.label(RichText::new("No snapshots of this bank yet").weak());
+                }
+            });
+
+        if let Some(index) = delete {
+            self.snapshots.remove(index);
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
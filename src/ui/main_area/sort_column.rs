@@ -8,6 +8,12 @@ pub enum SortColumn {
     Size,
     Filename,
     Type,
+    SampleRate,
+    Channels,
+    BitDepth,
+    Duration,
+    Entropy,
+    WavRatio,
     None,
 }
 
@@ -20,6 +26,12 @@ impl SortColumn {
             Self::Size => "Size",
             Self::Filename => "Filename",
             Self::Type => "Type",
+            Self::SampleRate => "Sample rate",
+            Self::Channels => "Channels",
+            Self::BitDepth => "Bit depth",
+            Self::Duration => "Duration",
+            Self::Entropy => "Byte entropy",
+            Self::WavRatio => "Size as WAV",
             Self::None => "",
         }
     }
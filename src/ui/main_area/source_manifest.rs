@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::project_file::{ProjectFile, ProjectTrack};
+
+/// What a manifest row queues for its track
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestAction {
+    Replace,
+    Add,
+    /// Replace with Empty; no source file
+    Silence,
+}
+
+impl ManifestAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ManifestAction::Replace => "replace",
+            ManifestAction::Add => "add",
+            ManifestAction::Silence => "silence",
+        }
+    }
+}
+
+/// One pending edit: the track, the source chosen for it and its parameters
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct ManifestEntry {
+    pub action: ManifestAction,
+    pub name: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PathBuf>,
+    #[serde(default)]
+    pub loop_enabled: bool,
+    /// Loop start and end in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_start: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_end: Option<f32>,
+    #[serde(default)]
+    pub gain_db: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silence_ms: Option<u32>,
+}
+
+impl ManifestEntry {
+    fn for_track(action: ManifestAction, track: &ProjectTrack) -> Self {
+        Self {
+            action,
+            name: track.name.clone(),
+            id: track.id.clone(),
+            hex_id: track.hex_id.clone(),
+            source: None,
+            loop_enabled: false,
+            loop_start: None,
+            loop_end: None,
+            gain_db: 0.0,
+            silence_ms: None,
+        }
+    }
+}
+
+/// Readable list of the pending sources of one bank, to rebuild the working set by hand
+/// or share it before anything is saved. Written as JSON, or CSV for spreadsheets.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SourceManifest {
+    pub bank: PathBuf,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl SourceManifest {
+    /// Replacements, silences and additions of a project, sorted by track name
+    pub fn from_project(project: &ProjectFile) -> Self {
+        let mut entries = Vec::new();
+        for replacement in &project.replacements {
+            let settings = &replacement.loop_settings;
+            let custom = settings.enable_loop && settings.use_custom_loop;
+            entries.push(ManifestEntry {
+                source: Some(replacement.source.clone()),
+                loop_enabled: settings.enable_loop,
+                loop_start: settings.loop_start.filter(|_| custom),
+                loop_end: settings.loop_end.filter(|_| custom),
+                gain_db: settings.gain_db,
+                ..ManifestEntry::for_track(ManifestAction::Replace, &replacement.track)
+            });
+        }
+        for empty in &project.empty_replacements {
+            entries.push(ManifestEntry {
+                silence_ms: Some(empty.min_ms),
+                ..ManifestEntry::for_track(ManifestAction::Silence, &empty.track)
+            });
+        }
+        for addition in &project.additions {
+            entries.push(ManifestEntry {
+                source: Some(addition.source.clone()),
+                ..ManifestEntry::for_track(ManifestAction::Add, &addition.track)
+            });
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        Self {
+            bank: project.bank_path.clone(),
+            entries,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to encode the manifest: {}", e))
+    }
+
+    /// Entries as CSV with a header row; the bank is not included
+    pub fn to_csv(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+        let number = |v: Option<f32>| v.map(|v| format!("{:.3}", v)).unwrap_or_default();
+        let mut out = String::from("action,name,id,hex_id,source,loop,loop_start,loop_end,gain_db,silence_ms\n");
+        for e in &self.entries {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.1},{}\n",
+                e.action.name(),
+                quote(&e.name),
+                quote(&e.id),
+                e.hex_id.as_deref().unwrap_or_default(),
+                e.source.as_ref().map(|s| quote(&s.display().to_string())).unwrap_or_default(),
+                e.loop_enabled,
+                number(e.loop_start),
+                number(e.loop_end),
+                e.gain_db,
+                e.silence_ms.map(|ms| ms.to_string()).unwrap_or_default(),
+            ));
+        }
+        out
+    }
+
+    /// Write as CSV when `path` ends in `.csv`, JSON otherwise
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let is_csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let text = if is_csv { self.to_csv() } else { self.to_json()? };
+        fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::main_area::loop_settings_modal::LoopSettings;
+    use crate::ui::main_area::project_file::{ProjectEmptyReplacement, ProjectReplacement};
+
+    #[test]
+    fn lists_sources_with_their_parameters() {
+        let track = |name: &str, id: &str| ProjectTrack {
+            name: name.to_string(),
+            id: id.to_string(),
+            hex_id: None,
+            is_nus3bank: false,
+            filename: String::new(),
+            file_type: "WAV".to_string(),
+            size: 0,
+        };
+        let mut project = ProjectFile::new(PathBuf::from("bgm.nus3audio"));
+        project.replacements.push(ProjectReplacement {
+            track: track("bgm_b", "1"),
+            source: PathBuf::from("mix \"final\".wav"),
+            loop_settings: LoopSettings {
+                use_custom_loop: true,
+                loop_start: Some(1.5),
+                gain_db: -3.0,
+                ..Default::default()
+            },
+        });
+        project.empty_replacements.push(ProjectEmptyReplacement {
+            track: track("bgm_a", "0"),
+            min_ms: 100,
+        });
+
+        let manifest = SourceManifest::from_project(&project);
+        assert_eq!(manifest.entries[0].action, ManifestAction::Silence);
+        assert_eq!(
+            manifest.to_csv().lines().nth(2),
+            Some("replace,\"bgm_b\",\"1\",,\"mix \"\"final\"\".wav\",true,1.500,,-3.0,")
+        );
+        let json = manifest.to_json().unwrap();
+        assert_eq!(serde_json::from_str::<SourceManifest>(&json).unwrap(), manifest);
+    }
+}
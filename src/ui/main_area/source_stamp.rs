@@ -0,0 +1,75 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::Hasher;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// How a replacement source file looked when it was processed
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+/// Result of comparing a source file with its stamp
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceChange {
+    Unchanged,
+    /// Saved again with the same content; the new stamp should replace the old one
+    Touched(SourceStamp),
+    Changed,
+    Missing,
+}
+
+impl SourceStamp {
+    pub fn read(path: &Path) -> Option<Self> {
+        let meta = fs::metadata(path).ok()?;
+        let data = fs::read(path).ok()?;
+        let mut hasher = DefaultHasher::new();
+        hasher.write(&data);
+        Some(Self {
+            modified: meta.modified().ok(),
+            len: data.len() as u64,
+            hash: hasher.finish(),
+        })
+    }
+
+    /// Compare with the file now on disk. The content is only hashed again when the size or
+    /// modification time moved.
+    pub fn check(&self, path: &Path) -> SourceChange {
+        let Ok(meta) = fs::metadata(path) else {
+            return SourceChange::Missing;
+        };
+        if meta.len() == self.len && meta.modified().ok() == self.modified {
+            return SourceChange::Unchanged;
+        }
+        match Self::read(path) {
+            None => SourceChange::Missing,
+            Some(now) if now.hash == self.hash && now.len == self.len => SourceChange::Touched(now),
+            Some(_) => SourceChange::Changed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_new_content_but_not_a_plain_resave() {
+        let path = std::env::temp_dir().join(format!("exvs2_source_stamp_{}.wav", std::process::id()));
+        fs::write(&path, b"first export").unwrap();
+        let stamp = SourceStamp::read(&path).unwrap();
+        assert_eq!(stamp.check(&path), SourceChange::Unchanged);
+
+        fs::write(&path, b"second export, longer").unwrap();
+        assert_eq!(stamp.check(&path), SourceChange::Changed);
+
+        fs::write(&path, b"first export").unwrap();
+        assert!(matches!(stamp.check(&path), SourceChange::Unchanged | SourceChange::Touched(_)));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(stamp.check(&path), SourceChange::Missing);
+    }
+}
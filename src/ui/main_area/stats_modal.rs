@@ -0,0 +1,285 @@
+use egui::{Color32, Context, Grid, ProgressBar, RichText, ScrollArea, Ui, Window};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::audio_file_info::AudioFileInfo;
+use super::bank_loader::load_payloads;
+use super::bank_stats::{self, BankStats, TrackMeasurement};
+use super::decode_pool;
+use super::export_utils::ExportUtils;
+use super::save_summary::format_size;
+
+/// Progress of the background measurement, shared with the worker thread
+#[derive(Default)]
+struct StatsProgress {
+    done: usize,
+    total: usize,
+    result: Option<Result<BankStats, String>>,
+}
+
+/// Statistics of the opened bank: codecs, duration, sizes and levels, with links to tracks
+pub struct StatsModal {
+    pub open: bool,
+    file_path: String,
+    progress: Option<Arc<Mutex<StatsProgress>>>,
+    cancel: Arc<AtomicBool>,
+    stats: Option<BankStats>,
+    error: Option<String>,
+    jump: Option<AudioFileInfo>,
+}
+
+impl Default for StatsModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            file_path: String::new(),
+            progress: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            stats: None,
+            error: None,
+            jump: None,
+        }
+    }
+
+    /// Open the window and measure `tracks` of `file_path` in the background
+    pub fn open_for(&mut self, file_path: &str, tracks: Vec<AudioFileInfo>) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.file_path = file_path.to_string();
+        self.stats = None;
+        self.error = None;
+        self.jump = None;
+        self.open = true;
+
+        let progress = Arc::new(Mutex::new(StatsProgress {
+            total: tracks.len(),
+            ..Default::default()
+        }));
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.progress = Some(progress.clone());
+        let cancel = self.cancel.clone();
+        let file_path = self.file_path.clone();
+
+        std::thread::spawn(move || {
+            let result = measure_tracks(&file_path, tracks, &progress, &cancel)
+                .map(|measurements| BankStats::summarize(&measurements));
+            if let Ok(mut p) = progress.lock() {
+                p.result = Some(result);
+            }
+        });
+    }
+
+    /// Take the track whose link was clicked
+    pub fn take_jump(&mut self) -> Option<AudioFileInfo> {
+        self.jump.take()
+    }
+
+    fn poll(&mut self) {
+        let finished = self
+            .progress
+            .as_ref()
+            .and_then(|p| p.lock().ok().and_then(|mut p| p.result.take()));
+        let Some(result) = finished else {
+            return;
+        };
+        self.progress = None;
+        match result {
+            Ok(stats) => self.stats = Some(stats),
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        self.poll();
+
+        let mut open = self.open;
+        Window::new("Bank Statistics")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                let file_name = std::path::Path::new(&self.file_path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                ui.label(RichText::new(file_name).strong());
+                ui.label(
+                    RichText::new("Measured from the file on disk; pending changes are not included.")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.add_space(6.0);
+
+                if let Some(progress) = &self.progress {
+                    let (done, total) = progress.lock().map(|p| (p.done, p.total)).unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(format!("Measuring tracks {} / {}...", done, total));
+                        if ui.button("Stop").on_hover_text("Summarize the tracks measured so far").clicked() {
+                            self.cancel.store(true, Ordering::Relaxed);
+                        }
+                    });
+                    ui.add(ProgressBar::new(done as f32 / total.max(1) as f32));
+                    ctx.request_repaint();
+                    return;
+                }
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::from_rgb(255, 100, 100), error);
+                    return;
+                }
+                let Some(stats) = &self.stats else {
+                    return;
+                };
+
+                let mut jump = None;
+                ScrollArea::vertical().show(ui, |ui| {
+                    Self::show_stats(ui, stats, &mut jump);
+                });
+                if jump.is_some() {
+                    self.jump = jump;
+                }
+            });
+        if !open {
+            self.cancel.store(true, Ordering::Relaxed);
+            self.open = false;
+        }
+    }
+
+    fn show_stats(ui: &mut Ui, stats: &BankStats, jump: &mut Option<AudioFileInfo>) {
+        Grid::new("bank_stats_overview").num_columns(2).show(ui, |ui| {
+            ui.label("Tracks:");
+            ui.label(stats.track_count.to_string());
+            ui.end_row();
+            ui.label("Total size:");
+            ui.label(format_size(stats.total_size));
+            ui.end_row();
+            ui.label("Total duration:");
+            let total = stats.total_duration_secs.round() as u64;
+            let mut text = format!("{}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60);
+            if stats.measured_count < stats.track_count {
+                text.push_str(&format!(" ({} of {} tracks measured)", stats.measured_count, stats.track_count));
+            }
+            ui.label(text);
+            ui.end_row();
+        });
+
+        ui.add_space(8.0);
+        ui.heading("By codec");
+        Grid::new("bank_stats_codecs").num_columns(2).show(ui, |ui| {
+            for (codec, count) in &stats.codec_counts {
+                ui.label(codec);
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.heading("Size distribution");
+        let max_bucket = stats.size_histogram.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+        Grid::new("bank_stats_sizes").num_columns(2).show(ui, |ui| {
+            for bucket in &stats.size_histogram {
+                ui.label(&bucket.label);
+                ui.add(
+                    ProgressBar::new(bucket.count as f32 / max_bucket as f32)
+                        .desired_width(260.0)
+                        .text(bucket.count.to_string()),
+                );
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(8.0);
+        ui.heading(format!("Largest {}", bank_stats::TOP_COUNT));
+        Grid::new("bank_stats_largest").num_columns(2).show(ui, |ui| {
+            for info in &stats.largest {
+                Self::track_link(ui, info, jump);
+                ui.label(format_size(info.size));
+                ui.end_row();
+            }
+        });
+
+        for (title, levels, id) in [
+            ("Loudest", &stats.loudest, "bank_stats_loudest"),
+            ("Quietest", &stats.quietest, "bank_stats_quietest"),
+        ] {
+            ui.add_space(8.0);
+            ui.heading(format!("{} {}", title, bank_stats::TOP_COUNT));
+            if levels.is_empty() {
+                ui.label(RichText::new("No track levels could be measured.").weak());
+                continue;
+            }
+            Grid::new(id).num_columns(2).show(ui, |ui| {
+                for (info, level) in levels {
+                    Self::track_link(ui, info, jump);
+                    ui.label(format!("{:.1} dBFS", level));
+                    ui.end_row();
+                }
+            });
+        }
+    }
+
+    fn track_link(ui: &mut Ui, info: &AudioFileInfo, jump: &mut Option<AudioFileInfo>) {
+        if ui
+            .link(&info.name)
+            .on_hover_text("Show this track in the table")
+            .clicked()
+        {
+            *jump = Some(info.clone());
+        }
+    }
+}
+
+/// Measure every track on the decode pool: WAV payloads natively, everything else decoded
+/// with vgmstream-cli. Tracks left when `cancel` is set keep their size and codec but no
+/// duration or level.
+fn measure_tracks(
+    file_path: &str,
+    tracks: Vec<AudioFileInfo>,
+    progress: &Arc<Mutex<StatsProgress>>,
+    cancel: &Arc<AtomicBool>,
+) -> Result<Vec<TrackMeasurement>, String> {
+    let mut payloads = load_payloads(file_path, &tracks)?;
+    let jobs: Vec<_> = tracks
+        .into_iter()
+        .map(|info| {
+            let data = payloads.remove(&info.track_key());
+            (info, data)
+        })
+        .collect();
+    let (file_path, progress, cancel) = (file_path.to_string(), progress.clone(), cancel.clone());
+    Ok(decode_pool::map(jobs, move |(info, data)| {
+        let (duration_secs, level_dbfs) = if cancel.load(Ordering::Relaxed) {
+            (None, None)
+        } else {
+            match data {
+                Some(data) if data.starts_with(b"RIFF") => bank_stats::measure_wav(&data),
+                _ => measure_with_vgmstream(&info, &file_path),
+            }
+        };
+        if let Ok(mut p) = progress.lock() {
+            p.done += 1;
+        }
+        TrackMeasurement { info, duration_secs, level_dbfs }
+    }))
+}
+
+fn measure_with_vgmstream(info: &AudioFileInfo, file_path: &str) -> (Option<f64>, Option<f32>) {
+    let Ok(temp_path) = ExportUtils::convert_to_wav_temp_path(info, file_path) else {
+        return (None, None);
+    };
+    let result = fs::read(&temp_path)
+        .map(|data| bank_stats::measure_wav(&data))
+        .unwrap_or((None, None));
+    let _ = fs::remove_file(&temp_path);
+    result
+}
@@ -1,10 +1,53 @@
 use egui::{
     Button, Color32, Grid, Layout, Rect, RichText, ScrollArea, Stroke, StrokeKind, TextWrapMode, Ui, Vec2, Direction,
 };
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use super::audio_file_info::AudioFileInfo;
+use super::color_labels::ColorLabel;
+use super::empty_tracks::EmptyKind;
+use super::file_drop;
+use super::file_types::FileTypeRegistry;
 
+use super::loop_clipboard;
+use super::pad_grid::PAD_COUNT;
+use super::replace_utils::ReplaceUtils;
 use super::sort_column::SortColumn;
+use super::track_categories::TrackCategories;
+use super::track_format::{MetadataColumn, TrackFormat};
+use super::table_view;
+use super::transcripts::Transcripts;
+use crate::labels::LabelFile;
+use crate::track_key::TrackKey;
+
+/// Actions offered in the right-click menu of a table row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowMenuAction {
+    CopyLoopSettings,
+    PasteLoopSettings,
+    BlindTest,
+    /// Keep the track to paste into another open file
+    CopyTrack,
+    /// Copy the track into a new slot of the same file
+    Duplicate,
+    EditTranscript,
+    /// Change the loop and gain of the queued replacement
+    EditReplacement,
+    /// Open the mixing panel for the track
+    Mix,
+    AssignPad(usize),
+    ReplaceFromUrl,
+    RevealReplacementSource,
+    OpenReplacementSource,
+    SetColorLabel(Option<ColorLabel>),
+    /// Lock or unlock the track against batch operations
+    SetLocked(bool),
+    /// Give a NUS3BANK track the ID before or after its own
+    MoveUp,
+    MoveDown,
+    EditId,
+    /// Audio files were dropped onto the row
+    FilesDropped,
+}
 
 /// Table renderer for displaying audio files
 pub struct TableRenderer;
@@ -17,15 +60,27 @@ impl TableRenderer {
         selected_rows: &mut HashSet<usize>,
         persistent_selected: &mut HashSet<String>,
         now_playing_key: Option<&str>,
+        scroll_to_row: Option<usize>,
+        row_labels: &BTreeMap<TrackKey, ColorLabel>,
+        locked_rows: &BTreeSet<TrackKey>,
+        track_labels: Option<&LabelFile>,
+        transcripts: &Transcripts,
+        track_categories: &TrackCategories,
+        empty_tracks: &HashMap<TrackKey, EmptyKind>,
+        unplayable: &HashMap<TrackKey, &'static str>,
+        track_formats: &HashMap<TrackKey, TrackFormat>,
+        metadata_columns: &[MetadataColumn],
         striped: bool,
         clickable: bool,
         show_grid_lines: bool,
+        accept_drops: bool,
         available_height: f32,
         available_width: f32,
         on_export_clicked: &mut dyn FnMut(usize),
         on_play_clicked: &mut dyn FnMut(usize),
         on_replace_clicked: &mut dyn FnMut(usize),
         on_remove_clicked: &mut dyn FnMut(usize),
+        on_row_menu: &mut dyn FnMut(usize, RowMenuAction),
         sort_column: &mut SortColumn,
         sort_ascending: &mut bool,
     ) {
@@ -86,6 +141,23 @@ impl TableRenderer {
             )
         };
 
+        // The Labels column only exists while a companion label file is loaded; it takes its
+        // width from the Name and Filename columns
+        let (col_width_name, col_width_filename, col_width_label) = if track_labels.is_some() {
+            (
+                col_width_name - available_width * 0.06,
+                col_width_filename - available_width * 0.06,
+                available_width * 0.12,
+            )
+        } else {
+            (col_width_name, col_width_filename, 0.0)
+        };
+        // Metadata columns chosen in the column chooser also share the Name and Filename width
+        let col_width_meta = available_width * 0.06;
+        let meta_share = col_width_meta * metadata_columns.len() as f32 / 2.0;
+        let (col_width_name, col_width_filename) = (col_width_name - meta_share, col_width_filename - meta_share);
+        let num_columns = if track_labels.is_some() { 8 } else { 7 } + metadata_columns.len();
+
         // Header text size
         let heading_size = 17.0;
         let now_playing_bg = if ui.visuals().dark_mode {
@@ -114,7 +186,7 @@ impl TableRenderer {
         );
 
         Grid::new("table_header")
-            .num_columns(7)
+            .num_columns(num_columns)
             .spacing([5.0, 0.0])
             .show(ui, |ui| {
                 // Header with sort indicators and clickable functionality
@@ -239,6 +311,20 @@ impl TableRenderer {
                     }
                 };
 
+                // Labels column header (not sortable)
+                if let Some(labels) = track_labels {
+                    let file_name = labels
+                        .path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    ui.add_sized(
+                        [col_width_label, header_height],
+                        Button::new(RichText::new("Labels").size(heading_size).strong()).fill(header_bg_color),
+                    )
+                    .on_hover_text(format!("Read from {} ({} labels)", file_name, labels.len()));
+                }
+
                 // Type column header
                 let type_sort_icon = if *sort_column == SortColumn::Type {
                     if *sort_ascending {
@@ -263,6 +349,33 @@ impl TableRenderer {
                     }
                 };
 
+                // Metadata column headers
+                for column in metadata_columns {
+                    let column_sort = column.sort_column();
+                    let sort_icon = if *sort_column == column_sort {
+                        if *sort_ascending {
+                            format!(" {}", egui_phosphor::regular::ARROW_UP)
+                        } else {
+                            format!(" {}", egui_phosphor::regular::ARROW_DOWN)
+                        }
+                    } else {
+                        "".to_string()
+                    };
+                    let text = RichText::new(format!("{}{}", column.name(), sort_icon)).size(heading_size).strong();
+
+                    if ui.add_sized(
+                        [col_width_meta, header_height],
+                        Button::new(text).fill(header_bg_color)
+                    ).on_hover_text(column.long_name()).clicked() {
+                        if *sort_column == column_sort {
+                            *sort_ascending = !*sort_ascending;
+                        } else {
+                            *sort_column = column_sort;
+                            *sort_ascending = true;
+                        }
+                    };
+                }
+
                 // Action column header - consistent button style
                 ui.allocate_ui_with_layout(
                     Vec2::new(col_action, header_height),
@@ -282,14 +395,21 @@ impl TableRenderer {
         // let row_height = ui.spacing().interact_size.y; // if you are adding buttons instead of labels.
         ui.set_min_height(available_height / 3.0); // Adjusted for header and spacing
 
-        ScrollArea::vertical().show_rows(ui, row_height, audio_files.len(), |ui, row_range| {
+        let mut scroll_area = ScrollArea::vertical();
+        if let Some(row) = scroll_to_row {
+            // Leave a couple of rows above the target so it is not glued to the header
+            let row_stride = row_height + ui.spacing().item_spacing.y;
+            scroll_area = scroll_area.vertical_scroll_offset(row.saturating_sub(2) as f32 * row_stride);
+        }
+        scroll_area.show_rows(ui, row_height, audio_files.len(), |ui, row_range| {
             Grid::new("table_content")
-                .num_columns(7)
+                .num_columns(num_columns)
                 .spacing([5.0, 2.0])
                 .show(ui, |ui| {
                     for row_index in row_range {
                         let file = &audio_files[row_index];
                         let key = format!("{}:{}", file.name, file.id);
+                        let track_key = file.track_key();
                         let is_persist_selected = persistent_selected.contains(&key);
                         let is_row_selected = selected_rows.contains(&row_index);
                         let is_selected = is_persist_selected || is_row_selected;
@@ -326,6 +446,14 @@ impl TableRenderer {
                             );
                         }
 
+                        if let Some(label) = row_labels.get(&track_key) {
+                            let strip = Rect::from_min_size(
+                                row_rect.min + Vec2::new(3.0, 2.0),
+                                Vec2::new(4.0, row_height - 4.0),
+                            );
+                            ui.painter().rect_filled(strip, 2.0, label.color32());
+                        }
+
                         if is_now_playing {
                             let rect = Rect::from_min_size(row_rect.min, row_size);
                             let left_line_start = rect.min + Vec2::new(0.0, 2.0);
@@ -354,6 +482,18 @@ impl TableRenderer {
                             sense,
                         );
 
+                        // Dropping audio files onto a row targets that track
+                        let row_rect_full = Rect::from_min_size(row_rect.min, row_size);
+                        if accept_drops
+                            && file_drop::pointer_pos(ui.ctx()).is_some_and(|pos| row_rect_full.contains(pos))
+                        {
+                            if file_drop::is_hovering(ui.ctx()) {
+                                file_drop::paint_drop_target(ui, row_rect_full);
+                            } else if !file_drop::dropped_audio_files(ui.ctx()).is_empty() {
+                                on_row_menu(row_index, RowMenuAction::FilesDropped);
+                            }
+                        }
+
                         // Handle row click events: toggle row selection only (checkbox controls persistent selection)
                         if row_response.clicked() && clickable {
                             if selected_rows.contains(&row_index) {
@@ -363,6 +503,172 @@ impl TableRenderer {
                             }
                         }
 
+                        row_response.context_menu(|ui| {
+                            if ui.button("Copy loop settings").clicked() {
+                                on_row_menu(row_index, RowMenuAction::CopyLoopSettings);
+                                ui.close();
+                            }
+                            let paste_label = match loop_clipboard::get() {
+                                Some(settings) => {
+                                    format!("Paste loop settings ({})", loop_clipboard::describe(&settings))
+                                }
+                                None => "Paste loop settings".to_string(),
+                            };
+                            let paste_hint = if is_persist_selected {
+                                "Apply to every selected track with a pending replacement"
+                            } else {
+                                "Apply to this track's pending replacement"
+                            };
+                            if ui
+                                .add_enabled(loop_clipboard::has_settings(), Button::new(paste_label))
+                                .on_hover_text(paste_hint)
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::PasteLoopSettings);
+                                ui.close();
+                            }
+                            ui.separator();
+                            if ui
+                                .button("Replace from URL...")
+                                .on_hover_text("Download the replacement audio from an http(s) link")
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::ReplaceFromUrl);
+                                ui.close();
+                            }
+                            if let Some(source) = ReplaceUtils::get_replacement_path(file) {
+                                let source = source.display().to_string();
+                                if ui
+                                    .button("Edit replacement settings...")
+                                    .on_hover_text("Change loop and gain; the replacement is made again from its source")
+                                    .clicked()
+                                {
+                                    on_row_menu(row_index, RowMenuAction::EditReplacement);
+                                    ui.close();
+                                }
+                                if ui
+                                    .button("Show replacement source in folder")
+                                    .on_hover_text(&source)
+                                    .clicked()
+                                {
+                                    on_row_menu(row_index, RowMenuAction::RevealReplacementSource);
+                                    ui.close();
+                                }
+                                if ui
+                                    .button("Open replacement source")
+                                    .on_hover_text(&source)
+                                    .clicked()
+                                {
+                                    on_row_menu(row_index, RowMenuAction::OpenReplacementSource);
+                                    ui.close();
+                                }
+                            }
+                            if ui
+                                .button("Mix...")
+                                .on_hover_text("Gain, normalize, fades and downmix of what the track plays now")
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::Mix);
+                                ui.close();
+                            }
+                            if ui
+                                .button("A/B blind test...")
+                                .on_hover_text("Compare the original and the pending replacement without knowing which is which")
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::BlindTest);
+                                ui.close();
+                            }
+                            if ui
+                                .button("Copy track")
+                                .on_hover_text("Copy the track, with its pending replacement, to paste into another open file")
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::CopyTrack);
+                                ui.close();
+                            }
+                            if ui
+                                .button("Duplicate...")
+                                .on_hover_text("Copy the track, with its settings, to a new track of this file")
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::Duplicate);
+                                ui.close();
+                            }
+                            if file.is_nus3bank {
+                                ui.menu_button("Track ID", |ui| {
+                                    if ui.button("Move Up").on_hover_text("Swap IDs with the track before this one").clicked() {
+                                        on_row_menu(row_index, RowMenuAction::MoveUp);
+                                        ui.close();
+                                    }
+                                    if ui.button("Move Down").on_hover_text("Swap IDs with the track after this one").clicked() {
+                                        on_row_menu(row_index, RowMenuAction::MoveDown);
+                                        ui.close();
+                                    }
+                                    if ui.button("Edit ID...").clicked() {
+                                        on_row_menu(row_index, RowMenuAction::EditId);
+                                        ui.close();
+                                    }
+                                });
+                            }
+                            let transcript_label = if transcripts.get(&track_key).is_some() {
+                                "Edit transcript..."
+                            } else {
+                                "Add transcript..."
+                            };
+                            if ui
+                                .button(transcript_label)
+                                .on_hover_text("Note what is said in this cue so the search box finds it")
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::EditTranscript);
+                                ui.close();
+                            }
+                            ui.menu_button("Color label", |ui| {
+                                let current = row_labels.get(&track_key).copied();
+                                for label in ColorLabel::ALL {
+                                    let text = RichText::new(format!("\u{25CF} {}", label.name())).color(label.color32());
+                                    let text = if current == Some(label) { text.strong() } else { text };
+                                    if ui.button(text).clicked() {
+                                        on_row_menu(row_index, RowMenuAction::SetColorLabel(Some(label)));
+                                        ui.close();
+                                    }
+                                }
+                                ui.separator();
+                                if ui.add_enabled(current.is_some(), Button::new("No label")).clicked() {
+                                    on_row_menu(row_index, RowMenuAction::SetColorLabel(None));
+                                    ui.close();
+                                }
+                            })
+                            .response
+                            .on_hover_text(if is_persist_selected {
+                                "Applies to every selected track"
+                            } else {
+                                "Applies to this track"
+                            });
+                            let locked = locked_rows.contains(&track_key);
+                            if ui
+                                .button(if locked { "Unlock" } else { "Lock" })
+                                .on_hover_text(if is_persist_selected {
+                                    "Applies to every selected track. Locked tracks are skipped by batch replace, clear, remove and normalize"
+                                } else {
+                                    "Locked tracks are skipped by batch replace, clear, remove and normalize"
+                                })
+                                .clicked()
+                            {
+                                on_row_menu(row_index, RowMenuAction::SetLocked(!locked));
+                                ui.close();
+                            }
+                            ui.menu_button("Assign to trigger pad", |ui| {
+                                for slot in 0..PAD_COUNT {
+                                    if ui.button(format!("Pad {}", slot + 1)).clicked() {
+                                        on_row_menu(row_index, RowMenuAction::AssignPad(slot));
+                                        ui.close();
+                                    }
+                                }
+                            });
+                        });
+
                         // Column 0: Checkbox (centered)
                         {
                             let mut checked = is_persist_selected;
@@ -382,20 +688,36 @@ impl TableRenderer {
                         // Column 1: Name - with text clipping
                         ui.scope(|ui| {
                             ui.style_mut().wrap_mode = Some(TextWrapMode::Truncate);
+                            let locked = locked_rows.contains(&track_key);
+                            let mut name = if locked {
+                                format!("{} {}", egui_phosphor::regular::LOCK, file.name)
+                            } else {
+                                file.name.clone()
+                            };
+                            if let Some(icon) = track_categories.icon(&file.name) {
+                                name = format!("{} {}", icon, name);
+                            }
                             let text = if is_now_playing {
                                 RichText::new(format!(
                                     "{} {}",
                                     egui_phosphor::regular::PLAY,
-                                    file.name
+                                    name
                                 ))
                                 .size(text_size)
                                 .color(now_playing_accent)
                                 .strong()
                             } else {
-                                RichText::new(&file.name).size(text_size)
+                                RichText::new(name).size(text_size)
+                            };
+                            let mut hover = match transcripts.get(&track_key) {
+                                Some(transcript) => format!("{}\n\n\u{201C}{}\u{201D}", file.name, transcript),
+                                None => file.name.clone(),
                             };
+                            if locked {
+                                hover.push_str("\n\nLocked: batch operations skip this track");
+                            }
                             ui.add_sized([col_width_name, row_height], egui::Label::new(text))
-                                .on_hover_text(&file.name);
+                                .on_hover_text(hover);
                         });
 
                         // Column 2: ID - with text clipping and ellipsis
@@ -412,13 +734,7 @@ impl TableRenderer {
                         });
 
                         // Column 3: Size
-                        let size_text = if file.size < 1024 {
-                            format!("{} B", file.size)
-                        } else if file.size < 1024 * 1024 {
-                            format!("{:.1} KB", file.size as f32 / 1024.0)
-                        } else {
-                            format!("{:.1} MB", file.size as f32 / (1024.0 * 1024.0))
-                        };
+                        let size_text = table_view::size_text(file.size);
 
                         ui.add_sized(
                             [col_width_size, row_height],
@@ -433,23 +749,59 @@ impl TableRenderer {
                                 .on_hover_text(&file.filename);
                         });
 
+                        // Labels column: label from the companion file
+                        if let Some(labels) = track_labels {
+                            ui.scope(|ui| {
+                                ui.style_mut().wrap_mode = Some(TextWrapMode::Truncate);
+                                match labels.label_for(&file.name, &file.id, file.hex_id.as_deref()) {
+                                    Some(label) => {
+                                        ui.add_sized(
+                                            [col_width_label, row_height],
+                                            egui::Label::new(RichText::new(label).size(text_size)),
+                                        )
+                                        .on_hover_text(label);
+                                    }
+                                    None => {
+                                        ui.add_sized(
+                                            [col_width_label, row_height],
+                                            egui::Label::new(RichText::new("-").size(text_size).weak()),
+                                        );
+                                    }
+                                }
+                            });
+                        }
+
                         // Column 5: Type
                         ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
 
-                        // Set different colors based on file type
-                        let type_text = match file.file_type.as_str() {
-                            "OPUS Audio" => RichText::new(&file.file_type)
-                                .size(text_size)
-                                .color(Color32::from_rgb(100, 200, 100)), // Green
-                            "IDSP Audio" => RichText::new(&file.file_type)
+                        // Color and decode hint come from the file type table
+                        let file_type = FileTypeRegistry::lookup(&file.file_type);
+                        let empty = empty_tracks.get(&track_key);
+                        let type_text = match empty {
+                            Some(kind) => RichText::new(format!("{} \u{2022} {}", file.file_type, kind.badge()))
                                 .size(text_size)
-                                .color(Color32::from_rgb(100, 150, 255)), // Blue
-                            _ => RichText::new(&file.file_type)
-                                .size(text_size)
-                                .color(Color32::from_rgb(200, 150, 100)), // Yellow/Brown
+                                .color(Color32::GRAY)
+                                .italics(),
+                            None => RichText::new(&file.file_type).size(text_size).color(file_type.color32()),
                         };
 
-                        ui.add_sized([col_width_type, row_height], egui::Label::new(type_text));
+                        ui.add_sized([col_width_type, row_height], egui::Label::new(type_text))
+                            .on_hover_text(match empty {
+                                Some(kind) => format!("{}\n{}", kind.describe(), file_type.decode.label()),
+                                None => file_type.decode.label().to_string(),
+                            });
+
+                        // Metadata columns, from the payload header
+                        let format = track_formats.get(&track_key);
+                        for column in metadata_columns {
+                            let response = ui.add_sized(
+                                [col_width_meta, row_height],
+                                egui::Label::new(RichText::new(column.cell(format)).size(text_size)),
+                            );
+                            if let Some(hover) = column.hover(format) {
+                                response.on_hover_text(hover);
+                            }
+                        }
                         
                         // Column 6: Actions - responsive buttons with overflow menu, centered in the cell
                         let (_id, cell_rect) = ui.allocate_space(Vec2::new(col_action, row_height));
@@ -471,7 +823,13 @@ impl TableRenderer {
                                                     .size(text_size)
                                                     .color(Color32::from_rgb(100, 255, 150)),
                                             );
-                                            if ui.add(play_btn).on_hover_text("Play").clicked() {
+                                            let play_hover = unplayable.get(&track_key).copied().unwrap_or("Play");
+                                            if ui
+                                                .add_enabled(!unplayable.contains_key(&track_key), play_btn)
+                                                .on_hover_text(play_hover)
+                                                .on_disabled_hover_text(play_hover)
+                                                .clicked()
+                                            {
                                                 on_play_clicked(row_index);
                                             }
 
@@ -520,13 +878,18 @@ impl TableRenderer {
 
                                         // Always show Play as icon-only (highest priority)
                                         add_spacing(button_ui);
-                                        let play_button = button_ui.add(
+                                        let play_button = button_ui.add_enabled(
+                                            !unplayable.contains_key(&track_key),
                                             Button::new(
                                                 RichText::new(egui_phosphor::regular::PLAY.to_string())
                                                     .size(text_size)
                                                     .color(Color32::from_rgb(100, 255, 150)),
                                             ),
                                         );
+                                        let play_button = match unplayable.get(&track_key) {
+                                            Some(reason) => play_button.on_disabled_hover_text(*reason),
+                                            None => play_button,
+                                        };
                                         if play_button.clicked() {
                                             on_play_clicked(row_index);
                                         }
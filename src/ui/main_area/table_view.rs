@@ -0,0 +1,109 @@
+use std::fs;
+
+use super::audio_file_info::AudioFileInfo;
+use crate::labels::LabelFile;
+
+/// Size as shown in the table's Size column
+pub fn size_text(size: usize) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f32 / 1024.0)
+    } else {
+        format!("{:.1} MB", size as f32 / (1024.0 * 1024.0))
+    }
+}
+
+/// The table as it is on screen: the filtered rows in their current order, with the columns
+/// the table shows and cells formatted the same way. Unlike Bank Statistics it carries no
+/// decoded metadata, so it is cheap to build for sharing in issue reports or spreadsheets.
+pub struct TableView {
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl TableView {
+    /// `labels` adds the Labels column, like the table does while a label file is loaded
+    pub fn of(files: &[AudioFileInfo], labels: Option<&LabelFile>) -> Self {
+        let mut headers = vec!["Name", "ID", "Size", "Filename"];
+        if labels.is_some() {
+            headers.push("Labels");
+        }
+        headers.push("Type");
+
+        let rows = files
+            .iter()
+            .map(|file| {
+                let mut row = vec![file.name.clone(), file.id.clone(), size_text(file.size), file.filename.clone()];
+                if let Some(labels) = labels {
+                    let label = labels.label_for(&file.name, &file.id, file.hex_id.as_deref());
+                    row.push(label.unwrap_or_default().to_string());
+                }
+                row.push(file.file_type.clone());
+                row
+            })
+            .collect();
+        Self { headers, rows }
+    }
+
+    /// Tab-separated text, which pastes into spreadsheets as cells and reads fine in an issue
+    pub fn to_tsv(&self) -> String {
+        let clean = |cell: &str| cell.replace(['\t', '\r', '\n'], " ");
+        let mut text = self.headers.join("\t");
+        text.push('\n');
+        for row in &self.rows {
+            text.push_str(&row.iter().map(|c| clean(c)).collect::<Vec<_>>().join("\t"));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// RFC 4180 CSV; cells with commas, quotes or line breaks are quoted
+    pub fn to_csv(&self) -> String {
+        let quote = |cell: &str| {
+            if cell.contains([',', '"', '\r', '\n']) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.to_string()
+            }
+        };
+        let mut text = self.headers.join(",");
+        text.push_str("\r\n");
+        for row in &self.rows {
+            text.push_str(&row.iter().map(|c| quote(c)).collect::<Vec<_>>().join(","));
+            text.push_str("\r\n");
+        }
+        text
+    }
+
+    pub fn write_csv(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_csv()).map_err(|e| format!("Failed to write {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn view_keeps_row_order_and_quotes_csv_cells() {
+        let mut first = AudioFileInfo::from_nus3audio("vc_b".to_string(), "1".to_string(), 2048, "vc_b.lopus".to_string(), "OPUS".to_string());
+        first.hex_id = Some("0x1".to_string());
+        let second = AudioFileInfo::from_nus3audio("se, \"hit\"".to_string(), "0".to_string(), 10, "se.idsp".to_string(), "IDSP".to_string());
+        let files = vec![first, second];
+
+        let view = TableView::of(&files, None);
+        assert_eq!(view.headers, vec!["Name", "ID", "Size", "Filename", "Type"]);
+        assert_eq!(
+            view.to_csv(),
+            "Name,ID,Size,Filename,Type\r\nvc_b,1,2.0 KB,vc_b.lopus,OPUS\r\n\"se, \"\"hit\"\"\",0,10 B,se.idsp,IDSP\r\n"
+        );
+        assert_eq!(view.to_tsv().lines().nth(2), Some("se, \"hit\"\t0\t10 B\tse.idsp\tIDSP"));
+
+        let labels = LabelFile::parse("0x1 = Line one\n", "bank.tonelabel").unwrap();
+        let view = TableView::of(&files, Some(&labels));
+        assert_eq!(view.headers[4], "Labels");
+        assert_eq!(view.rows[0][4], "Line one");
+        assert_eq!(view.rows[1][4], "");
+    }
+}
@@ -1,5 +1,11 @@
 use egui::Color32;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Toasts of the same color added within this time of each other are merged into one
+pub const COALESCE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Most toasts on screen at once; older ones are dropped (they stay in the notification center)
+pub const MAX_VISIBLE_TOASTS: usize = 4;
 
 /// Toast notification message
 #[derive(Clone)]
@@ -7,20 +13,93 @@ pub struct ToastMessage {
     pub message: String,
     pub expires_at: Instant,
     pub color: Color32,
+    /// Messages merged into this toast, oldest first; a single entry is shown as is
+    pub details: Vec<String>,
+    // When the last message was merged in
+    updated_at: Instant,
 }
 
 impl ToastMessage {
     /// Create a new toast message
     pub fn new(message: String, color: Color32, duration_secs: u64) -> Self {
+        let now = Instant::now();
         Self {
+            details: vec![message.clone()],
             message,
-            expires_at: Instant::now() + std::time::Duration::from_secs(duration_secs),
+            expires_at: now + Duration::from_secs(duration_secs),
             color,
+            updated_at: now,
         }
     }
-    
+
     /// Check if the toast message has expired
     pub fn has_expired(&self) -> bool {
         self.expires_at <= Instant::now()
     }
+
+    /// Whether this toast stands for several messages
+    pub fn is_summary(&self) -> bool {
+        self.details.len() > 1
+    }
+
+    fn merge(&mut self, message: String, duration: Duration, now: Instant) {
+        self.details.push(message);
+        let count = self.details.len();
+        self.message = if self.color == Color32::RED {
+            format!("{} failed \u{2014} view details", count)
+        } else if self.color == Color32::GOLD {
+            format!("{} warnings \u{2014} view details", count)
+        } else {
+            format!("{} notifications \u{2014} view details", count)
+        };
+        // Keep a summary up a little longer than a single toast, it is easy to miss
+        self.expires_at = self.expires_at.max(now + duration * 2);
+        self.updated_at = now;
+    }
+}
+
+/// Add a toast, merging it into the newest toast of the same color when that one was
+/// updated within `COALESCE_WINDOW`, so a batch that fails item by item shows one summary
+pub fn push_toast(toasts: &mut Vec<ToastMessage>, message: String, color: Color32, duration_secs: u64) {
+    let now = Instant::now();
+    let recent = toasts
+        .iter_mut()
+        .rev()
+        .find(|t| t.color == color && !t.has_expired() && now.duration_since(t.updated_at) <= COALESCE_WINDOW);
+    match recent {
+        Some(toast) => toast.merge(message, Duration::from_secs(duration_secs), now),
+        None => toasts.push(ToastMessage::new(message, color, duration_secs)),
+    }
+    if toasts.len() > MAX_VISIBLE_TOASTS {
+        toasts.drain(..toasts.len() - MAX_VISIBLE_TOASTS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_failures_are_coalesced_per_color() {
+        let mut toasts = Vec::new();
+        push_toast(&mut toasts, "Exported 3 files".to_string(), Color32::GREEN, 3);
+        for i in 0..12 {
+            push_toast(&mut toasts, format!("Failed to replace track_{}", i), Color32::RED, 3);
+        }
+        push_toast(&mut toasts, "One warning".to_string(), Color32::GOLD, 3);
+
+        assert_eq!(toasts.len(), 3);
+        assert_eq!(toasts[0].message, "Exported 3 files");
+        assert!(!toasts[0].is_summary());
+        assert_eq!(toasts[1].message, "12 failed \u{2014} view details");
+        assert_eq!(toasts[1].details.len(), 12);
+        assert_eq!(toasts[1].details[11], "Failed to replace track_11");
+        assert_eq!(toasts[2].message, "One warning");
+
+        for i in 0..6 {
+            let color = if i % 2 == 0 { Color32::BLUE } else { Color32::WHITE };
+            push_toast(&mut toasts, format!("{}", i), color, 3);
+        }
+        assert_eq!(toasts.len(), MAX_VISIBLE_TOASTS);
+    }
 }
@@ -0,0 +1,139 @@
+use egui::{Color32, Context, RichText, TextEdit, Window};
+
+use super::audio_file_info::AudioFileInfo;
+
+/// Reads an ID typed as hex ("0x1a") or decimal
+pub fn parse_tone_id(text: &str) -> Option<usize> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Assigns a NUS3BANK track another ID, that is another slot in the TONE list. The track
+/// holding the ID already gets the old ID of the edited one.
+pub struct ToneIdModal {
+    pub open: bool,
+    track: Option<AudioFileInfo>,
+    current: usize,
+    /// Name of the tone in each slot, in the pending order
+    slots: Vec<String>,
+    input: String,
+    saved: Option<(AudioFileInfo, usize)>,
+}
+
+impl Default for ToneIdModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToneIdModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            track: None,
+            current: 0,
+            slots: Vec::new(),
+            input: String::new(),
+            saved: None,
+        }
+    }
+
+    pub fn open_with(&mut self, track: &AudioFileInfo, current: usize, slots: Vec<String>) {
+        self.track = Some(track.clone());
+        self.current = current;
+        self.slots = slots;
+        self.input = format!("0x{:x}", current);
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Track and the ID it was given
+    pub fn take_saved(&mut self) -> Option<(AudioFileInfo, usize)> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        let Some(track) = self.track.clone() else {
+            self.open = false;
+            return;
+        };
+
+        let mut open = self.open;
+        let mut close = false;
+        Window::new("Edit ID")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.label(format!("{} is ID 0x{:x} ({})", track.name, self.current, self.current));
+                ui.horizontal(|ui| {
+                    ui.label("New ID:");
+                    ui.add(TextEdit::singleline(&mut self.input).desired_width(100.0));
+                });
+
+                let target = parse_tone_id(&self.input);
+                let valid = match target {
+                    None => {
+                        ui.colored_label(Color32::RED, "Enter a hex (0x1a) or decimal ID");
+                        false
+                    }
+                    Some(id) if id >= self.slots.len() => {
+                        ui.colored_label(
+                            Color32::RED,
+                            format!("IDs run from 0x0 to 0x{:x}; the bank has no free IDs past its tones", self.slots.len() - 1),
+                        );
+                        false
+                    }
+                    Some(id) if id == self.current => false,
+                    Some(id) => {
+                        ui.colored_label(
+                            Color32::GOLD,
+                            format!("ID 0x{:x} is used by {}, which gets 0x{:x} instead", id, self.slots[id], self.current),
+                        );
+                        true
+                    }
+                };
+                ui.label(
+                    RichText::new("A track's ID is its place in the TONE list; the game finds sounds by it. The new IDs are written on save.")
+                        .size(11.0)
+                        .weak(),
+                );
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(valid, egui::Button::new("Swap IDs")).clicked() {
+                        if let Some(id) = target {
+                            self.saved = Some((track.clone(), id));
+                        }
+                        close = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        close = true;
+                    }
+                });
+            });
+
+        if !open || close {
+            self.open = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_hex_and_decimal_ids() {
+        assert_eq!(parse_tone_id("0x1a"), Some(26));
+        assert_eq!(parse_tone_id(" 26 "), Some(26));
+        assert_eq!(parse_tone_id("0XFF"), Some(255));
+        assert_eq!(parse_tone_id("1a"), None);
+    }
+}
@@ -0,0 +1,133 @@
+use egui_phosphor::regular;
+use serde::{Deserialize, Serialize};
+
+/// Kind of sound a track holds, inferred from its name for the icon in the Name column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrackCategory {
+    Voice,
+    Bgm,
+    Se,
+    Jingle,
+}
+
+impl TrackCategory {
+    pub const ALL: [TrackCategory; 4] = [
+        TrackCategory::Voice,
+        TrackCategory::Bgm,
+        TrackCategory::Se,
+        TrackCategory::Jingle,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrackCategory::Voice => "Voice",
+            TrackCategory::Bgm => "BGM",
+            TrackCategory::Se => "SE",
+            TrackCategory::Jingle => "Jingle",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            TrackCategory::Voice => regular::MICROPHONE,
+            TrackCategory::Bgm => regular::MUSIC_NOTES,
+            TrackCategory::Se => regular::LIGHTNING,
+            TrackCategory::Jingle => regular::BELL,
+        }
+    }
+}
+
+/// Track names matching `pattern` belong to `category`. The pattern is matched without
+/// case; a `*` at either end leaves that end open, so "vo_*" is a prefix, "*_vo_*" a
+/// substring and "*_bgm" a suffix. A pattern without `*` must match the whole name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryRule {
+    pub pattern: String,
+    pub category: TrackCategory,
+}
+
+impl CategoryRule {
+    pub fn new(pattern: &str, category: TrackCategory) -> Self {
+        Self { pattern: pattern.to_string(), category }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let pattern = self.pattern.trim().to_lowercase();
+        let name = name.to_lowercase();
+        let open_start = pattern.starts_with('*');
+        let open_end = pattern.len() > 1 && pattern.ends_with('*');
+        let core = pattern.trim_matches('*');
+        if core.is_empty() {
+            return false;
+        }
+        match (open_start, open_end) {
+            (true, true) => name.contains(core),
+            (false, true) => name.starts_with(core),
+            (true, false) => name.ends_with(core),
+            (false, false) => name == core,
+        }
+    }
+}
+
+/// Category icons of the Name column, edited in Settings > Track Icons. Rules are tried in
+/// order and the first match wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TrackCategories {
+    pub enabled: bool,
+    pub rules: Vec<CategoryRule>,
+}
+
+impl Default for TrackCategories {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: vec![
+                CategoryRule::new("*bgm*", TrackCategory::Bgm),
+                CategoryRule::new("*jingle*", TrackCategory::Jingle),
+                CategoryRule::new("jg_*", TrackCategory::Jingle),
+                CategoryRule::new("vo_*", TrackCategory::Voice),
+                CategoryRule::new("vc_*", TrackCategory::Voice),
+                CategoryRule::new("voice*", TrackCategory::Voice),
+                CategoryRule::new("*_vo_*", TrackCategory::Voice),
+                CategoryRule::new("*_vc_*", TrackCategory::Voice),
+                CategoryRule::new("se_*", TrackCategory::Se),
+                CategoryRule::new("*_se_*", TrackCategory::Se),
+            ],
+        }
+    }
+}
+
+impl TrackCategories {
+    pub fn category(&self, name: &str) -> Option<TrackCategory> {
+        self.rules.iter().find(|rule| rule.matches(name)).map(|rule| rule.category)
+    }
+
+    /// Icon to put before a track name, if icons are on and a rule matches
+    pub fn icon(&self, name: &str) -> Option<&'static str> {
+        if !self.enabled {
+            return None;
+        }
+        self.category(name).map(|category| category.icon())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_matching_rule_picks_the_category() {
+        let categories = TrackCategories::default();
+        assert_eq!(categories.category("BGM_Stage01"), Some(TrackCategory::Bgm));
+        assert_eq!(categories.category("jg_win_bgm"), Some(TrackCategory::Bgm));
+        assert_eq!(categories.category("JG_Lose"), Some(TrackCategory::Jingle));
+        assert_eq!(categories.category("rx78_vc_attack"), Some(TrackCategory::Voice));
+        assert_eq!(categories.category("se_beam_rifle"), Some(TrackCategory::Se));
+        assert_eq!(categories.category("footstep"), None);
+
+        assert!(CategoryRule::new("*_hit", TrackCategory::Se).matches("beam_HIT"));
+        assert!(!CategoryRule::new("hit", TrackCategory::Se).matches("beam_hit"));
+        assert!(!CategoryRule::new("*", TrackCategory::Se).matches("anything"));
+    }
+}
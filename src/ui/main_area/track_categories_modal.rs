@@ -0,0 +1,103 @@
+use egui::{ComboBox, Context, Grid, RichText, TextEdit, Window};
+use egui_phosphor::regular;
+
+use super::track_categories::{CategoryRule, TrackCategories, TrackCategory};
+
+/// Edits the name patterns that pick the category icon of each track
+pub struct TrackCategoriesModal {
+    pub open: bool,
+    draft: TrackCategories,
+    saved: Option<TrackCategories>,
+}
+
+impl Default for TrackCategoriesModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrackCategoriesModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            draft: TrackCategories::default(),
+            saved: None,
+        }
+    }
+
+    pub fn open_with(&mut self, categories: &TrackCategories) {
+        self.draft = categories.clone();
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Settings edited since the last call
+    pub fn take_saved(&mut self) -> Option<TrackCategories> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut changed = false;
+        let mut open = self.open;
+        Window::new("Track Icons")
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                changed |= ui.checkbox(&mut self.draft.enabled, "Show category icons in the Name column").changed();
+                ui.label(
+                    RichText::new("Rules are tried top to bottom; the first pattern that matches the name wins. Use * for an open end, e.g. vo_* or *_bgm.")
+                        .size(11.0)
+                        .weak(),
+                );
+                ui.add_space(6.0);
+
+                let mut remove = None;
+                Grid::new("track_categories_grid").num_columns(3).striped(true).spacing([8.0, 4.0]).show(ui, |ui| {
+                    for (index, rule) in self.draft.rules.iter_mut().enumerate() {
+                        changed |= ui.add(TextEdit::singleline(&mut rule.pattern).desired_width(160.0)).changed();
+                        ComboBox::from_id_salt(("track_category", index))
+                            .selected_text(format!("{} {}", rule.category.icon(), rule.category.name()))
+                            .show_ui(ui, |ui| {
+                                for category in TrackCategory::ALL {
+                                    let text = format!("{} {}", category.icon(), category.name());
+                                    changed |= ui.selectable_value(&mut rule.category, category, text).changed();
+                                }
+                            });
+                        if ui.small_button(regular::X.to_string()).on_hover_text("Remove rule").clicked() {
+                            remove = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(index) = remove {
+                    self.draft.rules.remove(index);
+                    changed = true;
+                }
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("{} Add Rule", regular::PLUS)).clicked() {
+                        self.draft.rules.push(CategoryRule::new("", TrackCategory::Se));
+                        changed = true;
+                    }
+                    if ui.button("Reset to Defaults").clicked() {
+                        self.draft = TrackCategories::default();
+                        changed = true;
+                    }
+                });
+            });
+
+        if changed {
+            self.saved = Some(self.draft.clone());
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,296 @@
+use serde::{Deserialize, Serialize};
+
+use super::sort_column::SortColumn;
+use crate::wav::riff::Riff;
+
+/// Audio format read from a payload header, without decoding it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Bits per sample of PCM data; `None` for compressed codecs
+    pub bits_per_sample: Option<u16>,
+    pub duration_secs: Option<f64>,
+    /// Loop start and last looped frame in seconds, from a WAV `smpl` chunk or OPUS header
+    pub loop_secs: Option<(f64, f64)>,
+    /// Shannon entropy of the payload bytes, 0 to 8 bits per byte
+    pub entropy_bits: f32,
+    pub payload_bytes: usize,
+}
+
+/// Entropy above which a payload is taken to be coded already; PCM rarely gets past 7
+const COMPRESSED_ENTROPY_BITS: f32 = 7.5;
+
+/// Shannon entropy of `data` in bits per byte
+pub fn byte_entropy(data: &[u8]) -> f32 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let total = data.len() as f64;
+    let bits: f64 = counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total;
+            -p * p.log2()
+        })
+        .sum();
+    bits as f32
+}
+
+impl TrackFormat {
+    /// Size of the track as 16-bit WAV relative to the payload, from the header's duration:
+    /// about 1 for PCM and several times that for compressed codecs
+    pub fn wav_size_ratio(&self) -> Option<f64> {
+        let secs = self.duration_secs?;
+        let pcm_bytes = secs * self.sample_rate as f64 * self.channels as f64 * 2.0;
+        (self.payload_bytes > 0 && pcm_bytes > 0.0).then(|| pcm_bytes / self.payload_bytes as f64)
+    }
+
+    /// Whether the payload bytes look coded already rather than raw PCM
+    pub fn looks_compressed(&self) -> bool {
+        self.entropy_bits >= COMPRESSED_ENTROPY_BITS
+    }
+
+    /// The loop scaled onto a source of `duration_secs`, keeping where it falls relative to
+    /// the length. A new arrangement of the same song usually keeps its structure, so this
+    /// is a sensible first loop to fine-tune.
+    pub fn loop_mapped_onto(&self, duration_secs: f64) -> Option<(f64, f64)> {
+        let (start, end) = self.loop_secs?;
+        let original = self.duration_secs.filter(|d| *d > 0.0)?;
+        if duration_secs <= 0.0 {
+            return None;
+        }
+        let scale = duration_secs / original;
+        Some(((start * scale).min(duration_secs), (end * scale).min(duration_secs)))
+    }
+}
+
+/// Read the format from a WAV `fmt ` chunk, a Namco OPUS header or a Namco IDSP header
+pub fn parse(data: &[u8]) -> Option<TrackFormat> {
+    let format = if data.starts_with(b"RIFF") {
+        parse_wav(data)
+    } else if data.starts_with(b"OPUS") {
+        parse_opus(data)
+    } else if data.starts_with(b"IDSP") {
+        parse_idsp(data)
+    } else {
+        None
+    }?;
+    Some(TrackFormat { entropy_bits: byte_entropy(data), payload_bytes: data.len(), ..format })
+}
+
+fn parse_wav(data: &[u8]) -> Option<TrackFormat> {
+    let riff = Riff::parse_wav(data).ok()?;
+    let fmt = riff.fmt()?;
+    let data_len = riff.get(b"data").map(|c| c.data.len());
+    let sample_rate = fmt.sample_rate;
+    let bits = fmt.bits_per_sample;
+    Some(TrackFormat {
+        sample_rate,
+        channels: fmt.channels,
+        // PCM and IEEE float, plus the extensible header that wraps them
+        bits_per_sample: (matches!(fmt.tag, 1 | 3 | 0xFFFE) && bits > 0).then_some(bits),
+        duration_secs: data_len
+            .filter(|_| fmt.byte_rate > 0)
+            .map(|len| len as f64 / fmt.byte_rate as f64),
+        loop_secs: riff
+            .smpl_loop()
+            .filter(|_| sample_rate > 0)
+            .map(|lp| (lp.start as f64 / sample_rate as f64, lp.end as f64 / sample_rate as f64)),
+        entropy_bits: 0.0,
+        payload_bytes: 0,
+    })
+}
+
+/// The Namco header keeps the sample count at 0x08 and the loop start and end at 0x14 and
+/// 0x18; channels and rate come from the Switch Opus stream header that follows it
+fn parse_opus(data: &[u8]) -> Option<TrackFormat> {
+    let read_le = |at: usize| data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+    let stream = (4..data.len().min(0x100))
+        .step_by(4)
+        .find(|&at| read_le(at) == Some(0x8000_0001))?;
+    let channels = *data.get(stream + 9)? as u16;
+    let sample_rate = read_le(stream + 0x0c)?;
+    let read_be = |at: usize| data.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let samples = read_be(0x08)?;
+    let loop_frames = read_be(0x14).zip(read_be(0x18)).filter(|&(start, end)| end > start);
+    Some(TrackFormat {
+        sample_rate,
+        channels,
+        bits_per_sample: None,
+        duration_secs: (samples > 0 && sample_rate > 0).then(|| samples as f64 / sample_rate as f64),
+        loop_secs: loop_frames
+            .filter(|_| sample_rate > 0)
+            .map(|(start, end)| (start as f64 / sample_rate as f64, end as f64 / sample_rate as f64)),
+        entropy_bits: 0.0,
+        payload_bytes: 0,
+    })
+}
+
+/// Big-endian channels, sample rate and sample count at 0x08, 0x0C and 0x10
+fn parse_idsp(data: &[u8]) -> Option<TrackFormat> {
+    let read_be = |at: usize| data.get(at..at + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let channels = read_be(0x08)?;
+    let sample_rate = read_be(0x0C)?;
+    let samples = read_be(0x10)?;
+    if channels == 0 || channels > 8 || sample_rate == 0 {
+        return None;
+    }
+    Some(TrackFormat {
+        sample_rate,
+        channels: channels as u16,
+        bits_per_sample: None,
+        duration_secs: Some(samples as f64 / sample_rate as f64),
+        loop_secs: None,
+        entropy_bits: 0.0,
+        payload_bytes: 0,
+    })
+}
+
+/// Optional table columns filled from `TrackFormat`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MetadataColumn {
+    SampleRate,
+    Channels,
+    BitDepth,
+    Duration,
+    Entropy,
+    WavRatio,
+}
+
+impl MetadataColumn {
+    pub const ALL: [MetadataColumn; 6] = [
+        MetadataColumn::SampleRate,
+        MetadataColumn::Channels,
+        MetadataColumn::BitDepth,
+        MetadataColumn::Duration,
+        MetadataColumn::Entropy,
+        MetadataColumn::WavRatio,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MetadataColumn::SampleRate => "Rate",
+            MetadataColumn::Channels => "Ch",
+            MetadataColumn::BitDepth => "Bits",
+            MetadataColumn::Duration => "Duration",
+            MetadataColumn::Entropy => "Entropy",
+            MetadataColumn::WavRatio => "As WAV",
+        }
+    }
+
+    /// Name in the column chooser
+    pub fn long_name(&self) -> &'static str {
+        match self {
+            MetadataColumn::SampleRate => "Sample rate",
+            MetadataColumn::Channels => "Channels",
+            MetadataColumn::BitDepth => "Bit depth",
+            MetadataColumn::Duration => "Duration",
+            MetadataColumn::Entropy => "Byte entropy",
+            MetadataColumn::WavRatio => "Size as WAV",
+        }
+    }
+
+    pub fn sort_column(&self) -> SortColumn {
+        match self {
+            MetadataColumn::SampleRate => SortColumn::SampleRate,
+            MetadataColumn::Channels => SortColumn::Channels,
+            MetadataColumn::BitDepth => SortColumn::BitDepth,
+            MetadataColumn::Duration => SortColumn::Duration,
+            MetadataColumn::Entropy => SortColumn::Entropy,
+            MetadataColumn::WavRatio => SortColumn::WavRatio,
+        }
+    }
+
+    /// Cell text; "-" where the header does not say
+    pub fn cell(&self, format: Option<&TrackFormat>) -> String {
+        let text = format.and_then(|f| match self {
+            MetadataColumn::SampleRate => Some(format!("{} Hz", f.sample_rate)),
+            MetadataColumn::Channels => Some(f.channels.to_string()),
+            MetadataColumn::BitDepth => f.bits_per_sample.map(|b| b.to_string()),
+            MetadataColumn::Duration => f.duration_secs.map(|secs| {
+                let tenths = (secs * 10.0).round() as u64;
+                format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+            }),
+            MetadataColumn::Entropy => Some(format!("{:.2}", f.entropy_bits)),
+            MetadataColumn::WavRatio => f.wav_size_ratio().map(|ratio| format!("{:.1}x", ratio)),
+        });
+        text.unwrap_or_else(|| "-".to_string())
+    }
+
+    /// Explanation shown over a cell, for the columns that need one
+    pub fn hover(&self, format: Option<&TrackFormat>) -> Option<String> {
+        let f = format?;
+        match self {
+            MetadataColumn::Entropy => Some(format!(
+                "{:.2} bits per byte: {}",
+                f.entropy_bits,
+                if f.looks_compressed() { "compressed codec data" } else { "raw PCM or other uncompressed data" }
+            )),
+            MetadataColumn::WavRatio => f.wav_size_ratio().map(|ratio| {
+                format!("About {:.1} times the current size when converted to 16-bit WAV", ratio)
+            }),
+            _ => None,
+        }
+    }
+
+    /// Value the column sorts by; rows without one sort first
+    pub fn sort_key(&self, format: Option<&TrackFormat>) -> f64 {
+        format
+            .and_then(|f| match self {
+                MetadataColumn::SampleRate => Some(f.sample_rate as f64),
+                MetadataColumn::Channels => Some(f.channels as f64),
+                MetadataColumn::BitDepth => f.bits_per_sample.map(f64::from),
+                MetadataColumn::Duration => f.duration_secs,
+                MetadataColumn::Entropy => Some(f.entropy_bits as f64),
+                MetadataColumn::WavRatio => f.wav_size_ratio(),
+            })
+            .unwrap_or(-1.0)
+    }
+
+    /// Column a sort column belongs to, for the sortable metadata columns
+    pub fn of_sort_column(sort_column: SortColumn) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.sort_column() == sort_column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_wav_and_idsp_headers() {
+        let mut wav = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        wav.extend_from_slice(&[1, 0, 2, 0]);
+        wav.extend_from_slice(&44100u32.to_le_bytes());
+        wav.extend_from_slice(&(44100u32 * 4).to_le_bytes());
+        wav.extend_from_slice(&[4, 0, 16, 0]);
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(44100u32 * 4).to_le_bytes());
+        wav.extend(std::iter::repeat_n(0u8, 44100 * 4));
+        let format = parse(&wav).unwrap();
+        assert_eq!((format.sample_rate, format.channels, format.bits_per_sample), (44100, 2, Some(16)));
+        assert_eq!(MetadataColumn::Duration.cell(Some(&format)), "0:01.0");
+
+        let mut idsp = b"IDSP\0\0\0\0".to_vec();
+        for value in [1u32, 32000, 96000] {
+            idsp.extend_from_slice(&value.to_be_bytes());
+        }
+        let format = parse(&idsp).unwrap();
+        assert_eq!(MetadataColumn::Duration.cell(Some(&format)), "0:03.0");
+        assert_eq!(MetadataColumn::BitDepth.cell(Some(&format)), "-");
+        assert_eq!(MetadataColumn::WavRatio.cell(Some(&format)), "9600.0x");
+        assert!(!format.looks_compressed());
+        assert_eq!(byte_entropy(&[7; 64]), 0.0);
+        assert_eq!(byte_entropy(&(0..=255).collect::<Vec<u8>>()), 8.0);
+
+        let looped = TrackFormat { loop_secs: Some((12.0, 90.0)), duration_secs: Some(120.0), ..format };
+        assert_eq!(looped.loop_mapped_onto(60.0), Some((6.0, 45.0)));
+        assert_eq!(format.loop_mapped_onto(60.0), None);
+    }
+}
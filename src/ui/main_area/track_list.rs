@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::audio_file_info::AudioFileInfo;
+use super::track_format::TrackFormat;
+
+/// Pending edit of a listed track
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackStatus {
+    Unchanged,
+    Replaced,
+    Added,
+}
+
+impl TrackStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TrackStatus::Unchanged => "unchanged",
+            TrackStatus::Replaced => "replaced",
+            TrackStatus::Added => "added",
+        }
+    }
+}
+
+/// One row of the track table as exported
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct TrackListRow {
+    pub name: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hex_id: Option<String>,
+    pub size: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_secs: Option<f64>,
+    pub codec: String,
+    /// Loop start and last looped frame in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_start: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_end: Option<f64>,
+    pub status: TrackStatus,
+}
+
+impl TrackListRow {
+    pub fn new(info: &AudioFileInfo, format: Option<&TrackFormat>, status: TrackStatus) -> Self {
+        let loop_secs = format.and_then(|f| f.loop_secs);
+        Self {
+            name: info.name.clone(),
+            id: info.id.clone(),
+            hex_id: info.hex_id.clone(),
+            size: info.size,
+            duration_secs: format.and_then(|f| f.duration_secs),
+            codec: info.file_type.clone(),
+            loop_start: loop_secs.map(|(start, _)| start),
+            loop_end: loop_secs.map(|(_, end)| end),
+            status,
+        }
+    }
+}
+
+/// The tracks of one file as the table shows them, to document a mod or diff two versions.
+/// Written as JSON, or CSV for spreadsheets.
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct TrackList {
+    pub file: PathBuf,
+    pub tracks: Vec<TrackListRow>,
+}
+
+impl TrackList {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to encode the track list: {}", e))
+    }
+
+    /// Rows as CSV with a header row; the file is not included
+    pub fn to_csv(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+        let number = |v: Option<f64>| v.map(|v| format!("{:.3}", v)).unwrap_or_default();
+        let mut out = String::from("name,id,hex_id,size,duration_secs,codec,loop_start,loop_end,status\n");
+        for t in &self.tracks {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                quote(&t.name),
+                quote(&t.id),
+                t.hex_id.as_deref().unwrap_or_default(),
+                t.size,
+                number(t.duration_secs),
+                quote(&t.codec),
+                number(t.loop_start),
+                number(t.loop_end),
+                t.status.name(),
+            ));
+        }
+        out
+    }
+
+    /// Write as CSV when `path` ends in `.csv`, JSON otherwise
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let is_csv = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        let text = if is_csv { self.to_csv() } else { self.to_json()? };
+        fs::write(path, text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_tracks_with_format_and_status() {
+        let info = AudioFileInfo::from_nus3bank_track("bgm \"a\"".to_string(), 0, "0x0".to_string(), 1024, "bgm.wav".to_string());
+        let format = TrackFormat {
+            sample_rate: 48000,
+            channels: 2,
+            bits_per_sample: Some(16),
+            duration_secs: Some(2.5),
+            loop_secs: Some((0.5, 2.0)),
+            entropy_bits: 6.0,
+            payload_bytes: 1024,
+        };
+        let list = TrackList {
+            file: PathBuf::from("bgm.nus3bank"),
+            tracks: vec![
+                TrackListRow::new(&info, Some(&format), TrackStatus::Replaced),
+                TrackListRow::new(&info, None, TrackStatus::Unchanged),
+            ],
+        };
+
+        let csv = list.to_csv();
+        assert_eq!(csv.lines().nth(1), Some("\"bgm \"\"a\"\"\",\"0\",0x0,1024,2.500,\"WAV\",0.500,2.000,replaced"));
+        assert_eq!(csv.lines().nth(2), Some("\"bgm \"\"a\"\"\",\"0\",0x0,1024,,\"WAV\",,,unchanged"));
+        let json = list.to_json().unwrap();
+        assert_eq!(serde_json::from_str::<TrackList>(&json).unwrap(), list);
+    }
+}
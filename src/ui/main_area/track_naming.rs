@@ -0,0 +1,133 @@
+use egui::Ui;
+use std::path::Path;
+
+use super::wav_metadata;
+
+/// What the name of an added track is derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Deserialize, serde::Serialize)]
+pub enum NameSource {
+    #[default]
+    FileName,
+    /// The title tag, or the file name when there is none
+    Title,
+}
+
+impl NameSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NameSource::FileName => "File name",
+            NameSource::Title => "Title tag",
+        }
+    }
+}
+
+/// How names of added tracks are derived from their source files
+#[derive(Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct NamingRule {
+    /// Off: the file name is used as is
+    pub enabled: bool,
+    pub source: NameSource,
+    pub lowercase: bool,
+    /// Spaces and dashes become single underscores, as in the game's cue names
+    pub underscores: bool,
+    pub prefix: String,
+}
+
+/// Title tag of a source file, if it has one
+pub fn source_title(path: &Path) -> Option<String> {
+    let is_wav = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+    if is_wav {
+        return wav_metadata::read_title(&std::fs::read(path).ok()?);
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    return crate::audio_processing::decode::read_title(path);
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
+impl NamingRule {
+    /// Name for a track added from `path`
+    pub fn name_for(&self, path: &Path) -> String {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        if !self.enabled {
+            return stem;
+        }
+        let raw = match self.source {
+            NameSource::FileName => stem,
+            NameSource::Title => source_title(path).unwrap_or(stem),
+        };
+        self.transform(&raw)
+    }
+
+    /// Apply the case, separator and prefix settings to a raw name
+    pub fn transform(&self, raw: &str) -> String {
+        let mut name = raw.trim().to_string();
+        if self.underscores {
+            let mut joined = String::with_capacity(name.len());
+            for c in name.chars() {
+                let c = if c.is_whitespace() || c == '-' { '_' } else { c };
+                if !(c == '_' && joined.ends_with('_')) {
+                    joined.push(c);
+                }
+            }
+            name = joined;
+        }
+        if self.lowercase {
+            name = name.to_lowercase();
+        }
+        if !name.starts_with(&self.prefix) {
+            name = format!("{}{}", self.prefix, name);
+        }
+        name
+    }
+
+    /// Controls of the rule. Returns whether anything changed.
+    pub fn show_controls(&mut self, ui: &mut Ui) -> bool {
+        let before = self.clone();
+        ui.checkbox(&mut self.enabled, "Name tracks automatically")
+            .on_hover_text("Derive names from each source file instead of using the file name as is");
+        ui.add_enabled_ui(self.enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("From:");
+                for source in [NameSource::FileName, NameSource::Title] {
+                    ui.radio_value(&mut self.source, source, source.name());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.lowercase, "Lowercase");
+                ui.checkbox(&mut self.underscores, "Spaces to underscores");
+                ui.label("Prefix:");
+                ui.add(egui::TextEdit::singleline(&mut self.prefix).desired_width(100.0).hint_text("e.g. bgm_"));
+            });
+        });
+        *self != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::wav_metadata::ExportMetadata;
+
+    #[test]
+    fn names_from_title_or_file_name_with_transforms() {
+        let rule = NamingRule {
+            enabled: true,
+            source: NameSource::Title,
+            lowercase: true,
+            underscores: true,
+            prefix: "bgm_".to_string(),
+        };
+        assert_eq!(rule.transform(" Final Boss - Phase 2 "), "bgm_final_boss_phase_2");
+        assert_eq!(rule.transform("bgm_intro"), "bgm_intro");
+
+        let wav = crate::audio_processing::splice::write_pcm16(1, 8000, &[0; 16]);
+        let tagged = wav_metadata::embed(&wav, &ExportMetadata::new("x.nus3bank", "Stage Theme", "0x1", "WAV")).unwrap();
+        let path = std::env::temp_dir().join(format!("exvs2_naming_{}.wav", std::process::id()));
+        std::fs::write(&path, tagged).unwrap();
+        assert_eq!(rule.name_for(&path), "bgm_stage_theme");
+        assert_eq!(NamingRule::default().name_for(&path), path.file_stem().unwrap().to_string_lossy());
+        std::fs::remove_file(&path).ok();
+    }
+}
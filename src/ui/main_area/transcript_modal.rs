@@ -0,0 +1,94 @@
+use egui::{Context, RichText, Window};
+
+use super::audio_file_info::AudioFileInfo;
+use crate::track_key::TrackKey;
+
+/// Edits the transcript of one voice track
+pub struct TranscriptModal {
+    pub open: bool,
+    track_name: String,
+    key: Option<TrackKey>,
+    draft: String,
+    saved: Option<(TrackKey, String)>,
+}
+
+impl Default for TranscriptModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            track_name: String::new(),
+            key: None,
+            draft: String::new(),
+            saved: None,
+        }
+    }
+
+    pub fn open_for(&mut self, info: &AudioFileInfo, current: Option<&str>) {
+        self.track_name = info.name.clone();
+        self.key = Some(info.track_key());
+        self.draft = current.unwrap_or_default().to_string();
+        self.saved = None;
+        self.open = true;
+    }
+
+    /// Track and text saved since the last call; empty text means removal
+    pub fn take_saved(&mut self) -> Option<(TrackKey, String)> {
+        self.saved.take()
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        let mut save = false;
+        Window::new("Transcript")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.track_name).strong());
+                ui.label(RichText::new("What is said in this cue. Found by the search box.").weak().size(11.0));
+                ui.add_space(6.0);
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.draft)
+                        .hint_text("e.g. Target locked, firing!")
+                        .desired_rows(4)
+                        .desired_width(f32::INFINITY),
+                );
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Cancel").clicked() {
+                            self.open = false;
+                        }
+                        if ui.button("Save").clicked() {
+                            save = true;
+                        }
+                        if ui
+                            .add_enabled(!self.draft.trim().is_empty(), egui::Button::new("Clear"))
+                            .clicked()
+                        {
+                            self.draft.clear();
+                        }
+                    });
+                });
+            });
+
+        if let Some(key) = self.key.clone().filter(|_| save) {
+            self.saved = Some((key, self.draft.clone()));
+            open = false;
+        }
+        if !open {
+            self.open = false;
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::track_key::TrackKey;
+
+/// Suffix of the sidecar written next to a bank, after its full file name
+pub const TRANSCRIPT_SUFFIX: &str = ".transcripts.json";
+
+/// What is said in the voice cues of one bank, by track. Kept in
+/// `<bank file name>.transcripts.json`, keyed by the string form of the track key, so it
+/// travels with the bank.
+#[derive(Debug, Clone, Default)]
+pub struct Transcripts {
+    path: Option<PathBuf>,
+    entries: BTreeMap<TrackKey, String>,
+}
+
+/// `se_pilot.nus3bank` -> `se_pilot.nus3bank.transcripts.json`
+pub fn sidecar_path(bank_path: &Path) -> PathBuf {
+    let mut name = bank_path.file_name().unwrap_or_default().to_os_string();
+    name.push(TRANSCRIPT_SUFFIX);
+    bank_path.with_file_name(name)
+}
+
+impl Transcripts {
+    /// Transcripts of `bank_path`; empty when the bank has no sidecar yet
+    pub fn load_for(bank_path: &Path) -> Result<Self, String> {
+        let path = sidecar_path(bank_path);
+        let mut transcripts = Self {
+            path: Some(path.clone()),
+            entries: BTreeMap::new(),
+        };
+        if path.is_file() {
+            let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            let entries: BTreeMap<String, String> = serde_json::from_str(&text)
+                .map_err(|e| format!("Invalid transcript file {}: {}", path.display(), e))?;
+            transcripts.set_all(&entries);
+        }
+        Ok(transcripts)
+    }
+
+    pub fn get(&self, key: &TrackKey) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Transcripts by the string form of their track key, as the sidecar and projects hold them
+    pub fn to_strings(&self) -> BTreeMap<String, String> {
+        self.entries.iter().map(|(key, text)| (key.to_string(), text.clone())).collect()
+    }
+
+    /// Set the transcript of a track; blank text removes it
+    pub fn set(&mut self, key: &TrackKey, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.entries.remove(key);
+        } else {
+            self.entries.insert(key.clone(), text.to_string());
+        }
+    }
+
+    /// Set transcripts keyed by the string form of their track key; unreadable keys are skipped
+    pub fn set_all(&mut self, entries: &BTreeMap<String, String>) {
+        for (key, text) in entries {
+            if let Some(key) = TrackKey::parse_legacy(key) {
+                self.set(&key, text);
+            }
+        }
+    }
+
+    /// Whether the transcript of a track contains `query`, which must be lowercase
+    pub fn matches(&self, key: &TrackKey, query: &str) -> bool {
+        self.get(key).is_some_and(|text| text.to_lowercase().contains(query))
+    }
+
+    /// Write the sidecar, or delete it once the last transcript is removed
+    pub fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Err("No file is open".to_string());
+        };
+        if self.entries.is_empty() {
+            if path.is_file() {
+                fs::remove_file(path).map_err(|e| format!("Failed to delete {}: {}", path.display(), e))?;
+            }
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(&self.to_strings())
+            .map_err(|e| format!("Failed to encode transcripts: {}", e))?;
+        fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_the_sidecar() {
+        let dir = std::env::temp_dir().join(format!("exvs2_transcripts_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bank = dir.join("vo_pilot.nus3bank");
+        assert_eq!(sidecar_path(&bank), dir.join("vo_pilot.nus3bank.transcripts.json"));
+
+        let (first, second) = (TrackKey::bank("0x0", "vo_001"), TrackKey::bank("0x1", "vo_002"));
+        let mut transcripts = Transcripts::load_for(&bank).unwrap();
+        transcripts.set(&first, "  Target locked, firing!  ");
+        transcripts.set(&second, "Retreat");
+        transcripts.set(&second, " ");
+        transcripts.save().unwrap();
+
+        let loaded = Transcripts::load_for(&bank).unwrap();
+        assert_eq!(loaded.get(&first), Some("Target locked, firing!"));
+        assert!(loaded.matches(&first, "firing"));
+        assert_eq!(loaded.to_strings().len(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
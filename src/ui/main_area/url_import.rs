@@ -0,0 +1,325 @@
+use egui::{Color32, Context, ProgressBar, RichText, Window};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::audio_file_info::AudioFileInfo;
+use super::path_utils;
+
+// Downloads larger than this are rejected; no track in a bank comes close
+const MAX_DOWNLOAD_BYTES: u64 = 512 * 1024 * 1024;
+
+const CONNECT_TIMEOUT_SEC: u64 = 15;
+
+/// What the downloaded file will be used for
+#[derive(Debug, Clone)]
+pub enum UrlImportTarget {
+    /// Replacement source for an existing track
+    Replace(AudioFileInfo),
+    /// New track for the Add Audio dialog
+    Add,
+}
+
+/// Progress of the background download, shared with the worker thread
+#[derive(Default)]
+struct DownloadProgress {
+    downloaded: u64,
+    total: Option<u64>,
+    result: Option<Result<PathBuf, String>>,
+}
+
+/// Window that downloads replacement or new audio from an http(s) URL
+pub struct UrlImportModal {
+    pub open: bool,
+    target: Option<UrlImportTarget>,
+    url: String,
+    progress: Option<Arc<Mutex<DownloadProgress>>>,
+    cancel: Arc<AtomicBool>,
+    error: Option<String>,
+    completed: Option<(UrlImportTarget, PathBuf)>,
+}
+
+impl Default for UrlImportModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UrlImportModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            target: None,
+            url: String::new(),
+            progress: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            error: None,
+            completed: None,
+        }
+    }
+
+    pub fn open_for(&mut self, target: UrlImportTarget) {
+        self.target = Some(target);
+        self.error = None;
+        self.progress = None;
+        self.open = true;
+    }
+
+    /// Take a finished, verified download
+    pub fn take_completed(&mut self) -> Option<(UrlImportTarget, PathBuf)> {
+        self.completed.take()
+    }
+
+    fn is_downloading(&self) -> bool {
+        self.progress
+            .as_ref()
+            .and_then(|p| p.lock().ok().map(|p| p.result.is_none()))
+            .unwrap_or(false)
+    }
+
+    fn start_download(&mut self) {
+        let url = self.url.trim().to_string();
+        if let Err(e) = validate_url(&url) {
+            self.error = Some(e);
+            return;
+        }
+        self.error = None;
+
+        let progress = Arc::new(Mutex::new(DownloadProgress::default()));
+        self.cancel = Arc::new(AtomicBool::new(false));
+        self.progress = Some(progress.clone());
+        let cancel = self.cancel.clone();
+
+        std::thread::spawn(move || {
+            let result = download(&url, &progress, &cancel).and_then(|path| {
+                if let Err(e) = verify_audio(&path) {
+                    let _ = fs::remove_file(&path);
+                    return Err(e);
+                }
+                Ok(path)
+            });
+            if let Ok(mut p) = progress.lock() {
+                p.result = Some(result);
+            }
+        });
+    }
+
+    /// Move a finished download into `completed` or `error`
+    fn poll(&mut self) {
+        let finished = self
+            .progress
+            .as_ref()
+            .and_then(|p| p.lock().ok().and_then(|mut p| p.result.take()));
+        let Some(result) = finished else {
+            return;
+        };
+        self.progress = None;
+        match result {
+            Ok(path) => {
+                if let Some(target) = self.target.take() {
+                    self.completed = Some((target, path));
+                }
+                self.open = false;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+        self.poll();
+        if !self.open {
+            return;
+        }
+        let downloading = self.is_downloading();
+        if downloading {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+
+        let title = match &self.target {
+            Some(UrlImportTarget::Replace(info)) => format!("Replace {} from URL", info.name),
+            _ => "Add Audio from URL".to_string(),
+        };
+
+        let mut open = self.open;
+        Window::new(title)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .default_width(460.0)
+            .show(ctx, |ui| {
+                ui.label("Paste an http(s) link to a WAV, MP3, FLAC, OGG, LOPUS or IDSP file:");
+                ui.add_enabled(
+                    !downloading,
+                    egui::TextEdit::singleline(&mut self.url)
+                        .hint_text("https://...")
+                        .desired_width(f32::INFINITY),
+                );
+                ui.add_space(6.0);
+
+                if let Some(progress) = &self.progress {
+                    if let Ok(p) = progress.lock() {
+                        let text = match p.total {
+                            Some(total) => format!("{} / {}", format_bytes(p.downloaded), format_bytes(total)),
+                            None => format_bytes(p.downloaded),
+                        };
+                        let fraction = p
+                            .total
+                            .filter(|t| *t > 0)
+                            .map(|t| p.downloaded as f32 / t as f32)
+                            .unwrap_or(0.0);
+                        ui.add(ProgressBar::new(fraction).text(text).animate(p.total.is_none()));
+                    }
+                }
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(Color32::RED, error);
+                }
+
+                ui.horizontal(|ui| {
+                    if downloading {
+                        if ui.button("Cancel Download").clicked() {
+                            self.cancel.store(true, Ordering::Relaxed);
+                        }
+                    } else if ui
+                        .add_enabled(!self.url.trim().is_empty(), egui::Button::new("Download"))
+                        .clicked()
+                    {
+                        self.start_download();
+                    }
+                });
+                ui.label(
+                    RichText::new("The file is checked to decode before it is queued.")
+                        .size(11.0)
+                        .color(Color32::GRAY),
+                );
+            });
+
+        if !open {
+            self.cancel.store(true, Ordering::Relaxed);
+        }
+        self.open = open;
+    }
+}
+
+fn validate_url(url: &str) -> Result<(), String> {
+    let lower = url.to_lowercase();
+    if !(lower.starts_with("http://") || lower.starts_with("https://")) {
+        return Err("Only http:// and https:// links are supported".to_string());
+    }
+    Ok(())
+}
+
+/// File name for a download: the last path segment of the URL without query or fragment
+fn file_name_from_url(url: &str) -> String {
+    let without_query = url.split(['?', '#']).next().unwrap_or(url);
+    let after_host = without_query.splitn(4, '/').nth(3).unwrap_or("");
+    let segment = after_host.rsplit('/').next().unwrap_or("");
+    if segment.is_empty() {
+        "download.bin".to_string()
+    } else {
+        path_utils::ascii_file_name(segment)
+    }
+}
+
+fn download(url: &str, progress: &Mutex<DownloadProgress>, cancel: &AtomicBool) -> Result<PathBuf, String> {
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SEC))
+        .timeout(None)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .map_err(|e| format!("Download failed: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Server returned error status: {}", response.status()));
+    }
+
+    let total = response.content_length();
+    if total.is_some_and(|t| t > MAX_DOWNLOAD_BYTES) {
+        return Err(format!("The file is too large ({})", format_bytes(total.unwrap_or(0))));
+    }
+    if let Ok(mut p) = progress.lock() {
+        p.total = total;
+    }
+
+    let dir = path_utils::safe_temp_dir().join("exvs2_url_imports");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create download folder: {}", e))?;
+    let path = dir.join(format!("{}_{}", std::process::id(), file_name_from_url(url)));
+    let mut file = fs::File::create(&path).map_err(|e| format!("Failed to create download file: {}", e))?;
+
+    let mut buffer = [0u8; 64 * 1024];
+    let mut downloaded = 0u64;
+    let outcome = loop {
+        if cancel.load(Ordering::Relaxed) {
+            break Err("Download cancelled".to_string());
+        }
+        let n = match response.read(&mut buffer) {
+            Ok(0) => break Ok(()),
+            Ok(n) => n,
+            Err(e) => break Err(format!("Download interrupted: {}", e)),
+        };
+        downloaded += n as u64;
+        if downloaded > MAX_DOWNLOAD_BYTES {
+            break Err("The file is too large".to_string());
+        }
+        if let Err(e) = file.write_all(&buffer[..n]) {
+            break Err(format!("Failed to write download: {}", e));
+        }
+        if let Ok(mut p) = progress.lock() {
+            p.downloaded = downloaded;
+        }
+    };
+    drop(file);
+
+    if let Err(e) = outcome {
+        let _ = fs::remove_file(&path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// Check that a downloaded file is audio the import pipeline can use
+fn verify_audio(path: &Path) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read download: {}", e))?;
+    if data.is_empty() {
+        return Err("The downloaded file is empty".to_string());
+    }
+    // Switch-native formats are decoded by vgmstream later; check their header only
+    if data.starts_with(b"OPUS") || data.starts_with(b"IDSP") {
+        return Ok(());
+    }
+    if data.starts_with(b"<") || data.starts_with(b"\xEF\xBB\xBF<") {
+        return Err("The link returned a web page instead of an audio file".to_string());
+    }
+    kira::sound::static_sound::StaticSoundData::from_file(path)
+        .map(|_| ())
+        .map_err(|e| format!("The downloaded file could not be decoded as audio: {}", e))
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_name_ignores_query_and_host() {
+        assert_eq!(file_name_from_url("https://example.com/mods/bgm%2001.wav?dl=1"), "bgm_2001.wav");
+        assert_eq!(file_name_from_url("https://example.com/"), "download.bin");
+        assert_eq!(file_name_from_url("http://example.com"), "download.bin");
+        assert!(validate_url("ftp://example.com/a.wav").is_err());
+    }
+}
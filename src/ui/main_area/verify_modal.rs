@@ -0,0 +1,82 @@
+use egui::{Color32, Context, RichText, Window};
+use egui_phosphor::regular;
+
+use crate::nus3bank::VerifyReport;
+
+/// Shows the result of saving the open bank into memory and reading it back
+pub struct VerifyModal {
+    pub open: bool,
+    bank_path: String,
+    result: Option<Result<VerifyReport, String>>,
+}
+
+impl Default for VerifyModal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VerifyModal {
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            bank_path: String::new(),
+            result: None,
+        }
+    }
+
+    pub fn open_with(&mut self, bank_path: &str, result: Result<VerifyReport, String>) {
+        self.bank_path = bank_path.to_string();
+        self.result = Some(result);
+        self.open = true;
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if !self.open {
+            return;
+        }
+
+        let mut open = self.open;
+        Window::new("Verify")
+            .open(&mut open)
+            .resizable(true)
+            .collapsible(false)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(&self.bank_path).weak().size(11.0));
+                ui.add_space(6.0);
+                match &self.result {
+                    Some(Ok(report)) if report.is_ok() => {
+                        ui.colored_label(
+                            Color32::from_rgb(90, 170, 90),
+                            format!("{} Saving now would read back identical", regular::CHECK_CIRCLE),
+                        );
+                    }
+                    Some(Ok(report)) => {
+                        ui.colored_label(
+                            Color32::from_rgb(255, 180, 60),
+                            format!(
+                                "{} {} mismatch(es) between the edits and what would be saved",
+                                regular::WARNING,
+                                report.mismatches.len()
+                            ),
+                        );
+                    }
+                    Some(Err(message)) => {
+                        ui.colored_label(Color32::RED, message);
+                    }
+                    None => {}
+                }
+                if let Some(Ok(report)) = &self.result {
+                    ui.separator();
+                    egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                        ui.label(RichText::new(report.describe()).monospace().size(11.0));
+                    });
+                }
+            });
+
+        if !open {
+            self.open = false;
+        }
+    }
+}
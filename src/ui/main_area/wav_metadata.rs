@@ -0,0 +1,254 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::audio_file_info::AudioFileInfo;
+use crate::wav::riff::{Chunk, Riff};
+
+const SOFTWARE_NAME: &str = "EXVS2 Audio Editor";
+
+/// Fixed part of a BWF version 1 `bext` chunk, before the coding history
+const BEXT_FIXED_LEN: usize = 602;
+
+/// Where an exported WAV came from, written into its `bext` and `LIST/INFO` chunks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportMetadata {
+    pub bank_name: String,
+    pub track_name: String,
+    pub hex_id: String,
+    /// Codec of the audio inside the bank before conversion
+    pub codec: String,
+    /// UTC date as YYYY-MM-DD
+    pub date: String,
+    /// UTC time as HH:MM:SS
+    pub time: String,
+}
+
+impl ExportMetadata {
+    /// Metadata for a track exported now. `codec` overrides the codec shown in the table.
+    pub fn new(bank_path: &str, track_name: &str, hex_id: &str, codec: &str) -> Self {
+        let bank_name = Path::new(bank_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| bank_path.to_string());
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let (date, time) = utc_date_time(secs);
+        Self {
+            bank_name,
+            track_name: track_name.to_string(),
+            hex_id: hex_id.to_string(),
+            codec: codec.to_string(),
+            date,
+            time,
+        }
+    }
+
+    pub fn for_track(audio_info: &AudioFileInfo, bank_path: &str) -> Self {
+        Self::new(bank_path, &audio_info.name, audio_info.effective_id(), &audio_info.file_type)
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Bank: {}; Track: {}; ID: {}; Codec: {}",
+            self.bank_name, self.track_name, self.hex_id, self.codec
+        )
+    }
+}
+
+/// Codec name for a track payload, from its header
+pub fn codec_from_header(data: &[u8]) -> String {
+    match data.get(..4) {
+        Some(b"OPUS") => "OPUS".to_string(),
+        Some(b"IDSP") => "IDSP".to_string(),
+        Some(b"BNSF") => "BNSF".to_string(),
+        Some(b"RIFF") => match Riff::parse_wav(data).ok().and_then(|riff| riff.get(b"fmt ")?.borrowed()) {
+            Some(fmt) if fmt.len() >= 2 => {
+                let tag = u16::from_le_bytes([fmt[0], fmt[1]]);
+                match tag {
+                    0x0001 => "WAV (PCM)".to_string(),
+                    0x0003 => "WAV (float)".to_string(),
+                    0xFFFE => "WAV (extensible)".to_string(),
+                    other => format!("WAV (format 0x{:04X})", other),
+                }
+            }
+            _ => "WAV".to_string(),
+        },
+        _ => "Unknown".to_string(),
+    }
+}
+
+/// Convert seconds since the Unix epoch to UTC ("YYYY-MM-DD", "HH:MM:SS")
+pub(super) fn utc_date_time(secs: u64) -> (String, String) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        format!("{:04}-{:02}-{:02}", year, month, day),
+        format!("{:02}:{:02}:{:02}", rem / 3600, (rem / 60) % 60, rem % 60),
+    )
+}
+
+/// Title (`INAM`) of a `LIST/INFO` chunk anywhere in a RIFF/WAVE buffer
+pub fn read_title(wav: &[u8]) -> Option<String> {
+    let riff = Riff::parse_wav(wav).ok()?;
+    riff.chunks
+        .iter()
+        .filter(|c| c.list_type() == Some(b"INFO".as_slice()))
+        .find_map(|list| {
+            let name = list.sub_chunks().into_iter().find(|c| &c.id == b"INAM")?;
+            let text = String::from_utf8_lossy(&name.data).trim_end_matches('\0').trim().to_string();
+            (!text.is_empty()).then_some(text)
+        })
+}
+
+fn push_chunk(out: &mut Vec<u8>, id: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        out.push(0);
+    }
+}
+
+fn fixed_ascii(out: &mut Vec<u8>, text: &str, len: usize) {
+    let bytes: Vec<u8> = text.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' }).collect();
+    let n = bytes.len().min(len);
+    out.extend_from_slice(&bytes[..n]);
+    out.resize(out.len() + (len - n), 0);
+}
+
+fn bext_chunk(meta: &ExportMetadata) -> Vec<u8> {
+    let mut data = Vec::with_capacity(BEXT_FIXED_LEN + 64);
+    fixed_ascii(&mut data, &meta.description(), 256);
+    fixed_ascii(&mut data, SOFTWARE_NAME, 32);
+    fixed_ascii(&mut data, &meta.hex_id, 32);
+    fixed_ascii(&mut data, &meta.date, 10);
+    fixed_ascii(&mut data, &meta.time, 8);
+    data.extend_from_slice(&0u64.to_le_bytes()); // TimeReference
+    data.extend_from_slice(&1u16.to_le_bytes()); // Version
+    data.resize(BEXT_FIXED_LEN, 0); // UMID and reserved
+    data.extend_from_slice(format!("T=Decoded from {} by {}\r\n", meta.codec, SOFTWARE_NAME).as_bytes());
+    // Keep the chunk size even: some readers (hound included) skip chunks without the pad byte
+    if data.len() % 2 == 1 {
+        data.push(0);
+    }
+    data
+}
+
+fn info_list_chunk(meta: &ExportMetadata) -> Vec<u8> {
+    let entries: [(&[u8; 4], String); 5] = [
+        (b"INAM", meta.track_name.clone()),
+        (b"IPRD", meta.bank_name.clone()),
+        (
+            b"ICMT",
+            format!("ID: {}; Original codec: {}; Exported: {} {} UTC", meta.hex_id, meta.codec, meta.date, meta.time),
+        ),
+        (b"ICRD", meta.date.clone()),
+        (b"ISFT", format!("{} {}", SOFTWARE_NAME, env!("CARGO_PKG_VERSION"))),
+    ];
+    let mut data = b"INFO".to_vec();
+    for (id, text) in entries {
+        let mut value = text.into_bytes();
+        value.push(0);
+        push_chunk(&mut data, id, &value);
+    }
+    data
+}
+
+/// Rebuild a WAV with `bext` (after `fmt `) and `LIST/INFO` (at the end) chunks describing
+/// the export. Existing `bext` and `LIST/INFO` chunks are replaced, all other chunks are kept.
+/// Returns `None` when the input is not a RIFF/WAVE file.
+pub fn embed(wav: &[u8], meta: &ExportMetadata) -> Option<Vec<u8>> {
+    let mut riff = Riff::parse_wav(wav).ok()?;
+    riff.chunks
+        .retain(|c| &c.id != b"bext" && c.list_type() != Some(b"INFO".as_slice()));
+    // bext goes right after fmt, or before data when that comes first
+    let at = riff
+        .chunks
+        .iter()
+        .position(|c| &c.id == b"fmt " || &c.id == b"data")
+        .map_or(riff.chunks.len(), |i| i + usize::from(&riff.chunks[i].id == b"fmt "));
+    riff.chunks.insert(at, Chunk { id: *b"bext", data: Cow::Owned(bext_chunk(meta)) });
+    riff.chunks.push(Chunk { id: *b"LIST", data: Cow::Owned(info_list_chunk(meta)) });
+    Some(riff.to_bytes())
+}
+
+/// Embed metadata into an exported file in place. Files that are not WAV are left untouched.
+pub fn embed_in_file(path: &str, meta: &ExportMetadata) -> Result<(), String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {} for metadata: {}", path, e))?;
+    if let Some(tagged) = embed(&data, meta) {
+        fs::write(path, tagged).map_err(|e| format!("Failed to write metadata to {}: {}", path, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_meta() -> ExportMetadata {
+        ExportMetadata {
+            bank_name: "bgm_test.nus3bank".to_string(),
+            track_name: "bgm_01".to_string(),
+            hex_id: "0x12".to_string(),
+            codec: "OPUS".to_string(),
+            date: "2024-02-29".to_string(),
+            time: "12:34:56".to_string(),
+        }
+    }
+
+    #[test]
+    fn utc_conversion_handles_leap_day() {
+        // 2024-02-29T12:34:56Z
+        assert_eq!(
+            utc_date_time(1_709_210_096),
+            ("2024-02-29".to_string(), "12:34:56".to_string())
+        );
+        assert_eq!(utc_date_time(0), ("1970-01-01".to_string(), "00:00:00".to_string()));
+    }
+
+    #[test]
+    fn embedded_wav_still_decodes_and_is_idempotent() {
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        let spec = hound::WavSpec {
+            channels: 1,
+            sample_rate: 8000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+        for i in 0..101i16 {
+            writer.write_sample(i).unwrap();
+        }
+        writer.finalize().unwrap();
+        let wav = cursor.into_inner();
+
+        let tagged = embed(&wav, &sample_meta()).unwrap();
+        let ids: Vec<[u8; 4]> = Riff::parse_wav(&tagged).unwrap().chunks.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![*b"fmt ", *b"bext", *b"data", *b"LIST"]);
+        assert_eq!(codec_from_header(&tagged), "WAV (PCM)");
+
+        let mut reader = hound::WavReader::new(std::io::Cursor::new(tagged.clone())).unwrap();
+        let samples: Vec<i16> = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+        assert_eq!(samples.len(), 101);
+        assert_eq!(samples[100], 100);
+
+        assert_eq!(embed(&tagged, &sample_meta()).unwrap(), tagged);
+        assert_eq!(embed(b"OPUS....", &sample_meta()), None);
+    }
+}
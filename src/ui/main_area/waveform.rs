@@ -0,0 +1,239 @@
+use egui::{Color32, Pos2, Sense, Stroke, Ui, Vec2};
+use std::io::Cursor;
+use std::path::Path;
+
+use super::add_audio_utils::AddAudioUtils;
+
+/// Number of peak columns kept per preview
+const PEAK_COLUMNS: usize = 400;
+
+/// Frames summarised by one min/max block, used when a zoomed view is too wide to draw
+/// from the samples directly
+const BLOCK_FRAMES: usize = 64;
+
+/// Peak overview of a source file, drawn in the replace and add dialogs
+#[derive(Debug, Clone)]
+pub struct Waveform {
+    /// Largest absolute sample per column, 0.0..=1.0
+    pub peaks: Vec<f32>,
+    pub duration_secs: f32,
+    pub sample_rate: u32,
+    frames: usize,
+    /// Channels mixed to mono, for zoomed drawing and zero-crossing search. Empty when the
+    /// waveform was read back from a peak file.
+    samples: Vec<f32>,
+    /// Smallest and largest sample of every `BLOCK_FRAMES` frames
+    blocks: Vec<(f32, f32)>,
+}
+
+impl Waveform {
+    /// Peaks of a WAV file held in memory, all channels mixed together
+    pub fn from_wav(data: &[u8], columns: usize) -> Result<Self, String> {
+        let mut reader = hound::WavReader::new(Cursor::new(data)).map_err(|e| format!("Failed to read WAV: {}", e))?;
+        let spec = reader.spec();
+        if spec.sample_rate == 0 || spec.channels == 0 {
+            return Err("WAV has no sample rate or channels".to_string());
+        }
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.samples::<f32>().filter_map(Result::ok).collect(),
+            hound::SampleFormat::Int => {
+                let scale = (1i64 << (spec.bits_per_sample.clamp(1, 32) - 1)) as f32;
+                reader.samples::<i32>().filter_map(Result::ok).map(|s| s as f32 / scale).collect()
+            }
+        };
+        let frames = samples.len() / spec.channels as usize;
+        let mut peaks = vec![0.0f32; columns.clamp(1, frames.max(1))];
+        let last = peaks.len() - 1;
+        let per_column = frames.div_ceil(peaks.len()).max(1);
+        let mut mono = Vec::with_capacity(frames);
+        for (frame, chunk) in samples.chunks_exact(spec.channels as usize).enumerate() {
+            let peak = &mut peaks[(frame / per_column).min(last)];
+            for s in chunk {
+                *peak = peak.max(s.abs().min(1.0));
+            }
+            mono.push(chunk.iter().sum::<f32>() / chunk.len() as f32);
+        }
+        let blocks = mono
+            .chunks(BLOCK_FRAMES)
+            .map(|block| block.iter().fold((0.0f32, 0.0f32), |(lo, hi), &s| (lo.min(s), hi.max(s))))
+            .collect();
+        Ok(Self {
+            peaks,
+            duration_secs: frames as f32 / spec.sample_rate as f32,
+            sample_rate: spec.sample_rate,
+            frames,
+            samples: mono,
+            blocks,
+        })
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// All channels mixed to mono
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// The frame nearest to `frame`, within `radius`, where the signal crosses zero. Looping
+    /// there avoids a click at the seam. `frame` itself when there is no crossing nearby.
+    pub fn nearest_zero_crossing(&self, frame: usize, radius: usize) -> usize {
+        let crosses = |i: usize| {
+            i < self.samples.len()
+                && (self.samples[i] == 0.0 || (i > 0 && (self.samples[i - 1] < 0.0) != (self.samples[i] < 0.0)))
+        };
+        (0..=radius)
+            .flat_map(|d| [frame.checked_add(d), frame.checked_sub(d)])
+            .flatten()
+            .find(|&i| crosses(i))
+            .unwrap_or(frame)
+    }
+
+    /// Smallest and largest sample of the frames `from..to`
+    pub fn range_extent(&self, from: usize, to: usize) -> (f32, f32) {
+        let to = to.min(self.frames);
+        if from >= to {
+            return (0.0, 0.0);
+        }
+        let fold = |acc: (f32, f32), (lo, hi): (f32, f32)| (acc.0.min(lo), acc.1.max(hi));
+        if to - from < BLOCK_FRAMES * 4 && !self.samples.is_empty() {
+            return self.samples[from..to].iter().map(|&s| (s, s)).fold((0.0, 0.0), fold);
+        }
+        self.blocks[from / BLOCK_FRAMES..to.div_ceil(BLOCK_FRAMES)].iter().copied().fold((0.0, 0.0), fold)
+    }
+
+    /// Peaks and blocks in the peak file layout: "PEAK", sample rate, frame count, then the
+    /// peak and block counts each followed by their values, all little-endian
+    pub fn to_peak_file(&self) -> Vec<u8> {
+        let mut out = b"PEAK".to_vec();
+        out.extend_from_slice(&self.sample_rate.to_le_bytes());
+        out.extend_from_slice(&(self.frames as u64).to_le_bytes());
+        out.extend_from_slice(&(self.peaks.len() as u32).to_le_bytes());
+        for peak in &self.peaks {
+            out.extend_from_slice(&peak.to_le_bytes());
+        }
+        out.extend_from_slice(&(self.blocks.len() as u32).to_le_bytes());
+        for (lo, hi) in &self.blocks {
+            out.extend_from_slice(&lo.to_le_bytes());
+            out.extend_from_slice(&hi.to_le_bytes());
+        }
+        out
+    }
+
+    /// Read back a peak file. The samples are not kept, so zero-crossing search and the
+    /// spectrogram need the audio itself.
+    pub fn from_peak_file(data: &[u8]) -> Option<Self> {
+        let mut pos = 0;
+        let mut take = |len: usize| {
+            let bytes = data.get(pos..pos + len)?;
+            pos += len;
+            Some(bytes)
+        };
+        if take(4)? != b"PEAK" {
+            return None;
+        }
+        let u32_at = |b: &[u8]| u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        let sample_rate = u32_at(take(4)?);
+        let frames = u64::from_le_bytes(take(8)?.try_into().ok()?) as usize;
+        let peak_count = u32_at(take(4)?) as usize;
+        let peaks: Vec<f32> = take(peak_count * 4)?.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        let block_count = u32_at(take(4)?) as usize;
+        let blocks = take(block_count * 8)?
+            .chunks_exact(8)
+            .map(|b| (f32::from_le_bytes([b[0], b[1], b[2], b[3]]), f32::from_le_bytes([b[4], b[5], b[6], b[7]])))
+            .collect();
+        if sample_rate == 0 || block_count != frames.div_ceil(BLOCK_FRAMES) {
+            return None;
+        }
+        Some(Self {
+            peaks,
+            duration_secs: frames as f32 / sample_rate as f32,
+            sample_rate,
+            frames,
+            samples: Vec::new(),
+            blocks,
+        })
+    }
+
+    /// Peaks of any source file; formats other than WAV are decoded with vgmstream first
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let is_wav = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("wav"));
+        let data = if is_wav {
+            std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        } else {
+            AddAudioUtils::convert_to_wav(&path.to_string_lossy())?
+        };
+        Self::from_wav(&data, PEAK_COLUMNS)
+    }
+
+    /// Draw the peaks full width, shading the loop region given in seconds
+    pub fn show(&self, ui: &mut Ui, height: f32, loop_region: Option<(f32, f32)>) {
+        let (rect, _) = ui.allocate_exact_size(Vec2::new(ui.available_width(), height), Sense::hover());
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+
+        if let (Some((start, end)), true) = (loop_region, self.duration_secs > 0.0) {
+            let x = |t: f32| rect.left() + rect.width() * (t / self.duration_secs).clamp(0.0, 1.0);
+            let region = egui::Rect::from_x_y_ranges(x(start)..=x(end), rect.y_range());
+            painter.rect_filled(region, 0.0, Color32::from_rgba_unmultiplied(90, 170, 255, 40));
+        }
+
+        let stroke = Stroke::new(1.0, ui.visuals().widgets.active.fg_stroke.color);
+        let mid = rect.center().y;
+        let step = rect.width() / self.peaks.len().max(1) as f32;
+        for (i, peak) in self.peaks.iter().enumerate() {
+            let x = rect.left() + (i as f32 + 0.5) * step;
+            let half = (peak * rect.height() * 0.5).max(0.5);
+            painter.line_segment([Pos2::new(x, mid - half), Pos2::new(x, mid + half)], stroke);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peaks_follow_the_loudest_sample_of_each_column() {
+        let spec = hound::WavSpec { channels: 2, sample_rate: 100, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut data = Vec::new();
+        {
+            let mut writer = hound::WavWriter::new(Cursor::new(&mut data), spec).unwrap();
+            for frame in 0..200 {
+                let loud = if frame < 100 { 0 } else { i16::MAX };
+                writer.write_sample(loud).unwrap();
+                writer.write_sample(-(loud / 2)).unwrap();
+            }
+            writer.finalize().unwrap();
+        }
+        let waveform = Waveform::from_wav(&data, 4).unwrap();
+        assert_eq!(waveform.duration_secs, 2.0);
+        assert_eq!(waveform.peaks.len(), 4);
+        assert_eq!(waveform.peaks[0], 0.0);
+        assert!(waveform.peaks[3] > 0.99);
+
+        let cached = Waveform::from_peak_file(&waveform.to_peak_file()).unwrap();
+        assert_eq!((&cached.peaks, cached.frames(), cached.duration_secs), (&waveform.peaks, 200, 2.0));
+        assert_eq!(cached.range_extent(0, 200), waveform.range_extent(0, 200));
+    }
+
+    #[test]
+    fn snaps_to_the_closest_sign_change() {
+        let spec = hound::WavSpec { channels: 1, sample_rate: 1000, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+        let mut data = Vec::new();
+        {
+            let mut writer = hound::WavWriter::new(Cursor::new(&mut data), spec).unwrap();
+            // Positive for 10 frames, then negative: the only crossing is at frame 10
+            for frame in 0..20 {
+                writer.write_sample(if frame < 10 { 1000i16 } else { -1000 }).unwrap();
+            }
+            writer.finalize().unwrap();
+        }
+        let waveform = Waveform::from_wav(&data, 4).unwrap();
+        assert_eq!(waveform.frames(), 20);
+        assert_eq!(waveform.nearest_zero_crossing(7, 5), 10);
+        assert_eq!(waveform.nearest_zero_crossing(3, 5), 3);
+        assert_eq!(waveform.range_extent(8, 12).0, -1000.0 / 32768.0);
+    }
+}
@@ -0,0 +1,62 @@
+use super::audio_file_info::AudioFileInfo;
+
+/// Tracks of one open file that match a workspace search
+pub struct BankMatches {
+    pub path: String,
+    pub title: String,
+    pub tracks: Vec<AudioFileInfo>,
+}
+
+/// Tracks whose name, ID or file name contain `query`, grouped by the open file they are in.
+/// Files without a match are left out; `None` rows are files still loading.
+pub fn search<'a>(
+    banks: impl IntoIterator<Item = (String, String, Option<&'a [AudioFileInfo]>)>,
+    query: &str,
+) -> Vec<BankMatches> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    banks
+        .into_iter()
+        .filter_map(|(path, title, rows)| {
+            let tracks: Vec<AudioFileInfo> = rows?
+                .iter()
+                .filter(|file| {
+                    file.name.to_lowercase().contains(&query)
+                        || file.id.to_lowercase().contains(&query)
+                        || file.filename.to_lowercase().contains(&query)
+                })
+                .cloned()
+                .collect();
+            (!tracks.is_empty()).then_some(BankMatches { path, title, tracks })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_matches_by_bank_and_skips_banks_without_any() {
+        let row = |name: &str, id: &str| {
+            AudioFileInfo::from_nus3audio(name.to_string(), id.to_string(), 0, format!("{}.idsp", name), "IDSP".to_string())
+        };
+        let a = vec![row("bgm_stage01", "1"), row("se_hit", "2")];
+        let b = vec![row("se_jump", "1")];
+        let c = vec![row("BGM_Title", "7")];
+        let banks = vec![
+            ("a.nus3audio".to_string(), "a".to_string(), Some(a.as_slice())),
+            ("b.nus3audio".to_string(), "b".to_string(), Some(b.as_slice())),
+            ("c.nus3audio".to_string(), "c".to_string(), Some(c.as_slice())),
+            ("d.nus3audio".to_string(), "d".to_string(), None),
+        ];
+
+        let found = search(banks, " bgm ");
+        let titles: Vec<&str> = found.iter().map(|m| m.title.as_str()).collect();
+        assert_eq!(titles, ["a", "c"]);
+        assert_eq!(found[0].tracks.len(), 1);
+        assert_eq!(found[1].tracks[0].name, "BGM_Title");
+    }
+}
@@ -0,0 +1,110 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Reason a save destination cannot be written
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteAccessIssue {
+    /// The destination file exists and is marked read-only
+    ReadOnlyFile(String),
+    /// The destination directory refuses new files (e.g. Program Files)
+    ProtectedDirectory(String),
+    /// The destination directory does not exist
+    MissingDirectory(String),
+}
+
+impl WriteAccessIssue {
+    /// Short description of the problem
+    pub fn message(&self) -> String {
+        match self {
+            WriteAccessIssue::ReadOnlyFile(path) => format!("The file is read-only:\n{}", path),
+            WriteAccessIssue::ProtectedDirectory(dir) => {
+                format!("The folder does not allow writing:\n{}", dir)
+            }
+            WriteAccessIssue::MissingDirectory(dir) => format!("The folder does not exist:\n{}", dir),
+        }
+    }
+
+    /// Instructions for fixing the problem before retrying
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            WriteAccessIssue::ReadOnlyFile(_) => {
+                "Right-click the file, open Properties and clear the \"Read-only\" attribute, then retry. \
+                 Files extracted from archives or copied from discs are often read-only."
+            }
+            WriteAccessIssue::ProtectedDirectory(_) => {
+                "The folder is protected (for example inside Program Files). Either save to another \
+                 location and copy the file back manually, or restart the editor with \"Run as administrator\" and retry."
+            }
+            WriteAccessIssue::MissingDirectory(_) => {
+                "The folder may have been moved, renamed or be on a disconnected drive. Save to another location \
+                 or reconnect the drive and retry."
+            }
+        }
+    }
+}
+
+/// Check that `path` can be written before running the save pipeline.
+/// When `needs_backup` is set a sibling `.bak` file must be creatable too.
+pub fn check_destination(path: &str, needs_backup: bool) -> Result<(), WriteAccessIssue> {
+    let target = Path::new(path);
+    let dir = match target.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => std::env::current_dir().unwrap_or_default(),
+    };
+    let dir_display = dir.to_string_lossy().to_string();
+
+    if !dir.is_dir() {
+        return Err(WriteAccessIssue::MissingDirectory(dir_display));
+    }
+
+    if let Ok(metadata) = fs::metadata(target) {
+        if metadata.permissions().readonly() {
+            return Err(WriteAccessIssue::ReadOnlyFile(path.to_string()));
+        }
+    }
+
+    // Creating a new file in the folder is the only reliable check for protected
+    // directories (ACLs and UAC virtualization are not visible in permissions()).
+    if needs_backup || !target.exists() {
+        let probe = dir.join(format!(".exvs2_write_probe_{}", std::process::id()));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+            Ok(_) => {
+                let _ = fs::remove_file(&probe);
+            }
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => {
+                return Err(WriteAccessIssue::ProtectedDirectory(dir_display));
+            }
+            Err(_) => {}
+        }
+    } else if let Err(e) = fs::OpenOptions::new().append(true).open(target) {
+        if e.kind() == ErrorKind::PermissionDenied {
+            return Err(WriteAccessIssue::ProtectedDirectory(dir_display));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn temp_dir_is_writable() {
+        let path = std::env::temp_dir().join("exvs2_write_access_test.nus3bank");
+        let _ = fs::remove_file(&path);
+        assert_eq!(check_destination(&path.to_string_lossy(), true), Ok(()));
+    }
+
+    #[test]
+    fn missing_directory_is_reported() {
+        let path = std::env::temp_dir()
+            .join("exvs2_write_access_missing_dir")
+            .join("out.nus3bank");
+        assert!(matches!(
+            check_destination(&path.to_string_lossy(), false),
+            Err(WriteAccessIssue::MissingDirectory(_))
+        ));
+    }
+}
@@ -1,8 +1,10 @@
+use crate::tools::VgmstreamRunner;
 use crate::version_check;
-use egui::{Context, Id};
+use egui::{Color32, Context, Id};
+use std::path::Path;
 use std::sync::Mutex;
 use once_cell::sync::Lazy;
-use crate::ui::main_area::Nus3audioFileUtils;
+use crate::ui::main_area::{check_destination, decode_pool, Nus3audioFileUtils, PostSaveHook, ProjectFile, ReplaceUtils, SaveSummary, SaveTarget, PROJECT_EXTENSION};
 
 // Modal dialog information
 #[derive(Clone, Default)]
@@ -94,6 +96,11 @@ impl TopPanel {
             }
         }
         
+        // Show the save summary dialog and run the save once confirmed
+        if let Some(app_mut) = app.as_mut() {
+            TopPanel::show_save_summary(ctx, app_mut);
+        }
+        
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
                 // Don't show Quit button in web environment
@@ -103,76 +110,8 @@ impl TopPanel {
                         if ui.button("Save Changes").clicked() {
                             // Save pending changes to the current nus3audio file
                             ui.ctx().request_repaint();
-
-                            // Initialize file path
-                            let mut selected_file_path = None;
-
-                            // Extract path first without moving app
-                            {
-                                if let Some(app_ref) = app.as_ref() {
-                                    let main_area = app_ref.main_area();
-                                    if let Some(path) = &main_area.selected_file {
-                                        selected_file_path = Some(path.to_string());
-                                    }
-                                }
-                            }
-
-                            if selected_file_path.is_none() {
-                                println!("No file selected to save changes");
-                                // Show error dialog
-                                show_modal(
-                                    "Save Failed",
-                                    "No file selected to save changes to",
-                                    true,
-                                );
-                                return;
-                            }
-
-                            // Check if there are any pending changes
-                            if !Nus3audioFileUtils::has_pending_changes() {
-                                println!("No pending changes to save");
-                                // Show info dialog
-                                show_modal(
-                                    "No Changes",
-                                    "There are no pending changes to save",
-                                    false,
-                                );
-                                return;
-                            }
-
-                            // Save changes to the current file
-                            if let Some(file_path) = selected_file_path {
-                                match Nus3audioFileUtils::save_changes_to_file(&file_path) {
-                                    Ok(_) => {
-                                        println!("Changes saved successfully to: {}", file_path);
-                                        
-                                        // Show success dialog
-                                        show_modal(
-                                            "Save Successful",
-                                            &format!("Successfully saved {} changes to:\n{}", 
-                                                Nus3audioFileUtils::get_pending_changes_count(),
-                                                file_path),
-                                            false,
-                                        );
-                                        
-                                        // Update UI if needed
-                                        if let Some(app_mut) = app.as_mut() {
-                                            // Refresh the file view by reloading it
-                                            let main_area = app_mut.main_area_mut();
-                                            main_area.update_selected_file(Some(file_path.clone()));
-                                        }
-                                    },
-                                    Err(e) => {
-                                        println!("Failed to save changes: {}", e);
-                                        
-                                        // Show error dialog
-                                        show_modal(
-                                            "Save Failed",
-                                            &format!("Failed to save changes: {}", e),
-                                            true,
-                                        );
-                                    }
-                                }
+                            if let Some(app_mut) = app.as_mut() {
+                                TopPanel::save_changes(app_mut);
                             }
                         }
                         
@@ -236,14 +175,100 @@ impl TopPanel {
                                     // Get path as string
                                     let path_str = path.to_string_lossy().to_string();
 
-                                    // Execute save operation with selected file path
-                                    if let Some(original_path) = selected_file_path {
-                                        // Save using unified method (supports both file types)
-                                        TopPanel::save_nus3audio_file(&original_path, &path_str);
+                                    // Show the save summary; the save runs once confirmed
+                                    if let (Some(original_path), Some(app_mut)) = (selected_file_path, app.as_mut()) {
+                                        TopPanel::open_save_summary(
+                                            app_mut,
+                                            SaveTarget::SaveAs {
+                                                original: original_path,
+                                                destination: path_str,
+                                            },
+                                        );
                                     }
                                 }
                             }
                         }
+
+                        ui.separator();
+                        if ui
+                            .button("Open Project...")
+                            .on_hover_text("Open a bank together with the pending edits saved in a project file")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                TopPanel::open_project(app_mut);
+                            }
+                            ui.close();
+                        }
+                        if ui
+                            .button("Save Project...")
+                            .on_hover_text("Save the pending edits, with the paths of their source audio, to a project file")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                TopPanel::save_project(app_mut);
+                            }
+                            ui.close();
+                        }
+                        if ui
+                            .button("Export Source Manifest...")
+                            .on_hover_text("Write each pending edit's track, source file and loop/gain settings as JSON or CSV to share or rebuild later")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                TopPanel::export_source_manifest(app_mut);
+                            }
+                            ui.close();
+                        }
+                        if ui
+                            .button("Import Replacement Manifest...")
+                            .on_hover_text("Replace the tracks listed in a JSON or CSV file (track name or hex ID, WAV path, loop start/end, gain) in one go")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                TopPanel::import_replacement_manifest(app_mut);
+                            }
+                            ui.close();
+                        }
+                        if ui
+                            .button("Export Track List...")
+                            .on_hover_text("Write the tracks shown in the table, with their format, loop and pending edit, as JSON or CSV for documentation or diffing")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                TopPanel::export_track_list(app_mut);
+                            }
+                            ui.close();
+                        }
+
+                        ui.separator();
+                        let has_file = app.as_ref().is_some_and(|a| a.main_area().selected_file.is_some());
+                        if ui
+                            .add_enabled(has_file, egui::Button::new("Restore Backup..."))
+                            .on_hover_text("Put back one of the copies written before each in-place save")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                let main_area = app_mut.main_area_mut();
+                                if let Some(file) = main_area.selected_file.clone() {
+                                    let settings = main_area.backup_settings.clone();
+                                    main_area.backups_modal.open_for(&file, &settings);
+                                }
+                            }
+                            ui.close();
+                        }
+                        if ui
+                            .button("Edit Log...")
+                            .on_hover_text("Every replacement, addition, removal and save of this session, with times")
+                            .clicked()
+                        {
+                            if let Some(app_mut) = app.as_mut() {
+                                let main_area = app_mut.main_area_mut();
+                                let bank = main_area.selected_file.clone();
+                                main_area.audit_log_modal.open_for(bank.as_deref());
+                            }
+                            ui.close();
+                        }
                     });
                 }
 
@@ -257,6 +282,25 @@ impl TopPanel {
                         );
                         ui.close();
                     }
+
+                    if let Some(app_mut) = app.as_mut() {
+                        ui.separator();
+                        TopPanel::show_vgmstream_settings(ui, app_mut);
+                        TopPanel::show_post_save_hook_settings(ui, app_mut);
+                        TopPanel::show_decode_thread_settings(ui, app_mut);
+                        if ui.button("Keyboard Shortcuts...").clicked() {
+                            let main_area = app_mut.main_area_mut();
+                            main_area.shortcuts_modal.open_with(&main_area.shortcuts);
+                            ui.close();
+                        }
+                        if ui.button("Track Icons...").clicked() {
+                            let main_area = app_mut.main_area_mut();
+                            main_area.track_categories_modal.open_with(&main_area.track_categories);
+                            ui.close();
+                        }
+                        ui.checkbox(&mut app_mut.main_area_mut().auto_normalize_on_open, "Convert legacy WAV to PCM16 on open")
+                            .on_hover_text("When a .nus3bank is opened, queue each WAV track that is not plain PCM16, such as WAVEFORMATEXTENSIBLE ones, for conversion as pending replacements");
+                    }
                 });
 
                 ui.menu_button("Help", |ui| {
@@ -276,6 +320,90 @@ impl TopPanel {
         });
     }
 
+    /// vgmstream-cli location: the binary in use, and a way to pick or forget a custom one
+    fn show_vgmstream_settings(ui: &mut egui::Ui, app: &mut crate::TemplateApp) {
+        ui.menu_button("vgmstream-cli", |ui| {
+            match VgmstreamRunner::locate() {
+                Ok(runner) => ui.label(format!("Using: {}", runner.path().display())),
+                Err(e) => ui.colored_label(egui::Color32::from_rgb(255, 100, 100), e),
+            };
+            let configured = app.main_area().vgmstream_path.is_some();
+            ui.label(
+                egui::RichText::new(if configured { "Set manually" } else { "Found automatically" })
+                    .weak()
+                    .size(11.0),
+            );
+            ui.separator();
+
+            if ui.button("Choose Executable...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Select vgmstream-cli")
+                    .pick_file()
+                {
+                    app.main_area_mut()
+                        .set_vgmstream_path(Some(path.to_string_lossy().to_string()));
+                }
+                ui.close();
+            }
+            if ui
+                .add_enabled(configured, egui::Button::new("Find Automatically"))
+                .on_hover_text("Look in the tools folder, next to the editor and on PATH")
+                .clicked()
+            {
+                app.main_area_mut().set_vgmstream_path(None);
+                ui.close();
+            }
+        });
+    }
+
+    /// Command run after each successful save, with its placeholders explained
+    fn show_post_save_hook_settings(ui: &mut egui::Ui, app: &mut crate::TemplateApp) {
+        ui.menu_button("After Save", |ui| {
+            let hook = &mut app.main_area_mut().post_save_hook;
+            ui.checkbox(&mut hook.enabled, "Run a command after saving");
+            ui.add_enabled(
+                hook.enabled,
+                egui::TextEdit::singleline(&mut hook.command)
+                    .hint_text(r#"e.g. copy_mod.bat "{path}" "{bank}""#)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(360.0),
+            );
+            ui.label(egui::RichText::new("Placeholders:").weak().size(11.0));
+            for (placeholder, meaning) in PostSaveHook::PLACEHOLDERS {
+                ui.label(egui::RichText::new(format!("{}  {}", placeholder, meaning)).weak().size(11.0));
+            }
+            ui.label(
+                egui::RichText::new(
+                    "The command runs in the background; its result is shown as a notification. A bare program name is looked up in the tools folder and on PATH.",
+                )
+                .weak()
+                .size(11.0),
+            );
+        });
+    }
+
+    /// Size of the pool that decodes previews, waveforms and bank analyses
+    fn show_decode_thread_settings(ui: &mut egui::Ui, app: &mut crate::TemplateApp) {
+        ui.menu_button("Decode Threads", |ui| {
+            let mut threads = app.main_area().decode_threads;
+            let automatic = decode_pool::automatic_threads();
+            let response = ui.add(
+                egui::Slider::new(&mut threads, 0..=decode_pool::MAX_THREADS)
+                    .custom_formatter(|n, _| if n == 0.0 { format!("Auto ({})", automatic) } else { format!("{}", n) }),
+            );
+            if response.changed() {
+                app.main_area_mut().set_decode_threads(threads);
+            }
+            ui.label(
+                egui::RichText::new(
+                    "Previews go ahead of whole-bank analyses. Leave a core free to keep the interface smooth.",
+                )
+                .weak()
+                .size(11.0),
+            );
+        });
+    }
+
     /// Check for updates and show notification if a new version is available
     fn check_for_updates(_ctx: &Context) {
         // Only show update notice once per session
@@ -333,10 +461,332 @@ impl TopPanel {
         }
     }
     
-    /// Save current audio files to a new file (supports both NUS3AUDIO and NUS3BANK)
-    fn save_nus3audio_file(original_path: &str, save_path: &str) {
+    /// Save the pending changes into the open file, after the save summary is confirmed
+    pub fn save_changes(app: &mut crate::TemplateApp) {
+        let Some(file_path) = app.main_area().selected_file.clone() else {
+            println!("No file selected to save changes");
+            show_modal("Save Failed", "No file selected to save changes to", true);
+            return;
+        };
+
+        if !Nus3audioFileUtils::has_pending_changes() {
+            println!("No pending changes to save");
+            show_modal("No Changes", "There are no pending changes to save", false);
+            return;
+        }
+
+        // Show the save summary before writing anything
+        TopPanel::open_save_summary(app, SaveTarget::InPlace(file_path));
+    }
+
+    /// Collect pending changes and open the save summary dialog
+    fn open_save_summary(app: &mut crate::TemplateApp, target: SaveTarget) {
+        match SaveSummary::collect(target.original_path()) {
+            Ok(mut summary) => {
+                summary.check_payload_limit(app.main_area().memory_budget.payload_limit_for(target.original_path()));
+                app.main_area_mut().save_summary_modal.open(summary, target);
+            }
+            Err(e) => {
+                show_modal(
+                    "Save Failed",
+                    &format!("Failed to prepare save summary: {}", e),
+                    true,
+                );
+            }
+        }
+    }
+
+    /// Show the save summary dialog and execute the save when confirmed
+    fn show_save_summary(ctx: &Context, app: &mut crate::TemplateApp) {
+        let payload_limit = app
+            .main_area()
+            .save_summary_modal
+            .target
+            .as_ref()
+            .and_then(|t| app.main_area().memory_budget.payload_limit_for(t.original_path()));
+        let modal = &mut app.main_area_mut().save_summary_modal;
+        modal.show(ctx);
+
+        if let Some(entry) = modal
+            .audition_requested
+            .take()
+            .and_then(|index| modal.summary.entries.get(index).cloned())
+        {
+            let main_area = app.main_area_mut();
+            match main_area.audition_original(&entry.name, &entry.id) {
+                Ok(()) => {
+                    main_area.save_summary_modal.auditioning = true;
+                    main_area.add_toast(format!("Now playing: {} (original)", entry.name), Color32::GREEN);
+                }
+                Err(e) => main_area.add_toast(format!("Failed to play '{}': {}", entry.name, e), Color32::RED),
+            }
+        }
+        let modal = &mut app.main_area_mut().save_summary_modal;
+
+        if let Some((key, option)) = modal.downsample_requested.take() {
+            modal.downsample_result = Some(
+                ReplaceUtils::downsample_replacement(&key, option)
+                    .map(|_| format!("{} now {}", key.name(), option.label())),
+            );
+            // Sizes changed; collect them again
+            if let Some(target) = &modal.target {
+                if let Ok(mut summary) = SaveSummary::collect(target.original_path()) {
+                    summary.check_payload_limit(payload_limit);
+                    modal.summary = summary;
+                }
+            }
+        }
+
+        if modal.dry_run_requested {
+            modal.dry_run_requested = false;
+            if let Some(target) = &modal.target {
+                modal.dry_run_result = Some(
+                    ReplaceUtils::dry_run_save_unified(target.original_path(), target.destination_path())
+                        .map(|report| report.describe()),
+                );
+            }
+        }
+
+        if modal.choose_other_location_requested {
+            modal.choose_other_location_requested = false;
+            if let Some(original) = modal.target.as_ref().map(|t| t.original_path().to_string()) {
+                let extension = Path::new(&original)
+                    .extension()
+                    .map(|e| e.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "nus3audio".to_string());
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter(extension.to_uppercase(), &[extension.as_str()])
+                    .set_file_name(format!("output.{}", extension))
+                    .save_file()
+                {
+                    modal.target = Some(SaveTarget::SaveAs {
+                        original,
+                        destination: path.to_string_lossy().to_string(),
+                    });
+                    modal.access_issue = None;
+                    modal.dry_run_result = None;
+                }
+            }
+        }
+
+        if modal.confirmed {
+            // Detect read-only files and protected folders before running the pipeline
+            if let Some(target) = &modal.target {
+                let needs_backup = matches!(target, SaveTarget::InPlace(_));
+                if let Err(issue) = check_destination(target.destination_path(), needs_backup) {
+                    modal.report_access_issue(issue);
+                    return;
+                }
+            }
+
+            let target = modal.target.clone();
+            modal.reset_state();
+            match target {
+                Some(SaveTarget::InPlace(file_path)) => {
+                    TopPanel::save_changes_in_place(app, &file_path);
+                }
+                Some(SaveTarget::SaveAs { original, destination }) => {
+                    if TopPanel::save_nus3audio_file(&original, &destination) {
+                        app.main_area().post_save_hook.run(Path::new(&destination), Path::new(&original));
+                    }
+                }
+                None => {}
+            }
+        } else if modal.cancelled {
+            modal.reset_state();
+        }
+    }
+
+    /// Ask for a project file, open its bank and register its edits again
+    fn open_project(app: &mut crate::TemplateApp) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Editor Project", &[PROJECT_EXTENSION])
+            .set_title("Open Project")
+            .pick_file()
+        else {
+            return;
+        };
+        let project = match ProjectFile::load(&path) {
+            Ok(project) => project,
+            Err(e) => {
+                show_modal("Open Project Failed", &e, true);
+                return;
+            }
+        };
+        if !project.bank_path.is_file() {
+            show_modal(
+                "Open Project Failed",
+                &format!("The project's file was not found:\n{}", project.bank_path.display()),
+                true,
+            );
+            return;
+        }
+
+        let bank = project.bank_path.to_string_lossy().to_string();
+        app.file_list_mut().select_file(bank.clone());
+        let main_area = app.main_area_mut();
+        main_area.open_file(bank.clone());
+        // Start from the file on disk even when its tab had edits
+        main_area.update_selected_file(Some(bank));
+        let (applied, errors) = main_area.apply_project(&project);
+        main_area.add_toast(
+            format!("Restored {} of {} pending edits from the project", applied, project.change_count()),
+            if errors.is_empty() { egui::Color32::GREEN } else { egui::Color32::GOLD },
+        );
+        for error in errors {
+            main_area.add_toast(error, egui::Color32::RED);
+        }
+    }
+
+    /// Ask where to save the pending edits of the opened file as a project
+    fn save_project(app: &mut crate::TemplateApp) {
+        let main_area = app.main_area_mut();
+        let Some(bank) = main_area.selected_file.clone() else {
+            show_modal("Save Project Failed", "No file is open", true);
+            return;
+        };
+        let bank = Path::new(&bank);
+        let stem = bank.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter("Editor Project", &[PROJECT_EXTENSION])
+            .set_file_name(format!("{}.{}", stem, PROJECT_EXTENSION));
+        if let Some(dir) = bank.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        match main_area.save_project(&path) {
+            Ok((count, temporary)) => {
+                main_area.add_toast(
+                    format!("Saved {} pending edits to {}", count, path.display()),
+                    egui::Color32::GREEN,
+                );
+                if temporary > 0 {
+                    main_area.add_toast(
+                        format!("{} edits use temporary files as their source and may not reopen after a restart", temporary),
+                        egui::Color32::GOLD,
+                    );
+                }
+            }
+            Err(e) => show_modal("Save Project Failed", &e, true),
+        }
+    }
+
+    /// Ask where to write the manifest of pending sources
+    fn export_source_manifest(app: &mut crate::TemplateApp) {
+        let main_area = app.main_area_mut();
+        let Some(bank) = main_area.selected_file.clone() else {
+            show_modal("Export Manifest Failed", "No file is open", true);
+            return;
+        };
+        let bank = Path::new(&bank);
+        let stem = bank.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .add_filter("CSV", &["csv"])
+            .set_file_name(format!("{}.sources.json", stem));
+        if let Some(dir) = bank.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        match main_area.export_source_manifest(&path) {
+            Ok(count) => main_area.add_toast(
+                format!("Wrote {} pending source(s) to {}", count, path.display()),
+                egui::Color32::GREEN,
+            ),
+            Err(e) => show_modal("Export Manifest Failed", &e, true),
+        }
+    }
+
+    /// Ask for a replacement manifest and queue its replacements in the opened file in the
+    /// background
+    fn import_replacement_manifest(app: &mut crate::TemplateApp) {
+        let main_area = app.main_area_mut();
+        let Some(bank) = main_area.selected_file.clone() else {
+            show_modal("Import Manifest Failed", "No file is open", true);
+            return;
+        };
+        let mut dialog = rfd::FileDialog::new().add_filter("Manifest", &["json", "csv"]);
+        if let Some(dir) = Path::new(&bank).parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.pick_file() else {
+            return;
+        };
+        if let Err(e) = main_area.import_replacement_manifest(&path) {
+            show_modal("Import Manifest Failed", &e, true);
+        }
+    }
+
+    /// Ask where to write the list of tracks shown in the table
+    fn export_track_list(app: &mut crate::TemplateApp) {
+        let main_area = app.main_area_mut();
+        let Some(file) = main_area.selected_file.clone() else {
+            show_modal("Export Track List Failed", "No file is open", true);
+            return;
+        };
+        let file = Path::new(&file);
+        let stem = file.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        let mut dialog = rfd::FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .add_filter("JSON", &["json"])
+            .set_file_name(format!("{}.tracks.csv", stem));
+        if let Some(dir) = file.parent() {
+            dialog = dialog.set_directory(dir);
+        }
+        let Some(path) = dialog.save_file() else {
+            return;
+        };
+        match main_area.export_track_list(&path) {
+            Ok(count) => main_area.add_toast(
+                format!("Wrote {} track(s) to {}", count, path.display()),
+                egui::Color32::GREEN,
+            ),
+            Err(e) => show_modal("Export Track List Failed", &e, true),
+        }
+    }
+
+    /// Save pending changes back into the opened file
+    fn save_changes_in_place(app: &mut crate::TemplateApp, file_path: &str) {
+        let retention = app.main_area().backup_settings.retention;
+        match Nus3audioFileUtils::save_changes_to_file(file_path, retention) {
+            Ok(_) => {
+                println!("Changes saved successfully to: {}", file_path);
+                
+                // Show success dialog
+                show_modal(
+                    "Save Successful",
+                    &format!("Successfully saved {} changes to:\n{}", 
+                        Nus3audioFileUtils::get_pending_changes_count(),
+                        file_path),
+                    false,
+                );
+                
+                // Refresh the file view by reloading it
+                app.main_area_mut().update_selected_file(Some(file_path.to_string()));
+                app.main_area().post_save_hook.run(Path::new(file_path), Path::new(file_path));
+            },
+            Err(e) => {
+                println!("Failed to save changes: {}", e);
+                
+                // Show error dialog
+                show_modal(
+                    "Save Failed",
+                    &format!("Failed to save changes: {}", e),
+                    true,
+                );
+            }
+        }
+    }
+
+    /// Save current audio files to a new file (supports both NUS3AUDIO and NUS3BANK).
+    /// Returns whether the save succeeded.
+    fn save_nus3audio_file(original_path: &str, save_path: &str) -> bool {
         // Use unified method to support both NUS3AUDIO and NUS3BANK files
-        match crate::ui::main_area::ReplaceUtils::apply_replacements_and_save_unified(original_path, save_path) {
+        match ReplaceUtils::apply_replacements_and_save_unified(original_path, save_path) {
             Ok(_) => {
                 println!("File save success: {}", save_path);
                 
@@ -346,6 +796,7 @@ impl TopPanel {
                     &format!("Audio file has been successfully saved to:\n{}", save_path),
                     false
                 );
+                true
             }
             Err(e) => {
                 eprintln!("File save fail: {}", e);
@@ -356,6 +807,7 @@ impl TopPanel {
                     &format!("Failed to save file: {}", e),
                     true
                 );
+                false
             }
         }
     }
@@ -0,0 +1,2 @@
+//! WAV container handling shared by the import, export and loop code
+pub mod riff;
@@ -0,0 +1,402 @@
+//! RIFF chunk walker and editor for WAV files.
+//!
+//! A file is read into its top-level chunks in file order, edited, and written back with
+//! the RIFF size and pad bytes recomputed. Chunks that are not touched are written back
+//! unchanged, wherever they were, so `smpl`, `cue ` or `LIST` chunks can be found and
+//! rewritten no matter what a tool put before them.
+//!
+//! Parsing borrows the chunk bodies from the file, so reading a format or a loop does not
+//! copy the audio; a chunk is copied when it is edited.
+
+use std::borrow::Cow;
+
+/// Bytes of the `smpl` header before the loop records
+const SMPL_HEADER_LEN: usize = 36;
+
+/// Bytes of one `smpl` loop record
+const SMPL_LOOP_LEN: usize = 24;
+
+/// One forward loop, in sample frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmplLoop {
+    pub start: u32,
+    /// Last sample of the loop (inclusive)
+    pub end: u32,
+}
+
+/// Build a `smpl` chunk body with one forward loop
+pub fn build_smpl_chunk(sample_rate: u32, lp: SmplLoop) -> Vec<u8> {
+    let mut body = Vec::with_capacity(SMPL_HEADER_LEN + SMPL_LOOP_LEN);
+    body.extend_from_slice(&0u32.to_le_bytes()); // manufacturer
+    body.extend_from_slice(&0u32.to_le_bytes()); // product
+    body.extend_from_slice(&(1_000_000_000 / sample_rate.max(1)).to_le_bytes()); // sample period (ns)
+    body.extend_from_slice(&60u32.to_le_bytes()); // MIDI unity note (middle C)
+    body.extend_from_slice(&0u32.to_le_bytes()); // MIDI pitch fraction
+    body.extend_from_slice(&0u32.to_le_bytes()); // SMPTE format
+    body.extend_from_slice(&0u32.to_le_bytes()); // SMPTE offset
+    body.extend_from_slice(&1u32.to_le_bytes()); // number of loops
+    body.extend_from_slice(&0u32.to_le_bytes()); // sampler data size
+    body.extend_from_slice(&0u32.to_le_bytes()); // cue point id
+    body.extend_from_slice(&0u32.to_le_bytes()); // loop type: forward
+    body.extend_from_slice(&lp.start.to_le_bytes());
+    body.extend_from_slice(&lp.end.to_le_bytes());
+    body.extend_from_slice(&0u32.to_le_bytes()); // fraction
+    body.extend_from_slice(&0u32.to_le_bytes()); // play count: infinite
+    body
+}
+
+/// The fields every `fmt ` chunk starts with, whatever the codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavFormat {
+    /// 1 for PCM, 3 for IEEE float, 0xFFFE for the extensible header
+    pub tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub byte_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+}
+
+/// One top-level chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk<'a> {
+    pub id: [u8; 4],
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> Chunk<'a> {
+    /// The body as a slice of the parsed file; `None` once the chunk was edited
+    pub fn borrowed(&self) -> Option<&'a [u8]> {
+        match self.data {
+            Cow::Borrowed(data) => Some(data),
+            Cow::Owned(_) => None,
+        }
+    }
+
+    /// List type of a `LIST` chunk, e.g. `INFO` or `adtl`
+    pub fn list_type(&self) -> Option<&[u8]> {
+        if &self.id == b"LIST" {
+            self.data.get(..4)
+        } else {
+            None
+        }
+    }
+
+    /// Sub-chunks of a `LIST` chunk, e.g. the `INAM` and `ISFT` entries of `INFO`
+    pub fn sub_chunks(&self) -> Vec<Chunk<'_>> {
+        self.list_type().map_or_else(Vec::new, |_| split_chunks(&self.data[4..]).0)
+    }
+}
+
+/// Chunks laid out one after another from the start of `data`, each padded to an even size,
+/// and the ID of a last chunk that claims more bytes than there are
+fn split_chunks(data: &[u8]) -> (Vec<Chunk<'_>>, Option<[u8; 4]>) {
+    let mut chunks = Vec::new();
+    let mut cut = None;
+    let mut pos = 0;
+    while pos + 8 <= data.len() {
+        let id: [u8; 4] = [data[pos], data[pos + 1], data[pos + 2], data[pos + 3]];
+        let len = read_u32(data, pos + 4).unwrap_or(0) as usize;
+        let start = pos + 8;
+        let end = start.saturating_add(len);
+        if end > data.len() {
+            cut = Some(id);
+        }
+        chunks.push(Chunk { id, data: Cow::Borrowed(&data[start..end.min(data.len())]) });
+        pos = end.saturating_add(len & 1);
+    }
+    (chunks, cut)
+}
+
+/// A RIFF file as its form type and chunks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Riff<'a> {
+    pub form: [u8; 4],
+    pub chunks: Vec<Chunk<'a>>,
+}
+
+fn read_u16(data: &[u8], at: usize) -> Option<u16> {
+    data.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], at: usize) -> Option<u32> {
+    data.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+impl<'a> Riff<'a> {
+    /// Split a RIFF file into chunks. A last chunk that claims more bytes than the file has,
+    /// as streamed WAVs often do, keeps what is there.
+    pub fn parse(data: &'a [u8]) -> Result<Self, String> {
+        Self::parse_checked(data).map(|(riff, _)| riff)
+    }
+
+    /// The file and the ID of a last chunk that was cut short
+    fn parse_checked(data: &'a [u8]) -> Result<(Self, Option<[u8; 4]>), String> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" {
+            return Err("Not a RIFF file".to_string());
+        }
+        let form: [u8; 4] = data[8..12].try_into().map_err(|_| "Invalid RIFF header")?;
+        let (chunks, cut) = split_chunks(&data[12..]);
+        Ok((Self { form, chunks }, cut))
+    }
+
+    /// Read and split a WAV file
+    pub fn parse_wav(data: &'a [u8]) -> Result<Self, String> {
+        let riff = Self::parse(data)?;
+        if &riff.form != b"WAVE" {
+            return Err("Not a WAVE file".to_string());
+        }
+        Ok(riff)
+    }
+
+    /// Read a WAV file whose chunks all fit in it, for checks that must not accept a cut-off
+    /// `data` chunk
+    pub fn parse_wav_exact(data: &'a [u8]) -> Result<Self, String> {
+        let (riff, cut) = Self::parse_checked(data)?;
+        if &riff.form != b"WAVE" {
+            return Err("Not a WAVE file".to_string());
+        }
+        if let Some(id) = cut {
+            return Err(format!("{} chunk runs past the end of the file", String::from_utf8_lossy(&id).trim_end()));
+        }
+        Ok(riff)
+    }
+
+    /// Copy the chunks so the file they were read from can be dropped
+    pub fn into_owned(self) -> Riff<'static> {
+        Riff {
+            form: self.form,
+            chunks: self
+                .chunks
+                .into_iter()
+                .map(|c| Chunk { id: c.id, data: Cow::Owned(c.data.into_owned()) })
+                .collect(),
+        }
+    }
+
+    /// The file with every chunk in order, odd sizes padded and the RIFF size updated
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let body_len: usize = 4 + self.chunks.iter().map(|c| 8 + c.data.len() + (c.data.len() & 1)).sum::<usize>();
+        let mut out = Vec::with_capacity(body_len + 8);
+        out.extend_from_slice(b"RIFF");
+        out.extend_from_slice(&(body_len as u32).to_le_bytes());
+        out.extend_from_slice(&self.form);
+        for chunk in &self.chunks {
+            out.extend_from_slice(&chunk.id);
+            out.extend_from_slice(&(chunk.data.len() as u32).to_le_bytes());
+            out.extend_from_slice(&chunk.data);
+            if chunk.data.len() % 2 == 1 {
+                out.push(0);
+            }
+        }
+        out
+    }
+
+    pub fn position(&self, id: &[u8; 4]) -> Option<usize> {
+        self.chunks.iter().position(|c| &c.id == id)
+    }
+
+    /// First chunk with `id`
+    pub fn get(&self, id: &[u8; 4]) -> Option<&Chunk<'a>> {
+        self.chunks.iter().find(|c| &c.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: &[u8; 4]) -> Option<&mut Chunk<'a>> {
+        self.chunks.iter_mut().find(|c| &c.id == id)
+    }
+
+    /// The `LIST` chunk of a list type; a file can have several `LIST` chunks
+    pub fn get_list(&self, list_type: &[u8; 4]) -> Option<&Chunk<'a>> {
+        self.chunks.iter().find(|c| c.list_type() == Some(list_type.as_slice()))
+    }
+
+    /// Replace the body of the first chunk with `id`, or add the chunk. New chunks go before
+    /// `data`, where players and the game look for metadata, or at the end without one.
+    pub fn set(&mut self, id: &[u8; 4], data: Vec<u8>) {
+        if let Some(chunk) = self.get_mut(id) {
+            chunk.data = Cow::Owned(data);
+            return;
+        }
+        let at = self.position(b"data").unwrap_or(self.chunks.len());
+        self.chunks.insert(at, Chunk { id: *id, data: Cow::Owned(data) });
+    }
+
+    /// Replace or append the `LIST` chunk of a list type; `body` excludes the list type
+    pub fn set_list(&mut self, list_type: &[u8; 4], body: &[u8]) {
+        let mut data = list_type.to_vec();
+        data.extend_from_slice(body);
+        match self.chunks.iter_mut().find(|c| c.list_type() == Some(list_type.as_slice())) {
+            Some(chunk) => chunk.data = Cow::Owned(data),
+            None => self.chunks.push(Chunk { id: *b"LIST", data: Cow::Owned(data) }),
+        }
+    }
+
+    /// Drop every chunk with `id`, returning how many there were
+    pub fn remove(&mut self, id: &[u8; 4]) -> usize {
+        let before = self.chunks.len();
+        self.chunks.retain(|c| &c.id != id);
+        before - self.chunks.len()
+    }
+
+    /// Format from the `fmt ` chunk, if it has the 16 bytes common to every codec
+    pub fn fmt(&self) -> Option<WavFormat> {
+        let data = &self.get(b"fmt ")?.data;
+        if data.len() < 16 {
+            return None;
+        }
+        Some(WavFormat {
+            tag: read_u16(data, 0)?,
+            channels: read_u16(data, 2)?,
+            sample_rate: read_u32(data, 4)?,
+            byte_rate: read_u32(data, 8)?,
+            block_align: read_u16(data, 12)?,
+            bits_per_sample: read_u16(data, 14)?,
+        })
+    }
+
+    /// Sample rate from the `fmt ` chunk
+    pub fn sample_rate(&self) -> Option<u32> {
+        read_u32(&self.get(b"fmt ")?.data, 4)
+    }
+
+    /// Number of sample frames in the `data` chunk, from the block align in `fmt `
+    pub fn frames(&self) -> Option<u32> {
+        let block_align = read_u16(&self.get(b"fmt ")?.data, 12)?;
+        if block_align == 0 {
+            return None;
+        }
+        Some((self.get(b"data")?.data.len() / block_align as usize) as u32)
+    }
+
+    /// First loop of the `smpl` chunk
+    pub fn smpl_loop(&self) -> Option<SmplLoop> {
+        let data = &self.get(b"smpl")?.data;
+        if read_u32(data, 28)? == 0 {
+            return None;
+        }
+        Some(SmplLoop {
+            start: read_u32(data, SMPL_HEADER_LEN + 8)?,
+            end: read_u32(data, SMPL_HEADER_LEN + 12)?,
+        })
+    }
+
+    /// Make `lp` the first loop of the `smpl` chunk. An existing loop is rewritten in place,
+    /// keeping the rest of the chunk; a chunk without loops grows one; a file without `smpl`
+    /// gets a new chunk.
+    pub fn set_smpl_loop(&mut self, lp: SmplLoop) -> Result<(), String> {
+        if lp.start > lp.end {
+            return Err(format!("Loop start {} is after loop end {}", lp.start, lp.end));
+        }
+        let sample_rate = self.sample_rate().ok_or("WAV has no fmt chunk")?;
+        let Some(chunk) = self.get_mut(b"smpl") else {
+            self.set(b"smpl", build_smpl_chunk(sample_rate, lp));
+            return Ok(());
+        };
+
+        let data = chunk.data.to_mut();
+        let loops = read_u32(data, 28).unwrap_or(0) as usize;
+        if loops == 0 || data.len() < SMPL_HEADER_LEN + SMPL_LOOP_LEN {
+            // Keep what header there is and any sampler data, with one loop record in front
+            let mut header = data.get(..SMPL_HEADER_LEN).unwrap_or(data.as_slice()).to_vec();
+            header.resize(SMPL_HEADER_LEN, 0);
+            let rest = data.get(SMPL_HEADER_LEN + loops.min(1) * SMPL_LOOP_LEN..).unwrap_or_default().to_vec();
+            header[28..32].copy_from_slice(&1u32.to_le_bytes());
+            let mut record = [0u8; SMPL_LOOP_LEN];
+            record[8..12].copy_from_slice(&lp.start.to_le_bytes());
+            record[12..16].copy_from_slice(&lp.end.to_le_bytes());
+            header.extend_from_slice(&record);
+            header.extend_from_slice(&rest);
+            *data = header;
+        } else {
+            data[SMPL_HEADER_LEN + 8..SMPL_HEADER_LEN + 12].copy_from_slice(&lp.start.to_le_bytes());
+            data[SMPL_HEADER_LEN + 12..SMPL_HEADER_LEN + 16].copy_from_slice(&lp.end.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// Replace the `cue ` chunk with one cue point per frame position, numbered from 1
+    pub fn set_cue_points(&mut self, positions: &[u32]) {
+        let mut data = Vec::with_capacity(4 + positions.len() * 24);
+        data.extend_from_slice(&(positions.len() as u32).to_le_bytes());
+        for (i, &position) in positions.iter().enumerate() {
+            data.extend_from_slice(&(i as u32 + 1).to_le_bytes()); // cue point id
+            data.extend_from_slice(&position.to_le_bytes()); // play order position
+            data.extend_from_slice(b"data");
+            data.extend_from_slice(&0u32.to_le_bytes()); // chunk start
+            data.extend_from_slice(&0u32.to_le_bytes()); // block start
+            data.extend_from_slice(&position.to_le_bytes()); // sample offset
+        }
+        self.set(b"cue ", data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wav_with(chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let riff = Riff {
+            form: *b"WAVE",
+            chunks: chunks.iter().map(|(id, data)| Chunk { id: **id, data: Cow::Borrowed(*data) }).collect(),
+        };
+        riff.to_bytes()
+    }
+
+    fn fmt() -> Vec<u8> {
+        let mut fmt = vec![1, 0, 2, 0];
+        fmt.extend_from_slice(&48000u32.to_le_bytes());
+        fmt.extend_from_slice(&(48000u32 * 4).to_le_bytes());
+        fmt.extend_from_slice(&[4, 0, 16, 0]);
+        fmt
+    }
+
+    #[test]
+    fn rewrites_smpl_wherever_it_is() {
+        // LIST and an odd-sized chunk before smpl move it away from 0x24
+        let mut smpl = build_smpl_chunk(48000, SmplLoop { start: 1, end: 2 });
+        smpl.extend_from_slice(&[9, 9, 9, 9]); // sampler data after the loop record
+        let wav = wav_with(&[
+            (b"fmt ", &fmt()),
+            (b"LIST", b"INFOISFT\x03\x00\x00\x00ab\x00"),
+            (b"junk", &[1, 2, 3]),
+            (b"smpl", &smpl),
+            (b"data", &[0; 40]),
+        ]);
+        let mut riff = Riff::parse_wav(&wav).unwrap();
+        assert_eq!(riff.to_bytes(), wav);
+        assert_eq!(riff.frames(), Some(10));
+        let format = riff.fmt().unwrap();
+        assert_eq!((format.tag, format.channels, format.sample_rate), (1, 2, 48000));
+        assert_eq!((format.byte_rate, format.block_align, format.bits_per_sample), (192000, 4, 16));
+        assert_eq!(riff.get_list(b"INFO").map(|c| c.data.len()), Some(15));
+        let info = riff.get_list(b"INFO").unwrap().sub_chunks();
+        assert_eq!((info[0].id, &*info[0].data), (*b"ISFT", b"ab\x00".as_slice()));
+
+        riff.set_smpl_loop(SmplLoop { start: 3, end: 9 }).unwrap();
+        let bytes = riff.to_bytes();
+        let rewritten = Riff::parse_wav(&bytes).unwrap();
+        assert_eq!(rewritten.smpl_loop(), Some(SmplLoop { start: 3, end: 9 }));
+        assert!(rewritten.get(b"smpl").unwrap().data.ends_with(&[9, 9, 9, 9]));
+        assert_eq!(rewritten.chunks.iter().map(|c| c.id).collect::<Vec<_>>(), [*b"fmt ", *b"LIST", *b"junk", *b"smpl", *b"data"]);
+        assert!(riff.set_smpl_loop(SmplLoop { start: 5, end: 4 }).is_err());
+    }
+
+    #[test]
+    fn creates_and_grows_loop_chunks() {
+        let wav = wav_with(&[(b"fmt ", &fmt()), (b"data", &[0; 40])]);
+        let mut riff = Riff::parse_wav(&wav).unwrap();
+        riff.set_smpl_loop(SmplLoop { start: 0, end: 9 }).unwrap();
+        riff.set_cue_points(&[0, 5]);
+        let ids: Vec<_> = riff.chunks.iter().map(|c| c.id).collect();
+        assert_eq!(ids, [*b"fmt ", *b"smpl", *b"cue ", *b"data"]);
+        let bytes = riff.to_bytes();
+        assert_eq!(Riff::parse_wav(&bytes).unwrap().smpl_loop(), Some(SmplLoop { start: 0, end: 9 }));
+
+        // A smpl chunk without loops grows a loop record
+        riff.get_mut(b"smpl").unwrap().data = Cow::Owned(vec![0; SMPL_HEADER_LEN]);
+        riff.set_smpl_loop(SmplLoop { start: 2, end: 7 }).unwrap();
+        assert_eq!(riff.get(b"smpl").unwrap().data.len(), SMPL_HEADER_LEN + SMPL_LOOP_LEN);
+        assert_eq!(riff.smpl_loop(), Some(SmplLoop { start: 2, end: 7 }));
+        assert_eq!(riff.remove(b"cue "), 1);
+    }
+}